@@ -0,0 +1,159 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Drawing without a graphics device
+
+use std::any::Any;
+use std::ops::Range;
+
+use kas::draw::{
+    ClipRegion, Colour, Draw, DrawHandle, FillRule, HighlightShape, Icon, ImageId, InputState,
+    Layer, MockSizeHandle, Pass, Path, SizeHandle, StrokeStyle, TextClass, TextHighlight,
+};
+use kas::geom::{Coord, Quad, Rect};
+use kas::text::PreparedText;
+use kas::Direction;
+
+/// A [`Draw`] implementation which draws nothing
+///
+/// Clip regions are counted, such that each is assigned a distinct [`Pass`].
+#[derive(Clone, Debug, Default)]
+pub struct NullDraw {
+    passes: u32,
+}
+
+impl Draw for NullDraw {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn add_clip_region(&mut self, _: Rect, depth: f32) -> Pass {
+        self.passes += 1;
+        Pass::new_pass_with_depth(self.passes, depth)
+    }
+
+    fn rect(&mut self, _: Pass, _: Quad, _: Colour) {}
+
+    fn frame(&mut self, _: Pass, _: Quad, _: Quad, _: Colour) {}
+}
+
+/// A [`DrawHandle`] which draws nothing
+///
+/// Sizes are provided by a [`MockSizeHandle`]. Wrap with a
+/// [`kas::draw::DrawRecorder`] to inspect draw commands.
+pub struct NullDrawHandle<'a> {
+    draw: &'a mut NullDraw,
+    size_handle: &'a mut MockSizeHandle,
+    pass: Pass,
+    offset: Coord,
+    rect: Rect,
+}
+
+impl<'a> NullDrawHandle<'a> {
+    /// Construct, targeting the window area `rect`
+    pub fn new(draw: &'a mut NullDraw, size_handle: &'a mut MockSizeHandle, rect: Rect) -> Self {
+        let pass = draw.add_clip_region(rect, 0.0);
+        NullDrawHandle {
+            draw,
+            size_handle,
+            pass,
+            offset: Coord::ZERO,
+            rect,
+        }
+    }
+
+    fn sub_handle(&mut self, rect: Rect, offset: Coord, f: &mut dyn FnMut(&mut dyn DrawHandle)) {
+        let rect = rect + self.offset;
+        let pass = self.draw.add_clip_region(rect, self.pass.depth());
+        let mut handle = NullDrawHandle {
+            draw: &mut *self.draw,
+            size_handle: &mut *self.size_handle,
+            pass,
+            offset: self.offset - offset,
+            rect,
+        };
+        f(&mut handle);
+    }
+}
+
+impl<'a> DrawHandle for NullDrawHandle<'a> {
+    fn size_handle_dyn(&mut self, f: &mut dyn FnMut(&mut dyn SizeHandle)) {
+        f(&mut *self.size_handle);
+    }
+
+    fn draw_device(&mut self) -> (Pass, Coord, &mut dyn Draw) {
+        (self.pass, self.offset, &mut *self.draw)
+    }
+
+    fn clip_region(
+        &mut self,
+        rect: Rect,
+        offset: Coord,
+        _: ClipRegion,
+        f: &mut dyn FnMut(&mut dyn DrawHandle),
+    ) {
+        self.sub_handle(rect, offset, f);
+    }
+
+    fn layer(&mut self, rect: Rect, _: Layer, f: &mut dyn FnMut(&mut dyn DrawHandle)) {
+        self.sub_handle(rect, Coord::ZERO, f);
+    }
+
+    fn target_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn outer_frame(&mut self, _: Rect) {}
+    fn menu_frame(&mut self, _: Rect) {}
+    fn separator(&mut self, _: Rect) {}
+    fn text_offset(&mut self, _: Coord, _: Coord, _: &PreparedText, _: TextClass, _: InputState) {}
+    fn text_with_underline(
+        &mut self,
+        _: Coord,
+        _: Coord,
+        _: &PreparedText,
+        _: TextClass,
+        _: InputState,
+        _: usize,
+    ) {
+    }
+    fn text_selected_range(
+        &mut self,
+        _: Coord,
+        _: Coord,
+        _: &PreparedText,
+        _: Range<usize>,
+        _: TextClass,
+        _: InputState,
+    ) {
+    }
+    fn text_highlighted(
+        &mut self,
+        _: Coord,
+        _: Coord,
+        _: &PreparedText,
+        _: TextClass,
+        _: InputState,
+        _: &[TextHighlight],
+    ) {
+    }
+    fn edit_marker(&mut self, _: Coord, _: Coord, _: &PreparedText, _: TextClass, _: usize) {}
+    fn menu_entry(&mut self, _: Rect, _: InputState) {}
+    fn button(&mut self, _: Rect, _: InputState) {}
+    fn edit_box(&mut self, _: Rect, _: InputState) {}
+    fn checkbox(&mut self, _: Rect, _: bool, _: InputState) {}
+    fn radiobox(&mut self, _: Rect, _: bool, _: InputState) {}
+    fn scrollbar(&mut self, _: Rect, _: Rect, _: Direction, _: InputState) {}
+    fn slider(&mut self, _: Rect, _: Rect, _: Direction, _: InputState) {}
+    fn icon(&mut self, _: Rect, _: &Icon, _: InputState) {}
+    fn image(&mut self, _: Rect, _: ImageId) {}
+    fn fill_path(&mut self, _: &Path, _: FillRule, _: Colour) {}
+    fn stroke_path(&mut self, _: &Path, _: &StrokeStyle, _: Colour) {}
+    fn selection(&mut self, _: Rect, _: HighlightShape, _: InputState) {}
+    fn focus_ring(&mut self, _: Rect, _: HighlightShape, _: InputState) {}
+    fn shadow(&mut self, _: Rect) {}
+    fn well(&mut self, _: Rect, _: InputState) {}
+    fn badge(&mut self, _: Rect, _: &PreparedText, _: InputState) {}
+}
@@ -0,0 +1,36 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Headless toolkit for KAS
+//!
+//! This crate hosts KAS windows without a window system or graphics device,
+//! for automated testing of widgets. Input is simulated and widget state and
+//! layout may be inspected directly:
+//! ```
+//! use kas::class::HasBool;
+//! use kas::event::VoidMsg;
+//! use kas::widget::{CheckBoxBare, Window};
+//! use kas::WidgetChildren;
+//!
+//! let mut window = kas_headless::Window::new(Window::new("Test", CheckBoxBare::new()));
+//! let id = window.widget().get(0).unwrap().id();
+//! let checked = |w: &kas_headless::Window| {
+//!     w.find_as::<CheckBoxBare<VoidMsg>>(id).unwrap().get_bool()
+//! };
+//! assert!(!checked(&window));
+//!
+//! window.send_click(id).unwrap();
+//! assert!(checked(&window));
+//! ```
+//!
+//! Sizes are provided by [`kas::draw::MockSizeHandle`] and drawing (see
+//! [`Window::draw`]) only records draw commands.
+
+pub mod draw;
+mod window;
+
+pub use window::Window;
+
+pub use kas;
@@ -0,0 +1,663 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Headless window
+
+use log::debug;
+use std::any::Any;
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+use kas::draw::{DrawRecord, DrawRecorder, MockSizeHandle, SizeHandle};
+use kas::event::{
+    CursorIcon, DragData, ManagerState, ModifiersState, MouseButton, UpdateHandle, VirtualKeyCode,
+};
+use kas::geom::{Coord, Rect, Size};
+use kas::layout::SolveCache;
+use kas::{Capture, Instant, ThemeAction, ThemeApi, TkAction, WidgetConfig, WidgetId, WindowId};
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceId, ElementState, KeyboardInput, WindowEvent};
+
+use crate::draw::{NullDraw, NullDrawHandle};
+
+/// A window without a window system
+///
+/// This hosts a [`kas::Window`] for testing: input is simulated via methods
+/// such as [`Window::send_click`] and [`Window::type_text`], after which
+/// widget state and layout may be inspected via [`Window::find_as`] and
+/// [`Window::rect`].
+///
+/// Sizes are determined by [`MockSizeHandle`], thus layout is deterministic
+/// and does not require fonts. Pending actions are handled after each input,
+/// as by a real toolkit. Time only passes (for the purposes of widget timers)
+/// when advanced by [`Window::advance_time`]. The clipboard is simulated.
+/// Theme adjustments are unsupported.
+pub struct Window {
+    widget: Box<dyn kas::Window>,
+    mgr: ManagerState,
+    solve_cache: SolveCache,
+    size_handle: MockSizeHandle,
+    state: State,
+    size: Size,
+    scale_factor: f64,
+    modifiers: ModifiersState,
+}
+
+impl Window {
+    /// Construct
+    ///
+    /// The window is sized to the widget's ideal size.
+    pub fn new<W: kas::Window + 'static>(widget: W) -> Self {
+        Self::new_boxed(Box::new(widget))
+    }
+
+    /// Construct from a boxed widget
+    pub fn new_boxed(mut widget: Box<dyn kas::Window>) -> Self {
+        let mut size_handle = MockSizeHandle::default();
+        let solve_cache = SolveCache::find_constraints(widget.as_widget_mut(), &mut size_handle);
+        let size = solve_cache.ideal(true);
+
+        let mut state = State::new();
+        let mut mgr = ManagerState::new(1.0);
+        let mut tkw = TkHeadless::new(&mut size_handle, &mut state);
+        mgr.configure(&mut tkw, &mut *widget);
+
+        let mut window = Window {
+            widget,
+            mgr,
+            solve_cache,
+            size_handle,
+            state,
+            size,
+            scale_factor: 1.0,
+            modifiers: ModifiersState::empty(),
+        };
+        window.apply_size();
+        window.update();
+        window
+    }
+
+    /// Access the window's widget
+    pub fn widget(&self) -> &dyn kas::Window {
+        &*self.widget
+    }
+
+    /// Access the event manager state
+    ///
+    /// This allows querying input state, for example
+    /// [`ManagerState::char_focus`].
+    pub fn mgr(&self) -> &ManagerState {
+        &self.mgr
+    }
+
+    /// Get the window size
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Resize the window
+    pub fn resize(&mut self, size: Size) {
+        if size != self.size {
+            self.size = size;
+            self.apply_size();
+            self.update();
+        }
+    }
+
+    /// Set the scale factor (DPI factor)
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+        self.scale_resize();
+        self.update();
+    }
+
+    /// Find a widget by identifier
+    pub fn find(&self, id: WidgetId) -> Option<&dyn WidgetConfig> {
+        self.widget.find(id)
+    }
+
+    /// Find a widget by identifier and downcast to type `T`
+    ///
+    /// Returns `None` if no widget with this `id` exists or it is not of
+    /// type `T`.
+    pub fn find_as<T: Any>(&self, id: WidgetId) -> Option<&T> {
+        self.widget.find(id).and_then(|w| w.as_any().downcast_ref())
+    }
+
+    /// Find a widget registered under `name`
+    ///
+    /// See [`kas::event::Manager::register_name`].
+    pub fn find_named(&self, name: &str) -> Option<WidgetId> {
+        self.mgr.find_named(name)
+    }
+
+    /// Find the widget at `coord`, if any
+    pub fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        self.widget.find_id(coord)
+    }
+
+    /// Get the layout rect of a widget
+    pub fn rect(&self, id: WidgetId) -> Option<Rect> {
+        self.widget.find(id).map(|w| w.rect())
+    }
+
+    /// Send a winit event
+    ///
+    /// Resize and scale-factor events are ignored; use [`Window::resize`] and
+    /// [`Window::set_scale_factor`] instead.
+    pub fn handle_event(&mut self, event: WindowEvent) -> TkAction {
+        match event {
+            WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => (),
+            event => {
+                let mut tkw = TkHeadless::new(&mut self.size_handle, &mut self.state);
+                let widget = &mut *self.widget;
+                self.mgr.with(&mut tkw, |mgr| {
+                    mgr.handle_winit(widget, event);
+                });
+            }
+        }
+        self.update()
+    }
+
+    /// Move the mouse cursor to `coord`
+    pub fn move_cursor(&mut self, coord: Coord) -> TkAction {
+        #[allow(deprecated)]
+        let event = WindowEvent::CursorMoved {
+            device_id: device_id(),
+            position: PhysicalPosition::new(coord.0 as f64, coord.1 as f64),
+            modifiers: self.modifiers,
+        };
+        self.handle_event(event)
+    }
+
+    /// Press or release a mouse button at the current cursor position
+    pub fn mouse_input(&mut self, button: MouseButton, pressed: bool) -> TkAction {
+        let state = match pressed {
+            true => ElementState::Pressed,
+            false => ElementState::Released,
+        };
+        #[allow(deprecated)]
+        let event = WindowEvent::MouseInput {
+            device_id: device_id(),
+            state,
+            button,
+            modifiers: self.modifiers,
+        };
+        self.handle_event(event)
+    }
+
+    /// Click the left mouse button at `coord`
+    pub fn click_at(&mut self, coord: Coord) -> TkAction {
+        let mut action = self.move_cursor(coord);
+        action += self.mouse_input(MouseButton::Left, true);
+        action + self.mouse_input(MouseButton::Left, false)
+    }
+
+    /// Click the left mouse button at the centre of widget `id`
+    ///
+    /// Returns `None` if no widget with this `id` exists.
+    pub fn send_click(&mut self, id: WidgetId) -> Option<TkAction> {
+        let rect = self.rect(id)?;
+        let size = Coord::from(rect.size);
+        let coord = rect.pos + Coord(size.0 / 2, size.1 / 2);
+        Some(self.click_at(coord))
+    }
+
+    /// Set the state of modifier keys
+    pub fn set_modifiers(&mut self, modifiers: ModifiersState) -> TkAction {
+        self.modifiers = modifiers;
+        self.handle_event(WindowEvent::ModifiersChanged(modifiers))
+    }
+
+    /// Press or release a key
+    pub fn key_input(&mut self, vkey: VirtualKeyCode, pressed: bool) -> TkAction {
+        let state = match pressed {
+            true => ElementState::Pressed,
+            false => ElementState::Released,
+        };
+        #[allow(deprecated)]
+        let input = KeyboardInput {
+            // Scancodes only need to match between press and release
+            scancode: vkey as u32,
+            state,
+            virtual_keycode: Some(vkey),
+            modifiers: self.modifiers,
+        };
+        self.handle_event(WindowEvent::KeyboardInput {
+            device_id: device_id(),
+            input,
+            is_synthetic: false,
+        })
+    }
+
+    /// Press and release a key
+    pub fn press_key(&mut self, vkey: VirtualKeyCode) -> TkAction {
+        self.key_input(vkey, true) + self.key_input(vkey, false)
+    }
+
+    /// Type some text
+    ///
+    /// Each character is received by the widget with character focus (if
+    /// any); this is usually gained by clicking an edit field. Key events are
+    /// not simulated, thus to send keys such as Enter use
+    /// [`Window::press_key`].
+    pub fn type_text(&mut self, text: &str) -> TkAction {
+        let mut action = TkAction::None;
+        for c in text.chars() {
+            action += self.handle_event(WindowEvent::ReceivedCharacter(c));
+        }
+        action
+    }
+
+    /// Advance time
+    ///
+    /// Widget timers (see [`kas::event::Manager::update_on_timer`]) due by
+    /// the new time are fired.
+    pub fn advance_time(&mut self, duration: Duration) -> TkAction {
+        self.state.time_offset += duration;
+        let mut tkw = TkHeadless::new(&mut self.size_handle, &mut self.state);
+        let widget = &mut *self.widget;
+        self.mgr.with(&mut tkw, |mgr| {
+            mgr.update_timer(widget);
+        });
+        self.update()
+    }
+
+    /// Handle pending actions
+    ///
+    /// This is called automatically after each input; it is only required
+    /// after modifying widgets via other means (e.g. [`kas::event::UpdateHandle`]).
+    /// Returns the action handled.
+    pub fn update(&mut self) -> TkAction {
+        self.handle_pending();
+
+        let mut tkw = TkHeadless::new(&mut self.size_handle, &mut self.state);
+        let action = self.mgr.update(&mut tkw, &mut *self.widget);
+        let relayout = self.mgr.take_relayout();
+
+        match action {
+            TkAction::None | TkAction::Redraw => (),
+            TkAction::RegionMoved => {
+                self.mgr.region_moved(&mut tkw, &mut *self.widget);
+            }
+            TkAction::Popup => {
+                self.widget.resize_popups(&mut self.size_handle);
+                let mut tkw = TkHeadless::new(&mut self.size_handle, &mut self.state);
+                self.mgr.region_moved(&mut tkw, &mut *self.widget);
+            }
+            TkAction::SetSize => self.apply_size(),
+            TkAction::Relayout => {
+                self.mgr.configure_pending(&mut tkw, &mut *self.widget);
+                for id in relayout {
+                    kas::layout::mark_dirty(self.widget.as_widget_mut(), id);
+                }
+                kas::layout::invalidate_dirty(self.widget.as_widget_mut());
+                self.solve_cache.refresh_dirty_rules();
+                self.apply_size();
+            }
+            TkAction::Resize => {
+                self.solve_cache.invalidate_rule_cache();
+                self.apply_size();
+            }
+            TkAction::Reconfigure => {
+                debug!("Window::reconfigure");
+                let mut tkw = TkHeadless::new(&mut self.size_handle, &mut self.state);
+                self.mgr.configure(&mut tkw, &mut *self.widget);
+                self.solve_cache.invalidate_rule_cache();
+                self.apply_size();
+            }
+            TkAction::Close | TkAction::CloseAll => self.state.closed = true,
+        }
+
+        let mut tkw = TkHeadless::new(&mut self.size_handle, &mut self.state);
+        self.mgr.with(&mut tkw, |mgr| mgr.run_idle_tasks());
+        action
+    }
+
+    /// Draw the window, returning the draw commands issued
+    ///
+    /// Records are annotated with widget identifiers; see [`DrawRecorder`].
+    pub fn draw(&mut self) -> Vec<DrawRecord> {
+        let mut records = vec![];
+        let mut draw = NullDraw::default();
+        let rect = Rect::new(Coord::ZERO, self.size);
+        let mut handle = NullDrawHandle::new(&mut draw, &mut self.size_handle, rect);
+        let mut recorder = DrawRecorder::new(&mut handle, &mut records);
+        self.widget.draw(&mut recorder, &self.mgr, false);
+        DrawRecorder::annotate(&mut records, &*self.widget);
+
+        let time = self.state.now();
+        let mut tkw = TkHeadless::new(&mut self.size_handle, &mut self.state);
+        let widget = &mut *self.widget;
+        self.mgr.with(&mut tkw, |mgr| {
+            widget.handle_frame(mgr, time);
+        });
+        records
+    }
+
+    /// The current content of the (simulated) clipboard
+    pub fn clipboard(&self) -> Option<&str> {
+        self.state.clipboard.as_deref()
+    }
+
+    /// Set the content of the (simulated) clipboard
+    pub fn set_clipboard(&mut self, content: Option<String>) {
+        self.state.clipboard = content;
+    }
+
+    /// The cursor icon requested by widgets
+    pub fn cursor_icon(&self) -> CursorIcon {
+        self.state.cursor_icon
+    }
+
+    /// True if the window has requested closure
+    pub fn is_closed(&self) -> bool {
+        self.state.closed
+    }
+
+    /// Take windows opened via [`kas::event::Manager::add_window`]
+    ///
+    /// New windows are not shown, but may be inspected (or wrapped in a new
+    /// headless [`Window`]).
+    pub fn take_new_windows(&mut self) -> Vec<Box<dyn kas::Window>> {
+        std::mem::take(&mut self.state.new_windows)
+    }
+
+    fn apply_size(&mut self) {
+        let rect = Rect::new(Coord::ZERO, self.size);
+        debug!("Resizing window to rect = {:?}", rect);
+
+        self.solve_cache.apply_rect(
+            self.widget.as_widget_mut(),
+            &mut self.size_handle,
+            rect,
+            true,
+        );
+        self.widget.resize_popups(&mut self.size_handle);
+        self.mgr.check_pending(self.widget.as_widget());
+    }
+
+    /// Recompute sizes after a change to the scale factor
+    fn scale_resize(&mut self) {
+        let scale_factor = self.scale_factor * self.state.ui_scale as f64;
+        self.size_handle = MockSizeHandle::new(scale_factor as f32);
+        self.mgr.set_dpi_factor(scale_factor);
+        self.solve_cache.invalidate_rule_cache();
+        self.apply_size();
+    }
+
+    /// Handle actions deferred by [`TkHeadless`]
+    fn handle_pending(&mut self) {
+        while !self.state.pending.is_empty() {
+            for pending in std::mem::take(&mut self.state.pending) {
+                let mut tkw = TkHeadless::new(&mut self.size_handle, &mut self.state);
+                let widget = &mut *self.widget;
+                match pending {
+                    Pending::AddPopup(id, popup) => self.mgr.with(&mut tkw, |mut mgr| {
+                        kas::Window::add_popup(widget, &mut mgr, id, popup);
+                    }),
+                    Pending::ClosePopup(id) => self.mgr.with(&mut tkw, |mut mgr| {
+                        widget.remove_popup(&mut mgr, id);
+                    }),
+                    Pending::Update(handle, payload) => self.mgr.with(&mut tkw, |mgr| {
+                        mgr.update_handle(widget, handle, payload);
+                    }),
+                    Pending::EndDrag => self.mgr.with(&mut tkw, |mgr| {
+                        mgr.drag_end(widget, false);
+                    }),
+                    Pending::ScaleResize => self.scale_resize(),
+                }
+            }
+        }
+    }
+}
+
+fn device_id() -> DeviceId {
+    // Safety: the dummy identifier is only compared with other identifiers
+    unsafe { DeviceId::dummy() }
+}
+
+/// Actions deferred until [`Window::update`]
+enum Pending {
+    AddPopup(WindowId, kas::Popup),
+    ClosePopup(WindowId),
+    Update(UpdateHandle, u64),
+    EndDrag,
+    ScaleResize,
+}
+
+/// State accessible to [`TkHeadless`]
+struct State {
+    pending: Vec<Pending>,
+    popups: Vec<WindowId>,
+    new_windows: Vec<Box<dyn kas::Window>>,
+    next_id: u32,
+    clipboard: Option<String>,
+    primary: Option<String>,
+    ui_scale: f32,
+    cursor_icon: CursorIcon,
+    closed: bool,
+    time_offset: Duration,
+}
+
+impl State {
+    fn new() -> Self {
+        State {
+            pending: vec![],
+            popups: vec![],
+            new_windows: vec![],
+            next_id: 0,
+            clipboard: None,
+            primary: None,
+            ui_scale: 1.0,
+            cursor_icon: CursorIcon::Default,
+            closed: false,
+            time_offset: Duration::default(),
+        }
+    }
+
+    /// The simulated time: real time plus time advanced
+    fn now(&self) -> Instant {
+        Instant::now() + self.time_offset
+    }
+
+    fn next_window_id(&mut self) -> WindowId {
+        self.next_id += 1;
+        WindowId::new(NonZeroU32::new(self.next_id).unwrap())
+    }
+}
+
+struct TkHeadless<'a> {
+    size_handle: &'a mut MockSizeHandle,
+    state: &'a mut State,
+}
+
+impl<'a> TkHeadless<'a> {
+    fn new(size_handle: &'a mut MockSizeHandle, state: &'a mut State) -> Self {
+        TkHeadless { size_handle, state }
+    }
+}
+
+impl<'a> kas::TkWindow for TkHeadless<'a> {
+    fn add_popup(&mut self, popup: kas::Popup) -> WindowId {
+        let id = self.state.next_window_id();
+        self.state.popups.push(id);
+        self.state.pending.push(Pending::AddPopup(id, popup));
+        id
+    }
+
+    fn add_window(&mut self, widget: Box<dyn kas::Window>) -> WindowId {
+        self.state.new_windows.push(widget);
+        self.state.next_window_id()
+    }
+
+    fn close_window(&mut self, id: WindowId) {
+        if let Some(index) = self.state.popups.iter().position(|p| *p == id) {
+            self.state.popups.remove(index);
+            self.state.pending.push(Pending::ClosePopup(id));
+        } else {
+            debug!("TkHeadless::close_window({:?}): unknown window", id);
+        }
+    }
+
+    fn trigger_update(&mut self, handle: UpdateHandle, payload: u64) {
+        self.state.pending.push(Pending::Update(handle, payload));
+    }
+
+    fn get_clipboard(&mut self) -> Option<String> {
+        self.state.clipboard.clone()
+    }
+
+    fn set_clipboard<'c>(&mut self, content: std::borrow::Cow<'c, str>) {
+        self.state.clipboard = Some(content.into_owned());
+    }
+
+    fn get_primary(&mut self) -> Option<String> {
+        self.state.primary.clone()
+    }
+
+    fn set_primary<'c>(&mut self, content: std::borrow::Cow<'c, str>) {
+        self.state.primary = Some(content.into_owned());
+    }
+
+    fn set_taskbar_progress(&mut self, _: Option<f32>) {}
+
+    fn set_taskbar_badge(&mut self, _: Option<u32>) {}
+
+    fn request_attention(&mut self, _: bool) {}
+
+    fn inhibit_screensaver(&mut self, _: bool) {}
+
+    fn capture(&mut self, _: Box<dyn FnOnce(Capture)>) {
+        debug!("TkHeadless::capture: unsupported");
+    }
+
+    #[inline]
+    fn ui_scale(&self) -> f32 {
+        self.state.ui_scale
+    }
+
+    fn set_ui_scale(&mut self, scale: f32) {
+        let scale = scale.max(0.25).min(4.0);
+        if scale != self.state.ui_scale {
+            self.state.ui_scale = scale;
+            self.state.pending.push(Pending::ScaleResize);
+        }
+    }
+
+    fn adjust_theme(&mut self, _: &mut dyn FnMut(&mut dyn ThemeApi) -> ThemeAction) {
+        debug!("TkHeadless::adjust_theme: unsupported");
+    }
+
+    fn size_handle(&mut self, f: &mut dyn FnMut(&mut dyn SizeHandle)) {
+        f(&mut *self.size_handle);
+    }
+
+    #[inline]
+    fn set_cursor_icon(&mut self, icon: CursorIcon) {
+        self.state.cursor_icon = icon;
+    }
+
+    fn set_cursor_grab(&mut self, _: bool) -> bool {
+        true
+    }
+
+    fn set_cursor_visible(&mut self, _: bool) {}
+
+    fn set_cursor_position(&mut self, _: Coord) {}
+
+    fn start_drag(&mut self, _: DragData) {
+        debug!("TkHeadless::start_drag: unsupported");
+    }
+
+    fn end_drag(&mut self, _: bool) {
+        // The drop is never accepted, but the source expects notification
+        self.state.pending.push(Pending::EndDrag);
+    }
+
+    fn now(&self) -> Instant {
+        self.state.now()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use kas::prelude::*;
+
+    /// Counts timer updates, each scheduling the next after one second
+    #[handler(handle = noauto)]
+    #[widget(config = noauto)]
+    #[derive(Clone, Debug, Default, Widget)]
+    struct Ticker {
+        #[widget_core]
+        core: CoreData,
+        ticks: u32,
+    }
+
+    impl Layout for Ticker {
+        fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
+            SizeRules::fixed(10, (0, 0))
+        }
+
+        fn draw(&self, _: &mut dyn DrawHandle, _: &ManagerState, _: bool) {}
+    }
+
+    impl WidgetConfig for Ticker {
+        fn configure(&mut self, mgr: &mut Manager) {
+            mgr.update_on_timer(Duration::from_secs(1), self.id());
+        }
+    }
+
+    impl Handler for Ticker {
+        type Msg = VoidMsg;
+
+        fn handle(&mut self, mgr: &mut Manager, event: Event) -> Response<Self::Msg> {
+            match event {
+                Event::TimerUpdate => {
+                    self.ticks += 1;
+                    mgr.update_on_timer(Duration::from_secs(1), self.id());
+                    Response::None
+                }
+                event => Response::Unhandled(event),
+            }
+        }
+    }
+
+    fn ticks(window: &Window) -> u32 {
+        let id = window.widget().get(0).unwrap().id();
+        window.find_as::<Ticker>(id).unwrap().ticks
+    }
+
+    #[test]
+    fn timer_fires_when_due() {
+        let widget = kas::widget::Window::new("Ticker", Ticker::default());
+        let mut window = Window::new(widget);
+        let ms = Duration::from_millis;
+
+        window.advance_time(ms(500));
+        assert_eq!(ticks(&window), 0);
+        window.advance_time(ms(600));
+        assert_eq!(ticks(&window), 1);
+        window.advance_time(ms(1000));
+        assert_eq!(ticks(&window), 2);
+    }
+
+    #[test]
+    fn timer_scheduled_in_simulated_time() {
+        let widget = kas::widget::Window::new("Ticker", Ticker::default());
+        let mut window = Window::new(widget);
+        let ms = Duration::from_millis;
+
+        // One update is due; the next is scheduled a second after the
+        // simulated (not the real) time
+        window.advance_time(ms(10_000));
+        assert_eq!(ticks(&window), 1);
+        window.advance_time(ms(500));
+        assert_eq!(ticks(&window), 1);
+        window.advance_time(ms(600));
+        assert_eq!(ticks(&window), 2);
+    }
+}
@@ -14,14 +14,110 @@ use syn::token::{Brace, Colon, Comma, Eq, For, Impl, Paren};
 use syn::{braced, bracketed, parenthesized, parse_quote};
 use syn::{
     Attribute, ConstParam, Data, DeriveInput, Expr, Fields, FieldsNamed, FieldsUnnamed,
-    GenericParam, Generics, Ident, Index, Lifetime, LifetimeDef, Lit, Member, Token, Type,
-    TypeParam, TypePath, TypeTraitObject,
+    GenericArgument, GenericParam, Generics, Ident, Index, Lifetime, LifetimeDef, Lit, LitInt,
+    Member, Token, Type, TypeParam, TypePath, TypeTraitObject, Visibility,
 };
 
 #[derive(Debug)]
 pub struct Child {
     pub ident: Member,
+    pub ty: Type,
+    /// `M` where the child is `Box<dyn Widget<Msg = M>>` (or a `Vec` or
+    /// `Option` of such)
+    pub boxed_msg: Option<Type>,
     pub args: WidgetAttrArgs,
+    pub kind: ChildKind,
+}
+
+/// How a child widget is stored in its field
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChildKind {
+    /// The field is a widget
+    Field,
+    /// The field is a `Vec` of widgets
+    Vec,
+    /// The field is an `Option` of a widget
+    Option,
+}
+
+/// A field of the widget struct, as used by derived `Clone` and `Debug` impls
+#[derive(Debug)]
+pub struct DeriveField {
+    pub ident: Member,
+    pub ty: Type,
+    /// The `widget_core` or `layout_data` field
+    pub internal: bool,
+    /// Omit from `Debug` output (`#[widget_skip_debug]`)
+    pub skip_debug: bool,
+    /// Summarise in `Debug` output and use `Default` on `Clone`
+    /// (`#[widget_derive(skip)]`)
+    pub skip: bool,
+    /// Default value used by the builder (`#[widget_derive(default = expr)]`)
+    pub default: Option<Expr>,
+}
+
+/// Field arguments: `#[widget_derive(skip, default = expr)]`
+#[derive(Default)]
+pub struct FieldDeriveArgs {
+    pub skip: bool,
+    pub default: Option<Expr>,
+}
+
+impl Parse for FieldDeriveArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut args = FieldDeriveArgs::default();
+        let content;
+        let _ = parenthesized!(content in input);
+
+        while !content.is_empty() {
+            let lookahead = content.lookahead1();
+            if !args.skip && lookahead.peek(kw::skip) {
+                let _: kw::skip = content.parse()?;
+                args.skip = true;
+            } else if args.default.is_none() && lookahead.peek(Token![default]) {
+                let _: Token![default] = content.parse()?;
+                let _: Eq = content.parse()?;
+                args.default = Some(content.parse()?);
+            } else {
+                return Err(lookahead.error());
+            }
+
+            if content.peek(Comma) {
+                let _: Comma = content.parse()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// Traits to derive (`#[widget_derive(Clone, Debug)]`)
+#[derive(Debug, Default)]
+pub struct WidgetDerive {
+    pub clone: bool,
+    pub debug: bool,
+}
+
+impl Parse for WidgetDerive {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut derive = WidgetDerive::default();
+        let content;
+        let _ = parenthesized!(content in input);
+        let traits = content.parse_terminated::<Ident, Comma>(Ident::parse)?;
+        for ident in traits {
+            if ident == "Clone" && !derive.clone {
+                derive.clone = true;
+            } else if ident == "Debug" && !derive.debug {
+                derive.debug = true;
+            } else {
+                return Err(Error::new(
+                    ident.span(),
+                    "expected `Clone` or `Debug` (each at most once)",
+                ));
+            }
+        }
+        Ok(derive)
+    }
 }
 
 pub struct Args {
@@ -31,13 +127,15 @@ pub struct Args {
     pub layout: Option<LayoutArgs>,
     pub handler: Vec<HandlerArgs>,
     pub children: Vec<Child>,
+    pub derive: WidgetDerive,
+    pub fields: Vec<DeriveField>,
 }
 
 pub fn read_attrs(ast: &mut DeriveInput) -> Result<Args> {
     let not_struct_err = |span| {
         Err(Error::new(
             span,
-            "cannot derive Widget on an enum, union or unit struct",
+            "cannot derive Widget on a union or unit struct",
         ))
     };
     let (fields, span) = match &mut ast.data {
@@ -59,31 +157,36 @@ pub fn read_attrs(ast: &mut DeriveInput) -> Result<Args> {
     let mut core_data = None;
     let mut layout_data = None;
     let mut children = vec![];
+    let mut derive_fields = vec![];
 
     for (i, field) in fields.iter_mut().enumerate() {
+        let mut skip_debug = false;
+        let mut skip = false;
+        let mut default: Option<Expr> = None;
+        let mut internal = false;
         for attr in field.attrs.drain(..) {
             if attr.path == parse_quote! { layout } || attr.path == parse_quote! { handler } {
                 // These are valid attributes according to proc_macro_derive, so we need to catch them
-                #[cfg(nightly)]
-                attr.span()
-                    .unwrap()
-                    .error("invalid attribute on Widget field (applicable to struct only)")
-                    .emit()
+                return Err(Error::new(
+                    attr.span(),
+                    "invalid attribute on Widget field (applicable to struct only)",
+                ));
             } else if attr.path == parse_quote! { widget_core } {
                 if core_data.is_none() {
+                    internal = true;
                     core_data = Some(member(i, field.ident.clone()));
                 } else {
-                    #[cfg(nightly)]
-                    attr.span()
-                        .unwrap()
-                        .error("multiple fields marked with #[widget_core]")
-                        .emit();
+                    return Err(Error::new(
+                        attr.span(),
+                        "multiple fields marked with #[widget_core]",
+                    ));
                 }
             } else if attr.path == parse_quote! { layout_data } {
                 if layout_data.is_none() {
                     if field.ty != parse_quote! { <Self as kas::LayoutData>::Data }
                         && field.ty != parse_quote! { <Self as LayoutData>::Data }
                     {
+                        // Stable Rust has no API for warnings; this is nightly-only
                         #[cfg(nightly)]
                         field
                             .ty
@@ -92,60 +195,103 @@ pub fn read_attrs(ast: &mut DeriveInput) -> Result<Args> {
                             .warning("expected type `<Self as kas::LayoutData>::Data`")
                             .emit();
                     }
+                    internal = true;
                     layout_data = Some(member(i, field.ident.clone()));
                 } else {
-                    #[cfg(nightly)]
-                    attr.span()
-                        .unwrap()
-                        .error("multiple fields marked with #[layout_data]")
-                        .emit();
+                    return Err(Error::new(
+                        attr.span(),
+                        "multiple fields marked with #[layout_data]",
+                    ));
                 }
             } else if attr.path == parse_quote! { widget } {
                 let ident = member(i, field.ident.clone());
-                let args = syn::parse2(attr.tokens)?;
-                children.push(Child { ident, args });
+                let args: WidgetAttrArgs = syn::parse2(attr.tokens)?;
+                let ty = field.ty.clone();
+                let kind = child_kind(&ty);
+                let boxed_msg = boxed_msg(&ty, kind);
+                if let Some(expr) = &args.default {
+                    if default.replace(expr.clone()).is_some() {
+                        return Err(Error::new(expr.span(), "multiple default values for field"));
+                    }
+                }
+                children.push(Child {
+                    ident,
+                    ty,
+                    boxed_msg,
+                    args,
+                    kind,
+                });
+            } else if attr.path == parse_quote! { widget_skip_debug } {
+                skip_debug = true;
+            } else if attr.path == parse_quote! { widget_derive } {
+                let span = attr.span();
+                let args: FieldDeriveArgs = syn::parse2(attr.tokens)?;
+                skip |= args.skip;
+                if let Some(expr) = args.default {
+                    if default.replace(expr).is_some() {
+                        return Err(Error::new(span, "multiple default values for field"));
+                    }
+                }
             }
         }
+        derive_fields.push(DeriveField {
+            ident: member(i, field.ident.clone()),
+            ty: field.ty.clone(),
+            internal,
+            skip_debug,
+            skip,
+            default,
+        });
     }
 
     let mut widget = None;
     let mut layout = None;
     let mut handler = vec![];
+    let mut derive = None;
 
     for attr in ast.attrs.drain(..) {
         if attr.path == parse_quote! { widget_core } || attr.path == parse_quote! { layout_data } {
             // These are valid attributes according to proc_macro_derive, so we need to catch them
-            #[cfg(nightly)]
-            attr.span()
-                .unwrap()
-                .error("invalid attribute on Widget struct (applicable to fields only)")
-                .emit()
+            return Err(Error::new(
+                attr.span(),
+                "invalid attribute on Widget struct (applicable to fields only)",
+            ));
         } else if attr.path == parse_quote! { widget } {
             if widget.is_none() {
                 widget = Some(syn::parse2(attr.tokens)?);
             } else {
-                #[cfg(nightly)]
-                attr.span()
-                    .unwrap()
-                    .error("multiple #[widget(..)] attributes on type")
-                    .emit()
+                return Err(Error::new(
+                    attr.span(),
+                    "multiple #[widget(..)] attributes on type",
+                ));
             }
         } else if attr.path == parse_quote! { layout } {
             if layout.is_none() {
                 layout = Some(syn::parse2(attr.tokens)?);
             } else {
-                #[cfg(nightly)]
-                attr.span()
-                    .unwrap()
-                    .error("multiple #[layout(..)] attributes on type")
-                    .emit()
+                return Err(Error::new(
+                    attr.span(),
+                    "multiple #[layout(..)] attributes on type",
+                ));
             }
         } else if attr.path == parse_quote! { handler } {
             handler.push(syn::parse2(attr.tokens)?);
+        } else if attr.path == parse_quote! { widget_derive } {
+            if derive.is_none() {
+                derive = Some(syn::parse2(attr.tokens)?);
+            } else {
+                return Err(Error::new(
+                    attr.span(),
+                    "multiple #[widget_derive(..)] attributes on type",
+                ));
+            }
         }
     }
 
     let widget = widget.unwrap_or(WidgetArgs::default());
+    let mut derive: WidgetDerive = derive.unwrap_or_default();
+    // #[widget(debug)] is equivalent to #[widget_derive(Debug)]
+    derive.debug |= widget.debug;
 
     if let Some(core_data) = core_data {
         Ok(Args {
@@ -155,6 +301,8 @@ pub fn read_attrs(ast: &mut DeriveInput) -> Result<Args> {
             layout,
             handler,
             children,
+            derive,
+            fields: derive_fields,
         })
     } else {
         Err(Error::new(
@@ -164,6 +312,78 @@ pub fn read_attrs(ast: &mut DeriveInput) -> Result<Args> {
     }
 }
 
+// Determine kind from the (syntactic) type: `Vec<T>`, `Option<T>` or other
+fn child_kind(ty: &Type) -> ChildKind {
+    if let Type::Path(TypePath { qself: None, path }) = ty {
+        if let Some(seg) = path.segments.last() {
+            if let syn::PathArguments::AngleBracketed(_) = seg.arguments {
+                if seg.ident == "Vec" {
+                    return ChildKind::Vec;
+                } else if seg.ident == "Option" {
+                    return ChildKind::Option;
+                }
+            }
+        }
+    }
+    ChildKind::Field
+}
+
+// Determine `M` from the (syntactic) type `Box<dyn Widget<Msg = M>>`,
+// optionally within the `Vec` or `Option` of `kind`
+fn boxed_msg(ty: &Type, kind: ChildKind) -> Option<Type> {
+    fn last_args(ty: &Type) -> Option<(&Ident, &Punctuated<GenericArgument, Comma>)> {
+        match ty {
+            Type::Path(TypePath { qself: None, path }) => {
+                let seg = path.segments.last()?;
+                match &seg.arguments {
+                    syn::PathArguments::AngleBracketed(args) => Some((&seg.ident, &args.args)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+    fn single_type(args: &Punctuated<GenericArgument, Comma>) -> Option<&Type> {
+        match args.first() {
+            Some(GenericArgument::Type(ty)) if args.len() == 1 => Some(ty),
+            _ => None,
+        }
+    }
+
+    let mut ty = ty;
+    if kind != ChildKind::Field {
+        ty = single_type(last_args(ty)?.1)?;
+    }
+
+    let (ident, args) = last_args(ty)?;
+    if ident != "Box" {
+        return None;
+    }
+    let bounds = match single_type(args)? {
+        Type::TraitObject(TypeTraitObject { bounds, .. }) => bounds,
+        _ => return None,
+    };
+    for bound in bounds {
+        if let syn::TypeParamBound::Trait(bound) = bound {
+            let seg = bound.path.segments.last()?;
+            if seg.ident != "Widget" {
+                continue;
+            }
+            if let syn::PathArguments::AngleBracketed(args) = &seg.arguments {
+                for arg in &args.args {
+                    match arg {
+                        GenericArgument::Binding(b) if b.ident == "Msg" => {
+                            return Some(b.ty.clone());
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
 fn member(index: usize, ident: Option<Ident>) -> Member {
     match ident {
         None => Member::Unnamed(Index {
@@ -179,6 +399,10 @@ mod kw {
     use syn::custom_keyword;
 
     custom_keyword!(area);
+    custom_keyword!(cols);
+    custom_keyword!(gap);
+    custom_keyword!(col_gap);
+    custom_keyword!(row_gap);
     custom_keyword!(layout);
     custom_keyword!(col);
     custom_keyword!(row);
@@ -186,6 +410,7 @@ mod kw {
     custom_keyword!(rspan);
     custom_keyword!(widget);
     custom_keyword!(handler);
+    custom_keyword!(map);
     custom_keyword!(msg);
     custom_keyword!(generics);
     custom_keyword!(single);
@@ -194,9 +419,17 @@ mod kw {
     custom_keyword!(down);
     custom_keyword!(up);
     custom_keyword!(grid);
+    custom_keyword!(flow);
     custom_keyword!(halign);
     custom_keyword!(valign);
+    custom_keyword!(stretch);
+    custom_keyword!(margins);
+    custom_keyword!(min_size);
+    custom_keyword!(ideal_size);
+    custom_keyword!(max_size);
     custom_keyword!(key_nav);
+    custom_keyword!(tab_index);
+    custom_keyword!(nav_group);
     custom_keyword!(cursor_icon);
     custom_keyword!(handle);
     custom_keyword!(send);
@@ -204,6 +437,11 @@ mod kw {
     custom_keyword!(noauto);
     custom_keyword!(children);
     custom_keyword!(column);
+    custom_keyword!(debug);
+    custom_keyword!(accessors);
+    custom_keyword!(skip);
+    custom_keyword!(builder);
+    custom_keyword!(hidden);
 }
 
 #[derive(Debug)]
@@ -214,13 +452,29 @@ pub struct WidgetAttrArgs {
     pub rspan: Option<Lit>,
     pub halign: Option<Ident>,
     pub valign: Option<Ident>,
+    pub stretch: Option<Lit>,
+    pub margins: Option<Expr>,
+    pub min_size: Option<Expr>,
+    pub ideal_size: Option<Expr>,
+    pub max_size: Option<Expr>,
     pub handler: Option<Ident>,
+    pub map: Option<Expr>,
+    pub default: Option<Expr>,
+    /// Initially hidden
+    pub hidden: bool,
+    pub tab_index: Option<u32>,
+    pub nav_group: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct GridPos(pub u32, pub u32, pub u32, pub u32);
 
 impl WidgetAttrArgs {
+    // True if either of column or row is given
+    pub fn has_pos(&self) -> bool {
+        self.col.is_some() || self.row.is_some()
+    }
+
     // Parse widget position, filling in missing information with defaults.
     pub fn as_pos(&self) -> Result<GridPos> {
         fn parse_lit(lit: &Lit) -> Result<u32> {
@@ -255,6 +509,14 @@ impl WidgetAttrArgs {
             }
         })
     }
+    // Parse stretch weight, if given
+    pub fn stretch(&self) -> Result<Option<u32>> {
+        match self.stretch {
+            Some(Lit::Int(ref li)) => Ok(Some(li.base10_parse()?)),
+            Some(ref lit) => Err(Error::new(lit.span(), "expected integer literal")),
+            None => Ok(None),
+        }
+    }
     pub fn halign_toks(&self) -> Result<Option<TokenStream>> {
         if let Some(ref ident) = self.halign {
             Ok(Some(Self::match_align(ident, true)?))
@@ -280,7 +542,17 @@ impl Parse for WidgetAttrArgs {
             rspan: None,
             halign: None,
             valign: None,
+            stretch: None,
+            margins: None,
+            min_size: None,
+            ideal_size: None,
+            max_size: None,
             handler: None,
+            map: None,
+            default: None,
+            hidden: false,
+            tab_index: None,
+            nav_group: None,
         };
         if input.is_empty() {
             return Ok(args);
@@ -319,10 +591,55 @@ impl Parse for WidgetAttrArgs {
                 let _: kw::valign = content.parse()?;
                 let _: Eq = content.parse()?;
                 args.valign = Some(content.parse()?);
+            } else if args.stretch.is_none() && lookahead.peek(kw::stretch) {
+                let _: kw::stretch = content.parse()?;
+                let _: Eq = content.parse()?;
+                args.stretch = Some(content.parse()?);
+            } else if args.margins.is_none() && lookahead.peek(kw::margins) {
+                let _: kw::margins = content.parse()?;
+                let _: Eq = content.parse()?;
+                args.margins = Some(content.parse()?);
+            } else if args.min_size.is_none() && lookahead.peek(kw::min_size) {
+                let _: kw::min_size = content.parse()?;
+                let _: Eq = content.parse()?;
+                args.min_size = Some(content.parse()?);
+            } else if args.ideal_size.is_none() && lookahead.peek(kw::ideal_size) {
+                let _: kw::ideal_size = content.parse()?;
+                let _: Eq = content.parse()?;
+                args.ideal_size = Some(content.parse()?);
+            } else if args.max_size.is_none() && lookahead.peek(kw::max_size) {
+                let _: kw::max_size = content.parse()?;
+                let _: Eq = content.parse()?;
+                args.max_size = Some(content.parse()?);
             } else if args.handler.is_none() && lookahead.peek(kw::handler) {
                 let _: kw::handler = content.parse()?;
                 let _: Eq = content.parse()?;
                 args.handler = Some(content.parse()?);
+            } else if args.map.is_none() && lookahead.peek(kw::map) {
+                let _: kw::map = content.parse()?;
+                let _: Eq = content.parse()?;
+                args.map = Some(content.parse()?);
+            } else if args.default.is_none() && lookahead.peek(Token![default]) {
+                let _: Token![default] = content.parse()?;
+                let _: Eq = content.parse()?;
+                args.default = Some(content.parse()?);
+            } else if !args.hidden && lookahead.peek(kw::hidden) {
+                let _: kw::hidden = content.parse()?;
+                args.hidden = true;
+            } else if args.tab_index.is_none() && lookahead.peek(kw::tab_index) {
+                let _: kw::tab_index = content.parse()?;
+                let _: Eq = content.parse()?;
+                let index: LitInt = content.parse()?;
+                args.tab_index = Some(index.base10_parse()?);
+            } else if args.nav_group.is_none() && lookahead.peek(kw::nav_group) {
+                let _: kw::nav_group = content.parse()?;
+                let _: Eq = content.parse()?;
+                let name = if content.peek(syn::LitStr) {
+                    content.parse::<syn::LitStr>()?.value()
+                } else {
+                    content.parse::<Ident>()?.to_string()
+                };
+                args.nav_group = Some(name);
             } else {
                 return Err(lookahead.error());
             }
@@ -333,6 +650,13 @@ impl Parse for WidgetAttrArgs {
             let _: Comma = content.parse()?;
         }
 
+        if let (Some(_), Some(ref map)) = (&args.handler, &args.map) {
+            return Err(Error::new(
+                map.span(),
+                "the `handler` and `map` arguments are mutually exclusive",
+            ));
+        }
+
         Ok(args)
     }
 }
@@ -345,7 +669,14 @@ impl ToTokens for WidgetAttrArgs {
             || self.rspan.is_some()
             || self.halign.is_some()
             || self.valign.is_some()
+            || self.stretch.is_some()
+            || self.margins.is_some()
+            || self.min_size.is_some()
+            || self.ideal_size.is_some()
+            || self.max_size.is_some()
             || self.handler.is_some()
+            || self.map.is_some()
+            || self.default.is_some()
         {
             let comma = TokenTree::from(Punct::new(',', Spacing::Alone));
             let mut args = TokenStream::new();
@@ -382,12 +713,54 @@ impl ToTokens for WidgetAttrArgs {
                 }
                 args.append_all(quote! { valign = #ident });
             }
+            if let Some(ref lit) = self.stretch {
+                if !args.is_empty() {
+                    args.append(comma.clone());
+                }
+                args.append_all(quote! { stretch = #lit });
+            }
+            if let Some(ref expr) = self.margins {
+                if !args.is_empty() {
+                    args.append(comma.clone());
+                }
+                args.append_all(quote! { margins = #expr });
+            }
+            if let Some(ref expr) = self.min_size {
+                if !args.is_empty() {
+                    args.append(comma.clone());
+                }
+                args.append_all(quote! { min_size = #expr });
+            }
+            if let Some(ref expr) = self.ideal_size {
+                if !args.is_empty() {
+                    args.append(comma.clone());
+                }
+                args.append_all(quote! { ideal_size = #expr });
+            }
+            if let Some(ref expr) = self.max_size {
+                if !args.is_empty() {
+                    args.append(comma.clone());
+                }
+                args.append_all(quote! { max_size = #expr });
+            }
             if let Some(ref ident) = self.handler {
                 if !args.is_empty() {
-                    args.append(comma);
+                    args.append(comma.clone());
                 }
                 args.append_all(quote! { handler = #ident });
             }
+            if let Some(ref expr) = self.map {
+                if !args.is_empty() {
+                    args.append(comma.clone());
+                }
+                args.append_all(quote! { map = #expr });
+            }
+            if let Some(ref expr) = self.default {
+                if !args.is_empty() {
+                    args.append(comma);
+                }
+                args.append_all(quote! { default = #expr });
+            }
             tokens.append_all(quote! { ( #args ) });
         }
     }
@@ -415,6 +788,9 @@ impl ToTokens for GridPos {
 pub struct WidgetArgs {
     pub config: Option<WidgetConfig>,
     pub children: bool,
+    pub debug: bool,
+    pub accessors: bool,
+    pub builder: bool,
 }
 
 impl Default for WidgetArgs {
@@ -422,6 +798,9 @@ impl Default for WidgetArgs {
         WidgetArgs {
             config: Some(WidgetConfig::default()),
             children: true,
+            debug: false,
+            accessors: false,
+            builder: false,
         }
     }
 }
@@ -446,6 +825,9 @@ impl Parse for WidgetArgs {
         let mut have_config = false;
         let mut children = true;
         let mut have_children = false;
+        let mut debug = false;
+        let mut accessors = false;
+        let mut builder = false;
 
         if !input.is_empty() {
             let content;
@@ -459,6 +841,15 @@ impl Parse for WidgetArgs {
                     let _: Eq = content.parse()?;
                     let _: kw::noauto = content.parse()?;
                     children = false;
+                } else if lookahead.peek(kw::debug) && !debug {
+                    let _: kw::debug = content.parse()?;
+                    debug = true;
+                } else if lookahead.peek(kw::accessors) && !accessors {
+                    let _: kw::accessors = content.parse()?;
+                    accessors = true;
+                } else if lookahead.peek(kw::builder) && !builder {
+                    let _: kw::builder = content.parse()?;
+                    builder = true;
                 } else if lookahead.peek(kw::config) && !have_config {
                     have_config = true;
                     let _: kw::config = content.parse()?;
@@ -520,7 +911,13 @@ impl Parse for WidgetArgs {
             config = Some(WidgetConfig::default());
         }
 
-        Ok(WidgetArgs { config, children })
+        Ok(WidgetArgs {
+            config,
+            children,
+            debug,
+            accessors,
+            builder,
+        })
     }
 }
 
@@ -532,12 +929,13 @@ pub enum LayoutType {
     Down,
     Up,
     Grid,
+    Flow,
 }
 
 impl ToTokens for LayoutType {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         tokens.append_all(match self {
-            LayoutType::Single | LayoutType::Grid => unreachable!(),
+            LayoutType::Single | LayoutType::Grid | LayoutType::Flow => unreachable!(),
             LayoutType::Right => quote! { kas::Right },
             LayoutType::Left => quote! { kas::Left },
             LayoutType::Down => quote! { kas::Down },
@@ -550,6 +948,82 @@ pub struct LayoutArgs {
     pub span: Span,
     pub layout: LayoutType,
     pub area: Option<Ident>,
+    /// Number of columns for automatic placement (grid only)
+    pub cols: Option<u32>,
+    /// Minimum gap between columns and rows (grid only)
+    pub gap: (u16, u16),
+    /// Nested structure (row and column layouts only)
+    pub nested: Option<LayoutNode>,
+}
+
+/// A row or column within a nested layout
+pub struct LayoutNode {
+    pub dir: LayoutType,
+    pub items: Vec<LayoutItem>,
+}
+
+/// An item within a [`LayoutNode`]
+pub enum LayoutItem {
+    Child(Member),
+    Node(LayoutNode),
+}
+
+impl LayoutNode {
+    /// Parse a bracketed list of items: `[a, row: [b, c]]`
+    fn parse_items(dir: LayoutType, input: ParseStream) -> Result<Self> {
+        let content;
+        let bracket = bracketed!(content in input);
+
+        let mut items = vec![];
+        while !content.is_empty() {
+            items.push(content.parse()?);
+            if content.is_empty() {
+                break;
+            }
+            let _: Comma = content.parse()?;
+        }
+
+        if items.is_empty() {
+            return Err(Error::new(bracket.span, "expected at least one item"));
+        }
+        Ok(LayoutNode { dir, items })
+    }
+}
+
+impl Parse for LayoutItem {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if !input.peek2(Colon) {
+            return Ok(LayoutItem::Child(input.parse()?));
+        }
+
+        let lookahead = input.lookahead1();
+        let dir = if lookahead.peek(kw::row) {
+            let _: kw::row = input.parse()?;
+            LayoutType::Right
+        } else if lookahead.peek(kw::right) {
+            let _: kw::right = input.parse()?;
+            LayoutType::Right
+        } else if lookahead.peek(kw::left) {
+            let _: kw::left = input.parse()?;
+            LayoutType::Left
+        } else if lookahead.peek(kw::col) {
+            let _: kw::col = input.parse()?;
+            LayoutType::Down
+        } else if lookahead.peek(kw::column) {
+            let _: kw::column = input.parse()?;
+            LayoutType::Down
+        } else if lookahead.peek(kw::down) {
+            let _: kw::down = input.parse()?;
+            LayoutType::Down
+        } else if lookahead.peek(kw::up) {
+            let _: kw::up = input.parse()?;
+            LayoutType::Up
+        } else {
+            return Err(lookahead.error());
+        };
+        let _: Colon = input.parse()?;
+        Ok(LayoutItem::Node(LayoutNode::parse_items(dir, input)?))
+    }
 }
 
 impl Parse for LayoutArgs {
@@ -594,15 +1068,36 @@ impl Parse for LayoutArgs {
         } else if lookahead.peek(kw::grid) {
             let _: kw::grid = content.parse()?;
             LayoutType::Grid
+        } else if lookahead.peek(kw::flow) {
+            let _: kw::flow = content.parse()?;
+            LayoutType::Flow
         } else {
             return Err(lookahead.error());
         };
 
+        let mut nested = None;
+        if content.peek(Colon) {
+            match layout {
+                LayoutType::Right | LayoutType::Left | LayoutType::Down | LayoutType::Up => {
+                    let _: Colon = content.parse()?;
+                    nested = Some(LayoutNode::parse_items(layout, &content)?);
+                }
+                _ => {
+                    return Err(Error::new(
+                        content.span(),
+                        "nested layout is only supported by row and column layouts",
+                    ));
+                }
+            }
+        }
+
         if content.peek(Comma) {
             let _: Comma = content.parse()?;
         }
 
         let mut area = None;
+        let mut cols = None;
+        let mut gap = (0, 0);
 
         while !content.is_empty() {
             let lookahead = content.lookahead1();
@@ -610,6 +1105,31 @@ impl Parse for LayoutArgs {
                 let _: kw::area = content.parse()?;
                 let _: Eq = content.parse()?;
                 area = Some(content.parse()?);
+            } else if layout == LayoutType::Grid && cols.is_none() && lookahead.peek(kw::cols) {
+                let _: kw::cols = content.parse()?;
+                let _: Eq = content.parse()?;
+                let lit: LitInt = content.parse()?;
+                let n = lit.base10_parse()?;
+                if n == 0 {
+                    return Err(Error::new(lit.span(), "expected `cols` > 0"));
+                }
+                cols = Some(n);
+            } else if layout == LayoutType::Grid && lookahead.peek(kw::gap) {
+                let _: kw::gap = content.parse()?;
+                let _: Eq = content.parse()?;
+                let lit: LitInt = content.parse()?;
+                let n = lit.base10_parse()?;
+                gap = (n, n);
+            } else if layout == LayoutType::Grid && lookahead.peek(kw::col_gap) {
+                let _: kw::col_gap = content.parse()?;
+                let _: Eq = content.parse()?;
+                let lit: LitInt = content.parse()?;
+                gap.0 = lit.base10_parse()?;
+            } else if layout == LayoutType::Grid && lookahead.peek(kw::row_gap) {
+                let _: kw::row_gap = content.parse()?;
+                let _: Eq = content.parse()?;
+                let lit: LitInt = content.parse()?;
+                gap.1 = lit.base10_parse()?;
             } else {
                 return Err(lookahead.error());
             }
@@ -619,7 +1139,14 @@ impl Parse for LayoutArgs {
             }
         }
 
-        Ok(LayoutArgs { span, layout, area })
+        Ok(LayoutArgs {
+            span,
+            layout,
+            area,
+            cols,
+            gap,
+            nested,
+        })
     }
 }
 
@@ -628,7 +1155,7 @@ pub struct HandlerArgs {
     pub handle: bool,
     pub send: bool,
     pub msg: Type,
-    pub substitutions: HashMap<Ident, Type>,
+    pub substitutions: HashMap<Ident, GenericArgument>,
     pub generics: Generics,
 }
 
@@ -691,12 +1218,20 @@ impl Parse for HandlerArgs {
                     let _: kw::generics = content.parse()?;
                     let _: Eq = content.parse()?;
 
-                    // Optionally, substitutions come first
+                    // Optionally, substitutions come first. The value is a
+                    // type, or for const parameters a literal or `{ expr }`.
                     while content.peek(Ident) {
                         let ident: Ident = content.parse()?;
                         let _: Token![=>] = content.parse()?;
-                        let ty: Type = content.parse()?;
-                        args.substitutions.insert(ident, ty);
+                        let arg = if content.peek(Lit)
+                            || content.peek(Brace)
+                            || content.peek(Token![-])
+                        {
+                            GenericArgument::Const(content.parse()?)
+                        } else {
+                            GenericArgument::Type(content.parse()?)
+                        };
+                        args.substitutions.insert(ident, arg);
                         let _: Comma = content.parse()?;
                     }
 
@@ -800,6 +1335,7 @@ pub struct WidgetField {
     pub ident: Option<Ident>,
     pub ty: ChildType,
     pub value: Expr,
+    pub handler_closure: Option<syn::ExprClosure>,
 }
 
 pub struct MakeWidget {
@@ -807,6 +1343,9 @@ pub struct MakeWidget {
     pub handler: Option<HandlerArgs>,
     // additional attributes
     pub extra_attrs: TokenStream,
+    // visibility and name, if given
+    pub vis: Visibility,
+    pub name: Option<Ident>,
     pub generics: Generics,
     pub struct_span: Span,
     // child widgets and data fields
@@ -831,7 +1370,13 @@ impl Parse for MakeWidget {
             }
         }
 
+        let vis: Visibility = input.parse()?;
         let s: Token![struct] = input.parse()?;
+        let name = if input.peek(Ident) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
 
         let mut generics: syn::Generics = input.parse()?;
         if input.peek(syn::token::Where) {
@@ -875,6 +1420,8 @@ impl Parse for MakeWidget {
         Ok(MakeWidget {
             handler,
             extra_attrs,
+            vis,
+            name,
             generics,
             struct_span: s.span(),
             fields,
@@ -998,11 +1545,40 @@ impl Parse for WidgetField {
         let _: Eq = input.parse()?;
         let value: Expr = input.parse()?;
 
+        let mut handler_closure = None;
+        if input.peek(Token![=>]) {
+            let arrow: Token![=>] = input.parse()?;
+            let closure: syn::ExprClosure = input.parse()?;
+            match &widget_attr {
+                None => {
+                    return Err(Error::new(
+                        arrow.span(),
+                        "can only use `=> |w, mgr, msg| ...` handler on widgets",
+                    ));
+                }
+                Some(attr) if attr.args.handler.is_some() || attr.args.map.is_some() => {
+                    return Err(Error::new(
+                        arrow.span(),
+                        "`=> |w, mgr, msg| ...` handler is exclusive with `handler` and `map` arguments",
+                    ));
+                }
+                _ => (),
+            }
+            if closure.inputs.len() != 3 {
+                return Err(Error::new(
+                    closure.span(),
+                    "handler closures must take three arguments: |w, mgr, msg| ...",
+                ));
+            }
+            handler_closure = Some(closure);
+        }
+
         Ok(WidgetField {
             widget_attr,
             ident,
             ty,
             value,
+            handler_closure,
         })
     }
 }
@@ -3,12 +3,138 @@
 // You may obtain a copy of the License in the LICENSE-APACHE file or at:
 //     https://www.apache.org/licenses/LICENSE-2.0
 
-use crate::args::{Child, LayoutArgs, LayoutType};
+use crate::args::{Child, ChildKind, GridPos, LayoutArgs, LayoutItem, LayoutNode, LayoutType};
 use proc_macro2::TokenStream;
-use quote::{quote, TokenStreamExt};
+use quote::{format_ident, quote, TokenStreamExt};
 use syn::parse::{Error, Result};
 use syn::Member;
 
+/// Find grid positions of children
+///
+/// Where `layout.cols` is given, children without `col` or `row` are placed
+/// in the next free cell, filling rows left-to-right (without backtracking).
+fn grid_positions(children: &[Child], layout: &LayoutArgs) -> Result<Vec<GridPos>> {
+    let mut positions = Vec::with_capacity(children.len());
+    for child in children {
+        positions.push(child.args.as_pos()?);
+    }
+
+    let cols = match layout.cols {
+        Some(cols) => cols,
+        None => return Ok(positions),
+    };
+    let overlaps = |a: &GridPos, b: &GridPos| {
+        a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+    };
+    let mut placed: Vec<GridPos> = children
+        .iter()
+        .zip(positions.iter())
+        .filter(|(child, _)| child.args.has_pos())
+        .map(|(_, pos)| *pos)
+        .collect();
+
+    let (mut col, mut row) = (0, 0);
+    for (child, pos) in children.iter().zip(positions.iter_mut()) {
+        if child.args.has_pos() {
+            continue;
+        }
+        if pos.2 > cols {
+            return Err(Error::new_spanned(
+                &child.ident,
+                "`cspan` exceeds number of columns (`cols`)",
+            ));
+        }
+        loop {
+            if col + pos.2 > cols {
+                col = 0;
+                row += 1;
+            }
+            pos.0 = col;
+            pos.1 = row;
+            if !placed.iter().any(|p| overlaps(p, pos)) {
+                break;
+            }
+            col += 1;
+        }
+        col += pos.2;
+        placed.push(*pos);
+    }
+    Ok(positions)
+}
+
+/// Collect nodes of a nested layout, in pre-order
+fn nested_nodes<'a>(node: &'a LayoutNode, nodes: &mut Vec<&'a LayoutNode>) {
+    nodes.push(node);
+    for item in &node.items {
+        if let LayoutItem::Node(ref inner) = item {
+            nested_nodes(inner, nodes);
+        }
+    }
+}
+
+/// Check that each child appears exactly once in a nested layout
+fn check_nested(children: &[Child], layout: &LayoutArgs, nodes: &[&LayoutNode]) -> Result<()> {
+    // Data is a tuple of storage, one per node; std implements traits to 12
+    if nodes.len() > 12 {
+        return Err(Error::new(
+            layout.span,
+            "nested layout supports at most 12 rows and columns",
+        ));
+    }
+
+    let mut placed = vec![false; children.len()];
+    for node in nodes {
+        for item in &node.items {
+            if let LayoutItem::Child(ref member) = item {
+                let index = match children.iter().position(|c| c.ident == *member) {
+                    Some(index) => index,
+                    None => {
+                        return Err(Error::new_spanned(member, "not a #[widget] field"));
+                    }
+                };
+                if children[index].kind != ChildKind::Field {
+                    return Err(Error::new_spanned(
+                        member,
+                        "nested layout does not support Option children",
+                    ));
+                }
+                if placed[index] {
+                    return Err(Error::new_spanned(
+                        member,
+                        "child appears more than once in layout",
+                    ));
+                }
+                placed[index] = true;
+            }
+        }
+    }
+
+    if let Some(index) = placed.iter().position(|placed| !placed) {
+        return Err(Error::new_spanned(
+            &children[index].ident,
+            "child is missing from nested layout",
+        ));
+    }
+    Ok(())
+}
+
+fn row_storage(len: usize) -> TokenStream {
+    quote! {
+        kas::layout::FixedRowStorage::<
+            [kas::layout::SizeRules; #len + 1],
+            [u32; #len],
+        >
+    }
+}
+
+fn row_temp(len: usize) -> TokenStream {
+    if len > 16 {
+        quote! { Vec<u32> }
+    } else {
+        quote! { [u32; #len] }
+    }
+}
+
 pub(crate) fn data_type(children: &Vec<Child>, layout: &LayoutArgs) -> Result<TokenStream> {
     if layout.layout == LayoutType::Single {
         if !children.len() == 1 {
@@ -22,16 +148,35 @@ pub(crate) fn data_type(children: &Vec<Child>, layout: &LayoutArgs) -> Result<To
         }
     }
 
+    if let Some(ref root) = layout.nested {
+        let mut nodes = vec![];
+        nested_nodes(root, &mut nodes);
+        check_nested(children, layout, &nodes)?;
+
+        let storage = nodes.iter().map(|node| row_storage(node.items.len()));
+        let dir = root.dir;
+        let temp = row_temp(root.items.len());
+        let root_storage = row_storage(root.items.len());
+        return Ok(quote! {
+            type Data = (#(#storage,)*);
+            type Solver = kas::layout::RowSolver::<#root_storage>;
+            type Setter = kas::layout::RowSetter::<#dir, #temp, #root_storage>;
+        });
+    }
+
     let mut cols: usize = 0;
     let mut rows: usize = 0;
     let mut col_spans: usize = 0;
     let mut row_spans: usize = 0;
 
-    for child in children.iter() {
-        let args = &child.args;
+    let positions = match layout.layout {
+        LayoutType::Grid => grid_positions(children, layout)?,
+        _ => vec![],
+    };
 
+    for (index, _) in children.iter().enumerate() {
         match layout.layout {
-            LayoutType::Single => (),
+            LayoutType::Single | LayoutType::Flow => (),
             LayoutType::Right | LayoutType::Left => {
                 cols += 1;
                 rows = 1;
@@ -41,7 +186,7 @@ pub(crate) fn data_type(children: &Vec<Child>, layout: &LayoutArgs) -> Result<To
                 rows += 1;
             }
             LayoutType::Grid => {
-                let pos = args.as_pos()?;
+                let pos = positions[index];
                 let c1 = pos.0 + pos.2;
                 let r1 = pos.1 + pos.3;
                 cols = cols.max(c1 as usize);
@@ -101,6 +246,11 @@ pub(crate) fn data_type(children: &Vec<Child>, layout: &LayoutArgs) -> Result<To
                 Self::Data,
             >;
         },
+        LayoutType::Flow => quote! {
+            type Data = kas::layout::FlowStorage;
+            type Solver = kas::layout::FlowSolver;
+            type Setter = kas::layout::FlowSetter;
+        },
         LayoutType::Grid => quote! {
             type Data = kas::layout::FixedGridStorage::<
                 [kas::layout::SizeRules; #cols + 1],
@@ -151,8 +301,20 @@ pub(crate) fn derive(
     let mut set_rect = TokenStream::new();
     let mut draw = TokenStream::new();
     let mut find_id_child = TokenStream::new();
+    // Per-child size adjustments and alignment, used by nested layouts
+    let mut leaf_rules = Vec::with_capacity(children.len());
+    let mut leaf_align = Vec::with_capacity(children.len());
 
-    for child in children.iter() {
+    let positions = match layout.layout {
+        LayoutType::Grid => grid_positions(children, layout)?,
+        _ => vec![],
+    };
+    // Grid dimensions (used for gaps)
+    let grid_dim = positions.iter().fold((0, 0), |(cols, rows), pos| {
+        (cols.max(pos.0 + pos.2), rows.max(pos.1 + pos.3))
+    });
+
+    for (index, child) in children.iter().enumerate() {
         let ident = &child.ident;
         let args = &child.args;
 
@@ -172,8 +334,14 @@ pub(crate) fn derive(
 
                 quote! { #row }
             }
+            LayoutType::Flow => {
+                let index = cols;
+                cols += 1;
+
+                quote! { #index }
+            }
             LayoutType::Grid => {
-                let pos = args.as_pos()?;
+                let pos = positions[index];
                 let (c0, c1) = (pos.0, pos.0 + pos.2);
                 let (r0, r1) = (pos.1, pos.1 + pos.3);
                 cols = cols.max(c1 as usize);
@@ -190,38 +358,145 @@ pub(crate) fn derive(
             }
         };
 
-        size.append_all(quote! {
-            let child = &mut self.#ident;
-            solver.for_child(
-                &mut #data,
-                #child_info,
-                |axis| child.size_rules(size_handle, axis)
-            );
-        });
+        let weight = match (args.stretch()?, &layout.layout) {
+            (None, _) => quote! {},
+            (Some(w), LayoutType::Right)
+            | (Some(w), LayoutType::Left)
+            | (Some(w), LayoutType::Down)
+            | (Some(w), LayoutType::Up) => quote! { .with_weight(#w) },
+            (Some(_), _) => {
+                return Err(Error::new_spanned(
+                    ident,
+                    "`stretch` is only supported by row and column layouts",
+                ));
+            }
+        };
 
-        set_rect.append_all(quote! { let mut align = kas::AlignHints::NONE; });
+        let mut adjust = TokenStream::new();
+        if let Some(ref expr) = args.margins {
+            adjust.append_all(quote! {
+                let margins = kas::layout::Margins::from(#expr)
+                    .scaled(size_handle.scale_factor());
+                rules.set_margins(margins.axis(axis.is_vertical()));
+            });
+        }
+        let sizes = [
+            (&args.min_size, quote! { set_min_size }),
+            (&args.ideal_size, quote! { set_ideal_size }),
+            (&args.max_size, quote! { set_max_size }),
+        ];
+        for (expr, method) in sizes.iter() {
+            if let Some(expr) = expr {
+                // A zero component leaves that axis unchanged
+                adjust.append_all(quote! {
+                    let size = kas::geom::Size::from(#expr) * size_handle.scale_factor();
+                    let size = axis.extract_size(size);
+                    if size != 0 {
+                        rules.#method(size);
+                    }
+                });
+            }
+        }
+
+        if layout.layout == LayoutType::Grid && layout.gap != (0, 0) {
+            // Gaps are implemented as margins on internal edges of cells
+            let pos = positions[index];
+            let (gh, gv) = layout.gap;
+            let h0 = if pos.0 > 0 { gh } else { 0 };
+            let h1 = if pos.0 + pos.2 < grid_dim.0 { gh } else { 0 };
+            let v0 = if pos.1 > 0 { gv } else { 0 };
+            let v1 = if pos.1 + pos.3 < grid_dim.1 { gv } else { 0 };
+            adjust.append_all(quote! {
+                let gap = if axis.is_vertical() { (#v0, #v1) } else { (#h0, #h1) };
+                let scale = size_handle.scale_factor();
+                let scale = |x: u16| (x as f32 * scale).round() as u16;
+                rules.include_margins((scale(gap.0), scale(gap.1)));
+            });
+        }
+
+        leaf_rules.push((weight.clone(), adjust.clone()));
+        let child_rules = if adjust.is_empty() {
+            quote! { |axis| child.size_rules(size_handle, axis)#weight }
+        } else {
+            quote! {
+                |axis| {
+                    let mut rules = child.size_rules(size_handle, axis)#weight;
+                    #adjust
+                    rules
+                }
+            }
+        };
+
+        // A hidden or absent child occupies its cell with empty size
+        let is_option = child.kind == ChildKind::Option;
+        if is_option {
+            size.append_all(quote! {
+                match self.#ident.as_mut() {
+                    Some(child) if !child.is_hidden() => {
+                        solver.for_child(&mut #data, #child_info, #child_rules)
+                    }
+                    _ => solver.for_hidden_child(&mut #data, #child_info),
+                }
+            });
+        } else {
+            size.append_all(quote! {
+                let child = &mut self.#ident;
+                if child.is_hidden() {
+                    solver.for_hidden_child(&mut #data, #child_info);
+                } else {
+                    solver.for_child(&mut #data, #child_info, #child_rules);
+                }
+            });
+        }
+
+        let mut align = quote! { let mut align = kas::AlignHints::NONE; };
         if let Some(toks) = args.halign_toks()? {
-            set_rect.append_all(quote! { align.horiz = Some(#toks); });
+            align.append_all(quote! { align.horiz = Some(#toks); });
         }
         if let Some(toks) = args.valign_toks()? {
-            set_rect.append_all(quote! { align.vert = Some(#toks); });
+            align.append_all(quote! { align.vert = Some(#toks); });
         }
+        set_rect.append_all(align.clone());
+        leaf_align.push(align);
+        // Bind `child`, conditionally where optional
+        let (child, child_mut) = if is_option {
+            (
+                quote! { if let Some(ref child) = self.#ident },
+                quote! { if let Some(ref mut child) = self.#ident },
+            )
+        } else {
+            (
+                quote! { let child = &self.#ident; },
+                quote! { let child = &mut self.#ident; },
+            )
+        };
         set_rect.append_all(quote! {
-            self.#ident.set_rect(setter.child_rect(&mut #data, #child_info), align);
+            let child_rect = setter.child_rect(&mut #data, #child_info);
+            #child_mut {
+                child.set_rect(child_rect, align);
+            }
         });
 
         draw.append_all(quote! {
-            let c0 = self.#ident.rect().pos;
-            let c1 = c0 + Coord::from(self.#ident.rect().size);
-            if c0.0 <= pos1.0 && c1.0 >= pos0.0 && c0.1 <= pos1.1 && c1.1 >= pos0.1 {
-                self.#ident.draw(draw_handle, mgr, disabled);
+            #child {
+                let c0 = child.rect().pos;
+                let c1 = c0 + Coord::from(child.rect().size);
+                if !child.is_hidden()
+                    && c0.0 <= pos1.0 && c1.0 >= pos0.0 && c0.1 <= pos1.1 && c1.1 >= pos0.1
+                {
+                    child.draw(draw_handle, mgr, disabled);
+                }
             }
         });
 
         // TODO: more efficient search strategy?
         find_id_child.append_all(quote! {
-            if let Some(id) = self.#ident.find_id(coord) {
-                return Some(id);
+            #child {
+                if !child.is_hidden() {
+                    if let Some(id) = child.find_id(coord) {
+                        return Some(id);
+                    }
+                }
             }
         });
     }
@@ -233,6 +508,7 @@ pub(crate) fn derive(
         LayoutType::Down => quote! { (kas::Down, #rows) },
         LayoutType::Up => quote! { (kas::Up, #rows) },
         LayoutType::Grid => quote! { (#cols, #rows) },
+        LayoutType::Flow => quote! { #cols },
     };
 
     let find_id_body = find_id_area.unwrap_or_else(|| {
@@ -242,39 +518,53 @@ pub(crate) fn derive(
         }
     });
 
-    Ok(quote! {
-        fn size_rules(
-            &mut self,
-            size_handle: &mut dyn kas::draw::SizeHandle,
-            axis: kas::layout::AxisInfo
-        )
-            -> kas::layout::SizeRules
-        {
-            use kas::WidgetCore;
-            use kas::layout::RulesSolver;
+    let layout_fns = if let Some(ref root) = layout.nested {
+        derive_nested(children, root, &data, &leaf_rules, &leaf_align)
+    } else {
+        quote! {
+            fn size_rules(
+                &mut self,
+                size_handle: &mut dyn kas::draw::SizeHandle,
+                axis: kas::layout::AxisInfo
+            )
+                -> kas::layout::SizeRules
+            {
+                use kas::WidgetCore;
+                use kas::layout::RulesSolver;
 
-            let mut solver = <Self as kas::LayoutData>::Solver::new(
-                axis,
-                #dim,
-                &mut #data,
-            );
-            #size
-            solver.finish(&mut #data)
-        }
+                if let Some(rules) = self.core_data().layout.get(axis) {
+                    return rules;
+                }
 
-        fn set_rect(&mut self, rect: kas::geom::Rect, align: kas::AlignHints) {
-            use kas::{WidgetCore, Widget};
-            use kas::layout::{Margins, RulesSetter};
-            self.core.rect = rect;
+                let mut solver = <Self as kas::LayoutData>::Solver::new(
+                    axis,
+                    #dim,
+                    &mut #data,
+                );
+                #size
+                let rules = solver.finish(&mut #data);
+                self.core_data_mut().layout.set(axis, rules);
+                rules
+            }
 
-            let mut setter = <Self as kas::LayoutData>::Setter::new(
-                rect,
-                #dim,
-                align,
-                &mut #data,
-            );
-            #set_rect
+            fn set_rect(&mut self, rect: kas::geom::Rect, align: kas::AlignHints) {
+                use kas::{WidgetCore, Widget};
+                use kas::layout::{Margins, RulesSetter};
+                self.core.rect = rect;
+
+                let mut setter = <Self as kas::LayoutData>::Setter::new(
+                    rect,
+                    #dim,
+                    align,
+                    &mut #data,
+                );
+                #set_rect
+            }
         }
+    };
+
+    Ok(quote! {
+        #layout_fns
 
         fn find_id(&self, coord: kas::geom::Coord) -> Option<kas::WidgetId> {
             use kas::WidgetCore;
@@ -301,3 +591,165 @@ pub(crate) fn derive(
         }
     })
 }
+
+/// Generate `size_rules` and `set_rect` for a nested layout
+///
+/// Each node uses its own row storage (a field of the data tuple); nodes are
+/// numbered in pre-order, matching [`data_type`].
+fn derive_nested(
+    children: &[Child],
+    root: &LayoutNode,
+    data: &TokenStream,
+    leaf_rules: &[(TokenStream, TokenStream)],
+    leaf_align: &[TokenStream],
+) -> TokenStream {
+    fn index_of(children: &[Child], member: &Member) -> usize {
+        // Checked by check_nested
+        children.iter().position(|c| c.ident == *member).unwrap()
+    }
+
+    fn node_rules(
+        node: &LayoutNode,
+        next: &mut usize,
+        children: &[Child],
+        leaf_rules: &[(TokenStream, TokenStream)],
+    ) -> TokenStream {
+        let storage = format_ident!("_d{}", *next);
+        *next += 1;
+        let dir = node.dir;
+        let len = node.items.len();
+
+        let mut toks = TokenStream::new();
+        for (i, item) in node.items.iter().enumerate() {
+            toks.append_all(match item {
+                LayoutItem::Child(member) => {
+                    let index = index_of(children, member);
+                    let child = format_ident!("_c{}", index);
+                    let (ref weight, ref adjust) = leaf_rules[index];
+                    let child_rules = if adjust.is_empty() {
+                        quote! { |axis| #child.size_rules(size_handle, axis)#weight }
+                    } else {
+                        quote! {
+                            |axis| {
+                                let mut rules = #child.size_rules(size_handle, axis)#weight;
+                                #adjust
+                                rules
+                            }
+                        }
+                    };
+                    quote! {
+                        if #child.is_hidden() {
+                            solver.for_hidden_child(&mut *#storage, #i);
+                        } else {
+                            solver.for_child(&mut *#storage, #i, #child_rules);
+                        }
+                    }
+                }
+                LayoutItem::Node(inner) => {
+                    let inner = node_rules(inner, next, children, leaf_rules);
+                    quote! {
+                        solver.for_child(&mut *#storage, #i, |axis| #inner);
+                    }
+                }
+            });
+        }
+
+        quote! {{
+            let mut solver = kas::layout::RowSolver::new(
+                axis,
+                (#dir, #len),
+                &mut *#storage,
+            );
+            #toks
+            solver.finish(&mut *#storage)
+        }}
+    }
+
+    fn node_set_rect(
+        node: &LayoutNode,
+        next: &mut usize,
+        children: &[Child],
+        leaf_align: &[TokenStream],
+    ) -> TokenStream {
+        let storage = format_ident!("_d{}", *next);
+        *next += 1;
+        let dir = node.dir;
+        let len = node.items.len();
+        let temp = row_temp(len);
+
+        let mut toks = TokenStream::new();
+        for (i, item) in node.items.iter().enumerate() {
+            toks.append_all(match item {
+                LayoutItem::Child(member) => {
+                    let align = &leaf_align[index_of(children, member)];
+                    quote! {{
+                        let child_rect = setter.child_rect(&mut *#storage, #i);
+                        #align
+                        self.#member.set_rect(child_rect, align);
+                    }}
+                }
+                LayoutItem::Node(inner) => {
+                    let inner = node_set_rect(inner, next, children, leaf_align);
+                    quote! {{
+                        let rect = setter.child_rect(&mut *#storage, #i);
+                        let align = kas::AlignHints::NONE;
+                        #inner
+                    }}
+                }
+            });
+        }
+
+        quote! {{
+            let mut setter = kas::layout::RowSetter::<#dir, #temp, _>::new(
+                rect,
+                (#dir, #len),
+                align,
+                &mut *#storage,
+            );
+            #toks
+        }}
+    }
+
+    let mut num_nodes = 0;
+    let rules = node_rules(root, &mut num_nodes, children, leaf_rules);
+    let storage: Vec<_> = (0..num_nodes).map(|n| format_ident!("_d{}", n)).collect();
+    let child_vars = (0..children.len()).map(|n| format_ident!("_c{}", n));
+    let child_idents = children.iter().map(|c| &c.ident);
+
+    let mut next = 0;
+    let set_rect = node_set_rect(root, &mut next, children, leaf_align);
+
+    quote! {
+        fn size_rules(
+            &mut self,
+            size_handle: &mut dyn kas::draw::SizeHandle,
+            axis: kas::layout::AxisInfo
+        )
+            -> kas::layout::SizeRules
+        {
+            use kas::WidgetCore;
+            use kas::layout::RulesSolver;
+
+            if let Some(rules) = self.core_data().layout.get(axis) {
+                return rules;
+            }
+
+            let rules = {
+                let (#(#storage,)*) = &mut #data;
+                #(let #child_vars = &mut self.#child_idents;)*
+                #rules
+            };
+            self.core_data_mut().layout.set(axis, rules);
+            rules
+        }
+
+        fn set_rect(&mut self, rect: kas::geom::Rect, align: kas::AlignHints) {
+            use kas::{WidgetCore, Widget};
+            use kas::layout::{Margins, RulesSetter};
+            self.core.rect = rect;
+
+            let (#(#storage,)*) = &mut #data;
+            #set_rect
+        }
+    }
+}
@@ -12,20 +12,19 @@ mod args;
 
 use std::collections::HashMap;
 
-use proc_macro2::{Span, TokenStream};
-use quote::{quote, ToTokens, TokenStreamExt};
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
 use std::fmt::Write;
-#[cfg(nightly)]
 use syn::spanned::Spanned;
 use syn::Token;
 use syn::{parse_macro_input, parse_quote};
-use syn::{GenericParam, Ident, Type, TypeParam, TypePath};
+use syn::{ConstParam, GenericArgument, GenericParam, Ident, Type, TypeParam, TypePath};
 
-use self::args::{ChildType, HandlerArgs};
+use self::args::{ChildKind, ChildType, HandlerArgs};
 
 mod layout;
 
-struct SubstTyGenerics<'a>(&'a syn::Generics, HashMap<Ident, Type>);
+struct SubstTyGenerics<'a>(&'a syn::Generics, HashMap<Ident, GenericArgument>);
 
 // impl copied from syn, with modifications
 impl<'a> ToTokens for SubstTyGenerics<'a> {
@@ -60,17 +59,19 @@ impl<'a> ToTokens for SubstTyGenerics<'a> {
             }
             match *param.value() {
                 GenericParam::Lifetime(_) => unreachable!(),
-                GenericParam::Type(param) => {
+                GenericParam::Type(param) => match self.1.get(&param.ident) {
+                    Some(GenericArgument::Const(expr)) => const_arg(expr).to_tokens(tokens),
+                    Some(result) => result.to_tokens(tokens),
+                    None => param.ident.to_tokens(tokens),
+                },
+                GenericParam::Const(param) => {
+                    // Leave off the const parameter defaults
                     if let Some(result) = self.1.get(&param.ident) {
                         result.to_tokens(tokens);
                     } else {
                         param.ident.to_tokens(tokens);
                     }
                 }
-                GenericParam::Const(param) => {
-                    // Leave off the const parameter defaults
-                    param.ident.to_tokens(tokens);
-                }
             }
             param.punct().to_tokens(tokens);
         }
@@ -79,30 +80,509 @@ impl<'a> ToTokens for SubstTyGenerics<'a> {
     }
 }
 
+// Const generic arguments other than literals and blocks must be braced
+fn const_arg(expr: &syn::Expr) -> TokenStream {
+    match expr {
+        syn::Expr::Lit(_) | syn::Expr::Block(_) => expr.to_token_stream(),
+        expr => quote! { { #expr } },
+    }
+}
+
+// Replace substituted generic parameters wherever they appear in `tokens`
+fn subst_tokens(tokens: TokenStream, subs: &HashMap<Ident, GenericArgument>) -> TokenStream {
+    let mut out = TokenStream::new();
+    for tt in tokens {
+        match tt {
+            TokenTree::Ident(ref ident) if subs.contains_key(ident) => match &subs[ident] {
+                GenericArgument::Const(expr) => out.append_all(const_arg(expr)),
+                arg => arg.to_tokens(&mut out),
+            },
+            TokenTree::Group(group) => {
+                let mut new = Group::new(group.delimiter(), subst_tokens(group.stream(), subs));
+                new.set_span(group.span());
+                out.append(new);
+            }
+            tt => out.append(tt),
+        }
+    }
+    out
+}
+
+/// Extend the where clause of `generics` with `T: #bound` for each type param
+fn bound_type_params(generics: &syn::Generics, bound: TokenStream) -> syn::WhereClause {
+    let mut clause = generics
+        .where_clause
+        .clone()
+        .unwrap_or_else(|| syn::WhereClause {
+            where_token: Default::default(),
+            predicates: Default::default(),
+        });
+    for param in generics.type_params() {
+        let ident = &param.ident;
+        clause.predicates.push(parse_quote! { #ident: #bound });
+    }
+    clause
+}
+
+/// Implementation of `WidgetChildren::nav_hint` from child attributes
+///
+/// Returns an empty token stream (thus using the default) if no child has
+/// `tab_index` or `nav_group` attributes.
+fn nav_hint(children: &[args::Child]) -> TokenStream {
+    if children
+        .iter()
+        .all(|c| c.args.tab_index.is_none() && c.args.nav_group.is_none())
+    {
+        return quote! {};
+    }
+
+    let mut rules = quote! {};
+    for child in children {
+        let ident = &child.ident;
+        let tab_index = match child.args.tab_index {
+            Some(index) => quote! { Some(#index) },
+            None => quote! { None },
+        };
+        let group = match child.args.nav_group {
+            Some(ref name) => quote! { Some(#name) },
+            None => quote! { None },
+        };
+        let hint = quote! { kas::NavHint { tab_index: #tab_index, group: #group } };
+        rules.append_all(match child.kind {
+            ChildKind::Field => quote! {
+                if _index == 0 {
+                    return #hint;
+                }
+                _index -= 1;
+            },
+            ChildKind::Vec => quote! {
+                if _index < self.#ident.len() {
+                    return #hint;
+                }
+                _index -= self.#ident.len();
+            },
+            ChildKind::Option => quote! {
+                if self.#ident.is_some() {
+                    if _index == 0 {
+                        return #hint;
+                    }
+                    _index -= 1;
+                }
+            },
+        });
+    }
+    quote! {
+        fn nav_hint(&self, _index: usize) -> kas::NavHint {
+            let mut _index = _index;
+            #rules
+            kas::NavHint::default()
+        }
+    }
+}
+
+type Bounds = syn::punctuated::Punctuated<syn::TypeParamBound, Token![+]>;
+
+/// If `ty` is a boxed closure (`Box<dyn Fn..>`, `Rc<..>` or `Arc<..>`), get
+/// the wrapper's path (without arguments) and the closure's bounds
+fn boxed_closure(ty: &Type) -> Option<(syn::Path, &Bounds)> {
+    let path = match ty {
+        Type::Path(TypePath { qself: None, path }) => path,
+        _ => return None,
+    };
+    let seg = path.segments.last()?;
+    if !(seg.ident == "Box" || seg.ident == "Rc" || seg.ident == "Arc") {
+        return None;
+    }
+    let bounds = match &seg.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(Type::TraitObject(obj)) => &obj.bounds,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let is_fn = |bound: &syn::TypeParamBound| match bound {
+        syn::TypeParamBound::Trait(bound) => match bound.path.segments.last() {
+            Some(seg) => seg.ident == "Fn" || seg.ident == "FnMut" || seg.ident == "FnOnce",
+            None => false,
+        },
+        _ => false,
+    };
+    if !bounds.iter().any(is_fn) {
+        return None;
+    }
+    let mut wrapper = path.clone();
+    wrapper.segments.last_mut().unwrap().arguments = syn::PathArguments::None;
+    Some((wrapper, bounds))
+}
+
+/// Builder type generated by `#[widget(builder)]`
+///
+/// Required fields (those without default value) are typed by a generic
+/// parameter of the builder: `()` until set. `build` is implemented only where
+/// all these parameters are the field types, thus a missing field is a
+/// compile-time error.
+fn builder(ast: &syn::DeriveInput, fields: &[args::DeriveField]) -> syn::Result<TokenStream> {
+    let name = &ast.ident;
+    let builder = format_ident!("{}Builder", name);
+    let vis = &ast.vis;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let mut fields_named = vec![];
+    for field in fields.iter().filter(|field| !field.internal) {
+        match &field.ident {
+            syn::Member::Named(ident) => fields_named.push((ident, field)),
+            syn::Member::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(
+                    &field.ident,
+                    "#[widget(builder)] requires named fields",
+                ));
+            }
+        }
+    }
+
+    // Type arguments of the widget, followed by those of required fields
+    let args: Vec<TokenStream> = ast
+        .generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Type(TypeParam { ident, .. }) => quote! { #ident },
+            GenericParam::Lifetime(param) => param.lifetime.to_token_stream(),
+            GenericParam::Const(ConstParam { ident, .. }) => quote! { #ident },
+        })
+        .collect();
+    let mut params = vec![];
+    let mut unset = vec![];
+    let mut set = vec![];
+
+    let mut struct_fields = quote! {};
+    let mut init = quote! {};
+    let mut build = quote! {};
+    for field in fields.iter().filter(|field| field.internal) {
+        let ident = &field.ident;
+        build.append_all(quote! { #ident: Default::default(), });
+    }
+    for (ident, field) in &fields_named {
+        let ty = &field.ty;
+        match &field.default {
+            Some(expr) => {
+                struct_fields.append_all(quote! { #ident: Option<#ty>, });
+                init.append_all(quote! { #ident: None, });
+                build.append_all(quote! { #ident: self.#ident.unwrap_or_else(|| #expr), });
+            }
+            None => {
+                let param = format_ident!("__Field{}", params.len());
+                struct_fields.append_all(quote! { #ident: #param, });
+                init.append_all(quote! { #ident: (), });
+                build.append_all(quote! { #ident: self.#ident, });
+                params.push(param);
+                unset.push(quote! { () });
+                set.push(quote! { #ty });
+            }
+        }
+    }
+
+    let mut setters = quote! {};
+    let mut required = 0;
+    for (ident, field) in &fields_named {
+        let ty = &field.ty;
+        // Closures are accepted directly, otherwise anything convertible
+        let (arg_ty, value) = match boxed_closure(ty) {
+            Some((wrapper, bounds)) => {
+                let lifetime = bounds
+                    .iter()
+                    .any(|bound| matches!(bound, syn::TypeParamBound::Lifetime(_)));
+                let arg_ty = if lifetime {
+                    quote! { impl #bounds }
+                } else {
+                    quote! { impl #bounds + 'static }
+                };
+                (arg_ty, quote! { #wrapper::new(#ident) })
+            }
+            None => (quote! { impl Into<#ty> }, quote! { #ident.into() }),
+        };
+        let doc = format!("Set the `{}` field (chain style)", ident);
+
+        if field.default.is_some() {
+            setters.append_all(quote! {
+                #[doc = #doc]
+                #[inline]
+                pub fn #ident(mut self, #ident: #arg_ty) -> Self {
+                    let #ident: #ty = #value;
+                    self.#ident = Some(#ident);
+                    self
+                }
+            });
+            continue;
+        }
+
+        let mut moves = quote! {};
+        for (other, _) in &fields_named {
+            if other != ident {
+                moves.append_all(quote! { #other: self.#other, });
+            }
+        }
+        let out: Vec<TokenStream> = params
+            .iter()
+            .enumerate()
+            .map(|(i, param)| {
+                if i == required {
+                    quote! { #ty }
+                } else {
+                    quote! { #param }
+                }
+            })
+            .collect();
+        setters.append_all(quote! {
+            #[doc = #doc]
+            #[inline]
+            pub fn #ident(self, #ident: #arg_ty) -> #builder<#(#args,)* #(#out),*> {
+                let #ident: #ty = #value;
+                #builder {
+                    #ident,
+                    #moves
+                    _pd: self._pd,
+                }
+            }
+        });
+        required += 1;
+    }
+
+    let mut generics = ast.generics.clone();
+    for param in &params {
+        generics.params.push(parse_quote! { #param });
+    }
+    let (b_impl_generics, b_ty_generics, _) = generics.split_for_impl();
+
+    let doc = format!(
+        "Builder for [`{}`]\n\nThe `build` method is available once all fields \
+        without default value are set.",
+        name
+    );
+    let doc_builder = format!("Construct a [`{}`]", builder);
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #builder #b_impl_generics #where_clause {
+            #struct_fields
+            _pd: std::marker::PhantomData<fn() -> #name #ty_generics>,
+        }
+
+        impl #b_impl_generics #builder #b_ty_generics #where_clause {
+            #setters
+        }
+
+        impl #impl_generics #builder<#(#args,)* #(#set),*> #where_clause {
+            /// Build the widget
+            pub fn build(self) -> #name #ty_generics {
+                #name {
+                    #build
+                }
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc = #doc_builder]
+            #[inline]
+            pub fn builder() -> #builder<#(#args,)* #(#unset),*> {
+                #builder {
+                    #init
+                    _pd: Default::default(),
+                }
+            }
+        }
+    })
+}
+
+/// Implementation of `WidgetChildren::initially_hidden` from child attributes
+///
+/// Returns an empty token stream (thus using the default) if no child has the
+/// `hidden` attribute.
+fn initially_hidden(children: &[args::Child]) -> TokenStream {
+    if children.iter().all(|c| !c.args.hidden) {
+        return quote! {};
+    }
+
+    let mut rules = quote! {};
+    for child in children {
+        let ident = &child.ident;
+        let hidden = child.args.hidden;
+        rules.append_all(match child.kind {
+            ChildKind::Field => quote! {
+                if _index == 0 {
+                    return #hidden;
+                }
+                _index -= 1;
+            },
+            ChildKind::Vec => quote! {
+                if _index < self.#ident.len() {
+                    return #hidden;
+                }
+                _index -= self.#ident.len();
+            },
+            ChildKind::Option => quote! {
+                if self.#ident.is_some() {
+                    if _index == 0 {
+                        return #hidden;
+                    }
+                    _index -= 1;
+                }
+            },
+        });
+    }
+    quote! {
+        fn initially_hidden(&self, _index: usize) -> bool {
+            let mut _index = _index;
+            #rules
+            false
+        }
+    }
+}
+
 /// Macro to derive widget traits
 ///
 /// See the [`kas::macros`](../kas/macros/index.html) module documentation.
-#[proc_macro_derive(Widget, attributes(widget_core, widget, layout, handler, layout_data))]
+#[proc_macro_derive(
+    Widget,
+    attributes(
+        widget_core,
+        widget,
+        layout,
+        handler,
+        layout_data,
+        widget_skip_debug,
+        widget_derive
+    )
+)]
 pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let mut ast = parse_macro_input!(input as syn::DeriveInput);
 
+    if let syn::Data::Enum(ref data) = ast.data {
+        return match derive_enum(&ast, data) {
+            Ok(toks) => toks.into(),
+            Err(err) => err.to_compile_error().into(),
+        };
+    }
+
     let mut args = match args::read_attrs(&mut ast) {
         Ok(w) => w,
         Err(err) => return err.to_compile_error().into(),
     };
+    // Trait impls on `Box<dyn Widget<Msg = M>>` require `M: 'static`
+    for child in &args.children {
+        if let Some(Type::Path(TypePath { qself: None, path })) = &child.boxed_msg {
+            if let Some(ident) = path.get_ident() {
+                if ast
+                    .generics
+                    .type_params()
+                    .any(|param| param.ident == *ident)
+                {
+                    let clause = ast.generics.make_where_clause();
+                    clause.predicates.push(parse_quote! { #ident: 'static });
+                }
+            }
+        }
+    }
+
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
     let name = &ast.ident;
     let widget_name = name.to_string();
 
     let core_data = args.core_data;
     let count = args.children.len();
+    let mut count = quote! { #count };
 
     let mut get_rules = quote! {};
     let mut get_mut_rules = quote! {};
-    for (i, child) in args.children.iter().enumerate() {
-        let ident = &child.ident;
-        get_rules.append_all(quote! { #i => Some(&self.#ident), });
-        get_mut_rules.append_all(quote! { #i => Some(&mut self.#ident), });
+    let is_dynamic = args.children.iter().any(|c| c.kind != ChildKind::Field);
+    if is_dynamic {
+        // Children are enumerated in order, with the contents of each Vec or
+        // Option inlined in place of the field.
+        let mut len = quote! { 0 };
+        for child in args.children.iter() {
+            let ident = &child.ident;
+            match child.kind {
+                ChildKind::Field => {
+                    len.append_all(quote! { + 1 });
+                    get_rules.append_all(quote! {
+                        if _index == 0 {
+                            return Some(kas::WidgetCore::as_widget(&self.#ident));
+                        }
+                        _index -= 1;
+                    });
+                    get_mut_rules.append_all(quote! {
+                        if _index == 0 {
+                            return Some(kas::WidgetCore::as_widget_mut(&mut self.#ident));
+                        }
+                        _index -= 1;
+                    });
+                }
+                ChildKind::Vec => {
+                    len.append_all(quote! { + self.#ident.len() });
+                    get_rules.append_all(quote! {
+                        if _index < self.#ident.len() {
+                            return Some(kas::WidgetCore::as_widget(&self.#ident[_index]));
+                        }
+                        _index -= self.#ident.len();
+                    });
+                    get_mut_rules.append_all(quote! {
+                        if _index < self.#ident.len() {
+                            return Some(kas::WidgetCore::as_widget_mut(&mut self.#ident[_index]));
+                        }
+                        _index -= self.#ident.len();
+                    });
+                }
+                ChildKind::Option => {
+                    len.append_all(quote! { + self.#ident.is_some() as usize });
+                    get_rules.append_all(quote! {
+                        if let Some(ref child) = self.#ident {
+                            if _index == 0 {
+                                return Some(kas::WidgetCore::as_widget(child));
+                            }
+                            _index -= 1;
+                        }
+                    });
+                    get_mut_rules.append_all(quote! {
+                        if let Some(ref mut child) = self.#ident {
+                            if _index == 0 {
+                                return Some(kas::WidgetCore::as_widget_mut(child));
+                            }
+                            _index -= 1;
+                        }
+                    });
+                }
+            }
+        }
+        count = len;
+        get_rules = quote! {
+            let mut _index = _index;
+            #get_rules
+            None
+        };
+        get_mut_rules = quote! {
+            let mut _index = _index;
+            #get_mut_rules
+            None
+        };
+    } else {
+        for (i, child) in args.children.iter().enumerate() {
+            let ident = &child.ident;
+            get_rules.append_all(quote! { #i => Some(kas::WidgetCore::as_widget(&self.#ident)), });
+            get_mut_rules.append_all(
+                quote! { #i => Some(kas::WidgetCore::as_widget_mut(&mut self.#ident)), },
+            );
+        }
+        get_rules = quote! {
+            match _index {
+                #get_rules
+                _ => None
+            }
+        };
+        get_mut_rules = quote! {
+            match _index {
+                #get_mut_rules
+                _ => None
+            }
+        };
     }
 
     let mut toks = quote! {
@@ -130,6 +610,8 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     };
 
     if args.widget.children {
+        let nav_hint = nav_hint(&args.children);
+        let initially_hidden = initially_hidden(&args.children);
         toks.append_all(quote! {
             impl #impl_generics kas::WidgetChildren
                 for #name #ty_generics #where_clause
@@ -138,21 +620,117 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                     #count
                 }
                 fn get(&self, _index: usize) -> Option<&dyn kas::WidgetConfig> {
-                    match _index {
-                        #get_rules
-                        _ => None
-                    }
+                    #get_rules
                 }
                 fn get_mut(&mut self, _index: usize) -> Option<&mut dyn kas::WidgetConfig> {
-                    match _index {
-                        #get_mut_rules
-                        _ => None
+                    #get_mut_rules
+                }
+                #nav_hint
+                #initially_hidden
+            }
+        });
+    }
+
+    if args.derive.clone {
+        let mut clone_fields = quote! {};
+        for field in &args.fields {
+            let ident = &field.ident;
+            if field.skip {
+                clone_fields.append_all(quote! { #ident: Default::default(), });
+            } else {
+                clone_fields.append_all(quote! { #ident: self.#ident.clone(), });
+            }
+        }
+
+        let where_clause = bound_type_params(&ast.generics, quote! { Clone });
+        toks.append_all(quote! {
+            impl #impl_generics Clone
+                for #name #ty_generics #where_clause
+            {
+                fn clone(&self) -> Self {
+                    #name {
+                        #clone_fields
                     }
                 }
             }
         });
     }
 
+    if args.derive.debug {
+        let mut debug_fields = quote! {};
+        for field in args.fields.iter().filter(|field| !field.skip_debug) {
+            let ident = &field.ident;
+            let field_name = match ident {
+                syn::Member::Named(ident) => ident.to_string(),
+                syn::Member::Unnamed(index) => index.index.to_string(),
+            };
+            if field.skip {
+                debug_fields.append_all(quote! { .field(#field_name, &format_args!("<omitted>")) });
+            } else {
+                debug_fields.append_all(quote! { .field(#field_name, &self.#ident) });
+            }
+        }
+
+        let where_clause = bound_type_params(&ast.generics, quote! { std::fmt::Debug });
+        toks.append_all(quote! {
+            impl #impl_generics std::fmt::Debug
+                for #name #ty_generics #where_clause
+            {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.debug_struct(#widget_name)
+                        #debug_fields
+                        .finish()
+                }
+            }
+        });
+    }
+
+    if args.widget.accessors {
+        let mut accessors = quote! {};
+        for child in &args.children {
+            let ident = match &child.ident {
+                syn::Member::Named(ident) => ident,
+                syn::Member::Unnamed(_) => {
+                    return syn::Error::new_spanned(
+                        &child.ident,
+                        "#[widget(accessors)] requires named child fields",
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+            };
+            let ident_mut = format_ident!("{}_mut", ident);
+            let ty = &child.ty;
+            let doc = format!("Access the `{}` child widget", ident);
+            let doc_mut = format!("Mutably access the `{}` child widget", ident);
+            accessors.append_all(quote! {
+                #[doc = #doc]
+                #[inline]
+                pub fn #ident(&self) -> &#ty {
+                    &self.#ident
+                }
+                #[doc = #doc_mut]
+                #[inline]
+                pub fn #ident_mut(&mut self) -> &mut #ty {
+                    &mut self.#ident
+                }
+            });
+        }
+
+        toks.append_all(quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                #accessors
+            }
+        });
+    }
+
+    if args.widget.builder {
+        match builder(&ast, &args.fields) {
+            Ok(builder) => toks.append_all(builder),
+            Err(err) => return err.to_compile_error().into(),
+        }
+    }
+
     if let Some(config) = args.widget.config {
         let key_nav = config.key_nav;
         let cursor_icon = config.cursor_icon;
@@ -172,6 +750,15 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     }
 
     if let Some(ref layout) = args.layout {
+        if let Some(child) = args.children.iter().find(|c| c.kind == ChildKind::Vec) {
+            return syn::Error::new_spanned(
+                &child.ident,
+                "derived layout does not support Vec children; implement Layout manually",
+            )
+            .to_compile_error()
+            .into();
+        }
+
         match layout::data_type(&args.children, layout) {
             Ok(dt) => toks.append_all(quote! {
                 impl #impl_generics kas::LayoutData
@@ -199,7 +786,13 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     // Usually we only have one instance of this, but we support multiple; in
     // case no `#[handler]` attribute is present, we use a default value.
     if args.handler.is_empty() {
-        args.handler.push(Default::default());
+        // Where boxed children agree on a message type, we forward that
+        let mut boxed = args.children.iter().filter_map(|c| c.boxed_msg.as_ref());
+        let handler = match boxed.next() {
+            Some(msg) if boxed.all(|m| m == msg) => HandlerArgs::new(msg.clone(), true, true),
+            _ => Default::default(),
+        };
+        args.handler.push(handler);
     }
     for handler in args.handler.drain(..) {
         let subs = handler.substitutions;
@@ -209,21 +802,10 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             .into_pairs()
             .filter(|pair| match pair.value() {
                 &GenericParam::Type(TypeParam { ref ident, .. }) => !subs.contains_key(ident),
+                &GenericParam::Const(ConstParam { ref ident, .. }) => !subs.contains_key(ident),
                 _ => true,
             })
             .collect();
-        /* Problem: bounded_ty is too generic with no way to extract the Ident
-        if let Some(clause) = &mut generics.where_clause {
-            clause.predicates = clause.predicates
-                .into_pairs()
-                .filter(|pair| match pair.value() {
-                    &WherePredicate::Type(PredicateType { ref bounded_ty, .. }) =>
-                        subs.iter().all(|pair| &pair.0 != ident),
-                    _ => true,
-                })
-                .collect();
-        }
-        */
         if !handler.generics.params.is_empty() {
             if !generics.params.empty_or_trailing() {
                 generics.params.push_punct(Default::default());
@@ -240,6 +822,24 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                 generics.where_clause = Some(h_clauses);
             }
         }
+        if !subs.is_empty() {
+            // Remaining bounds and where clauses may still refer to
+            // substituted parameters (e.g. `W: Widget<Msg = M>`)
+            let params = &generics.params;
+            let params = subst_tokens(quote! { < #params > }, &subs);
+            let clause = generics.where_clause.as_ref().map(|clause| {
+                let clause = clause.to_token_stream();
+                subst_tokens(clause, &subs)
+            });
+            let result = syn::parse2::<syn::Generics>(params).and_then(|mut g| {
+                g.where_clause = clause.map(syn::parse2).transpose()?;
+                Ok(g)
+            });
+            match result {
+                Ok(g) => generics = g,
+                Err(err) => return err.to_compile_error().into(),
+            }
+        }
         // Note: we may have extra generic types used in where clauses, but we
         // don't want these in ty_generics.
         let (impl_generics, _ty, where_clause) = generics.split_for_impl();
@@ -262,14 +862,39 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                 let ident = &child.ident;
                 let handler = if let Some(ref h) = child.args.handler {
                     quote! { r.try_into().unwrap_or_else(|msg| self.#h(mgr, msg)) }
+                } else if let Some(ref map) = child.args.map {
+                    quote! { r.try_into().unwrap_or_else(|msg| Response::Msg((#map)(msg))) }
                 } else {
                     quote! { r.into() }
                 };
-                ev_to_num.append_all(quote! {
-                    if id <= self.#ident.id() {
-                        let r = self.#ident.send(mgr, id, event);
-                        #handler
-                    } else
+                ev_to_num.append_all(match child.kind {
+                    ChildKind::Field => quote! {
+                        if id <= self.#ident.id() {
+                            if self.#ident.is_hidden() {
+                                return Response::Unhandled(event);
+                            }
+                            let r = self.#ident.send(mgr, id, event);
+                            #handler
+                        } else
+                    },
+                    ChildKind::Vec => quote! {
+                        if let Some(index) = self.#ident.iter().position(|w| id <= w.id()) {
+                            if self.#ident[index].is_hidden() {
+                                return Response::Unhandled(event);
+                            }
+                            let r = self.#ident[index].send(mgr, id, event);
+                            #handler
+                        } else
+                    },
+                    ChildKind::Option => quote! {
+                        if let Some(child) = self.#ident.as_mut().filter(|w| id <= w.id()) {
+                            if child.is_hidden() {
+                                return Response::Unhandled(event);
+                            }
+                            let r = child.send(mgr, id, event);
+                            #handler
+                        } else
+                    },
                 });
             }
 
@@ -306,20 +931,213 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     toks.into()
 }
 
+// Derive Widget for an enum, each of whose variants wraps a single widget
+//
+// All traits are implemented by dispatching to the current variant's widget,
+// thus the enum is "transparent" (it shares its core data with the variant).
+fn derive_enum(ast: &syn::DeriveInput, data: &syn::DataEnum) -> syn::Result<TokenStream> {
+    let mut variants = Vec::with_capacity(data.variants.len());
+    for variant in &data.variants {
+        match &variant.fields {
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                variants.push((&variant.ident, &fields.unnamed[0].ty));
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    variant.ident.span(),
+                    "derive(Widget) on an enum requires each variant to wrap exactly one widget, e.g. `A(W)`",
+                ))
+            }
+        }
+    }
+    if variants.is_empty() {
+        return Err(syn::Error::new(
+            ast.ident.span(),
+            "derive(Widget) on an enum requires at least one variant",
+        ));
+    }
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let name = &ast.ident;
+    let msg_ty = variants[0].1;
+
+    // Generate `match self { Self::A(w) => body, ... }`
+    let dispatch = |body: TokenStream| -> TokenStream {
+        let mut arms = TokenStream::new();
+        for (ident, _) in &variants {
+            arms.append_all(quote! { Self::#ident(w) => #body, });
+        }
+        quote! { match self { #arms } }
+    };
+
+    let core_data = dispatch(quote! { kas::WidgetCore::core_data(w) });
+    let core_data_mut = dispatch(quote! { kas::WidgetCore::core_data_mut(w) });
+    let widget_name = dispatch(quote! { kas::WidgetCore::widget_name(w) });
+    let len = dispatch(quote! { kas::WidgetChildren::len(w) });
+    let get = dispatch(quote! { kas::WidgetChildren::get(w, index) });
+    let get_mut = dispatch(quote! { kas::WidgetChildren::get_mut(w, index) });
+    let nav_hint = dispatch(quote! { kas::WidgetChildren::nav_hint(w, index) });
+    let initially_hidden = dispatch(quote! { kas::WidgetChildren::initially_hidden(w, index) });
+    let configure = dispatch(quote! { kas::WidgetConfig::configure(w, mgr) });
+    let configure_recurse = dispatch(quote! { kas::WidgetConfig::configure_recurse(w, cmgr) });
+    let key_nav = dispatch(quote! { kas::WidgetConfig::key_nav(w) });
+    let cursor_icon = dispatch(quote! { kas::WidgetConfig::cursor_icon(w) });
+    let debug_label = dispatch(quote! { kas::WidgetConfig::debug_label(w) });
+    let size_rules = dispatch(quote! { kas::Layout::size_rules(w, size_handle, axis) });
+    let set_rect = dispatch(quote! { kas::Layout::set_rect(w, rect, align) });
+    let set_viewport = dispatch(quote! { kas::Layout::set_viewport(w, viewport) });
+    let translation = dispatch(quote! { kas::Layout::translation(w, child_index) });
+    let spatial_range = dispatch(quote! { kas::Layout::spatial_range(w) });
+    let find_id = dispatch(quote! { kas::Layout::find_id(w, coord) });
+    let draw = dispatch(quote! { kas::Layout::draw(w, draw_handle, mgr, disabled) });
+    let activation_via_press = dispatch(quote! { kas::event::Handler::activation_via_press(w) });
+    let handle = dispatch(quote! { kas::event::Handler::handle(w, mgr, event) });
+    let send = dispatch(quote! { kas::event::SendEvent::send(w, mgr, id, event) });
+
+    Ok(quote! {
+        impl #impl_generics kas::WidgetCore for #name #ty_generics #where_clause {
+            fn as_any(&self) -> &dyn std::any::Any { self }
+            fn as_any_mut(&mut self) -> &mut dyn std::any::Any { self }
+
+            fn core_data(&self) -> &kas::CoreData {
+                #core_data
+            }
+            fn core_data_mut(&mut self) -> &mut kas::CoreData {
+                #core_data_mut
+            }
+
+            fn widget_name(&self) -> &'static str {
+                #widget_name
+            }
+
+            fn as_widget(&self) -> &dyn kas::WidgetConfig { self }
+            fn as_widget_mut(&mut self) -> &mut dyn kas::WidgetConfig { self }
+        }
+
+        impl #impl_generics kas::WidgetChildren for #name #ty_generics #where_clause {
+            fn len(&self) -> usize {
+                #len
+            }
+            fn get(&self, index: usize) -> Option<&dyn kas::WidgetConfig> {
+                #get
+            }
+            fn get_mut(&mut self, index: usize) -> Option<&mut dyn kas::WidgetConfig> {
+                #get_mut
+            }
+            fn nav_hint(&self, index: usize) -> kas::NavHint {
+                #nav_hint
+            }
+            fn initially_hidden(&self, index: usize) -> bool {
+                #initially_hidden
+            }
+        }
+
+        impl #impl_generics kas::WidgetConfig for #name #ty_generics #where_clause {
+            fn configure(&mut self, mgr: &mut kas::event::Manager) {
+                #configure
+            }
+            fn configure_recurse<'a, 'b>(&mut self, cmgr: kas::event::ConfigureManager<'a, 'b>) {
+                #configure_recurse
+            }
+            fn key_nav(&self) -> bool {
+                #key_nav
+            }
+            fn cursor_icon(&self) -> kas::event::CursorIcon {
+                #cursor_icon
+            }
+            fn debug_label(&self) -> Option<&str> {
+                #debug_label
+            }
+        }
+
+        impl #impl_generics kas::Layout for #name #ty_generics #where_clause {
+            fn size_rules(
+                &mut self,
+                size_handle: &mut dyn kas::draw::SizeHandle,
+                axis: kas::layout::AxisInfo,
+            ) -> kas::layout::SizeRules {
+                #size_rules
+            }
+            fn set_rect(&mut self, rect: kas::geom::Rect, align: kas::AlignHints) {
+                #set_rect
+            }
+            fn set_viewport(&mut self, viewport: kas::geom::Rect) -> kas::TkAction {
+                #set_viewport
+            }
+            fn translation(&self, child_index: usize) -> kas::geom::Coord {
+                #translation
+            }
+            fn spatial_range(&self) -> (usize, usize) {
+                #spatial_range
+            }
+            fn find_id(&self, coord: kas::geom::Coord) -> Option<kas::WidgetId> {
+                #find_id
+            }
+            fn draw(
+                &self,
+                draw_handle: &mut dyn kas::draw::DrawHandle,
+                mgr: &kas::event::ManagerState,
+                disabled: bool,
+            ) {
+                #draw
+            }
+        }
+
+        impl #impl_generics kas::event::Handler for #name #ty_generics #where_clause {
+            type Msg = <#msg_ty as kas::event::Handler>::Msg;
+
+            fn activation_via_press(&self) -> bool {
+                #activation_via_press
+            }
+            fn handle(
+                &mut self,
+                mgr: &mut kas::event::Manager,
+                event: kas::event::Event,
+            ) -> kas::event::Response<Self::Msg> {
+                #handle
+            }
+        }
+
+        impl #impl_generics kas::event::SendEvent for #name #ty_generics #where_clause {
+            fn send(
+                &mut self,
+                mgr: &mut kas::event::Manager,
+                id: kas::WidgetId,
+                event: kas::event::Event,
+            ) -> kas::event::Response<Self::Msg> {
+                #send
+            }
+        }
+
+        impl #impl_generics kas::Widget for #name #ty_generics #where_clause {}
+    })
+}
+
+// Find the argument type of a closure like `|msg: T| ...`
+fn map_closure_arg_ty(map: &syn::Expr) -> Option<Type> {
+    match map {
+        syn::Expr::Closure(closure) if closure.inputs.len() == 1 => match &closure.inputs[0] {
+            syn::Pat::Type(pat) => Some((*pat.ty).clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 /// Macro to create a widget with anonymous type
 ///
 /// See the [`kas::macros`](../kas/macros/index.html) module documentation.
 #[proc_macro]
 pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let mut find_handler_ty_buf: Vec<(Ident, Type)> = vec![];
-    // find type of handler's message; return None on error
+    // find type of handler's message
     let mut find_handler_ty = |handler: &Ident,
                                impls: &Vec<(Option<TypePath>, Vec<syn::ImplItem>)>|
-     -> Option<Type> {
+     -> syn::Result<Type> {
         // check the buffer in case we did this already
         for (ident, ty) in &find_handler_ty_buf {
             if ident == handler {
-                return Some(ty.clone());
+                return Ok(ty.clone());
             }
         }
 
@@ -331,33 +1149,17 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                     syn::ImplItem::Method(syn::ImplItemMethod { sig, .. })
                         if sig.ident == *handler =>
                     {
-                        if let Some(_x) = x {
-                            #[cfg(nightly)]
-                            handler
-                                .span()
-                                .unwrap()
-                                .error("multiple methods with this name")
-                                .emit();
-                            #[cfg(nightly)]
-                            _x.0.span()
-                                .unwrap()
-                                .error("first method with this name")
-                                .emit();
-                            #[cfg(nightly)]
-                            sig.ident
-                                .span()
-                                .unwrap()
-                                .error("second method with this name")
-                                .emit();
-                            return None;
+                        if x.is_some() {
+                            return Err(syn::Error::new(
+                                sig.ident.span(),
+                                "multiple methods with this name",
+                            ));
                         }
                         if sig.inputs.len() != 3 {
-                            #[cfg(nightly)]
-                            sig.span()
-                                .unwrap()
-                                .error("handler functions must have signature: fn handler(&mut self, mgr: &mut Manager, msg: T)")
-                                .emit();
-                            return None;
+                            return Err(syn::Error::new(
+                                sig.span(),
+                                "handler functions must have signature: fn handler(&mut self, mgr: &mut Manager, msg: T)",
+                            ));
                         }
                         let arg = sig.inputs.last().unwrap();
                         let ty = match arg {
@@ -372,20 +1174,69 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         }
         if let Some(x) = x {
             find_handler_ty_buf.push((handler.clone(), x.1.clone()));
-            Some(x.1)
+            Ok(x.1)
         } else {
-            #[cfg(nightly)]
-            handler
-                .span()
-                .unwrap()
-                .error("no methods with this name found")
-                .emit();
-            None
+            Err(syn::Error::new(
+                handler.span(),
+                "no methods with this name found",
+            ))
         }
     };
 
     let mut args = parse_macro_input!(input as args::MakeWidget);
 
+    // Closure handlers (`=> |w, mgr, msg| ...`) become methods
+    let mut closure_methods = vec![];
+    for (index, field) in args.fields.iter_mut().enumerate() {
+        let closure = match field.handler_closure.take() {
+            Some(closure) => closure,
+            None => continue,
+        };
+        let mut pats = closure.inputs.iter().map(|pat| match pat {
+            syn::Pat::Type(pat) => (&*pat.pat, Some(&*pat.ty)),
+            pat => (pat, None),
+        });
+        let (w, mgr, (msg, msg_ty)) = (
+            pats.next().unwrap().0,
+            pats.next().unwrap().0,
+            pats.next().unwrap(),
+        );
+        let msg_ty: Type = match (msg_ty, &field.ty) {
+            (Some(ty), _) => ty.clone(),
+            (None, ChildType::Fixed(ty)) => parse_quote! { <#ty as kas::event::Handler>::Msg },
+            (None, ChildType::Generic(Some(ty), _)) => ty.clone(),
+            (None, _) => {
+                return syn::Error::new(
+                    msg.span(),
+                    "make_widget: handler closure on a child of unspecified type requires a typed message argument: |w, mgr, msg: T| ...",
+                )
+                .to_compile_error()
+                .into();
+            }
+        };
+        let body = &closure.body;
+
+        let method = format_ident!("mw_handler_{}", index);
+        closure_methods.push(parse_quote! {
+            fn #method(
+                &mut self,
+                mgr: &mut kas::event::Manager,
+                msg: #msg_ty,
+            ) -> kas::event::Response<<Self as kas::event::Handler>::Msg> {
+                let #w = self;
+                let #mgr = mgr;
+                let #msg = msg;
+                #body
+            }
+        });
+        if let Some(attr) = field.widget_attr.as_mut() {
+            attr.args.handler = Some(method);
+        }
+    }
+    if !closure_methods.is_empty() {
+        args.impls.push((None, closure_methods));
+    }
+
     // Used to make fresh identifiers for generic types
     let mut name_buf = String::with_capacity(32);
 
@@ -439,12 +1290,12 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         } else {
             // We could default to msg=VoidMsg here. If error messages weren't
             // so terrible this might even be a good idea!
-            #[cfg(nightly)]
-            args.struct_span
-                .unwrap()
-                .error("make_widget: cannot discover msg type from #[handler] attr or Handler impl")
-                .emit();
-            return proc_macro::TokenStream::new();
+            return syn::Error::new(
+                args.struct_span,
+                "make_widget: cannot discover msg type from #[handler] attr or Handler impl",
+            )
+            .to_compile_error()
+            .into();
         }
     };
     let msg = &handler.msg;
@@ -456,6 +1307,8 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     };
 
     let extra_attrs = args.extra_attrs;
+    // Span of a field whose type requires generics (if any)
+    let mut generic_field = None;
 
     for (index, field) in args.fields.drain(..).enumerate() {
         let attr = field.widget_attr;
@@ -474,10 +1327,12 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         let ty: Type = match field.ty {
             ChildType::Fixed(ty) => ty.clone(),
             ChildType::InternGeneric(gen_args, ty) => {
+                generic_field.get_or_insert(ident.span());
                 args.generics.params.extend(gen_args);
                 ty.clone()
             }
             ChildType::Generic(gen_msg, gen_bound) => {
+                generic_field.get_or_insert(ident.span());
                 name_buf.clear();
                 name_buf.write_fmt(format_args!("MWAnon{}", index)).unwrap();
                 let ty = Ident::new(&name_buf, Span::call_site());
@@ -487,14 +1342,26 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                         handler_clauses.push(parse_quote! { #ty: kas::Widget<Msg = #tyr> });
                     } else {
                         // No typing. If a handler is specified, then the child must implement
-                        // Handler<Msg = X> where the handler takes type X; otherwise
-                        // we use `msg.into()` and this conversion must be supported.
+                        // Handler<Msg = X> where the handler takes type X; similarly
+                        // if a map closure is specified with argument type X;
+                        // otherwise we use `msg.into()` and this conversion must be supported.
                         if let Some(ref handler) = wattr.args.handler {
-                            if let Some(ty_bound) = find_handler_ty(handler, &args.impls) {
+                            match find_handler_ty(handler, &args.impls) {
+                                Ok(ty_bound) => handler_clauses
+                                    .push(parse_quote! { #ty: kas::Widget<Msg = #ty_bound> }),
+                                Err(err) => return err.to_compile_error().into(),
+                            }
+                        } else if let Some(ref map) = wattr.args.map {
+                            if let Some(ty_bound) = map_closure_arg_ty(map) {
                                 handler_clauses
                                     .push(parse_quote! { #ty: kas::Widget<Msg = #ty_bound> });
                             } else {
-                                return quote! {}.into(); // exit after emitting error
+                                return syn::Error::new(
+                                    map.span(),
+                                    "map on a child of unspecified type requires a closure with typed argument: |msg: T| ...",
+                                )
+                                .to_compile_error()
+                                .into();
                             }
                         } else {
                             name_buf.push_str("R");
@@ -526,6 +1393,12 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         };
 
         let value = &field.value;
+        let value = match &attr {
+            Some(attr) if attr.args.hidden => {
+                quote! { kas::WidgetCore::with_visible(#value, false) }
+            }
+            _ => quote! { #value },
+        };
 
         field_toks.append_all(quote! { #attr #ident: #ty, });
         field_val_toks.append_all(quote! { #ident: #value, });
@@ -540,8 +1413,37 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         });
     }
 
+    let is_named = args.name.is_some();
+    let name = match args.name {
+        Some(name) => {
+            if let Some(span) = generic_field {
+                return syn::Error::new(
+                    span,
+                    "make_widget: fields of a named struct must have explicit, non-generic types",
+                )
+                .to_compile_error()
+                .into();
+            }
+            name
+        }
+        None => parse_quote! { AnonWidget },
+    };
+
     let (impl_generics, ty_generics, where_clause) = args.generics.split_for_impl();
 
+    // Derive Clone and Debug unless implemented explicitly
+    let mut derives = quote! {};
+    for derive in &[quote! { Clone }, quote! { Debug }] {
+        let derive_name = derive.to_string();
+        let is_impl = args.impls.iter().any(|(target, _)| match target {
+            Some(path) => path.path.segments.last().unwrap().ident == derive_name,
+            None => false,
+        });
+        if !is_impl {
+            derives.append_all(quote! { #derive, });
+        }
+    }
+
     let mut impls = quote! {};
 
     for impl_block in args.impls {
@@ -555,31 +1457,51 @@ pub fn make_widget(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             quote! {}
         };
         impls.append_all(quote! {
-            impl #impl_generics #target AnonWidget #ty_generics #where_clause {
+            impl #impl_generics #target #name #ty_generics #where_clause {
                 #contents
             }
         });
     }
 
-    // TODO: we should probably not rely on recursive macro expansion here!
-    // (I.e. use direct code generation for Widget derivation, instead of derive.)
-    let toks = (quote! { {
+    let vis = args.vis;
+    let def = quote! {
         #handler
         #extra_attrs
-        #[derive(Clone, Debug, kas::macros::Widget)]
-        struct AnonWidget #impl_generics #where_clause {
+        #[derive(#derives kas::macros::Widget)]
+        #vis struct #name #impl_generics #where_clause {
             #field_toks
         }
 
         #impls
+    };
+
+    // TODO: we should probably not rely on recursive macro expansion here!
+    // (I.e. use direct code generation for Widget derivation, instead of derive.)
+    let toks = if !is_named {
+        quote! { {
+            #def
 
-        AnonWidget {
-            #field_val_toks
+            AnonWidget {
+                #field_val_toks
+            }
+        } }
+    } else {
+        // A named struct is defined as an item, with a constructor
+        quote! {
+            #def
+
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// Construct, with field values as given in the definition
+                #vis fn new() -> Self {
+                    #name {
+                        #field_val_toks
+                    }
+                }
+            }
         }
-    } })
-    .into();
+    };
 
-    toks
+    toks.into()
 }
 
 /// Macro to derive `From<VoidMsg>`
@@ -0,0 +1,313 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Animation support
+
+use instant::Instant;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::ThemeColours;
+use kas::anim::{Easing, Timeline};
+use kas::draw::Colour;
+use kas::geom::{Quad, Rect};
+
+static REDUCE_MOTION: AtomicBool = AtomicBool::new(false);
+
+/// Duration of press feedback animations
+const PRESS_DURATION: Duration = Duration::from_millis(150);
+
+/// Duration of hover highlight fades
+const HOVER_DURATION: Duration = Duration::from_millis(120);
+
+/// Duration of toggle (check mark) transitions
+const TOGGLE_DURATION: Duration = Duration::from_millis(150);
+
+/// Default duration of colour-scheme transitions
+const COLOUR_DURATION: Duration = Duration::from_millis(250);
+
+/// Disable (or re-enable) all theme animations
+///
+/// This is a global setting, intended to respect a "reduce motion"
+/// accessibility preference. Animations are enabled by default.
+pub fn set_reduce_motion(reduce: bool) {
+    REDUCE_MOTION.store(reduce, Ordering::Relaxed);
+}
+
+/// Get whether theme animations are disabled
+///
+/// See [`set_reduce_motion`].
+pub fn reduce_motion() -> bool {
+    REDUCE_MOTION.load(Ordering::Relaxed)
+}
+
+/// Per-window animation state
+///
+/// Animations are driven by the clock: while any animation is incomplete,
+/// [`AnimState::take_redraw`] returns true and the toolkit should draw another
+/// frame.
+#[derive(Clone, Debug, Default)]
+pub struct AnimState {
+    /// Start of press animations: (element, start, seen this frame)
+    press: Vec<(Rect, Instant, bool)>,
+    /// Hover highlights: (element, timeline, seen this frame)
+    hover: Vec<(Rect, Timeline, bool)>,
+    /// Toggle positions: (element, timeline, seen this frame)
+    toggle: Vec<(Rect, Timeline, bool)>,
+    /// Keys of cached regions whose content is animated
+    animated_caches: Vec<u64>,
+    redraw: bool,
+}
+
+impl AnimState {
+    /// Start a new frame
+    ///
+    /// Animation states of elements not drawn since the last call are dropped.
+    pub fn new_frame(&mut self) {
+        self.press.retain(|item| item.2);
+        for item in &mut self.press {
+            item.2 = false;
+        }
+        self.hover.retain(|item| item.2);
+        for item in &mut self.hover {
+            item.2 = false;
+        }
+        self.toggle.retain(|item| item.2);
+        for item in &mut self.toggle {
+            item.2 = false;
+        }
+    }
+
+    /// Get progress of the press animation for the element at `rect`
+    ///
+    /// Returns a value from 0 (just pressed) to 1 (complete). Returns 1 when
+    /// the element is not depressed or when animations are disabled.
+    pub fn press(&mut self, rect: Rect, depress: bool) -> f32 {
+        if !depress || reduce_motion() {
+            self.press.retain(|item| item.0 != rect);
+            return 1.0;
+        }
+
+        let now = Instant::now();
+        let start = match self.press.iter_mut().find(|item| item.0 == rect) {
+            Some(item) => {
+                item.2 = true;
+                item.1
+            }
+            None => {
+                self.press.push((rect, now, true));
+                now
+            }
+        };
+
+        let p = (now - start).as_secs_f32() / PRESS_DURATION.as_secs_f32();
+        if p < 1.0 {
+            self.redraw = true;
+            p
+        } else {
+            1.0
+        }
+    }
+
+    /// Get progress of the hover highlight for the element at `rect`
+    ///
+    /// Returns a value from 0 (not highlighted) to 1 (fully highlighted),
+    /// fading in and out as `hover` changes. Returns 0 or 1 immediately when
+    /// animations are disabled.
+    pub fn hover(&mut self, rect: Rect, hover: bool) -> f32 {
+        if reduce_motion() {
+            self.hover.retain(|item| item.0 != rect);
+            return if hover { 1.0 } else { 0.0 };
+        }
+
+        let now = Instant::now();
+        let index = match self.hover.iter().position(|item| item.0 == rect) {
+            Some(index) => index,
+            None if !hover => return 0.0,
+            None => {
+                let timeline = Timeline::new(HOVER_DURATION, Easing::EaseOut);
+                self.hover.push((rect, timeline, true));
+                self.hover.len() - 1
+            }
+        };
+
+        let item = &mut self.hover[index];
+        item.1.set_target(hover, now);
+        item.2 = true;
+        let value = item.1.value(now);
+        if item.1.is_running(now) {
+            self.redraw = true;
+        } else if !hover {
+            self.hover.remove(index);
+        }
+        value
+    }
+
+    /// Get position of the toggle (e.g. check mark) of the element at `rect`
+    ///
+    /// Returns a value from 0 (off) to 1 (on), moving as `on` changes. An
+    /// element not drawn in the previous frame starts at rest. Returns 0 or 1
+    /// immediately when animations are disabled.
+    pub fn toggle(&mut self, rect: Rect, on: bool) -> f32 {
+        if reduce_motion() {
+            self.toggle.retain(|item| item.0 != rect);
+            return if on { 1.0 } else { 0.0 };
+        }
+
+        let now = Instant::now();
+        let index = match self.toggle.iter().position(|item| item.0 == rect) {
+            Some(index) => index,
+            None => {
+                let mut timeline = Timeline::new(TOGGLE_DURATION, Easing::EaseInOut);
+                timeline.set_target(on, now);
+                timeline.finish();
+                self.toggle.push((rect, timeline, true));
+                self.toggle.len() - 1
+            }
+        };
+
+        let item = &mut self.toggle[index];
+        item.1.set_target(on, now);
+        item.2 = true;
+        if item.1.is_running(now) {
+            self.redraw = true;
+        }
+        item.1.value(now)
+    }
+
+    /// Whether content cached under `key` may be re-used
+    ///
+    /// Returns false if the content was animated when last drawn, since it
+    /// must then be redrawn each frame until the animation completes.
+    pub fn cache_valid(&self, key: u64) -> bool {
+        !self.animated_caches.contains(&key)
+    }
+
+    /// Start drawing the content of a cached region
+    ///
+    /// Returns a token to be passed to [`AnimState::end_cache`].
+    pub fn begin_cache(&mut self) -> bool {
+        std::mem::replace(&mut self.redraw, false)
+    }
+
+    /// Finish drawing the content of cached region `key`
+    ///
+    /// Records whether any animation was drawn since [`AnimState::begin_cache`].
+    pub fn end_cache(&mut self, key: u64, token: bool) {
+        self.animated_caches.retain(|k| *k != key);
+        if self.redraw {
+            self.animated_caches.push(key);
+        }
+        self.redraw |= token;
+    }
+
+    /// Returns true if an animation requires another frame, and resets
+    pub fn take_redraw(&mut self) -> bool {
+        std::mem::replace(&mut self.redraw, false)
+    }
+}
+
+/// Double-buffered theme colours, supporting cross-fades
+///
+/// Holds the *target* colours (as last set) and the *current* colours used
+/// for drawing. When new colours are set, the current colours fade from their
+/// previous values to the new target over the transition duration; during
+/// this time [`ColourFade::update`] returns true and the toolkit should draw
+/// further frames. Transitions are instant when the duration is zero or when
+/// [`reduce_motion`] is set.
+#[derive(Clone, Debug)]
+pub struct ColourFade {
+    current: ThemeColours,
+    target: ThemeColours,
+    /// Colours and time at the start of a transition
+    from: Option<(ThemeColours, Instant)>,
+    duration: Duration,
+}
+
+impl ColourFade {
+    /// Construct, with the given colours and the default duration (250ms)
+    pub fn new(cols: ThemeColours) -> Self {
+        ColourFade {
+            current: cols.clone(),
+            target: cols,
+            from: None,
+            duration: COLOUR_DURATION,
+        }
+    }
+
+    /// Set the transition duration
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
+    /// Colours to draw with
+    #[inline]
+    pub fn current(&self) -> &ThemeColours {
+        &self.current
+    }
+
+    /// Target colours
+    #[inline]
+    pub fn target(&self) -> &ThemeColours {
+        &self.target
+    }
+
+    /// Set new target colours, starting a transition
+    pub fn set(&mut self, cols: ThemeColours) {
+        if self.duration == Duration::from_secs(0) || reduce_motion() {
+            self.current = cols.clone();
+            self.from = None;
+        } else {
+            // A transition in progress continues from the current blend
+            self.from = Some((self.current.clone(), Instant::now()));
+        }
+        self.target = cols;
+    }
+
+    /// Set new colours immediately, without transition
+    pub fn reset(&mut self, cols: ThemeColours) {
+        self.current = cols.clone();
+        self.target = cols;
+        self.from = None;
+    }
+
+    /// Advance the transition
+    ///
+    /// Returns true if the transition is incomplete (thus another frame should
+    /// be drawn).
+    pub fn update(&mut self) -> bool {
+        let (from, start) = match self.from.as_ref() {
+            Some(from) => from,
+            None => return false,
+        };
+        let t = start.elapsed().as_secs_f32() / self.duration.as_secs_f32();
+        if t < 1.0 && !reduce_motion() {
+            self.current = from.blend(&self.target, t);
+            true
+        } else {
+            self.current = self.target.clone();
+            self.from = None;
+            false
+        }
+    }
+}
+
+/// Blend colours `a` and `b`, where `t = 0` yields `a` and `t = 1` yields `b`
+pub(crate) fn blend(a: Colour, b: Colour, t: f32) -> Colour {
+    let s = 1.0 - t;
+    Colour {
+        r: a.r * s + b.r * t,
+        g: a.g * s + b.g * t,
+        b: a.b * s + b.b * t,
+        a: a.a * s + b.a * t,
+    }
+}
+
+/// Shrink `quad` towards its centre, where `s = 1` yields `quad`
+///
+/// Square quads are scaled by factor `s`.
+pub(crate) fn scale(quad: Quad, s: f32) -> Quad {
+    quad.shrink(0.5 * (1.0 - s) * quad.size().min_comp())
+}
@@ -7,10 +7,30 @@
 
 use log::warn;
 
-use kas::draw::{Colour, InputState, TextClass};
+use crate::anim;
+use kas::draw::{Colour, ColourScheme, InputState, TextClass};
+
+/// Minimum contrast ratio for text, as recommended by WCAG 2.0 (level AA)
+pub const MIN_TEXT_CONTRAST: f32 = 4.5;
+
+/// A text and background colour pair with insufficient contrast
+///
+/// See [`ThemeColours::contrast_issues`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContrastIssue {
+    /// Description of the text and background pair (usually a widget class)
+    pub pair: &'static str,
+    /// Contrast ratio (see [`Colour::contrast_ratio`])
+    pub ratio: f32,
+}
 
 /// Provides standard theme colours
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_::Serialize, serde_::Deserialize),
+    serde(crate = "serde_")
+)]
 pub struct ThemeColours {
     /// Background colour
     pub background: Colour,
@@ -50,90 +70,72 @@ impl ThemeColours {
     /// Open the given scheme, if found
     ///
     /// TODO: the intention is that this method can read and cache data from
-    /// external resources. For now, we simply hard-code a few instances.
+    /// external resources. For now, we use the built-in schemes of
+    /// [`ColourScheme::open`].
+    ///
+    /// In debug builds, insufficient text contrast is reported via
+    /// [`ThemeColours::log_contrast_issues`].
     pub fn open(scheme: &str) -> Option<Self> {
-        Some(match scheme {
-            "default" | "white" => Self::new(),
-            "grey" => Self::grey(),
-            "light" => Self::light(),
-            "dark" => Self::dark(),
-            other => {
-                warn!("ThemeColours::open: scheme \"{}\" not found", other);
-                return None;
+        match ColourScheme::open(scheme) {
+            Some(scheme) => Some(Self::from_scheme(&scheme)),
+            None => {
+                warn!("ThemeColours::open: scheme \"{}\" not found", scheme);
+                None
             }
-        })
+        }
+    }
+
+    /// Derive from a [`ColourScheme`]
+    ///
+    /// In debug builds, insufficient text contrast is reported via
+    /// [`ThemeColours::log_contrast_issues`].
+    pub fn from_scheme(scheme: &ColourScheme) -> Self {
+        let cols = Self::from(scheme);
+        #[cfg(debug_assertions)]
+        cols.log_contrast_issues();
+        cols
     }
 
     /// Default theme: white with blue activable items
     pub fn new() -> Self {
-        ThemeColours {
-            background: Colour::grey(1.0),
-            frame: Colour::grey(0.7),
-            bg: Colour::grey(1.0),
-            bg_disabled: Colour::grey(0.85),
-            bg_error: Colour::new(1.0, 0.5, 0.5),
-            text: Colour::grey(0.0),
-            text_sel: Colour::grey(1.0),
-            text_sel_bg: Colour::new(0.15, 0.525, 0.75),
-            label_text: Colour::grey(0.0),
-            button_text: Colour::grey(1.0),
-            nav_focus: Colour::new(1.0, 0.7, 0.5),
-            button: Colour::new(0.2, 0.7, 1.0),
-            button_disabled: Colour::grey(0.5),
-            button_highlighted: Colour::new(0.25, 0.8, 1.0),
-            button_depressed: Colour::new(0.15, 0.525, 0.75),
-            checkbox: Colour::new(0.2, 0.7, 1.0),
-        }
+        Self::from(&ColourScheme::new())
     }
 
     /// Grey with blue activable items
     pub fn grey() -> Self {
-        let mut col = ThemeColours::new();
-        col.background = Colour::grey(0.8);
-        col
+        Self::from(&ColourScheme::grey())
     }
 
     /// Light scheme
     pub fn light() -> Self {
-        ThemeColours {
-            background: Colour::grey(0.9),
-            frame: Colour::new(0.8, 0.8, 0.9),
-            bg: Colour::grey(1.0),
-            bg_disabled: Colour::grey(0.85),
-            bg_error: Colour::new(1.0, 0.5, 0.5),
-            text: Colour::grey(0.0),
-            text_sel: Colour::grey(0.0),
-            text_sel_bg: Colour::new(0.8, 0.72, 0.24),
-            label_text: Colour::grey(0.0),
-            button_text: Colour::grey(0.0),
-            nav_focus: Colour::new(1.0, 0.7, 0.5),
-            button: Colour::new(1.0, 0.9, 0.3),
-            button_disabled: Colour::grey(0.6),
-            button_highlighted: Colour::new(1.0, 0.95, 0.6),
-            button_depressed: Colour::new(0.8, 0.72, 0.24),
-            checkbox: Colour::grey(0.4),
-        }
+        Self::from(&ColourScheme::light())
     }
 
     /// Dark scheme
     pub fn dark() -> Self {
+        Self::from(&ColourScheme::dark())
+    }
+
+    /// Blend with `other`, where `t = 0` yields `self` and `t = 1` yields `other`
+    pub fn blend(&self, other: &Self, t: f32) -> Self {
+        let blend = |a, b| anim::blend(a, b, t);
         ThemeColours {
-            background: Colour::grey(0.2),
-            frame: Colour::grey(0.4),
-            bg: Colour::grey(0.1),
-            bg_disabled: Colour::grey(0.3),
-            bg_error: Colour::new(1.0, 0.5, 0.5),
-            text: Colour::grey(1.0),
-            text_sel: Colour::grey(1.0),
-            text_sel_bg: Colour::new(0.6, 0.3, 0.1),
-            label_text: Colour::grey(1.0),
-            button_text: Colour::grey(1.0),
-            nav_focus: Colour::new(1.0, 0.7, 0.5),
-            button: Colour::new(0.5, 0.1, 0.1),
-            button_disabled: Colour::grey(0.7),
-            button_highlighted: Colour::new(0.6, 0.3, 0.1),
-            button_depressed: Colour::new(0.3, 0.1, 0.1),
-            checkbox: Colour::new(0.5, 0.1, 0.1),
+            background: blend(self.background, other.background),
+            frame: blend(self.frame, other.frame),
+            bg: blend(self.bg, other.bg),
+            bg_disabled: blend(self.bg_disabled, other.bg_disabled),
+            bg_error: blend(self.bg_error, other.bg_error),
+            text: blend(self.text, other.text),
+            text_sel: blend(self.text_sel, other.text_sel),
+            text_sel_bg: blend(self.text_sel_bg, other.text_sel_bg),
+            label_text: blend(self.label_text, other.label_text),
+            button_text: blend(self.button_text, other.button_text),
+            nav_focus: blend(self.nav_focus, other.nav_focus),
+            button: blend(self.button, other.button),
+            button_disabled: blend(self.button_disabled, other.button_disabled),
+            button_highlighted: blend(self.button_highlighted, other.button_highlighted),
+            button_depressed: blend(self.button_depressed, other.button_depressed),
+            checkbox: blend(self.checkbox, other.checkbox),
         }
     }
 
@@ -200,6 +202,24 @@ impl ThemeColours {
         }
     }
 
+    /// Get colour of a selection highlight, depending on state
+    pub fn selection_state(&self, state: InputState) -> Colour {
+        if state.disabled {
+            self.button_disabled
+        } else {
+            self.text_sel_bg
+        }
+    }
+
+    /// Get background colour of a badge, depending on state
+    pub fn badge_state(&self, state: InputState) -> Colour {
+        if state.disabled {
+            self.button_disabled
+        } else {
+            self.nav_focus
+        }
+    }
+
     /// Get colour of a scrollbar, depending on state
     #[inline]
     pub fn scrollbar_state(&self, state: InputState) -> Colour {
@@ -214,4 +234,73 @@ impl ThemeColours {
             TextClass::Edit | TextClass::EditMulti => self.text,
         }
     }
+
+    /// Find text and background colour pairs with insufficient contrast
+    ///
+    /// Text colour of each widget class is checked against each background
+    /// it may be drawn on. Pairs with a contrast ratio less than `min_ratio`
+    /// are returned (see also [`MIN_TEXT_CONTRAST`]).
+    pub fn contrast_issues(&self, min_ratio: f32) -> Vec<ContrastIssue> {
+        let pairs = [
+            ("Label", self.label_text, self.background),
+            ("Label on frame", self.label_text, self.frame),
+            ("EditBox", self.text, self.bg),
+            ("EditBox disabled", self.text, self.bg_disabled),
+            ("EditBox error", self.text, self.bg_error),
+            ("Selected text", self.text_sel, self.text_sel_bg),
+            ("Button", self.button_text, self.button),
+            ("Button disabled", self.button_text, self.button_disabled),
+            ("Button hover", self.button_text, self.button_highlighted),
+            ("Button depressed", self.button_text, self.button_depressed),
+            ("Menu hover", self.label_text, self.button_highlighted),
+            ("Menu depressed", self.label_text, self.button_depressed),
+        ];
+        pairs
+            .iter()
+            .filter_map(|(pair, text, bg)| {
+                let ratio = text.contrast_ratio(*bg);
+                if ratio < min_ratio {
+                    Some(ContrastIssue { pair, ratio })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Log text and background colour pairs with insufficient contrast
+    ///
+    /// This reports [`ThemeColours::contrast_issues`] (using
+    /// [`MIN_TEXT_CONTRAST`]) as warnings.
+    pub fn log_contrast_issues(&self) {
+        for issue in self.contrast_issues(MIN_TEXT_CONTRAST) {
+            warn!(
+                "ThemeColours: low contrast ratio {:.2} for {} (minimum: {})",
+                issue.ratio, issue.pair, MIN_TEXT_CONTRAST
+            );
+        }
+    }
+}
+
+impl From<&ColourScheme> for ThemeColours {
+    fn from(scheme: &ColourScheme) -> Self {
+        ThemeColours {
+            background: scheme.background,
+            frame: scheme.frame,
+            bg: scheme.edit_bg,
+            bg_disabled: scheme.edit_disabled,
+            bg_error: scheme.error,
+            text: scheme.text,
+            text_sel: scheme.selection_text,
+            text_sel_bg: scheme.selection,
+            label_text: scheme.text,
+            button_text: scheme.accent_text,
+            nav_focus: scheme.nav_focus,
+            button: scheme.accent,
+            button_disabled: scheme.disabled,
+            button_highlighted: scheme.accent_hover,
+            button_depressed: scheme.accent_depressed,
+            checkbox: scheme.check_mark,
+        }
+    }
 }
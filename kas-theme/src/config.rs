@@ -0,0 +1,121 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Theme configuration
+
+#[cfg(feature = "serde")]
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+use crate::{DimensionsParams, FontConfig, ThemeColours};
+
+/// Error loading or saving a [`ThemeConfig`]
+///
+/// **Feature gated**: this is only available with feature `serde`.
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum ConfigError {
+    /// Error reading or writing the file
+    Io(std::io::Error),
+    /// Error (de)serialising JSON
+    Json(serde_json::Error),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "error accessing theme config: {}", e),
+            ConfigError::Json(e) => write!(f, "error in theme config: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(e) => Some(e),
+            ConfigError::Json(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for ConfigError {
+    fn from(e: serde_json::Error) -> Self {
+        ConfigError::Json(e)
+    }
+}
+
+/// Theme parameters
+///
+/// This collects the parameters of [`crate::FlatTheme`] and
+/// [`crate::ShadedTheme`] which may be adjusted at run-time via
+/// [`crate::Theme::apply_config`]. Fields set to `None` (except
+/// `dimensions`) keep the theme's current value.
+///
+/// With feature `serde`, the configuration may be (de)serialised, and loaded
+/// from or saved to a JSON file. Missing fields are `None`:
+///
+/// ```
+/// # #[cfg(feature = "serde")] {
+/// use kas_theme::ThemeConfig;
+///
+/// let config = ThemeConfig::from_json(r#"{ "font_size": 14.0 }"#).unwrap();
+/// assert_eq!(config.font_size, Some(14.0));
+/// assert!(config.fonts.is_none());
+/// assert!(config.colours.is_none());
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_::Serialize, serde_::Deserialize),
+    serde(crate = "serde_", default)
+)]
+pub struct ThemeConfig {
+    /// Font size, in points (if `None`, the current size is kept)
+    pub font_size: Option<f32>,
+    /// Font families (if `None`, the current fonts are kept)
+    pub fonts: Option<FontConfig>,
+    /// Colours (if `None`, the current scheme is kept)
+    pub colours: Option<ThemeColours>,
+    /// Dimensions (if `None`, the theme's defaults are used)
+    pub dimensions: Option<DimensionsParams>,
+}
+
+#[cfg(feature = "serde")]
+impl ThemeConfig {
+    /// Parse from JSON
+    pub fn from_json(source: &str) -> Result<Self, ConfigError> {
+        Ok(serde_json::from_str(source)?)
+    }
+
+    /// Serialise to (pretty-printed) JSON
+    pub fn to_json(&self) -> Result<String, ConfigError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Load from a JSON file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let source = std::fs::read_to_string(path)?;
+        ThemeConfig::from_json(&source)
+    }
+
+    /// Save to a JSON file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+}
@@ -12,23 +12,34 @@ use std::f32;
 
 use kas::draw::{self, TextClass};
 use kas::geom::{Size, Vec2};
-use kas::layout::{AxisInfo, Margins, SizeRules, StretchPolicy};
+use kas::layout::{AxisInfo, Length, Margins, SizeRules, StretchPolicy};
 use kas::text::{FontId, PreparedText};
 
+use crate::{AnimState, Fonts};
+
 /// Parameterisation of [`Dimensions`]
 ///
 /// All dimensions are multiplied by the DPI factor, then rounded to the
-/// nearest integer. Example: `(2.0 * 1.25).round() = 3.0`.
-#[derive(Clone, Debug)]
+/// nearest integer (see [`kas::geom::scale_px`]). Example:
+/// `(2.0 * 1.25).round() = 3.0`. Non-zero dimensions are at least 1.
+///
+/// Margins and frame sizes are [`Length`]s, thus may be specified relative to
+/// the font size.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_::Serialize, serde_::Deserialize),
+    serde(crate = "serde_")
+)]
 pub struct DimensionsParams {
     /// Space between elements
-    pub outer_margin: f32,
+    pub outer_margin: Length,
     /// Margin inside a frame before contents
-    pub inner_margin: f32,
+    pub inner_margin: Length,
     /// Frame size
-    pub frame_size: f32,
+    pub frame_size: Length,
     /// Button frame size (non-flat outer region)
-    pub button_frame: f32,
+    pub button_frame: Length,
     /// Scrollbar minimum handle size
     pub scrollbar_size: Vec2,
     /// Slider minimum handle size
@@ -42,7 +53,9 @@ pub struct Dimensions {
     pub dpp: f32,
     pub font_id: FontId,
     pub pt_size: f32,
+    pub dpem: f32,
     pub font_marker_width: f32,
+    pub focus_ring: f32,
     pub line_height: u32,
     pub min_line_length: u32,
     pub ideal_line_length: u32,
@@ -51,33 +64,39 @@ pub struct Dimensions {
     pub frame: u32,
     pub button_frame: u32,
     pub checkbox: u32,
+    pub icon: u32,
     pub scrollbar: Size,
     pub slider: Size,
 }
 
 impl Dimensions {
-    pub fn new(params: DimensionsParams, font_id: FontId, pt_size: f32, scale_factor: f32) -> Self {
+    pub fn new(params: DimensionsParams, fonts: &Fonts, pt_size: f32, scale_factor: f32) -> Self {
+        let font_id = fonts.default_font();
         let dpp = scale_factor * (96.0 / 72.0);
         let dpem = dpp * pt_size;
         let line_height = kas::text::fonts().get(font_id).line_height(dpem).ceil() as u32;
 
-        let outer_margin = (params.outer_margin * scale_factor).round() as u32;
-        let inner_margin = (params.inner_margin * scale_factor).round() as u32;
-        let frame = (params.frame_size * scale_factor).round() as u32;
+        let resolve = |len: Length| len.resolve(scale_factor, dpem, line_height);
+        let outer_margin = resolve(params.outer_margin);
+        let inner_margin = resolve(params.inner_margin);
+        let frame = resolve(params.frame_size);
         Dimensions {
             scale_factor,
             dpp,
             font_id,
             pt_size,
+            dpem,
             font_marker_width: (1.6 * scale_factor).round().max(1.0),
+            focus_ring: (frame as f32 / 2.0).max(1.0),
             line_height,
             min_line_length: (8.0 * dpem).round() as u32,
             ideal_line_length: (24.0 * dpem).round() as u32,
             outer_margin,
             inner_margin,
             frame,
-            button_frame: (params.button_frame * scale_factor).round() as u32,
+            button_frame: resolve(params.button_frame),
             checkbox: (9.0 * dpp).round() as u32 + 2 * (inner_margin + frame),
+            icon: line_height,
             scrollbar: Size::from(params.scrollbar_size * scale_factor),
             slider: Size::from(params.slider_size * scale_factor),
         }
@@ -87,12 +106,14 @@ impl Dimensions {
 /// A convenient implementation of [`crate::Window`]
 pub struct DimensionsWindow {
     pub dims: Dimensions,
+    pub anim: AnimState,
 }
 
 impl DimensionsWindow {
-    pub fn new(dims: DimensionsParams, font_id: FontId, font_size: f32, scale_factor: f32) -> Self {
+    pub fn new(dims: DimensionsParams, fonts: &Fonts, font_size: f32, scale_factor: f32) -> Self {
         DimensionsWindow {
-            dims: Dimensions::new(dims, font_id, font_size, scale_factor),
+            dims: Dimensions::new(dims, fonts, font_size, scale_factor),
+            anim: AnimState::default(),
         }
     }
 }
@@ -114,6 +135,10 @@ impl crate::Window for DimensionsWindow {
         SizeHandle::new(&self.dims)
     }
 
+    fn take_redraw(&mut self) -> bool {
+        self.anim.take_redraw()
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
@@ -155,6 +180,10 @@ impl<'a> draw::SizeHandle for SizeHandle<'a> {
         self.dims.line_height
     }
 
+    fn dpem(&self) -> f32 {
+        self.dims.dpem
+    }
+
     fn text_bound(
         &mut self,
         text: &mut PreparedText,
@@ -213,10 +242,18 @@ impl<'a> draw::SizeHandle for SizeHandle<'a> {
         }
     }
 
+    fn line_length(&self) -> (u32, u32) {
+        (self.dims.min_line_length, self.dims.ideal_line_length)
+    }
+
     fn edit_marker_width(&self) -> f32 {
         self.dims.font_marker_width
     }
 
+    fn focus_ring_width(&self) -> f32 {
+        self.dims.focus_ring
+    }
+
     fn button_surround(&self) -> (Size, Size) {
         let s = Size::uniform(self.dims.button_frame);
         (s, s)
@@ -236,6 +273,10 @@ impl<'a> draw::SizeHandle for SizeHandle<'a> {
         self.checkbox()
     }
 
+    fn icon(&self) -> Size {
+        Size::uniform(self.dims.icon)
+    }
+
     fn scrollbar(&self) -> (Size, u32) {
         let size = self.dims.scrollbar;
         (size, 2 * size.0)
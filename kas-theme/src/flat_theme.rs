@@ -9,40 +9,147 @@
 
 use std::f32;
 use std::ops::Range;
+use std::time::Duration;
 
-use crate::{Dimensions, DimensionsParams, DimensionsWindow, Theme, ThemeColours, Window};
+use crate::stylesheet::Element;
+use crate::{
+    anim, ColourFade, ColourScheme, Dimensions, DimensionsParams, DimensionsWindow, FontConfig,
+    Fonts, StyleSheet, Theme, ThemeColours, ThemeConfig, Window,
+};
 use kas::draw::{
-    self, ClipRegion, Colour, Draw, DrawRounded, DrawShared, DrawText, InputState, Pass,
-    SizeHandle, TextClass, TextEffect,
+    self, ClipRegion, Colour, Draw, DrawRounded, DrawShared, DrawText, FillRule, HighlightKind,
+    HighlightShape, Icon, ImageId, InputState, Layer, Pass, Path, SizeHandle, StrokeStyle,
+    TextClass, TextEffect, TextHighlight,
 };
 use kas::geom::*;
-use kas::text::{FontId, PreparedText};
+use kas::layout::Length;
+use kas::text::PreparedText;
 use kas::{Direction, Directional, ThemeAction, ThemeApi};
 
 /// A theme with flat (unshaded) rendering
 #[derive(Clone, Debug)]
 pub struct FlatTheme {
-    font_id: FontId,
+    font_config: FontConfig,
+    fonts: Fonts,
     font_size: f32,
-    cols: ThemeColours,
+    dims: DimensionsParams,
+    cols: ColourFade,
+    follow_system: bool,
+    style: StyleSheet,
 }
 
 impl FlatTheme {
     /// Construct
     pub fn new() -> Self {
         FlatTheme {
-            font_id: Default::default(),
+            font_config: FontConfig::new(),
+            fonts: Default::default(),
             font_size: 12.0,
-            cols: ThemeColours::new(),
+            dims: DIMS,
+            cols: ColourFade::new(ThemeColours::new()),
+            follow_system: true,
+            style: StyleSheet::new(),
+        }
+    }
+
+    /// Set the duration of colour-scheme transitions (chain style)
+    ///
+    /// When the colour scheme changes, colours cross-fade over this duration
+    /// (default: 250ms). A duration of zero switches colours instantly, as
+    /// does the [`crate::reduce_motion`] setting.
+    pub fn with_colour_transition(mut self, duration: Duration) -> Self {
+        self.cols.set_duration(duration);
+        self
+    }
+
+    /// Set whether to follow the system's dark-mode preference (chain style)
+    ///
+    /// When enabled (the default), the "dark" or "default" colour scheme is
+    /// selected to match the preference reported by the toolkit. Choosing a
+    /// scheme via [`ThemeApi::set_colours`] disables this.
+    pub fn with_system_colours(mut self, follow: bool) -> Self {
+        self.follow_system = follow;
+        self
+    }
+
+    /// Set the font configuration (chain style)
+    ///
+    /// Fonts are loaded when the theme is initialised by the toolkit.
+    pub fn with_fonts(mut self, config: FontConfig) -> Self {
+        self.font_config = config;
+        self
+    }
+
+    /// Set the font size (chain style)
+    ///
+    /// See also [`ThemeApi::set_font_size`].
+    pub fn with_font_size(mut self, size: f32) -> Self {
+        self.font_size = size;
+        self
+    }
+
+    /// Apply a theme configuration (chain style)
+    ///
+    /// Colours, if given, are set immediately. See also
+    /// [`Theme::apply_config`].
+    pub fn with_config(mut self, config: &ThemeConfig) -> Self {
+        if let Some(fonts) = config.fonts.as_ref() {
+            self.font_config = fonts.clone();
+        }
+        if let Some(size) = config.font_size {
+            self.font_size = size;
+        }
+        self.dims = config.dimensions.clone().unwrap_or(DIMS);
+        if let Some(cols) = config.colours.as_ref() {
+            self.cols.reset(cols.clone());
+            self.follow_system = false;
+        }
+        self
+    }
+
+    /// Set the colour scheme (chain style)
+    ///
+    /// Colours are set immediately. This disables following the system's
+    /// dark-mode preference.
+    pub fn with_colour_scheme(mut self, scheme: &ColourScheme) -> Self {
+        self.cols.reset(ThemeColours::from_scheme(scheme));
+        self.follow_system = false;
+        self
+    }
+
+    /// Set the style sheet (chain style)
+    ///
+    /// See [`StyleSheet`] for details.
+    pub fn with_stylesheet(mut self, style: StyleSheet) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the style sheet
+    pub fn set_stylesheet(&mut self, style: StyleSheet) -> ThemeAction {
+        let resize = style.params_differ(&self.style);
+        self.style = style;
+        if resize {
+            ThemeAction::ThemeResize
+        } else {
+            ThemeAction::RedrawAll
         }
     }
 }
 
+/// Colour of drop shadows
+const SHADOW: Colour = Colour {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.25,
+};
+
 const DIMS: DimensionsParams = DimensionsParams {
-    outer_margin: 8.0,
-    inner_margin: 1.0,
-    frame_size: 4.0,
-    button_frame: 6.0,
+    outer_margin: Length::Px(8.0),
+    inner_margin: Length::Px(1.0),
+    frame_size: Length::Px(4.0),
+    button_frame: Length::Px(6.0),
     scrollbar_size: Vec2::splat(8.0),
     slider_size: Vec2(12.0, 25.0),
 };
@@ -51,6 +158,7 @@ pub struct DrawHandle<'a, D: Draw> {
     pub(crate) draw: &'a mut D,
     pub(crate) window: &'a mut DimensionsWindow,
     pub(crate) cols: &'a ThemeColours,
+    pub(crate) style: &'a StyleSheet,
     pub(crate) rect: Rect,
     pub(crate) offset: Coord,
     pub(crate) pass: Pass,
@@ -68,15 +176,19 @@ where
     type DrawHandle<'a> = DrawHandle<'a, D::Draw>;
 
     fn init(&mut self, _draw: &mut D) {
-        self.font_id = kas::text::fonts().load_default().unwrap();
+        self.fonts = self.font_config.load();
     }
 
     fn new_window(&self, _draw: &mut D::Draw, dpi_factor: f32) -> Self::Window {
-        DimensionsWindow::new(DIMS, self.font_id, self.font_size, dpi_factor)
+        let dims = self.style.dims(&self.dims);
+        let font_size = self.style.font_size(self.font_size);
+        DimensionsWindow::new(dims, &self.fonts, font_size, dpi_factor)
     }
 
     fn update_window(&self, window: &mut Self::Window, dpi_factor: f32) {
-        window.dims = Dimensions::new(DIMS, self.font_id, self.font_size, dpi_factor);
+        let dims = self.style.dims(&self.dims);
+        let font_size = self.style.font_size(self.font_size);
+        window.dims = Dimensions::new(dims, &self.fonts, font_size, dpi_factor);
     }
 
     #[cfg(not(feature = "gat"))]
@@ -86,12 +198,15 @@ where
         window: &'a mut Self::Window,
         rect: Rect,
     ) -> Self::DrawHandle {
+        window.anim.new_frame();
+        draw.set_fallback_fonts(self.fonts.fallback());
         // We extend lifetimes (unsafe) due to the lack of associated type generics.
         use std::mem::transmute;
         DrawHandle {
             draw: transmute::<&'a mut D::Draw, &'static mut D::Draw>(draw),
             window: transmute::<&'a mut Self::Window, &'static mut Self::Window>(window),
-            cols: transmute::<&'a ThemeColours, &'static ThemeColours>(&self.cols),
+            cols: transmute::<&'a ThemeColours, &'static ThemeColours>(self.cols.current()),
+            style: transmute::<&'a StyleSheet, &'static StyleSheet>(&self.style),
             rect,
             offset: Coord::ZERO,
             pass: super::START_PASS,
@@ -104,18 +219,51 @@ where
         window: &'a mut Self::Window,
         rect: Rect,
     ) -> Self::DrawHandle<'a> {
+        window.anim.new_frame();
+        draw.set_fallback_fonts(self.fonts.fallback());
         DrawHandle {
             draw,
             window,
-            cols: &self.cols,
+            cols: self.cols.current(),
+            style: &self.style,
             rect,
             offset: Coord::ZERO,
             pass: super::START_PASS,
         }
     }
 
+    fn animate(&mut self) -> bool {
+        self.cols.update()
+    }
+
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        let mut action = ThemeAction::None;
+        if let Some(cols) = config.colours.as_ref() {
+            if cols != self.cols.target() {
+                self.cols.set(cols.clone());
+                self.follow_system = false;
+                action = ThemeAction::RedrawAll;
+            }
+        }
+        if let Some(fonts) = config.fonts.as_ref() {
+            if *fonts != self.font_config {
+                self.font_config = fonts.clone();
+                self.fonts = self.font_config.load();
+                action = ThemeAction::ThemeResize;
+            }
+        }
+        let font_size = config.font_size.unwrap_or(self.font_size);
+        let dims = config.dimensions.clone().unwrap_or(DIMS);
+        if font_size != self.font_size || dims != self.dims {
+            self.font_size = font_size;
+            self.dims = dims;
+            action = ThemeAction::ThemeResize;
+        }
+        action
+    }
+
     fn clear_colour(&self) -> Colour {
-        self.cols.background
+        self.cols.current().background
     }
 }
 
@@ -127,7 +275,27 @@ impl ThemeApi for FlatTheme {
 
     fn set_colours(&mut self, scheme: &str) -> ThemeAction {
         if let Some(scheme) = ThemeColours::open(scheme) {
-            self.cols = scheme;
+            self.follow_system = false;
+            self.cols.set(scheme);
+            ThemeAction::RedrawAll
+        } else {
+            ThemeAction::None
+        }
+    }
+
+    fn set_colour_scheme(&mut self, scheme: &ColourScheme) -> ThemeAction {
+        self.cols.set(ThemeColours::from_scheme(scheme));
+        self.follow_system = false;
+        ThemeAction::RedrawAll
+    }
+
+    fn set_dark_mode(&mut self, dark: bool) -> ThemeAction {
+        if !self.follow_system {
+            return ThemeAction::None;
+        }
+        let scheme = if dark { "dark" } else { "default" };
+        if let Some(scheme) = ThemeColours::open(scheme) {
+            self.cols.set(scheme);
             ThemeAction::RedrawAll
         } else {
             ThemeAction::None
@@ -140,11 +308,18 @@ impl<'a, D: Draw + DrawRounded> DrawHandle<'a, D> {
     /// Return the inner rect.
     ///
     /// - `outer`: define position via outer rect
+    /// - `frame_col`: colour of frame
     /// - `bg_col`: colour of background
     /// - `nav_col`: colour of navigation highlight, if visible
-    fn draw_edit_box(&mut self, outer: Rect, bg_col: Colour, nav_col: Option<Colour>) -> Quad {
+    fn draw_edit_box(
+        &mut self,
+        outer: Rect,
+        frame_col: Colour,
+        bg_col: Colour,
+        nav_col: Option<Colour>,
+    ) -> Quad {
         let outer = Quad::from(outer);
-        let inner1 = outer.shrink(self.window.dims.frame as f32 / 2.0);
+        let inner1 = outer.shrink_snapped(self.window.dims.frame as f32 / 2.0);
         let inner2 = outer.shrink(self.window.dims.frame as f32);
 
         self.draw.rect(self.pass, inner1, bg_col);
@@ -152,7 +327,7 @@ impl<'a, D: Draw + DrawRounded> DrawHandle<'a, D> {
         // We draw over the inner rect, taking advantage of the fact that
         // rounded frames get drawn after flat rects.
         self.draw
-            .rounded_frame(self.pass, outer, inner2, 0.333, self.cols.frame);
+            .rounded_frame(self.pass, outer, inner2, 0.333, frame_col);
 
         if let Some(col) = nav_col {
             self.draw.rounded_frame(self.pass, inner1, inner2, 0.0, col);
@@ -162,19 +337,147 @@ impl<'a, D: Draw + DrawRounded> DrawHandle<'a, D> {
     }
 
     /// Draw a handle (for slider, scrollbar)
-    fn draw_handle(&mut self, rect: Rect, state: InputState) {
+    pub(crate) fn draw_handle(&mut self, rect: Rect, cols: &ThemeColours, state: InputState) {
         let outer = Quad::from(rect + self.offset);
         let thickness = outer.size().min_comp() / 2.0;
         let inner = outer.shrink(thickness);
-        let col = self.cols.scrollbar_state(state);
+        let col = cols.scrollbar_state(state);
         self.draw.rounded_frame(self.pass, outer, inner, 0.0, col);
 
-        if let Some(col) = self.cols.nav_region(state) {
+        if let Some(col) = cols.nav_region(state) {
             let outer = outer.shrink(thickness / 4.0);
             self.draw
                 .rounded_frame(self.pass, outer, inner, 2.0 / 3.0, col);
         }
     }
+
+    /// Draw a highlight of the given `shape` within `outer`
+    ///
+    /// If `width` is given, only a ring of this width is drawn.
+    fn draw_highlight(
+        &mut self,
+        outer: Quad,
+        shape: HighlightShape,
+        width: Option<f32>,
+        col: Colour,
+    ) {
+        let half = outer.size().min_comp() / 2.0;
+        let width = width.map(|w| w.min(half));
+        match shape {
+            HighlightShape::Rect => match width {
+                None => self.draw.rect(self.pass, outer, col),
+                Some(w) => {
+                    let (a, b) = (outer.a, outer.b);
+                    let inner = outer.shrink(w);
+                    let top = Quad::with_coords(a, Vec2(b.0, inner.a.1));
+                    let bottom = Quad::with_coords(Vec2(a.0, inner.b.1), b);
+                    let left = Quad::with_coords(Vec2(a.0, inner.a.1), inner.ba());
+                    let right = Quad::with_coords(inner.ab(), Vec2(b.0, inner.b.1));
+                    for quad in &[top, bottom, left, right] {
+                        self.draw.rect(self.pass, *quad, col);
+                    }
+                }
+            },
+            HighlightShape::Rounded(radius) => {
+                let radius = (radius * self.window.dims.scale_factor).min(half);
+                let radius = width.map(|w| radius.max(w)).unwrap_or(radius);
+                let inner = outer.shrink(radius);
+                match width {
+                    None => {
+                        self.draw.rounded_frame(self.pass, outer, inner, 0.0, col);
+                        self.draw.rect(self.pass, inner, col);
+                    }
+                    Some(w) => {
+                        let inner_radius = 1.0 - w / radius.max(f32::EPSILON);
+                        self.draw
+                            .rounded_frame(self.pass, outer, inner, inner_radius, col);
+                    }
+                }
+            }
+            HighlightShape::Oval => {
+                let inner_radius = width.map(|w| 1.0 - w / half.max(f32::EPSILON));
+                self.draw
+                    .circle(self.pass, outer, inner_radius.unwrap_or(0.0), col);
+            }
+        }
+    }
+
+    /// Draw a standard icon within the square `q`
+    fn draw_icon(&mut self, q: Quad, icon: &Icon, cols: &ThemeColours, disabled: bool) {
+        const WARNING: Colour = Colour::new(0.95, 0.65, 0.05);
+        const ERROR: Colour = Colour::new(0.8, 0.1, 0.1);
+
+        let (a, s) = (q.a, q.size().0);
+        let p = |x: f32, y: f32| a + Vec2(x, y) * s;
+        let r = s / 16.0;
+        let (col, badge) = match icon {
+            _ if disabled => (cols.button_disabled, cols.button_disabled),
+            Icon::Info => (cols.label_text, cols.nav_focus),
+            Icon::Warning => (cols.label_text, WARNING),
+            Icon::Error => (cols.label_text, ERROR),
+            _ => (cols.label_text, cols.label_text),
+        };
+        let dot = |x: f32, y: f32| Quad::with_pos_and_size(p(x, y) - 1.5 * r, Vec2::splat(3.0 * r));
+
+        let lines: &[(f32, f32, f32, f32)] = match icon {
+            Icon::Close => &[(0.2, 0.2, 0.8, 0.8), (0.2, 0.8, 0.8, 0.2)],
+            Icon::Check => &[(0.15, 0.55, 0.4, 0.8), (0.4, 0.8, 0.85, 0.25)],
+            Icon::Plus => &[(0.5, 0.15, 0.5, 0.85), (0.15, 0.5, 0.85, 0.5)],
+            Icon::Minus => &[(0.15, 0.5, 0.85, 0.5)],
+            Icon::Up => &[(0.2, 0.65, 0.5, 0.35), (0.5, 0.35, 0.8, 0.65)],
+            Icon::Down => &[(0.2, 0.35, 0.5, 0.65), (0.5, 0.65, 0.8, 0.35)],
+            Icon::Left => &[(0.65, 0.2, 0.35, 0.5), (0.35, 0.5, 0.65, 0.8)],
+            Icon::Right => &[(0.35, 0.2, 0.65, 0.5), (0.65, 0.5, 0.35, 0.8)],
+            Icon::Menu => &[
+                (0.15, 0.25, 0.85, 0.25),
+                (0.15, 0.5, 0.85, 0.5),
+                (0.15, 0.75, 0.85, 0.75),
+            ],
+            Icon::Copy => {
+                let back = Quad::with_coords(p(0.1, 0.1), p(0.65, 0.7));
+                let front = Quad::with_coords(p(0.35, 0.3), p(0.9, 0.9));
+                let f = 2.0 * r;
+                self.draw
+                    .rounded_frame(self.pass, back, back.shrink(f), 0.0, col);
+                self.draw.rect(self.pass, front, cols.background);
+                self.draw
+                    .rounded_frame(self.pass, front, front.shrink(f), 0.0, col);
+                &[]
+            }
+            Icon::Info => {
+                self.draw.circle(self.pass, q, 0.0, badge);
+                self.draw
+                    .circle(self.pass, dot(0.5, 0.3), 0.0, cols.background);
+                self.draw
+                    .rounded_line(self.pass, p(0.5, 0.48), p(0.5, 0.75), r, cols.background);
+                &[]
+            }
+            Icon::Warning => {
+                let (t, bl, br) = (p(0.5, 0.1), p(0.1, 0.85), p(0.9, 0.85));
+                self.draw.rounded_line(self.pass, t, bl, r, badge);
+                self.draw.rounded_line(self.pass, bl, br, r, badge);
+                self.draw.rounded_line(self.pass, br, t, r, badge);
+                self.draw
+                    .rounded_line(self.pass, p(0.5, 0.38), p(0.5, 0.56), r, col);
+                self.draw.circle(self.pass, dot(0.5, 0.7), 0.0, col);
+                &[]
+            }
+            Icon::Error => {
+                self.draw.circle(self.pass, q, 0.0, badge);
+                let col = cols.background;
+                self.draw
+                    .rounded_line(self.pass, p(0.32, 0.32), p(0.68, 0.68), r, col);
+                self.draw
+                    .rounded_line(self.pass, p(0.32, 0.68), p(0.68, 0.32), r, col);
+                &[]
+            }
+            Icon::Image(_) => &[],
+        };
+        for (x1, y1, x2, y2) in lines {
+            self.draw
+                .rounded_line(self.pass, p(*x1, *y1), p(*x2, *y2), r, col);
+        }
+    }
 }
 
 impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D> {
@@ -196,7 +499,10 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
         class: ClipRegion,
         f: &mut dyn FnMut(&mut dyn draw::DrawHandle),
     ) {
-        let rect = rect + self.offset;
+        let mut rect = rect + self.offset;
+        if class != ClipRegion::Popup {
+            rect = rect.intersection(&self.rect);
+        }
         let depth = self.pass.depth() + super::relative_region_depth(class);
         let pass = self.draw.add_clip_region(rect, depth);
         if depth < self.pass.depth() {
@@ -208,6 +514,7 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
             draw: self.draw,
             window: self.window,
             cols: self.cols,
+            style: self.style,
             rect,
             offset: self.offset - offset,
             pass,
@@ -215,37 +522,96 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
         f(&mut handle);
     }
 
+    fn cached_region(
+        &mut self,
+        key: u64,
+        rect: Rect,
+        valid: bool,
+        f: &mut dyn FnMut(&mut dyn draw::DrawHandle),
+    ) {
+        let rect = (rect + self.offset).intersection(&self.rect);
+        let valid = valid && self.window.anim.cache_valid(key);
+        if let Some(pass) = self.draw.add_cached_region(key, rect, self.pass, valid) {
+            let token = self.window.anim.begin_cache();
+            let mut handle = DrawHandle {
+                draw: self.draw,
+                window: self.window,
+                cols: self.cols,
+                style: self.style,
+                rect,
+                offset: self.offset,
+                pass,
+            };
+            f(&mut handle);
+            self.window.anim.end_cache(key, token);
+        }
+    }
+
+    fn layer(&mut self, rect: Rect, layer: Layer, f: &mut dyn FnMut(&mut dyn draw::DrawHandle)) {
+        let rect = (rect + self.offset).intersection(&self.rect);
+        let pass = self.draw.add_layer(rect, self.pass, layer);
+        let mut handle = DrawHandle {
+            draw: self.draw,
+            window: self.window,
+            cols: self.cols,
+            style: self.style,
+            rect,
+            offset: self.offset,
+            pass,
+        };
+        f(&mut handle);
+    }
+
     fn target_rect(&self) -> Rect {
         // Translate to local coordinates
         self.rect - self.offset
     }
 
     fn outer_frame(&mut self, rect: Rect) {
+        let cols = self
+            .style
+            .resolve(self.cols, Element::Frame, Default::default());
         let outer = Quad::from(rect + self.offset);
         let inner = outer.shrink(self.window.dims.frame as f32);
         self.draw
-            .rounded_frame(self.pass, outer, inner, 0.5, self.cols.frame);
+            .rounded_frame(self.pass, outer, inner, 0.5, cols.frame);
     }
 
     fn menu_frame(&mut self, rect: Rect) {
+        let cols = self
+            .style
+            .resolve(self.cols, Element::MenuFrame, Default::default());
         let outer = Quad::from(rect + self.offset);
         let inner = outer.shrink(self.window.dims.frame as f32);
         self.draw
-            .rounded_frame(self.pass, outer, inner, 0.5, self.cols.frame);
-        let inner = outer.shrink(self.window.dims.frame as f32 / 3.0);
-        self.draw.rect(self.pass, inner, self.cols.background);
+            .rounded_frame(self.pass, outer, inner, 0.5, cols.frame);
+        let inner = outer.shrink_snapped(self.window.dims.frame as f32 / 3.0);
+        self.draw.rect(self.pass, inner, cols.background);
     }
 
     fn separator(&mut self, rect: Rect) {
+        let cols = self
+            .style
+            .resolve(self.cols, Element::Separator, Default::default());
         let outer = Quad::from(rect + self.offset);
         let inner = outer.shrink(outer.size().min_comp() / 2.0);
         self.draw
-            .rounded_frame(self.pass, outer, inner, 0.5, self.cols.frame);
+            .rounded_frame(self.pass, outer, inner, 0.5, cols.frame);
     }
 
-    fn text_offset(&mut self, pos: Coord, offset: Coord, text: &PreparedText, class: TextClass) {
+    fn text_offset(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+    ) {
         let pos = pos + self.offset;
-        let col = self.cols.text_class(class);
+        let col = self
+            .style
+            .resolve_text(self.cols, class, state)
+            .text_class(class);
         self.draw
             .text(self.pass, pos.into(), offset.into(), col, text);
     }
@@ -256,6 +622,7 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
         offset: Coord,
         text: &PreparedText,
         class: TextClass,
+        state: InputState,
         underline: usize,
     ) {
         let pos = pos + self.offset;
@@ -268,8 +635,12 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
                 .map(|(i, _)| underline + i)
                 .unwrap_or(text.text_len());
         }
+        let col = self
+            .style
+            .resolve_text(self.cols, class, state)
+            .text_class(class);
         let effects = [
-            TextEffect::col(0, self.cols.text_class(class)),
+            TextEffect::col(0, col),
             TextEffect::underline(underline, true),
             TextEffect::underline(end, false),
         ];
@@ -284,31 +655,64 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
         text: &PreparedText,
         range: Range<usize>,
         class: TextClass,
+        state: InputState,
+    ) {
+        let highlight = TextHighlight::new(range, HighlightKind::Selection);
+        self.text_highlighted(pos, offset, text, class, state, &[highlight]);
+    }
+
+    fn text_highlighted(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+        highlights: &[TextHighlight],
     ) {
         let pos = Vec2::from(pos + self.offset);
         let offset = Vec2::from(offset);
         let bounds = Vec2::from(text.env().bounds);
-        let col = self.cols.text_class(class);
-
-        // Draw background:
-        for (p1, p2) in &text.highlight_lines(range.clone()) {
-            let mut p1 = Vec2::from(*p1) - offset;
-            let mut p2 = Vec2::from(*p2) - offset;
-            if !p2.gt(Vec2::ZERO) || !p1.lt(bounds) {
-                continue;
-            }
-            p1 = p1.max(Vec2::ZERO);
-            p2 = p2.min(bounds);
+        let cols = self.style.resolve_text(self.cols, class, state);
+        let col = cols.text_class(class);
+        let underline = self.window.dims.font_marker_width;
+
+        // Draw backgrounds and underlines:
+        for highlight in highlights {
+            let bg = match highlight.kind {
+                HighlightKind::Selection => cols.text_sel_bg,
+                HighlightKind::Match => cols.nav_focus,
+                HighlightKind::Error => cols.bg_error,
+            };
+            for (p1, p2) in &text.highlight_lines(highlight.range.clone()) {
+                let mut p1 = Vec2::from(*p1) - offset;
+                let mut p2 = Vec2::from(*p2) - offset;
+                if highlight.kind == HighlightKind::Error {
+                    p1.1 = p2.1 - underline;
+                }
+                if !p2.gt(Vec2::ZERO) || !p1.lt(bounds) {
+                    continue;
+                }
+                p1 = p1.max(Vec2::ZERO);
+                p2 = p2.min(bounds);
 
-            let quad = Quad::with_coords(pos + p1, pos + p2);
-            self.draw.rect(self.pass, quad, self.cols.text_sel_bg);
+                let quad = Quad::with_coords(pos + p1, pos + p2);
+                self.draw.rect(self.pass, quad, bg);
+            }
         }
 
-        let effects = [
-            TextEffect::col(0, col),
-            TextEffect::col(range.start, self.cols.text_sel),
-            TextEffect::col(range.end, col),
-        ];
+        // Only selections change the text colour
+        let mut selections: Vec<_> = highlights
+            .iter()
+            .filter(|h| h.kind == HighlightKind::Selection)
+            .map(|h| h.range.clone())
+            .collect();
+        selections.sort_by_key(|range| range.start);
+        let mut effects = vec![TextEffect::col(0, col)];
+        for range in selections {
+            effects.push(TextEffect::col(range.start, cols.text_sel));
+            effects.push(TextEffect::col(range.end, col));
+        }
         self.draw
             .text_with_effects(self.pass, pos, offset, text, &effects);
     }
@@ -328,7 +732,10 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
         let bounds = Quad::with_pos_and_size(p, size);
         let pos = Vec2::from(pos - offset + self.offset);
 
-        let mut col = self.cols.text_class(class);
+        let cols = self
+            .style
+            .resolve_text(self.cols, class, Default::default());
+        let mut col = cols.text_class(class);
         for cursor in text.text_glyph_pos(byte).rev() {
             let mut p1 = pos + Vec2::from(cursor.pos);
             let mut p2 = p1;
@@ -352,46 +759,80 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
                 }
             }
             // hack to make secondary marker grey:
-            col = self.cols.button_disabled;
+            col = cols.button_disabled;
         }
     }
 
     fn menu_entry(&mut self, rect: Rect, state: InputState) {
-        if let Some(col) = self.cols.menu_entry(state) {
+        let cols = self.style.resolve(self.cols, Element::MenuEntry, state);
+        let mut col = cols.menu_entry(state);
+        if !state.depress && !state.nav_focus {
+            // Hover feedback: fade the highlight in and out
+            let h = self.window.anim.hover(rect, state.hover);
+            if h > 0.0 {
+                col = Some(anim::blend(cols.background, cols.button_highlighted, h));
+            }
+        }
+        if let Some(col) = col {
             let quad = Quad::from(rect + self.offset);
             self.draw.rect(self.pass, quad, col);
         }
     }
 
     fn button(&mut self, rect: Rect, state: InputState) {
-        let outer = Quad::from(rect + self.offset);
-        let col = self.cols.button_state(state);
+        let cols = self.style.resolve(self.cols, Element::Button, state);
+        let mut outer = Quad::from(rect + self.offset);
+        let mut col = cols.button_state(state);
+
+        if !state.disabled && !state.depress {
+            // Hover feedback: fade between normal and highlighted colours
+            let h = self.window.anim.hover(rect, state.hover);
+            col = anim::blend(cols.button, cols.button_highlighted, h);
+        }
+
+        let p = self
+            .window
+            .anim
+            .press(rect, state.depress && !state.disabled);
+        if p < 1.0 {
+            // Press feedback: fade in depressed colour and briefly inset
+            col = anim::blend(cols.button_highlighted, col, p);
+            let inset = self.window.dims.scale_factor * (p * f32::consts::PI).sin();
+            outer = outer.shrink(inset);
+        }
 
         let inner = outer.shrink(self.window.dims.button_frame as f32);
         self.draw.rounded_frame(self.pass, outer, inner, 0.0, col);
         self.draw.rect(self.pass, inner, col);
 
-        if let Some(col) = self.cols.nav_region(state) {
-            let outer = outer.shrink(self.window.dims.button_frame as f32 / 3.0);
+        if let Some(col) = cols.nav_region(state) {
+            let outer = outer.shrink_snapped(self.window.dims.button_frame as f32 / 3.0);
             self.draw.rounded_frame(self.pass, outer, inner, 0.5, col);
         }
     }
 
     fn edit_box(&mut self, rect: Rect, state: InputState) {
-        let bg_col = self.cols.bg_col(state);
-        self.draw_edit_box(rect + self.offset, bg_col, self.cols.nav_region(state));
+        let cols = self.style.resolve(self.cols, Element::EditBox, state);
+        let bg_col = cols.bg_col(state);
+        let nav_col = cols.nav_region(state);
+        self.draw_edit_box(rect + self.offset, cols.frame, bg_col, nav_col);
     }
 
     fn checkbox(&mut self, rect: Rect, checked: bool, state: InputState) {
-        let bg_col = self.cols.bg_col(state);
-        let nav_col = self.cols.nav_region(state).or(Some(bg_col));
+        let cols = self.style.resolve(self.cols, Element::CheckBox, state);
+        let bg_col = cols.bg_col(state);
+        let nav_col = cols.nav_region(state).or(Some(bg_col));
 
-        let inner = self.draw_edit_box(rect + self.offset, bg_col, nav_col);
+        let inner = self.draw_edit_box(rect + self.offset, cols.frame, bg_col, nav_col);
 
-        if let Some(col) = self.cols.check_mark_state(state, checked) {
+        // Toggle feedback: the mark grows in (also while depressed) and
+        // shrinks out
+        let t = self.window.anim.toggle(rect, checked || state.depress);
+        if let Some(col) = cols.check_mark_state(state, checked || t > 0.0) {
             let radius = inner.size().sum() * (1.0 / 16.0);
             let inner = inner.shrink(self.window.dims.inner_margin as f32 + radius);
-            let radius = radius as f32;
+            let inner = anim::scale(inner, t);
+            let radius = radius * t;
             self.draw
                 .rounded_line(self.pass, inner.a, inner.b, radius, col);
             self.draw
@@ -400,40 +841,139 @@ impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D
     }
 
     fn radiobox(&mut self, rect: Rect, checked: bool, state: InputState) {
-        let bg_col = self.cols.bg_col(state);
-        let nav_col = self.cols.nav_region(state).or(Some(bg_col));
+        let cols = self.style.resolve(self.cols, Element::RadioBox, state);
+        let bg_col = cols.bg_col(state);
+        let nav_col = cols.nav_region(state).or(Some(bg_col));
 
-        let inner = self.draw_edit_box(rect + self.offset, bg_col, nav_col);
+        let inner = self.draw_edit_box(rect + self.offset, cols.frame, bg_col, nav_col);
 
-        if let Some(col) = self.cols.check_mark_state(state, checked) {
+        let t = self.window.anim.toggle(rect, checked || state.depress);
+        if let Some(col) = cols.check_mark_state(state, checked || t > 0.0) {
             let inner = inner.shrink(self.window.dims.inner_margin as f32);
+            let inner = anim::scale(inner, t);
             self.draw.circle(self.pass, inner, 0.3, col);
         }
     }
 
     fn scrollbar(&mut self, rect: Rect, h_rect: Rect, _dir: Direction, state: InputState) {
+        let cols = self.style.resolve(self.cols, Element::ScrollBar, state);
         // track
         let outer = Quad::from(rect + self.offset);
         let inner = outer.shrink(outer.size().min_comp() / 2.0);
-        let col = self.cols.frame;
+        let col = cols.frame;
         self.draw.rounded_frame(self.pass, outer, inner, 0.0, col);
 
         // handle
-        self.draw_handle(h_rect, state);
+        self.draw_handle(h_rect, &cols, state);
     }
 
     fn slider(&mut self, rect: Rect, h_rect: Rect, dir: Direction, state: InputState) {
+        let cols = self.style.resolve(self.cols, Element::Slider, state);
         // track
         let mut outer = Quad::from(rect + self.offset);
         outer = match dir.is_horizontal() {
             true => outer.shrink_vec(Vec2(0.0, outer.size().1 * (3.0 / 8.0))),
             false => outer.shrink_vec(Vec2(outer.size().0 * (3.0 / 8.0), 0.0)),
-        };
+        }
+        .snap();
         let inner = outer.shrink(outer.size().min_comp() / 2.0);
-        let col = self.cols.frame;
+        let col = cols.frame;
         self.draw.rounded_frame(self.pass, outer, inner, 0.0, col);
 
         // handle
-        self.draw_handle(h_rect, state);
+        self.draw_handle(h_rect, &cols, state);
+    }
+
+    fn icon(&mut self, rect: Rect, icon: &Icon, state: InputState) {
+        let outer = Quad::from(rect + self.offset);
+        if let Icon::Image(image) = icon {
+            self.draw.image(self.pass, outer, image);
+            return;
+        }
+
+        // Standard icons are drawn within a centred square
+        let size = outer.size();
+        let s = size.min_comp();
+        let q = Quad::with_pos_and_size(outer.a + (size - s) * 0.5, Vec2::splat(s)).snap();
+        let cols = self.style.resolve(self.cols, Element::Icon, state);
+        self.draw_icon(q, icon, &cols, state.disabled);
+    }
+
+    fn image(&mut self, rect: Rect, id: ImageId) {
+        if let Some(draw) = self.draw.as_draw_image() {
+            let region = Quad::with_coords(Vec2::ZERO, Vec2::splat(1.0));
+            draw.draw_image(self.pass, (rect + self.offset).into(), id, region);
+        }
+    }
+
+    fn fill_path(&mut self, path: &Path, rule: FillRule, col: Colour) {
+        let offset = Vec2::from(self.offset);
+        if let Some(draw) = self.draw.as_draw_path() {
+            draw.fill_path(self.pass, &path.translated(offset), rule, col);
+        }
+    }
+
+    fn stroke_path(&mut self, path: &Path, style: &StrokeStyle, col: Colour) {
+        let offset = Vec2::from(self.offset);
+        if let Some(draw) = self.draw.as_draw_path() {
+            draw.stroke_path(self.pass, &path.translated(offset), style, col);
+        }
+    }
+
+    fn selection(&mut self, rect: Rect, shape: HighlightShape, state: InputState) {
+        let cols = self.style.resolve(self.cols, Element::Selection, state);
+        let outer = Quad::from(rect + self.offset);
+        self.draw_highlight(outer, shape, None, cols.selection_state(state));
+    }
+
+    fn focus_ring(&mut self, rect: Rect, shape: HighlightShape, state: InputState) {
+        let cols = self.style.resolve(self.cols, Element::FocusRing, state);
+        if let Some(col) = cols.nav_region(state) {
+            let outer = Quad::from(rect + self.offset);
+            let width = self.window.dims.focus_ring;
+            self.draw_highlight(outer, shape, Some(width), col);
+        }
+    }
+
+    fn shadow(&mut self, rect: Rect) {
+        // Right and bottom strips outside of rect. We use DrawRounded since
+        // (unlike Draw::rect) it supports transparency; the inner rect of
+        // each frame has zero width, giving square ends.
+        let d = self.window.dims.frame as f32;
+        let rect = Quad::from(rect + self.offset);
+        let (a, b) = (rect.a, rect.b);
+        if b.0 - a.0 < d || b.1 - a.1 < d {
+            return;
+        }
+        let right = Quad::with_coords(Vec2(b.0, a.1 + d), b + d);
+        let inner = right.shrink_vec(Vec2(d / 2.0, 0.0));
+        self.draw
+            .rounded_frame(self.pass, right, inner, 0.0, SHADOW);
+        let bottom = Quad::with_coords(Vec2(a.0 + d, b.1), Vec2(b.0, b.1 + d));
+        let inner = bottom.shrink_vec(Vec2(0.0, d / 2.0));
+        self.draw
+            .rounded_frame(self.pass, bottom, inner, 0.0, SHADOW);
+    }
+
+    fn well(&mut self, rect: Rect, state: InputState) {
+        let cols = self.style.resolve(self.cols, Element::Well, state);
+        let bg_col = cols.bg_col(state);
+        self.draw_edit_box(rect + self.offset, cols.frame, bg_col, None);
+    }
+
+    fn badge(&mut self, rect: Rect, text: &PreparedText, state: InputState) {
+        let cols = self.style.resolve(self.cols, Element::Badge, state);
+        let col = cols.badge_state(state);
+        let outer = Quad::from(rect + self.offset);
+        let inner = outer.shrink(outer.size().min_comp() / 2.0);
+        self.draw.rounded_frame(self.pass, outer, inner, 0.0, col);
+
+        let (black, white) = (Colour::grey(0.0), Colour::grey(1.0));
+        let text_col = match col.contrast_ratio(white) >= col.contrast_ratio(black) {
+            true => white,
+            false => black,
+        };
+        let pos = outer.a;
+        self.draw.text(self.pass, pos, Vec2::ZERO, text_col, text);
     }
 }
@@ -0,0 +1,184 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Font configuration
+
+#[cfg(all(feature = "font-kit", not(target_arch = "wasm32")))]
+use font_kit_::{
+    family_name::FamilyName, handle::Handle, properties::Properties, source::SystemSource,
+};
+use log::warn;
+
+use kas::text::{FallbackFont, FontId};
+
+/// A font family
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_::Serialize, serde_::Deserialize),
+    serde(crate = "serde_")
+)]
+pub enum FontFamily {
+    /// The system's default serif font
+    Serif,
+    /// The system's default sans-serif font
+    SansSerif,
+    /// The system's default monospace font
+    Monospace,
+    /// A family by name, e.g. `"Noto Sans CJK JP"`
+    Name(String),
+}
+
+impl From<&str> for FontFamily {
+    fn from(name: &str) -> Self {
+        match name {
+            "serif" => FontFamily::Serif,
+            "sans-serif" => FontFamily::SansSerif,
+            "monospace" => FontFamily::Monospace,
+            name => FontFamily::Name(name.to_string()),
+        }
+    }
+}
+
+#[cfg(all(feature = "font-kit", not(target_arch = "wasm32")))]
+impl FontFamily {
+    fn to_family_name(&self) -> FamilyName {
+        match self {
+            FontFamily::Serif => FamilyName::Serif,
+            FontFamily::SansSerif => FamilyName::SansSerif,
+            FontFamily::Monospace => FamilyName::Monospace,
+            FontFamily::Name(name) => FamilyName::Title(name.clone()),
+        }
+    }
+}
+
+/// Font configuration
+///
+/// All text is shaped and laid out using the default font of `kas-text` (the
+/// system's default sans-serif font), which cannot currently be replaced.
+///
+/// Families from the *fallback* list are used glyph-by-glyph for characters
+/// not supported by the default font (e.g. CJK or symbols): each such glyph
+/// is drawn with the first fallback font supporting it. Note that the text is
+/// still laid out using the metrics of the default font.
+///
+/// Fonts are discovered and loaded when the theme is initialised. Discovery
+/// of system fonts requires the `font-kit` feature (and is unavailable on
+/// WASM).
+///
+/// ```
+/// use kas_theme::{FlatTheme, FontConfig, FontFamily};
+///
+/// let fonts = FontConfig::new()
+///     .with_fallback(vec!["Noto Sans CJK JP".into(), FontFamily::Serif]);
+/// let theme = FlatTheme::new().with_fonts(fonts);
+/// # let _ = theme;
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_::Serialize, serde_::Deserialize),
+    serde(crate = "serde_", default)
+)]
+pub struct FontConfig {
+    fallback: Vec<FontFamily>,
+}
+
+impl FontConfig {
+    /// Construct a default configuration
+    ///
+    /// This uses the toolkit's default font for all text, without fallbacks.
+    pub fn new() -> Self {
+        FontConfig::default()
+    }
+
+    /// Set the fallback fonts (chain style)
+    ///
+    /// Each installed family of the list is used, in order of preference.
+    pub fn with_fallback(mut self, fallback: Vec<FontFamily>) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Discover and load fonts
+    ///
+    /// Families which cannot be found or loaded are reported as warnings.
+    pub fn load(&self) -> Fonts {
+        let default = match kas::text::fonts().load_default() {
+            Ok(id) => id,
+            Err(e) => {
+                warn!("Failed to load default font: {}", e);
+                Default::default()
+            }
+        };
+        let fallback = self.fallback.iter().filter_map(load_family).collect();
+        Fonts { default, fallback }
+    }
+}
+
+/// Load a font family
+#[cfg(all(feature = "font-kit", not(target_arch = "wasm32")))]
+fn load_family(family: &FontFamily) -> Option<FallbackFont> {
+    let names = [family.to_family_name()];
+    let handle = match SystemSource::new().select_best_match(&names, &Properties::new()) {
+        Ok(handle) => handle,
+        Err(e) => {
+            warn!("No font found for family {:?}: {:?}", family, e);
+            return None;
+        }
+    };
+    match handle {
+        Handle::Path { path, font_index } => match FallbackFont::load(&path, font_index) {
+            Ok(font) => Some(font),
+            Err(e) => {
+                warn!("Failed to load font {}: {}", path.display(), e);
+                None
+            }
+        },
+        Handle::Memory { .. } => {
+            warn!("Unable to load in-memory font for family {:?}", family);
+            None
+        }
+    }
+}
+
+/// Load a font family
+///
+/// System fonts cannot be discovered within the browser or without the
+/// `font-kit` feature.
+#[cfg(not(all(feature = "font-kit", not(target_arch = "wasm32"))))]
+fn load_family(family: &FontFamily) -> Option<FallbackFont> {
+    warn!(
+        "Unable to load font family {:?}: system font discovery is unavailable",
+        family
+    );
+    None
+}
+
+/// Loaded fonts
+///
+/// This is constructed by [`FontConfig::load`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fonts {
+    default: FontId,
+    fallback: Vec<FallbackFont>,
+}
+
+impl Fonts {
+    /// Get the default font
+    #[inline]
+    pub fn default_font(&self) -> FontId {
+        self.default
+    }
+
+    /// Get the fallback fonts
+    ///
+    /// These are used (in order of preference) to draw glyphs not supported
+    /// by the default font; see [`kas::draw::DrawText::set_fallback_fonts`].
+    #[inline]
+    pub fn fallback(&self) -> &[FallbackFont] {
+        &self.fallback
+    }
+}
@@ -12,18 +12,24 @@
 //! and provides an implementation of [`kas::draw::DrawHandle`].
 //!
 //! Two themes are provided by this trait: [`FlatTheme`] and [`ShadedTheme`].
+//! [`NinePatchTheme`] draws elements from application-supplied images.
 //! Additionally, a meta-theme, [`MultiTheme`], allows run-time switching
 //! between themes.
 
 #![cfg_attr(feature = "gat", feature(generic_associated_types))]
 #![cfg_attr(feature = "unsize", feature(unsize))]
 
+mod anim;
 mod col;
+mod config;
 mod dim;
 mod flat_theme;
+mod fonts;
 #[cfg(feature = "stack_dst")]
 mod multi;
+mod nine_patch;
 mod shaded_theme;
+mod stylesheet;
 #[cfg(feature = "stack_dst")]
 mod theme_dst;
 mod traits;
@@ -31,12 +37,20 @@ mod traits;
 pub use kas;
 use kas::draw::{ClipRegion, Pass};
 
-pub use col::ThemeColours;
+pub use anim::{reduce_motion, set_reduce_motion, AnimState, ColourFade};
+pub use col::{ContrastIssue, ThemeColours, MIN_TEXT_CONTRAST};
+#[cfg(feature = "serde")]
+pub use config::ConfigError;
+pub use config::ThemeConfig;
 pub use dim::{Dimensions, DimensionsParams, DimensionsWindow};
 pub use flat_theme::FlatTheme;
+pub use fonts::{FontConfig, FontFamily, Fonts};
+pub use kas::draw::ColourScheme;
 #[cfg(feature = "stack_dst")]
 pub use multi::{MultiTheme, MultiThemeBuilder};
+pub use nine_patch::{NinePatch, NinePatchTheme, Skin, StatePatch};
 pub use shaded_theme::ShadedTheme;
+pub use stylesheet::{StyleSheet, StyleSheetError};
 #[cfg(feature = "stack_dst")]
 pub use theme_dst::{ThemeDst, WindowDst};
 pub use traits::{Theme, Window};
@@ -59,5 +73,8 @@ fn relative_region_depth(class: ClipRegion) -> f32 {
     match class {
         ClipRegion::Popup => 0.01,
         ClipRegion::Scroll => -1e-5,
+        ClipRegion::Clip => 1e-5,
+        // Above nested clip regions of the parent, but below pop-ups
+        ClipRegion::Layer(n) => 1e-4 * (1 + n.min(63)) as f32,
     }
 }
@@ -9,8 +9,8 @@ use std::collections::HashMap;
 #[cfg(feature = "unsize")]
 use std::marker::Unsize;
 
-use crate::{StackDst, Theme, ThemeDst, WindowDst};
-use kas::draw::{Colour, DrawHandle, DrawShared};
+use crate::{StackDst, Theme, ThemeConfig, ThemeDst, WindowDst};
+use kas::draw::{Colour, ColourScheme, DrawHandle, DrawShared};
 use kas::geom::Rect;
 use kas::{ThemeAction, ThemeApi};
 
@@ -144,6 +144,23 @@ impl<D: DrawShared + 'static> Theme<D> for MultiTheme<D> {
         self.themes[self.active].draw_handle(draw, window, rect)
     }
 
+    fn animate(&mut self) -> bool {
+        // Inactive themes may also be mid-transition (see set_colours)
+        let mut redraw = false;
+        for theme in &mut self.themes {
+            redraw |= theme.animate();
+        }
+        redraw
+    }
+
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        let mut action = ThemeAction::None;
+        for theme in &mut self.themes {
+            action = action.max(theme.apply_config(config));
+        }
+        action
+    }
+
     fn clear_colour(&self) -> Colour {
         self.themes[self.active].clear_colour()
     }
@@ -170,6 +187,22 @@ impl<Draw> ThemeApi for MultiTheme<Draw> {
         action
     }
 
+    fn set_colour_scheme(&mut self, scheme: &ColourScheme) -> ThemeAction {
+        let mut action = ThemeAction::None;
+        for theme in &mut self.themes {
+            action = action.max(theme.set_colour_scheme(scheme));
+        }
+        action
+    }
+
+    fn set_dark_mode(&mut self, dark: bool) -> ThemeAction {
+        let mut action = ThemeAction::None;
+        for theme in &mut self.themes {
+            action = action.max(theme.set_dark_mode(dark));
+        }
+        action
+    }
+
     fn set_theme(&mut self, theme: &str) -> ThemeAction {
         if let Some(index) = self.names.get(theme).cloned() {
             if index != self.active {
@@ -0,0 +1,616 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Image-based (nine-patch) theme
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::stylesheet::Element;
+use crate::{flat_theme, ColourScheme, DimensionsWindow, FlatTheme, Theme, ThemeConfig};
+use kas::draw::{
+    self, ClipRegion, Colour, Draw, DrawRounded, DrawShared, DrawText, FillRule, HighlightShape,
+    Icon, IconImage, ImageId, InputState, Layer, Pass, Path, SizeHandle, StrokeStyle, TextClass,
+    TextHighlight,
+};
+use kas::geom::*;
+use kas::layout::Margins;
+use kas::text::PreparedText;
+use kas::{Direction, ThemeAction, ThemeApi};
+
+/// A nine-patch image
+///
+/// The image is divided into a 3×3 grid by its `insets`: when drawn, the
+/// corners keep their size, the edges are stretched along their length and
+/// the centre is stretched to fill the remaining space.
+///
+/// Variants of the image may be supplied for higher scale factors (e.g. a
+/// "@2x" asset for scale factor 2); the smallest variant at least as large
+/// as the window's scale factor is drawn (or else the largest variant).
+///
+/// Images should be raster images (see [`IconImage::size`]); scalable
+/// images are simply stretched to fill the drawn rect.
+#[derive(Clone, Debug)]
+pub struct NinePatch {
+    /// Image variants, sorted by scale factor
+    variants: Vec<(f32, Arc<IconImage>)>,
+    insets: Margins,
+}
+
+impl NinePatch {
+    /// Construct from an image at scale factor 1
+    ///
+    /// The `insets` give the size of the fixed borders of this image in
+    /// pixels: `horiz` gives the (left, right) and `vert` the (top, bottom)
+    /// borders. Borders are drawn at this size multiplied by the window's
+    /// scale factor.
+    pub fn new<I: Into<Arc<IconImage>>>(image: I, insets: Margins) -> Self {
+        NinePatch {
+            variants: vec![(1.0, image.into())],
+            insets,
+        }
+    }
+
+    /// Add an image variant for the given scale factor (chain style)
+    ///
+    /// The image's borders should be `scale` times the size of the `insets`.
+    pub fn with_variant<I: Into<Arc<IconImage>>>(mut self, scale: f32, image: I) -> Self {
+        let index = self.variants.iter().take_while(|v| v.0 < scale).count();
+        self.variants.insert(index, (scale, image.into()));
+        self
+    }
+
+    /// Get the insets (at scale factor 1)
+    #[inline]
+    pub fn insets(&self) -> Margins {
+        self.insets
+    }
+
+    /// Select the variant to draw at `scale_factor`
+    fn variant(&self, scale_factor: f32) -> &(f32, Arc<IconImage>) {
+        self.variants
+            .iter()
+            .find(|v| v.0 >= scale_factor)
+            .unwrap_or_else(|| self.variants.last().unwrap())
+    }
+
+    /// Draw, filling `outer`
+    pub fn draw<D: Draw + ?Sized>(&self, draw: &mut D, pass: Pass, outer: Quad, scale_factor: f32) {
+        let (scale, image) = self.variant(scale_factor);
+        let size = match image.size() {
+            Some(size) => Vec2::from(size),
+            None => return draw.image(pass, outer, image),
+        };
+
+        let m = self.insets;
+        let inset_a = Vec2(m.horiz.0 as f32, m.vert.0 as f32);
+        let inset_b = Vec2(m.horiz.1 as f32, m.vert.1 as f32);
+        let (src_a, src_b) = (inset_a * *scale / size, inset_b * *scale / size);
+
+        // Borders are reduced where the target is too small
+        let (mut dst_a, mut dst_b) = (inset_a * scale_factor, inset_b * scale_factor);
+        let fit =
+            (outer.size() / (dst_a + dst_b).max(Vec2::splat(f32::EPSILON))).min(Vec2::splat(1.0));
+        dst_a = dst_a * fit;
+        dst_b = dst_b * fit;
+
+        let xs = [
+            outer.a.0,
+            outer.a.0 + dst_a.0,
+            outer.b.0 - dst_b.0,
+            outer.b.0,
+        ];
+        let ys = [
+            outer.a.1,
+            outer.a.1 + dst_a.1,
+            outer.b.1 - dst_b.1,
+            outer.b.1,
+        ];
+        let us = [0.0, src_a.0, 1.0 - src_b.0, 1.0];
+        let vs = [0.0, src_a.1, 1.0 - src_b.1, 1.0];
+        for i in 0..3 {
+            for j in 0..3 {
+                let rect = Quad::with_coords(Vec2(xs[i], ys[j]), Vec2(xs[i + 1], ys[j + 1]));
+                let region = Quad::with_coords(Vec2(us[i], vs[j]), Vec2(us[i + 1], vs[j + 1]));
+                draw.image_region(pass, rect, image, region);
+            }
+        }
+    }
+}
+
+/// A set of nine-patch images for an element, by input state
+#[derive(Clone, Debug)]
+pub struct StatePatch {
+    /// Image used in the normal state (and for states without an image)
+    pub normal: NinePatch,
+    /// Image used when hovered by the mouse
+    pub hover: Option<NinePatch>,
+    /// Image used when depressed
+    pub depress: Option<NinePatch>,
+    /// Image used when disabled
+    pub disabled: Option<NinePatch>,
+}
+
+impl StatePatch {
+    /// Construct, with only a normal-state image
+    pub fn new(normal: NinePatch) -> Self {
+        StatePatch {
+            normal,
+            hover: None,
+            depress: None,
+            disabled: None,
+        }
+    }
+
+    /// Set the hover image (chain style)
+    pub fn with_hover(mut self, patch: NinePatch) -> Self {
+        self.hover = Some(patch);
+        self
+    }
+
+    /// Set the depressed image (chain style)
+    pub fn with_depress(mut self, patch: NinePatch) -> Self {
+        self.depress = Some(patch);
+        self
+    }
+
+    /// Set the disabled image (chain style)
+    pub fn with_disabled(mut self, patch: NinePatch) -> Self {
+        self.disabled = Some(patch);
+        self
+    }
+
+    /// Get the image for the given `state`
+    pub fn get(&self, state: InputState) -> &NinePatch {
+        let patch = if state.disabled {
+            self.disabled.as_ref()
+        } else if state.depress {
+            self.depress.as_ref()
+        } else if state.hover {
+            self.hover.as_ref()
+        } else {
+            None
+        };
+        patch.unwrap_or(&self.normal)
+    }
+}
+
+/// Images used by [`NinePatchTheme`]
+///
+/// Elements without an image are drawn as by [`FlatTheme`].
+#[derive(Clone, Debug, Default)]
+pub struct Skin {
+    /// Frame around grouped content
+    pub frame: Option<NinePatch>,
+    /// Frame and background of pop-up menus
+    pub menu_frame: Option<NinePatch>,
+    /// Button background
+    pub button: Option<StatePatch>,
+    /// Slider track, drawn over the whole slider area
+    ///
+    /// Include transparent padding in the image for a thinner track.
+    pub slider_track: Option<NinePatch>,
+    /// Scrollbar track, drawn over the whole scrollbar area
+    pub scrollbar_track: Option<NinePatch>,
+}
+
+/// A theme rendering elements from application-supplied images
+///
+/// Frames, buttons and slider and scrollbar tracks are drawn from the
+/// nine-patch images of a [`Skin`]; all other elements (and elements without
+/// an image) are drawn by an inner [`FlatTheme`], which also provides fonts,
+/// colours and dimensions. Theme dimensions (e.g. frame sizes) are not
+/// derived from the images; use [`ThemeConfig::dimensions`] to match them.
+///
+/// ```
+/// use kas::draw::IconImage;
+/// use kas::geom::Size;
+/// use kas::layout::Margins;
+/// use kas_theme::{NinePatch, NinePatchTheme, Skin, StatePatch};
+///
+/// let image = IconImage::from_rgba(Size(9, 9), vec![255; 4 * 81]).unwrap();
+/// let button = NinePatch::new(image, Margins::uniform(3));
+/// let skin = Skin {
+///     button: Some(StatePatch::new(button)),
+///     ..Default::default()
+/// };
+/// let theme = NinePatchTheme::new(skin);
+/// # let _ = theme;
+/// ```
+#[derive(Clone, Debug)]
+pub struct NinePatchTheme {
+    inner: FlatTheme,
+    skin: Skin,
+}
+
+impl NinePatchTheme {
+    /// Construct
+    pub fn new(skin: Skin) -> Self {
+        NinePatchTheme {
+            inner: FlatTheme::new(),
+            skin,
+        }
+    }
+
+    /// Set the theme used for other elements (chain style)
+    pub fn with_theme(mut self, theme: FlatTheme) -> Self {
+        self.inner = theme;
+        self
+    }
+
+    /// Get the skin
+    pub fn skin(&self) -> &Skin {
+        &self.skin
+    }
+
+    /// Set the skin
+    pub fn set_skin(&mut self, skin: Skin) -> ThemeAction {
+        self.skin = skin;
+        ThemeAction::RedrawAll
+    }
+}
+
+pub struct DrawHandle<'a, D: Draw> {
+    inner: flat_theme::DrawHandle<'a, D>,
+    skin: &'a Skin,
+}
+
+impl<D: DrawShared + 'static> Theme<D> for NinePatchTheme
+where
+    D::Draw: DrawRounded + DrawText,
+{
+    type Window = DimensionsWindow;
+
+    #[cfg(not(feature = "gat"))]
+    type DrawHandle = DrawHandle<'static, D::Draw>;
+    #[cfg(feature = "gat")]
+    type DrawHandle<'a> = DrawHandle<'a, D::Draw>;
+
+    fn init(&mut self, draw: &mut D) {
+        self.inner.init(draw);
+    }
+
+    fn new_window(&self, draw: &mut D::Draw, dpi_factor: f32) -> Self::Window {
+        Theme::<D>::new_window(&self.inner, draw, dpi_factor)
+    }
+
+    fn update_window(&self, window: &mut Self::Window, dpi_factor: f32) {
+        Theme::<D>::update_window(&self.inner, window, dpi_factor);
+    }
+
+    #[cfg(not(feature = "gat"))]
+    unsafe fn draw_handle<'a>(
+        &'a self,
+        draw: &'a mut D::Draw,
+        window: &'a mut Self::Window,
+        rect: Rect,
+    ) -> Self::DrawHandle {
+        // We extend lifetimes (unsafe) due to the lack of associated type generics.
+        use std::mem::transmute;
+        DrawHandle {
+            inner: Theme::<D>::draw_handle(&self.inner, draw, window, rect),
+            skin: transmute::<&'a Skin, &'static Skin>(&self.skin),
+        }
+    }
+    #[cfg(feature = "gat")]
+    fn draw_handle<'a>(
+        &'a self,
+        draw: &'a mut D::Draw,
+        window: &'a mut Self::Window,
+        rect: Rect,
+    ) -> Self::DrawHandle<'a> {
+        DrawHandle {
+            inner: Theme::<D>::draw_handle(&self.inner, draw, window, rect),
+            skin: &self.skin,
+        }
+    }
+
+    fn animate(&mut self) -> bool {
+        Theme::<D>::animate(&mut self.inner)
+    }
+
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        Theme::<D>::apply_config(&mut self.inner, config)
+    }
+
+    fn clear_colour(&self) -> Colour {
+        Theme::<D>::clear_colour(&self.inner)
+    }
+}
+
+impl ThemeApi for NinePatchTheme {
+    fn set_font_size(&mut self, size: f32) -> ThemeAction {
+        self.inner.set_font_size(size)
+    }
+
+    fn set_colours(&mut self, scheme: &str) -> ThemeAction {
+        self.inner.set_colours(scheme)
+    }
+
+    fn set_colour_scheme(&mut self, scheme: &ColourScheme) -> ThemeAction {
+        self.inner.set_colour_scheme(scheme)
+    }
+
+    fn set_dark_mode(&mut self, dark: bool) -> ThemeAction {
+        self.inner.set_dark_mode(dark)
+    }
+}
+
+impl<'a, D: Draw> DrawHandle<'a, D> {
+    /// Draw `patch` over `rect`
+    fn draw_patch(&mut self, rect: Rect, patch: &NinePatch) {
+        let inner = &mut self.inner;
+        let outer = Quad::from(rect + inner.offset);
+        let scale_factor = inner.window.dims.scale_factor;
+        patch.draw(inner.draw, inner.pass, outer, scale_factor);
+    }
+}
+
+impl<'a, D: Draw + DrawRounded + DrawText> draw::DrawHandle for DrawHandle<'a, D> {
+    fn size_handle_dyn(&mut self, f: &mut dyn FnMut(&mut dyn SizeHandle)) {
+        self.inner.size_handle_dyn(f)
+    }
+
+    fn draw_device(&mut self) -> (Pass, Coord, &mut dyn Draw) {
+        self.inner.draw_device()
+    }
+
+    fn clip_region(
+        &mut self,
+        rect: Rect,
+        offset: Coord,
+        class: ClipRegion,
+        f: &mut dyn FnMut(&mut dyn draw::DrawHandle),
+    ) {
+        let inner = &mut self.inner;
+        let mut rect = rect + inner.offset;
+        if class != ClipRegion::Popup {
+            rect = rect.intersection(&inner.rect);
+        }
+        let depth = inner.pass.depth() + super::relative_region_depth(class);
+        let pass = inner.draw.add_clip_region(rect, depth);
+        if depth < inner.pass.depth() {
+            // draw to depth buffer to enable correct text rendering
+            inner
+                .draw
+                .rect(pass, (rect + inner.offset).into(), inner.cols.background);
+        }
+        let mut handle = DrawHandle {
+            inner: flat_theme::DrawHandle {
+                draw: inner.draw,
+                window: inner.window,
+                cols: inner.cols,
+                style: inner.style,
+                rect,
+                offset: inner.offset - offset,
+                pass,
+            },
+            skin: self.skin,
+        };
+        f(&mut handle);
+    }
+
+    fn cached_region(
+        &mut self,
+        key: u64,
+        rect: Rect,
+        valid: bool,
+        f: &mut dyn FnMut(&mut dyn draw::DrawHandle),
+    ) {
+        let inner = &mut self.inner;
+        let rect = (rect + inner.offset).intersection(&inner.rect);
+        let valid = valid && inner.window.anim.cache_valid(key);
+        if let Some(pass) = inner.draw.add_cached_region(key, rect, inner.pass, valid) {
+            let token = inner.window.anim.begin_cache();
+            let mut handle = DrawHandle {
+                inner: flat_theme::DrawHandle {
+                    draw: inner.draw,
+                    window: inner.window,
+                    cols: inner.cols,
+                    style: inner.style,
+                    rect,
+                    offset: inner.offset,
+                    pass,
+                },
+                skin: self.skin,
+            };
+            f(&mut handle);
+            inner.window.anim.end_cache(key, token);
+        }
+    }
+
+    fn layer(&mut self, rect: Rect, layer: Layer, f: &mut dyn FnMut(&mut dyn draw::DrawHandle)) {
+        let inner = &mut self.inner;
+        let rect = (rect + inner.offset).intersection(&inner.rect);
+        let pass = inner.draw.add_layer(rect, inner.pass, layer);
+        let mut handle = DrawHandle {
+            inner: flat_theme::DrawHandle {
+                draw: inner.draw,
+                window: inner.window,
+                cols: inner.cols,
+                style: inner.style,
+                rect,
+                offset: inner.offset,
+                pass,
+            },
+            skin: self.skin,
+        };
+        f(&mut handle);
+    }
+
+    fn target_rect(&self) -> Rect {
+        self.inner.target_rect()
+    }
+
+    fn outer_frame(&mut self, rect: Rect) {
+        let skin = self.skin;
+        match skin.frame.as_ref() {
+            Some(patch) => self.draw_patch(rect, patch),
+            None => self.inner.outer_frame(rect),
+        }
+    }
+
+    fn menu_frame(&mut self, rect: Rect) {
+        let skin = self.skin;
+        match skin.menu_frame.as_ref() {
+            Some(patch) => self.draw_patch(rect, patch),
+            None => self.inner.menu_frame(rect),
+        }
+    }
+
+    fn separator(&mut self, rect: Rect) {
+        self.inner.separator(rect);
+    }
+
+    fn text_offset(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+    ) {
+        self.inner.text_offset(pos, offset, text, class, state);
+    }
+
+    fn text_with_underline(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+        underline: usize,
+    ) {
+        self.inner
+            .text_with_underline(pos, offset, text, class, state, underline);
+    }
+
+    fn text_selected_range(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        range: Range<usize>,
+        class: TextClass,
+        state: InputState,
+    ) {
+        self.inner
+            .text_selected_range(pos, offset, text, range, class, state);
+    }
+
+    fn text_highlighted(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+        highlights: &[TextHighlight],
+    ) {
+        self.inner
+            .text_highlighted(pos, offset, text, class, state, highlights);
+    }
+
+    fn edit_marker(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        byte: usize,
+    ) {
+        self.inner.edit_marker(pos, offset, text, class, byte);
+    }
+
+    fn menu_entry(&mut self, rect: Rect, state: InputState) {
+        self.inner.menu_entry(rect, state);
+    }
+
+    fn button(&mut self, rect: Rect, state: InputState) {
+        let skin = self.skin;
+        match skin.button.as_ref() {
+            Some(patches) => {
+                self.draw_patch(rect, patches.get(state));
+                self.inner.focus_ring(rect, HighlightShape::Rect, state);
+            }
+            None => self.inner.button(rect, state),
+        }
+    }
+
+    fn edit_box(&mut self, rect: Rect, state: InputState) {
+        self.inner.edit_box(rect, state);
+    }
+
+    fn checkbox(&mut self, rect: Rect, checked: bool, state: InputState) {
+        self.inner.checkbox(rect, checked, state);
+    }
+
+    fn radiobox(&mut self, rect: Rect, checked: bool, state: InputState) {
+        self.inner.radiobox(rect, checked, state);
+    }
+
+    fn scrollbar(&mut self, rect: Rect, h_rect: Rect, dir: Direction, state: InputState) {
+        let skin = self.skin;
+        match skin.scrollbar_track.as_ref() {
+            Some(patch) => {
+                self.draw_patch(rect, patch);
+                let inner = &mut self.inner;
+                let cols = inner.style.resolve(inner.cols, Element::ScrollBar, state);
+                inner.draw_handle(h_rect, &cols, state);
+            }
+            None => self.inner.scrollbar(rect, h_rect, dir, state),
+        }
+    }
+
+    fn slider(&mut self, rect: Rect, h_rect: Rect, dir: Direction, state: InputState) {
+        let skin = self.skin;
+        match skin.slider_track.as_ref() {
+            Some(patch) => {
+                self.draw_patch(rect, patch);
+                let inner = &mut self.inner;
+                let cols = inner.style.resolve(inner.cols, Element::Slider, state);
+                inner.draw_handle(h_rect, &cols, state);
+            }
+            None => self.inner.slider(rect, h_rect, dir, state),
+        }
+    }
+
+    fn icon(&mut self, rect: Rect, icon: &Icon, state: InputState) {
+        self.inner.icon(rect, icon, state);
+    }
+
+    fn image(&mut self, rect: Rect, id: ImageId) {
+        self.inner.image(rect, id);
+    }
+
+    fn fill_path(&mut self, path: &Path, rule: FillRule, col: Colour) {
+        self.inner.fill_path(path, rule, col);
+    }
+
+    fn stroke_path(&mut self, path: &Path, style: &StrokeStyle, col: Colour) {
+        self.inner.stroke_path(path, style, col);
+    }
+
+    fn selection(&mut self, rect: Rect, shape: HighlightShape, state: InputState) {
+        self.inner.selection(rect, shape, state);
+    }
+
+    fn focus_ring(&mut self, rect: Rect, shape: HighlightShape, state: InputState) {
+        self.inner.focus_ring(rect, shape, state);
+    }
+
+    fn shadow(&mut self, rect: Rect) {
+        self.inner.shadow(rect);
+    }
+
+    fn well(&mut self, rect: Rect, state: InputState) {
+        self.inner.well(rect, state);
+    }
+
+    fn badge(&mut self, rect: Rect, text: &PreparedText, state: InputState) {
+        self.inner.badge(rect, text, state);
+    }
+}
@@ -7,40 +7,139 @@
 
 use std::f32;
 use std::ops::Range;
+use std::time::Duration;
 
-use crate::{Dimensions, DimensionsParams, DimensionsWindow, Theme, ThemeColours, Window};
+use crate::stylesheet::Element;
+use crate::{
+    anim, ColourFade, ColourScheme, Dimensions, DimensionsParams, DimensionsWindow, FontConfig,
+    Fonts, StyleSheet, Theme, ThemeColours, ThemeConfig, Window,
+};
 use kas::draw::{
-    self, ClipRegion, Colour, Draw, DrawRounded, DrawShaded, DrawShared, DrawText, InputState,
-    Pass, SizeHandle, TextClass,
+    self, ClipRegion, Colour, Draw, DrawRounded, DrawShaded, DrawShared, DrawText, FillRule,
+    HighlightShape, Icon, ImageId, InputState, Layer, Pass, Path, SizeHandle, StrokeStyle,
+    TextClass, TextHighlight,
 };
 use kas::geom::*;
-use kas::text::{FontId, PreparedText};
+use kas::layout::Length;
+use kas::text::PreparedText;
 use kas::{Direction, Directional, ThemeAction, ThemeApi};
 
 /// A theme using simple shading to give apparent depth to elements
 #[derive(Clone, Debug)]
 pub struct ShadedTheme {
-    font_id: FontId,
+    font_config: FontConfig,
+    fonts: Fonts,
     font_size: f32,
-    cols: ThemeColours,
+    dims: DimensionsParams,
+    cols: ColourFade,
+    follow_system: bool,
+    style: StyleSheet,
 }
 
 impl ShadedTheme {
     /// Construct
     pub fn new() -> Self {
         ShadedTheme {
-            font_id: Default::default(),
+            font_config: FontConfig::new(),
+            fonts: Default::default(),
             font_size: 12.0,
-            cols: ThemeColours::new(),
+            dims: DIMS,
+            cols: ColourFade::new(ThemeColours::new()),
+            follow_system: true,
+            style: StyleSheet::new(),
+        }
+    }
+
+    /// Set the duration of colour-scheme transitions (chain style)
+    ///
+    /// When the colour scheme changes, colours cross-fade over this duration
+    /// (default: 250ms). A duration of zero switches colours instantly, as
+    /// does the [`crate::reduce_motion`] setting.
+    pub fn with_colour_transition(mut self, duration: Duration) -> Self {
+        self.cols.set_duration(duration);
+        self
+    }
+
+    /// Set whether to follow the system's dark-mode preference (chain style)
+    ///
+    /// When enabled (the default), the "dark" or "default" colour scheme is
+    /// selected to match the preference reported by the toolkit. Choosing a
+    /// scheme via [`ThemeApi::set_colours`] disables this.
+    pub fn with_system_colours(mut self, follow: bool) -> Self {
+        self.follow_system = follow;
+        self
+    }
+
+    /// Set the font configuration (chain style)
+    ///
+    /// Fonts are loaded when the theme is initialised by the toolkit.
+    pub fn with_fonts(mut self, config: FontConfig) -> Self {
+        self.font_config = config;
+        self
+    }
+
+    /// Set the font size (chain style)
+    ///
+    /// See also [`ThemeApi::set_font_size`].
+    pub fn with_font_size(mut self, size: f32) -> Self {
+        self.font_size = size;
+        self
+    }
+
+    /// Apply a theme configuration (chain style)
+    ///
+    /// Colours, if given, are set immediately. See also
+    /// [`Theme::apply_config`].
+    pub fn with_config(mut self, config: &ThemeConfig) -> Self {
+        if let Some(fonts) = config.fonts.as_ref() {
+            self.font_config = fonts.clone();
+        }
+        if let Some(size) = config.font_size {
+            self.font_size = size;
+        }
+        self.dims = config.dimensions.clone().unwrap_or(DIMS);
+        if let Some(cols) = config.colours.as_ref() {
+            self.cols.reset(cols.clone());
+            self.follow_system = false;
+        }
+        self
+    }
+
+    /// Set the colour scheme (chain style)
+    ///
+    /// Colours are set immediately. This disables following the system's
+    /// dark-mode preference.
+    pub fn with_colour_scheme(mut self, scheme: &ColourScheme) -> Self {
+        self.cols.reset(ThemeColours::from_scheme(scheme));
+        self.follow_system = false;
+        self
+    }
+
+    /// Set the style sheet (chain style)
+    ///
+    /// See [`StyleSheet`] for details.
+    pub fn with_stylesheet(mut self, style: StyleSheet) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the style sheet
+    pub fn set_stylesheet(&mut self, style: StyleSheet) -> ThemeAction {
+        let resize = style.params_differ(&self.style);
+        self.style = style;
+        if resize {
+            ThemeAction::ThemeResize
+        } else {
+            ThemeAction::RedrawAll
         }
     }
 }
 
 const DIMS: DimensionsParams = DimensionsParams {
-    outer_margin: 6.0,
-    inner_margin: 1.0,
-    frame_size: 5.0,
-    button_frame: 5.0,
+    outer_margin: Length::Px(6.0),
+    inner_margin: Length::Px(1.0),
+    frame_size: Length::Px(5.0),
+    button_frame: Length::Px(5.0),
     scrollbar_size: Vec2::splat(8.0),
     slider_size: Vec2(12.0, 25.0),
 };
@@ -49,6 +148,7 @@ pub struct DrawHandle<'a, D: Draw> {
     draw: &'a mut D,
     window: &'a mut DimensionsWindow,
     cols: &'a ThemeColours,
+    style: &'a StyleSheet,
     rect: Rect,
     offset: Coord,
     pass: Pass,
@@ -66,15 +166,19 @@ where
     type DrawHandle<'a> = DrawHandle<'a, D::Draw>;
 
     fn init(&mut self, _draw: &mut D) {
-        self.font_id = kas::text::fonts().load_default().unwrap();
+        self.fonts = self.font_config.load();
     }
 
     fn new_window(&self, _draw: &mut D::Draw, dpi_factor: f32) -> Self::Window {
-        DimensionsWindow::new(DIMS, self.font_id, self.font_size, dpi_factor)
+        let dims = self.style.dims(&self.dims);
+        let font_size = self.style.font_size(self.font_size);
+        DimensionsWindow::new(dims, &self.fonts, font_size, dpi_factor)
     }
 
     fn update_window(&self, window: &mut Self::Window, dpi_factor: f32) {
-        window.dims = Dimensions::new(DIMS, self.font_id, self.font_size, dpi_factor);
+        let dims = self.style.dims(&self.dims);
+        let font_size = self.style.font_size(self.font_size);
+        window.dims = Dimensions::new(dims, &self.fonts, font_size, dpi_factor);
     }
 
     #[cfg(not(feature = "gat"))]
@@ -84,12 +188,15 @@ where
         window: &'a mut Self::Window,
         rect: Rect,
     ) -> Self::DrawHandle {
+        window.anim.new_frame();
+        draw.set_fallback_fonts(self.fonts.fallback());
         // We extend lifetimes (unsafe) due to the lack of associated type generics.
         use std::mem::transmute;
         DrawHandle {
             draw: transmute::<&'a mut D::Draw, &'static mut D::Draw>(draw),
             window: transmute::<&'a mut Self::Window, &'static mut Self::Window>(window),
-            cols: transmute::<&'a ThemeColours, &'static ThemeColours>(&self.cols),
+            cols: transmute::<&'a ThemeColours, &'static ThemeColours>(self.cols.current()),
+            style: transmute::<&'a StyleSheet, &'static StyleSheet>(&self.style),
             rect,
             offset: Coord::ZERO,
             pass: super::START_PASS,
@@ -102,18 +209,51 @@ where
         window: &'a mut Self::Window,
         rect: Rect,
     ) -> Self::DrawHandle<'a> {
+        window.anim.new_frame();
+        draw.set_fallback_fonts(self.fonts.fallback());
         DrawHandle {
             draw,
             window,
-            cols: &self.cols,
+            cols: self.cols.current(),
+            style: &self.style,
             rect,
             offset: Coord::ZERO,
             pass: super::START_PASS,
         }
     }
 
+    fn animate(&mut self) -> bool {
+        self.cols.update()
+    }
+
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        let mut action = ThemeAction::None;
+        if let Some(cols) = config.colours.as_ref() {
+            if cols != self.cols.target() {
+                self.cols.set(cols.clone());
+                self.follow_system = false;
+                action = ThemeAction::RedrawAll;
+            }
+        }
+        if let Some(fonts) = config.fonts.as_ref() {
+            if *fonts != self.font_config {
+                self.font_config = fonts.clone();
+                self.fonts = self.font_config.load();
+                action = ThemeAction::ThemeResize;
+            }
+        }
+        let font_size = config.font_size.unwrap_or(self.font_size);
+        let dims = config.dimensions.clone().unwrap_or(DIMS);
+        if font_size != self.font_size || dims != self.dims {
+            self.font_size = font_size;
+            self.dims = dims;
+            action = ThemeAction::ThemeResize;
+        }
+        action
+    }
+
     fn clear_colour(&self) -> Colour {
-        self.cols.background
+        self.cols.current().background
     }
 }
 
@@ -125,7 +265,27 @@ impl ThemeApi for ShadedTheme {
 
     fn set_colours(&mut self, scheme: &str) -> ThemeAction {
         if let Some(scheme) = ThemeColours::open(scheme) {
-            self.cols = scheme;
+            self.follow_system = false;
+            self.cols.set(scheme);
+            ThemeAction::RedrawAll
+        } else {
+            ThemeAction::None
+        }
+    }
+
+    fn set_colour_scheme(&mut self, scheme: &ColourScheme) -> ThemeAction {
+        self.cols.set(ThemeColours::from_scheme(scheme));
+        self.follow_system = false;
+        ThemeAction::RedrawAll
+    }
+
+    fn set_dark_mode(&mut self, dark: bool) -> ThemeAction {
+        if !self.follow_system {
+            return ThemeAction::None;
+        }
+        let scheme = if dark { "dark" } else { "default" };
+        if let Some(scheme) = ThemeColours::open(scheme) {
+            self.cols.set(scheme);
             ThemeAction::RedrawAll
         } else {
             ThemeAction::None
@@ -144,6 +304,7 @@ impl<'a, D: Draw + DrawRounded + DrawShaded> DrawHandle<'a, D> {
             draw: *&mut self.draw,
             window: *&mut self.window,
             cols: *&self.cols,
+            style: *&self.style,
             rect: self.rect,
             offset: self.offset,
             pass: self.pass,
@@ -154,14 +315,21 @@ impl<'a, D: Draw + DrawRounded + DrawShaded> DrawHandle<'a, D> {
     /// Return the inner rect.
     ///
     /// - `outer`: define position via outer rect
+    /// - `frame_col`: colour of frame
     /// - `bg_col`: colour of background
     /// - `nav_col`: colour of navigation highlight, if visible
-    fn draw_edit_box(&mut self, outer: Rect, bg_col: Colour, nav_col: Option<Colour>) -> Quad {
+    fn draw_edit_box(
+        &mut self,
+        outer: Rect,
+        frame_col: Colour,
+        bg_col: Colour,
+        nav_col: Option<Colour>,
+    ) -> Quad {
         let mut outer = Quad::from(outer);
         let mut inner = outer.shrink(self.window.dims.frame as f32);
 
         self.draw
-            .shaded_square_frame(self.pass, outer, inner, (-0.6, 0.0), self.cols.background);
+            .shaded_square_frame(self.pass, outer, inner, (-0.6, 0.0), frame_col);
 
         if let Some(col) = nav_col {
             outer = inner;
@@ -174,15 +342,15 @@ impl<'a, D: Draw + DrawRounded + DrawShaded> DrawHandle<'a, D> {
     }
 
     /// Draw a handle (for slider, scrollbar)
-    fn draw_handle(&mut self, rect: Rect, state: InputState) {
+    fn draw_handle(&mut self, rect: Rect, cols: &ThemeColours, state: InputState) {
         let outer = Quad::from(rect + self.offset);
         let thickness = outer.size().min_comp() / 2.0;
         let inner = outer.shrink(thickness);
-        let col = self.cols.scrollbar_state(state);
+        let col = cols.scrollbar_state(state);
         self.draw
             .shaded_round_frame(self.pass, outer, inner, (0.0, 0.6), col);
 
-        if let Some(col) = self.cols.nav_region(state) {
+        if let Some(col) = cols.nav_region(state) {
             let outer = outer.shrink(thickness / 4.0);
             self.draw
                 .rounded_frame(self.pass, outer, inner, 2.0 / 3.0, col);
@@ -212,7 +380,10 @@ where
         class: ClipRegion,
         f: &mut dyn FnMut(&mut dyn draw::DrawHandle),
     ) {
-        let rect = rect + self.offset;
+        let mut rect = rect + self.offset;
+        if class != ClipRegion::Popup {
+            rect = rect.intersection(&self.rect);
+        }
         let depth = self.pass.depth() + super::relative_region_depth(class);
         let pass = self.draw.add_clip_region(rect, depth);
         if depth < self.pass.depth() {
@@ -224,6 +395,7 @@ where
             draw: self.draw,
             window: self.window,
             cols: self.cols,
+            style: self.style,
             rect,
             offset: self.offset - offset,
             pass,
@@ -231,6 +403,46 @@ where
         f(&mut handle);
     }
 
+    fn cached_region(
+        &mut self,
+        key: u64,
+        rect: Rect,
+        valid: bool,
+        f: &mut dyn FnMut(&mut dyn draw::DrawHandle),
+    ) {
+        let rect = (rect + self.offset).intersection(&self.rect);
+        let valid = valid && self.window.anim.cache_valid(key);
+        if let Some(pass) = self.draw.add_cached_region(key, rect, self.pass, valid) {
+            let token = self.window.anim.begin_cache();
+            let mut handle = DrawHandle {
+                draw: self.draw,
+                window: self.window,
+                cols: self.cols,
+                style: self.style,
+                rect,
+                offset: self.offset,
+                pass,
+            };
+            f(&mut handle);
+            self.window.anim.end_cache(key, token);
+        }
+    }
+
+    fn layer(&mut self, rect: Rect, layer: Layer, f: &mut dyn FnMut(&mut dyn draw::DrawHandle)) {
+        let rect = (rect + self.offset).intersection(&self.rect);
+        let pass = self.draw.add_layer(rect, self.pass, layer);
+        let mut handle = DrawHandle {
+            draw: self.draw,
+            window: self.window,
+            cols: self.cols,
+            style: self.style,
+            rect,
+            offset: self.offset,
+            pass,
+        };
+        f(&mut handle);
+    }
+
     fn target_rect(&self) -> Rect {
         // Translate to local coordinates
         self.rect - self.offset
@@ -240,7 +452,10 @@ where
         let outer = Quad::from(rect + self.offset);
         let inner = outer.shrink(self.window.dims.frame as f32);
         let norm = (0.7, -0.7);
-        let col = self.cols.background;
+        let cols = self
+            .style
+            .resolve(self.cols, Element::Frame, Default::default());
+        let col = cols.background;
         self.draw
             .shaded_round_frame(self.pass, outer, inner, norm, col);
     }
@@ -249,23 +464,36 @@ where
         let outer = Quad::from(rect + self.offset);
         let inner = outer.shrink(self.window.dims.frame as f32);
         let norm = (0.7, 0.0);
-        let col = self.cols.background;
+        let cols = self
+            .style
+            .resolve(self.cols, Element::MenuFrame, Default::default());
+        let col = cols.background;
         self.draw
             .shaded_round_frame(self.pass, outer, inner, norm, col);
-        self.draw.rect(self.pass, inner, self.cols.background);
+        self.draw.rect(self.pass, inner, cols.background);
     }
 
     fn separator(&mut self, rect: Rect) {
         let outer = Quad::from(rect + self.offset);
         let inner = outer.shrink(outer.size().min_comp() / 2.0);
         let norm = (0.0, -0.7);
-        let col = self.cols.background;
+        let cols = self
+            .style
+            .resolve(self.cols, Element::Separator, Default::default());
+        let col = cols.background;
         self.draw
             .shaded_round_frame(self.pass, outer, inner, norm, col);
     }
 
-    fn text_offset(&mut self, pos: Coord, offset: Coord, text: &PreparedText, class: TextClass) {
-        self.as_flat().text_offset(pos, offset, text, class);
+    fn text_offset(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+    ) {
+        self.as_flat().text_offset(pos, offset, text, class, state);
     }
 
     fn text_with_underline(
@@ -274,10 +502,11 @@ where
         offset: Coord,
         text: &PreparedText,
         class: TextClass,
+        state: InputState,
         underline: usize,
     ) {
         self.as_flat()
-            .text_with_underline(pos, offset, text, class, underline);
+            .text_with_underline(pos, offset, text, class, state, underline);
     }
 
     fn text_selected_range(
@@ -287,9 +516,23 @@ where
         text: &PreparedText,
         range: Range<usize>,
         class: TextClass,
+        state: InputState,
     ) {
         self.as_flat()
-            .text_selected_range(pos, offset, text, range, class);
+            .text_selected_range(pos, offset, text, range, class, state);
+    }
+
+    fn text_highlighted(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+        highlights: &[TextHighlight],
+    ) {
+        self.as_flat()
+            .text_highlighted(pos, offset, text, class, state, highlights);
     }
 
     fn edit_marker(
@@ -310,72 +553,132 @@ where
     fn button(&mut self, rect: Rect, state: InputState) {
         let outer = Quad::from(rect + self.offset);
         let inner = outer.shrink(self.window.dims.button_frame as f32);
-        let col = self.cols.button_state(state);
+        let cols = self.style.resolve(self.cols, Element::Button, state);
+        let mut col = cols.button_state(state);
+        let mut norm = (0.0, 0.6);
+
+        if !state.disabled && !state.depress {
+            // Hover feedback: fade between normal and highlighted colours
+            let h = self.window.anim.hover(rect, state.hover);
+            col = anim::blend(cols.button, cols.button_highlighted, h);
+        }
+
+        let p = self
+            .window
+            .anim
+            .press(rect, state.depress && !state.disabled);
+        if p < 1.0 {
+            // Press feedback: fade in depressed colour and briefly flatten
+            col = anim::blend(cols.button_highlighted, col, p);
+            norm.1 *= 1.0 - 0.5 * (p * f32::consts::PI).sin();
+        }
 
         self.draw
-            .shaded_round_frame(self.pass, outer, inner, (0.0, 0.6), col);
+            .shaded_round_frame(self.pass, outer, inner, norm, col);
         self.draw.rect(self.pass, inner, col);
 
-        if let Some(col) = self.cols.nav_region(state) {
-            let outer = outer.shrink(self.window.dims.button_frame as f32 / 3.0);
+        if let Some(col) = cols.nav_region(state) {
+            let outer = outer.shrink_snapped(self.window.dims.button_frame as f32 / 3.0);
             self.draw.rounded_frame(self.pass, outer, inner, 0.5, col);
         }
     }
 
     fn edit_box(&mut self, rect: Rect, state: InputState) {
-        let bg_col = self.cols.bg_col(state);
-        self.draw_edit_box(rect + self.offset, bg_col, self.cols.nav_region(state));
+        let cols = self.style.resolve(self.cols, Element::EditBox, state);
+        let bg_col = cols.bg_col(state);
+        let nav_col = cols.nav_region(state);
+        self.draw_edit_box(rect + self.offset, cols.background, bg_col, nav_col);
     }
 
     fn checkbox(&mut self, rect: Rect, checked: bool, state: InputState) {
-        let bg_col = self.cols.bg_col(state);
-        let nav_col = self.cols.nav_region(state).or(Some(bg_col));
+        let cols = self.style.resolve(self.cols, Element::CheckBox, state);
+        let bg_col = cols.bg_col(state);
+        let nav_col = cols.nav_region(state).or(Some(bg_col));
 
-        let inner = self.draw_edit_box(rect + self.offset, bg_col, nav_col);
+        let inner = self.draw_edit_box(rect + self.offset, cols.background, bg_col, nav_col);
 
-        if let Some(col) = self.cols.check_mark_state(state, checked) {
+        // Toggle feedback: the mark grows in (also while depressed) and
+        // shrinks out
+        let t = self.window.anim.toggle(rect, checked || state.depress);
+        if let Some(col) = cols.check_mark_state(state, checked || t > 0.0) {
+            let inner = anim::scale(inner, t);
             self.draw.shaded_square(self.pass, inner, (0.0, 0.4), col);
         }
     }
 
     fn radiobox(&mut self, rect: Rect, checked: bool, state: InputState) {
-        let bg_col = self.cols.bg_col(state);
-        let nav_col = self.cols.nav_region(state).or(Some(bg_col));
+        let cols = self.style.resolve(self.cols, Element::RadioBox, state);
+        let bg_col = cols.bg_col(state);
+        let nav_col = cols.nav_region(state).or(Some(bg_col));
 
-        let inner = self.draw_edit_box(rect + self.offset, bg_col, nav_col);
+        let inner = self.draw_edit_box(rect + self.offset, cols.background, bg_col, nav_col);
 
-        if let Some(col) = self.cols.check_mark_state(state, checked) {
+        let t = self.window.anim.toggle(rect, checked || state.depress);
+        if let Some(col) = cols.check_mark_state(state, checked || t > 0.0) {
+            let inner = anim::scale(inner, t);
             self.draw.shaded_circle(self.pass, inner, (0.0, 1.0), col);
         }
     }
 
     fn scrollbar(&mut self, rect: Rect, h_rect: Rect, _dir: Direction, state: InputState) {
+        let cols = self.style.resolve(self.cols, Element::ScrollBar, state);
         // track
         let outer = Quad::from(rect + self.offset);
         let inner = outer.shrink(outer.size().min_comp() / 2.0);
         let norm = (0.0, -0.7);
-        let col = self.cols.background;
+        let col = cols.background;
         self.draw
             .shaded_round_frame(self.pass, outer, inner, norm, col);
 
         // handle
-        self.draw_handle(h_rect, state);
+        self.draw_handle(h_rect, &cols, state);
     }
 
     fn slider(&mut self, rect: Rect, h_rect: Rect, dir: Direction, state: InputState) {
+        let cols = self.style.resolve(self.cols, Element::Slider, state);
         // track
         let mut outer = Quad::from(rect + self.offset);
         outer = match dir.is_horizontal() {
             true => outer.shrink_vec(Vec2(0.0, outer.size().1 * (3.0 / 8.0))),
             false => outer.shrink_vec(Vec2(outer.size().0 * (3.0 / 8.0), 0.0)),
-        };
+        }
+        .snap();
         let inner = outer.shrink(outer.size().min_comp() / 2.0);
         let norm = (0.0, -0.7);
-        let col = self.cols.background;
+        let col = cols.background;
         self.draw
             .shaded_round_frame(self.pass, outer, inner, norm, col);
 
         // handle
-        self.draw_handle(h_rect, state);
+        self.draw_handle(h_rect, &cols, state);
+    }
+    fn icon(&mut self, rect: Rect, icon: &Icon, state: InputState) {
+        self.as_flat().icon(rect, icon, state);
+    }
+    fn image(&mut self, rect: Rect, id: ImageId) {
+        self.as_flat().image(rect, id);
+    }
+    fn fill_path(&mut self, path: &Path, rule: FillRule, col: Colour) {
+        self.as_flat().fill_path(path, rule, col);
+    }
+    fn stroke_path(&mut self, path: &Path, style: &StrokeStyle, col: Colour) {
+        self.as_flat().stroke_path(path, style, col);
+    }
+    fn selection(&mut self, rect: Rect, shape: HighlightShape, state: InputState) {
+        self.as_flat().selection(rect, shape, state);
+    }
+    fn focus_ring(&mut self, rect: Rect, shape: HighlightShape, state: InputState) {
+        self.as_flat().focus_ring(rect, shape, state);
+    }
+    fn shadow(&mut self, rect: Rect) {
+        self.as_flat().shadow(rect);
+    }
+    fn well(&mut self, rect: Rect, state: InputState) {
+        let cols = self.style.resolve(self.cols, Element::Well, state);
+        let bg_col = cols.bg_col(state);
+        self.draw_edit_box(rect + self.offset, cols.background, bg_col, None);
+    }
+    fn badge(&mut self, rect: Rect, text: &PreparedText, state: InputState) {
+        self.as_flat().badge(rect, text, state);
     }
 }
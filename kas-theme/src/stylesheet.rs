@@ -0,0 +1,601 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Style sheets
+
+use std::borrow::Cow;
+use std::fmt;
+use std::path::Path;
+
+use crate::{DimensionsParams, ThemeColours};
+use kas::draw::{Colour, InputState, TextClass};
+use kas::geom::Vec2;
+use kas::layout::Length;
+
+/// A drawn element, as matched by a selector class
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Element {
+    Button,
+    CheckBox,
+    RadioBox,
+    EditBox,
+    MenuEntry,
+    ScrollBar,
+    Slider,
+    Label,
+    Frame,
+    MenuFrame,
+    Separator,
+    Icon,
+    Selection,
+    FocusRing,
+    Well,
+    Badge,
+}
+
+impl Element {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "button" => Element::Button,
+            "checkbox" => Element::CheckBox,
+            "radiobox" => Element::RadioBox,
+            "edit" => Element::EditBox,
+            "menu-entry" => Element::MenuEntry,
+            "scrollbar" => Element::ScrollBar,
+            "slider" => Element::Slider,
+            "label" => Element::Label,
+            "frame" => Element::Frame,
+            "menu-frame" => Element::MenuFrame,
+            "separator" => Element::Separator,
+            "icon" => Element::Icon,
+            "selection" => Element::Selection,
+            "focus-ring" => Element::FocusRing,
+            "well" => Element::Well,
+            "badge" => Element::Badge,
+            _ => return None,
+        })
+    }
+
+    /// The element on which text of this class is drawn
+    pub(crate) fn text(class: TextClass) -> Self {
+        match class {
+            TextClass::Label | TextClass::LabelSingle => Element::Label,
+            TextClass::Button => Element::Button,
+            TextClass::Edit | TextClass::EditMulti => Element::EditBox,
+        }
+    }
+}
+
+/// Names of [`ThemeColours`] fields, as indexed by [`field_mut`]
+const FIELDS: [&str; 16] = [
+    "background",
+    "frame",
+    "bg",
+    "bg_disabled",
+    "bg_error",
+    "text",
+    "text_sel",
+    "text_sel_bg",
+    "label_text",
+    "button_text",
+    "nav_focus",
+    "button",
+    "button_disabled",
+    "button_highlighted",
+    "button_depressed",
+    "checkbox",
+];
+
+fn field_mut(cols: &mut ThemeColours, index: usize) -> &mut Colour {
+    match index {
+        0 => &mut cols.background,
+        1 => &mut cols.frame,
+        2 => &mut cols.bg,
+        3 => &mut cols.bg_disabled,
+        4 => &mut cols.bg_error,
+        5 => &mut cols.text,
+        6 => &mut cols.text_sel,
+        7 => &mut cols.text_sel_bg,
+        8 => &mut cols.label_text,
+        9 => &mut cols.button_text,
+        10 => &mut cols.nav_focus,
+        11 => &mut cols.button,
+        12 => &mut cols.button_disabled,
+        13 => &mut cols.button_highlighted,
+        14 => &mut cols.button_depressed,
+        15 => &mut cols.checkbox,
+        _ => unreachable!(),
+    }
+}
+
+/// Theme parameters, as set by `*` rules
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Params {
+    font_size: Option<f32>,
+    outer_margin: Option<Length>,
+    inner_margin: Option<Length>,
+    frame_size: Option<Length>,
+    button_frame: Option<Length>,
+    scrollbar_size: Option<Vec2>,
+    slider_size: Option<Vec2>,
+}
+
+impl Params {
+    fn field(&mut self, key: &str) -> Option<Param> {
+        Some(match key {
+            "font_size" => Param::Number(&mut self.font_size),
+            "outer_margin" => Param::Length(&mut self.outer_margin),
+            "inner_margin" => Param::Length(&mut self.inner_margin),
+            "frame_size" => Param::Length(&mut self.frame_size),
+            "button_frame" => Param::Length(&mut self.button_frame),
+            "scrollbar_size" => Param::Size(&mut self.scrollbar_size),
+            "slider_size" => Param::Size(&mut self.slider_size),
+            _ => return None,
+        })
+    }
+}
+
+/// Reference to a field of [`Params`], by value type
+enum Param<'a> {
+    Number(&'a mut Option<f32>),
+    Length(&'a mut Option<Length>),
+    Size(&'a mut Option<Vec2>),
+}
+
+impl<'a> Param<'a> {
+    fn parse(self, parser: &mut Parser) -> Result<(), StyleSheetError> {
+        match self {
+            Param::Number(x) => *x = Some(parser.number()?),
+            Param::Length(x) => *x = Some(parser.length()?),
+            Param::Size(x) => *x = Some(Vec2(parser.px()?, parser.px()?)),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Selector {
+    class: Option<Element>,
+    /// Style name (see [`kas::WidgetCore::with_style_name`])
+    name: Option<String>,
+    /// Required states (all must be set)
+    states: InputState,
+}
+
+impl Selector {
+    fn specificity(&self) -> (bool, u32, bool) {
+        let s = &self.states;
+        let states = [
+            s.disabled,
+            s.error,
+            s.hover,
+            s.depress,
+            s.nav_focus,
+            s.char_focus,
+        ];
+        let n = states.iter().filter(|b| **b).count() as u32;
+        (self.name.is_some(), n, self.class.is_some())
+    }
+
+    fn matches(&self, class: Element, state: InputState) -> bool {
+        let req = &self.states;
+        self.class.map(|c| c == class).unwrap_or(true)
+            && self
+                .name
+                .as_deref()
+                .map(|n| state.style_name == Some(n))
+                .unwrap_or(true)
+            && (!req.disabled || state.disabled)
+            && (!req.error || state.error)
+            && (!req.hover || state.hover)
+            && (!req.depress || state.depress)
+            && (!req.nav_focus || state.nav_focus)
+            && (!req.char_focus || state.char_focus)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Rule {
+    selector: Selector,
+    /// Declarations: field index and value
+    decls: Vec<(usize, Colour)>,
+}
+
+/// Error loading a [`StyleSheet`]
+#[derive(Debug)]
+pub enum StyleSheetError {
+    /// Error reading the file
+    Io(std::io::Error),
+    /// Syntax error, with line number (from 1) and description
+    Parse(usize, String),
+}
+
+impl fmt::Display for StyleSheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StyleSheetError::Io(e) => write!(f, "error reading style sheet: {}", e),
+            StyleSheetError::Parse(line, msg) => {
+                write!(f, "error parsing style sheet at line {}: {}", line, msg)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StyleSheetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StyleSheetError::Io(e) => Some(e),
+            StyleSheetError::Parse(..) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for StyleSheetError {
+    fn from(e: std::io::Error) -> Self {
+        StyleSheetError::Io(e)
+    }
+}
+
+/// A style sheet
+///
+/// A style sheet adjusts theme colours per drawn element and theme
+/// parameters, allowing the appearance of an application to be tweaked
+/// without recompiling. Style sheets use a small CSS-like language:
+///
+/// ```text
+/// /* Comments are supported */
+/// * { font-size: 14; frame-size: 0.25em; }
+/// button:hover, slider:hover {
+///     button_highlighted: #ff9922;
+/// }
+/// edit:error { bg_error: #ffcccc; }
+/// #ok { button: #22aa44; }
+/// label#warning:disabled { label-text: #996633; }
+/// ```
+///
+/// Each rule consists of a list of selectors and a list of declarations.
+/// A selector is `*` or any combination of an element class, a style name
+/// (`#name`, see [`kas::WidgetCore::with_style_name`]) and states
+/// (`:state`), in this order:
+///
+/// -   classes: `button`, `checkbox`, `radiobox`, `edit`, `menu-entry`,
+///     `scrollbar`, `slider`, `label`, `frame`, `menu-frame`, `separator`,
+///     `icon` (standard icons are drawn with `label_text`), `selection`
+///     (drawn with `text_sel_bg`), `focus-ring` (drawn with `nav_focus`),
+///     `well`, `badge` (drawn with `nav_focus`)
+/// -   states: `disabled`, `error`, `hover`, `depress`, `focus` (keyboard
+///     navigation focus), `char-focus`
+///
+/// Text is matched by the class of the element it is drawn on (`label`,
+/// `button` or `edit`) and by the style name and state of its widget.
+///
+/// Declarations assign a colour to a field of [`ThemeColours`], e.g.
+/// `label_text: #336699;` (hyphens may be used in place of underscores).
+/// Colours are written as `#rgb`, `#rrggbb` or `#rrggbbaa`.
+///
+/// Rules whose only selector is `*` may additionally set theme parameters,
+/// which apply to the whole theme (since they affect sizes):
+///
+/// -   `font-size`: a number (in the units of [`kas::ThemeApi::set_font_size`])
+/// -   `outer-margin`, `inner-margin`, `frame-size`, `button-frame`: a length
+///     in `px` (logical pixels), `em` (font size) or `lh` (line height); see
+///     [`DimensionsParams`]
+/// -   `scrollbar-size`, `slider-size`: two lengths in `px`
+///
+/// Where multiple rules match, declarations of more specific selectors take
+/// precedence (with name before state before class), then those of later
+/// rules.
+///
+/// Style sheets are assigned to a theme via e.g.
+/// [`crate::FlatTheme::with_stylesheet`].
+///
+/// ```
+/// use kas_theme::StyleSheet;
+///
+/// let sheet = StyleSheet::parse("button:hover { button_highlighted: #f92; }").unwrap();
+/// assert_eq!(sheet.len(), 1);
+/// assert!(StyleSheet::parse("#ok, label#warning { label-text: #963; }").is_ok());
+/// assert!(StyleSheet::parse("* { font-size: 14; frame-size: 0.25em; }").is_ok());
+/// assert!(StyleSheet::parse("button { no_such_field: #fff; }").is_err());
+/// assert!(StyleSheet::parse("button { font-size: 14; }").is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StyleSheet {
+    /// Rules, in order of application
+    rules: Vec<Rule>,
+    params: Params,
+}
+
+impl StyleSheet {
+    /// Construct an empty style sheet
+    pub fn new() -> Self {
+        StyleSheet::default()
+    }
+
+    /// Load a style sheet from a file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, StyleSheetError> {
+        let source = std::fs::read_to_string(path)?;
+        StyleSheet::parse(&source)
+    }
+
+    /// Parse a style sheet
+    pub fn parse(source: &str) -> Result<Self, StyleSheetError> {
+        let mut parser = Parser::new(source);
+        let mut rules = vec![];
+        let mut params = Params::default();
+        while parser.skip_space() {
+            let mut selectors = vec![parser.selector()?];
+            while parser.eat(',') {
+                selectors.push(parser.selector()?);
+            }
+            let global = selectors.len() == 1 && selectors[0] == Selector::default();
+            parser.expect('{')?;
+            let mut decls = vec![];
+            while !parser.eat('}') {
+                let name = parser.ident()?;
+                let key = name.replace('-', "_");
+                parser.expect(':')?;
+                if let Some(index) = FIELDS.iter().position(|f| *f == key) {
+                    decls.push((index, parser.colour()?));
+                } else if let Some(param) = params.field(&key) {
+                    if !global {
+                        let msg = format!("property `{}` may only be set in a `*` rule", name);
+                        return Err(parser.error(msg));
+                    }
+                    param.parse(&mut parser)?;
+                } else {
+                    return Err(parser.error(format!("unknown property `{}`", name)));
+                }
+                if !parser.eat(';') {
+                    parser.expect('}')?;
+                    break;
+                }
+            }
+            for selector in selectors {
+                rules.push(Rule {
+                    selector,
+                    decls: decls.clone(),
+                });
+            }
+        }
+        // Stable sort: more specific rules are applied later
+        rules.sort_by_key(|rule| rule.selector.specificity());
+        Ok(StyleSheet { rules, params })
+    }
+
+    /// Number of rules (counting each selector separately)
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// True if there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Get the font size, given the theme's default
+    pub(crate) fn font_size(&self, size: f32) -> f32 {
+        self.params.font_size.unwrap_or(size)
+    }
+
+    /// Get dimension parameters, given the theme's defaults
+    pub(crate) fn dims(&self, dims: &DimensionsParams) -> DimensionsParams {
+        let p = &self.params;
+        DimensionsParams {
+            outer_margin: p.outer_margin.unwrap_or(dims.outer_margin),
+            inner_margin: p.inner_margin.unwrap_or(dims.inner_margin),
+            frame_size: p.frame_size.unwrap_or(dims.frame_size),
+            button_frame: p.button_frame.unwrap_or(dims.button_frame),
+            scrollbar_size: p.scrollbar_size.unwrap_or(dims.scrollbar_size),
+            slider_size: p.slider_size.unwrap_or(dims.slider_size),
+        }
+    }
+
+    /// True if theme parameters of `self` and `other` differ
+    pub(crate) fn params_differ(&self, other: &StyleSheet) -> bool {
+        self.params != other.params
+    }
+
+    /// Get colours for drawing `class` in `state`
+    pub(crate) fn resolve<'a>(
+        &self,
+        cols: &'a ThemeColours,
+        class: Element,
+        state: InputState,
+    ) -> Cow<'a, ThemeColours> {
+        let mut cols = Cow::Borrowed(cols);
+        for rule in &self.rules {
+            if rule.selector.matches(class, state) {
+                let cols = cols.to_mut();
+                for (index, col) in &rule.decls {
+                    *field_mut(cols, *index) = *col;
+                }
+            }
+        }
+        cols
+    }
+
+    /// Get colours for drawing text of the given `class` in `state`
+    pub(crate) fn resolve_text<'a>(
+        &self,
+        cols: &'a ThemeColours,
+        class: TextClass,
+        state: InputState,
+    ) -> Cow<'a, ThemeColours> {
+        self.resolve(cols, Element::text(class), state)
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Parser { source, pos: 0 }
+    }
+
+    fn error(&self, msg: String) -> StyleSheetError {
+        let line = self.source[..self.pos].matches('\n').count() + 1;
+        StyleSheetError::Parse(line, msg)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    /// Skip white-space and comments; return true if input remains
+    fn skip_space(&mut self) -> bool {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("/*") {
+                match trimmed[2..].find("*/") {
+                    Some(end) => self.pos += end + 4,
+                    None => self.pos = self.source.len(),
+                }
+            } else {
+                return !trimmed.is_empty();
+            }
+        }
+    }
+
+    /// Consume `c` (after any white-space) if next
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_space();
+        if self.rest().starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), StyleSheetError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", c)))
+        }
+    }
+
+    /// Read a (possibly empty) word, without skipping white-space first
+    fn word(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn ident(&mut self) -> Result<&'a str, StyleSheetError> {
+        self.skip_space();
+        match self.word() {
+            "" => Err(self.error("expected identifier".to_string())),
+            word => Ok(word),
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, StyleSheetError> {
+        let mut selector = Selector::default();
+        if self.eat('*') {
+            return Ok(selector);
+        }
+        self.skip_space();
+        let start = self.pos;
+        let class = self.word();
+        if !class.is_empty() {
+            match Element::from_name(class) {
+                Some(class) => selector.class = Some(class),
+                None => {
+                    self.pos = start;
+                    return Err(self.error(format!("unknown class `{}`", class)));
+                }
+            }
+        }
+        if self.rest().starts_with('#') {
+            self.pos += 1;
+            match self.word() {
+                "" => return Err(self.error("expected style name".to_string())),
+                name => selector.name = Some(name.to_string()),
+            }
+        }
+        while self.rest().starts_with(':') {
+            self.pos += 1;
+            let s = &mut selector.states;
+            match self.word() {
+                "disabled" => s.disabled = true,
+                "error" => s.error = true,
+                "hover" => s.hover = true,
+                "depress" => s.depress = true,
+                "focus" => s.nav_focus = true,
+                "char-focus" => s.char_focus = true,
+                state => return Err(self.error(format!("unknown state `:{}`", state))),
+            }
+        }
+        if selector == Selector::default() {
+            return Err(self.error("expected selector".to_string()));
+        }
+        Ok(selector)
+    }
+
+    fn number(&mut self) -> Result<f32, StyleSheetError> {
+        self.skip_space();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        match rest[..len].parse::<f32>() {
+            Ok(x) => {
+                self.pos += len;
+                Ok(x)
+            }
+            Err(_) => Err(self.error("expected number".to_string())),
+        }
+    }
+
+    fn length(&mut self) -> Result<Length, StyleSheetError> {
+        let x = self.number()?;
+        match self.word() {
+            "px" => Ok(Length::Px(x)),
+            "em" => Ok(Length::Em(x)),
+            "lh" => Ok(Length::Lines(x)),
+            unit => Err(self.error(format!("unknown unit `{}`", unit))),
+        }
+    }
+
+    fn px(&mut self) -> Result<f32, StyleSheetError> {
+        match self.length()? {
+            Length::Px(x) => Ok(x),
+            _ => Err(self.error("expected length in `px`".to_string())),
+        }
+    }
+
+    fn colour(&mut self) -> Result<Colour, StyleSheetError> {
+        if !self.eat('#') {
+            return Err(self.error("expected colour".to_string()));
+        }
+        let hex = self.word();
+        let digits: Option<Vec<u8>> = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect();
+        let v: Vec<u8> = match (digits, hex.len()) {
+            (Some(d), 3) => d.iter().map(|x| x * 17).collect(),
+            (Some(d), 6) | (Some(d), 8) => d.chunks(2).map(|x| x[0] * 16 + x[1]).collect(),
+            _ => return Err(self.error(format!("invalid colour `#{}`", hex))),
+        };
+        let c = |i: usize| v.get(i).map(|x| *x as f32 / 255.0).unwrap_or(1.0);
+        Ok(Colour {
+            r: c(0),
+            g: c(1),
+            b: c(2),
+            a: c(3),
+        })
+    }
+}
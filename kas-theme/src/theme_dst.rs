@@ -8,10 +8,10 @@
 use std::any::Any;
 use std::ops::DerefMut;
 
-use super::{StackDst, Theme, Window};
+use super::{StackDst, Theme, ThemeConfig, Window};
 use kas::draw::{Colour, DrawHandle, DrawShared, SizeHandle};
 use kas::geom::Rect;
-use kas::ThemeApi;
+use kas::{ThemeAction, ThemeApi};
 
 /// As [`Theme`], but without associated types
 ///
@@ -67,6 +67,16 @@ pub trait ThemeDst<D: DrawShared>: ThemeApi {
         rect: Rect,
     ) -> StackDst<dyn DrawHandle + 'a>;
 
+    /// Advance theme-wide animations
+    ///
+    /// See also [`Theme::animate`].
+    fn animate(&mut self) -> bool;
+
+    /// Apply a theme configuration
+    ///
+    /// See also [`Theme::apply_config`].
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction;
+
     /// Background colour
     ///
     /// See also [`Theme::clear_colour`].
@@ -125,6 +135,14 @@ where
         }
     }
 
+    fn animate(&mut self) -> bool {
+        self.animate()
+    }
+
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        self.apply_config(config)
+    }
+
     fn clear_colour(&self) -> Colour {
         self.clear_colour()
     }
@@ -157,6 +175,14 @@ impl<'a, D: DrawShared + 'static, T: Theme<D>> ThemeDst<D> for T {
         StackDst::new_or_boxed(h)
     }
 
+    fn animate(&mut self) -> bool {
+        self.animate()
+    }
+
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        self.apply_config(config)
+    }
+
     fn clear_colour(&self) -> Colour {
         self.clear_colour()
     }
@@ -187,6 +213,9 @@ pub trait WindowDst {
     #[cfg(feature = "gat")]
     fn size_handle<'a>(&'a mut self) -> StackDst<dyn SizeHandle + 'a>;
 
+    /// Returns true if animations require another frame to be drawn
+    fn take_redraw(&mut self) -> bool;
+
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
@@ -209,6 +238,10 @@ where
         }
     }
 
+    fn take_redraw(&mut self) -> bool {
+        <W as Window>::take_redraw(self)
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self.as_any_mut()
     }
@@ -221,6 +254,10 @@ impl<W: Window> WindowDst for W {
         StackDst::new_or_boxed(h)
     }
 
+    fn take_redraw(&mut self) -> bool {
+        <W as Window>::take_redraw(self)
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self.as_any_mut()
     }
@@ -242,6 +279,10 @@ impl Window for StackDst<dyn WindowDst> {
         self.deref_mut().size_handle()
     }
 
+    fn take_redraw(&mut self) -> bool {
+        self.deref_mut().take_redraw()
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self.deref_mut().as_any_mut()
     }
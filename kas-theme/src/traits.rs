@@ -10,7 +10,9 @@ use std::ops::{Deref, DerefMut};
 
 use kas::draw::{Colour, DrawHandle, DrawShared, SizeHandle};
 use kas::geom::Rect;
-use kas::ThemeApi;
+use kas::{ThemeAction, ThemeApi};
+
+use crate::ThemeConfig;
 
 /// A *theme* provides widget sizing and drawing implementations.
 ///
@@ -77,6 +79,29 @@ pub trait Theme<D: DrawShared>: ThemeApi {
         rect: Rect,
     ) -> Self::DrawHandle<'a>;
 
+    /// Advance theme-wide animations
+    ///
+    /// The toolkit should call this before drawing each frame. Returns true
+    /// while an animation (e.g. a colour-scheme transition) requires further
+    /// frames to be drawn.
+    ///
+    /// The default implementation does nothing.
+    fn animate(&mut self) -> bool {
+        false
+    }
+
+    /// Apply a theme configuration
+    ///
+    /// This updates fonts, colours and dimensions from `config`, returning
+    /// the action required to apply the changes (e.g. to reload theme files
+    /// at run-time).
+    ///
+    /// The default implementation does nothing.
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        let _ = config;
+        ThemeAction::None
+    }
+
     /// Background colour
     fn clear_colour(&self) -> Colour;
 }
@@ -103,6 +128,12 @@ pub trait Window {
     #[cfg(feature = "gat")]
     fn size_handle<'a>(&'a mut self) -> Self::SizeHandle<'a>;
 
+    /// Returns true if animations require another frame to be drawn
+    ///
+    /// The toolkit should call this after drawing each frame. The state is
+    /// reset by this call.
+    fn take_redraw(&mut self) -> bool;
+
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
@@ -144,6 +175,14 @@ impl<T: Theme<D>, D: DrawShared> Theme<D> for Box<T> {
         self.deref().draw_handle(draw, window, rect)
     }
 
+    fn animate(&mut self) -> bool {
+        self.deref_mut().animate()
+    }
+
+    fn apply_config(&mut self, config: &ThemeConfig) -> ThemeAction {
+        self.deref_mut().apply_config(config)
+    }
+
     fn clear_colour(&self) -> Colour {
         self.deref().clear_colour()
     }
@@ -164,6 +203,10 @@ impl<W: Window> Window for Box<W> {
         self.deref_mut().size_handle()
     }
 
+    fn take_redraw(&mut self) -> bool {
+        self.deref_mut().take_redraw()
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self.deref_mut().as_any_mut()
     }
@@ -90,6 +90,10 @@ where
         Theme::<D>::draw_handle(&self.inner, draw, window, rect)
     }
 
+    fn animate(&mut self) -> bool {
+        Theme::<D>::animate(&mut self.inner)
+    }
+
     fn clear_colour(&self) -> Colour {
         BACKGROUND.with(|b| b.get())
     }
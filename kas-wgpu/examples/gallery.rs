@@ -9,6 +9,7 @@
 //! (excepting custom graphics).
 
 use kas::class::HasString;
+use kas::draw::Icon;
 use kas::event::{UpdateHandle, VoidResponse};
 use kas::prelude::*;
 use kas::widget::*;
@@ -63,7 +64,12 @@ fn main() -> Result<(), kas_wgpu::Error> {
         MenuEntry::new("Dar&k", Menu::Colour("dark")),
     ];
     let menubar = MenuBar::<Right, _>::new(vec![
-        SubMenu::new("&App", vec![MenuEntry::new("&Quit", Menu::Quit).boxed()]),
+        SubMenu::new(
+            "&App",
+            vec![MenuEntry::new("&Quit", Menu::Quit)
+                .with_icon(Icon::Close)
+                .boxed()],
+        ),
         SubMenu::new("&Theme", themes),
         SubMenu::new(
             "&Style",
@@ -20,8 +20,6 @@ use kas::widget::{Label, Slider, Window};
 use kas_wgpu::draw::{CustomPipe, CustomPipeBuilder, CustomWindow, DrawCustom, DrawWindow};
 use kas_wgpu::Options;
 
-
-
 struct Shaders {
     vertex: ShaderModule,
     fragment: ShaderModule,
@@ -29,8 +27,12 @@ struct Shaders {
 
 impl Shaders {
     fn compile(device: &wgpu::Device) -> Self {
-        let vertex = device.create_shader_module(wgpu::include_spirv!("mandlebrot_shaders/spir-v/shader.vert"));
-        let fragment = device.create_shader_module(wgpu::include_spirv!("mandlebrot_shaders/spir-v/shader.frag"));
+        let vertex = device.create_shader_module(wgpu::include_spirv!(
+            "mandlebrot_shaders/spir-v/shader.vert"
+        ));
+        let fragment = device.create_shader_module(wgpu::include_spirv!(
+            "mandlebrot_shaders/spir-v/shader.frag"
+        ));
         Shaders { vertex, fragment }
     }
 }
@@ -51,6 +53,7 @@ impl CustomPipeBuilder for PipeBuilder {
         device: &wgpu::Device,
         tex_format: wgpu::TextureFormat,
         depth_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) -> Self::Pipe {
         // Note: real apps should compile shaders once and share between windows
         let shaders = Shaders::compile(device);
@@ -139,7 +142,7 @@ impl CustomPipeBuilder for PipeBuilder {
                     attributes: &wgpu::vertex_attr_array![0 => Float3, 1 => Float2],
                 }],
             },
-            sample_count: 1,
+            sample_count,
             sample_mask: !0,
             alpha_to_coverage_enabled: false,
         });
@@ -470,8 +473,10 @@ impl event::Handler for Mandlebrot {
                 Response::Msg(())
             }
             Event::Scroll(delta) => {
+                // Line deltas are scaled by scroll_lines; we zoom per notch
+                let lines = mgr.config().scroll_lines.max(1) as f64;
                 let factor = match delta {
-                    event::ScrollDelta::LineDelta(_, y) => -0.5 * y as f64,
+                    event::ScrollDelta::LineDelta(_, y) => -0.5 * y as f64 / lines,
                     event::ScrollDelta::PixelDelta(coord) => -0.01 * coord.1 as f64,
                 };
                 self.alpha = self.alpha * 2f64.powf(factor);
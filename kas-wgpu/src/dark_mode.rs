@@ -0,0 +1,127 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! System dark-mode preference
+//!
+//! -   On Windows and the web, winit reports the preference; changes are
+//!     reported via `WindowEvent::ThemeChanged`.
+//! -   On macOS, the `AppleInterfaceStyle` user default is read.
+//! -   On Linux and BSD, the `color-scheme` setting of the freedesktop
+//!     settings portal is read (with feature `dbus`), falling back to the
+//!     `GTK_THEME` environment variable (e.g. `Adwaita:dark`).
+//!
+//! Where winit does not report changes, the preference is re-read when a
+//! window gains focus ([`TRACKS_CHANGES`] is false).
+
+pub use imp::{system_dark_mode, TRACKS_CHANGES};
+
+#[cfg(any(target_os = "windows", target_arch = "wasm32"))]
+mod imp {
+    #[cfg(target_arch = "wasm32")]
+    use winit::platform::web::WindowExtWebSys;
+    #[cfg(target_os = "windows")]
+    use winit::platform::windows::WindowExtWindows;
+    use winit::window::Window;
+
+    pub const TRACKS_CHANGES: bool = true;
+
+    pub fn system_dark_mode(window: &Window) -> Option<bool> {
+        Some(window.is_dark_mode())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::ffi::CStr;
+    use winit::window::Window;
+
+    pub const TRACKS_CHANGES: bool = false;
+
+    pub fn system_dark_mode(_: &Window) -> Option<bool> {
+        unsafe {
+            let defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+            let key = NSString::alloc(nil).init_str("AppleInterfaceStyle");
+            let style: id = msg_send![defaults, stringForKey: key];
+            let _: () = msg_send![key, release];
+            // The key is absent in light mode
+            if style == nil {
+                return Some(false);
+            }
+            let style = CStr::from_ptr(style.UTF8String());
+            Some(style.to_bytes() == b"Dark")
+        }
+    }
+}
+
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+mod imp {
+    use winit::window::Window;
+
+    pub const TRACKS_CHANGES: bool = false;
+
+    pub fn system_dark_mode(_: &Window) -> Option<bool> {
+        #[cfg(feature = "dbus")]
+        if let Some(dark) = portal_color_scheme() {
+            return Some(dark);
+        }
+        let theme = std::env::var("GTK_THEME").ok()?;
+        Some(theme.ends_with(":dark"))
+    }
+
+    /// Read `org.freedesktop.appearance color-scheme` from the settings portal
+    ///
+    /// The value is 1 when dark is preferred, 2 when light is preferred and 0
+    /// without preference (in which case `None` is returned).
+    #[cfg(feature = "dbus")]
+    fn portal_color_scheme() -> Option<bool> {
+        use dbus::arg::{RefArg, Variant};
+        use dbus::blocking::Connection;
+        use log::debug;
+        use std::time::Duration;
+
+        let conn = Connection::new_session().ok()?;
+        let proxy = conn.with_proxy(
+            "org.freedesktop.portal.Desktop",
+            "/org/freedesktop/portal/desktop",
+            Duration::from_millis(100),
+        );
+        let result: Result<(Variant<Box<dyn RefArg>>,), _> = proxy.method_call(
+            "org.freedesktop.portal.Settings",
+            "Read",
+            ("org.freedesktop.appearance", "color-scheme"),
+        );
+        match result {
+            // The value is wrapped in a second variant
+            Ok((value,)) => match value.as_u64() {
+                Some(1) => Some(true),
+                Some(2) => Some(false),
+                _ => None,
+            },
+            Err(e) => {
+                debug!("Unable to read the color-scheme setting: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    target_arch = "wasm32",
+    target_os = "macos",
+    all(unix, not(target_os = "android"))
+)))]
+mod imp {
+    use winit::window::Window;
+
+    pub const TRACKS_CHANGES: bool = false;
+
+    pub fn system_dark_mode(_: &Window) -> Option<bool> {
+        None
+    }
+}
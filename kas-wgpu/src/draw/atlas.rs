@@ -0,0 +1,259 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Texture atlases holding uploaded images
+//!
+//! Small images are packed into shared atlas pages using a simple shelf
+//! allocator; larger images are given a page (texture) of their own. Space
+//! freed within a shared page is only reclaimed once the page is empty.
+//!
+//! Like other blit targets, pages hold premultiplied colour; image data is
+//! converted on upload.
+
+use std::collections::HashMap;
+
+use super::blit;
+use kas::draw::{ImageError, ImageId};
+use kas::geom::{Quad, Size, Vec2};
+
+/// Size of shared atlas pages
+const PAGE_SIZE: u32 = 1024;
+/// Images with a side larger than this are allocated a dedicated page
+const MAX_SHARED: u32 = 256;
+/// Largest supported image side (the default wgpu texture size limit)
+const MAX_SIZE: u32 = 8192;
+/// Transparent space between images within a page, to avoid filter bleeding
+const PADDING: u32 = 1;
+
+/// A row of images within a page
+struct Shelf {
+    y: u32,
+    height: u32,
+    x: u32,
+}
+
+struct Page {
+    size: Size,
+    shared: bool,
+    shelves: Vec<Shelf>,
+    count: u32,
+    target: Option<blit::Target>,
+}
+
+impl Page {
+    fn new(size: Size, shared: bool) -> Self {
+        Page {
+            size,
+            shared,
+            shelves: vec![],
+            count: 0,
+            target: None,
+        }
+    }
+
+    /// Allocate space for an image of the given `size`
+    fn alloc(&mut self, size: Size) -> Option<(u32, u32)> {
+        let (w, h) = (size.0 + PADDING, size.1 + PADDING);
+        for shelf in &mut self.shelves {
+            if h <= shelf.height && shelf.x + w <= self.size.0 {
+                let origin = (shelf.x, shelf.y);
+                shelf.x += w;
+                return Some(origin);
+            }
+        }
+
+        let y = self.shelves.last().map(|s| s.y + s.height).unwrap_or(0);
+        if w > self.size.0 || y + h > self.size.1 {
+            return None;
+        }
+        self.shelves.push(Shelf { y, height: h, x: w });
+        Some((0, y))
+    }
+}
+
+struct Image {
+    page: u32,
+    origin: (u32, u32),
+    size: Size,
+}
+
+/// Uploaded images and the pages holding them
+///
+/// Texture creation and data upload is deferred until [`Atlases::prepare`].
+#[derive(Default)]
+pub struct Atlases {
+    next_id: u32,
+    images: HashMap<ImageId, Image>,
+    pages: Vec<Option<Page>>,
+    pending: Vec<(u32, (u32, u32), Size, Vec<u8>)>,
+}
+
+impl Atlases {
+    /// Allocate an image and queue upload of its data
+    pub fn alloc(&mut self, size: Size, rgba: &[u8]) -> Result<ImageId, ImageError> {
+        if size.0 == 0 || size.1 == 0 || size.0 > MAX_SIZE || size.1 > MAX_SIZE {
+            return Err(ImageError::Size);
+        }
+        if rgba.len() != 4 * size.0 as usize * size.1 as usize {
+            return Err(ImageError::Size);
+        }
+
+        let (page, origin) = if size.0 <= MAX_SHARED && size.1 <= MAX_SHARED {
+            self.alloc_shared(size)
+        } else {
+            let index = self.new_page(Page::new(size, false));
+            (index, (0, 0))
+        };
+        self.pages[page as usize].as_mut().unwrap().count += 1;
+
+        let id = ImageId::new(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.images.insert(id, Image { page, origin, size });
+        self.pending.push((page, origin, size, premultiply(rgba)));
+        Ok(id)
+    }
+
+    fn alloc_shared(&mut self, size: Size) -> (u32, (u32, u32)) {
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            if let Some(page) = page.as_mut().filter(|page| page.shared) {
+                if let Some(origin) = page.alloc(size) {
+                    return (index as u32, origin);
+                }
+            }
+        }
+
+        let mut page = Page::new(Size::uniform(PAGE_SIZE), true);
+        let origin = page.alloc(size).unwrap();
+        (self.new_page(page), origin)
+    }
+
+    fn new_page(&mut self, page: Page) -> u32 {
+        match self.pages.iter().position(|page| page.is_none()) {
+            Some(index) => {
+                self.pages[index] = Some(page);
+                index as u32
+            }
+            None => {
+                self.pages.push(Some(page));
+                (self.pages.len() - 1) as u32
+            }
+        }
+    }
+
+    /// Queue replacement of image data
+    pub fn update(&mut self, id: ImageId, rgba: &[u8]) -> Result<(), ImageError> {
+        let image = self.images.get(&id).ok_or(ImageError::Id)?;
+        let size = image.size;
+        if rgba.len() != 4 * size.0 as usize * size.1 as usize {
+            return Err(ImageError::Size);
+        }
+        self.pending
+            .push((image.page, image.origin, size, premultiply(rgba)));
+        Ok(())
+    }
+
+    /// Free an image
+    ///
+    /// Dedicated pages are freed immediately; shared pages once empty.
+    pub fn free(&mut self, id: ImageId) {
+        if let Some(image) = self.images.remove(&id) {
+            let slot = &mut self.pages[image.page as usize];
+            let page = slot.as_mut().unwrap();
+            page.count -= 1;
+            if page.count == 0 {
+                *slot = None;
+                self.pending.retain(|upload| upload.0 != image.page);
+            }
+        }
+    }
+
+    /// Get the size of an image
+    pub fn size(&self, id: ImageId) -> Option<Size> {
+        self.images.get(&id).map(|image| image.size)
+    }
+
+    /// Get the blit source and texture coordinates of `region` of an image
+    ///
+    /// The `region` is in normalised image coordinates.
+    pub fn source(&self, id: ImageId, region: Quad) -> Option<(blit::Source, Quad)> {
+        let image = self.images.get(&id)?;
+        let page = self.pages[image.page as usize].as_ref().unwrap();
+        let scale = Vec2(1.0 / page.size.0 as f32, 1.0 / page.size.1 as f32);
+        let origin = Vec2(image.origin.0 as f32, image.origin.1 as f32);
+        let size = Vec2::from(image.size);
+        let a = (origin + region.a * size) * scale;
+        let b = (origin + region.b * size) * scale;
+        Some((blit::Source::Atlas(image.page), Quad::with_coords(a, b)))
+    }
+
+    /// Create textures of new pages and upload pending image data
+    pub fn prepare(&mut self, blit: &blit::Pipeline, device: &wgpu::Device, queue: &wgpu::Queue) {
+        for page in self.pages.iter_mut().flatten() {
+            if page.target.is_none() {
+                page.target = Some(blit.new_atlas(device, page.size));
+            }
+        }
+
+        for (page, origin, size, rgba) in self.pending.drain(..) {
+            let page = self.pages[page as usize].as_ref().unwrap();
+            page.target
+                .as_ref()
+                .unwrap()
+                .write(queue, origin, size, &rgba);
+        }
+    }
+
+    /// Get the target of a page
+    pub fn target(&self, page: u32) -> Option<&blit::Target> {
+        self.pages
+            .get(page as usize)
+            .and_then(|page| page.as_ref())
+            .and_then(|page| page.target.as_ref())
+    }
+}
+
+/// Convert sRGB RGBA data to premultiplied alpha
+///
+/// Colour is multiplied by alpha in linear space, matching blending.
+fn premultiply(rgba: &[u8]) -> Vec<u8> {
+    let mut data = rgba.to_vec();
+    for pixel in data.chunks_exact_mut(4) {
+        if pixel[3] < 255 {
+            let alpha = f32::from(pixel[3]) / 255.0;
+            for c in &mut pixel[0..3] {
+                *c = to_srgb(from_srgb(*c) * alpha);
+            }
+        }
+    }
+    data
+}
+
+fn from_srgb(c: u8) -> f32 {
+    let c = f32::from(c) / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn to_srgb(c: f32) -> u8 {
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+#[test]
+fn premultiplied() {
+    let rgba = [255, 128, 0, 255, 255, 255, 255, 0, 255, 0, 0, 128];
+    let data = premultiply(&rgba);
+    // Opaque and fully transparent pixels
+    assert_eq!(&data[0..8], &[255, 128, 0, 255, 0, 0, 0, 0]);
+    // Linear 0.5 is encoded as 188
+    assert_eq!(&data[8..12], &[188, 0, 0, 128]);
+}
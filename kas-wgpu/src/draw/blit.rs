@@ -0,0 +1,756 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Pipeline drawing cached textures and images
+
+use std::mem::size_of;
+use std::ops::Range;
+use wgpu::util::DeviceExt;
+
+use crate::draw::{ShaderManager, TEX_FORMAT};
+use kas::draw::Pass;
+use kas::geom::{Quad, Size, Vec2};
+
+/// A quad: corners, texture coordinates of these and depth
+///
+/// Quads are drawn as instances, each of six vertices (see `quad.vert`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct Instance(Vec2, Vec2, Vec2, Vec2, f32);
+unsafe impl bytemuck::Zeroable for Instance {}
+unsafe impl bytemuck::Pod for Instance {}
+
+impl Instance {
+    /// The quad `rect` at `depth`, with texture coordinates `tex`
+    fn new(rect: Quad, tex: Quad, depth: f32) -> Self {
+        Instance(rect.a, rect.b, tex.a, tex.b, depth)
+    }
+}
+
+/// Source texture of a blit
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// A cached region, by key
+    Cache(u64),
+    /// An atlas page holding uploaded images and icons, by index
+    Atlas(u32),
+    /// A compositing layer, by index
+    Layer(u32),
+    /// The shadow of a compositing layer, by index
+    Shadow(u32),
+}
+
+/// Maximum number of samples each side of the centre of a blur kernel
+const MAX_BLUR_STEPS: i32 = 32;
+
+/// Parameters of one direction of a (separable) gaussian blur
+#[derive(Clone, Copy, Debug)]
+pub struct Blur {
+    /// Region of the destination to draw
+    pub rect: Quad,
+    /// Unit vector along which to blur
+    pub direction: Vec2,
+    /// Kernel radius (three standard deviations)
+    pub radius: f32,
+    /// Scale factor applied to the result
+    pub opacity: f32,
+    /// If true, only alpha is written, leaving the result black
+    pub shadow: bool,
+}
+
+/// A render target for cached content, or an uploaded image
+///
+/// Targets are sized to the window, so that content may be drawn with the
+/// window's usual coordinates and depth buffer. Content is stored with
+/// premultiplied alpha, such that a target cleared to transparent may be
+/// drawn to and later composited.
+pub struct Target {
+    texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    pub size: Size,
+}
+
+/// A pipeline for drawing cached textures
+pub struct Pipeline {
+    bind_group_layout: wgpu::BindGroupLayout,
+    tex_bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    image_sampler: wgpu::Sampler,
+    render_pipeline: wgpu::RenderPipeline,
+    depth_pipeline: Option<wgpu::RenderPipeline>,
+    fade_pipeline: wgpu::RenderPipeline,
+    fade_bind_group: wgpu::BindGroup,
+    blur_pipeline: wgpu::RenderPipeline,
+    shadow_pipeline: wgpu::RenderPipeline,
+}
+
+/// Per-window state
+pub struct Window {
+    bind_group: wgpu::BindGroup,
+    scale_buf: wgpu::Buffer,
+    passes: Vec<Vec<(Source, Instance)>>,
+    buffer: Option<wgpu::Buffer>,
+    draws: Vec<Vec<(Source, Range<u32>)>>,
+}
+
+/// Buffer used during render pass
+///
+/// This buffer must not be dropped before the render pass.
+pub struct RenderBuffer<'a> {
+    pipe: &'a wgpu::RenderPipeline,
+    depth_pipe: Option<&'a wgpu::RenderPipeline>,
+    bind_group: &'a wgpu::BindGroup,
+    draws: Vec<(&'a wgpu::BindGroup, Range<u32>)>,
+    buffer: &'a wgpu::Buffer,
+}
+
+impl<'a> RenderBuffer<'a> {
+    /// Do the render
+    pub fn render(&'a self, rpass: &mut wgpu::RenderPass<'a>) {
+        self.draw(rpass, self.pipe);
+    }
+
+    /// Render depth only, if multi-sampling (see [`super::new_depth_pipeline`])
+    pub fn render_depth(&'a self, rpass: &mut wgpu::RenderPass<'a>) {
+        if let Some(pipe) = self.depth_pipe {
+            self.draw(rpass, pipe);
+        }
+    }
+
+    fn draw(&'a self, rpass: &mut wgpu::RenderPass<'a>, pipe: &'a wgpu::RenderPipeline) {
+        rpass.set_pipeline(pipe);
+        rpass.set_bind_group(0, self.bind_group, &[]);
+        rpass.set_vertex_buffer(0, self.buffer.slice(..));
+        for (bind_group, range) in &self.draws {
+            rpass.set_bind_group(1, bind_group, &[]);
+            rpass.draw(0..6, range.clone());
+        }
+    }
+}
+
+impl Pipeline {
+    /// Construct
+    pub fn new(device: &wgpu::Device, shaders: &ShaderManager, sample_count: u32) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("BL bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX,
+                ty: wgpu::BindingType::UniformBuffer {
+                    dynamic: false,
+                    min_binding_size: None, // TODO
+                },
+                count: None,
+            }],
+        });
+
+        let tex_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("BL tex_bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStage::FRAGMENT,
+                        ty: wgpu::BindingType::SampledTexture {
+                            dimension: wgpu::TextureViewDimension::D2,
+                            component_type: wgpu::TextureComponentType::Float,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStage::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler { comparison: false },
+                        count: None,
+                    },
+                ],
+            });
+
+        // Targets are drawn pixel-aligned, hence nearest filtering suffices
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("BL sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        // Images are scaled, hence use linear filtering
+        let image_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("BL image sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("BL pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout, &tex_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let effect_pipeline =
+            |label, state| new_effect_pipeline(device, shaders, &pipeline_layout, label, state);
+        // Multiplies (premultiplied) content of a target by the blend colour
+        let scale = wgpu::BlendDescriptor {
+            src_factor: wgpu::BlendFactor::Zero,
+            dst_factor: wgpu::BlendFactor::BlendColor,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let fade_pipeline = effect_pipeline(
+            "BL fade_pipeline",
+            wgpu::ColorStateDescriptor {
+                format: TEX_FORMAT,
+                color_blend: scale.clone(),
+                alpha_blend: scale,
+                write_mask: wgpu::ColorWrite::ALL,
+            },
+        );
+        // Accumulates source samples weighted by the blend colour
+        let weighted = wgpu::BlendDescriptor {
+            src_factor: wgpu::BlendFactor::BlendColor,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let blur_pipeline = effect_pipeline(
+            "BL blur_pipeline",
+            wgpu::ColorStateDescriptor {
+                format: TEX_FORMAT,
+                color_blend: weighted.clone(),
+                alpha_blend: weighted.clone(),
+                write_mask: wgpu::ColorWrite::ALL,
+            },
+        );
+        // As above, but leaving colour black
+        let shadow_pipeline = effect_pipeline(
+            "BL shadow_pipeline",
+            wgpu::ColorStateDescriptor {
+                format: TEX_FORMAT,
+                color_blend: wgpu::BlendDescriptor::REPLACE,
+                alpha_blend: weighted,
+                write_mask: wgpu::ColorWrite::ALPHA,
+            },
+        );
+
+        // The fade pipeline ignores its source, but some texture must be bound
+        let fade_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("BL fade source"),
+            size: wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TEX_FORMAT,
+            usage: wgpu::TextureUsage::SAMPLED,
+        });
+        let fade_view = fade_texture.create_view(&Default::default());
+        let fade_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("BL fade bind group"),
+            layout: &tex_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&fade_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let desc = wgpu::RenderPipelineDescriptor {
+            label: Some("BL render_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex_stage: wgpu::ProgrammableStageDescriptor {
+                module: &shaders.vert_quad,
+                entry_point: "main",
+            },
+            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                module: &shaders.frag_blit,
+                entry_point: "main",
+            }),
+            rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: wgpu::CullMode::None,
+                clamp_depth: false,
+                depth_bias: 0,
+                depth_bias_slope_scale: 0.0,
+                depth_bias_clamp: 0.0,
+            }),
+            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+            color_states: &[wgpu::ColorStateDescriptor {
+                format: TEX_FORMAT,
+                // Targets and atlases hold premultiplied colour
+                color_blend: wgpu::BlendDescriptor {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha_blend: wgpu::BlendDescriptor {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+            depth_stencil_state: Some(super::DEPTH_DESC),
+            vertex_state: wgpu::VertexStateDescriptor {
+                index_format: wgpu::IndexFormat::Uint16,
+                vertex_buffers: &[wgpu::VertexBufferDescriptor {
+                    stride: size_of::<Instance>() as wgpu::BufferAddress,
+                    step_mode: wgpu::InputStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float2, 1 => Float2, 2 => Float2, 3 => Float2, 4 => Float
+                    ],
+                }],
+            },
+            sample_count,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        };
+        let render_pipeline = device.create_render_pipeline(&desc);
+        let depth_pipeline = super::new_depth_pipeline(device, desc);
+
+        Pipeline {
+            bind_group_layout,
+            tex_bind_group_layout,
+            sampler,
+            image_sampler,
+            render_pipeline,
+            depth_pipeline,
+            fade_pipeline,
+            fade_bind_group,
+            blur_pipeline,
+            shadow_pipeline,
+        }
+    }
+
+    /// Construct per-window state
+    pub fn new_window(&self, device: &wgpu::Device, size: Size) -> Window {
+        type Scale = [f32; 2];
+        let scale_factor: Scale = [2.0 / size.0 as f32, -2.0 / size.1 as f32];
+        let scale_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("BL scale_buf"),
+            contents: bytemuck::cast_slice(&scale_factor),
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("BL bind group"),
+            layout: &self.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(scale_buf.slice(..)),
+            }],
+        });
+
+        Window {
+            bind_group,
+            scale_buf,
+            passes: vec![],
+            buffer: None,
+            draws: vec![],
+        }
+    }
+
+    /// Construct a render target of the given `size`
+    pub fn new_target(&self, device: &wgpu::Device, size: Size) -> Target {
+        let usage = wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED;
+        let texture = self.new_texture(device, size, TEX_FORMAT, usage);
+        self.make_target(device, texture, &self.sampler, size)
+    }
+
+    /// Construct an (initially transparent) target for uploaded images
+    pub fn new_atlas(&self, device: &wgpu::Device, size: Size) -> Target {
+        let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+        let usage = wgpu::TextureUsage::COPY_DST | wgpu::TextureUsage::SAMPLED;
+        let texture = self.new_texture(device, size, format, usage);
+        self.make_target(device, texture, &self.image_sampler, size)
+    }
+
+    fn new_texture(
+        &self,
+        device: &wgpu::Device,
+        size: Size,
+        format: wgpu::TextureFormat,
+        usage: wgpu::TextureUsage,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("BL target"),
+            size: wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+        })
+    }
+
+    fn make_target(
+        &self,
+        device: &wgpu::Device,
+        texture: wgpu::Texture,
+        sampler: &wgpu::Sampler,
+        size: Size,
+    ) -> Target {
+        let view = texture.create_view(&Default::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("BL target bind group"),
+            layout: &self.tex_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+
+        Target {
+            texture,
+            view,
+            bind_group,
+            size,
+        }
+    }
+
+    /// Construct a render buffer
+    ///
+    /// Quads must first be uploaded via [`Window::prepare`]. Those whose
+    /// source is not found by `targets` are skipped.
+    pub fn render_buf<'a, F: Fn(Source) -> Option<&'a Target>>(
+        &'a self,
+        window: &'a Window,
+        pass: usize,
+        targets: F,
+    ) -> Option<RenderBuffer<'a>> {
+        let draws: Vec<_> = window
+            .draws
+            .get(pass)?
+            .iter()
+            .filter_map(|(source, range)| {
+                targets(*source).map(|target| (&target.bind_group, range.clone()))
+            })
+            .collect();
+        if draws.is_empty() {
+            return None;
+        }
+
+        Some(RenderBuffer {
+            pipe: &self.render_pipeline,
+            depth_pipe: self.depth_pipeline.as_ref(),
+            bind_group: &window.bind_group,
+            draws,
+            buffer: window.buffer.as_ref()?,
+        })
+    }
+}
+
+impl Pipeline {
+    /// Multiply all channels of (window-sized) `target` by `opacity`
+    ///
+    /// Since target content is premultiplied, blitting it afterwards
+    /// composites with the given opacity.
+    pub fn fade(
+        &self,
+        window: &Window,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &Target,
+        opacity: f32,
+    ) {
+        let rect = Quad::with_coords(Vec2::ZERO, Vec2::from(target.size));
+        let tex = Quad::with_coords(Vec2::ZERO, Vec2::splat(1.0));
+        let instance = Instance::new(rect, tex, 0.0);
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("BL fade buffer"),
+            contents: bytemuck::cast_slice(&[instance]),
+            usage: wgpu::BufferUsage::VERTEX,
+        });
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: &target.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        rpass.set_pipeline(&self.fade_pipeline);
+        let opacity = f64::from(opacity);
+        rpass.set_blend_color(wgpu::Color {
+            r: opacity,
+            g: opacity,
+            b: opacity,
+            a: opacity,
+        });
+        rpass.set_bind_group(0, &window.bind_group, &[]);
+        rpass.set_bind_group(1, &self.fade_bind_group, &[]);
+        rpass.set_vertex_buffer(0, buffer.slice(..));
+        rpass.draw(0..6, 0..1);
+    }
+}
+
+impl Pipeline {
+    /// Blur (window-sized) `source` to `dest`
+    ///
+    /// The destination is cleared first.
+    pub fn blur(
+        &self,
+        window: &Window,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &Target,
+        dest: &Target,
+        blur: &Blur,
+    ) {
+        let steps = (blur.radius.ceil() as i32).max(0).min(MAX_BLUR_STEPS);
+        let step = match steps {
+            0 => 0.0,
+            n => blur.radius / n as f32,
+        };
+        let sigma = (blur.radius / 3.0).max(f32::EPSILON);
+        let weights: Vec<f32> = (-steps..=steps)
+            .map(|i| {
+                let x = i as f32 * step;
+                (-x * x / (2.0 * sigma * sigma)).exp()
+            })
+            .collect();
+        let sum: f32 = weights.iter().sum();
+
+        let scale = Vec2(1.0 / source.size.0 as f32, 1.0 / source.size.1 as f32);
+        let instances: Vec<Instance> = (-steps..=steps)
+            .map(|i| {
+                let d = blur.direction * (i as f32 * step);
+                let tex = Quad::with_coords((blur.rect.a - d) * scale, (blur.rect.b - d) * scale);
+                Instance::new(blur.rect, tex, 0.0)
+            })
+            .collect();
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("BL blur buffer"),
+            contents: bytemuck::cast_slice(&instances),
+            usage: wgpu::BufferUsage::VERTEX,
+        });
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: &dest.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        rpass.set_pipeline(match blur.shadow {
+            false => &self.blur_pipeline,
+            true => &self.shadow_pipeline,
+        });
+        rpass.set_bind_group(0, &window.bind_group, &[]);
+        rpass.set_bind_group(1, &source.bind_group, &[]);
+        rpass.set_vertex_buffer(0, buffer.slice(..));
+        for (i, weight) in weights.into_iter().enumerate() {
+            let w = f64::from(weight / sum * blur.opacity);
+            rpass.set_blend_color(wgpu::Color {
+                r: w,
+                g: w,
+                b: w,
+                a: w,
+            });
+            let i = i as u32;
+            rpass.draw(0..6, i..(i + 1));
+        }
+    }
+}
+
+impl Target {
+    /// Write image data `rgba` of the given `size` at `origin`
+    ///
+    /// This is only valid for targets constructed by [`Pipeline::new_atlas`].
+    pub fn write(&self, queue: &wgpu::Queue, origin: (u32, u32), size: Size, rgba: &[u8]) {
+        queue.write_texture(
+            wgpu::TextureCopyView {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: origin.0,
+                    y: origin.1,
+                    z: 0,
+                },
+            },
+            rgba,
+            wgpu::TextureDataLayout {
+                offset: 0,
+                bytes_per_row: 4 * size.0,
+                rows_per_image: size.1,
+            },
+            wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth: 1,
+            },
+        );
+    }
+}
+
+impl Window {
+    /// Upload quads of all passes to a single instance buffer
+    ///
+    /// This is called once per frame, before rendering any pass. Consecutive
+    /// quads of each pass with the same source are batched into one
+    /// (instanced) draw.
+    pub fn prepare(&mut self, device: &wgpu::Device) {
+        let mut instances = vec![];
+        self.draws.resize_with(self.passes.len(), Vec::new);
+        for (pass, draws) in self.passes.iter_mut().zip(self.draws.iter_mut()) {
+            draws.clear();
+            for (source, quad) in pass.drain(..) {
+                let index = instances.len() as u32;
+                instances.push(quad);
+                match draws.last_mut() {
+                    Some((last, range)) if *last == source => range.end = index + 1,
+                    _ => draws.push((source, index..(index + 1))),
+                }
+            }
+        }
+
+        self.buffer = match instances.is_empty() {
+            true => None,
+            false => Some(
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("BL instances"),
+                    contents: bytemuck::cast_slice(&instances),
+                    usage: wgpu::BufferUsage::VERTEX,
+                }),
+            ),
+        };
+    }
+
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        size: Size,
+    ) {
+        type Scale = [f32; 2];
+        let scale_factor: Scale = [2.0 / size.0 as f32, -2.0 / size.1 as f32];
+        let scale_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("BL scale_buf copy"),
+            contents: bytemuck::cast_slice(&scale_factor),
+            usage: wgpu::BufferUsage::COPY_SRC,
+        });
+        let byte_len = size_of::<Scale>() as u64;
+
+        encoder.copy_buffer_to_buffer(&scale_buf, 0, &self.scale_buf, 0, byte_len);
+    }
+
+    /// Draw the region `rect` of the target `source` to `dest`
+    ///
+    /// The target must have size `size`.
+    pub fn blit(&mut self, pass: Pass, source: Source, rect: Quad, dest: Quad, size: Size) {
+        let scale = Vec2(1.0 / size.0 as f32, 1.0 / size.1 as f32);
+        let tex = Quad::with_coords(rect.a * scale, rect.b * scale);
+        self.push(pass, source, dest, tex);
+    }
+
+    /// Draw the `region` (texture coordinates) of atlas page `source`,
+    /// scaled to `rect`
+    pub fn image(&mut self, pass: Pass, source: Source, rect: Quad, region: Quad) {
+        self.push(pass, source, rect, region);
+    }
+
+    fn push(&mut self, pass: Pass, source: Source, rect: Quad, tex: Quad) {
+        let aa = rect.a;
+        let bb = rect.b;
+
+        if !aa.lt(bb) {
+            // zero / negative size: nothing to draw
+            return;
+        }
+
+        let quad = Instance::new(rect, tex, pass.depth());
+
+        let pass = pass.pass();
+        if self.passes.len() <= pass {
+            // We only need one more, but no harm in adding extra
+            self.passes.resize(pass + 8, vec![]);
+        }
+
+        self.passes[pass].push((source, quad));
+    }
+}
+
+/// Construct a single-sampled pipeline without depth buffer
+///
+/// These pipelines apply effects to targets using the blend `state`.
+fn new_effect_pipeline(
+    device: &wgpu::Device,
+    shaders: &ShaderManager,
+    layout: &wgpu::PipelineLayout,
+    label: &str,
+    state: wgpu::ColorStateDescriptor,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(layout),
+        vertex_stage: wgpu::ProgrammableStageDescriptor {
+            module: &shaders.vert_quad,
+            entry_point: "main",
+        },
+        fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+            module: &shaders.frag_blit,
+            entry_point: "main",
+        }),
+        rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: wgpu::CullMode::None,
+            clamp_depth: false,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+        }),
+        primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+        color_states: &[state],
+        depth_stencil_state: None,
+        vertex_state: wgpu::VertexStateDescriptor {
+            index_format: wgpu::IndexFormat::Uint16,
+            vertex_buffers: &[wgpu::VertexBufferDescriptor {
+                stride: size_of::<Instance>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Instance,
+                attributes: &wgpu::vertex_attr_array![
+                    0 => Float2, 1 => Float2, 2 => Float2, 3 => Float2, 4 => Float
+                ],
+            }],
+        },
+        sample_count: 1,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    })
+}
@@ -0,0 +1,79 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Colour filters (debug tool)
+
+use kas::draw::Colour;
+
+/// Colour-blindness simulation filter
+///
+/// When enabled, all colours passed to the draw pipes (including text and the
+/// window's clear colour, excluding custom pipes) are transformed to simulate
+/// the given colour vision deficiency. This allows checking that themes and
+/// status colours remain distinguishable.
+///
+/// Simulation uses the model of Machado, Oliveira and Fernandes (2009) at full
+/// severity, applied in linear RGB space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColourFilter {
+    /// No filter
+    None,
+    /// Simulate absence of L (red) cones
+    Protanopia,
+    /// Simulate absence of M (green) cones
+    Deuteranopia,
+    /// Simulate absence of S (blue) cones
+    Tritanopia,
+}
+
+impl Default for ColourFilter {
+    fn default() -> Self {
+        ColourFilter::None
+    }
+}
+
+impl ColourFilter {
+    /// Get the next filter, cycling back to [`ColourFilter::None`]
+    pub fn next(self) -> Self {
+        match self {
+            ColourFilter::None => ColourFilter::Protanopia,
+            ColourFilter::Protanopia => ColourFilter::Deuteranopia,
+            ColourFilter::Deuteranopia => ColourFilter::Tritanopia,
+            ColourFilter::Tritanopia => ColourFilter::None,
+        }
+    }
+
+    /// Apply the filter to a colour
+    pub fn apply(self, col: Colour) -> Colour {
+        let m = match self {
+            ColourFilter::None => return col,
+            ColourFilter::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            ColourFilter::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            ColourFilter::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        };
+        let f = |row: [f32; 3]| {
+            let x = row[0] * col.r + row[1] * col.g + row[2] * col.b;
+            x.max(0.0).min(1.0)
+        };
+        Colour {
+            r: f(m[0]),
+            g: f(m[1]),
+            b: f(m[2]),
+            a: col.a,
+        }
+    }
+}
@@ -24,13 +24,16 @@ pub trait CustomPipeBuilder {
 
     /// Build a pipe
     ///
-    /// The given texture format and depth format should be used to construct a
-    /// compatible [`wgpu::RenderPipeline`].
+    /// The given texture format, depth format and sample count should be used
+    /// to construct a compatible [`wgpu::RenderPipeline`]. The sample count is
+    /// greater than 1 when multi-sampled anti-aliasing is enabled (see
+    /// [`crate::Options::msaa_samples`]).
     fn build(
         &mut self,
         device: &wgpu::Device,
         tex_format: wgpu::TextureFormat,
         depth_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) -> Self::Pipe;
 }
 
@@ -110,6 +113,9 @@ pub trait CustomPipe {
     /// `depth_compare: wgpu::CompareFunction::GreaterEqual` to avoid drawing
     /// over pop-up elements and outside of scroll regions.
     ///
+    /// When multi-sampling, `frame_view` is the multi-sampled target, which is
+    /// resolved after this method.
+    ///
     /// This method is optional; by default it does nothing.
     #[allow(unused)]
     fn render_final<'a>(
@@ -148,6 +154,7 @@ impl CustomPipeBuilder for () {
         _: &wgpu::Device,
         _: wgpu::TextureFormat,
         _: wgpu::TextureFormat,
+        _: u32,
     ) -> Self::Pipe {
         ()
     }
@@ -0,0 +1,129 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Path drawing API for `kas_wgpu`
+//!
+//! Paths are tessellated via `lyon` into triangles, drawn by the
+//! `shaded_square` pipeline.
+
+use log::warn;
+use lyon_tessellation::math::{point, Point};
+use lyon_tessellation::{self as lt, BuffersBuilder, VertexBuffers};
+use lyon_tessellation::{FillAttributes, FillOptions, FillTessellator};
+use lyon_tessellation::{StrokeAttributes, StrokeOptions, StrokeTessellator};
+
+use super::{CustomWindow, DrawWindow};
+use kas::draw::{Colour, DrawPath, FillRule, LineCap, LineJoin, Pass, Path, PathCmd, StrokeStyle};
+use kas::geom::Vec2;
+
+/// Maximum deviation of tessellated curves, in pixels
+const TOLERANCE: f32 = 0.1;
+
+fn to_point(Vec2(x, y): Vec2) -> Point {
+    point(x, y)
+}
+
+fn to_lyon(path: &Path) -> lt::path::Path {
+    let mut builder = lt::path::Path::builder();
+    let mut open = false;
+    let mut start = Vec2::ZERO;
+    let mut last = Vec2::ZERO;
+    for cmd in path.commands() {
+        let is_segment = !matches!(cmd, PathCmd::MoveTo(_) | PathCmd::Close);
+        if is_segment && !open {
+            // Segments not preceded by a move start at the current point
+            builder.move_to(to_point(last));
+            open = true;
+            start = last;
+        }
+        match *cmd {
+            PathCmd::MoveTo(p) => {
+                builder.move_to(to_point(p));
+                open = true;
+                start = p;
+                last = p;
+            }
+            PathCmd::LineTo(p) => {
+                builder.line_to(to_point(p));
+                last = p;
+            }
+            PathCmd::QuadTo(c, p) => {
+                builder.quadratic_bezier_to(to_point(c), to_point(p));
+                last = p;
+            }
+            PathCmd::CubicTo(c1, c2, p) => {
+                builder.cubic_bezier_to(to_point(c1), to_point(c2), to_point(p));
+                last = p;
+            }
+            PathCmd::Close => {
+                if open {
+                    builder.close();
+                    open = false;
+                }
+                last = start;
+            }
+        }
+    }
+    builder.build()
+}
+
+impl<CW: CustomWindow + 'static> DrawPath for DrawWindow<CW> {
+    fn fill_path(&mut self, pass: Pass, path: &Path, rule: FillRule, col: Colour) {
+        let path = to_lyon(path);
+        let rule = match rule {
+            FillRule::NonZero => lt::FillRule::NonZero,
+            FillRule::EvenOdd => lt::FillRule::EvenOdd,
+        };
+        let options = FillOptions::tolerance(TOLERANCE).with_fill_rule(rule);
+
+        let mut geometry: VertexBuffers<Vec2, u32> = VertexBuffers::new();
+        let mut output =
+            BuffersBuilder::new(&mut geometry, |p: Point, _: FillAttributes| Vec2(p.x, p.y));
+        let result = FillTessellator::new().tessellate_path(&path, &options, &mut output);
+        if let Err(e) = result {
+            warn!("Failed to tessellate path fill: {:?}", e);
+            return;
+        }
+
+        let col = self.colour_filter.apply(col);
+        self.shaded_square
+            .triangles(pass, &geometry.vertices, &geometry.indices, col);
+    }
+
+    fn stroke_path(&mut self, pass: Pass, path: &Path, style: &StrokeStyle, col: Colour) {
+        let path = match style.dashes.is_empty() {
+            true => to_lyon(path),
+            false => to_lyon(&path.dashed(&style.dashes, style.dash_offset, TOLERANCE)),
+        };
+        let join = match style.join {
+            LineJoin::Miter => lt::LineJoin::Miter,
+            LineJoin::Round => lt::LineJoin::Round,
+            LineJoin::Bevel => lt::LineJoin::Bevel,
+        };
+        let cap = match style.cap {
+            LineCap::Butt => lt::LineCap::Butt,
+            LineCap::Round => lt::LineCap::Round,
+            LineCap::Square => lt::LineCap::Square,
+        };
+        let options = StrokeOptions::tolerance(TOLERANCE)
+            .with_line_width(style.width)
+            .with_line_join(join)
+            .with_line_cap(cap);
+
+        let mut geometry: VertexBuffers<Vec2, u32> = VertexBuffers::new();
+        let mut output = BuffersBuilder::new(&mut geometry, |p: Point, _: StrokeAttributes| {
+            Vec2(p.x, p.y)
+        });
+        let result = StrokeTessellator::new().tessellate_path(&path, &options, &mut output);
+        if let Err(e) = result {
+            warn!("Failed to tessellate path stroke: {:?}", e);
+            return;
+        }
+
+        let col = self.colour_filter.apply(col);
+        self.shaded_square
+            .triangles(pass, &geometry.vertices, &geometry.indices, col);
+    }
+}
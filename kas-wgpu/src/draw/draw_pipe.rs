@@ -6,18 +6,44 @@
 //! Drawing API for `kas_wgpu`
 
 use std::any::Any;
+use std::collections::HashMap;
 use std::f32::consts::FRAC_PI_2;
 use wgpu::TextureView;
 use wgpu_glyph::GlyphBrushBuilder;
 
 use super::{
-    flat_round, shaded_round, shaded_square, CustomPipe, CustomPipeBuilder, CustomWindow, DrawPipe,
-    DrawWindow, ShaderManager, TEX_FORMAT,
+    atlas, blit, flat_round, shaded_round, shaded_square, CustomPipe, CustomPipeBuilder,
+    CustomWindow, DrawPipe, DrawWindow, GlyphBrush, ShaderManager, TEX_FORMAT,
+};
+use kas::draw::{
+    Border, Colour, CornerRadii, Draw, DrawImage, DrawPath, DrawRounded, DrawShaded, DrawShared,
+    DrawText, IconImage, ImageError, ImageId, Layer, Pass, SizeHandle, TextClass,
 };
-use kas::draw::{Colour, Draw, DrawRounded, DrawShaded, DrawShared, Pass};
 use kas::geom::{Coord, Quad, Rect, Size, Vec2};
+use kas::layout::AxisInfo;
+use kas::text::{FallbackFont, PreparedText};
+
+/// Initial side length of the glyph cache texture
+///
+/// The cache is shared by all windows and persists across frames. Should it
+/// become full, `glyph_brush` enlarges it (re-rasterising cached glyphs).
+const GLYPH_CACHE_SIZE: u32 = 1024;
+
+fn new_glyph_brush(device: &wgpu::Device) -> GlyphBrush {
+    // Fonts are added by DrawPipe::sync_fonts since they may be loaded later
+    GlyphBrushBuilder::using_fonts(vec![])
+        .initial_cache_size((GLYPH_CACHE_SIZE, GLYPH_CACHE_SIZE))
+        .depth_stencil_state(super::GLPYH_DEPTH_DESC)
+        .build(device, TEX_FORMAT)
+}
 
-fn make_depth_texture(device: &wgpu::Device, size: Size) -> Option<TextureView> {
+fn make_attachment(
+    device: &wgpu::Device,
+    size: Size,
+    label: &str,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> Option<TextureView> {
     // NOTE: initially the DrawWindow is created with Size::ZERO to calculate
     // initial window size. Wgpu does not support creation of zero-sized
     // textures, so as a special case we return None here:
@@ -26,16 +52,16 @@ fn make_depth_texture(device: &wgpu::Device, size: Size) -> Option<TextureView>
     }
 
     let tex = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("window depth"),
+        label: Some(label),
         size: wgpu::Extent3d {
             width: size.0,
             height: size.1,
             depth: 1,
         },
         mip_level_count: 1,
-        sample_count: 1,
+        sample_count,
         dimension: wgpu::TextureDimension::D2,
-        format: super::DEPTH_FORMAT,
+        format,
         usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
     });
     Some(tex.create_view(&Default::default()))
@@ -43,19 +69,24 @@ fn make_depth_texture(device: &wgpu::Device, size: Size) -> Option<TextureView>
 
 impl<C: CustomPipe> DrawPipe<C> {
     /// Construct
+    ///
+    /// If `sample_count > 1`, multi-sampled anti-aliasing is used.
     pub fn new<CB: CustomPipeBuilder<Pipe = C>>(
         mut custom: CB,
         device: &wgpu::Device,
         shaders: &ShaderManager,
+        sample_count: u32,
     ) -> Self {
         // Create staging belt and a local pool
         let staging_belt = wgpu::util::StagingBelt::new(1024);
         let local_pool = futures::executor::LocalPool::new();
 
-        let shaded_square = shaded_square::Pipeline::new(device, shaders);
-        let shaded_round = shaded_round::Pipeline::new(device, shaders);
-        let flat_round = flat_round::Pipeline::new(device, shaders);
-        let custom = custom.build(&device, TEX_FORMAT, super::DEPTH_FORMAT);
+        let shaded_square = shaded_square::Pipeline::new(device, shaders, sample_count);
+        let shaded_round = shaded_round::Pipeline::new(device, shaders, sample_count);
+        let flat_round = flat_round::Pipeline::new(device, shaders, sample_count);
+        let blit = blit::Pipeline::new(device, shaders, sample_count);
+        let custom = custom.build(&device, TEX_FORMAT, super::DEPTH_FORMAT, sample_count);
+        let glyph_brush = new_glyph_brush(device);
 
         DrawPipe {
             local_pool,
@@ -63,7 +94,89 @@ impl<C: CustomPipe> DrawPipe<C> {
             shaded_square,
             shaded_round,
             flat_round,
+            blit,
             custom,
+            glyph_brush,
+            glyph_fonts: 0,
+            glyph_fallbacks: 0,
+            sample_count,
+        }
+    }
+
+    /// Rebuild pipelines from (reloaded) shaders
+    ///
+    /// Bind group layouts are recreated too; since wgpu de-duplicates equal
+    /// layouts, existing per-window state remains compatible.
+    pub fn reload_shaders(&mut self, device: &wgpu::Device, shaders: &ShaderManager) {
+        let sample_count = self.sample_count;
+        self.shaded_square = shaded_square::Pipeline::new(device, shaders, sample_count);
+        self.shaded_round = shaded_round::Pipeline::new(device, shaders, sample_count);
+        self.flat_round = flat_round::Pipeline::new(device, shaders, sample_count);
+        self.blit = blit::Pipeline::new(device, shaders, sample_count);
+    }
+
+    /// Construct the render target of a cached region or layer
+    ///
+    /// If `own_depth`, depth buffers are allocated for the target.
+    fn new_cache(&self, device: &wgpu::Device, size: Size, own_depth: bool) -> super::Cache {
+        let (mut depth, mut text_depth) = (None, None);
+        if own_depth {
+            let format = super::DEPTH_FORMAT;
+            depth = make_attachment(device, size, "cache depth", format, self.sample_count);
+            if self.sample_count > 1 {
+                text_depth = make_attachment(device, size, "cache text depth", format, 1);
+            }
+        }
+        super::Cache {
+            target: self.blit.new_target(device, size),
+            msaa: self.new_msaa(device, size),
+            depth,
+            text_depth,
+            shadow: None,
+            stale: false,
+            used: true,
+        }
+    }
+
+    /// Construct a multi-sampled colour target (if multi-sampling)
+    fn new_msaa(&self, device: &wgpu::Device, size: Size) -> Option<TextureView> {
+        match self.sample_count {
+            1 => None,
+            n => make_attachment(device, size, "msaa", TEX_FORMAT, n),
+        }
+    }
+
+    /// (Re-)create the depth buffer and multi-sampling targets of a window
+    fn make_attachments(
+        &self,
+        window: &mut DrawWindow<C::Window>,
+        device: &wgpu::Device,
+        size: Size,
+    ) {
+        let depth_format = super::DEPTH_FORMAT;
+        window.depth = make_attachment(device, size, "depth", depth_format, self.sample_count);
+        window.msaa = self.new_msaa(device, size);
+        if self.sample_count > 1 {
+            window.text_depth = make_attachment(device, size, "text depth", depth_format, 1);
+        }
+    }
+
+    /// Add fonts loaded since the last call to the glyph brush
+    ///
+    /// Fallback fonts follow the fonts of `kas-text`. The latter has only a
+    /// single (default) font, thus this order is stable once it is loaded.
+    fn sync_fonts(&mut self) {
+        let fonts = kas::text::fonts().ab_glyph_fonts_vec();
+        if fonts.is_empty() {
+            return;
+        }
+        for font in &fonts[self.glyph_fonts..] {
+            self.glyph_brush.add_font(*font);
+        }
+        self.glyph_fonts = fonts.len();
+        while let Some(font) = FallbackFont::get(self.glyph_fallbacks) {
+            self.glyph_brush.add_font(font.font());
+            self.glyph_fallbacks += 1;
         }
     }
 
@@ -88,22 +201,31 @@ impl<C: CustomPipe> DrawPipe<C> {
         let shaded_square = self.shaded_square.new_window(device, size, norm);
         let shaded_round = self.shaded_round.new_window(device, size, norm);
         let flat_round = self.flat_round.new_window(device, size);
+        let blit = self.blit.new_window(device, size);
         let custom = self.custom.new_window(device, size);
 
-        let fonts = kas::text::fonts().ab_glyph_fonts_vec();
-        let glyph_brush = GlyphBrushBuilder::using_fonts(fonts)
-            .depth_stencil_state(super::GLPYH_DEPTH_DESC)
-            .build(device, TEX_FORMAT);
-
-        DrawWindow {
-            depth: make_depth_texture(device, size),
+        let mut window = DrawWindow {
+            depth: None,
+            msaa: None,
+            text_depth: None,
             clip_regions: vec![rect],
             shaded_square,
             shaded_round,
             flat_round,
+            blit,
             custom,
-            glyph_brush,
-        }
+            caches: HashMap::new(),
+            layers: vec![],
+            blur_target: None,
+            icons: HashMap::new(),
+            atlases: Default::default(),
+            cache_passes: vec![],
+            text: vec![],
+            fallback_fonts: vec![],
+            colour_filter: Default::default(),
+        };
+        self.make_attachments(&mut window, device, size);
+        window
     }
 
     /// Process window resize
@@ -113,7 +235,7 @@ impl<C: CustomPipe> DrawPipe<C> {
         device: &wgpu::Device,
         size: Size,
     ) -> wgpu::CommandBuffer {
-        window.depth = make_depth_texture(device, size);
+        self.make_attachments(window, device, size);
         window.clip_regions[0].size = size;
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("resize"),
@@ -123,31 +245,298 @@ impl<C: CustomPipe> DrawPipe<C> {
         self.custom
             .resize(&mut window.custom, device, &mut encoder, size);
         window.flat_round.resize(device, &mut encoder, size);
+        window.blit.resize(device, &mut encoder, size);
         encoder.finish()
     }
 
-    /// Render batched draw instructions via `rpass`
-    pub fn render(
+    /// Warm up pipelines and the glyph cache
+    ///
+    /// This draws each primitive type and the printable ASCII characters (at
+    /// the size and DPI of `size_handle`) to an off-screen target, such that
+    /// pipeline set-up and glyph rasterisation do not delay the first frame.
+    pub fn warm_up(
         &mut self,
         window: &mut DrawWindow<C::Window>,
+        size_handle: &mut dyn SizeHandle,
         device: &mut wgpu::Device,
         queue: &mut wgpu::Queue,
+    ) {
+        let size = window.clip_regions[0].size;
+        if size.0 * size.1 == 0 {
+            return;
+        }
+
+        let ascii: String = (b' '..=b'~').map(char::from).collect();
+        let mut text = PreparedText::new_single(ascii.as_str().into());
+        size_handle.text_bound(
+            &mut text,
+            TextClass::LabelSingle,
+            AxisInfo::new(false, None),
+        );
+
+        let pass = kas_theme::START_PASS;
+        let col = Colour::grey(0.5);
+        let dpem = size_handle.dpem();
+        let quad = Quad::with_pos_and_size(Vec2::ZERO, Vec2::splat(dpem));
+        window.rect(pass, quad, col);
+        window.circle(pass, quad, 0.5, col);
+        window.shaded_square(pass, quad, (0.0, 0.0), col);
+        window.shaded_circle(pass, quad, (0.0, 0.0), col);
+        window.text(pass, Vec2::ZERO, Vec2::ZERO, col, &text);
+
+        let tex = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("warm-up"),
+            size: wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TEX_FORMAT,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        });
+        let view = tex.create_view(&Default::default());
+        let load = wgpu::LoadOp::Clear(wgpu::Color::BLACK);
+        self.render(window, device, queue, &view, load);
+    }
+
+    /// Render the content of cached regions to their targets
+    ///
+    /// Passes are rendered in reverse order such that nested caches are
+    /// complete before being drawn to their parent.
+    fn render_caches(
+        &mut self,
+        window: &mut DrawWindow<C::Window>,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        size: Size,
+    ) {
+        for (pass, source, layer) in &window.cache_passes {
+            let cache = match *source {
+                blit::Source::Cache(key) => {
+                    let size = window.clip_regions[*pass].size;
+                    let cache = window
+                        .caches
+                        .entry(key)
+                        .or_insert_with(|| self.new_cache(device, size, true));
+                    if cache.target.size != size {
+                        *cache = self.new_cache(device, size, true);
+                    }
+                    cache
+                }
+                blit::Source::Layer(index) => {
+                    // Layers are allocated in order, hence index <= len
+                    if index as usize == window.layers.len() {
+                        window.layers.push(self.new_cache(device, size, false));
+                    }
+                    let cache = &mut window.layers[index as usize];
+                    if cache.target.size != size {
+                        *cache = self.new_cache(device, size, false);
+                    }
+                    cache
+                }
+                _ => unreachable!(),
+            };
+            if layer.shadow.is_some() && cache.shadow.as_ref().map(|t| t.size) != Some(size) {
+                cache.shadow = Some(self.blit.new_target(device, size));
+            }
+            cache.used = true;
+        }
+
+        let passes = &window.cache_passes;
+        let effects = passes
+            .iter()
+            .any(|p| p.2.blur > 0.0 || p.2.shadow.is_some());
+        if effects && window.blur_target.as_ref().map(|t| t.size) != Some(size) {
+            window.blur_target = Some(self.blit.new_target(device, size));
+        }
+
+        for (pass, source, layer) in window.cache_passes.iter().rev() {
+            let (pass, rect) = (*pass, window.clip_regions[*pass]);
+            let ss = self.shaded_square.render_buf(&window.shaded_square, pass);
+            let sr = self.shaded_round.render_buf(&window.shaded_round, pass);
+            let fr = self.flat_round.render_buf(&window.flat_round, pass);
+            let targets = blit_targets(&window.caches, &window.layers, &window.atlases);
+            let bl = self.blit.render_buf(&window.blit, pass, targets);
+
+            let cache = match *source {
+                blit::Source::Cache(key) => &window.caches[&key],
+                blit::Source::Layer(index) => &window.layers[index as usize],
+                _ => unreachable!(),
+            };
+            let (target, view) = (&cache.target, &cache.target.view);
+            // Content is drawn in window coordinates, offset to the target
+            let (offset, depth, text_depth) = match *source {
+                blit::Source::Cache(_) => (rect.pos, &cache.depth, &cache.text_depth),
+                _ => (Coord::ZERO, &window.depth, &window.text_depth),
+            };
+            let scissor = rect - offset;
+            let depth_stencil_attachment = wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                attachment: depth.as_ref().unwrap(),
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(kas_theme::START_PASS.depth()),
+                    store: true,
+                }),
+                stencil_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0),
+                    store: true,
+                }),
+            };
+
+            {
+                let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                        attachment: cache.msaa.as_ref().unwrap_or(view),
+                        resolve_target: cache.msaa.as_ref().map(|_| view),
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: Some(depth_stencil_attachment.clone()),
+                });
+                set_viewport(&mut rpass, offset, size);
+                rpass.set_scissor_rect(
+                    scissor.pos.0 as u32,
+                    scissor.pos.1 as u32,
+                    scissor.size.0,
+                    scissor.size.1,
+                );
+
+                ss.as_ref().map(|buf| buf.render(&mut rpass));
+                sr.as_ref().map(|buf| buf.render(&mut rpass));
+                fr.as_ref().map(|buf| buf.render(&mut rpass));
+                bl.as_ref().map(|buf| buf.render(&mut rpass));
+                self.custom
+                    .render_pass(&mut window.custom, device, pass, &mut rpass);
+            }
+
+            let mut any_text = false;
+            for (_, glyphs, extra, bounds) in window.text.iter().filter(|t| t.0 == pass) {
+                self.glyph_brush
+                    .queue_pre_positioned(glyphs.clone(), extra.clone(), *bounds);
+                any_text = true;
+            }
+            if any_text {
+                let mut text_depth_attachment = wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    }),
+                    stencil_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    }),
+                    ..depth_stencil_attachment
+                };
+                if let Some(text_depth) = text_depth.as_ref() {
+                    let load = wgpu::LoadOp::Clear(kas_theme::START_PASS.depth());
+                    let mut rpass = begin_depth_pass(encoder, text_depth, load);
+                    set_viewport(&mut rpass, offset, size);
+                    rpass.set_scissor_rect(
+                        scissor.pos.0 as u32,
+                        scissor.pos.1 as u32,
+                        scissor.size.0,
+                        scissor.size.1,
+                    );
+
+                    ss.as_ref().map(|buf| buf.render_depth(&mut rpass));
+                    sr.as_ref().map(|buf| buf.render_depth(&mut rpass));
+                    fr.as_ref().map(|buf| buf.render_depth(&mut rpass));
+                    bl.as_ref().map(|buf| buf.render_depth(&mut rpass));
+                    drop(rpass);
+
+                    text_depth_attachment.attachment = text_depth;
+                    text_depth_attachment.stencil_ops = None;
+                }
+                self.glyph_brush
+                    .draw_queued_with_transform(
+                        device,
+                        &mut self.staging_belt,
+                        encoder,
+                        view,
+                        text_depth_attachment,
+                        text_transform(offset, target.size),
+                    )
+                    .expect("glyph_brush.draw_queued");
+            }
+
+            if layer.opacity < 1.0 {
+                let opacity = layer.opacity;
+                self.blit
+                    .fade(&window.blit, device, encoder, target, opacity);
+            }
+
+            // Blur horizontally to a temporary target, then vertically to dest
+            let temp = window.blur_target.as_ref();
+            let mut apply_blur =
+                |source: &blit::Target, dest: &blit::Target, radius: f32, opacity, shadow| {
+                    let temp = temp.unwrap();
+                    let mut blur = blit::Blur {
+                        rect: Quad::from(rect).shrink(-radius),
+                        direction: Vec2(1.0, 0.0),
+                        radius,
+                        opacity: 1.0,
+                        shadow,
+                    };
+                    self.blit
+                        .blur(&window.blit, device, encoder, source, temp, &blur);
+                    blur.direction = Vec2(0.0, 1.0);
+                    blur.opacity = opacity;
+                    self.blit
+                        .blur(&window.blit, device, encoder, temp, dest, &blur);
+                };
+            // The shadow is cast by content before blurring
+            if let Some(shadow) = layer.shadow {
+                let dest = cache.shadow.as_ref().unwrap();
+                apply_blur(target, dest, shadow.radius, shadow.opacity, true);
+            }
+            if layer.blur > 0.0 {
+                apply_blur(target, target, layer.blur, 1.0, false);
+            }
+        }
+    }
+
+    /// Render batched draw instructions to `frame_view`
+    ///
+    /// The frame is initialised according to `load`: usually this clears to
+    /// the theme's background colour, but [`wgpu::LoadOp::Load`] may be used
+    /// to draw over existing content. The latter is not supported when
+    /// multi-sampling, since drawing then targets a separate texture.
+    pub fn render(
+        &mut self,
+        window: &mut DrawWindow<C::Window>,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
         frame_view: &wgpu::TextureView,
-        clear_color: wgpu::Color,
+        load: wgpu::LoadOp<wgpu::Color>,
     ) {
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("render"),
         });
 
         self.custom.update(&mut window.custom, device, &mut encoder);
+        self.sync_fonts();
 
+        window.atlases.prepare(&self.blit, device, queue);
+
+        // Vertices of all passes are uploaded once per frame
+        window.shaded_square.prepare(device);
+        window.shaded_round.prepare(device);
+        window.flat_round.prepare(device);
+        window.blit.prepare(device);
+
+        let size = window.clip_regions[0].size;
+        self.render_caches(window, device, &mut encoder, size);
+
+        // When multi-sampling, we render to window.msaa and resolve at the end
+        let color_view = window.msaa.as_ref().unwrap_or(frame_view);
         let mut color_attachments = [wgpu::RenderPassColorAttachmentDescriptor {
-            attachment: frame_view,
+            attachment: color_view,
             resolve_target: None,
-            ops: wgpu::Operations {
-                load: wgpu::LoadOp::Clear(clear_color),
-                store: true,
-            },
+            ops: wgpu::Operations { load, store: true },
         }];
         let mut depth_stencil_attachment = wgpu::RenderPassDepthStencilAttachmentDescriptor {
             attachment: window.depth.as_ref().unwrap(),
@@ -160,18 +549,19 @@ impl<C: CustomPipe> DrawPipe<C> {
                 store: true,
             }),
         };
+        let mut text_depth_load = wgpu::LoadOp::Clear(kas_theme::START_PASS.depth());
 
         // We use a separate render pass for each clipped region.
         for (pass, rect) in window.clip_regions.iter().enumerate() {
-            let ss = self
-                .shaded_square
-                .render_buf(&mut window.shaded_square, device, pass);
-            let sr = self
-                .shaded_round
-                .render_buf(&mut window.shaded_round, device, pass);
-            let fr = self
-                .flat_round
-                .render_buf(&mut window.flat_round, device, pass);
+            if window.cache_passes.iter().any(|(p, _, _)| *p == pass) {
+                continue;
+            }
+
+            let ss = self.shaded_square.render_buf(&window.shaded_square, pass);
+            let sr = self.shaded_round.render_buf(&window.shaded_round, pass);
+            let fr = self.flat_round.render_buf(&window.flat_round, pass);
+            let targets = blit_targets(&window.caches, &window.layers, &window.atlases);
+            let bl = self.blit.render_buf(&window.blit, pass, targets);
 
             {
                 let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -188,10 +578,27 @@ impl<C: CustomPipe> DrawPipe<C> {
                 ss.as_ref().map(|buf| buf.render(&mut rpass));
                 sr.as_ref().map(|buf| buf.render(&mut rpass));
                 fr.as_ref().map(|buf| buf.render(&mut rpass));
+                bl.as_ref().map(|buf| buf.render(&mut rpass));
                 self.custom
                     .render_pass(&mut window.custom, device, pass, &mut rpass);
             }
 
+            if let Some(text_depth) = window.text_depth.as_ref() {
+                let mut rpass = begin_depth_pass(&mut encoder, text_depth, text_depth_load);
+                rpass.set_scissor_rect(
+                    rect.pos.0 as u32,
+                    rect.pos.1 as u32,
+                    rect.size.0,
+                    rect.size.1,
+                );
+
+                ss.as_ref().map(|buf| buf.render_depth(&mut rpass));
+                sr.as_ref().map(|buf| buf.render_depth(&mut rpass));
+                fr.as_ref().map(|buf| buf.render_depth(&mut rpass));
+                bl.as_ref().map(|buf| buf.render_depth(&mut rpass));
+                text_depth_load = wgpu::LoadOp::Load;
+            }
+
             color_attachments[0].ops.load = wgpu::LoadOp::Load;
             depth_stencil_attachment.depth_ops = Some(wgpu::Operations {
                 load: wgpu::LoadOp::Load,
@@ -204,25 +611,46 @@ impl<C: CustomPipe> DrawPipe<C> {
         }
 
         // Fonts and custom pipes use their own render pass(es).
-        let size = window.clip_regions[0].size;
-
         self.custom.render_final(
             &mut window.custom,
             device,
             &mut encoder,
-            frame_view,
+            color_view,
             depth_stencil_attachment.clone(),
             size,
         );
 
-        window
-            .glyph_brush
+        if window.msaa.is_some() {
+            // An empty pass, resolving the multi-sampled target
+            color_attachments[0].resolve_target = Some(frame_view);
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &color_attachments,
+                depth_stencil_attachment: None,
+            });
+        }
+
+        let mut text_depth_attachment = depth_stencil_attachment;
+        if let Some(text_depth) = window.text_depth.as_ref() {
+            text_depth_attachment.attachment = text_depth;
+            text_depth_attachment.stencil_ops = None;
+        }
+
+        // Text of cached regions was drawn by render_caches
+        let cache_passes = &window.cache_passes;
+        let text = window
+            .text
+            .drain(..)
+            .filter(|t| !cache_passes.iter().any(|(p, _, _)| *p == t.0));
+        for (_, glyphs, extra, bounds) in text {
+            self.glyph_brush.queue_pre_positioned(glyphs, extra, bounds);
+        }
+        self.glyph_brush
             .draw_queued(
                 device,
                 &mut self.staging_belt,
                 &mut encoder,
                 frame_view,
-                depth_stencil_attachment,
+                text_depth_attachment,
                 size.0,
                 size.1,
             )
@@ -230,6 +658,24 @@ impl<C: CustomPipe> DrawPipe<C> {
 
         // Keep only first clip region (which is the entire window)
         window.clip_regions.truncate(1);
+        // Keep only layers used this frame
+        let layers = window.cache_passes.iter();
+        let layers = layers.filter(|p| matches!(p.1, blit::Source::Layer(_)));
+        window.layers.truncate(layers.count());
+        window.cache_passes.clear();
+        // Discard caches neither re-used nor redrawn this frame
+        window
+            .caches
+            .retain(|_, cache| std::mem::replace(&mut cache.used, false));
+        // Free icons not drawn this frame
+        let atlases = &mut window.atlases;
+        window.icons.retain(|_, icon| {
+            let used = std::mem::replace(&mut icon.used, false);
+            if let (false, Some(id)) = (used, icon.id) {
+                atlases.free(id);
+            }
+            used
+        });
 
         self.staging_belt.finish();
         queue.submit(std::iter::once(encoder.finish()));
@@ -244,10 +690,63 @@ impl<C: CustomPipe> DrawPipe<C> {
     }
 }
 
+/// Map window coordinates to a target whose origin is at `offset`
+///
+/// Here `size` is the size of the window, not the target.
+fn set_viewport(rpass: &mut wgpu::RenderPass, offset: Coord, size: Size) {
+    let (x, y) = (-offset.0 as f32, -offset.1 as f32);
+    rpass.set_viewport(x, y, size.0 as f32, size.1 as f32, 0.0, 1.0);
+}
+
+/// Text transform, mapping window coordinates to a target of `size` whose
+/// origin is at `offset`
+///
+/// With zero `offset` this is [`wgpu_glyph::orthographic_projection`].
+fn text_transform(offset: Coord, size: Size) -> [f32; 16] {
+    let (w, h) = (size.0 as f32, size.1 as f32);
+    let (x, y) = (offset.0 as f32, offset.1 as f32);
+    #[rustfmt::skip]
+    let transform = [
+        2.0 / w, 0.0, 0.0, 0.0,
+        0.0, -2.0 / h, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        -1.0 - 2.0 * x / w, 1.0 + 2.0 * y / h, 0.0, 1.0,
+    ];
+    transform
+}
+
+/// Begin a depth-only render pass (see [`super::new_depth_pipeline`])
+fn begin_depth_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    depth: &'a TextureView,
+    load: wgpu::LoadOp<f32>,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        color_attachments: &[],
+        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
+            attachment: depth,
+            depth_ops: Some(wgpu::Operations { load, store: true }),
+            stencil_ops: None,
+        }),
+    })
+}
+
 impl<C: CustomPipe> DrawShared for DrawPipe<C> {
     type Draw = DrawWindow<C::Window>;
 }
 
+impl<CW: CustomWindow> DrawWindow<CW> {
+    /// Invalidate the content of all cached regions
+    ///
+    /// This must be called when the theme changes appearance, since cached
+    /// content is otherwise re-used regardless of the theme.
+    pub(crate) fn invalidate_caches(&mut self) {
+        for cache in self.caches.values_mut() {
+            cache.stale = true;
+        }
+    }
+}
+
 impl<CW: CustomWindow + 'static> Draw for DrawWindow<CW> {
     #[inline]
     fn as_any_mut(&mut self) -> &mut dyn Any {
@@ -256,29 +755,185 @@ impl<CW: CustomWindow + 'static> Draw for DrawWindow<CW> {
 
     fn add_clip_region(&mut self, rect: Rect, depth: f32) -> Pass {
         let pass = self.clip_regions.len();
+        // Clamp to the window, since scissor rects may not exceed the target
+        let rect = rect.intersection(&self.clip_regions[0]);
         self.clip_regions.push(rect);
         Pass::new_pass_with_depth(pass as u32, depth)
     }
 
+    fn add_cached_region(
+        &mut self,
+        key: u64,
+        rect: Rect,
+        parent: Pass,
+        valid: bool,
+    ) -> Option<Pass> {
+        let rect = rect.intersection(&self.clip_regions[0]);
+        if rect.size.0 == 0 || rect.size.1 == 0 {
+            return None;
+        }
+
+        // Targets are sized to the region
+        let size = rect.size;
+        let source = blit::Source::Cache(key);
+        let tex = Quad::from(Rect::new(Coord::ZERO, size));
+        self.blit.blit(parent, source, tex, Quad::from(rect), size);
+
+        let reuse = match self.caches.get_mut(&key) {
+            Some(cache) if valid && !cache.stale && cache.target.size == size => {
+                cache.used = true;
+                true
+            }
+            Some(cache) => {
+                cache.stale = false;
+                false
+            }
+            None => false,
+        };
+        if reuse {
+            return None;
+        }
+
+        let pass = self.add_clip_region(rect, parent.depth());
+        self.cache_passes
+            .push((pass.pass(), source, Layer::default()));
+        Some(pass)
+    }
+
+    fn add_layer(&mut self, rect: Rect, parent: Pass, mut layer: Layer) -> Pass {
+        layer.opacity = layer.opacity.max(0.0).min(1.0);
+        layer.blur = layer.blur.max(0.0);
+        if let Some(shadow) = layer.shadow.as_mut() {
+            shadow.radius = shadow.radius.max(0.0);
+            shadow.opacity = shadow.opacity.max(0.0).min(1.0);
+        }
+
+        let layers = self.cache_passes.iter();
+        let index = layers
+            .filter(|p| matches!(p.1, blit::Source::Layer(_)))
+            .count() as u32;
+        let source = blit::Source::Layer(index);
+
+        // Layer targets are window-sized; the transform applies on blitting.
+        // Effects extend beyond the layer, hence blitted regions are expanded.
+        let size = self.clip_regions[0].size;
+        let quad = Quad::from(rect);
+        if let Some(shadow) = layer.shadow {
+            let quad = quad.shrink(-shadow.radius);
+            let dest = layer.transform(quad);
+            let dest = Quad::with_coords(dest.a + shadow.offset, dest.b + shadow.offset);
+            let shadow = blit::Source::Shadow(index);
+            self.blit.blit(parent, shadow, quad, dest, size);
+        }
+        let quad = quad.shrink(-layer.blur);
+        self.blit
+            .blit(parent, source, quad, layer.transform(quad), size);
+
+        let pass = self.add_clip_region(rect, parent.depth());
+        self.cache_passes.push((pass.pass(), source, layer));
+        pass
+    }
+
     #[inline]
     fn rect(&mut self, pass: Pass, rect: Quad, col: Colour) {
+        let col = self.colour_filter.apply(col);
         self.shaded_square.rect(pass, rect, col);
     }
 
     #[inline]
     fn frame(&mut self, pass: Pass, outer: Quad, inner: Quad, col: Colour) {
+        let col = self.colour_filter.apply(col);
         self.shaded_square.frame(pass, outer, inner, col);
     }
+
+    fn image(&mut self, pass: Pass, rect: Quad, image: &IconImage) {
+        let region = Quad::with_coords(Vec2::ZERO, Vec2::splat(1.0));
+        self.image_region(pass, rect, image, region);
+    }
+
+    fn image_region(&mut self, pass: Pass, rect: Quad, image: &IconImage, region: Quad) {
+        // Scalable images are rasterised at the size of the whole image drawn
+        let size = match image.is_scalable() {
+            true => Size::from((rect.size() / region.size()).round()),
+            false => Size::ZERO,
+        };
+        let atlases = &mut self.atlases;
+        let icon = self.icons.entry((image.id(), size)).or_insert_with(|| {
+            let rgba = image.rgba(size);
+            let id = rgba.and_then(|(size, rgba)| atlases.alloc(size, &rgba).ok());
+            super::Icon { id, used: false }
+        });
+        icon.used = true;
+        if let Some(id) = icon.id {
+            self.draw_image(pass, rect, id, region);
+        }
+    }
+
+    #[inline]
+    fn as_draw_image(&mut self) -> Option<&mut dyn DrawImage> {
+        Some(self)
+    }
+
+    #[inline]
+    fn as_draw_path(&mut self) -> Option<&mut dyn DrawPath> {
+        Some(self)
+    }
+}
+
+/// Look up blit sources from cached regions, layers and atlases
+fn blit_targets<'a>(
+    caches: &'a HashMap<u64, super::Cache>,
+    layers: &'a [super::Cache],
+    atlases: &'a atlas::Atlases,
+) -> impl Fn(blit::Source) -> Option<&'a blit::Target> {
+    move |source| match source {
+        blit::Source::Cache(key) => caches.get(&key).map(|cache| &cache.target),
+        blit::Source::Atlas(page) => atlases.target(page),
+        blit::Source::Layer(index) => layers.get(index as usize).map(|layer| &layer.target),
+        blit::Source::Shadow(index) => layers
+            .get(index as usize)
+            .and_then(|layer| layer.shadow.as_ref()),
+    }
+}
+
+impl<CW: CustomWindow + 'static> DrawImage for DrawWindow<CW> {
+    #[inline]
+    fn image_alloc(&mut self, size: Size, rgba: &[u8]) -> Result<ImageId, ImageError> {
+        self.atlases.alloc(size, rgba)
+    }
+
+    #[inline]
+    fn image_update(&mut self, id: ImageId, rgba: &[u8]) -> Result<(), ImageError> {
+        self.atlases.update(id, rgba)
+    }
+
+    #[inline]
+    fn image_free(&mut self, id: ImageId) {
+        self.atlases.free(id);
+    }
+
+    #[inline]
+    fn image_size(&self, id: ImageId) -> Option<Size> {
+        self.atlases.size(id)
+    }
+
+    fn draw_image(&mut self, pass: Pass, rect: Quad, id: ImageId, region: Quad) {
+        if let Some((source, tex)) = self.atlases.source(id, region) {
+            self.blit.image(pass, source, rect, tex);
+        }
+    }
 }
 
 impl<CW: CustomWindow + 'static> DrawRounded for DrawWindow<CW> {
     #[inline]
     fn rounded_line(&mut self, pass: Pass, p1: Vec2, p2: Vec2, radius: f32, col: Colour) {
+        let col = self.colour_filter.apply(col);
         self.flat_round.line(pass, p1, p2, radius, col);
     }
 
     #[inline]
     fn circle(&mut self, pass: Pass, rect: Quad, inner_radius: f32, col: Colour) {
+        let col = self.colour_filter.apply(col);
         self.flat_round.circle(pass, rect, inner_radius, col);
     }
 
@@ -291,20 +946,56 @@ impl<CW: CustomWindow + 'static> DrawRounded for DrawWindow<CW> {
         inner_radius: f32,
         col: Colour,
     ) {
+        let col = self.colour_filter.apply(col);
         self.flat_round
             .rounded_frame(pass, outer, inner, inner_radius, col);
     }
+
+    fn rounded_rect(
+        &mut self,
+        pass: Pass,
+        rect: Quad,
+        radii: CornerRadii,
+        col: Colour,
+        border: Option<Border>,
+    ) {
+        if !rect.a.lt(rect.b) {
+            return;
+        }
+        let mut radii = [
+            radii.top_left,
+            radii.top_right,
+            radii.bottom_right,
+            radii.bottom_left,
+        ];
+        let mut rect = rect;
+        if let Some(border) = border {
+            let w = border.width.min(0.5 * rect.size().min_comp()).max(0.0);
+            let border_col = self.colour_filter.apply(border.col);
+            self.flat_round
+                .rounded_outline(pass, rect, radii, w, border_col);
+            // The fill is inset by the border, with concentric corners
+            rect = rect.shrink(w);
+            for r in &mut radii {
+                *r = (*r - w).max(0.0);
+            }
+        }
+        let col = self.colour_filter.apply(col);
+        self.flat_round.rounded_rect(pass, rect, radii, col);
+    }
 }
 
 impl<CW: CustomWindow + 'static> DrawShaded for DrawWindow<CW> {
     #[inline]
     fn shaded_square(&mut self, pass: Pass, rect: Quad, norm: (f32, f32), col: Colour) {
+        let col = self.colour_filter.apply(col);
         self.shaded_square
             .shaded_rect(pass, rect, Vec2::from(norm), col);
     }
 
     #[inline]
     fn shaded_circle(&mut self, pass: Pass, rect: Quad, norm: (f32, f32), col: Colour) {
+        let col = self.colour_filter.apply(col);
         self.shaded_round.circle(pass, rect, Vec2::from(norm), col);
     }
 
@@ -317,6 +1008,7 @@ impl<CW: CustomWindow + 'static> DrawShaded for DrawWindow<CW> {
         norm: (f32, f32),
         col: Colour,
     ) {
+        let col = self.colour_filter.apply(col);
         self.shaded_square
             .shaded_frame(pass, outer, inner, Vec2::from(norm), col);
     }
@@ -330,6 +1022,7 @@ impl<CW: CustomWindow + 'static> DrawShaded for DrawWindow<CW> {
         norm: (f32, f32),
         col: Colour,
     ) {
+        let col = self.colour_filter.apply(col);
         self.shaded_round
             .shaded_frame(pass, outer, inner, Vec2::from(norm), col);
     }
@@ -5,18 +5,33 @@
 
 //! Text drawing API for `kas_wgpu`
 
-use wgpu_glyph::{ab_glyph, Extra, FontId, SectionGlyph};
+use wgpu_glyph::ab_glyph::{self, Font};
+use wgpu_glyph::{Extra, FontId, SectionGlyph};
 
 use super::{CustomWindow, DrawWindow};
 use kas::draw::{Colour, DrawText, Pass, TextEffect};
 use kas::geom::Vec2;
-use kas::text::PreparedText;
+use kas::text::{FallbackFont, PreparedText};
 
 fn to_point(Vec2(x, y): Vec2) -> ab_glyph::Point {
     ab_glyph::Point { x, y }
 }
 
 impl<CW: CustomWindow + 'static> DrawText for DrawWindow<CW> {
+    fn set_fallback_fonts(&mut self, fonts: &[FallbackFont]) {
+        // This is called each frame; only look up fonts on change
+        let current = self.fallback_fonts.iter().map(|f| f.1);
+        if current.eq(fonts.iter().cloned()) {
+            return;
+        }
+        // The glyph brush has fallback fonts after those of kas-text
+        let base = kas::text::fonts().ab_glyph_fonts_vec().len();
+        self.fallback_fonts = fonts
+            .iter()
+            .map(|font| (FontId(base + font.id()), *font))
+            .collect();
+    }
+
     fn text_with_effects(
         &mut self,
         pass: Pass,
@@ -31,24 +46,41 @@ impl<CW: CustomWindow + 'static> DrawText for DrawWindow<CW> {
         let mut section = 0;
         let mut next = 0;
         let mut next_start = effects.get(next).map(|e| e.start).unwrap_or(u32::MAX);
+        let source = text.text();
+        let fallback = &self.fallback_fonts;
         let glyphs = text.positioned_glyphs(|_, font_id, scale, glyph| {
             while glyph.index >= next_start {
                 section = next;
                 next += 1;
                 next_start = effects.get(next).map(|e| e.start).unwrap_or(u32::MAX);
             }
+            let (mut font_id, mut id) = (FontId(font_id.get()), glyph.id);
+            if id.0 == 0 {
+                // Use the first fallback font supporting this char, if any
+                if let Some(c) = source[glyph.index as usize..].chars().next() {
+                    for (fallback_id, font) in fallback {
+                        let fallback_glyph = font.font().glyph_id(c);
+                        if fallback_glyph.0 != 0 {
+                            font_id = *fallback_id;
+                            id = fallback_glyph;
+                            break;
+                        }
+                    }
+                }
+            }
             SectionGlyph {
                 section_index: section,
                 byte_index: 0, // not used
                 glyph: ab_glyph::Glyph {
-                    id: glyph.id,
+                    id,
                     scale,
                     position: offset + glyph.position.into(),
                 },
-                font_id: FontId(font_id.get()),
+                font_id,
             }
         });
 
+        let filter = self.colour_filter;
         let mut col = Colour::grey(0.0);
         let mut col2 = col;
         let mut underline = false;
@@ -67,16 +99,21 @@ impl<CW: CustomWindow + 'static> DrawText for DrawWindow<CW> {
                     col = if u { Colour::new(0.9, 0.2, 0.5) } else { col2 };
                 }
                 Extra {
-                    color: col.into(),
+                    color: filter.apply(col).into(),
                     z: pass.depth(),
                 }
             })
             .collect();
 
-        let min = pos;
+        // Glyphs are clipped to bounds, which we restrict to the clip region
+        let clip = self.clip_regions[pass.pass()];
+        let clip_min = to_point(clip.pos.into());
+        let clip_max = to_point(clip.pos_end().into());
         let max = pos + text.env().bounds.into();
+        let min = ab_glyph::point(pos.x.max(clip_min.x), pos.y.max(clip_min.y));
+        let max = ab_glyph::point(max.x.min(clip_max.x), max.y.min(clip_max.y));
         let bounds = ab_glyph::Rect { min, max };
 
-        self.glyph_brush.queue_pre_positioned(glyphs, extra, bounds);
+        self.text.push((pass.pass(), glyphs, extra, bounds));
     }
 }
@@ -6,6 +6,7 @@
 //! Rounded flat pipeline
 
 use std::mem::size_of;
+use std::ops::Range;
 use wgpu::util::DeviceExt;
 
 use crate::draw::{Rgb, ShaderManager};
@@ -33,6 +34,7 @@ impl Vertex {
 pub struct Pipeline {
     bind_group_layout: wgpu::BindGroupLayout,
     render_pipeline: wgpu::RenderPipeline,
+    depth_pipeline: Option<wgpu::RenderPipeline>,
 }
 
 /// Per-window state
@@ -40,6 +42,8 @@ pub struct Window {
     bind_group: wgpu::BindGroup,
     scale_buf: wgpu::Buffer,
     passes: Vec<Vec<Vertex>>,
+    buffer: Option<wgpu::Buffer>,
+    ranges: Vec<Range<u32>>,
 }
 
 /// Buffer used during render pass
@@ -47,31 +51,36 @@ pub struct Window {
 /// This buffer must not be dropped before the render pass.
 pub struct RenderBuffer<'a> {
     pipe: &'a wgpu::RenderPipeline,
-    vertices: &'a mut Vec<Vertex>,
+    depth_pipe: Option<&'a wgpu::RenderPipeline>,
     bind_group: &'a wgpu::BindGroup,
-    buffer: wgpu::Buffer,
+    buffer: &'a wgpu::Buffer,
+    range: Range<u32>,
 }
 
 impl<'a> RenderBuffer<'a> {
     /// Do the render
     pub fn render(&'a self, rpass: &mut wgpu::RenderPass<'a>) {
-        let count = self.vertices.len() as u32;
-        rpass.set_pipeline(self.pipe);
-        rpass.set_bind_group(0, self.bind_group, &[]);
-        rpass.set_vertex_buffer(0, self.buffer.slice(..));
-        rpass.draw(0..count, 0..1);
+        self.draw(rpass, self.pipe);
+    }
+
+    /// Render depth only, if multi-sampling (see [`super::new_depth_pipeline`])
+    pub fn render_depth(&'a self, rpass: &mut wgpu::RenderPass<'a>) {
+        if let Some(pipe) = self.depth_pipe {
+            self.draw(rpass, pipe);
+        }
     }
-}
 
-impl<'a> Drop for RenderBuffer<'a> {
-    fn drop(&mut self) {
-        self.vertices.clear();
+    fn draw(&'a self, rpass: &mut wgpu::RenderPass<'a>, pipe: &'a wgpu::RenderPipeline) {
+        rpass.set_pipeline(pipe);
+        rpass.set_bind_group(0, self.bind_group, &[]);
+        rpass.set_vertex_buffer(0, self.buffer.slice(..));
+        rpass.draw(self.range.clone(), 0..1);
     }
 }
 
 impl Pipeline {
     /// Construct
-    pub fn new(device: &wgpu::Device, shaders: &ShaderManager) -> Self {
+    pub fn new(device: &wgpu::Device, shaders: &ShaderManager, sample_count: u32) -> Self {
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("FR bind_group_layout"),
             entries: &[wgpu::BindGroupLayoutEntry {
@@ -91,7 +100,7 @@ impl Pipeline {
             push_constant_ranges: &[],
         });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let desc = wgpu::RenderPipelineDescriptor {
             label: Some("FR render_pipeline"),
             layout: Some(&pipeline_layout),
             vertex_stage: wgpu::ProgrammableStageDescriptor {
@@ -118,9 +127,10 @@ impl Pipeline {
                     dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
                     operation: wgpu::BlendOperation::Add,
                 },
+                // Targets hold premultiplied colour, thus must track coverage
                 alpha_blend: wgpu::BlendDescriptor {
-                    src_factor: wgpu::BlendFactor::Zero,
-                    dst_factor: wgpu::BlendFactor::One,
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
                     operation: wgpu::BlendOperation::Add,
                 },
                 write_mask: wgpu::ColorWrite::ALL,
@@ -140,14 +150,17 @@ impl Pipeline {
                     ],
                 }],
             },
-            sample_count: 1,
+            sample_count,
             sample_mask: !0,
             alpha_to_coverage_enabled: false,
-        });
+        };
+        let render_pipeline = device.create_render_pipeline(&desc);
+        let depth_pipeline = super::new_depth_pipeline(device, desc);
 
         Pipeline {
             bind_group_layout,
             render_pipeline,
+            depth_pipeline,
         }
     }
 
@@ -174,37 +187,52 @@ impl Pipeline {
             bind_group,
             scale_buf,
             passes: vec![],
+            buffer: None,
+            ranges: vec![],
         }
     }
 
     /// Construct a render buffer
-    pub fn render_buf<'a>(
-        &'a self,
-        window: &'a mut Window,
-        device: &wgpu::Device,
-        pass: usize,
-    ) -> Option<RenderBuffer<'a>> {
-        if pass >= window.passes.len() || window.passes[pass].len() == 0 {
-            return None;
-        }
-
-        let vertices = &mut window.passes[pass];
-        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("FR render_buf"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsage::VERTEX,
-        });
+    ///
+    /// Vertices must first be uploaded via [`Window::prepare`].
+    pub fn render_buf<'a>(&'a self, window: &'a Window, pass: usize) -> Option<RenderBuffer<'a>> {
+        let range = window.ranges.get(pass).filter(|r| r.start < r.end)?;
 
         Some(RenderBuffer {
             pipe: &self.render_pipeline,
-            vertices,
+            depth_pipe: self.depth_pipeline.as_ref(),
             bind_group: &window.bind_group,
-            buffer,
+            buffer: window.buffer.as_ref()?,
+            range: range.clone(),
         })
     }
 }
 
 impl Window {
+    /// Upload vertices of all passes to a single buffer
+    ///
+    /// This is called once per frame, before rendering any pass.
+    pub fn prepare(&mut self, device: &wgpu::Device) {
+        let mut vertices = vec![];
+        self.ranges.clear();
+        for pass in &mut self.passes {
+            let start = vertices.len() as u32;
+            vertices.append(pass);
+            self.ranges.push(start..(vertices.len() as u32));
+        }
+
+        self.buffer = match vertices.is_empty() {
+            true => None,
+            false => Some(
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("FR vertices"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsage::VERTEX,
+                }),
+            ),
+        };
+    }
+
     pub fn resize(
         &mut self,
         device: &wgpu::Device,
@@ -421,6 +449,191 @@ impl Window {
         ]);
     }
 
+    /// Draw a filled rect with rounded corners
+    ///
+    /// The `radii` are clockwise from the top-left corner.
+    pub fn rounded_rect(&mut self, pass: Pass, rect: Quad, radii: [f32; 4], col: Colour) {
+        let (aa, bb) = (rect.a, rect.b);
+        if !aa.lt(bb) {
+            // zero / negative size: nothing to draw
+            return;
+        }
+
+        let r = fit_radii(bb - aa, radii);
+        let col = col.into();
+        let depth = pass.depth();
+        for (i, (corner, dir)) in corners(rect).iter().enumerate() {
+            if r[i] > 0.0 {
+                let centre = *corner + *dir * r[i];
+                self.corner(pass.pass(), depth, *corner, centre, 0.0, col);
+            }
+        }
+
+        // Fill the remainder with horizontal slabs between corners
+        let mut ys = [
+            aa.1,
+            aa.1 + r[0],
+            aa.1 + r[1],
+            bb.1 - r[2],
+            bb.1 - r[3],
+            bb.1,
+        ];
+        ys.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for y in ys.windows(2) {
+            let mid = 0.5 * (y[0] + y[1]);
+            let (mut x0, mut x1) = (aa.0, bb.0);
+            if mid < aa.1 + r[0] {
+                x0 += r[0];
+            } else if mid > bb.1 - r[3] {
+                x0 += r[3];
+            }
+            if mid < aa.1 + r[1] {
+                x1 -= r[1];
+            } else if mid > bb.1 - r[2] {
+                x1 -= r[2];
+            }
+            self.rect(pass.pass(), depth, Vec2(x0, y[0]), Vec2(x1, y[1]), col);
+        }
+    }
+
+    /// Draw the outline of a rect with rounded corners
+    ///
+    /// The outline has the given `width`, inside the edge of `rect`. The
+    /// `radii` are clockwise from the top-left corner.
+    pub fn rounded_outline(
+        &mut self,
+        pass: Pass,
+        rect: Quad,
+        radii: [f32; 4],
+        width: f32,
+        col: Colour,
+    ) {
+        let (aa, bb) = (rect.a, rect.b);
+        if !aa.lt(bb) {
+            // zero / negative size: nothing to draw
+            return;
+        }
+        let w = width.max(0.0);
+        if 2.0 * w >= (bb - aa).min_comp() {
+            return self.rounded_rect(pass, rect, radii, col);
+        }
+
+        let r = fit_radii(bb - aa, radii);
+        let col = col.into();
+        let depth = pass.depth();
+
+        // Corners, extended to the width where the radius is smaller
+        let mut ext = [0.0; 4];
+        for (i, (corner, dir)) in corners(rect).iter().enumerate() {
+            let corner = *corner;
+            ext[i] = r[i].max(w);
+            if r[i] > 0.0 {
+                let centre = corner + *dir * r[i];
+                let inner = ((r[i] - w) / r[i]).max(0.0);
+                self.corner(pass.pass(), depth, corner, centre, inner * inner, col);
+            }
+            if w > r[i] {
+                // L-shaped remainder of the w × w corner square
+                let (r, e) = (*dir * r[i], *dir * w);
+                self.rect(pass.pass(), depth, corner + Vec2(r.0, 0.0), corner + e, col);
+                let b = corner + Vec2(r.0, e.1);
+                self.rect(pass.pass(), depth, corner + Vec2(0.0, r.1), b, col);
+            }
+        }
+
+        // Bars between corners
+        let ab = Vec2(aa.0, bb.1);
+        let ba = Vec2(bb.0, aa.1);
+        let pass = pass.pass();
+        self.rect(
+            pass,
+            depth,
+            aa + Vec2(ext[0], 0.0),
+            ba + Vec2(-ext[1], w),
+            col,
+        );
+        self.rect(
+            pass,
+            depth,
+            ba + Vec2(-w, ext[1]),
+            bb - Vec2(0.0, ext[2]),
+            col,
+        );
+        self.rect(
+            pass,
+            depth,
+            ab + Vec2(ext[3], -w),
+            bb - Vec2(ext[2], 0.0),
+            col,
+        );
+        self.rect(
+            pass,
+            depth,
+            aa + Vec2(0.0, ext[0]),
+            ab + Vec2(w, -ext[3]),
+            col,
+        );
+    }
+
+    /// Add a quarter-oval centred on `centre` and extending to `corner`
+    fn corner(
+        &mut self,
+        pass: usize,
+        depth: f32,
+        corner: Vec2,
+        centre: Vec2,
+        inner: f32,
+        col: Rgb,
+    ) {
+        let n = (corner - centre).sign();
+        let p = Vec2::splat(OFFSET) / (corner - centre).abs();
+
+        let cc = Vertex::new2(centre, depth, col, inner, Vec2::splat(0.0), p);
+        let ca = Vertex::new2(
+            Vec2(centre.0, corner.1),
+            depth,
+            col,
+            inner,
+            Vec2(0.0, n.1),
+            p,
+        );
+        let ac = Vertex::new2(
+            Vec2(corner.0, centre.1),
+            depth,
+            col,
+            inner,
+            Vec2(n.0, 0.0),
+            p,
+        );
+        let aa = Vertex::new2(corner, depth, col, inner, n, p);
+
+        #[rustfmt::skip]
+        self.add_vertices(pass, &[
+            cc, ca, aa,
+            cc, aa, ac,
+        ]);
+    }
+
+    /// Add a solid rect between (unordered) corners `a` and `b`
+    fn rect(&mut self, pass: usize, depth: f32, a: Vec2, b: Vec2, col: Rgb) {
+        let (aa, bb) = (a.min(b), a.max(b));
+        if !aa.lt(bb) {
+            return;
+        }
+
+        let (n, p) = (Vec2::splat(0.0), Vec2::splat(0.0));
+        let ab = Vertex::new2(Vec2(aa.0, bb.1), depth, col, 0.0, n, p);
+        let ba = Vertex::new2(Vec2(bb.0, aa.1), depth, col, 0.0, n, p);
+        let aa = Vertex::new2(aa, depth, col, 0.0, n, p);
+        let bb = Vertex::new2(bb, depth, col, 0.0, n, p);
+
+        #[rustfmt::skip]
+        self.add_vertices(pass, &[
+            aa, ba, ab,
+            ab, ba, bb,
+        ]);
+    }
+
     fn add_vertices(&mut self, pass: usize, slice: &[Vertex]) {
         if self.passes.len() <= pass {
             // We only need one more, but no harm in adding extra
@@ -430,3 +643,36 @@ impl Window {
         self.passes[pass].extend_from_slice(slice);
     }
 }
+
+/// Corners of `rect` (clockwise from the top-left) with inward directions
+fn corners(rect: Quad) -> [(Vec2, Vec2); 4] {
+    [
+        (rect.a, Vec2(1.0, 1.0)),
+        (rect.ba(), Vec2(-1.0, 1.0)),
+        (rect.b, Vec2(-1.0, -1.0)),
+        (rect.ab(), Vec2(1.0, -1.0)),
+    ]
+}
+
+/// Clamp radii to be non-negative and reduce proportionally to fit `size`
+fn fit_radii(size: Vec2, radii: [f32; 4]) -> [f32; 4] {
+    let mut r = [0.0; 4];
+    for (r, radius) in r.iter_mut().zip(radii.iter()) {
+        *r = radius.max(0.0);
+    }
+    let mut scale: f32 = 1.0;
+    for &(sum, side) in &[
+        (r[0] + r[1], size.0),
+        (r[3] + r[2], size.0),
+        (r[0] + r[3], size.1),
+        (r[1] + r[2], size.1),
+    ] {
+        if sum > side {
+            scale = scale.min(side / sum);
+        }
+    }
+    for r in &mut r {
+        *r *= scale;
+    }
+    r
+}
@@ -7,7 +7,11 @@
 //!
 //! Extensions to the API of [`kas::draw`], plus some utility types.
 
+mod atlas;
+mod blit;
+mod colour_filter;
 mod custom;
+mod draw_path;
 mod draw_pipe;
 mod draw_text;
 mod flat_round;
@@ -15,12 +19,16 @@ mod shaded_round;
 mod shaded_square;
 mod shaders;
 
-use kas::geom::Rect;
+use kas::draw::ImageId;
+use kas::geom::{Rect, Size};
+use std::collections::HashMap;
 use wgpu::{CompareFunction, DepthStencilStateDescriptor, TextureFormat};
-use wgpu_glyph::ab_glyph::FontRef;
+use wgpu_glyph::ab_glyph::{self, FontRef};
+use wgpu_glyph::{Extra, SectionGlyph};
 
 pub(crate) use shaders::ShaderManager;
 
+pub use colour_filter::ColourFilter;
 pub use custom::{CustomPipe, CustomPipeBuilder, CustomWindow, DrawCustom};
 
 const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
@@ -42,6 +50,27 @@ const fn new_depth_desc(depth_compare: CompareFunction) -> DepthStencilStateDesc
 const DEPTH_DESC: DepthStencilStateDescriptor = new_depth_desc(CompareFunction::Always);
 const GLPYH_DEPTH_DESC: DepthStencilStateDescriptor = new_depth_desc(CompareFunction::GreaterEqual);
 
+/// Construct a single-sampled, depth-only variant of a pipeline
+///
+/// The glyph brush only supports single-sampled targets, thus when
+/// multi-sampling, text is drawn after resolving the frame. This variant is
+/// used to write a single-sampled depth buffer, against which text is tested
+/// (such that later passes, e.g. pop-ups, occlude text).
+///
+/// Returns `None` when not multi-sampling.
+fn new_depth_pipeline(
+    device: &wgpu::Device,
+    mut desc: wgpu::RenderPipelineDescriptor,
+) -> Option<wgpu::RenderPipeline> {
+    if desc.sample_count == 1 {
+        return None;
+    }
+    desc.fragment_stage = None;
+    desc.color_states = &[];
+    desc.sample_count = 1;
+    Some(device.create_render_pipeline(&desc))
+}
+
 /// 3-part colour data
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
@@ -68,7 +97,12 @@ pub struct DrawPipe<C> {
     shaded_square: shaded_square::Pipeline,
     shaded_round: shaded_round::Pipeline,
     flat_round: flat_round::Pipeline,
+    blit: blit::Pipeline,
     custom: C,
+    glyph_brush: GlyphBrush,
+    glyph_fonts: usize,
+    glyph_fallbacks: usize,
+    sample_count: u32,
 }
 
 type GlyphBrush = wgpu_glyph::GlyphBrush<DepthStencilStateDescriptor, &'static FontRef<'static>>;
@@ -76,10 +110,56 @@ type GlyphBrush = wgpu_glyph::GlyphBrush<DepthStencilStateDescriptor, &'static F
 /// Per-window pipeline data
 pub struct DrawWindow<CW: CustomWindow> {
     depth: Option<wgpu::TextureView>,
+    /// Multi-sampled colour target (if multi-sampling)
+    msaa: Option<wgpu::TextureView>,
+    /// Single-sampled depth buffer for text (if multi-sampling)
+    text_depth: Option<wgpu::TextureView>,
     clip_regions: Vec<Rect>,
     shaded_square: shaded_square::Window,
     shaded_round: shaded_round::Window,
     flat_round: flat_round::Window,
+    blit: blit::Window,
     custom: CW,
-    glyph_brush: GlyphBrush, // TODO: should be in DrawPipe
+    caches: HashMap<u64, Cache>,
+    layers: Vec<Cache>,
+    /// Intermediate target for blurring layers
+    blur_target: Option<blit::Target>,
+    /// Icon images uploaded to the atlases, by image identifier and (for
+    /// scalable images) rasterised size
+    icons: HashMap<(u64, Size), Icon>,
+    atlases: atlas::Atlases,
+    /// Passes rendered to a target: pass, target and compositing parameters
+    cache_passes: Vec<(usize, blit::Source, kas::draw::Layer)>,
+    text: Vec<QueuedText>,
+    /// Fallback fonts (see [`kas::draw::DrawText::set_fallback_fonts`])
+    fallback_fonts: Vec<(wgpu_glyph::FontId, kas::text::FallbackFont)>,
+    pub(crate) colour_filter: ColourFilter,
 }
+
+/// Render target of a cached region or layer
+///
+/// Targets of cached regions are sized to the region and have their own depth
+/// buffers; layers are window-sized and use the depth buffers of the window.
+struct Cache {
+    target: blit::Target,
+    msaa: Option<wgpu::TextureView>,
+    depth: Option<wgpu::TextureView>,
+    /// Single-sampled depth buffer for text (if multi-sampling)
+    text_depth: Option<wgpu::TextureView>,
+    /// Shadow of a layer (if it has one)
+    shadow: Option<blit::Target>,
+    /// Content must be redrawn (see [`DrawWindow::invalidate_caches`])
+    stale: bool,
+    used: bool,
+}
+
+/// An icon image, allocated in the atlases
+struct Icon {
+    /// The allocated image (`None` if the image could not be allocated)
+    id: Option<ImageId>,
+    /// Whether the icon was drawn this frame (otherwise it is freed)
+    used: bool,
+}
+
+/// Text queued for drawing: pass, glyphs, extra and bounds
+type QueuedText = (usize, Vec<SectionGlyph>, Vec<Extra>, ab_glyph::Rect);
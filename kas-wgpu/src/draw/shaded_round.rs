@@ -7,6 +7,7 @@
 
 use std::f32::consts::FRAC_PI_2;
 use std::mem::size_of;
+use std::ops::Range;
 use wgpu::util::DeviceExt;
 
 use crate::draw::{Rgb, ShaderManager};
@@ -34,6 +35,7 @@ impl Vertex {
 pub struct Pipeline {
     bind_group_layout: wgpu::BindGroupLayout,
     render_pipeline: wgpu::RenderPipeline,
+    depth_pipeline: Option<wgpu::RenderPipeline>,
 }
 
 /// Per-window state
@@ -41,6 +43,8 @@ pub struct Window {
     bind_group: wgpu::BindGroup,
     scale_buf: wgpu::Buffer,
     passes: Vec<Vec<Vertex>>,
+    buffer: Option<wgpu::Buffer>,
+    ranges: Vec<Range<u32>>,
 }
 
 /// Buffer used during render pass
@@ -48,31 +52,36 @@ pub struct Window {
 /// This buffer must not be dropped before the render pass.
 pub struct RenderBuffer<'a> {
     pipe: &'a wgpu::RenderPipeline,
-    vertices: &'a mut Vec<Vertex>,
+    depth_pipe: Option<&'a wgpu::RenderPipeline>,
     bind_group: &'a wgpu::BindGroup,
-    buffer: wgpu::Buffer,
+    buffer: &'a wgpu::Buffer,
+    range: Range<u32>,
 }
 
 impl<'a> RenderBuffer<'a> {
     /// Do the render
     pub fn render(&'a self, rpass: &mut wgpu::RenderPass<'a>) {
-        let count = self.vertices.len() as u32;
-        rpass.set_pipeline(self.pipe);
-        rpass.set_bind_group(0, self.bind_group, &[]);
-        rpass.set_vertex_buffer(0, self.buffer.slice(..));
-        rpass.draw(0..count, 0..1);
+        self.draw(rpass, self.pipe);
     }
-}
 
-impl<'a> Drop for RenderBuffer<'a> {
-    fn drop(&mut self) {
-        self.vertices.clear();
+    /// Render depth only, if multi-sampling (see [`super::new_depth_pipeline`])
+    pub fn render_depth(&'a self, rpass: &mut wgpu::RenderPass<'a>) {
+        if let Some(pipe) = self.depth_pipe {
+            self.draw(rpass, pipe);
+        }
+    }
+
+    fn draw(&'a self, rpass: &mut wgpu::RenderPass<'a>, pipe: &'a wgpu::RenderPipeline) {
+        rpass.set_pipeline(pipe);
+        rpass.set_bind_group(0, self.bind_group, &[]);
+        rpass.set_vertex_buffer(0, self.buffer.slice(..));
+        rpass.draw(self.range.clone(), 0..1);
     }
 }
 
 impl Pipeline {
     /// Construct
-    pub fn new(device: &wgpu::Device, shaders: &ShaderManager) -> Self {
+    pub fn new(device: &wgpu::Device, shaders: &ShaderManager, sample_count: u32) -> Self {
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("SR bind_group_layout"),
             entries: &[
@@ -102,7 +111,7 @@ impl Pipeline {
             push_constant_ranges: &[],
         });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let desc = wgpu::RenderPipelineDescriptor {
             label: Some("SR render_pipeline"),
             layout: Some(&pipeline_layout),
             vertex_stage: wgpu::ProgrammableStageDescriptor {
@@ -129,9 +138,10 @@ impl Pipeline {
                     dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
                     operation: wgpu::BlendOperation::Add,
                 },
+                // Targets hold premultiplied colour, thus must track coverage
                 alpha_blend: wgpu::BlendDescriptor {
-                    src_factor: wgpu::BlendFactor::Zero,
-                    dst_factor: wgpu::BlendFactor::One,
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
                     operation: wgpu::BlendOperation::Add,
                 },
                 write_mask: wgpu::ColorWrite::ALL,
@@ -151,14 +161,17 @@ impl Pipeline {
                     ],
                 }],
             },
-            sample_count: 1,
+            sample_count,
             sample_mask: !0,
             alpha_to_coverage_enabled: false,
-        });
+        };
+        let render_pipeline = device.create_render_pipeline(&desc);
+        let depth_pipeline = super::new_depth_pipeline(device, desc);
 
         Pipeline {
             bind_group_layout,
             render_pipeline,
+            depth_pipeline,
         }
     }
 
@@ -197,37 +210,52 @@ impl Pipeline {
             bind_group,
             scale_buf,
             passes: vec![],
+            buffer: None,
+            ranges: vec![],
         }
     }
 
     /// Construct a render buffer
-    pub fn render_buf<'a>(
-        &'a self,
-        window: &'a mut Window,
-        device: &wgpu::Device,
-        pass: usize,
-    ) -> Option<RenderBuffer<'a>> {
-        if pass >= window.passes.len() || window.passes[pass].len() == 0 {
-            return None;
-        }
-
-        let vertices = &mut window.passes[pass];
-        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("SR render_buf"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsage::VERTEX,
-        });
+    ///
+    /// Vertices must first be uploaded via [`Window::prepare`].
+    pub fn render_buf<'a>(&'a self, window: &'a Window, pass: usize) -> Option<RenderBuffer<'a>> {
+        let range = window.ranges.get(pass).filter(|r| r.start < r.end)?;
 
         Some(RenderBuffer {
             pipe: &self.render_pipeline,
-            vertices,
+            depth_pipe: self.depth_pipeline.as_ref(),
             bind_group: &window.bind_group,
-            buffer,
+            buffer: window.buffer.as_ref()?,
+            range: range.clone(),
         })
     }
 }
 
 impl Window {
+    /// Upload vertices of all passes to a single buffer
+    ///
+    /// This is called once per frame, before rendering any pass.
+    pub fn prepare(&mut self, device: &wgpu::Device) {
+        let mut vertices = vec![];
+        self.ranges.clear();
+        for pass in &mut self.passes {
+            let start = vertices.len() as u32;
+            vertices.append(pass);
+            self.ranges.push(start..(vertices.len() as u32));
+        }
+
+        self.buffer = match vertices.is_empty() {
+            true => None,
+            false => Some(
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("SR vertices"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsage::VERTEX,
+                }),
+            ),
+        };
+    }
+
     pub fn resize(
         &mut self,
         device: &wgpu::Device,
@@ -7,6 +7,7 @@
 
 use std::f32;
 use std::mem::size_of;
+use std::ops::Range;
 use wgpu::util::DeviceExt;
 
 use crate::draw::{Rgb, ShaderManager};
@@ -19,49 +20,103 @@ struct Vertex(Vec3, Rgb, Vec2);
 unsafe impl bytemuck::Zeroable for Vertex {}
 unsafe impl bytemuck::Pod for Vertex {}
 
+/// A frame: outer and inner corners, colour, normal and depth
+///
+/// Frames (including rects) are drawn as instances, each of 24 vertices (see
+/// `shaded_frame.vert`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct Instance(Vec2, Vec2, Vec2, Vec2, Rgb, Vec2, f32);
+unsafe impl bytemuck::Zeroable for Instance {}
+unsafe impl bytemuck::Pod for Instance {}
+
+/// A run of consecutive primitives of one kind
+///
+/// Runs preserve draw order within a pass.
+#[derive(Clone, Debug)]
+enum Run {
+    /// A range of vertices (triangles)
+    Triangles(Range<u32>),
+    /// A range of frame instances
+    Frames(Range<u32>),
+}
+
+/// Primitives of a pass, not yet uploaded
+#[derive(Clone, Debug, Default)]
+struct PassData {
+    vertices: Vec<Vertex>,
+    instances: Vec<Instance>,
+    runs: Vec<Run>,
+}
+
 /// A pipeline for rendering with flat and square-corner shading
 pub struct Pipeline {
     bind_group_layout: wgpu::BindGroupLayout,
     render_pipeline: wgpu::RenderPipeline,
+    depth_pipeline: Option<wgpu::RenderPipeline>,
+    frame_pipeline: wgpu::RenderPipeline,
+    frame_depth_pipeline: Option<wgpu::RenderPipeline>,
 }
 
 /// Per-window state
 pub struct Window {
     bind_group: wgpu::BindGroup,
     scale_buf: wgpu::Buffer,
-    passes: Vec<Vec<Vertex>>,
+    passes: Vec<PassData>,
+    vertex_buffer: Option<wgpu::Buffer>,
+    instance_buffer: Option<wgpu::Buffer>,
+    runs: Vec<Vec<Run>>,
 }
 
 /// Buffer used during render pass
 ///
 /// This buffer must not be dropped before the render pass.
 pub struct RenderBuffer<'a> {
-    pipe: &'a wgpu::RenderPipeline,
-    vertices: &'a mut Vec<Vertex>,
+    pipes: (&'a wgpu::RenderPipeline, &'a wgpu::RenderPipeline),
+    depth_pipes: Option<(&'a wgpu::RenderPipeline, &'a wgpu::RenderPipeline)>,
     bind_group: &'a wgpu::BindGroup,
-    buffer: wgpu::Buffer,
+    vertex_buffer: Option<&'a wgpu::Buffer>,
+    instance_buffer: Option<&'a wgpu::Buffer>,
+    runs: &'a [Run],
 }
 
 impl<'a> RenderBuffer<'a> {
     /// Do the render
     pub fn render(&'a self, rpass: &mut wgpu::RenderPass<'a>) {
-        let count = self.vertices.len() as u32;
-        rpass.set_pipeline(self.pipe);
-        rpass.set_bind_group(0, self.bind_group, &[]);
-        rpass.set_vertex_buffer(0, self.buffer.slice(..));
-        rpass.draw(0..count, 0..1);
+        self.draw(rpass, self.pipes);
     }
-}
 
-impl<'a> Drop for RenderBuffer<'a> {
-    fn drop(&mut self) {
-        self.vertices.clear();
+    /// Render depth only, if multi-sampling (see [`super::new_depth_pipeline`])
+    pub fn render_depth(&'a self, rpass: &mut wgpu::RenderPass<'a>) {
+        if let Some(pipes) = self.depth_pipes {
+            self.draw(rpass, pipes);
+        }
+    }
+
+    /// Draw runs using `pipes` (for triangles and frames respectively)
+    fn draw(
+        &'a self,
+        rpass: &mut wgpu::RenderPass<'a>,
+        pipes: (&'a wgpu::RenderPipeline, &'a wgpu::RenderPipeline),
+    ) {
+        for run in self.runs {
+            let (pipe, buffer, vertices, instances) = match run {
+                Run::Triangles(range) => (pipes.0, self.vertex_buffer, range.clone(), 0..1),
+                Run::Frames(range) => (pipes.1, self.instance_buffer, 0..24, range.clone()),
+            };
+            if let Some(buffer) = buffer {
+                rpass.set_pipeline(pipe);
+                rpass.set_bind_group(0, self.bind_group, &[]);
+                rpass.set_vertex_buffer(0, buffer.slice(..));
+                rpass.draw(vertices, instances);
+            }
+        }
     }
 }
 
 impl Pipeline {
     /// Construct
-    pub fn new(device: &wgpu::Device, shaders: &ShaderManager) -> Self {
+    pub fn new(device: &wgpu::Device, shaders: &ShaderManager, sample_count: u32) -> Self {
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("SS bind_group_layout"),
             entries: &[
@@ -92,7 +147,7 @@ impl Pipeline {
             push_constant_ranges: &[],
         });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let desc = wgpu::RenderPipelineDescriptor {
             label: Some("SS render_pipeline"),
             layout: Some(&pipeline_layout),
             vertex_stage: wgpu::ProgrammableStageDescriptor {
@@ -127,14 +182,40 @@ impl Pipeline {
                     attributes: &wgpu::vertex_attr_array![0 => Float3, 1 => Float3, 2 => Float2],
                 }],
             },
-            sample_count: 1,
+            sample_count,
             sample_mask: !0,
             alpha_to_coverage_enabled: false,
-        });
+        };
+        let frame_desc = wgpu::RenderPipelineDescriptor {
+            label: Some("SS frame_pipeline"),
+            vertex_stage: wgpu::ProgrammableStageDescriptor {
+                module: &shaders.vert_shaded_frame,
+                entry_point: "main",
+            },
+            vertex_state: wgpu::VertexStateDescriptor {
+                index_format: wgpu::IndexFormat::Uint16,
+                vertex_buffers: &[wgpu::VertexBufferDescriptor {
+                    stride: size_of::<Instance>() as wgpu::BufferAddress,
+                    step_mode: wgpu::InputStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float2, 1 => Float2, 2 => Float2, 3 => Float2,
+                        4 => Float3, 5 => Float2, 6 => Float
+                    ],
+                }],
+            },
+            ..desc.clone()
+        };
+        let render_pipeline = device.create_render_pipeline(&desc);
+        let depth_pipeline = super::new_depth_pipeline(device, desc);
+        let frame_pipeline = device.create_render_pipeline(&frame_desc);
+        let frame_depth_pipeline = super::new_depth_pipeline(device, frame_desc);
 
         Pipeline {
             bind_group_layout,
             render_pipeline,
+            depth_pipeline,
+            frame_pipeline,
+            frame_depth_pipeline,
         }
     }
 
@@ -173,37 +254,74 @@ impl Pipeline {
             bind_group,
             scale_buf,
             passes: vec![],
+            vertex_buffer: None,
+            instance_buffer: None,
+            runs: vec![],
         }
     }
 
     /// Construct a render buffer
-    pub fn render_buf<'a>(
-        &'a self,
-        window: &'a mut Window,
-        device: &wgpu::Device,
-        pass: usize,
-    ) -> Option<RenderBuffer<'a>> {
-        if pass >= window.passes.len() || window.passes[pass].len() == 0 {
-            return None;
-        }
-
-        let vertices = &mut window.passes[pass];
-        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("SS render_buf"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsage::VERTEX,
-        });
+    ///
+    /// Primitives must first be uploaded via [`Window::prepare`].
+    pub fn render_buf<'a>(&'a self, window: &'a Window, pass: usize) -> Option<RenderBuffer<'a>> {
+        let runs = window.runs.get(pass).filter(|runs| !runs.is_empty())?;
+        let depth_pipes = match (&self.depth_pipeline, &self.frame_depth_pipeline) {
+            (Some(pipe), Some(frame_pipe)) => Some((pipe, frame_pipe)),
+            _ => None,
+        };
 
         Some(RenderBuffer {
-            pipe: &self.render_pipeline,
-            vertices,
+            pipes: (&self.render_pipeline, &self.frame_pipeline),
+            depth_pipes,
             bind_group: &window.bind_group,
-            buffer,
+            vertex_buffer: window.vertex_buffer.as_ref(),
+            instance_buffer: window.instance_buffer.as_ref(),
+            runs,
         })
     }
 }
 
 impl Window {
+    /// Upload vertices and instances of all passes to a buffer each
+    ///
+    /// This is called once per frame, before rendering any pass.
+    pub fn prepare(&mut self, device: &wgpu::Device) {
+        let mut vertices = vec![];
+        let mut instances = vec![];
+        self.runs.resize_with(self.passes.len(), Vec::new);
+        for (pass, runs) in self.passes.iter_mut().zip(self.runs.iter_mut()) {
+            let (v0, i0) = (vertices.len() as u32, instances.len() as u32);
+            runs.clear();
+            runs.extend(pass.runs.drain(..).map(|run| match run {
+                Run::Triangles(r) => Run::Triangles((v0 + r.start)..(v0 + r.end)),
+                Run::Frames(r) => Run::Frames((i0 + r.start)..(i0 + r.end)),
+            }));
+            vertices.append(&mut pass.vertices);
+            instances.append(&mut pass.instances);
+        }
+
+        self.vertex_buffer = match vertices.is_empty() {
+            true => None,
+            false => Some(
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("SS vertices"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsage::VERTEX,
+                }),
+            ),
+        };
+        self.instance_buffer = match instances.is_empty() {
+            true => None,
+            false => Some(
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("SS instances"),
+                    contents: bytemuck::cast_slice(&instances),
+                    usage: wgpu::BufferUsage::VERTEX,
+                }),
+            ),
+        };
+    }
+
     pub fn resize(
         &mut self,
         device: &wgpu::Device,
@@ -224,28 +342,19 @@ impl Window {
 
     /// Add a rectangle to the buffer
     pub fn rect(&mut self, pass: Pass, rect: Quad, col: Colour) {
-        let aa = rect.a;
-        let bb = rect.b;
-
-        if !aa.lt(bb) {
-            // zero / negative size: nothing to draw
-            return;
-        }
+        self.shaded_rect(pass, rect, Vec2::splat(0.0), col);
+    }
 
+    /// Add a list of triangles to the buffer
+    ///
+    /// Each triple of `indices` references three `vertices`.
+    pub fn triangles(&mut self, pass: Pass, vertices: &[Vec2], indices: &[u32], col: Colour) {
         let depth = pass.depth();
-        let ab = Vec3(aa.0, bb.1, depth);
-        let ba = Vec3(bb.0, aa.1, depth);
-        let aa = Vec3::from2(aa, depth);
-        let bb = Vec3::from2(bb, depth);
-
         let col = col.into();
         let t = Vec2(0.0, 0.0);
-
-        #[rustfmt::skip]
-        self.add_vertices(pass.pass(), &[
-            Vertex(aa, col, t), Vertex(ba, col, t), Vertex(ab, col, t),
-            Vertex(ab, col, t), Vertex(ba, col, t), Vertex(bb, col, t),
-        ]);
+        let vertex = |i: &u32| Vertex(Vec3::from2(vertices[*i as usize], depth), col, t);
+        let vertices: Vec<Vertex> = indices.iter().map(vertex).collect();
+        self.add_vertices(pass.pass(), &vertices);
     }
 
     /// Add a rect to the buffer, defined by two outer corners, `aa` and `bb`.
@@ -263,26 +372,11 @@ impl Window {
             norm = Vec2::splat(0.0);
         }
 
-        let depth = pass.depth();
-        let mid = Vec3::from2((aa + bb) * 0.5, depth);
-        let ab = Vec3(aa.0, bb.1, depth);
-        let ba = Vec3(bb.0, aa.1, depth);
-        let aa = Vec3::from2(aa, depth);
-        let bb = Vec3::from2(bb, depth);
-
+        // A frame whose inner edge is collapsed to the centre
+        let mid = (aa + bb) * 0.5;
         let col = col.into();
-        let tt = (Vec2(0.0, -norm.1), Vec2(0.0, -norm.0));
-        let tl = (Vec2(-norm.1, 0.0), Vec2(-norm.0, 0.0));
-        let tb = (Vec2(0.0, norm.1), Vec2(0.0, norm.0));
-        let tr = (Vec2(norm.1, 0.0), Vec2(norm.0, 0.0));
-
-        #[rustfmt::skip]
-        self.add_vertices(pass.pass(), &[
-            Vertex(ba, col, tt.0), Vertex(mid, col, tt.1), Vertex(aa, col, tt.0),
-            Vertex(aa, col, tl.0), Vertex(mid, col, tl.1), Vertex(ab, col, tl.0),
-            Vertex(ab, col, tb.0), Vertex(mid, col, tb.1), Vertex(bb, col, tb.0),
-            Vertex(bb, col, tr.0), Vertex(mid, col, tr.1), Vertex(ba, col, tr.0),
-        ]);
+        let instance = Instance(aa, bb, mid, mid, col, norm, pass.depth());
+        self.add_frame(pass.pass(), instance);
     }
 
     #[inline]
@@ -325,45 +419,37 @@ impl Window {
             norm = Vec2::splat(0.0);
         }
 
-        let depth = pass.depth();
-        let ab = Vec3(aa.0, bb.1, depth);
-        let ba = Vec3(bb.0, aa.1, depth);
-        let cd = Vec3(cc.0, dd.1, depth);
-        let dc = Vec3(dd.0, cc.1, depth);
-        let aa = Vec3::from2(aa, depth);
-        let bb = Vec3::from2(bb, depth);
-        let cc = Vec3::from2(cc, depth);
-        let dd = Vec3::from2(dd, depth);
-
         let col = col.into();
-        let tt = (Vec2(0.0, -norm.1), Vec2(0.0, -norm.0));
-        let tl = (Vec2(-norm.1, 0.0), Vec2(-norm.0, 0.0));
-        let tb = (Vec2(0.0, norm.1), Vec2(0.0, norm.0));
-        let tr = (Vec2(norm.1, 0.0), Vec2(norm.0, 0.0));
-
-        #[rustfmt::skip]
-        self.add_vertices(pass.pass(), &[
-            // top bar: ba - dc - cc - aa
-            Vertex(ba, col, tt.0), Vertex(dc, col, tt.1), Vertex(aa, col, tt.0),
-            Vertex(aa, col, tt.0), Vertex(dc, col, tt.1), Vertex(cc, col, tt.1),
-            // left bar: aa - cc - cd - ab
-            Vertex(aa, col, tl.0), Vertex(cc, col, tl.1), Vertex(ab, col, tl.0),
-            Vertex(ab, col, tl.0), Vertex(cc, col, tl.1), Vertex(cd, col, tl.1),
-            // bottom bar: ab - cd - dd - bb
-            Vertex(ab, col, tb.0), Vertex(cd, col, tb.1), Vertex(bb, col, tb.0),
-            Vertex(bb, col, tb.0), Vertex(cd, col, tb.1), Vertex(dd, col, tb.1),
-            // right bar: bb - dd - dc - ba
-            Vertex(bb, col, tr.0), Vertex(dd, col, tr.1), Vertex(ba, col, tr.0),
-            Vertex(ba, col, tr.0), Vertex(dd, col, tr.1), Vertex(dc, col, tr.1),
-        ]);
+        let instance = Instance(aa, bb, cc, dd, col, norm, pass.depth());
+        self.add_frame(pass.pass(), instance);
     }
 
-    fn add_vertices(&mut self, pass: usize, slice: &[Vertex]) {
+    fn pass_data(&mut self, pass: usize) -> &mut PassData {
         if self.passes.len() <= pass {
             // We only need one more, but no harm in adding extra
-            self.passes.resize(pass + 8, vec![]);
+            self.passes.resize(pass + 8, Default::default());
         }
+        &mut self.passes[pass]
+    }
 
-        self.passes[pass].extend_from_slice(slice);
+    fn add_vertices(&mut self, pass: usize, slice: &[Vertex]) {
+        let data = self.pass_data(pass);
+        let start = data.vertices.len() as u32;
+        data.vertices.extend_from_slice(slice);
+        let end = data.vertices.len() as u32;
+        match data.runs.last_mut() {
+            Some(Run::Triangles(range)) => range.end = end,
+            _ => data.runs.push(Run::Triangles(start..end)),
+        }
+    }
+
+    fn add_frame(&mut self, pass: usize, instance: Instance) {
+        let data = self.pass_data(pass);
+        let index = data.instances.len() as u32;
+        data.instances.push(instance);
+        match data.runs.last_mut() {
+            Some(Run::Frames(range)) => range.end = index + 1,
+            _ => data.runs.push(Run::Frames(index..(index + 1))),
+        }
     }
 }
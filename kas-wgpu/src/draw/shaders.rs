@@ -4,50 +4,160 @@
 //     https://www.apache.org/licenses/LICENSE-2.0
 
 //! Shader management
+//!
+//! Sources (WGSL) are in `shaders/` and embedded into the binary. These are
+//! translated to SPIR-V by naga at start-up, thus no build step is required.
 
-use wgpu::{ShaderModule};
+use log::{info, warn};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use wgpu::ShaderModule;
 
 /// Shader manager
 ///
-/// For now, we embed the shader source into the binary and compile on start.
-/// Not really optimal (we could embed SPIR-V directly or load shaders from
-/// external resources), but simple to set up and use.
+/// Each embedded shader may be overridden by a file of the same name in the
+/// shader directory, if any (see [`crate::Options::shader_dir`]).
 pub struct ShaderManager {
     pub vert_3122: ShaderModule,
     pub vert_32: ShaderModule,
     pub vert_322: ShaderModule,
     pub vert_3222: ShaderModule,
+    pub vert_quad: ShaderModule,
+    pub vert_shaded_frame: ShaderModule,
     pub frag_flat_round: ShaderModule,
     pub frag_shaded_square: ShaderModule,
     pub frag_shaded_round: ShaderModule,
+    pub frag_blit: ShaderModule,
+    dir: Option<PathBuf>,
+    modified: Option<SystemTime>,
 }
 
 macro_rules! compile {
-    ($device:ident, $type:ident, $path:expr) => {{
-        $device.create_shader_module(wgpu::include_spirv!($path))
+    ($device:ident, $dir:ident, $name:expr) => {{
+        let source = include_str!(concat!("shaders/", $name));
+        compile($device, $dir, $name, source)
     }};
 }
 
+/// Create a shader module from the override of `name` in `dir` or `source`
+fn compile(device: &wgpu::Device, dir: Option<&Path>, name: &str, source: &str) -> ShaderModule {
+    let words = dir.and_then(|dir| {
+        let path = dir.join(name);
+        let source = std::fs::read_to_string(&path).ok()?;
+        match translate(&source) {
+            Ok(words) => {
+                info!("Using shader override {}", path.display());
+                Some(words)
+            }
+            Err(e) => {
+                warn!("Shader override {}: {}", path.display(), e);
+                None
+            }
+        }
+    });
+    let words = words.unwrap_or_else(|| match translate(source) {
+        Ok(words) => words,
+        Err(e) => panic!("embedded shader {}: {}", name, e),
+    });
+    device.create_shader_module(wgpu::ShaderModuleSource::SpirV(words.into()))
+}
+
+/// Translate WGSL `source` to SPIR-V
+fn translate(source: &str) -> Result<Vec<u32>, String> {
+    use naga::back::spv;
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+
+    let module = naga::front::wgsl::parse_str(source).map_err(|e| e.emit_to_string(source))?;
+    let info = Validator::new(ValidationFlags::all(), Capabilities::empty())
+        .validate(&module)
+        .map_err(|e| e.emit_to_string(source))?;
+    // Our shaders use wgpu 0.6's (Vulkan) coordinate space: no adjustment
+    let options = spv::Options {
+        flags: spv::WriterFlags::empty(),
+        ..Default::default()
+    };
+    spv::write_vec(&module, &info, &options, None).map_err(|e| e.to_string())
+}
+
+/// Time of the latest modification to a file in `dir`, if any
+fn last_modified(dir: &Path) -> Option<SystemTime> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.metadata().and_then(|m| m.modified()).ok())
+        .max()
+}
+
 impl ShaderManager {
-    pub fn new(device: &wgpu::Device) -> Self {
+    /// Interval between checks of the shader directory
+    pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    /// Construct, using overrides from `dir` if given
+    pub fn new(device: &wgpu::Device, dir: Option<&Path>) -> Self {
+        let modified = dir.and_then(last_modified);
 
-        let vert_3122 = compile!(device, Vertex, "shaders_bin/scaled3122.vert");
-        let vert_32 = compile!(device, Vertex, "shaders_bin/scaled32.vert");
-        let vert_322 = compile!(device, Vertex, "shaders_bin/scaled322.vert");
-        let vert_3222 = compile!(device, Vertex, "shaders_bin/scaled3222.vert");
+        let vert_3122 = compile!(device, dir, "scaled3122.vert.wgsl");
+        let vert_32 = compile!(device, dir, "scaled32.vert.wgsl");
+        let vert_322 = compile!(device, dir, "scaled322.vert.wgsl");
+        let vert_3222 = compile!(device, dir, "scaled3222.vert.wgsl");
+        let vert_quad = compile!(device, dir, "quad.vert.wgsl");
+        let vert_shaded_frame = compile!(device, dir, "shaded_frame.vert.wgsl");
 
-        let frag_flat_round = compile!(device, Fragment, "shaders_bin/flat_round.frag");
-        let frag_shaded_square = compile!(device, Fragment, "shaders_bin/shaded_square.frag");
-        let frag_shaded_round = compile!(device, Fragment, "shaders_bin/shaded_round.frag");
+        let frag_flat_round = compile!(device, dir, "flat_round.frag.wgsl");
+        let frag_shaded_square = compile!(device, dir, "shaded_square.frag.wgsl");
+        let frag_shaded_round = compile!(device, dir, "shaded_round.frag.wgsl");
+        let frag_blit = compile!(device, dir, "blit.frag.wgsl");
 
         ShaderManager {
             vert_3122,
             vert_32,
             vert_322,
             vert_3222,
+            vert_quad,
+            vert_shaded_frame,
             frag_flat_round,
             frag_shaded_square,
             frag_shaded_round,
+            frag_blit,
+            dir: dir.map(|dir| dir.to_path_buf()),
+            modified,
+        }
+    }
+
+    /// True if shader overrides are loaded from a directory
+    #[inline]
+    pub fn watches_dir(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// Check the shader directory for changes
+    ///
+    /// If any file was modified since the last poll, all shaders are reloaded
+    /// and true is returned; the caller must then rebuild its pipelines.
+    pub fn poll(&mut self, device: &wgpu::Device) -> bool {
+        let dir = match self.dir.as_ref() {
+            Some(dir) if last_modified(dir) != self.modified => dir.clone(),
+            _ => return false,
+        };
+        info!("Reloading shaders from {}", dir.display());
+        *self = ShaderManager::new(device, Some(&dir));
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn translate_embedded() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/src/draw/shaders");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let source = std::fs::read_to_string(&path).unwrap();
+            if let Err(e) = translate(&source) {
+                panic!("{}: {}", path.display(), e);
+            }
         }
     }
 }
@@ -0,0 +1,14 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+@group(1) @binding(0)
+var tex: texture_2d<f32>;
+@group(1) @binding(1)
+var tex_sampler: sampler;
+
+@fragment
+fn main(@location(1) b1: vec2<f32>) -> @location(0) vec4<f32> {
+    return textureSample(tex, tex_sampler, b1);
+}
@@ -0,0 +1,28 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+fn sample_a(inner: f32, pos: vec2<f32>) -> f32 {
+    let pos2 = pos * pos;
+    let ss = pos2.x + pos2.y;
+    return select(0.0, 0.25, inner <= ss && ss <= 1.0);
+}
+
+@fragment
+fn main(
+    @location(0) @interpolate(flat) col: vec3<f32>,
+    @location(1) @interpolate(flat) inner: f32,
+    @location(2) pos: vec2<f32>,
+    @location(3) off: vec2<f32>,
+) -> @location(0) vec4<f32> {
+    // Multi-sample alpha to avoid ugly aliasing.
+    let off1 = vec2<f32>(off.x, 3.0 * off.y);
+    let off2 = vec2<f32>(3.0 * off.x, off.y);
+    let alpha = sample_a(inner, pos + off1)
+        + sample_a(inner, pos - off1)
+        + sample_a(inner, pos + off2)
+        + sample_a(inner, pos - off2);
+
+    return vec4<f32>(col, alpha);
+}
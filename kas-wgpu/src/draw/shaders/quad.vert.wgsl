@@ -0,0 +1,37 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+// Instanced: each instance is a quad, drawn as six vertices
+
+struct Locals {
+    scale: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> locals: Locals;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(1) b1: vec2<f32>,
+}
+
+const offset: vec2<f32> = vec2<f32>(-1.0, 1.0);
+
+@vertex
+fn main(
+    @builtin(vertex_index) index: u32,
+    @location(0) a: vec2<f32>,
+    @location(1) b: vec2<f32>,
+    @location(2) ta: vec2<f32>,
+    @location(3) tb: vec2<f32>,
+    @location(4) depth: f32,
+) -> VertexOutput {
+    // Triangles aa-ba-ab and ab-ba-bb: bits select the b corner per vertex
+    let t = vec2<f32>(f32((0x32u >> index) & 1u), f32((0x2Cu >> index) & 1u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(locals.scale * mix(a, b, t) + offset, depth, 1.0);
+    out.b1 = mix(ta, tb, t);
+    return out;
+}
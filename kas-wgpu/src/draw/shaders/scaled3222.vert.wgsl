@@ -0,0 +1,38 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+struct Locals {
+    scale: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> locals: Locals;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) @interpolate(flat) col: vec3<f32>,
+    @location(1) b1: vec2<f32>,
+    @location(2) @interpolate(flat) b2: vec2<f32>,
+    @location(3) b3: vec2<f32>,
+}
+
+const offset: vec2<f32> = vec2<f32>(-1.0, 1.0);
+
+@vertex
+fn main(
+    @location(0) pos: vec3<f32>,
+    @location(1) col: vec3<f32>,
+    @location(2) a1: vec2<f32>,
+    @location(3) a2: vec2<f32>,
+    @location(4) a3: vec2<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(locals.scale * pos.xy + offset, pos.z, 1.0);
+    out.col = col;
+    out.b1 = a1;
+    out.b2 = a2;
+    out.b3 = a3;
+    return out;
+}
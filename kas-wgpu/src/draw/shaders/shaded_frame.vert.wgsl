@@ -0,0 +1,64 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+// Instanced: each instance is a frame (four bars), drawn as 24 vertices
+
+struct Locals {
+    scale: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> locals: Locals;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) @interpolate(flat) col: vec3<f32>,
+    @location(1) norm: vec2<f32>,
+}
+
+const offset: vec2<f32> = vec2<f32>(-1.0, 1.0);
+
+@vertex
+fn main(
+    @builtin(vertex_index) index: u32,
+    @location(0) outer_a: vec2<f32>,
+    @location(1) outer_b: vec2<f32>,
+    @location(2) inner_a: vec2<f32>,
+    @location(3) inner_b: vec2<f32>,
+    @location(4) col: vec3<f32>,
+    @location(5) norm: vec2<f32>,
+    @location(6) depth: f32,
+) -> VertexOutput {
+    // Bars are drawn in order top, left, bottom, right, each as triangles
+    // o1-i1-o2 and o2-i1-i2 where o1, o2 are outer corners (anti-clockwise
+    // from top-right) and i1, i2 the corresponding inner corners.
+    let bar = index / 6u;
+    let k = index % 6u;
+    let inner = ((0x32u >> k) & 1u) == 1u;
+    let corner = (bar + ((0x2Cu >> k) & 1u)) % 4u;
+
+    // Corners 0-3 are: ba, aa, ab, bb
+    let a = select(outer_a, inner_a, inner);
+    let b = select(outer_b, inner_b, inner);
+    let pos = vec2<f32>(
+        select(a.x, b.x, corner == 0u || corner == 3u),
+        select(a.y, b.y, corner >= 2u),
+    );
+
+    // Bar directions: up, left, down, right
+    var dir = vec2<f32>(0.0, 0.0);
+    switch bar {
+        case 0u: { dir = vec2<f32>(0.0, -1.0); }
+        case 1u: { dir = vec2<f32>(-1.0, 0.0); }
+        case 2u: { dir = vec2<f32>(0.0, 1.0); }
+        default: { dir = vec2<f32>(1.0, 0.0); }
+    }
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(locals.scale * pos + offset, depth, 1.0);
+    out.col = col;
+    out.norm = dir * select(norm.y, norm.x, inner);
+    return out;
+}
@@ -0,0 +1,58 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+struct Locals {
+    light_norm: vec3<f32>,
+}
+
+@group(0) @binding(1)
+var<uniform> locals: Locals;
+
+fn sample_a(dir: vec2<f32>) -> f32 {
+    let dir2 = dir * dir;
+    let ss = dir2.x + dir2.y;
+    return select(0.0, 0.25, ss <= 1.0);
+}
+
+@fragment
+fn main(
+    @location(0) @interpolate(flat) col: vec3<f32>,
+    @location(1) dir: vec2<f32>,
+    @location(2) @interpolate(flat) adjust: vec2<f32>,
+    @location(3) off: vec2<f32>,
+) -> @location(0) vec4<f32> {
+    // Multi-sample alpha to avoid ugly aliasing. A single colour sample is adequate.
+    let off1 = vec2<f32>(off.x, 3.0 * off.y);
+    let off2 = vec2<f32>(3.0 * off.x, off.y);
+    let alpha = sample_a(dir + off1)
+        + sample_a(dir - off1)
+        + sample_a(dir + off2)
+        + sample_a(dir - off2);
+    if alpha == 0.0 {
+        discard;
+    }
+
+    let dir2 = dir * dir;
+    let ss = dir2.x + dir2.y;
+
+    // With multi-sampling we can hit ss>1. Clamp to avoid imaginary roots:
+    var z = sqrt(max(1.0 - ss, 0.0));
+    let h = sqrt(ss);
+    let t = adjust.x + adjust.y * atan2(h, z);
+    var normh = vec2<f32>(0.0, 0.0);
+    if h > 0.0 {
+        normh = dir * (sin(t) / h);
+        z = cos(t);
+    }
+    let norm = vec3<f32>(normh, z);
+
+    // Simplified version with only scale adjustment; looks okay with convex
+    // curvature but not with concave:
+    // let z = sqrt(1.0 - adjust.y * ss);
+    // let norm = vec3<f32>(dir * sqrt(adjust.y), z);
+
+    let c = col * dot(norm, locals.light_norm);
+    return vec4<f32>(c, alpha);
+}
@@ -0,0 +1,22 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+struct Locals {
+    light_norm: vec3<f32>,
+}
+
+@group(0) @binding(1)
+var<uniform> locals: Locals;
+
+@fragment
+fn main(
+    @location(0) @interpolate(flat) col: vec3<f32>,
+    @location(1) norm2: vec2<f32>,
+) -> @location(0) vec4<f32> {
+    let n3 = 1.0 - sqrt(norm2.x * norm2.x + norm2.y * norm2.y);
+    let norm = vec3<f32>(norm2, n3);
+    let c = col * dot(norm, locals.light_norm);
+    return vec4<f32>(c, 1.0);
+}
@@ -5,19 +5,22 @@
 
 //! Event loop and handling
 
+use instant::Instant;
 use log::{debug, error, trace};
 use smallvec::SmallVec;
 use std::collections::HashMap;
-use std::time::Instant;
 
 use winit::event::{Event, StartCause};
 use winit::event_loop::{ControlFlow, EventLoopWindowTarget};
 use winit::window as ww;
 
+use kas::event::PowerSource;
+use kas::geom::Coord;
 use kas::TkAction;
 use kas_theme::Theme;
 
-use crate::draw::{CustomPipe, DrawPipe};
+use crate::draw::{CustomPipe, DrawPipe, ShaderManager};
+use crate::power;
 use crate::shared::{PendingAction, SharedState};
 use crate::{ProxyAction, Window, WindowId};
 
@@ -34,6 +37,18 @@ where
     shared: SharedState<C, T>,
     /// Timer resumes: (time, window index)
     resumes: Vec<(Instant, ww::WindowId)>,
+    /// Time of next power status poll, if supported
+    power_poll: Option<Instant>,
+    /// Time of next theme file poll, if watched and a window has focus
+    theme_poll: Option<Instant>,
+    /// Time of next shader directory poll, if watched
+    shader_poll: Option<Instant>,
+    /// The window with focus, if any
+    focused_window: Option<ww::WindowId>,
+    /// Time of the wake-up requested to continue idle tasks
+    idle_poll: Option<Instant>,
+    /// Serial number of the last window focus event
+    focus_serial: u64,
 }
 
 impl<C: CustomPipe + 'static, T: Theme<DrawPipe<C>>> Loop<C, T>
@@ -48,11 +63,27 @@ where
             .iter()
             .map(|w| (w.window_id, w.window.id()))
             .collect();
+        let power_poll = match shared.power.source {
+            PowerSource::Unknown => None,
+            _ => Some(Instant::now() + power::POLL_INTERVAL),
+        };
+        let shader_poll = if shared.shaders.watches_dir() {
+            Some(Instant::now() + ShaderManager::POLL_INTERVAL)
+        } else {
+            None
+        };
         Loop {
             windows: windows.drain(..).map(|w| (w.window.id(), w)).collect(),
             id_map,
             shared,
             resumes: vec![],
+            power_poll,
+            // The theme file is polled only while a window has focus
+            theme_poll: None,
+            shader_poll,
+            focused_window: None,
+            idle_poll: None,
+            focus_serial: 0,
         }
     }
 
@@ -66,9 +97,32 @@ where
 
         match event {
             WindowEvent { window_id, event } => {
+                #[cfg(target_arch = "wasm32")]
+                if let winit::event::WindowEvent::Focused(false) = event {
+                    let window = self.windows.get_mut(&window_id);
+                    if window.map(|w| w.ime_took_focus()) == Some(true) {
+                        return;
+                    }
+                }
+                let moved = match event {
+                    winit::event::WindowEvent::CursorMoved { .. } => true,
+                    _ => false,
+                };
+                if let winit::event::WindowEvent::Focused(focused) = event {
+                    self.set_focus(window_id, focused);
+                }
                 if let Some(window) = self.windows.get_mut(&window_id) {
+                    if let winit::event::WindowEvent::Focused(true) = event {
+                        self.focus_serial += 1;
+                        window.focus_serial = self.focus_serial;
+                    }
                     window.handle_event(&mut self.shared, event);
                 }
+                if moved {
+                    // The source window holds the grab, thus receives all
+                    // cursor motion during a drag
+                    self.update_drag();
+                }
             }
 
             DeviceEvent { .. } => return, // windows handle local input; we do not handle global input
@@ -97,6 +151,30 @@ where
                 *control_flow = ControlFlow::Wait;
 
                 match cause {
+                    StartCause::ResumeTimeReached {
+                        requested_resume, ..
+                    } if self.idle_poll == Some(requested_resume) => {
+                        // Idle tasks are run from MainEventsCleared
+                        self.idle_poll = None;
+                    }
+                    StartCause::ResumeTimeReached {
+                        requested_resume, ..
+                    } if self.power_poll == Some(requested_resume) => {
+                        self.poll_power();
+                    }
+                    #[cfg(feature = "theme_file")]
+                    StartCause::ResumeTimeReached {
+                        requested_resume, ..
+                    } if self.theme_poll == Some(requested_resume) => {
+                        self.theme_poll = Some(Instant::now() + crate::theme_file::POLL_INTERVAL);
+                        self.shared.poll_theme_file();
+                    }
+                    StartCause::ResumeTimeReached {
+                        requested_resume, ..
+                    } if self.shader_poll == Some(requested_resume) => {
+                        self.shader_poll = Some(Instant::now() + ShaderManager::POLL_INTERVAL);
+                        self.shared.poll_shaders();
+                    }
                     StartCause::ResumeTimeReached {
                         requested_resume, ..
                     } => {
@@ -135,8 +213,11 @@ where
 
             MainEventsCleared => {
                 let mut close_all = false;
+                let mut idle = false;
                 let mut to_close = SmallVec::<[ww::WindowId; 4]>::new();
                 for (window_id, window) in self.windows.iter_mut() {
+                    // Handle any new events, then continue idle work
+                    idle |= window.run_idle(&mut self.shared);
                     let (action, resume) = window.update(&mut self.shared);
                     match action {
                         TkAction::None
@@ -144,26 +225,21 @@ where
                         | TkAction::RegionMoved
                         | TkAction::Popup
                         | TkAction::SetSize
+                        | TkAction::Relayout
                         | TkAction::Resize
                         | TkAction::Reconfigure => (),
                         TkAction::Close => to_close.push(*window_id),
                         TkAction::CloseAll => close_all = true,
                     }
                     if let Some(instant) = resume {
-                        if let Some((i, _)) = self
-                            .resumes
-                            .iter()
-                            .enumerate()
-                            .find(|item| (item.1).1 == *window_id)
-                        {
-                            self.resumes[i].0 = instant;
-                        } else {
-                            self.resumes.push((instant, *window_id));
-                        }
+                        set_resume(&mut self.resumes, *window_id, instant);
                     }
                 }
 
                 for window_id in &to_close {
+                    if self.shared.drag.as_ref().map(|d| d.source) == Some(*window_id) {
+                        self.shared.drag = None;
+                    }
                     if let Some(window) = self.windows.remove(window_id) {
                         self.id_map.remove(&window.window_id);
                         if window.handle_closure(&mut self.shared) == TkAction::CloseAll {
@@ -185,17 +261,37 @@ where
                     ControlFlow::Exit
                 } else if *control_flow == ControlFlow::Poll {
                     ControlFlow::Poll
-                } else if let Some((instant, _)) = self.resumes.first() {
-                    trace!("Requesting resume at {:?}", *instant);
-                    ControlFlow::WaitUntil(*instant)
                 } else {
-                    ControlFlow::Wait
+                    let resume = self.resumes.first().map(|item| item.0);
+                    let polls = [self.power_poll, self.theme_poll, self.shader_poll];
+                    let resume = polls.iter().flatten().cloned().chain(resume).min();
+                    let (flow, idle_poll) = wait_until(resume, idle, Instant::now());
+                    trace!("Requesting resume: {:?}", flow);
+                    self.idle_poll = idle_poll;
+                    flow
                 };
             }
 
             RedrawRequested(id) => {
                 if let Some(window) = self.windows.get_mut(&id) {
-                    window.do_draw(&mut self.shared);
+                    if let Some(due) = window.do_draw(&mut self.shared) {
+                        // Frame deferred by the frame limiter. Since
+                        // MainEventsCleared already set control_flow, we must
+                        // schedule the wake-up here.
+                        let instant = self
+                            .resumes
+                            .iter()
+                            .find(|item| item.1 == id)
+                            .map(|item| item.0.min(due))
+                            .unwrap_or(due);
+                        set_resume(&mut self.resumes, id, instant);
+                        self.resumes.sort_by_key(|item| item.0);
+                        *control_flow = match *control_flow {
+                            ControlFlow::Wait => ControlFlow::WaitUntil(due),
+                            ControlFlow::WaitUntil(t) => ControlFlow::WaitUntil(t.min(due)),
+                            cf => cf,
+                        };
+                    }
                 }
             }
 
@@ -243,7 +339,7 @@ where
                 }
                 PendingAction::RedrawAll => {
                     for (_, window) in self.windows.iter_mut() {
-                        window.window.request_redraw();
+                        window.redraw_all();
                     }
                 }
                 PendingAction::Update(handle, payload) => {
@@ -251,7 +347,172 @@ where
                         window.update_handle(&mut self.shared, handle, payload);
                     }
                 }
+                PendingAction::EndDrag(drop) => self.end_drag(drop),
+                PendingAction::Capture(id, f) => {
+                    if let Some(window) = self.windows.get_mut(&id) {
+                        window.capture(&mut self.shared, f);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Poll the power status, notifying windows of changes
+    fn poll_power(&mut self) {
+        self.power_poll = Some(Instant::now() + power::POLL_INTERVAL);
+        if let Some(status) = power::read_status() {
+            if status != self.shared.power {
+                debug!("Power status changed: {:?}", status);
+                self.shared.set_power(status);
+                for window in self.windows.values_mut() {
+                    window.update_power(&mut self.shared, status);
+                }
+            }
+        }
+    }
+
+    /// Track window focus
+    ///
+    /// The theme file (if any) is polled only while a window has focus.
+    fn set_focus(&mut self, id: ww::WindowId, focused: bool) {
+        if focused {
+            self.focused_window = Some(id);
+        } else if self.focused_window == Some(id) {
+            self.focused_window = None;
+        }
+
+        #[cfg(feature = "theme_file")]
+        if self.focused_window.is_none() {
+            self.theme_poll = None;
+        } else if self.theme_poll.is_none() && self.shared.watches_theme_file() {
+            // The file may have been edited while unfocused: check now
+            self.theme_poll = Some(Instant::now() + crate::theme_file::POLL_INTERVAL);
+            self.shared.poll_theme_file();
+        }
+    }
+
+    /// Find the window and coordinate under the cursor of a drag
+    ///
+    /// Cursor motion is reported to the drag's source window (which holds the
+    /// mouse grab); we translate to screen coordinates to find the target.
+    /// Where windows overlap, the most recently focussed takes precedence.
+    fn find_drag_target(&self, source: ww::WindowId) -> Option<(ww::WindowId, Coord)> {
+        let window = self.windows.get(&source)?;
+        let coord = window.cursor_coord();
+        let pos = match window.coord_to_screen(coord) {
+            Some(pos) => pos,
+            // Without screen positions we can only target the source window
+            None => return Some((source, coord)).filter(|_| window.contains(coord)),
+        };
+        self.windows
+            .iter()
+            .filter_map(|(id, w)| {
+                let coord = w.screen_to_coord(pos)?;
+                Some((w.focus_serial, *id, coord))
+            })
+            .max_by_key(|item| item.0)
+            .map(|(_, id, coord)| (id, coord))
+    }
+
+    /// Send drag-over events following cursor motion
+    fn update_drag(&mut self) {
+        let (source, old_target) = match self.shared.drag.as_ref() {
+            Some(drag) => (drag.source, drag.target),
+            None => return,
+        };
+        let target = self.find_drag_target(source);
+        let data = self.shared.drag.as_ref().unwrap().data.clone();
+
+        if let Some(id) = old_target.filter(|id| Some(*id) != target.map(|t| t.0)) {
+            if let Some(window) = self.windows.get_mut(&id) {
+                window.drag_over(&mut self.shared, None, &data);
+            }
+        }
+        if let Some((id, coord)) = target {
+            if let Some(window) = self.windows.get_mut(&id) {
+                window.drag_over(&mut self.shared, Some(coord), &data);
+            }
+        }
+        if let Some(drag) = self.shared.drag.as_mut() {
+            drag.target = target.map(|t| t.0);
+        }
+    }
+
+    /// End a drag, dropping data on the target if `drop`
+    fn end_drag(&mut self, drop: bool) {
+        let drag = match self.shared.drag.take() {
+            Some(drag) => drag,
+            None => return,
+        };
+        debug!("Ending drag (drop: {})", drop);
+
+        let mut accepted = false;
+        if drop {
+            if let Some((id, coord)) = self.find_drag_target(drag.source) {
+                if let Some(window) = self.windows.get_mut(&id) {
+                    accepted = window.drag_drop(&mut self.shared, coord, drag.data.clone());
+                }
+            }
+        }
+        if let Some(id) = drag.target {
+            if let Some(window) = self.windows.get_mut(&id) {
+                // Sends DragLeave where the drop did not clear the target
+                window.drag_over(&mut self.shared, None, &drag.data);
             }
         }
+        if let Some(window) = self.windows.get_mut(&drag.source) {
+            window.drag_end(&mut self.shared, accepted);
+        }
     }
 }
+
+/// Set the resume time of a window, replacing any existing entry
+fn set_resume(resumes: &mut Vec<(Instant, ww::WindowId)>, id: ww::WindowId, instant: Instant) {
+    if let Some(item) = resumes.iter_mut().find(|item| item.1 == id) {
+        item.0 = instant;
+    } else {
+        resumes.push((instant, id));
+    }
+}
+
+// Control flow given the next requested resume time and whether idle tasks
+// remain. We do not use ControlFlow::Poll for idle tasks since timers would
+// then never be reported (as StartCause::ResumeTimeReached). Instead we wake
+// immediately unless a resume is already due, returning the idle wake-up
+// time, if any.
+fn wait_until(resume: Option<Instant>, idle: bool, now: Instant) -> (ControlFlow, Option<Instant>) {
+    match resume {
+        Some(instant) if !idle || instant <= now => (ControlFlow::WaitUntil(instant), None),
+        _ if idle => (ControlFlow::WaitUntil(now), Some(now)),
+        _ => (ControlFlow::Wait, None),
+    }
+}
+
+#[test]
+fn idle_wait_until() {
+    use std::time::Duration;
+
+    let now = Instant::now();
+    let (past, future) = (
+        now - Duration::from_millis(5),
+        now + Duration::from_millis(5),
+    );
+    assert_eq!(wait_until(None, false, now), (ControlFlow::Wait, None));
+    assert_eq!(
+        wait_until(Some(future), false, now),
+        (ControlFlow::WaitUntil(future), None)
+    );
+    // Idle work must not delay or prevent due timers
+    assert_eq!(
+        wait_until(Some(past), true, now),
+        (ControlFlow::WaitUntil(past), None)
+    );
+    assert_eq!(
+        wait_until(Some(future), true, now),
+        (ControlFlow::WaitUntil(now), Some(now))
+    );
+    assert_eq!(
+        wait_until(None, true, now),
+        (ControlFlow::WaitUntil(now), Some(now))
+    );
+}
@@ -0,0 +1,89 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Keyboard layout queries
+
+/// Update the [`kas::event::KeyLayout`] from the platform's current layout
+///
+/// Returns true if the layout changed. Where unsupported, this does nothing
+/// and the layout is learned from input instead.
+#[cfg(target_os = "windows")]
+pub fn query_layout() -> bool {
+    use kas::event::KeyLayout;
+    use kas::event::VirtualKeyCode as VK;
+    use winapi::um::winuser::{MapVirtualKeyW, MAPVK_VK_TO_CHAR, MAPVK_VK_TO_VSC};
+
+    // Windows virtual-key codes of digit and letter keys equal their ASCII
+    // codes. (Other keys are already mapped to a VirtualKeyCode by character.)
+    const KEYS: [(u8, VK); 36] = [
+        (b'0', VK::Key0),
+        (b'1', VK::Key1),
+        (b'2', VK::Key2),
+        (b'3', VK::Key3),
+        (b'4', VK::Key4),
+        (b'5', VK::Key5),
+        (b'6', VK::Key6),
+        (b'7', VK::Key7),
+        (b'8', VK::Key8),
+        (b'9', VK::Key9),
+        (b'A', VK::A),
+        (b'B', VK::B),
+        (b'C', VK::C),
+        (b'D', VK::D),
+        (b'E', VK::E),
+        (b'F', VK::F),
+        (b'G', VK::G),
+        (b'H', VK::H),
+        (b'I', VK::I),
+        (b'J', VK::J),
+        (b'K', VK::K),
+        (b'L', VK::L),
+        (b'M', VK::M),
+        (b'N', VK::N),
+        (b'O', VK::O),
+        (b'P', VK::P),
+        (b'Q', VK::Q),
+        (b'R', VK::R),
+        (b'S', VK::S),
+        (b'T', VK::T),
+        (b'U', VK::U),
+        (b'V', VK::V),
+        (b'W', VK::W),
+        (b'X', VK::X),
+        (b'Y', VK::Y),
+        (b'Z', VK::Z),
+    ];
+
+    KeyLayout::update(|layout| {
+        let mut changed = false;
+        for (code, vkey) in KEYS.iter() {
+            let code = *code as u32;
+            let (scancode, c) = unsafe {
+                (
+                    MapVirtualKeyW(code, MAPVK_VK_TO_VSC),
+                    MapVirtualKeyW(code, MAPVK_VK_TO_CHAR),
+                )
+            };
+            // The top bit marks dead keys; the low word is the character
+            let c = std::char::from_u32(c & 0xffff).filter(|c| !c.is_control());
+            if let Some(c) = c.filter(|_| scancode != 0) {
+                // Letters are reported in upper case, but input without
+                // modifiers (as learned from) is lower case
+                let c = c.to_lowercase().next().unwrap_or(c);
+                changed |= layout.insert(scancode, Some(*vkey), c);
+            }
+        }
+        changed
+    })
+}
+
+/// Update the [`kas::event::KeyLayout`] from the platform's current layout
+///
+/// Returns true if the layout changed. Where unsupported, this does nothing
+/// and the layout is learned from input instead.
+#[cfg(not(target_os = "windows"))]
+pub fn query_layout() -> bool {
+    false
+}
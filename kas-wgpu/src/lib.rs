@@ -14,16 +14,27 @@
 
 #![cfg_attr(feature = "gat", feature(generic_associated_types))]
 
+mod dark_mode;
 pub mod draw;
 mod event_loop;
+mod keyboard;
 pub mod options;
+mod overlay;
+mod power;
 mod shared;
+mod taskbar;
+#[cfg(feature = "theme_file")]
+mod theme_file;
+#[cfg(target_arch = "wasm32")]
+mod web;
 mod window;
 
+use std::cell::Cell;
+use std::rc::Rc;
 use std::{error, fmt};
 
 use kas::event::UpdateHandle;
-use kas::WindowId;
+use kas::{Capture, WindowId};
 use kas_theme::Theme;
 use winit::error::OsError;
 use winit::event_loop::{EventLoop, EventLoopProxy};
@@ -33,6 +44,7 @@ use crate::shared::SharedState;
 use window::Window;
 
 pub use options::Options;
+pub use overlay::Overlay;
 
 pub use kas;
 pub use kas_theme as theme;
@@ -49,7 +61,9 @@ pub enum Error {
     /// No suitable graphics adapter found
     ///
     /// This can be a driver/configuration issue or hardware limitation. Note
-    /// that for now, `wgpu` only supports DX11, DX12, Vulkan and Metal.
+    /// that for now, `wgpu` only supports DX11, DX12, Vulkan and Metal. This
+    /// is reported only once all backend fallbacks have failed (see
+    /// [`Options::backend_fallback`]).
     NoAdapter,
     #[doc(hidden)]
     /// OS error during window creation
@@ -95,9 +109,18 @@ where
     ///
     /// Environment variables may affect option selection; see documentation
     /// of [`Options::from_env`].
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn new(theme: T) -> Result<Self, Error> {
         Self::new_custom((), theme, Options::from_env())
     }
+
+    /// Construct a new instance with default options, asynchronously
+    ///
+    /// This is equivalent to [`Toolkit::new`], but usable within the browser
+    /// where blocking is not possible.
+    pub async fn new_async(theme: T) -> Result<Self, Error> {
+        Self::new_custom_async((), theme, Options::from_env()).await
+    }
 }
 
 impl<C: CustomPipe + 'static, T: Theme<DrawPipe<C>> + 'static> Toolkit<C, T>
@@ -111,11 +134,13 @@ where
     ///
     /// The [`Options`] parameter allows direct specification of toolkit
     /// options; usually, these are provided by [`Options::from_env`].
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn new_custom<CB: CustomPipeBuilder<Pipe = C>>(
         custom: CB,
         theme: T,
         options: Options,
     ) -> Result<Self, Error> {
+        kas_theme::set_reduce_motion(options.reduce_motion);
         let el = EventLoop::with_user_event();
         let scale_factor = el.primary_monitor().scale_factor();
         Ok(Toolkit {
@@ -125,6 +150,34 @@ where
         })
     }
 
+    /// Construct an instance with custom options, asynchronously
+    ///
+    /// This is equivalent to [`Toolkit::new_custom`], but usable within the
+    /// browser where blocking is not possible.
+    pub async fn new_custom_async<CB: CustomPipeBuilder<Pipe = C>>(
+        custom: CB,
+        theme: T,
+        options: Options,
+    ) -> Result<Self, Error> {
+        kas_theme::set_reduce_motion(options.reduce_motion);
+        let el = EventLoop::with_user_event();
+        let scale_factor = el.primary_monitor().scale_factor();
+        let shared = SharedState::new_async(custom, theme, options, scale_factor).await?;
+        Ok(Toolkit {
+            el,
+            windows: vec![],
+            shared,
+        })
+    }
+
+    /// Information on the graphics adapter in use
+    ///
+    /// This reports the backend actually selected, which may differ from that
+    /// requested if fallback occurred (see [`Options::backend_fallback`]).
+    pub fn backend_info(&self) -> &wgpu::AdapterInfo {
+        &self.shared.adapter_info
+    }
+
     /// Assume ownership of and display a window
     ///
     /// This is a convenience wrapper around [`Toolkit::add_boxed`].
@@ -142,6 +195,26 @@ where
         Ok(id)
     }
 
+    /// Capture the content of a window as an RGBA image
+    ///
+    /// The window is drawn to an off-screen texture which is then read back,
+    /// thus this may be used before [`Toolkit::run`], e.g. for thumbnails or
+    /// tests comparing output against reference images. From within the UI,
+    /// use [`kas::event::Manager::capture`] instead.
+    ///
+    /// Returns `None` if no window with this `id` exists, the window has zero
+    /// size or reading back the frame fails. Within the browser, reading back
+    /// may only be awaited, thus this always returns `None`.
+    pub fn capture(&mut self, id: WindowId) -> Option<Capture> {
+        let result = Rc::new(Cell::new(None));
+        let shared = &mut self.shared;
+        if let Some(window) = self.windows.iter_mut().find(|w| w.window_id == id) {
+            let result = result.clone();
+            window.capture(shared, Box::new(move |capture| result.set(Some(capture))));
+        }
+        result.take()
+    }
+
     /// Create a proxy which can be used to update the UI from another thread
     pub fn create_proxy(&self) -> ToolkitProxy {
         ToolkitProxy {
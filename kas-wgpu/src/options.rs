@@ -7,15 +7,109 @@
 
 use log::warn;
 use std::env::var;
-pub use wgpu::{BackendBit, PowerPreference};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::time::Duration;
+pub use wgpu::{BackendBit, PowerPreference, PresentMode};
+
+use crate::draw::ColourFilter;
+use kas::event::InputConfig;
 
 /// Toolkit options
-#[derive(Clone, PartialEq, Hash)]
+#[derive(Clone, PartialEq)]
 pub struct Options {
     /// Adapter power preference. Default value: low power.
     pub power_preference: PowerPreference,
     /// Adapter backend. Default value: PRIMARY (Vulkan/Metal/DX12).
     pub backends: BackendBit,
+    /// Fall back to other backends if the preferred ones are unavailable.
+    /// Default value: true.
+    ///
+    /// If no adapter supporting [`Options::backends`] can be opened, other
+    /// backends are tried in the order Vulkan, Metal, DX12, DX11.
+    /// [`crate::Toolkit::backend_info`] reports the adapter in use.
+    ///
+    /// Note that `wgpu` does not (yet) support OpenGL and KAS has no software
+    /// renderer: a software adapter (e.g. a CPU Vulkan driver such as
+    /// lavapipe) is used only if installed on the system. If all backends fail,
+    /// [`crate::Error::NoAdapter`] is returned.
+    pub backend_fallback: bool,
+    /// Path to which the draw commands of the first frame drawn are written.
+    /// Default value: none.
+    pub draw_capture: Option<PathBuf>,
+    /// Colour-blindness simulation filter. Default value: none.
+    ///
+    /// This may be cycled at run-time by pressing Ctrl+Shift+F12.
+    pub colour_filter: ColourFilter,
+    /// Input configuration (e.g. key repeat delay and interval).
+    pub input: InputConfig,
+    /// Disable theme animations. Default value: false.
+    pub reduce_motion: bool,
+    /// Disable theme animations while on battery power. Default value: false.
+    pub battery_saver: bool,
+    /// Warm up pipelines and the glyph cache before showing each window.
+    /// Default value: false.
+    ///
+    /// This avoids a stutter when text is first drawn at the cost of slower
+    /// window creation.
+    pub warm_up: bool,
+    /// Dark-mode preference passed to the theme. Default value: none.
+    ///
+    /// If none, the system preference is detected (where supported) and
+    /// followed; otherwise this value overrides the system preference.
+    pub dark_mode: Option<bool>,
+    /// Application-level UI scale factor. Default value: 1.0.
+    ///
+    /// This multiplies the monitor's DPI factor, scaling theme dimensions and
+    /// fonts. Unless handled by a widget, the zoom shortcuts (by default
+    /// Ctrl+= / Ctrl+- and Ctrl+0 to reset) adjust it at run-time; see
+    /// [`kas::event::ControlKey::ZoomIn`].
+    pub ui_scale: f32,
+    /// Theme configuration file (JSON). Default value: none.
+    ///
+    /// If set, a [`kas_theme::ThemeConfig`] is loaded from this file on start
+    /// and the file is watched: on modification, it is reloaded and the
+    /// theme updated. The file is checked when a window gains focus and
+    /// periodically while a window has focus. This requires feature
+    /// `theme_file` (otherwise the option is ignored).
+    pub theme_file: Option<PathBuf>,
+    /// Multi-sampled anti-aliasing sample count. Default value: 1.
+    ///
+    /// With value 1, only analytic anti-aliasing is used: rounded shapes and
+    /// text are smoothed, but straight edges of paths are not. This is the
+    /// cheapest option and gives the crispest output. Larger values enable
+    /// MSAA with this many samples per pixel, smoothing all edges at extra
+    /// GPU cost. Supported values are 1, 2, 4 and 8, though hardware may not
+    /// support all of these; 4 is the most widely supported.
+    pub msaa_samples: u32,
+    /// Swap-chain present mode. Default value: `Fifo`.
+    ///
+    /// `Fifo` waits for vertical sync: no tearing, but higher latency.
+    /// `Mailbox` also avoids tearing but replaces queued frames, reducing
+    /// latency at the cost of more rendering. `Immediate` presents without
+    /// waiting, with lowest latency but possible tearing. Modes other than
+    /// `Fifo` may not be supported on all platforms.
+    pub present_mode: PresentMode,
+    /// Maximum frame rate (per window). Default value: none.
+    ///
+    /// If set, redraws (e.g. during animations) are delayed such that each
+    /// window draws at most this many frames per second. This may be used to
+    /// save power, especially with present modes other than `Fifo`.
+    pub max_fps: Option<u32>,
+    /// Directory of shader overrides. Default value: none.
+    ///
+    /// If set, WGSL shaders found in this directory replace the embedded
+    /// shaders of the same name (e.g. `blit.frag.wgsl`), allowing shaders to
+    /// be tweaked without rebuilding. The directory is watched and shaders are
+    /// reloaded on change. Files which are missing or fail to compile are
+    /// ignored (with a warning).
+    pub shader_dir: Option<PathBuf>,
+    /// Name of the application's desktop entry. Default value: none.
+    ///
+    /// On Linux, taskbar progress, badges and attention requests apply to the
+    /// launcher of this desktop entry (e.g. `org.example.App`, identifying
+    /// `org.example.App.desktop`). If none, the executable's name is used.
+    pub desktop_entry: Option<String>,
 }
 
 impl Default for Options {
@@ -23,10 +117,47 @@ impl Default for Options {
         Options {
             power_preference: PowerPreference::LowPower,
             backends: BackendBit::PRIMARY,
+            backend_fallback: true,
+            draw_capture: None,
+            colour_filter: ColourFilter::None,
+            input: Default::default(),
+            reduce_motion: false,
+            battery_saver: false,
+            warm_up: false,
+            dark_mode: None,
+            ui_scale: 1.0,
+            theme_file: None,
+            msaa_samples: 1,
+            present_mode: PresentMode::Fifo,
+            max_fps: None,
+            shader_dir: None,
+            desktop_entry: None,
         }
     }
 }
 
+impl Hash for Options {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.power_preference.hash(state);
+        self.backends.hash(state);
+        self.backend_fallback.hash(state);
+        self.draw_capture.hash(state);
+        self.colour_filter.hash(state);
+        self.input.hash(state);
+        self.reduce_motion.hash(state);
+        self.battery_saver.hash(state);
+        self.warm_up.hash(state);
+        self.dark_mode.hash(state);
+        self.ui_scale.to_bits().hash(state);
+        self.theme_file.hash(state);
+        self.msaa_samples.hash(state);
+        self.present_mode.hash(state);
+        self.max_fps.hash(state);
+        self.shader_dir.hash(state);
+        self.desktop_entry.hash(state);
+    }
+}
+
 impl Options {
     /// Construct a new instance, reading from environment variables
     ///
@@ -51,6 +182,81 @@ impl Options {
     /// -   `DX12`
     /// -   `PRIMARY`: any of Vulkan, Metal or DX12
     /// -   `SECONDARY`: any of GL or DX11
+    ///
+    /// ### Backend fallback
+    ///
+    /// The `KAS_BACKEND_FALLBACK` variable enables fallback to other backends
+    /// (see [`Options::backend_fallback`]) when set to `1` or `true`, or
+    /// disables it when set to `0` or `false`.
+    ///
+    /// ### Reduce motion
+    ///
+    /// The `KAS_REDUCE_MOTION` variable disables theme animations when set to
+    /// `1` or `true`, or enables these when set to `0` or `false`.
+    ///
+    /// ### Battery saver
+    ///
+    /// The `KAS_BATTERY_SAVER` variable disables theme animations while on
+    /// battery power when set to `1` or `true`, or disables this behaviour
+    /// when set to `0` or `false`.
+    ///
+    /// ### Warm up
+    ///
+    /// The `KAS_WARM_UP` variable enables the warm-up step (see
+    /// [`Options::warm_up`]) when set to `1` or `true`, or disables it when
+    /// set to `0` or `false`.
+    ///
+    /// ### Dark mode
+    ///
+    /// The `KAS_DARK_MODE` variable overrides the system's dark-mode
+    /// preference (see [`Options::dark_mode`]): dark when set to `1` or `true`,
+    /// light when set to `0` or `false`.
+    ///
+    /// ### UI scale
+    ///
+    /// The `KAS_UI_SCALE` variable sets [`Options::ui_scale`], e.g. `1.25`.
+    ///
+    /// ### Anti-aliasing
+    ///
+    /// The `KAS_MSAA` variable sets [`Options::msaa_samples`], e.g. `4`.
+    ///
+    /// ### Present mode
+    ///
+    /// The `KAS_PRESENT_MODE` variable supports:
+    ///
+    /// -   `Fifo`
+    /// -   `Mailbox`
+    /// -   `Immediate`
+    ///
+    /// ### Frame rate
+    ///
+    /// The `KAS_MAX_FPS` variable sets [`Options::max_fps`], e.g. `30`.
+    ///
+    /// ### Theme file
+    ///
+    /// The `KAS_THEME_FILE` variable may specify a file path (see
+    /// [`Options::theme_file`]).
+    ///
+    /// ### Shader directory
+    ///
+    /// The `KAS_SHADER_DIR` variable may specify a directory path (see
+    /// [`Options::shader_dir`]).
+    ///
+    /// ### Draw capture
+    ///
+    /// The `KAS_DRAW_CAPTURE` variable may specify a file path. If set, the
+    /// high-level draw commands of the first frame drawn are recorded (see
+    /// [`kas::draw::DrawRecorder`]) and written to this path, as SVG if the
+    /// path ends with `.svg`, otherwise as JSON. This is a debugging tool.
+    ///
+    /// ### Colour filter
+    ///
+    /// The `KAS_COLOUR_FILTER` variable supports:
+    ///
+    /// -   `None`
+    /// -   `Protanopia`
+    /// -   `Deuteranopia`
+    /// -   `Tritanopia`
     pub fn from_env() -> Self {
         let mut options = Options::default();
 
@@ -87,6 +293,129 @@ impl Options {
             }
         }
 
+        if let Ok(mut v) = var("KAS_BACKEND_FALLBACK") {
+            v.make_ascii_uppercase();
+            options.backend_fallback = match v.as_str() {
+                "1" | "TRUE" => true,
+                "0" | "FALSE" => false,
+                other => {
+                    warn!(
+                        "Unexpected environment value: KAS_BACKEND_FALLBACK={}",
+                        other
+                    );
+                    options.backend_fallback
+                }
+            }
+        }
+
+        if let Ok(mut v) = var("KAS_COLOUR_FILTER") {
+            v.make_ascii_uppercase();
+            options.colour_filter = match v.as_str() {
+                "NONE" => ColourFilter::None,
+                "PROTANOPIA" => ColourFilter::Protanopia,
+                "DEUTERANOPIA" => ColourFilter::Deuteranopia,
+                "TRITANOPIA" => ColourFilter::Tritanopia,
+                other => {
+                    warn!("Unexpected environment value: KAS_COLOUR_FILTER={}", other);
+                    options.colour_filter
+                }
+            }
+        }
+
+        if let Ok(mut v) = var("KAS_REDUCE_MOTION") {
+            v.make_ascii_uppercase();
+            options.reduce_motion = match v.as_str() {
+                "1" | "TRUE" => true,
+                "0" | "FALSE" => false,
+                other => {
+                    warn!("Unexpected environment value: KAS_REDUCE_MOTION={}", other);
+                    options.reduce_motion
+                }
+            }
+        }
+
+        if let Ok(mut v) = var("KAS_BATTERY_SAVER") {
+            v.make_ascii_uppercase();
+            options.battery_saver = match v.as_str() {
+                "1" | "TRUE" => true,
+                "0" | "FALSE" => false,
+                other => {
+                    warn!("Unexpected environment value: KAS_BATTERY_SAVER={}", other);
+                    options.battery_saver
+                }
+            }
+        }
+
+        if let Ok(mut v) = var("KAS_WARM_UP") {
+            v.make_ascii_uppercase();
+            options.warm_up = match v.as_str() {
+                "1" | "TRUE" => true,
+                "0" | "FALSE" => false,
+                other => {
+                    warn!("Unexpected environment value: KAS_WARM_UP={}", other);
+                    options.warm_up
+                }
+            }
+        }
+
+        if let Ok(mut v) = var("KAS_DARK_MODE") {
+            v.make_ascii_uppercase();
+            options.dark_mode = match v.as_str() {
+                "1" | "TRUE" => Some(true),
+                "0" | "FALSE" => Some(false),
+                other => {
+                    warn!("Unexpected environment value: KAS_DARK_MODE={}", other);
+                    options.dark_mode
+                }
+            }
+        }
+
+        if let Ok(v) = var("KAS_UI_SCALE") {
+            match v.parse::<f32>() {
+                Ok(scale) if scale > 0.0 => options.ui_scale = scale,
+                _ => warn!("Unexpected environment value: KAS_UI_SCALE={}", v),
+            }
+        }
+
+        if let Ok(v) = var("KAS_MSAA") {
+            match v.parse::<u32>() {
+                Ok(n) if is_valid_sample_count(n) => options.msaa_samples = n,
+                _ => warn!("Unexpected environment value: KAS_MSAA={}", v),
+            }
+        }
+
+        if let Ok(mut v) = var("KAS_PRESENT_MODE") {
+            v.make_ascii_uppercase();
+            options.present_mode = match v.as_str() {
+                "FIFO" => PresentMode::Fifo,
+                "MAILBOX" => PresentMode::Mailbox,
+                "IMMEDIATE" => PresentMode::Immediate,
+                other => {
+                    warn!("Unexpected environment value: KAS_PRESENT_MODE={}", other);
+                    options.present_mode
+                }
+            }
+        }
+
+        if let Ok(v) = var("KAS_MAX_FPS") {
+            match v.parse::<u32>() {
+                Ok(fps) if fps > 0 => options.max_fps = Some(fps),
+                _ => warn!("Unexpected environment value: KAS_MAX_FPS={}", v),
+            }
+        }
+
+        if let Some(path) = std::env::var_os("KAS_THEME_FILE") {
+            options.theme_file = Some(path.into());
+        }
+
+        if let Some(path) = std::env::var_os("KAS_SHADER_DIR") {
+            options.shader_dir = Some(path.into());
+        }
+
+        if let Some(path) = std::env::var_os("KAS_DRAW_CAPTURE") {
+            options.draw_capture = Some(path.into());
+        }
+
         options
     }
 
@@ -97,7 +426,42 @@ impl Options {
         }
     }
 
-    pub(crate) fn backend(&self) -> BackendBit {
-        self.backends
+    /// Backend sets to try, in order
+    pub(crate) fn backend_candidates(&self) -> Vec<BackendBit> {
+        let mut candidates = vec![self.backends];
+        if self.backend_fallback {
+            let fallbacks = [
+                BackendBit::VULKAN,
+                BackendBit::METAL,
+                BackendBit::DX12,
+                BackendBit::DX11,
+            ];
+            for backend in fallbacks.iter() {
+                if !self.backends.contains(*backend) {
+                    candidates.push(*backend);
+                }
+            }
+        }
+        candidates
+    }
+
+    /// Minimum time between frames, if limited
+    pub(crate) fn frame_interval(&self) -> Option<Duration> {
+        self.max_fps
+            .filter(|fps| *fps > 0)
+            .map(|fps| Duration::from_secs(1) / fps)
     }
+
+    pub(crate) fn msaa_samples(&self) -> u32 {
+        if is_valid_sample_count(self.msaa_samples) {
+            self.msaa_samples
+        } else {
+            warn!("Unsupported MSAA sample count: {}", self.msaa_samples);
+            1
+        }
+    }
+}
+
+fn is_valid_sample_count(n: u32) -> bool {
+    matches!(n, 1 | 2 | 4 | 8)
 }
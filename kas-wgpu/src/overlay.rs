@@ -0,0 +1,488 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Rendering within an external `wgpu` application
+
+use instant::Instant;
+use log::{debug, trace};
+use std::num::NonZeroU32;
+
+use kas::draw::SizeHandle;
+use kas::event::{CursorIcon, DragData, ManagerState, UpdateHandle};
+use kas::geom::{Coord, Rect, Size};
+use kas::layout::SolveCache;
+use kas::{Capture, ThemeAction, ThemeApi, TkAction, WindowId};
+use kas_theme::{Theme, Window as _};
+use winit::event::WindowEvent;
+
+use crate::draw::{CustomPipe, CustomPipeBuilder, DrawPipe, DrawWindow, ShaderManager};
+
+/// A UI rendered via an existing `wgpu` device
+///
+/// This allows embedding a KAS UI within an existing `wgpu` application or
+/// game engine, for example as an overlay above the application's own scene.
+/// The application retains ownership of the [`wgpu::Device`], [`wgpu::Queue`]
+/// and render target: it forwards window events to [`Overlay::handle_event`],
+/// calls [`Overlay::update`] after each batch of events and
+/// [`Overlay::render`] whenever it draws a frame.
+///
+/// Since the application owns the window, some toolkit functionality is
+/// unavailable: new windows may not be opened, while the clipboard, cursor
+/// grabs and drag-and-drop are unsupported. Pop-ups are supported. The
+/// cursor icon requested by widgets is available from
+/// [`Overlay::cursor_icon`]. Multi-sampling is not used.
+pub struct Overlay<C: CustomPipe, T: Theme<DrawPipe<C>>>
+where
+    T::Window: kas_theme::Window,
+{
+    draw: DrawPipe<C>,
+    theme: T,
+    widget: Box<dyn kas::Window>,
+    mgr: ManagerState,
+    solve_cache: SolveCache,
+    window: DrawWindow<C::Window>,
+    theme_window: T::Window,
+    state: State,
+    size: Size,
+    scale_factor: f64,
+}
+
+impl<C: CustomPipe, T: Theme<DrawPipe<C>>> Overlay<C, T>
+where
+    T::Window: kas_theme::Window,
+{
+    /// The texture format required of render targets
+    pub const FORMAT: wgpu::TextureFormat = crate::draw::TEX_FORMAT;
+
+    /// Construct
+    ///
+    /// The `custom` parameter accepts a custom draw pipe (see
+    /// [`CustomPipeBuilder`]); pass `()` if you don't have one. The overlay
+    /// covers a target of the given `size` (in physical pixels), with
+    /// `scale_factor` the DPI factor of the application's window.
+    pub fn new<CB: CustomPipeBuilder<Pipe = C>>(
+        custom: CB,
+        mut theme: T,
+        mut widget: Box<dyn kas::Window>,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        size: Size,
+        scale_factor: f64,
+    ) -> Self {
+        let shaders = ShaderManager::new(device, None);
+        let mut draw = DrawPipe::new(custom, device, &shaders, 1);
+        theme.init(&mut draw);
+
+        let mut window = draw.new_window(device, Size::ZERO);
+        let buf = draw.resize(&mut window, device, size);
+        queue.submit(std::iter::once(buf));
+
+        let mut theme_window = theme.new_window(&mut window, scale_factor as f32);
+        let mut size_handle = unsafe { theme_window.size_handle() };
+        let solve_cache = SolveCache::find_constraints(widget.as_widget_mut(), &mut size_handle);
+        drop(size_handle);
+
+        let mut state = State::new();
+        let mut mgr = ManagerState::new(scale_factor);
+        let mut tkw = TkOverlay::new(&mut theme, &mut theme_window, &mut state);
+        mgr.configure(&mut tkw, &mut *widget);
+
+        let mut overlay = Overlay {
+            draw,
+            theme,
+            widget,
+            mgr,
+            solve_cache,
+            window,
+            theme_window,
+            state,
+            size,
+            scale_factor,
+        };
+        overlay.apply_size();
+        overlay
+    }
+
+    /// Access the theme
+    ///
+    /// After making changes, call [`Overlay::theme_resize`] or
+    /// [`Overlay::request_redraw`] as appropriate.
+    pub fn theme_mut(&mut self) -> &mut T {
+        &mut self.theme
+    }
+
+    /// Resize the render target
+    pub fn resize(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, size: Size) {
+        if size == self.size {
+            return;
+        }
+        let buf = self.draw.resize(&mut self.window, device, size);
+        queue.submit(std::iter::once(buf));
+        self.size = size;
+        self.apply_size();
+    }
+
+    /// Set the DPI factor of the application's window
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+        self.theme_resize();
+    }
+
+    /// Recompute sizes after a change to the theme or scale factor
+    pub fn theme_resize(&mut self) {
+        debug!("Overlay::theme_resize");
+        let scale_factor = self.scale_factor * self.state.ui_scale as f64;
+        self.theme
+            .update_window(&mut self.theme_window, scale_factor as f32);
+        self.mgr.set_dpi_factor(scale_factor);
+        self.solve_cache.invalidate_rule_cache();
+        self.apply_size();
+    }
+
+    /// Handle an input event
+    ///
+    /// Event positions are relative to the render target. Resize and scale
+    /// factor events are ignored; use [`Overlay::resize`] and
+    /// [`Overlay::set_scale_factor`] instead.
+    pub fn handle_event(&mut self, event: WindowEvent) {
+        match event {
+            WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => (),
+            event => {
+                let mut tkw =
+                    TkOverlay::new(&mut self.theme, &mut self.theme_window, &mut self.state);
+                let widget = &mut *self.widget;
+                self.mgr.with(&mut tkw, |mgr| {
+                    mgr.handle_winit(widget, event);
+                });
+            }
+        }
+    }
+
+    /// Update, after receiving all events
+    ///
+    /// Returns the time of the next scheduled update, if any.
+    pub fn update(&mut self) -> Option<Instant> {
+        self.handle_pending();
+
+        let mut tkw = TkOverlay::new(&mut self.theme, &mut self.theme_window, &mut self.state);
+        let action = self.mgr.update(&mut tkw, &mut *self.widget);
+        let relayout = self.mgr.take_relayout();
+
+        match action {
+            TkAction::None => (),
+            TkAction::Redraw => self.state.redraw = true,
+            TkAction::RegionMoved => {
+                self.mgr.region_moved(&mut tkw, &mut *self.widget);
+                self.state.redraw = true;
+            }
+            TkAction::Popup => {
+                let mut size_handle = unsafe { self.theme_window.size_handle() };
+                self.widget.resize_popups(&mut size_handle);
+                drop(size_handle);
+
+                let mut tkw =
+                    TkOverlay::new(&mut self.theme, &mut self.theme_window, &mut self.state);
+                self.mgr.region_moved(&mut tkw, &mut *self.widget);
+                self.state.redraw = true;
+            }
+            TkAction::SetSize => self.apply_size(),
+            TkAction::Relayout => {
+                self.mgr.configure_pending(&mut tkw, &mut *self.widget);
+                for id in relayout {
+                    kas::layout::mark_dirty(self.widget.as_widget_mut(), id);
+                }
+                kas::layout::invalidate_dirty(self.widget.as_widget_mut());
+                self.solve_cache.refresh_dirty_rules();
+                self.apply_size();
+            }
+            TkAction::Resize => {
+                self.solve_cache.invalidate_rule_cache();
+                self.apply_size();
+            }
+            TkAction::Reconfigure => {
+                debug!("Overlay::reconfigure");
+                let mut tkw =
+                    TkOverlay::new(&mut self.theme, &mut self.theme_window, &mut self.state);
+                self.mgr.configure(&mut tkw, &mut *self.widget);
+                self.solve_cache.invalidate_rule_cache();
+                self.apply_size();
+            }
+            TkAction::Close | TkAction::CloseAll => {
+                debug!("Overlay: close requested; ignoring");
+            }
+        }
+
+        self.mgr.next_resume()
+    }
+
+    /// Returns true if the overlay should be redrawn
+    ///
+    /// This is reset by [`Overlay::render`].
+    pub fn needs_redraw(&self) -> bool {
+        self.state.redraw
+    }
+
+    /// Request that the overlay be redrawn
+    pub fn request_redraw(&mut self) {
+        self.state.redraw = true;
+    }
+
+    /// The cursor icon requested by widgets
+    pub fn cursor_icon(&self) -> CursorIcon {
+        self.state.cursor_icon
+    }
+
+    /// Render the UI to `view`
+    ///
+    /// The UI is drawn over the existing content of `view`, which must have
+    /// format [`Overlay::FORMAT`] and the size last passed to
+    /// [`Overlay::resize`]. Commands are submitted to `queue`.
+    pub fn render(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, view: &wgpu::TextureView) {
+        trace!("Overlay::render");
+        self.state.redraw = false;
+
+        let animate = self.theme.animate();
+        if animate {
+            // Cached content uses theme colours
+            self.window.invalidate_caches();
+        }
+        let rect = Rect::new(Coord::ZERO, self.size);
+        let mut draw_handle = unsafe {
+            self.theme
+                .draw_handle(&mut self.window, &mut self.theme_window, rect)
+        };
+        self.widget.draw(&mut draw_handle, &self.mgr, false);
+        drop(draw_handle);
+
+        let frame_requested = self.mgr.take_animation_frame();
+        if self.theme_window.take_redraw() || animate || frame_requested {
+            self.state.redraw = true;
+        }
+
+        let load = wgpu::LoadOp::Load;
+        self.draw
+            .render(&mut self.window, device, queue, view, load);
+
+        let time = Instant::now();
+        let mut tkw = TkOverlay::new(&mut self.theme, &mut self.theme_window, &mut self.state);
+        let widget = &mut *self.widget;
+        self.mgr.with(&mut tkw, |mgr| {
+            widget.handle_frame(mgr, time);
+        });
+        if self.mgr.pending_action() != TkAction::None {
+            self.state.redraw = true;
+        }
+    }
+
+    fn apply_size(&mut self) {
+        let rect = Rect::new(Coord::ZERO, self.size);
+        debug!("Resizing overlay to rect = {:?}", rect);
+
+        let mut size_handle = unsafe { self.theme_window.size_handle() };
+        self.solve_cache
+            .apply_rect(self.widget.as_widget_mut(), &mut size_handle, rect, true);
+        self.widget.resize_popups(&mut size_handle);
+        self.mgr.check_pending(self.widget.as_widget());
+        self.state.redraw = true;
+    }
+
+    /// Handle actions deferred by [`TkOverlay`]
+    fn handle_pending(&mut self) {
+        while !self.state.pending.is_empty() {
+            for pending in std::mem::take(&mut self.state.pending) {
+                let mut tkw =
+                    TkOverlay::new(&mut self.theme, &mut self.theme_window, &mut self.state);
+                let widget = &mut *self.widget;
+                match pending {
+                    Pending::AddPopup(id, popup) => self.mgr.with(&mut tkw, |mut mgr| {
+                        kas::Window::add_popup(widget, &mut mgr, id, popup);
+                    }),
+                    Pending::ClosePopup(id) => self.mgr.with(&mut tkw, |mut mgr| {
+                        widget.remove_popup(&mut mgr, id);
+                    }),
+                    Pending::Update(handle, payload) => self.mgr.with(&mut tkw, |mgr| {
+                        mgr.update_handle(widget, handle, payload);
+                    }),
+                    Pending::EndDrag => self.mgr.with(&mut tkw, |mgr| {
+                        mgr.drag_end(widget, false);
+                    }),
+                    Pending::RedrawAll => {
+                        self.window.invalidate_caches();
+                        self.state.redraw = true;
+                    }
+                    Pending::ThemeResize => self.theme_resize(),
+                }
+            }
+        }
+    }
+}
+
+/// Actions deferred until [`Overlay::update`]
+enum Pending {
+    AddPopup(WindowId, kas::Popup),
+    ClosePopup(WindowId),
+    Update(UpdateHandle, u64),
+    EndDrag,
+    RedrawAll,
+    ThemeResize,
+}
+
+/// State accessible to [`TkOverlay`]
+struct State {
+    pending: Vec<Pending>,
+    popups: Vec<WindowId>,
+    next_id: u32,
+    ui_scale: f32,
+    cursor_icon: CursorIcon,
+    redraw: bool,
+}
+
+impl State {
+    fn new() -> Self {
+        State {
+            pending: vec![],
+            popups: vec![],
+            next_id: 0,
+            ui_scale: 1.0,
+            cursor_icon: CursorIcon::Default,
+            redraw: true,
+        }
+    }
+}
+
+struct TkOverlay<'a, T, TW> {
+    theme: &'a mut T,
+    theme_window: &'a mut TW,
+    state: &'a mut State,
+}
+
+impl<'a, T, TW> TkOverlay<'a, T, TW> {
+    fn new(theme: &'a mut T, theme_window: &'a mut TW, state: &'a mut State) -> Self {
+        TkOverlay {
+            theme,
+            theme_window,
+            state,
+        }
+    }
+}
+
+impl<'a, T: ThemeApi, TW: kas_theme::Window> kas::TkWindow for TkOverlay<'a, T, TW> {
+    fn add_popup(&mut self, popup: kas::Popup) -> WindowId {
+        self.state.next_id += 1;
+        let id = WindowId::new(NonZeroU32::new(self.state.next_id).unwrap());
+        self.state.popups.push(id);
+        self.state.pending.push(Pending::AddPopup(id, popup));
+        id
+    }
+
+    fn add_window(&mut self, _: Box<dyn kas::Window>) -> WindowId {
+        // We cannot fail here; the returned identifier is never used
+        debug!("TkOverlay::add_window: unsupported");
+        self.state.next_id += 1;
+        WindowId::new(NonZeroU32::new(self.state.next_id).unwrap())
+    }
+
+    fn close_window(&mut self, id: WindowId) {
+        if let Some(index) = self.state.popups.iter().position(|p| *p == id) {
+            self.state.popups.remove(index);
+            self.state.pending.push(Pending::ClosePopup(id));
+        } else {
+            debug!("TkOverlay::close_window({:?}): unsupported", id);
+        }
+    }
+
+    fn trigger_update(&mut self, handle: UpdateHandle, payload: u64) {
+        self.state.pending.push(Pending::Update(handle, payload));
+    }
+
+    fn get_clipboard(&mut self) -> Option<String> {
+        None
+    }
+
+    fn set_clipboard<'c>(&mut self, _: std::borrow::Cow<'c, str>) {
+        debug!("TkOverlay::set_clipboard: unsupported");
+    }
+
+    fn get_primary(&mut self) -> Option<String> {
+        None
+    }
+
+    fn set_primary<'c>(&mut self, _: std::borrow::Cow<'c, str>) {}
+
+    fn set_taskbar_progress(&mut self, progress: Option<f32>) {
+        debug!(
+            "TkOverlay::set_taskbar_progress({:?}): unsupported",
+            progress
+        );
+    }
+
+    fn set_taskbar_badge(&mut self, count: Option<u32>) {
+        debug!("TkOverlay::set_taskbar_badge({:?}): unsupported", count);
+    }
+
+    fn request_attention(&mut self, critical: bool) {
+        debug!("TkOverlay::request_attention({}): unsupported", critical);
+    }
+
+    fn inhibit_screensaver(&mut self, inhibit: bool) {
+        debug!("TkOverlay::inhibit_screensaver({}): unsupported", inhibit);
+    }
+
+    fn capture(&mut self, _: Box<dyn FnOnce(Capture)>) {
+        debug!("TkOverlay::capture: unsupported");
+    }
+
+    #[inline]
+    fn ui_scale(&self) -> f32 {
+        self.state.ui_scale
+    }
+
+    fn set_ui_scale(&mut self, scale: f32) {
+        let scale = scale.max(0.25).min(4.0);
+        if scale != self.state.ui_scale {
+            self.state.ui_scale = scale;
+            self.state.pending.push(Pending::ThemeResize);
+        }
+    }
+
+    fn adjust_theme(&mut self, f: &mut dyn FnMut(&mut dyn ThemeApi) -> ThemeAction) {
+        match f(&mut *self.theme) {
+            ThemeAction::None => (),
+            ThemeAction::RedrawAll => self.state.pending.push(Pending::RedrawAll),
+            ThemeAction::ThemeResize => self.state.pending.push(Pending::ThemeResize),
+        }
+    }
+
+    fn size_handle(&mut self, f: &mut dyn FnMut(&mut dyn SizeHandle)) {
+        let mut size_handle = unsafe { self.theme_window.size_handle() };
+        f(&mut size_handle);
+    }
+
+    #[inline]
+    fn set_cursor_icon(&mut self, icon: CursorIcon) {
+        self.state.cursor_icon = icon;
+    }
+
+    fn set_cursor_grab(&mut self, _: bool) -> bool {
+        false
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) {
+        debug!("TkOverlay::set_cursor_visible({}): unsupported", visible);
+    }
+
+    fn set_cursor_position(&mut self, coord: Coord) {
+        debug!("TkOverlay::set_cursor_position({:?}): unsupported", coord);
+    }
+
+    fn start_drag(&mut self, _: DragData) {
+        debug!("TkOverlay::start_drag: unsupported");
+    }
+
+    fn end_drag(&mut self, _: bool) {
+        // The drop is never accepted, but the source expects notification
+        self.state.pending.push(Pending::EndDrag);
+    }
+}
@@ -0,0 +1,85 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Power status
+
+use kas::event::PowerStatus;
+use std::time::Duration;
+
+/// Interval between polls of the power status
+pub const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Read the power status
+///
+/// Returns `None` where unsupported (including where no power supply is
+/// reported, e.g. on a desktop).
+#[cfg(target_os = "linux")]
+pub fn read_status() -> Option<PowerStatus> {
+    use kas::event::PowerSource;
+    use std::fs::{read_dir, read_to_string};
+    use std::path::Path;
+
+    fn read(path: &Path, name: &str) -> Option<String> {
+        read_to_string(path.join(name))
+            .ok()
+            .map(|s| s.trim().to_string())
+    }
+
+    let mut online = None;
+    let mut batteries = 0;
+    let mut discharging = false;
+    let (mut capacity, mut capacity_n) = (0.0, 0);
+
+    for entry in read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let path = entry.path();
+        match read(&path, "type").as_deref() {
+            Some("Mains") | Some("USB") => {
+                let is_online = read(&path, "online").as_deref() == Some("1");
+                online = Some(online.unwrap_or(false) || is_online);
+            }
+            Some("Battery") => {
+                // Ignore batteries of peripherals (e.g. a wireless mouse)
+                if read(&path, "scope").as_deref() == Some("Device") {
+                    continue;
+                }
+                batteries += 1;
+                discharging |= read(&path, "status").as_deref() == Some("Discharging");
+                if let Some(c) = read(&path, "capacity").and_then(|s| s.parse::<f32>().ok()) {
+                    capacity += c;
+                    capacity_n += 1;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let source = if batteries > 0 {
+        if discharging || online == Some(false) {
+            PowerSource::Battery
+        } else {
+            PowerSource::External
+        }
+    } else if online.is_some() {
+        PowerSource::External
+    } else {
+        return None;
+    };
+
+    let battery = if capacity_n > 0 {
+        Some((capacity / (100.0 * capacity_n as f32)).max(0.0).min(1.0))
+    } else {
+        None
+    };
+
+    Some(PowerStatus { source, battery })
+}
+
+/// Read the power status
+///
+/// Returns `None` where unsupported.
+#[cfg(not(target_os = "linux"))]
+pub fn read_status() -> Option<PowerStatus> {
+    None
+}
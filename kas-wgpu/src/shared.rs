@@ -7,20 +7,46 @@
 
 use log::{info, warn};
 use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::time::Duration;
 
-use crate::draw::{CustomPipe, CustomPipeBuilder, DrawPipe, DrawWindow, ShaderManager};
+use crate::draw::{
+    ColourFilter, CustomPipe, CustomPipeBuilder, DrawPipe, DrawWindow, ShaderManager, TEX_FORMAT,
+};
+use crate::taskbar::Taskbar;
 use crate::{Error, Options, WindowId};
-use kas::event::UpdateHandle;
+use kas::event::{DragData, InputConfig, KeyLayout, PowerStatus, UpdateHandle};
+use kas::geom::Size;
+use kas::{Capture, ThemeAction};
 use kas_theme::Theme;
 
-#[cfg(feature = "clipboard")]
+#[cfg(all(feature = "clipboard", not(target_arch = "wasm32")))]
 use clipboard::{ClipboardContext, ClipboardProvider};
 
+// The primary selection is only supported (by the clipboard crate) on X11
+#[cfg(all(
+    feature = "clipboard",
+    unix,
+    not(any(target_os = "macos", target_os = "android"))
+))]
+use clipboard::x11_clipboard::{Primary, X11ClipboardContext};
+
 /// State shared between windows
 pub struct SharedState<C: CustomPipe, T> {
-    #[cfg(feature = "clipboard")]
+    #[cfg(all(feature = "clipboard", not(target_arch = "wasm32")))]
     clipboard: Option<ClipboardContext>,
+    #[cfg(target_arch = "wasm32")]
+    web_clipboard: crate::web::Clipboard,
+    #[cfg(all(
+        feature = "clipboard",
+        unix,
+        not(any(target_os = "macos", target_os = "android"))
+    ))]
+    primary: Option<X11ClipboardContext<Primary>>,
     pub instance: wgpu::Instance,
+    pub taskbar: Taskbar,
+    /// Information on the graphics adapter in use
+    pub adapter_info: wgpu::AdapterInfo,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub shaders: ShaderManager,
@@ -30,6 +56,30 @@ pub struct SharedState<C: CustomPipe, T> {
     /// Newly created windows need to know the scale_factor *before* they are
     /// created. This is used to estimate ideal window size.
     pub scale_factor: f64,
+    /// Path to which the next frame's draw commands are written, if any
+    pub draw_capture: Option<PathBuf>,
+    /// Colour-blindness simulation filter, applied to all windows
+    pub colour_filter: ColourFilter,
+    pub input_config: InputConfig,
+    /// The active drag, if any
+    pub drag: Option<DragState>,
+    /// The last-read power status
+    pub power: PowerStatus,
+    /// Warm up draw pipelines and glyph cache for new windows
+    pub warm_up: bool,
+    pub present_mode: wgpu::PresentMode,
+    /// Minimum time between frames, if limited
+    pub frame_interval: Option<Duration>,
+    /// Application-level UI scale, multiplying the DPI factor
+    ui_scale: f32,
+    reduce_motion: bool,
+    battery_saver: bool,
+    /// The dark-mode preference last passed to the theme, if any
+    dark_mode: Option<bool>,
+    /// If true, system dark-mode preference changes are ignored
+    dark_mode_override: bool,
+    #[cfg(feature = "theme_file")]
+    theme_watcher: Option<crate::theme_file::ThemeWatcher>,
     window_id: u32,
 }
 
@@ -38,13 +88,28 @@ where
     T::Window: kas_theme::Window,
 {
     /// Construct
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn new<CB: CustomPipeBuilder<Pipe = C>>(
+        custom: CB,
+        theme: T,
+        options: Options,
+        scale_factor: f64,
+    ) -> Result<Self, Error> {
+        let fut = Self::new_async(custom, theme, options, scale_factor);
+        futures::executor::block_on(fut)
+    }
+
+    /// Construct asynchronously
+    ///
+    /// Within the browser, graphics adapter and device requests may only be
+    /// awaited, not blocked on.
+    pub async fn new_async<CB: CustomPipeBuilder<Pipe = C>>(
         custom: CB,
         mut theme: T,
         options: Options,
         scale_factor: f64,
     ) -> Result<Self, Error> {
-        #[cfg(feature = "clipboard")]
+        #[cfg(all(feature = "clipboard", not(target_arch = "wasm32")))]
         let clipboard = match ClipboardContext::new() {
             Ok(cb) => Some(cb),
             Err(e) => {
@@ -53,32 +118,79 @@ where
             }
         };
 
-        let instance = wgpu::Instance::new(options.backend());
-        let adapter_options = options.adapter_options();
-        let req = instance.request_adapter(&adapter_options);
-        let adapter = match futures::executor::block_on(req) {
-            Some(a) => a,
-            None => return Err(Error::NoAdapter),
+        #[cfg(all(
+            feature = "clipboard",
+            unix,
+            not(any(target_os = "macos", target_os = "android"))
+        ))]
+        let primary = match X11ClipboardContext::new() {
+            Ok(cb) => Some(cb),
+            Err(e) => {
+                warn!("Unable to open primary selection: {:?}", e);
+                None
+            }
         };
-        info!("Using graphics adapter: {}", adapter.get_info().name);
 
+        let adapter_options = options.adapter_options();
         let desc = wgpu::DeviceDescriptor {
             features: Default::default(),
             limits: Default::default(),
             shader_validation: true,
         };
-        let req = adapter.request_device(&desc, None);
-        let (device, queue) = futures::executor::block_on(req)?;
+        let mut result = None;
+        for backends in options.backend_candidates() {
+            let instance = wgpu::Instance::new(backends);
+            let adapter = match instance.request_adapter(&adapter_options).await {
+                Some(a) => a,
+                None => {
+                    info!("No graphics adapter found for backends {:?}", backends);
+                    continue;
+                }
+            };
+            let adapter_info = adapter.get_info();
+            match adapter.request_device(&desc, None).await {
+                Ok((device, queue)) => {
+                    result = Some((instance, adapter_info, device, queue));
+                    break;
+                }
+                Err(e) => warn!(
+                    "Unable to open graphics adapter {}: {:?}",
+                    adapter_info.name, e
+                ),
+            }
+        }
+        let (instance, adapter_info, device, queue) = result.ok_or(Error::NoAdapter)?;
+        if !options.backends.contains(adapter_info.backend.into()) {
+            warn!("Preferred graphics backends unavailable; falling back");
+        }
+        info!(
+            "Using graphics adapter: {} ({:?}, {:?})",
+            adapter_info.name, adapter_info.backend, adapter_info.device_type
+        );
 
-        let shaders = ShaderManager::new(&device);
-        let mut draw = DrawPipe::new(custom, &device, &shaders);
+        let shaders = ShaderManager::new(&device, options.shader_dir.as_deref());
+        let mut draw = DrawPipe::new(custom, &device, &shaders, options.msaa_samples());
 
         theme.init(&mut draw);
+        if let Some(dark) = options.dark_mode {
+            theme.set_dark_mode(dark);
+        }
 
-        Ok(SharedState {
-            #[cfg(feature = "clipboard")]
+        let frame_interval = options.frame_interval();
+        let mut shared = SharedState {
+            #[cfg(all(feature = "clipboard", not(target_arch = "wasm32")))]
             clipboard,
+            #[cfg(target_arch = "wasm32")]
+            web_clipboard: Default::default(),
+            #[cfg(all(
+                feature = "clipboard",
+                unix,
+                not(any(target_os = "macos", target_os = "android"))
+            ))]
+            primary,
             instance,
+            taskbar: Taskbar::new(options.desktop_entry.as_deref()),
+            adapter_info,
             device,
             queue,
             shaders,
@@ -86,8 +198,129 @@ where
             theme,
             pending: vec![],
             scale_factor,
+            draw_capture: options.draw_capture,
+            colour_filter: options.colour_filter,
+            input_config: options.input,
+            drag: None,
+            power: Default::default(),
+            warm_up: options.warm_up,
+            present_mode: options.present_mode,
+            frame_interval,
+            ui_scale: options.ui_scale,
+            reduce_motion: options.reduce_motion,
+            battery_saver: options.battery_saver,
+            dark_mode: options.dark_mode,
+            dark_mode_override: options.dark_mode.is_some(),
+            #[cfg(feature = "theme_file")]
+            theme_watcher: options.theme_file.map(crate::theme_file::ThemeWatcher::new),
             window_id: 0,
-        })
+        };
+
+        #[cfg(not(feature = "theme_file"))]
+        if options.theme_file.is_some() {
+            warn!("Options::theme_file requires feature theme_file");
+        }
+        shared.poll_theme_file();
+
+        if let Some(status) = crate::power::read_status() {
+            shared.set_power(status);
+        }
+        shared.update_key_layout();
+
+        Ok(shared)
+    }
+
+    /// Re-query the platform's keyboard layout, where supported
+    pub fn update_key_layout(&mut self) {
+        if crate::keyboard::query_layout() {
+            let handle = KeyLayout::update_handle();
+            self.pending.push(PendingAction::Update(handle, 0));
+        }
+    }
+
+    /// Set the power status
+    ///
+    /// When the battery saver is enabled, this also toggles theme animations.
+    pub fn set_power(&mut self, status: PowerStatus) {
+        self.power = status;
+        if self.battery_saver {
+            kas_theme::set_reduce_motion(self.reduce_motion || status.on_battery());
+        }
+    }
+
+    /// Set the system's dark-mode preference
+    ///
+    /// This is ignored when overridden via [`Options::dark_mode`]. Otherwise,
+    /// the theme is notified when the preference changes.
+    pub fn set_dark_mode(&mut self, dark: bool) {
+        if self.dark_mode_override || self.dark_mode == Some(dark) {
+            return;
+        }
+        self.dark_mode = Some(dark);
+        let action = self.theme.set_dark_mode(dark);
+        self.theme_action(action);
+    }
+
+    fn theme_action(&mut self, action: ThemeAction) {
+        match action {
+            ThemeAction::None => (),
+            ThemeAction::RedrawAll => self.pending.push(PendingAction::RedrawAll),
+            ThemeAction::ThemeResize => self.pending.push(PendingAction::ThemeResize),
+        }
+    }
+
+    /// True if a theme file is watched (see [`Options::theme_file`])
+    #[cfg(feature = "theme_file")]
+    #[inline]
+    pub fn watches_theme_file(&self) -> bool {
+        self.theme_watcher.is_some()
+    }
+
+    /// Reload the theme file if modified, updating the theme
+    pub fn poll_theme_file(&mut self) {
+        #[cfg(feature = "theme_file")]
+        {
+            let config = self.theme_watcher.as_mut().and_then(|w| w.poll());
+            if let Some(config) = config {
+                let action = self.theme.apply_config(&config);
+                self.theme_action(action);
+            }
+        }
+    }
+
+    /// Reload shaders if modified (see [`Options::shader_dir`])
+    pub fn poll_shaders(&mut self) {
+        if self.shaders.poll(&self.device) {
+            self.draw.reload_shaders(&self.device, &self.shaders);
+            self.pending.push(PendingAction::RedrawAll);
+        }
+    }
+
+    /// Get the application-level UI scale factor
+    #[inline]
+    pub fn ui_scale(&self) -> f32 {
+        self.ui_scale
+    }
+
+    /// Get the scale factor used by themes and event handling
+    ///
+    /// This is the window's DPI factor multiplied by the UI scale factor.
+    #[inline]
+    pub fn effective_scale(&self, dpi_factor: f64) -> f64 {
+        dpi_factor * self.ui_scale as f64
+    }
+
+    /// Set the application-level UI scale factor
+    ///
+    /// The value is clamped to the range `0.25..=4.0`. On change, all windows
+    /// are resized.
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        let scale = scale.max(0.25).min(4.0);
+        if scale != self.ui_scale {
+            info!("UI scale: {}%", (scale * 100.0).round());
+            self.ui_scale = scale;
+            self.pending.push(PendingAction::ThemeResize);
+        }
     }
 
     pub fn next_window_id(&mut self) -> WindowId {
@@ -101,22 +334,119 @@ where
         frame_view: &wgpu::TextureView,
         clear_color: wgpu::Color,
     ) {
-        self.draw.render(
-            window,
-            &mut self.device,
-            &mut self.queue,
-            frame_view,
-            clear_color,
+        let load = wgpu::LoadOp::Clear(clear_color);
+        self.draw
+            .render(window, &self.device, &self.queue, frame_view, load);
+    }
+
+    /// Render to an off-screen texture of the given `size` and read it back
+    ///
+    /// The result is passed to `f`, except on zero size or if reading back
+    /// fails. Natively this happens before returning; within the browser,
+    /// reading back may only be awaited, thus `f` is called later.
+    pub fn render_capture(
+        &mut self,
+        window: &mut DrawWindow<C::Window>,
+        size: Size,
+        clear_color: wgpu::Color,
+        f: Box<dyn FnOnce(Capture)>,
+    ) {
+        if size.0 * size.1 == 0 {
+            return;
+        }
+
+        let extent = wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("capture"),
+            size: extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TEX_FORMAT,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+        });
+        let view = texture.create_view(&Default::default());
+        self.render(window, &view, clear_color);
+
+        // Rows of the read-back buffer must be aligned
+        let row = 4 * size.0;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = (row + align - 1) / align * align;
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("capture buffer"),
+            size: padded_row as u64 * size.1 as u64,
+            usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("capture"),
+            });
+        encoder.copy_texture_to_buffer(
+            wgpu::TextureCopyView {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            wgpu::BufferCopyView {
+                buffer: &buffer,
+                layout: wgpu::TextureDataLayout {
+                    offset: 0,
+                    bytes_per_row: padded_row,
+                    rows_per_image: size.1,
+                },
+            },
+            extent,
         );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let mapping = buffer.slice(..).map_async(wgpu::MapMode::Read);
+        let read_back = async move {
+            if let Err(e) = mapping.await {
+                warn!("Failed to read back capture: {:?}", e);
+                return;
+            }
+
+            let slice = buffer.slice(..);
+            let mut rgba = Vec::with_capacity(row as usize * size.1 as usize);
+            for line in slice.get_mapped_range().chunks(padded_row as usize) {
+                // TEX_FORMAT is BGRA
+                for px in line[0..row as usize].chunks(4) {
+                    rgba.extend_from_slice(&[px[2], px[1], px[0], px[3]]);
+                }
+            }
+            buffer.unmap();
+            f(Capture { size, rgba });
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.device.poll(wgpu::Maintain::Wait);
+            futures::executor::block_on(read_back);
+        }
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(read_back);
     }
 
-    #[cfg(not(feature = "clipboard"))]
+    #[cfg(not(any(feature = "clipboard", target_arch = "wasm32")))]
     #[inline]
     pub fn get_clipboard(&mut self) -> Option<String> {
         None
     }
 
-    #[cfg(feature = "clipboard")]
+    #[cfg(target_arch = "wasm32")]
+    #[inline]
+    pub fn get_clipboard(&mut self) -> Option<String> {
+        self.web_clipboard.get()
+    }
+
+    #[cfg(all(feature = "clipboard", not(target_arch = "wasm32")))]
     pub fn get_clipboard(&mut self) -> Option<String> {
         self.clipboard
             .as_mut()
@@ -129,17 +459,70 @@ where
             })
     }
 
-    #[cfg(not(feature = "clipboard"))]
+    #[cfg(not(any(feature = "clipboard", target_arch = "wasm32")))]
     #[inline]
     pub fn set_clipboard<'c>(&mut self, _: std::borrow::Cow<'c, str>) {}
 
-    #[cfg(feature = "clipboard")]
+    #[cfg(target_arch = "wasm32")]
+    #[inline]
+    pub fn set_clipboard<'c>(&mut self, content: std::borrow::Cow<'c, str>) {
+        self.web_clipboard.set(content.into_owned());
+    }
+
+    #[cfg(all(feature = "clipboard", not(target_arch = "wasm32")))]
     pub fn set_clipboard<'c>(&mut self, content: std::borrow::Cow<'c, str>) {
         self.clipboard.as_mut().map(|cb| {
             cb.set_contents(content.into())
                 .unwrap_or_else(|e| warn!("Failed to set clipboard contents: {:?}", e))
         });
     }
+
+    #[cfg(not(all(
+        feature = "clipboard",
+        unix,
+        not(any(target_os = "macos", target_os = "android"))
+    )))]
+    #[inline]
+    pub fn get_primary(&mut self) -> Option<String> {
+        None
+    }
+
+    #[cfg(all(
+        feature = "clipboard",
+        unix,
+        not(any(target_os = "macos", target_os = "android"))
+    ))]
+    pub fn get_primary(&mut self) -> Option<String> {
+        self.primary
+            .as_mut()
+            .and_then(|cb| match cb.get_contents() {
+                Ok(c) => Some(c),
+                Err(e) => {
+                    warn!("Failed to get primary selection: {:?}", e);
+                    None
+                }
+            })
+    }
+
+    #[cfg(not(all(
+        feature = "clipboard",
+        unix,
+        not(any(target_os = "macos", target_os = "android"))
+    )))]
+    #[inline]
+    pub fn set_primary<'c>(&mut self, _: std::borrow::Cow<'c, str>) {}
+
+    #[cfg(all(
+        feature = "clipboard",
+        unix,
+        not(any(target_os = "macos", target_os = "android"))
+    ))]
+    pub fn set_primary<'c>(&mut self, content: std::borrow::Cow<'c, str>) {
+        self.primary.as_mut().map(|cb| {
+            cb.set_contents(content.into())
+                .unwrap_or_else(|e| warn!("Failed to set primary selection: {:?}", e))
+        });
+    }
 }
 
 pub enum PendingAction {
@@ -149,4 +532,15 @@ pub enum PendingAction {
     ThemeResize,
     RedrawAll,
     Update(UpdateHandle, u64),
+    EndDrag(bool),
+    Capture(winit::window::WindowId, Box<dyn FnOnce(Capture)>),
+}
+
+/// State of a drag (which may cross between windows)
+pub struct DragState {
+    pub data: DragData,
+    /// The window in which the drag started
+    pub source: winit::window::WindowId,
+    /// The window currently under the cursor, if any
+    pub target: Option<winit::window::WindowId>,
 }
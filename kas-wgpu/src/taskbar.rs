@@ -0,0 +1,567 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Taskbar integration
+//!
+//! -   On Windows, `ITaskbarList3` shows progress and a badge overlay on each
+//!     window's taskbar button and `FlashWindowEx` requests attention.
+//! -   On Linux and BSD (with feature `dbus`), the Unity `LauncherEntry` D-Bus
+//!     API (supported by e.g. Plasma, Dash to Dock and Plank) updates the
+//!     application's launcher, as identified by [`Options::desktop_entry`].
+//! -   On macOS, the application's dock tile is updated.
+//!
+//! Elsewhere, requests are ignored.
+//!
+//! [`Options::desktop_entry`]: crate::Options::desktop_entry
+
+pub use imp::Taskbar;
+
+#[cfg(target_os = "windows")]
+mod imp {
+    use log::warn;
+    use std::ptr;
+    use winapi::shared::windef::{HICON, HWND};
+    use winapi::shared::winerror::{FAILED, RPC_E_CHANGED_MODE, SUCCEEDED};
+    use winapi::shared::wtypesbase::CLSCTX_INPROC_SERVER;
+    use winapi::um::combaseapi::{CoCreateInstance, CoInitializeEx, CoUninitialize};
+    use winapi::um::objbase::COINIT_APARTMENTTHREADED;
+    use winapi::um::shobjidl_core::{
+        CLSID_TaskbarList, ITaskbarList3, TBPF_NOPROGRESS, TBPF_NORMAL,
+    };
+    use winapi::um::winuser::{
+        CreateIcon, DestroyIcon, FlashWindowEx, FLASHWINFO, FLASHW_ALL, FLASHW_TIMERNOFG,
+        FLASHW_TRAY,
+    };
+    use winapi::Interface;
+    use winit::platform::windows::WindowExtWindows;
+    use winit::window::Window;
+
+    /// Resolution of progress values passed to the taskbar
+    const PROGRESS_STEPS: u64 = 10_000;
+
+    /// Owned reference to the `ITaskbarList3` COM object
+    struct TaskbarList {
+        ptr: *mut ITaskbarList3,
+        /// True if our call to `CoInitializeEx` must be balanced
+        uninit: bool,
+    }
+
+    // SAFETY: the object is created in a single-threaded apartment, thus may
+    // only be used from the thread creating it. The toolkit creates and uses
+    // the taskbar (via `SharedState`) only from the event-loop thread.
+    unsafe impl Send for TaskbarList {}
+
+    impl TaskbarList {
+        fn new() -> Option<Self> {
+            let mut ptr: *mut ITaskbarList3 = ptr::null_mut();
+            unsafe {
+                // COM is usually initialised already by winit (in which case
+                // this returns S_FALSE and must still be balanced). If it was
+                // initialised in multi-threaded mode we may still use it.
+                let hr = CoInitializeEx(ptr::null_mut(), COINIT_APARTMENTTHREADED);
+                let uninit = SUCCEEDED(hr);
+                if !uninit && hr != RPC_E_CHANGED_MODE {
+                    warn!("Unable to initialise COM: error {:#x}", hr);
+                    return None;
+                }
+                // From here, Drop balances initialisation
+                let mut list = TaskbarList {
+                    ptr: ptr::null_mut(),
+                    uninit,
+                };
+
+                let hr = CoCreateInstance(
+                    &CLSID_TaskbarList,
+                    ptr::null_mut(),
+                    CLSCTX_INPROC_SERVER,
+                    &ITaskbarList3::uuidof(),
+                    &mut ptr as *mut *mut ITaskbarList3 as *mut _,
+                );
+                if FAILED(hr) || ptr.is_null() {
+                    warn!("Unable to open ITaskbarList3: error {:#x}", hr);
+                    return None;
+                }
+                list.ptr = ptr;
+                if FAILED((*ptr).HrInit()) {
+                    warn!("Unable to initialise ITaskbarList3");
+                    return None;
+                }
+                Some(list)
+            }
+        }
+    }
+
+    impl Drop for TaskbarList {
+        fn drop(&mut self) {
+            unsafe {
+                if !self.ptr.is_null() {
+                    (*self.ptr).Release();
+                }
+                if self.uninit {
+                    CoUninitialize();
+                }
+            }
+        }
+    }
+
+    /// Owned icon handle
+    struct Icon(HICON);
+
+    // SAFETY: icon handles are not bound to a thread
+    unsafe impl Send for Icon {}
+
+    impl Drop for Icon {
+        fn drop(&mut self) {
+            unsafe {
+                DestroyIcon(self.0);
+            }
+        }
+    }
+
+    pub struct Taskbar {
+        list: Option<TaskbarList>,
+        badge_icon: Option<Icon>,
+    }
+
+    impl Taskbar {
+        pub fn new(_: Option<&str>) -> Self {
+            Taskbar {
+                list: TaskbarList::new(),
+                badge_icon: None,
+            }
+        }
+
+        pub fn set_progress(&mut self, window: &Window, progress: Option<f32>) {
+            let list = match self.list.as_ref() {
+                Some(list) => list.ptr,
+                None => return,
+            };
+            let hwnd = window.hwnd() as HWND;
+            unsafe {
+                match progress {
+                    Some(p) => {
+                        (*list).SetProgressState(hwnd, TBPF_NORMAL);
+                        (*list).SetProgressValue(hwnd, progress_value(p), PROGRESS_STEPS);
+                    }
+                    None => {
+                        (*list).SetProgressState(hwnd, TBPF_NOPROGRESS);
+                    }
+                }
+            }
+        }
+
+        pub fn set_badge(&mut self, window: &Window, count: Option<u32>) {
+            let list = match self.list.as_ref() {
+                Some(list) => list.ptr,
+                None => return,
+            };
+            let hwnd = window.hwnd() as HWND;
+            // Overlay icons are too small to show a legible count, thus we
+            // show a dot and expose the count via the accessible description.
+            let (icon, desc) = match count {
+                Some(n) => {
+                    let icon = self.badge_icon.get_or_insert_with(|| Icon(badge_icon()));
+                    (icon.0, format!("{}", n))
+                }
+                None => (ptr::null_mut(), String::new()),
+            };
+            let desc: Vec<u16> = desc.encode_utf16().chain(std::iter::once(0)).collect();
+            unsafe {
+                let hr = (*list).SetOverlayIcon(hwnd, icon, desc.as_ptr());
+                if !SUCCEEDED(hr) {
+                    warn!("Failed to set taskbar overlay icon: error {:#x}", hr);
+                }
+            }
+        }
+
+        pub fn request_attention(&mut self, window: &Window, critical: bool) {
+            // Flash until the window is focussed; critical requests flash
+            // the caption as well as the taskbar button.
+            let flags = match critical {
+                false => FLASHW_TRAY,
+                true => FLASHW_ALL,
+            };
+            let mut info = FLASHWINFO {
+                cbSize: std::mem::size_of::<FLASHWINFO>() as u32,
+                hwnd: window.hwnd() as HWND,
+                dwFlags: flags | FLASHW_TIMERNOFG,
+                uCount: 0,
+                dwTimeout: 0,
+            };
+            unsafe {
+                FlashWindowEx(&mut info);
+            }
+        }
+
+        pub fn window_focused(&mut self) {}
+    }
+
+    /// Convert progress (expected in the range 0 to 1) to taskbar steps
+    fn progress_value(progress: f32) -> u64 {
+        let p = progress.max(0.0).min(1.0) as f64;
+        (p * PROGRESS_STEPS as f64).round() as u64
+    }
+
+    /// Size of the badge icon
+    const N: usize = 16;
+
+    /// Pixels of the badge icon: a red dot (pre-multiplied BGRA)
+    fn badge_pixels() -> [u8; N * N * 4] {
+        let mut colour = [0u8; N * N * 4];
+        let r = N as f32 / 2.0;
+        for y in 0..N {
+            for x in 0..N {
+                let dx = x as f32 + 0.5 - r;
+                let dy = y as f32 + 0.5 - r;
+                let dist = (dx * dx + dy * dy).sqrt();
+                let alpha = (r - dist).max(0.0).min(1.0);
+                let a = (alpha * 255.0).round() as u8;
+                // Pre-multiplied BGRA
+                let i = (y * N + x) * 4;
+                colour[i] = (0.16 * a as f32) as u8;
+                colour[i + 1] = (0.16 * a as f32) as u8;
+                colour[i + 2] = (0.86 * a as f32) as u8;
+                colour[i + 3] = a;
+            }
+        }
+        colour
+    }
+
+    /// Construct the badge icon
+    fn badge_icon() -> HICON {
+        let colour = badge_pixels();
+        // The mask is ignored where alpha is used, but must be provided
+        let mask = [0u8; N * N / 8];
+        unsafe {
+            CreateIcon(
+                ptr::null_mut(),
+                N as i32,
+                N as i32,
+                1,
+                32,
+                mask.as_ptr(),
+                colour.as_ptr(),
+            )
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn progress() {
+            assert_eq!(progress_value(0.0), 0);
+            assert_eq!(progress_value(0.5), PROGRESS_STEPS / 2);
+            assert_eq!(progress_value(1.0), PROGRESS_STEPS);
+            assert_eq!(progress_value(-1.0), 0);
+            assert_eq!(progress_value(2.0), PROGRESS_STEPS);
+        }
+
+        #[test]
+        fn badge() {
+            let pixels = badge_pixels();
+            let alpha = |x: usize, y: usize| pixels[(y * N + x) * 4 + 3];
+            assert_eq!(alpha(0, 0), 0);
+            assert_eq!(alpha(N / 2, N / 2), 255);
+            // Colour channels are pre-multiplied
+            assert!(pixels.chunks(4).all(|p| p[0] <= p[3] && p[2] <= p[3]));
+
+            let icon = Icon(badge_icon());
+            assert!(!icon.0.is_null());
+        }
+
+        #[test]
+        fn init_and_drop() {
+            // COM may be unavailable (e.g. on a headless CI), but must not
+            // cause a crash or leak an initialisation
+            for _ in 0..2 {
+                let taskbar = Taskbar::new(None);
+                drop(taskbar);
+            }
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "dbus",
+    unix,
+    not(any(target_os = "macos", target_os = "android"))
+))]
+mod imp {
+    use dbus::arg::{RefArg, Variant};
+    use dbus::blocking::Connection;
+    use dbus::channel::Sender;
+    use dbus::Message;
+    use log::warn;
+    use std::collections::HashMap;
+    use winit::window::Window;
+
+    const INTERFACE: &str = "com.canonical.Unity.LauncherEntry";
+
+    type Properties = HashMap<String, Variant<Box<dyn RefArg>>>;
+
+    pub struct Taskbar {
+        conn: Option<Connection>,
+        app_uri: String,
+        path: String,
+        urgent: bool,
+    }
+
+    impl Taskbar {
+        pub fn new(desktop_entry: Option<&str>) -> Self {
+            let conn = match Connection::new_session() {
+                Ok(conn) => Some(conn),
+                Err(e) => {
+                    warn!("Unable to connect to the D-Bus session bus: {}", e);
+                    None
+                }
+            };
+            Taskbar {
+                conn,
+                app_uri: app_uri(desktop_entry),
+                path: format!("/com/canonical/unity/launcherentry/{}", std::process::id()),
+                urgent: false,
+            }
+        }
+
+        /// Send a `LauncherEntry.Update` signal
+        ///
+        /// The launcher tracks this connection: if it is closed, the entry is
+        /// reset. We therefore keep the connection open.
+        fn update(&mut self, props: Properties) {
+            let conn = match self.conn.as_ref() {
+                Some(conn) => conn,
+                None => return,
+            };
+            let msg = match Message::new_signal(self.path.as_str(), INTERFACE, "Update") {
+                Ok(msg) => msg.append2(self.app_uri.as_str(), props),
+                Err(e) => {
+                    warn!("Unable to construct LauncherEntry signal: {}", e);
+                    return;
+                }
+            };
+            if conn.send(msg).is_err() {
+                warn!("Failed to send LauncherEntry signal");
+            }
+        }
+
+        fn prop<T: RefArg + 'static>(props: &mut Properties, key: &str, value: T) {
+            let value: Box<dyn RefArg> = Box::new(value);
+            props.insert(key.to_string(), Variant(value));
+        }
+
+        pub fn set_progress(&mut self, _: &Window, progress: Option<f32>) {
+            let mut props = Properties::new();
+            Self::prop(&mut props, "progress-visible", progress.is_some());
+            if let Some(p) = progress {
+                Self::prop(&mut props, "progress", p as f64);
+            }
+            self.update(props);
+        }
+
+        pub fn set_badge(&mut self, _: &Window, count: Option<u32>) {
+            let mut props = Properties::new();
+            Self::prop(&mut props, "count-visible", count.is_some());
+            if let Some(n) = count {
+                Self::prop(&mut props, "count", n as i64);
+            }
+            self.update(props);
+        }
+
+        pub fn request_attention(&mut self, _: &Window, _critical: bool) {
+            self.urgent = true;
+            let mut props = Properties::new();
+            Self::prop(&mut props, "urgent", true);
+            self.update(props);
+        }
+
+        /// Clear urgency once any window is focussed
+        pub fn window_focused(&mut self) {
+            if self.urgent {
+                self.urgent = false;
+                let mut props = Properties::new();
+                Self::prop(&mut props, "urgent", false);
+                self.update(props);
+            }
+        }
+    }
+
+    /// Get the URI identifying the application's launcher entry
+    fn app_uri(desktop_entry: Option<&str>) -> String {
+        let entry = match desktop_entry {
+            Some(entry) => entry.trim_end_matches(".desktop").to_string(),
+            None => std::env::current_exe()
+                .ok()
+                .and_then(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+                .unwrap_or_default(),
+        };
+        format!("application://{}.desktop", entry)
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn uri() {
+            let uri = "application://org.example.App.desktop";
+            assert_eq!(app_uri(Some("org.example.App")), uri);
+            assert_eq!(app_uri(Some("org.example.App.desktop")), uri);
+            let uri = app_uri(None);
+            assert!(uri.starts_with("application://") && uri.ends_with(".desktop"));
+        }
+
+        #[test]
+        fn update_without_window() {
+            // Works with or without a session bus; signals for an unknown
+            // desktop entry are ignored by launchers
+            let mut taskbar = Taskbar::new(Some("org.example.KasTaskbarTest"));
+            let mut props = Properties::new();
+            Taskbar::prop(&mut props, "count-visible", false);
+            taskbar.update(props);
+            taskbar.window_focused();
+            assert!(!taskbar.urgent);
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use cocoa::appkit::NSApp;
+    use cocoa::base::{id, nil, NO};
+    use cocoa::foundation::{NSPoint, NSRect, NSSize, NSString};
+    use objc::{class, msg_send, sel, sel_impl};
+    use winit::platform::macos::{RequestUserAttentionType, WindowExtMacOS};
+    use winit::window::Window;
+
+    /// `NSProgressIndicatorStyleBar`
+    const STYLE_BAR: u64 = 0;
+
+    pub struct Taskbar {
+        /// Progress indicator within the dock tile, if shown (retained)
+        indicator: id,
+    }
+
+    // SAFETY: AppKit objects may only be used from the main thread. The
+    // toolkit creates and uses the taskbar (via `SharedState`) only from the
+    // event-loop thread, which on macOS is the main thread.
+    unsafe impl Send for Taskbar {}
+
+    impl Taskbar {
+        pub fn new(_: Option<&str>) -> Self {
+            Taskbar { indicator: nil }
+        }
+
+        pub fn set_progress(&mut self, _: &Window, progress: Option<f32>) {
+            unsafe {
+                let tile: id = msg_send![NSApp(), dockTile];
+                match progress {
+                    Some(p) => {
+                        if self.indicator == nil {
+                            self.indicator = add_indicator(tile);
+                        }
+                        let _: () = msg_send![self.indicator, setDoubleValue: p as f64];
+                    }
+                    None => {
+                        if self.indicator != nil {
+                            let _: () = msg_send![tile, setContentView: nil];
+                            let _: () = msg_send![self.indicator, release];
+                            self.indicator = nil;
+                        }
+                    }
+                }
+                let _: () = msg_send![tile, display];
+            }
+        }
+
+        pub fn set_badge(&mut self, _: &Window, count: Option<u32>) {
+            unsafe {
+                let tile: id = msg_send![NSApp(), dockTile];
+                let label = match count {
+                    Some(n) => NSString::alloc(nil).init_str(&n.to_string()),
+                    None => nil,
+                };
+                let _: () = msg_send![tile, setBadgeLabel: label];
+                if label != nil {
+                    let _: () = msg_send![label, release];
+                }
+            }
+        }
+
+        pub fn request_attention(&mut self, window: &Window, critical: bool) {
+            let request_type = match critical {
+                false => RequestUserAttentionType::Informational,
+                true => RequestUserAttentionType::Critical,
+            };
+            window.request_user_attention(request_type);
+        }
+
+        pub fn window_focused(&mut self) {}
+    }
+
+    /// Replace the dock tile's content with the application icon and a
+    /// progress bar, returning the (retained) bar
+    unsafe fn add_indicator(tile: id) -> id {
+        let size: NSSize = msg_send![tile, size];
+        let frame = NSRect::new(NSPoint::new(0.0, 0.0), size);
+        let view: id = msg_send![class!(NSImageView), alloc];
+        let view: id = msg_send![view, initWithFrame: frame];
+        let icon: id = msg_send![NSApp(), applicationIconImage];
+        let _: () = msg_send![view, setImage: icon];
+
+        let bar_frame = NSRect::new(
+            NSPoint::new(size.width * 0.05, 0.0),
+            NSSize::new(size.width * 0.9, size.height * 0.15),
+        );
+        let bar: id = msg_send![class!(NSProgressIndicator), alloc];
+        let bar: id = msg_send![bar, initWithFrame: bar_frame];
+        let _: () = msg_send![bar, setStyle: STYLE_BAR];
+        let _: () = msg_send![bar, setIndeterminate: NO];
+        let _: () = msg_send![bar, setMinValue: 0.0f64];
+        let _: () = msg_send![bar, setMaxValue: 1.0f64];
+        let _: () = msg_send![view, addSubview: bar];
+
+        let _: () = msg_send![tile, setContentView: view];
+        let _: () = msg_send![view, release];
+        bar
+    }
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    all(
+        feature = "dbus",
+        unix,
+        not(any(target_os = "macos", target_os = "android"))
+    )
+)))]
+mod imp {
+    use log::debug;
+    use winit::window::Window;
+
+    pub struct Taskbar;
+
+    impl Taskbar {
+        pub fn new(_: Option<&str>) -> Self {
+            Taskbar
+        }
+
+        pub fn set_progress(&mut self, _: &Window, progress: Option<f32>) {
+            debug!("Taskbar::set_progress({:?}): unsupported", progress);
+        }
+
+        pub fn set_badge(&mut self, _: &Window, count: Option<u32>) {
+            debug!("Taskbar::set_badge({:?}): unsupported", count);
+        }
+
+        pub fn request_attention(&mut self, _: &Window, critical: bool) {
+            debug!("Taskbar::request_attention({}): unsupported", critical);
+        }
+
+        pub fn window_focused(&mut self) {}
+    }
+}
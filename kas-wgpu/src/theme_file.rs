@@ -0,0 +1,62 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Theme file watcher
+
+use log::{info, warn};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use kas_theme::ThemeConfig;
+
+/// Interval between checks of the theme file
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watches a theme configuration file for changes
+pub struct ThemeWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ThemeWatcher {
+    /// Construct (the file is read on the first poll)
+    pub fn new(path: PathBuf) -> Self {
+        ThemeWatcher {
+            path,
+            modified: None,
+        }
+    }
+
+    /// Check the file for changes
+    ///
+    /// Returns the newly loaded configuration if the file was modified since
+    /// the last poll and could be loaded.
+    pub fn poll(&mut self) -> Option<ThemeConfig> {
+        let modified = match std::fs::metadata(&self.path).and_then(|m| m.modified()) {
+            Ok(time) => time,
+            Err(e) => {
+                if self.modified.take().is_some() {
+                    warn!("Theme file {}: {}", self.path.display(), e);
+                }
+                return None;
+            }
+        };
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
+
+        match ThemeConfig::load(&self.path) {
+            Ok(config) => {
+                info!("Loaded theme file {}", self.path.display());
+                Some(config)
+            }
+            Err(e) => {
+                warn!("Failed to load theme file {}: {}", self.path.display(), e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Browser integration (WebAssembly only)
+
+use log::warn;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{EventTarget, HtmlCanvasElement, HtmlTextAreaElement, InputEvent, KeyboardEvent};
+
+/// Bridge to the browser's clipboard
+///
+/// The browser's clipboard API is asynchronous (and reading may require the
+/// user's permission), while KAS reads the clipboard synchronously. Reads
+/// therefore return the last known content while requesting an update in the
+/// background: the first paste of external content may see stale content.
+#[derive(Default)]
+pub struct Clipboard {
+    content: Rc<RefCell<Option<String>>>,
+}
+
+impl Clipboard {
+    pub fn get(&mut self) -> Option<String> {
+        let content = self.content.borrow().clone();
+        if let Some(clipboard) = navigator_clipboard() {
+            let promise = clipboard.read_text();
+            let cell = self.content.clone();
+            spawn_local(async move {
+                match JsFuture::from(promise).await {
+                    Ok(text) => *cell.borrow_mut() = text.as_string(),
+                    Err(e) => warn!("Failed to get clipboard contents: {:?}", e),
+                }
+            });
+        }
+        content
+    }
+
+    pub fn set(&mut self, content: String) {
+        if let Some(clipboard) = navigator_clipboard() {
+            let promise = clipboard.write_text(&content);
+            spawn_local(async move {
+                if let Err(e) = JsFuture::from(promise).await {
+                    warn!("Failed to set clipboard contents: {:?}", e);
+                }
+            });
+        }
+        *self.content.borrow_mut() = Some(content);
+    }
+}
+
+fn navigator_clipboard() -> Option<web_sys::Clipboard> {
+    Some(web_sys::window()?.navigator().clipboard())
+}
+
+/// Add the canvas of a window to the document body
+pub fn attach_canvas(window: &winit::window::Window) {
+    use winit::platform::web::WindowExtWebSys;
+
+    let body = web_sys::window()
+        .and_then(|win| win.document())
+        .and_then(|doc| doc.body());
+    match body {
+        Some(body) => {
+            if let Err(e) = body.append_child(&window.canvas()) {
+                warn!("Failed to add canvas to document: {:?}", e);
+            }
+        }
+        None => warn!("Failed to add canvas to document: no body"),
+    }
+}
+
+/// Bridge to the browser's input methods (IME)
+///
+/// Browsers only compose text (via IME or an on-screen keyboard) in editable
+/// elements, which a canvas is not. While a widget has character focus,
+/// keyboard focus is therefore moved to a hidden `<textarea>`. Key events which
+/// are not part of a composition are forwarded to the canvas (where winit
+/// handles them as usual), while composed text is sent to the canvas as one
+/// `keypress` event per character, which winit reports as received characters.
+pub struct Ime {
+    canvas: HtmlCanvasElement,
+    textarea: HtmlTextAreaElement,
+    state: Rc<ImeState>,
+    _listeners: Vec<Listener>,
+}
+
+#[derive(Default)]
+struct ImeState {
+    enabled: Cell<bool>,
+    /// Set when we move focus from the canvas to the textarea
+    focus_moved: Cell<bool>,
+    /// Set when a key press was forwarded (thus its input already handled)
+    typed: Cell<bool>,
+}
+
+impl Ime {
+    /// Construct for the canvas of `window`
+    ///
+    /// Returns `None` if the document is not available.
+    pub fn new(window: &winit::window::Window) -> Option<Self> {
+        use winit::platform::web::WindowExtWebSys;
+
+        let document = web_sys::window()?.document()?;
+        let textarea: HtmlTextAreaElement =
+            document.create_element("textarea").ok()?.dyn_into().ok()?;
+        for (name, value) in &[
+            ("autocapitalize", "off"),
+            ("autocomplete", "off"),
+            ("spellcheck", "false"),
+            ("aria-hidden", "true"),
+        ] {
+            let _ = textarea.set_attribute(name, value);
+        }
+        textarea.style().set_css_text(
+            "position: fixed; left: 0; top: 0; width: 1px; height: 1px; \
+            opacity: 0; border: none; padding: 0; pointer-events: none",
+        );
+        if let Err(e) = document.body()?.append_child(&textarea) {
+            warn!("Failed to add IME bridge to document: {:?}", e);
+            return None;
+        }
+
+        let canvas = window.canvas();
+        let state = Rc::new(ImeState::default());
+        let mut listeners = vec![];
+
+        for name in &["keydown", "keypress", "keyup"] {
+            let (canvas, state) = (canvas.clone(), state.clone());
+            let name = *name;
+            let listener = Listener::new(&textarea, name, move |event| {
+                let event: &KeyboardEvent = event.unchecked_ref();
+                // Key events of a composition are handled by the IME
+                if event.is_composing() || event.key_code() == 229 {
+                    return;
+                }
+                match name {
+                    // Tab navigation is handled by KAS, not the browser
+                    "keydown" if event.key() == "Tab" => event.prevent_default(),
+                    "keypress" => state.typed.set(true),
+                    _ => (),
+                }
+                forward_key(&canvas, name, event);
+            });
+            listeners.push(listener);
+        }
+
+        let (canvas2, state2, textarea2) = (canvas.clone(), state.clone(), textarea.clone());
+        listeners.push(Listener::new(&textarea, "input", move |event| {
+            let event: &InputEvent = event.unchecked_ref();
+            if event.is_composing() {
+                return;
+            }
+            // Text input without a key press (e.g. from an on-screen keyboard)
+            if !state2.typed.replace(false) {
+                send_text(&canvas2, &textarea2.value());
+            }
+            textarea2.set_value("");
+        }));
+
+        let (canvas2, textarea2) = (canvas.clone(), textarea.clone());
+        listeners.push(Listener::new(&textarea, "compositionend", move |_| {
+            send_text(&canvas2, &textarea2.value());
+            textarea2.set_value("");
+        }));
+
+        // Clicking the canvas focusses it; while enabled, we take focus back
+        let (state2, textarea2) = (state.clone(), textarea.clone());
+        listeners.push(Listener::new(&canvas, "focus", move |_| {
+            if state2.enabled.get() {
+                state2.focus_moved.set(true);
+                let _ = textarea2.focus();
+            }
+        }));
+
+        Some(Ime {
+            canvas,
+            textarea,
+            state,
+            _listeners: listeners,
+        })
+    }
+
+    /// Enable or disable text input
+    ///
+    /// This should be enabled while a widget has character focus.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if self.state.enabled.replace(enabled) == enabled {
+            return;
+        }
+        let active = web_sys::window()
+            .and_then(|win| win.document())
+            .and_then(|doc| doc.active_element());
+        let canvas: &web_sys::Element = self.canvas.as_ref();
+        let textarea: &web_sys::Element = self.textarea.as_ref();
+        if enabled && active.as_ref() == Some(canvas) {
+            self.state.focus_moved.set(true);
+            let _ = self.textarea.focus();
+        } else if !enabled && active.as_ref() == Some(textarea) {
+            self.textarea.set_value("");
+            let _ = self.canvas.focus();
+        }
+    }
+
+    /// True if the canvas lost focus to the bridge
+    ///
+    /// This is the case for the first call after focus was moved.
+    pub fn take_focus_moved(&mut self) -> bool {
+        self.state.focus_moved.replace(false)
+    }
+}
+
+impl Drop for Ime {
+    fn drop(&mut self) {
+        self.textarea.remove();
+    }
+}
+
+/// An event listener, removed on drop
+struct Listener {
+    target: EventTarget,
+    name: &'static str,
+    closure: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl Listener {
+    fn new<F>(target: &EventTarget, name: &'static str, f: F) -> Self
+    where
+        F: FnMut(web_sys::Event) + 'static,
+    {
+        let closure = Closure::wrap(Box::new(f) as Box<dyn FnMut(web_sys::Event)>);
+        let callback = closure.as_ref().unchecked_ref();
+        if let Err(e) = target.add_event_listener_with_callback(name, callback) {
+            warn!("Failed to add {} listener: {:?}", name, e);
+        }
+        Listener {
+            target: target.clone(),
+            name,
+            closure,
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let callback = self.closure.as_ref().unchecked_ref();
+        let _ = self
+            .target
+            .remove_event_listener_with_callback(self.name, callback);
+    }
+}
+
+/// Dispatch a copy of the key `event` of type `name` to the canvas
+fn forward_key(canvas: &HtmlCanvasElement, name: &str, event: &KeyboardEvent) {
+    let mut init = web_sys::KeyboardEventInit::new();
+    init.key(&event.key())
+        .code(&event.code())
+        .key_code(event.key_code())
+        .char_code(event.char_code())
+        .location(event.location())
+        .repeat(event.repeat())
+        .alt_key(event.alt_key())
+        .ctrl_key(event.ctrl_key())
+        .shift_key(event.shift_key())
+        .meta_key(event.meta_key());
+    dispatch_key(canvas, name, &init);
+}
+
+/// Send `text` to the canvas as `keypress` events
+fn send_text(canvas: &HtmlCanvasElement, text: &str) {
+    let mut buf = [0; 4];
+    for c in text.chars() {
+        let mut init = web_sys::KeyboardEventInit::new();
+        init.key(c.encode_utf8(&mut buf)).char_code(c as u32);
+        dispatch_key(canvas, "keypress", &init);
+    }
+}
+
+fn dispatch_key(canvas: &HtmlCanvasElement, name: &str, init: &web_sys::KeyboardEventInit) {
+    match KeyboardEvent::new_with_keyboard_event_init_dict(name, init) {
+        Ok(event) => {
+            let _ = canvas.dispatch_event(&event);
+        }
+        Err(e) => warn!("Failed to create {} event: {:?}", name, e),
+    }
+}
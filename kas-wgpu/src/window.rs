@@ -5,23 +5,24 @@
 
 //! `Window` and `WindowList` types
 
-use log::{debug, info, trace};
-use std::time::Instant;
+use instant::Instant;
+use log::{debug, info, trace, warn};
 
-use kas::draw::SizeHandle;
-use kas::event::{CursorIcon, ManagerState, UpdateHandle};
+use kas::draw::{DrawRecorder, SizeHandle};
+use kas::event::{CursorIcon, DragData, ManagerState, PowerStatus, UpdateHandle};
 use kas::geom::{Coord, Rect, Size};
 use kas::layout::SolveCache;
-use kas::{ThemeAction, ThemeApi, TkAction, WindowId};
+use kas::{Capture, ThemeAction, ThemeApi, TkAction, WindowId};
 use kas_theme::Theme;
-use winit::dpi::PhysicalSize;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
 use winit::error::OsError;
-use winit::event::WindowEvent;
+use winit::event::{ElementState, KeyboardInput, ModifiersState, VirtualKeyCode, WindowEvent};
 use winit::event_loop::EventLoopWindowTarget;
 use winit::window::WindowBuilder;
 
+use crate::dark_mode;
 use crate::draw::{CustomPipe, CustomWindow, DrawPipe, DrawWindow, TEX_FORMAT};
-use crate::shared::{PendingAction, SharedState};
+use crate::shared::{DragState, PendingAction, SharedState};
 use crate::ProxyAction;
 
 /// Per-window data
@@ -37,6 +38,17 @@ pub(crate) struct Window<CW: CustomWindow, TW> {
     swap_chain: wgpu::SwapChain,
     draw: DrawWindow<CW>,
     theme_window: TW,
+    /// Last known cursor position
+    cursor_pos: PhysicalPosition<f64>,
+    /// Serial number of the last focus event (higher is more recent)
+    pub(crate) focus_serial: u64,
+    /// Start time of the last frame (only if frame rate is limited)
+    frame_time: Option<Instant>,
+    /// Time of the next frame, if deferred by the frame limiter
+    frame_due: Option<Instant>,
+    /// Bridge to the browser's input methods
+    #[cfg(target_arch = "wasm32")]
+    ime: Option<crate::web::Ime>,
 }
 
 // Public functions, for use by the toolkit
@@ -57,7 +69,7 @@ where
         T: Theme<DrawPipe<C>, Window = TW>,
     {
         // Create draw immediately (with Size::ZERO) to find ideal window size
-        let scale_factor = shared.scale_factor as f32;
+        let scale_factor = shared.effective_scale(shared.scale_factor) as f32;
         let mut draw = shared.draw.new_window(&mut shared.device, Size::ZERO);
         let mut theme_window = shared.theme.new_window(&mut draw, scale_factor);
 
@@ -76,27 +88,46 @@ where
             builder = builder.with_max_inner_size(ideal);
         }
         let window = builder.with_title(widget.title()).build(elwt)?;
+        #[cfg(target_arch = "wasm32")]
+        crate::web::attach_canvas(&window);
+        #[cfg(target_arch = "wasm32")]
+        let ime = crate::web::Ime::new(&window);
 
         let scale_factor = window.scale_factor();
         shared.scale_factor = scale_factor;
         let size: Size = window.inner_size().into();
         info!("Constucted new window with size {:?}", size);
 
+        // Where supported, detect the initial dark-mode preference
+        if let Some(dark) = dark_mode::system_dark_mode(&window) {
+            shared.set_dark_mode(dark);
+        }
+
         // draw was initially created with Size::ZERO; we must resize
         let buf = shared.draw.resize(&mut draw, &shared.device, size);
         shared.queue.submit(std::iter::once(buf));
 
+        if shared.warm_up {
+            let mut size_handle = unsafe { theme_window.size_handle() };
+            let (device, queue) = (&mut shared.device, &mut shared.queue);
+            shared
+                .draw
+                .warm_up(&mut draw, &mut size_handle, device, queue);
+        }
+
         let surface = unsafe { shared.instance.create_surface(&window) };
         let sc_desc = wgpu::SwapChainDescriptor {
             usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
             format: TEX_FORMAT,
             width: size.0,
             height: size.1,
-            present_mode: wgpu::PresentMode::Fifo,
+            present_mode: shared.present_mode,
         };
         let swap_chain = shared.device.create_swap_chain(&surface, &sc_desc);
 
-        let mut mgr = ManagerState::new(scale_factor);
+        let mut mgr = ManagerState::new(shared.effective_scale(scale_factor));
+        mgr.set_config(shared.input_config.clone());
+        mgr.set_power_status(shared.power);
         let mut tkw = TkWindow::new(shared, &window, &mut theme_window);
         mgr.configure(&mut tkw, &mut *widget);
 
@@ -111,6 +142,12 @@ where
             swap_chain,
             draw,
             theme_window,
+            cursor_pos: PhysicalPosition::new(-1.0, -1.0),
+            focus_serial: 0,
+            frame_time: None,
+            frame_due: None,
+            #[cfg(target_arch = "wasm32")]
+            ime,
         };
         r.apply_size();
         Ok(r)
@@ -137,10 +174,11 @@ where
         T: Theme<DrawPipe<C>, Window = TW>,
     {
         debug!("Window::theme_resize");
-        let scale_factor = self.window.scale_factor() as f32;
+        let scale_factor = shared.effective_scale(self.window.scale_factor());
         shared
             .theme
-            .update_window(&mut self.theme_window, scale_factor);
+            .update_window(&mut self.theme_window, scale_factor as f32);
+        self.mgr.set_dpi_factor(scale_factor);
         self.solve_cache.invalidate_rule_cache();
         self.apply_size();
     }
@@ -155,12 +193,16 @@ where
         match event {
             WindowEvent::Destroyed => (),
             WindowEvent::Resized(size) => self.do_resize(shared, size),
+            WindowEvent::ThemeChanged(theme) => {
+                shared.set_dark_mode(theme == winit::window::Theme::Dark);
+            }
             WindowEvent::ScaleFactorChanged {
                 scale_factor,
                 new_inner_size,
             } => {
                 // Note: API allows us to set new window size here.
                 shared.scale_factor = scale_factor;
+                let scale_factor = shared.effective_scale(scale_factor);
                 shared
                     .theme
                     .update_window(&mut self.theme_window, scale_factor as f32);
@@ -168,16 +210,51 @@ where
                 self.solve_cache.invalidate_rule_cache();
                 self.do_resize(shared, *new_inner_size);
             }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::F12),
+                        ..
+                    },
+                ..
+            } if self.mgr.modifiers() == ModifiersState::CTRL | ModifiersState::SHIFT => {
+                shared.colour_filter = shared.colour_filter.next();
+                info!("Colour filter: {:?}", shared.colour_filter);
+                self.window.request_redraw();
+            }
             event @ _ => {
-                let mut tkw = TkWindow::new(shared, &self.window, &mut self.theme_window);
-                let widget = &mut *self.widget;
-                self.mgr.with(&mut tkw, |mgr| {
-                    mgr.handle_winit(widget, event);
-                });
+                match event {
+                    // While a mouse button is held the platform captures the
+                    // cursor, thus motion outside the window is reported too.
+                    WindowEvent::CursorMoved { position, .. } => self.cursor_pos = position,
+                    WindowEvent::Focused(true) => {
+                        shared.taskbar.window_focused();
+                        // The layout may have been switched while unfocussed
+                        shared.update_key_layout();
+                        // Likewise the dark-mode preference, where changes
+                        // are not reported via ThemeChanged
+                        if !dark_mode::TRACKS_CHANGES {
+                            if let Some(dark) = dark_mode::system_dark_mode(&self.window) {
+                                shared.set_dark_mode(dark);
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+                self.handle_winit(shared, event);
             }
         }
     }
 
+    /// True if a loss of focus is due to the IME bridge taking focus
+    ///
+    /// Such events should be ignored.
+    #[cfg(target_arch = "wasm32")]
+    pub fn ime_took_focus(&mut self) -> bool {
+        self.ime.as_mut().map(|ime| ime.take_focus_moved()) == Some(true)
+    }
+
     /// Update, after receiving all events
     pub fn update<C, T>(&mut self, shared: &mut SharedState<C, T>) -> (TkAction, Option<Instant>)
     where
@@ -186,6 +263,11 @@ where
     {
         let mut tkw = TkWindow::new(shared, &self.window, &mut self.theme_window);
         let action = self.mgr.update(&mut tkw, &mut *self.widget);
+        let relayout = self.mgr.take_relayout();
+        #[cfg(target_arch = "wasm32")]
+        if let Some(ime) = self.ime.as_mut() {
+            ime.set_enabled(self.mgr.has_char_focus());
+        }
 
         match action {
             TkAction::None => (),
@@ -204,6 +286,15 @@ where
                 self.window.request_redraw();
             }
             TkAction::SetSize => self.apply_size(),
+            TkAction::Relayout => {
+                self.mgr.configure_pending(&mut tkw, &mut *self.widget);
+                for id in relayout {
+                    kas::layout::mark_dirty(self.widget.as_widget_mut(), id);
+                }
+                kas::layout::invalidate_dirty(self.widget.as_widget_mut());
+                self.solve_cache.refresh_dirty_rules();
+                self.apply_size();
+            }
             TkAction::Resize => {
                 self.solve_cache.invalidate_rule_cache();
                 self.apply_size();
@@ -212,7 +303,7 @@ where
             TkAction::Close | TkAction::CloseAll => (),
         }
 
-        (action, self.mgr.next_resume())
+        (action, self.next_resume())
     }
 
     pub fn handle_closure<C, T>(mut self, shared: &mut SharedState<C, T>) -> TkAction
@@ -238,7 +329,28 @@ where
         self.mgr.with(&mut tkw, |mgr| {
             mgr.update_timer(widget);
         });
-        self.mgr.next_resume()
+
+        if let Some(due) = self.frame_due {
+            if due <= Instant::now() {
+                self.frame_due = None;
+                self.window.request_redraw();
+            }
+        }
+        self.next_resume()
+    }
+
+    /// Run idle tasks; returns true if tasks remain
+    pub fn run_idle<C, T>(&mut self, shared: &mut SharedState<C, T>) -> bool
+    where
+        C: CustomPipe<Window = CW>,
+        T: Theme<DrawPipe<C>, Window = TW>,
+    {
+        if !self.mgr.has_idle_tasks() {
+            return false;
+        }
+        let mut tkw = TkWindow::new(shared, &self.window, &mut self.theme_window);
+        self.mgr.with(&mut tkw, |mgr| mgr.run_idle_tasks());
+        self.mgr.has_idle_tasks()
     }
 
     pub fn update_handle<C, T>(
@@ -257,6 +369,18 @@ where
         });
     }
 
+    pub fn update_power<C, T>(&mut self, shared: &mut SharedState<C, T>, status: PowerStatus)
+    where
+        C: CustomPipe<Window = CW>,
+        T: Theme<DrawPipe<C>, Window = TW>,
+    {
+        let mut tkw = TkWindow::new(shared, &self.window, &mut self.theme_window);
+        let widget = &mut *self.widget;
+        self.mgr.with(&mut tkw, |mgr| {
+            mgr.update_power(widget, status);
+        });
+    }
+
     pub fn add_popup<C, T>(
         &mut self,
         shared: &mut SharedState<C, T>,
@@ -273,6 +397,88 @@ where
         });
     }
 
+    /// Convert a window coordinate to a screen position
+    ///
+    /// Returns `None` where the window position is unavailable (e.g. on
+    /// Wayland).
+    pub fn coord_to_screen(&self, coord: Coord) -> Option<PhysicalPosition<i32>> {
+        let pos = self.window.inner_position().ok()?;
+        Some(PhysicalPosition::new(pos.x + coord.0, pos.y + coord.1))
+    }
+
+    /// Convert a screen position to a window coordinate
+    ///
+    /// Returns `None` if the position is not within the window.
+    pub fn screen_to_coord(&self, pos: PhysicalPosition<i32>) -> Option<Coord> {
+        let origin = self.window.inner_position().ok()?;
+        let coord = Coord(pos.x - origin.x, pos.y - origin.y);
+        Some(coord).filter(|coord| self.contains(*coord))
+    }
+
+    /// True if `coord` is within the window
+    pub fn contains(&self, coord: Coord) -> bool {
+        let size = Size(self.sc_desc.width, self.sc_desc.height);
+        Rect::new(Coord::ZERO, size).contains(coord)
+    }
+
+    /// Last known cursor position, in window coordinates
+    pub fn cursor_coord(&self) -> Coord {
+        self.cursor_pos.into()
+    }
+
+    pub fn drag_over<C, T>(
+        &mut self,
+        shared: &mut SharedState<C, T>,
+        coord: Option<Coord>,
+        data: &DragData,
+    ) where
+        C: CustomPipe<Window = CW>,
+        T: Theme<DrawPipe<C>, Window = TW>,
+    {
+        let mut tkw = TkWindow::new(shared, &self.window, &mut self.theme_window);
+        let widget = &mut *self.widget;
+        self.mgr.with(&mut tkw, |mgr| {
+            mgr.drag_over(widget, coord, data);
+        });
+    }
+
+    pub fn drag_drop<C, T>(
+        &mut self,
+        shared: &mut SharedState<C, T>,
+        coord: Coord,
+        data: DragData,
+    ) -> bool
+    where
+        C: CustomPipe<Window = CW>,
+        T: Theme<DrawPipe<C>, Window = TW>,
+    {
+        let mut tkw = TkWindow::new(shared, &self.window, &mut self.theme_window);
+        let widget = &mut *self.widget;
+        let mut accepted = false;
+        self.mgr.with(&mut tkw, |mgr| {
+            accepted = mgr.drag_drop(widget, coord, data);
+        });
+        accepted
+    }
+
+    pub fn drag_end<C, T>(&mut self, shared: &mut SharedState<C, T>, accepted: bool)
+    where
+        C: CustomPipe<Window = CW>,
+        T: Theme<DrawPipe<C>, Window = TW>,
+    {
+        let mut tkw = TkWindow::new(shared, &self.window, &mut self.theme_window);
+        let widget = &mut *self.widget;
+        self.mgr.with(&mut tkw, |mgr| {
+            mgr.drag_end(widget, accepted);
+        });
+    }
+
+    /// Redraw, invalidating cached content (e.g. on theme change)
+    pub fn redraw_all(&mut self) {
+        self.draw.invalidate_caches();
+        self.window.request_redraw();
+    }
+
     pub fn send_action(&mut self, action: TkAction) {
         self.mgr.send_action(action);
     }
@@ -292,6 +498,22 @@ where
             });
         }
     }
+
+    /// Render the window off-screen and read back the result
+    ///
+    /// The window is redrawn on screen afterwards.
+    pub fn capture<C, T>(&mut self, shared: &mut SharedState<C, T>, f: Box<dyn FnOnce(Capture)>)
+    where
+        C: CustomPipe<Window = CW>,
+        T: Theme<DrawPipe<C>, Window = TW>,
+    {
+        self.draw_widgets(shared);
+        let size = Size(self.sc_desc.width, self.sc_desc.height);
+        let clear_color = shared.colour_filter.apply(shared.theme.clear_colour());
+        let clear_color = to_wgpu_color(clear_color);
+        shared.render_capture(&mut self.draw, size, clear_color, f);
+        self.window.request_redraw();
+    }
 }
 
 // Internal functions
@@ -300,6 +522,15 @@ where
     CW: CustomWindow + 'static,
     TW: kas_theme::Window + 'static,
 {
+    /// Time of the next widget timer or deferred frame, if any
+    fn next_resume(&self) -> Option<Instant> {
+        self.mgr
+            .next_resume()
+            .into_iter()
+            .chain(self.frame_due)
+            .min()
+    }
+
     fn apply_size(&mut self) {
         let size = Size(self.sc_desc.width, self.sc_desc.height);
         let rect = Rect::new(Coord::ZERO, size);
@@ -309,6 +540,7 @@ where
         self.solve_cache
             .apply_rect(self.widget.as_widget_mut(), &mut size_handle, rect, true);
         self.widget.resize_popups(&mut size_handle);
+        self.mgr.check_pending(self.widget.as_widget());
 
         let restrict_dimensions = self.widget.restrict_dimensions();
         if restrict_dimensions.0 {
@@ -323,6 +555,18 @@ where
         self.window.request_redraw();
     }
 
+    fn handle_winit<C, T>(&mut self, shared: &mut SharedState<C, T>, event: WindowEvent)
+    where
+        C: CustomPipe<Window = CW>,
+        T: Theme<DrawPipe<C>, Window = TW>,
+    {
+        let mut tkw = TkWindow::new(shared, &self.window, &mut self.theme_window);
+        let widget = &mut *self.widget;
+        self.mgr.with(&mut tkw, |mgr| {
+            mgr.handle_winit(widget, event);
+        });
+    }
+
     fn do_resize<C, T>(&mut self, shared: &mut SharedState<C, T>, size: PhysicalSize<u32>)
     where
         C: CustomPipe<Window = CW>,
@@ -347,12 +591,13 @@ where
         self.apply_size();
     }
 
-    pub(crate) fn do_draw<C, T>(&mut self, shared: &mut SharedState<C, T>)
+    /// Draw widgets to the draw buffers
+    fn draw_widgets<C, T>(&mut self, shared: &mut SharedState<C, T>)
     where
         C: CustomPipe<Window = CW>,
         T: Theme<DrawPipe<C>, Window = TW>,
     {
-        trace!("Window::do_draw");
+        self.draw.colour_filter = shared.colour_filter;
         let size = Size(self.sc_desc.width, self.sc_desc.height);
         let rect = Rect {
             pos: Coord::ZERO,
@@ -363,13 +608,83 @@ where
                 .theme
                 .draw_handle(&mut self.draw, &mut self.theme_window, rect)
         };
-        self.widget.draw(&mut draw_handle, &self.mgr, false);
-        drop(draw_handle);
+        if let Some(path) = shared.draw_capture.take() {
+            let mut records = vec![];
+            let mut recorder = DrawRecorder::new(&mut draw_handle, &mut records);
+            self.widget.draw(&mut recorder, &self.mgr, false);
+            DrawRecorder::annotate(&mut records, &*self.widget);
+            let data = if path.extension().map(|ext| ext == "svg").unwrap_or(false) {
+                DrawRecorder::to_svg(&records, size)
+            } else {
+                DrawRecorder::to_json(&records)
+            };
+            match std::fs::write(&path, data) {
+                Ok(()) => info!("Wrote draw capture to {}", path.display()),
+                Err(e) => warn!("Failed to write draw capture to {}: {}", path.display(), e),
+            }
+        } else {
+            self.widget.draw(&mut draw_handle, &self.mgr, false);
+        }
+    }
+
+    /// Draw a frame
+    ///
+    /// If the frame rate is limited and the last frame was too recent, the
+    /// frame is deferred and its due time returned.
+    pub(crate) fn do_draw<C, T>(&mut self, shared: &mut SharedState<C, T>) -> Option<Instant>
+    where
+        C: CustomPipe<Window = CW>,
+        T: Theme<DrawPipe<C>, Window = TW>,
+    {
+        trace!("Window::do_draw");
+        if let Some(interval) = shared.frame_interval {
+            let now = Instant::now();
+            if let Some(due) = self.frame_time.map(|time| time + interval) {
+                if due > now {
+                    self.frame_due = Some(due);
+                    return Some(due);
+                }
+            }
+            self.frame_time = Some(now);
+        }
+        self.frame_due = None;
+
+        let animate = shared.theme.animate();
+        if animate {
+            // The theme is shared: all windows must redraw until it settles
+            let pending = &mut shared.pending;
+            if !pending
+                .iter()
+                .any(|a| matches!(a, PendingAction::RedrawAll))
+            {
+                pending.push(PendingAction::RedrawAll);
+            }
+        }
+        self.draw_widgets(shared);
+        let frame_requested = self.mgr.take_animation_frame();
+        if self.theme_window.take_redraw() || animate || frame_requested {
+            self.window.request_redraw();
+        }
 
         let frame = self.swap_chain.get_current_frame().unwrap();
         // TODO: check frame.optimal ?
-        let clear_color = to_wgpu_color(shared.theme.clear_colour());
+        let clear_color = shared.colour_filter.apply(shared.theme.clear_colour());
+        let clear_color = to_wgpu_color(clear_color);
         shared.render(&mut self.draw, &frame.output.view, clear_color);
+
+        // The frame is presented on drop
+        drop(frame);
+        let time = Instant::now();
+        let mut tkw = TkWindow::new(shared, &self.window, &mut self.theme_window);
+        let widget = &mut *self.widget;
+        self.mgr.with(&mut tkw, |mgr| {
+            widget.handle_frame(mgr, time);
+        });
+        // Actions are handled on update, which only happens after new events
+        if self.mgr.pending_action() != TkAction::None {
+            self.window.request_redraw();
+        }
+        None
     }
 }
 
@@ -457,6 +772,68 @@ where
         self.shared.set_clipboard(content);
     }
 
+    #[inline]
+    fn get_primary(&mut self) -> Option<String> {
+        self.shared.get_primary()
+    }
+
+    #[inline]
+    fn set_primary<'c>(&mut self, content: std::borrow::Cow<'c, str>) {
+        self.shared.set_primary(content);
+    }
+
+    fn capture(&mut self, f: Box<dyn FnOnce(Capture)>) {
+        let id = self.window.id();
+        self.shared.pending.push(PendingAction::Capture(id, f));
+    }
+
+    fn set_taskbar_progress(&mut self, progress: Option<f32>) {
+        self.shared.taskbar.set_progress(self.window, progress);
+    }
+
+    fn set_taskbar_badge(&mut self, count: Option<u32>) {
+        self.shared.taskbar.set_badge(self.window, count);
+    }
+
+    fn request_attention(&mut self, critical: bool) {
+        self.shared.taskbar.request_attention(self.window, critical);
+    }
+
+    fn inhibit_screensaver(&mut self, inhibit: bool) {
+        // On X11, xdg-screensaver tracks the window and resumes when the
+        // window is destroyed. We do not block the event loop waiting on it.
+        #[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+        {
+            use winit::platform::unix::WindowExtUnix;
+            if let Some(xid) = self.window.xlib_window() {
+                let action = if inhibit { "suspend" } else { "resume" };
+                let xid = format!("{:#x}", xid);
+                std::thread::spawn(move || {
+                    let result = std::process::Command::new("xdg-screensaver")
+                        .args(&[action, &xid])
+                        .status();
+                    match result {
+                        Ok(status) if status.success() => (),
+                        Ok(status) => warn!("xdg-screensaver {} failed: {}", action, status),
+                        Err(e) => warn!("Unable to run xdg-screensaver: {}", e),
+                    }
+                });
+                return;
+            }
+        }
+        debug!("TkWindow::inhibit_screensaver({}): unsupported", inhibit);
+    }
+
+    #[inline]
+    fn ui_scale(&self) -> f32 {
+        self.shared.ui_scale()
+    }
+
+    #[inline]
+    fn set_ui_scale(&mut self, scale: f32) {
+        self.shared.set_ui_scale(scale);
+    }
+
     fn adjust_theme(&mut self, f: &mut dyn FnMut(&mut dyn ThemeApi) -> ThemeAction) {
         match f(&mut self.shared.theme) {
             ThemeAction::None => (),
@@ -475,4 +852,38 @@ where
     fn set_cursor_icon(&mut self, icon: CursorIcon) {
         self.window.set_cursor_icon(icon);
     }
+
+    fn set_cursor_grab(&mut self, grab: bool) -> bool {
+        match self.window.set_cursor_grab(grab) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Failed to set cursor grab: {}", e);
+                false
+            }
+        }
+    }
+
+    #[inline]
+    fn set_cursor_visible(&mut self, visible: bool) {
+        self.window.set_cursor_visible(visible);
+    }
+
+    fn set_cursor_position(&mut self, coord: Coord) {
+        let pos = PhysicalPosition::<i32>::from(coord);
+        if let Err(e) = self.window.set_cursor_position(pos) {
+            warn!("Failed to set cursor position: {}", e);
+        }
+    }
+
+    fn start_drag(&mut self, data: DragData) {
+        self.shared.drag = Some(DragState {
+            data,
+            source: self.window.id(),
+            target: None,
+        });
+    }
+
+    fn end_drag(&mut self, drop: bool) {
+        self.shared.pending.push(PendingAction::EndDrag(drop));
+    }
 }
@@ -0,0 +1,80 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Pixel tests of blending and layer compositing
+//!
+//! These require a display and graphics adapter, thus are ignored by default.
+//! Run with `cargo test -- --ignored`.
+
+use kas::draw::{Colour, DrawRounded, Layer};
+use kas::geom::Quad;
+use kas::prelude::*;
+use kas::widget::Window;
+use kas_wgpu::draw::DrawWindow;
+
+/// Draws on black: an opaque red circle in a layer (left) and a white square
+/// in a layer of half opacity (right)
+#[handler(msg = VoidMsg)]
+#[derive(Clone, Debug, Default, Widget)]
+struct Blend {
+    #[widget_core]
+    core: CoreData,
+}
+
+impl Layout for Blend {
+    fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
+        SizeRules::fixed(64, (0, 0))
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, _: &ManagerState, _: bool) {
+        let rect = draw_handle.target_rect();
+        let (pass, offset, draw) = draw_handle.draw_device();
+        draw.rect(pass, Quad::from(rect + offset), Colour::grey(0.0));
+
+        let half = Size(rect.size.0 / 2, rect.size.1);
+        let left = Rect::new(rect.pos, half);
+        let right = Rect::new(rect.pos + Size(half.0, 0), half);
+
+        draw_handle.layer(left, Layer::new(1.0), &mut |draw_handle| {
+            let (pass, offset, draw) = draw_handle.draw_device();
+            let draw = draw.as_any_mut().downcast_mut::<DrawWindow<()>>().unwrap();
+            draw.circle(
+                pass,
+                Quad::from(left + offset),
+                0.0,
+                Colour::new(1.0, 0.0, 0.0),
+            );
+        });
+        draw_handle.layer(right, Layer::new(0.5), &mut |draw_handle| {
+            let (pass, offset, draw) = draw_handle.draw_device();
+            draw.rect(pass, Quad::from(right + offset), Colour::grey(1.0));
+        });
+    }
+}
+
+fn assert_near(pixel: [u8; 4], expected: [u8; 4]) {
+    for (p, e) in pixel.iter().zip(expected.iter()) {
+        let diff = (i16::from(*p) - i16::from(*e)).abs();
+        assert!(diff <= 2, "pixel {:?} != {:?}", pixel, expected);
+    }
+}
+
+#[test]
+#[ignore]
+fn layer_compositing() {
+    let theme = kas_theme::FlatTheme::new();
+    let mut toolkit = kas_wgpu::Toolkit::new(theme).unwrap();
+    let id = toolkit.add(Window::new("Blend", Blend::default())).unwrap();
+    let capture = toolkit.capture(id).unwrap();
+    let (w, h) = (capture.size.0, capture.size.1);
+
+    // Coverage of content drawn to a layer must be preserved
+    assert_near(capture.pixel(w / 4, h / 2).unwrap(), [255, 0, 0, 255]);
+    // Linear 0.5 is encoded as sRGB 188; it must not be faded twice
+    assert_near(
+        capture.pixel(3 * w / 4, h / 2).unwrap(),
+        [188, 188, 188, 255],
+    );
+}
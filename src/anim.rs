@@ -0,0 +1,203 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Animation utilities
+//!
+//! This module provides easing curves ([`Easing`]) and reversible timelines
+//! ([`Timeline`]) for use by themes and widgets.
+//!
+//! Animations are driven by frames, not by timers: while drawing, a widget
+//! evaluates its timelines at the current time and, if any is still running,
+//! calls [`ManagerState::request_animation_frame`] to have the toolkit draw
+//! another frame. For example:
+//!
+//! ```
+//! use kas::anim::{Easing, Timeline};
+//! use kas::Instant;
+//! use std::time::Duration;
+//!
+//! let mut knob = Timeline::new(Duration::from_millis(200), Easing::EaseInOut);
+//! let start = Instant::now();
+//! knob.set_target(true, start);
+//! assert_eq!(knob.value(start), 0.0);
+//! assert!(knob.is_running(start));
+//!
+//! let end = start + Duration::from_millis(200);
+//! assert_eq!(knob.value(end), 1.0);
+//! assert!(!knob.is_running(end));
+//! ```
+//!
+//! [`ManagerState::request_animation_frame`]: crate::event::ManagerState::request_animation_frame
+
+use crate::Instant;
+use std::time::Duration;
+
+/// An easing curve
+///
+/// Maps linear progress in the range `0..=1` to eased progress, with
+/// `0 → 0` and `1 → 1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Easing {
+    /// Constant speed
+    Linear,
+    /// Start slowly, accelerating (cubic)
+    EaseIn,
+    /// Start quickly, decelerating (cubic)
+    EaseOut,
+    /// Start and end slowly (cubic)
+    EaseInOut,
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::EaseInOut
+    }
+}
+
+impl Easing {
+    /// Apply to linear progress `t`
+    ///
+    /// Input is clamped to the range `0..=1`.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.max(0.0).min(1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => {
+                let s = 1.0 - t;
+                1.0 - s * s * s
+            }
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    let s = 2.0 - 2.0 * t;
+                    1.0 - 0.5 * s * s * s
+                }
+            }
+        }
+    }
+}
+
+/// A reversible timeline
+///
+/// The timeline runs between a rest position at 0 and an active position
+/// at 1, moving towards its target at a constant rate (one `duration` for the
+/// full distance). When the target changes mid-way (e.g. the mouse leaves a
+/// button before its hover highlight has faded in), the timeline reverses
+/// from its current position.
+///
+/// Timelines do not store the time; each method takes the current time
+/// (`now`), usually the time at which the frame is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timeline {
+    duration: Duration,
+    easing: Easing,
+    /// Linear progress at `start`
+    from: f32,
+    start: Instant,
+    forward: bool,
+}
+
+impl Timeline {
+    /// Construct, at rest (at 0)
+    pub fn new(duration: Duration, easing: Easing) -> Self {
+        Timeline {
+            duration,
+            easing,
+            from: 0.0,
+            start: Instant::now(),
+            forward: false,
+        }
+    }
+
+    /// Set the target: active (`true`) or rest (`false`)
+    ///
+    /// Does nothing if the target is unchanged.
+    pub fn set_target(&mut self, active: bool, now: Instant) {
+        if active != self.forward {
+            self.from = self.progress(now);
+            self.start = now;
+            self.forward = active;
+        }
+    }
+
+    /// Get the target
+    #[inline]
+    pub fn target(&self) -> bool {
+        self.forward
+    }
+
+    /// Jump to the target position, completing any transition
+    pub fn finish(&mut self) {
+        self.from = if self.forward { 1.0 } else { 0.0 };
+    }
+
+    /// Linear progress, in the range `0..=1`
+    pub fn progress(&self, now: Instant) -> f32 {
+        let elapsed = now.saturating_duration_since(self.start);
+        let delta = if self.duration == Duration::from_secs(0) {
+            1.0
+        } else {
+            elapsed.as_secs_f32() / self.duration.as_secs_f32()
+        };
+        if self.forward {
+            (self.from + delta).min(1.0)
+        } else {
+            (self.from - delta).max(0.0)
+        }
+    }
+
+    /// Eased progress, in the range `0..=1`
+    pub fn value(&self, now: Instant) -> f32 {
+        self.easing.apply(self.progress(now))
+    }
+
+    /// True if the target has not yet been reached
+    pub fn is_running(&self, now: Instant) -> bool {
+        let p = self.progress(now);
+        if self.forward {
+            p < 1.0
+        } else {
+            p > 0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn easing_end_points() {
+        for easing in &[
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            assert_eq!(easing.apply(2.0), 1.0);
+        }
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn timeline_reverses() {
+        let ms = Duration::from_millis;
+        let start = Instant::now();
+        let mut t = Timeline::new(ms(100), Easing::Linear);
+        t.set_target(true, start);
+        let mid = start + ms(40);
+        assert!((t.progress(mid) - 0.4).abs() < 1e-4);
+
+        // Reversing returns to rest in the time taken so far
+        t.set_target(false, mid);
+        assert!(t.is_running(mid + ms(30)));
+        assert_eq!(t.progress(mid + ms(40)), 0.0);
+        assert!(!t.is_running(mid + ms(40)));
+    }
+}
@@ -0,0 +1,192 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Command utilities
+//!
+//! Standard shortcuts are mapped to [`ControlKey`] values by the event
+//! manager: for example, <kbd>Ctrl+Z</kbd> yields [`ControlKey::Undo`] while
+//! <kbd>Ctrl+Shift+Z</kbd> and <kbd>Ctrl+Y</kbd> yield [`ControlKey::Redo`].
+//! Widgets receiving these as [`Event::Control`] may use an [`UndoStack`] to
+//! implement history.
+//!
+//! [`Event::Control`]: crate::event::Event::Control
+
+use crate::event::ControlKey;
+
+/// An undo/redo history
+///
+/// The stack stores *states* of type `T` (for example, a snapshot of a text
+/// buffer and cursor position). Before each edit, the widget pushes the state
+/// prior to the edit; undo then swaps the current state for the last saved
+/// state, and redo reverses this.
+///
+/// Consecutive edits of the same kind (e.g. typing characters) may be merged
+/// into a single undo unit via [`UndoStack::push_merge`].
+///
+/// ```
+/// use kas::command::UndoStack;
+/// use kas::event::ControlKey;
+///
+/// let mut stack = UndoStack::new();
+/// let mut text = String::new();
+/// for c in "abc".chars() {
+///     stack.push_merge(0, || text.clone());
+///     text.push(c);
+/// }
+/// if let Some(state) = stack.handle_key(ControlKey::Undo, text.clone()) {
+///     text = state;
+/// }
+/// assert_eq!(text, "");
+/// ```
+#[derive(Clone, Debug)]
+pub struct UndoStack<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    merge: Option<u32>,
+    limit: usize,
+}
+
+impl<T> Default for UndoStack<T> {
+    fn default() -> Self {
+        UndoStack::new()
+    }
+}
+
+impl<T> UndoStack<T> {
+    /// Construct an empty stack with unlimited depth
+    pub fn new() -> Self {
+        UndoStack::with_limit(usize::MAX)
+    }
+
+    /// Construct an empty stack, holding at most `limit` undo states
+    ///
+    /// When the limit is exceeded, the oldest state is discarded.
+    pub fn with_limit(limit: usize) -> Self {
+        UndoStack {
+            undo: vec![],
+            redo: vec![],
+            merge: None,
+            limit: limit.max(1),
+        }
+    }
+
+    /// Clear all history
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.merge = None;
+    }
+
+    /// True if there is a state to undo to
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// True if there is a state to redo to
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Push the state prior to an edit as a new undo unit
+    ///
+    /// This clears the redo history and ends any merge group.
+    pub fn push(&mut self, state: T) {
+        self.merge = None;
+        self.push_state(state);
+    }
+
+    /// Push the state prior to an edit, merging with the previous edit
+    ///
+    /// If the previous push was a call to this method with the same `group`
+    /// (and neither [`UndoStack::end_merge`], [`UndoStack::undo`] nor
+    /// [`UndoStack::redo`] has been called since), the edit becomes part of
+    /// the previous undo unit and `state` is not called. Otherwise this is
+    /// equivalent to [`UndoStack::push`].
+    ///
+    /// The value of `group` is chosen by the caller, e.g. one value for typing
+    /// and another for deletion.
+    pub fn push_merge<F: FnOnce() -> T>(&mut self, group: u32, state: F) {
+        if self.merge == Some(group) {
+            self.redo.clear();
+            return;
+        }
+        self.push_state(state());
+        self.merge = Some(group);
+    }
+
+    /// End the current merge group, if any
+    ///
+    /// The next edit will start a new undo unit. This should be called e.g.
+    /// when the cursor is moved.
+    pub fn end_merge(&mut self) {
+        self.merge = None;
+    }
+
+    fn push_state(&mut self, state: T) {
+        self.redo.clear();
+        self.push_undo(state);
+    }
+
+    fn push_undo(&mut self, state: T) {
+        if self.undo.len() >= self.limit {
+            self.undo.remove(0);
+        }
+        self.undo.push(state);
+    }
+
+    /// Undo, given the `current` state
+    ///
+    /// If a saved state exists, `current` is moved to the redo history and
+    /// the saved state is returned; otherwise `None` is returned.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        self.merge = None;
+        let state = self.undo.pop()?;
+        self.redo.push(current);
+        Some(state)
+    }
+
+    /// Redo, given the `current` state
+    ///
+    /// If a state was undone, `current` is moved to the undo history and the
+    /// undone state is returned; otherwise `None` is returned.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        self.merge = None;
+        let state = self.redo.pop()?;
+        self.push_undo(current);
+        Some(state)
+    }
+
+    /// Handle [`ControlKey::Undo`] and [`ControlKey::Redo`]
+    ///
+    /// Returns the state to restore, if any. Other keys are ignored (and
+    /// `None` is returned).
+    pub fn handle_key(&mut self, key: ControlKey, current: T) -> Option<T> {
+        match key {
+            ControlKey::Undo => self.undo(current),
+            ControlKey::Redo => self.redo(current),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn undo_stack() {
+    let mut stack = UndoStack::with_limit(2);
+    stack.push_merge(1, || 0);
+    stack.push_merge(1, || 1);
+    stack.push(2);
+    stack.push(3);
+    assert_eq!(stack.undo(4), Some(3));
+    assert_eq!(stack.undo(3), Some(2));
+    assert_eq!(stack.undo(2), None);
+    assert_eq!(stack.redo(2), Some(3));
+    stack.push(3);
+    assert!(!stack.can_redo());
+    assert_eq!(stack.undo.len(), 2);
+    assert_eq!(stack.redo(4), None);
+    assert_eq!(stack.undo(4), Some(3));
+    assert_eq!(stack.redo(3), Some(4));
+    assert_eq!(stack.undo.len(), 2);
+}
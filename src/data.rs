@@ -12,6 +12,7 @@ use std::u32;
 
 use super::Align;
 use crate::geom::{Rect, Size};
+use crate::layout::LayoutCache;
 
 /// Widget identifier
 ///
@@ -95,6 +96,16 @@ pub struct CoreData {
     pub rect: Rect,
     pub id: WidgetId,
     pub disabled: bool,
+    pub hidden: bool,
+    pub layout: LayoutCache,
+    /// Set to request configuration of this widget's subtree
+    ///
+    /// See [`kas::event::ManagerState::configure_pending`].
+    pub reconfigure: bool,
+    /// Name used by themes to style this widget
+    ///
+    /// See [`kas::WidgetCore::with_style_name`].
+    pub style_name: Option<&'static str>,
 }
 
 /// Partial alignment information provided by the parent
@@ -129,6 +140,17 @@ impl AlignHints {
         Self { horiz, vert }
     }
 
+    /// Combine with `fallback`
+    ///
+    /// Hints from `self` take precedence; `fallback` is used on axes where
+    /// `self` provides no hint.
+    pub fn combine(self, fallback: AlignHints) -> AlignHints {
+        AlignHints {
+            horiz: self.horiz.or(fallback.horiz),
+            vert: self.vert.or(fallback.vert),
+        }
+    }
+
     /// Unwrap type's alignments or substitute parameters
     pub fn unwrap_or(self, horiz: Align, vert: Align) -> (Align, Align) {
         (self.horiz.unwrap_or(horiz), self.vert.unwrap_or(vert))
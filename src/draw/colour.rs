@@ -7,6 +7,7 @@
 
 /// Standard colour description
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Colour {
     pub r: f32,
     pub g: f32,
@@ -24,6 +25,24 @@ impl Colour {
     pub const fn grey(s: f32) -> Self {
         Colour::new(s, s, s)
     }
+
+    /// Relative luminance
+    ///
+    /// Computed as defined by WCAG 2.0, assuming that components are in
+    /// linear RGB space. Alpha is ignored.
+    pub fn relative_luminance(self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    /// Contrast ratio with `other`
+    ///
+    /// Computed as defined by WCAG 2.0; the result is between 1 (no contrast)
+    /// and 21 (black on white). The order of colours is not important.
+    pub fn contrast_ratio(self, other: Colour) -> f32 {
+        let a = self.relative_luminance();
+        let b = other.relative_luminance();
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
 }
 
 impl From<Colour> for [f32; 4] {
@@ -31,3 +50,217 @@ impl From<Colour> for [f32; 4] {
         [c.r, c.g, c.b, c.a]
     }
 }
+
+/// A simplified colour palette
+///
+/// This specifies a small set of key colours from which themes derive their
+/// complete set of colours, allowing an application to use its own colours
+/// without implementing a whole theme. Built-in themes take their preset
+/// colours from the schemes listed in [`ColourScheme::open`].
+///
+/// Schemes may be applied via [`ThemeApi::set_colour_scheme`] or, in
+/// `kas_theme`, by the `with_colour_scheme` method of the theme.
+///
+/// [`ThemeApi::set_colour_scheme`]: crate::ThemeApi::set_colour_scheme
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColourScheme {
+    /// Background colour
+    pub background: Colour,
+    /// Colour for frames (not always used)
+    pub frame: Colour,
+    /// Background colour of `EditBox`
+    pub edit_bg: Colour,
+    /// Background colour of `EditBox` (disabled state)
+    pub edit_disabled: Colour,
+    /// Text colour of labels and in an `EditBox`
+    pub text: Colour,
+    /// Accent colour: buttons, menu highlights
+    pub accent: Colour,
+    /// Accent colour when hovered by the mouse
+    pub accent_hover: Colour,
+    /// Accent colour when depressed
+    pub accent_depressed: Colour,
+    /// Text colour on accent-coloured items (e.g. a `TextButton`)
+    pub accent_text: Colour,
+    /// Colour of mark within a `CheckBox` or `RadioBox`
+    pub check_mark: Colour,
+    /// Selected text background colour
+    pub selection: Colour,
+    /// Selected text colour
+    pub selection_text: Colour,
+    /// Background colour of `EditBox` (error state)
+    pub error: Colour,
+    /// Highlight colour for keyboard navigation
+    pub nav_focus: Colour,
+    /// Colour of disabled items
+    pub disabled: Colour,
+}
+
+impl ColourScheme {
+    /// Open the given built-in scheme, if found
+    ///
+    /// Supported names are `default` (alias `white`), `grey`, `light` and
+    /// `dark`.
+    pub fn open(scheme: &str) -> Option<Self> {
+        Some(match scheme {
+            "default" | "white" => Self::new(),
+            "grey" => Self::grey(),
+            "light" => Self::light(),
+            "dark" => Self::dark(),
+            _ => return None,
+        })
+    }
+
+    /// Default scheme: white with blue activable items
+    pub fn new() -> Self {
+        ColourScheme {
+            background: Colour::grey(1.0),
+            frame: Colour::grey(0.7),
+            edit_bg: Colour::grey(1.0),
+            edit_disabled: Colour::grey(0.85),
+            text: Colour::grey(0.0),
+            accent: Colour::new(0.2, 0.7, 1.0),
+            accent_hover: Colour::new(0.25, 0.8, 1.0),
+            accent_depressed: Colour::new(0.15, 0.525, 0.75),
+            accent_text: Colour::grey(1.0),
+            check_mark: Colour::new(0.2, 0.7, 1.0),
+            selection: Colour::new(0.15, 0.525, 0.75),
+            selection_text: Colour::grey(1.0),
+            error: Colour::new(1.0, 0.5, 0.5),
+            nav_focus: Colour::new(1.0, 0.7, 0.5),
+            disabled: Colour::grey(0.5),
+        }
+    }
+
+    /// Grey with blue activable items
+    pub fn grey() -> Self {
+        ColourScheme::new().with_background(Colour::grey(0.8))
+    }
+
+    /// Light scheme
+    pub fn light() -> Self {
+        ColourScheme {
+            background: Colour::grey(0.9),
+            frame: Colour::new(0.8, 0.8, 0.9),
+            edit_bg: Colour::grey(1.0),
+            edit_disabled: Colour::grey(0.85),
+            text: Colour::grey(0.0),
+            accent: Colour::new(1.0, 0.9, 0.3),
+            accent_hover: Colour::new(1.0, 0.95, 0.6),
+            accent_depressed: Colour::new(0.8, 0.72, 0.24),
+            accent_text: Colour::grey(0.0),
+            check_mark: Colour::grey(0.4),
+            selection: Colour::new(0.8, 0.72, 0.24),
+            selection_text: Colour::grey(0.0),
+            error: Colour::new(1.0, 0.5, 0.5),
+            nav_focus: Colour::new(1.0, 0.7, 0.5),
+            disabled: Colour::grey(0.6),
+        }
+    }
+
+    /// Dark scheme
+    pub fn dark() -> Self {
+        ColourScheme {
+            background: Colour::grey(0.2),
+            frame: Colour::grey(0.4),
+            edit_bg: Colour::grey(0.1),
+            edit_disabled: Colour::grey(0.3),
+            text: Colour::grey(1.0),
+            accent: Colour::new(0.5, 0.1, 0.1),
+            accent_hover: Colour::new(0.6, 0.3, 0.1),
+            accent_depressed: Colour::new(0.3, 0.1, 0.1),
+            accent_text: Colour::grey(1.0),
+            check_mark: Colour::new(0.5, 0.1, 0.1),
+            selection: Colour::new(0.6, 0.3, 0.1),
+            selection_text: Colour::grey(1.0),
+            error: Colour::new(1.0, 0.5, 0.5),
+            nav_focus: Colour::new(1.0, 0.7, 0.5),
+            disabled: Colour::grey(0.7),
+        }
+    }
+
+    /// Set the background colour (chain style)
+    pub fn with_background(mut self, col: Colour) -> Self {
+        self.background = col;
+        self
+    }
+
+    /// Set the frame colour (chain style)
+    pub fn with_frame(mut self, col: Colour) -> Self {
+        self.frame = col;
+        self
+    }
+
+    /// Set the text colour (chain style)
+    pub fn with_text(mut self, col: Colour) -> Self {
+        self.text = col;
+        self
+    }
+
+    /// Set the accent colour (chain style)
+    ///
+    /// Hover and depressed states are derived by mixing with white and black
+    /// respectively, and the check mark uses the accent colour. The text
+    /// colour is chosen as black or white, whichever gives the greater
+    /// contrast.
+    pub fn with_accent(mut self, col: Colour) -> Self {
+        self.accent = col;
+        self.accent_hover = mix(col, Colour::grey(1.0), 0.2);
+        self.accent_depressed = mix(col, Colour::grey(0.0), 0.25);
+        self.accent_text = contrasting_text(col);
+        self.check_mark = col;
+        self
+    }
+
+    /// Set the selected-text background colour (chain style)
+    ///
+    /// The selected text colour is chosen as black or white, whichever gives
+    /// the greater contrast.
+    pub fn with_selection(mut self, col: Colour) -> Self {
+        self.selection = col;
+        self.selection_text = contrasting_text(col);
+        self
+    }
+
+    /// Set the error colour (chain style)
+    pub fn with_error(mut self, col: Colour) -> Self {
+        self.error = col;
+        self
+    }
+}
+
+impl Default for ColourScheme {
+    fn default() -> Self {
+        ColourScheme::new()
+    }
+}
+
+/// Mix colours `a` and `b`, where `t = 0` yields `a` and `t = 1` yields `b`
+fn mix(a: Colour, b: Colour, t: f32) -> Colour {
+    let s = 1.0 - t;
+    Colour {
+        r: a.r * s + b.r * t,
+        g: a.g * s + b.g * t,
+        b: a.b * s + b.b * t,
+        a: a.a * s + b.a * t,
+    }
+}
+
+/// Black or white, whichever has the greater contrast with `bg`
+fn contrasting_text(bg: Colour) -> Colour {
+    let (white, black) = (Colour::grey(1.0), Colour::grey(0.0));
+    if white.contrast_ratio(bg) >= black.contrast_ratio(bg) {
+        white
+    } else {
+        black
+    }
+}
+
+#[test]
+fn contrast_ratio() {
+    let black = Colour::grey(0.0);
+    let white = Colour::grey(1.0);
+    assert!((black.contrast_ratio(white) - 21.0).abs() < 1e-4);
+    assert!((white.contrast_ratio(black) - 21.0).abs() < 1e-4);
+    assert_eq!(white.contrast_ratio(white), 1.0);
+}
@@ -7,16 +7,29 @@
 
 use std::ops::{Bound, Deref, DerefMut, Range, RangeBounds};
 
-use kas::draw::{Draw, Pass};
+use kas::draw::{Colour, Draw, FillRule, Icon, ImageId, Layer, Pass, Path, StrokeStyle};
 use kas::geom::{Coord, Rect, Size};
 use kas::layout::{AxisInfo, Margins, SizeRules};
 use kas::text::PreparedText;
 use kas::Direction;
 
 /// Classification of a clip region
+///
+/// Clip regions are rectangular.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ClipRegion {
+    /// A pop-up, drawn above other content and not clipped by its parent
     Popup,
+    /// The viewport of a scroll region, clipped by its parent
     Scroll,
+    /// A region within the parent (e.g. a table cell or a partially revealed
+    /// pane), clipped by its parent
+    Clip,
+    /// An overlapping layer within the parent, clipped by its parent
+    ///
+    /// Layers are drawn above other content of the parent (in order of index)
+    /// but below pop-ups. At most 64 layers are distinguished.
+    Layer(u8),
 }
 
 /// Input and highlighting state of a widget
@@ -44,6 +57,11 @@ pub struct InputState {
     /// "Character focus" implies this widget is ready to receive text input
     /// (e.g. typing into an input field).
     pub char_focus: bool,
+    /// Style name of the widget drawn, if any
+    ///
+    /// Themes may use this to style individual widgets (see
+    /// [`kas::WidgetCore::with_style_name`]).
+    pub style_name: Option<&'static str>,
 }
 
 impl std::ops::BitOr for InputState {
@@ -57,14 +75,37 @@ impl std::ops::BitOr for InputState {
             depress: self.depress || rhs.depress,
             nav_focus: self.nav_focus || rhs.nav_focus,
             char_focus: self.char_focus || rhs.char_focus,
+            style_name: self.style_name.or(rhs.style_name),
         }
     }
 }
 
+/// Shape of a selection highlight or focus ring
+///
+/// See [`DrawHandle::selection`] and [`DrawHandle::focus_ring`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HighlightShape {
+    /// A rectangle
+    Rect,
+    /// A rectangle with rounded corners of the given radius
+    ///
+    /// The radius is in logical pixels (multiplied by the scale factor).
+    Rounded(f32),
+    /// An oval filling the target rect
+    Oval,
+}
+
+impl Default for HighlightShape {
+    fn default() -> Self {
+        HighlightShape::Rect
+    }
+}
+
 /// Class of text drawn
 ///
 /// Themes choose font, font size, colour, and alignment based on this.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextClass {
     /// Label text is drawn over the background colour
     Label,
@@ -92,6 +133,36 @@ impl Default for TextClass {
     }
 }
 
+/// Kind of a [`TextHighlight`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HighlightKind {
+    /// Selected text, drawn with selection text and background colours
+    Selection,
+    /// A match (e.g. of a search), drawn over a highlight colour
+    Match,
+    /// An error (e.g. a misspelling), underlined
+    Error,
+}
+
+/// A highlighted range of text
+///
+/// See [`DrawHandle::text_highlighted`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TextHighlight {
+    /// The highlighted byte range
+    pub range: Range<usize>,
+    /// Kind of highlight
+    pub kind: HighlightKind,
+}
+
+impl TextHighlight {
+    /// Construct
+    #[inline]
+    pub fn new(range: Range<usize>, kind: HighlightKind) -> Self {
+        TextHighlight { range, kind }
+    }
+}
+
 /// Handle passed to objects during draw and sizing operations
 ///
 /// This handle is provided by the toolkit (usually via a theme implementation)
@@ -138,6 +209,12 @@ pub trait SizeHandle {
     /// The height of a line of text
     fn line_height(&self, class: TextClass) -> u32;
 
+    /// Font size of standard text, in pixels per em
+    ///
+    /// This accounts for both the theme's font size and the scale factor. It
+    /// is used to resolve font-relative lengths (see [`kas::layout::Length`]).
+    fn dpem(&self) -> f32;
+
     /// Update a [`PreparedText`] and get a size bound
     ///
     /// First, this method updates the text's [`Environment`]: `bounds`, `dpp`
@@ -158,9 +235,37 @@ pub trait SizeHandle {
         axis: AxisInfo,
     ) -> SizeRules;
 
+    /// Measure `text` as displayed in the given `class`
+    ///
+    /// This prepares a new [`PreparedText`] via [`SizeHandle::text_bound`],
+    /// wrapping at `width` (if given and `class` is a wrapping class). The
+    /// result may be queried for its size via [`PreparedText::required_size`]
+    /// and for caret and line geometry via [`kas::text::PreparedTextExt`],
+    /// hence widgets need not duplicate layout logic.
+    fn text_measure(&mut self, text: &str, class: TextClass, width: Option<u32>) -> PreparedText {
+        let mut prepared = match class.line_wrap() {
+            true => PreparedText::new_multi(text.into()),
+            false => PreparedText::new_single(text.into()),
+        };
+        self.text_bound(&mut prepared, class, AxisInfo::new(true, width));
+        prepared
+    }
+
+    /// Minimum and ideal width of a line of wrapped text
+    ///
+    /// These bounds are used by [`SizeHandle::text_bound`] for wrapping text
+    /// classes.
+    fn line_length(&self) -> (u32, u32);
+
     /// Width of an edit marker
     fn edit_marker_width(&self) -> f32;
 
+    /// Width of the ring drawn by [`DrawHandle::focus_ring`]
+    ///
+    /// Widgets drawing a focus ring within their margin area may use this to
+    /// size the margin.
+    fn focus_ring_width(&self) -> f32;
+
     /// Size of the sides of a button.
     ///
     /// Returns `(top_left, bottom_right)` dimensions as two `Size`s.
@@ -179,6 +284,11 @@ pub trait SizeHandle {
     /// Size of the element drawn by [`DrawHandle::radiobox`].
     fn radiobox(&self) -> Size;
 
+    /// Standard size of an icon drawn by [`DrawHandle::icon`]
+    ///
+    /// This matches the height of a line of text.
+    fn icon(&self) -> Size;
+
     /// Dimensions for a scrollbar
     ///
     /// Returns:
@@ -243,6 +353,8 @@ pub trait DrawHandle {
     ///
     /// All content drawn by the new region is clipped to the given `rect`
     /// (in the current coordinate space, i.e. not translated by `offset`).
+    /// Except for [`ClipRegion::Popup`], content is additionally clipped to
+    /// the current region, hence clip regions may be nested.
     fn clip_region(
         &mut self,
         rect: Rect,
@@ -251,6 +363,40 @@ pub trait DrawHandle {
         f: &mut dyn FnMut(&mut dyn DrawHandle),
     );
 
+    /// Draw content via a render cache
+    ///
+    /// If `valid` and content previously drawn under `key` is available, that
+    /// content is re-used and `f` is not called. Otherwise, `f` is called with
+    /// a new draw-handle whose output is cached for re-use in later frames
+    /// (see [`Draw::add_cached_region`]).
+    ///
+    /// The new handle uses the same coordinates as `self`; content is clipped
+    /// to the given `rect` and the current clip region.
+    ///
+    /// The default implementation does not cache: it calls `f` via
+    /// [`DrawHandle::clip_region`].
+    fn cached_region(
+        &mut self,
+        key: u64,
+        rect: Rect,
+        valid: bool,
+        f: &mut dyn FnMut(&mut dyn DrawHandle),
+    ) {
+        let _ = (key, valid);
+        self.clip_region(rect, Coord::ZERO, ClipRegion::Clip, f);
+    }
+
+    /// Draw content via a compositing layer
+    ///
+    /// Content drawn by `f` is rendered to an intermediate layer, then
+    /// composited with the opacity and transform of `layer` (see
+    /// [`Draw::add_layer`]). This may be used to fade pop-ups, dim disabled
+    /// panes or draw drag ghosts.
+    ///
+    /// The new handle uses the same coordinates as `self`; content is clipped
+    /// to the given `rect` and the current clip region.
+    fn layer(&mut self, rect: Rect, layer: Layer, f: &mut dyn FnMut(&mut dyn DrawHandle));
+
     /// Target area for drawing
     ///
     /// If this instance of [`DrawHandle`] was created via
@@ -274,10 +420,18 @@ pub trait DrawHandle {
     /// Draw some text using the standard font
     ///
     /// The `text` is drawn within the rect from `pos` to `text.env().bounds`,
-    /// but offset by subtracting `offset` (allowing scrolling).
+    /// but offset by subtracting `offset` (allowing scrolling). Themes may
+    /// style the text according to the `state` of the widget drawing it.
     ///
     /// The dimensions required for this text may be queried with [`SizeHandle::text_bound`].
-    fn text_offset(&mut self, pos: Coord, offset: Coord, text: &PreparedText, class: TextClass);
+    fn text_offset(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+    );
 
     /// Draw some text, with an underlined glyph
     ///
@@ -289,6 +443,7 @@ pub trait DrawHandle {
         offset: Coord,
         text: &PreparedText,
         class: TextClass,
+        state: InputState,
         underline: usize,
     );
 
@@ -301,6 +456,23 @@ pub trait DrawHandle {
         text: &PreparedText,
         range: Range<usize>,
         class: TextClass,
+        state: InputState,
+    );
+
+    /// Draw some text with highlighted ranges
+    ///
+    /// This behaves as [`DrawHandle::text_offset`], additionally drawing each
+    /// of `highlights` in the theme's style for its [`HighlightKind`].
+    /// Highlights of different kinds may overlap; ranges of kind
+    /// [`HighlightKind::Selection`] should not overlap each other.
+    fn text_highlighted(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+        highlights: &[TextHighlight],
     );
 
     /// Draw an edit marker at the given `byte` index on this `text`
@@ -349,6 +521,68 @@ pub trait DrawHandle {
     /// -   `dir`: direction of slider (currently only LTR or TTB)
     /// -   `state`: highlighting information
     fn slider(&mut self, rect: Rect, h_rect: Rect, dir: Direction, state: InputState);
+
+    /// Draw an icon
+    ///
+    /// Standard icons are drawn by the theme to fill `rect` (preserving
+    /// aspect ratio). Custom images are scaled to fill `rect`.
+    fn icon(&mut self, rect: Rect, icon: &Icon, state: InputState);
+
+    /// Draw an uploaded image, scaled to fill `rect`
+    ///
+    /// Images are uploaded via [`kas::draw::DrawImage`] (see
+    /// [`Draw::as_draw_image`]). Nothing is drawn if this is unsupported or
+    /// `id` is not allocated.
+    fn image(&mut self, rect: Rect, id: ImageId);
+
+    /// Fill the area enclosed by `path`
+    ///
+    /// The path uses the coordinate space of this handle. Nothing is drawn
+    /// if paths are unsupported (see [`Draw::as_draw_path`]).
+    fn fill_path(&mut self, path: &Path, rule: FillRule, col: Colour);
+
+    /// Stroke the outline of `path`
+    ///
+    /// The path uses the coordinate space of this handle. Nothing is drawn
+    /// if paths are unsupported (see [`Draw::as_draw_path`]).
+    fn stroke_path(&mut self, path: &Path, style: &StrokeStyle, col: Colour);
+
+    /// Draw a selection highlight
+    ///
+    /// This fills `rect` (with the given `shape`) using the theme's selection
+    /// colour. Widgets such as lists, grids and calendars should draw this
+    /// behind the content of selected items.
+    fn selection(&mut self, rect: Rect, shape: HighlightShape, state: InputState);
+
+    /// Draw a keyboard-navigation focus ring
+    ///
+    /// The ring is drawn along the inside edge of `rect` (with the given
+    /// `shape`), with a theme-defined width, only if `state.nav_focus` is
+    /// true (and the widget is not disabled). Widgets usually pass a rect
+    /// including their margin area.
+    fn focus_ring(&mut self, rect: Rect, shape: HighlightShape, state: InputState);
+
+    /// Draw a drop shadow under an element occupying `rect`
+    ///
+    /// The shadow extends outside of `rect` (below and to the right) by up to
+    /// the size of [`SizeHandle::frame`]. It should be drawn before the
+    /// element, within a clip region large enough to contain it (as for
+    /// pop-ups).
+    fn shadow(&mut self, rect: Rect);
+
+    /// Draw an inset "well" background
+    ///
+    /// This is a recessed area for content such as lists, drawn with a frame
+    /// whose dimensions equal those of [`SizeHandle::frame`] on each side.
+    fn well(&mut self, rect: Rect, state: InputState);
+
+    /// Draw a notification badge
+    ///
+    /// The badge background fills `rect`, as a "pill" shape. The `text`
+    /// (e.g. a count, possibly empty) is drawn over the background at
+    /// `rect.pos` in a contrasting colour; usually it should be prepared with
+    /// bounds `rect.size` and centred alignment.
+    fn badge(&mut self, rect: Rect, text: &PreparedText, state: InputState);
 }
 
 /// Extension trait over [`DrawHandle`]
@@ -375,8 +609,8 @@ pub trait DrawHandleExt: DrawHandle {
     /// The `text` is drawn within the rect from `pos` to `text.env().bounds`.
     ///
     /// The dimensions required for this text may be queried with [`SizeHandle::text_bound`].
-    fn text(&mut self, pos: Coord, text: &PreparedText, class: TextClass) {
-        self.text_offset(pos, Coord::ZERO, text, class);
+    fn text(&mut self, pos: Coord, text: &PreparedText, class: TextClass, state: InputState) {
+        self.text_offset(pos, Coord::ZERO, text, class, state);
     }
 
     /// Draw some text using the standard font, with a subset selected
@@ -391,6 +625,7 @@ pub trait DrawHandleExt: DrawHandle {
         text: &PreparedText,
         range: R,
         class: TextClass,
+        state: InputState,
     ) {
         let start = match range.start_bound() {
             Bound::Included(n) => *n,
@@ -403,7 +638,7 @@ pub trait DrawHandleExt: DrawHandle {
             Bound::Unbounded => text.text_len(),
         };
         let range = Range { start, end };
-        self.text_selected_range(pos, offset, text, range, class);
+        self.text_selected_range(pos, offset, text, range, class, state);
     }
 }
 
@@ -430,6 +665,9 @@ impl<S: SizeHandle> SizeHandle for Box<S> {
     fn line_height(&self, class: TextClass) -> u32 {
         self.deref().line_height(class)
     }
+    fn dpem(&self) -> f32 {
+        self.deref().dpem()
+    }
     fn text_bound(
         &mut self,
         text: &mut PreparedText,
@@ -438,10 +676,18 @@ impl<S: SizeHandle> SizeHandle for Box<S> {
     ) -> SizeRules {
         self.deref_mut().text_bound(text, class, axis)
     }
+    fn line_length(&self) -> (u32, u32) {
+        self.deref().line_length()
+    }
+
     fn edit_marker_width(&self) -> f32 {
         self.deref().edit_marker_width()
     }
 
+    fn focus_ring_width(&self) -> f32 {
+        self.deref().focus_ring_width()
+    }
+
     fn button_surround(&self) -> (Size, Size) {
         self.deref().button_surround()
     }
@@ -455,6 +701,9 @@ impl<S: SizeHandle> SizeHandle for Box<S> {
     fn radiobox(&self) -> Size {
         self.deref().radiobox()
     }
+    fn icon(&self) -> Size {
+        self.deref().icon()
+    }
     fn scrollbar(&self) -> (Size, u32) {
         self.deref().scrollbar()
     }
@@ -488,6 +737,9 @@ where
     fn line_height(&self, class: TextClass) -> u32 {
         self.deref().line_height(class)
     }
+    fn dpem(&self) -> f32 {
+        self.deref().dpem()
+    }
     fn text_bound(
         &mut self,
         text: &mut PreparedText,
@@ -496,10 +748,18 @@ where
     ) -> SizeRules {
         self.deref_mut().text_bound(text, class, axis)
     }
+    fn line_length(&self) -> (u32, u32) {
+        self.deref().line_length()
+    }
+
     fn edit_marker_width(&self) -> f32 {
         self.deref().edit_marker_width()
     }
 
+    fn focus_ring_width(&self) -> f32 {
+        self.deref().focus_ring_width()
+    }
+
     fn button_surround(&self) -> (Size, Size) {
         self.deref().button_surround()
     }
@@ -513,6 +773,9 @@ where
     fn radiobox(&self) -> Size {
         self.deref().radiobox()
     }
+    fn icon(&self) -> Size {
+        self.deref().icon()
+    }
     fn scrollbar(&self) -> (Size, u32) {
         self.deref().scrollbar()
     }
@@ -537,6 +800,18 @@ impl<H: DrawHandle> DrawHandle for Box<H> {
     ) {
         self.deref_mut().clip_region(rect, offset, class, f)
     }
+    fn cached_region(
+        &mut self,
+        key: u64,
+        rect: Rect,
+        valid: bool,
+        f: &mut dyn FnMut(&mut dyn DrawHandle),
+    ) {
+        self.deref_mut().cached_region(key, rect, valid, f)
+    }
+    fn layer(&mut self, rect: Rect, layer: Layer, f: &mut dyn FnMut(&mut dyn DrawHandle)) {
+        self.deref_mut().layer(rect, layer, f)
+    }
     fn target_rect(&self) -> Rect {
         self.deref().target_rect()
     }
@@ -549,8 +824,16 @@ impl<H: DrawHandle> DrawHandle for Box<H> {
     fn separator(&mut self, rect: Rect) {
         self.deref_mut().separator(rect);
     }
-    fn text_offset(&mut self, pos: Coord, offset: Coord, text: &PreparedText, class: TextClass) {
-        self.deref_mut().text_offset(pos, offset, text, class)
+    fn text_offset(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+    ) {
+        self.deref_mut()
+            .text_offset(pos, offset, text, class, state)
     }
     fn text_with_underline(
         &mut self,
@@ -558,10 +841,11 @@ impl<H: DrawHandle> DrawHandle for Box<H> {
         offset: Coord,
         text: &PreparedText,
         class: TextClass,
+        state: InputState,
         underline: usize,
     ) {
         self.deref_mut()
-            .text_with_underline(pos, offset, text, class, underline)
+            .text_with_underline(pos, offset, text, class, state, underline)
     }
     fn text_selected_range(
         &mut self,
@@ -570,9 +854,22 @@ impl<H: DrawHandle> DrawHandle for Box<H> {
         text: &PreparedText,
         range: Range<usize>,
         class: TextClass,
+        state: InputState,
+    ) {
+        self.deref_mut()
+            .text_selected_range(pos, offset, text, range, class, state);
+    }
+    fn text_highlighted(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+        highlights: &[TextHighlight],
     ) {
         self.deref_mut()
-            .text_selected_range(pos, offset, text, range, class);
+            .text_highlighted(pos, offset, text, class, state, highlights)
     }
     fn edit_marker(
         &mut self,
@@ -605,6 +902,33 @@ impl<H: DrawHandle> DrawHandle for Box<H> {
     fn slider(&mut self, rect: Rect, h_rect: Rect, dir: Direction, state: InputState) {
         self.deref_mut().slider(rect, h_rect, dir, state)
     }
+    fn icon(&mut self, rect: Rect, icon: &Icon, state: InputState) {
+        self.deref_mut().icon(rect, icon, state)
+    }
+    fn image(&mut self, rect: Rect, id: ImageId) {
+        self.deref_mut().image(rect, id)
+    }
+    fn fill_path(&mut self, path: &Path, rule: FillRule, col: Colour) {
+        self.deref_mut().fill_path(path, rule, col)
+    }
+    fn stroke_path(&mut self, path: &Path, style: &StrokeStyle, col: Colour) {
+        self.deref_mut().stroke_path(path, style, col)
+    }
+    fn selection(&mut self, rect: Rect, shape: HighlightShape, state: InputState) {
+        self.deref_mut().selection(rect, shape, state)
+    }
+    fn focus_ring(&mut self, rect: Rect, shape: HighlightShape, state: InputState) {
+        self.deref_mut().focus_ring(rect, shape, state)
+    }
+    fn shadow(&mut self, rect: Rect) {
+        self.deref_mut().shadow(rect)
+    }
+    fn well(&mut self, rect: Rect, state: InputState) {
+        self.deref_mut().well(rect, state)
+    }
+    fn badge(&mut self, rect: Rect, text: &PreparedText, state: InputState) {
+        self.deref_mut().badge(rect, text, state)
+    }
 }
 
 #[cfg(feature = "stack_dst")]
@@ -627,6 +951,18 @@ where
     ) {
         self.deref_mut().clip_region(rect, offset, class, f)
     }
+    fn cached_region(
+        &mut self,
+        key: u64,
+        rect: Rect,
+        valid: bool,
+        f: &mut dyn FnMut(&mut dyn DrawHandle),
+    ) {
+        self.deref_mut().cached_region(key, rect, valid, f)
+    }
+    fn layer(&mut self, rect: Rect, layer: Layer, f: &mut dyn FnMut(&mut dyn DrawHandle)) {
+        self.deref_mut().layer(rect, layer, f)
+    }
     fn target_rect(&self) -> Rect {
         self.deref().target_rect()
     }
@@ -639,8 +975,16 @@ where
     fn separator(&mut self, rect: Rect) {
         self.deref_mut().separator(rect);
     }
-    fn text_offset(&mut self, pos: Coord, offset: Coord, text: &PreparedText, class: TextClass) {
-        self.deref_mut().text_offset(pos, offset, text, class)
+    fn text_offset(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+    ) {
+        self.deref_mut()
+            .text_offset(pos, offset, text, class, state)
     }
     fn text_with_underline(
         &mut self,
@@ -648,10 +992,11 @@ where
         offset: Coord,
         text: &PreparedText,
         class: TextClass,
+        state: InputState,
         underline: usize,
     ) {
         self.deref_mut()
-            .text_with_underline(pos, offset, text, class, underline)
+            .text_with_underline(pos, offset, text, class, state, underline)
     }
     fn text_selected_range(
         &mut self,
@@ -660,9 +1005,22 @@ where
         text: &PreparedText,
         range: Range<usize>,
         class: TextClass,
+        state: InputState,
+    ) {
+        self.deref_mut()
+            .text_selected_range(pos, offset, text, range, class, state);
+    }
+    fn text_highlighted(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+        highlights: &[TextHighlight],
     ) {
         self.deref_mut()
-            .text_selected_range(pos, offset, text, range, class);
+            .text_highlighted(pos, offset, text, class, state, highlights)
     }
     fn edit_marker(
         &mut self,
@@ -695,6 +1053,33 @@ where
     fn slider(&mut self, rect: Rect, h_rect: Rect, dir: Direction, state: InputState) {
         self.deref_mut().slider(rect, h_rect, dir, state)
     }
+    fn icon(&mut self, rect: Rect, icon: &Icon, state: InputState) {
+        self.deref_mut().icon(rect, icon, state)
+    }
+    fn image(&mut self, rect: Rect, id: ImageId) {
+        self.deref_mut().image(rect, id)
+    }
+    fn fill_path(&mut self, path: &Path, rule: FillRule, col: Colour) {
+        self.deref_mut().fill_path(path, rule, col)
+    }
+    fn stroke_path(&mut self, path: &Path, style: &StrokeStyle, col: Colour) {
+        self.deref_mut().stroke_path(path, style, col)
+    }
+    fn selection(&mut self, rect: Rect, shape: HighlightShape, state: InputState) {
+        self.deref_mut().selection(rect, shape, state)
+    }
+    fn focus_ring(&mut self, rect: Rect, shape: HighlightShape, state: InputState) {
+        self.deref_mut().focus_ring(rect, shape, state)
+    }
+    fn shadow(&mut self, rect: Rect) {
+        self.deref_mut().shadow(rect)
+    }
+    fn well(&mut self, rect: Rect, state: InputState) {
+        self.deref_mut().well(rect, state)
+    }
+    fn badge(&mut self, rect: Rect, text: &PreparedText, state: InputState) {
+        self.deref_mut().badge(rect, text, state)
+    }
 }
 
 #[cfg(test)]
@@ -709,6 +1094,6 @@ mod test {
 
         let zero = Coord::ZERO;
         let text = PreparedText::new_single("sample".into());
-        draw_handle.text_selected(zero, zero, &text, .., TextClass::Label)
+        draw_handle.text_selected(zero, zero, &text, .., TextClass::Label, Default::default())
     }
 }
@@ -0,0 +1,224 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Icons
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::geom::Size;
+
+/// An icon
+///
+/// Standard icons are drawn by the theme (see [`super::DrawHandle::icon`]),
+/// thus match its style and colours and are crisp at any size and DPI.
+/// Custom icons may be constructed from an [`IconImage`].
+///
+/// Icons are usually drawn at the size given by
+/// [`super::SizeHandle::icon`], which matches the height of a line of text.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Icon {
+    /// Close (a cross)
+    Close,
+    /// Confirm (a tick)
+    Check,
+    /// Add (a plus)
+    Plus,
+    /// Remove (a minus)
+    Minus,
+    /// Arrow up
+    Up,
+    /// Arrow down
+    Down,
+    /// Arrow left
+    Left,
+    /// Arrow right
+    Right,
+    /// Menu (three horizontal lines)
+    Menu,
+    /// Copy (two overlapping pages)
+    Copy,
+    /// Information
+    Info,
+    /// Warning
+    Warning,
+    /// Error
+    Error,
+    /// A custom image
+    Image(Arc<IconImage>),
+}
+
+impl From<IconImage> for Icon {
+    fn from(image: IconImage) -> Self {
+        Icon::Image(Arc::new(image))
+    }
+}
+
+/// Error type for [`IconImage`] construction
+#[derive(Debug)]
+pub enum IconError {
+    /// Buffer length does not match the image size
+    Size,
+    /// Image decoding failed
+    Decode(String),
+}
+
+impl fmt::Display for IconError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IconError::Size => write!(f, "image buffer does not match size"),
+            IconError::Decode(msg) => write!(f, "failed to decode image: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for IconError {}
+
+enum Source {
+    Rgba(Size, Vec<u8>),
+    #[cfg(feature = "svg")]
+    Svg(usvg::Tree),
+}
+
+/// A custom icon image
+///
+/// Images are either raster images (RGBA, optionally decoded from PNG) or,
+/// with feature `svg`, vector images (rasterised at the size drawn).
+///
+/// Each image has a unique identifier, used by toolkits to cache uploaded
+/// textures; clone the [`Icon`] (which shares the image) rather than
+/// re-constructing the image where it is used in multiple places.
+pub struct IconImage {
+    id: u64,
+    source: Source,
+}
+
+impl fmt::Debug for IconImage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.source {
+            Source::Rgba(size, _) => write!(f, "IconImage {{ id: {}, rgba: {:?} }}", self.id, size),
+            #[cfg(feature = "svg")]
+            Source::Svg(_) => write!(f, "IconImage {{ id: {}, svg }}", self.id),
+        }
+    }
+}
+
+impl PartialEq for IconImage {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl IconImage {
+    fn new(source: Source) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        IconImage { id, source }
+    }
+
+    /// Construct from RGBA pixel data
+    ///
+    /// Data is non-premultiplied 8-bit RGBA in row-major order, thus must have
+    /// length `4 * size.0 * size.1`.
+    pub fn from_rgba(size: Size, rgba: Vec<u8>) -> Result<Self, IconError> {
+        if rgba.len() != 4 * size.0 as usize * size.1 as usize {
+            return Err(IconError::Size);
+        }
+        Ok(IconImage::new(Source::Rgba(size, rgba)))
+    }
+
+    /// Construct from PNG data
+    #[cfg(feature = "png")]
+    pub fn from_png(data: &[u8]) -> Result<Self, IconError> {
+        use png_dep::ColorType;
+        let err = |e: png_dep::DecodingError| IconError::Decode(e.to_string());
+
+        let mut decoder = png_dep::Decoder::new(data);
+        decoder.set_transformations(
+            png_dep::Transformations::EXPAND | png_dep::Transformations::STRIP_16,
+        );
+        let (info, mut reader) = decoder.read_info().map_err(err)?;
+        let mut buf = vec![0; info.buffer_size()];
+        reader.next_frame(&mut buf).map_err(err)?;
+
+        let rgba = match info.color_type {
+            ColorType::RGBA => buf,
+            ColorType::RGB => buf
+                .chunks(3)
+                .flat_map(|c| vec![c[0], c[1], c[2], 255])
+                .collect(),
+            ColorType::GrayscaleAlpha => buf
+                .chunks(2)
+                .flat_map(|c| vec![c[0], c[0], c[0], c[1]])
+                .collect(),
+            ColorType::Grayscale => buf.iter().flat_map(|c| vec![*c, *c, *c, 255]).collect(),
+            ColorType::Indexed => return Err(IconError::Decode("unexpanded palette".into())),
+        };
+        IconImage::from_rgba(Size(info.width, info.height), rgba)
+    }
+
+    /// Construct from SVG data
+    #[cfg(feature = "svg")]
+    pub fn from_svg(data: &[u8]) -> Result<Self, IconError> {
+        let tree = usvg::Tree::from_data(data, &usvg::Options::default())
+            .map_err(|e| IconError::Decode(e.to_string()))?;
+        Ok(IconImage::new(Source::Svg(tree)))
+    }
+
+    /// Get the unique identifier
+    #[inline]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Get the size of a raster image
+    ///
+    /// Returns `None` for scalable images.
+    pub fn size(&self) -> Option<Size> {
+        match self.source {
+            Source::Rgba(size, _) => Some(size),
+            #[cfg(feature = "svg")]
+            Source::Svg(_) => None,
+        }
+    }
+
+    /// True if the image is rasterised at the size drawn
+    ///
+    /// Raster images have a fixed size and are scaled when drawn.
+    pub fn is_scalable(&self) -> bool {
+        match self.source {
+            Source::Rgba(..) => false,
+            #[cfg(feature = "svg")]
+            Source::Svg(_) => true,
+        }
+    }
+
+    /// Get RGBA pixel data for drawing at `size`
+    ///
+    /// Returns the size of the data and non-premultiplied 8-bit RGBA pixels.
+    /// For raster images, the image's own size and data are returned.
+    #[cfg_attr(not(feature = "svg"), allow(unused_variables))]
+    pub fn rgba(&self, size: Size) -> Option<(Size, Vec<u8>)> {
+        match &self.source {
+            Source::Rgba(img_size, data) => Some((*img_size, data.clone())),
+            #[cfg(feature = "svg")]
+            Source::Svg(tree) => {
+                // resvg preserves the aspect ratio: fit to the limiting side
+                let svg_size = tree.svg_node().size;
+                let fit_to =
+                    if svg_size.width() * size.1 as f64 >= svg_size.height() * size.0 as f64 {
+                        usvg::FitTo::Width(size.0)
+                    } else {
+                        usvg::FitTo::Height(size.1)
+                    };
+                // The result is not premultiplied
+                let image = resvg::render(tree, fit_to, None)?;
+                let size = Size(image.width(), image.height());
+                Some((size, image.take()))
+            }
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Uploaded images
+
+use std::fmt;
+
+/// Identifier of an image uploaded via [`super::DrawImage::image_alloc`]
+///
+/// Identifiers are unique within the [`super::Draw`] object which allocated
+/// them, but are not valid elsewhere.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ImageId(u32);
+
+impl ImageId {
+    /// Construct a new identifier from a `u32` value
+    #[cfg_attr(not(feature = "internal_doc"), doc(hidden))]
+    #[inline]
+    pub const fn new(n: u32) -> Self {
+        ImageId(n)
+    }
+
+    /// Get the identifier's value
+    #[inline]
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+/// Error type for [`super::DrawImage`] operations
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageError {
+    /// Size is zero or too large, or buffer length does not match the size
+    Size,
+    /// The image identifier is not (or no longer) allocated
+    Id,
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Size => write!(f, "invalid image size or buffer length"),
+            ImageError::Id => write!(f, "unknown image identifier"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
@@ -0,0 +1,175 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Mock size handle (debug tool)
+
+use super::{SizeHandle, TextClass};
+use kas::geom::Size;
+use kas::layout::{AxisInfo, Margins, SizeRules, StretchPolicy};
+use kas::text::PreparedText;
+
+/// Width of a character (before scaling)
+const CHAR_WIDTH: u32 = 8;
+/// Height of a line of text (before scaling)
+const LINE_HEIGHT: u32 = 16;
+/// Font size in pixels per em (before scaling)
+const DPEM: f32 = 16.0;
+/// Minimum line length (in characters) of wrapping text
+const MIN_WRAP_CHARS: u32 = 8;
+
+/// A [`SizeHandle`] with fixed dimensions, not requiring a theme or GPU
+///
+/// Text is measured as if rendered in a monospace font with characters 8
+/// pixels wide and lines 16 pixels high (before scaling). Text of classes
+/// [`TextClass::Label`] and [`TextClass::EditMulti`] wraps (at any character)
+/// to the width passed via [`AxisInfo`]. Fonts are not accessed. Other
+/// dimensions are simple constants, thus layouts are deterministic across
+/// systems: this is useful for debugging layouts and for tests (see
+/// [`kas::layout::layout_to_text`]).
+#[derive(Clone, Debug)]
+pub struct MockSizeHandle {
+    scale_factor: f32,
+}
+
+impl Default for MockSizeHandle {
+    fn default() -> Self {
+        MockSizeHandle::new(1.0)
+    }
+}
+
+impl MockSizeHandle {
+    /// Construct with the given scale factor
+    pub fn new(scale_factor: f32) -> Self {
+        MockSizeHandle { scale_factor }
+    }
+
+    fn scale(&self, x: u32) -> u32 {
+        (x as f32 * self.scale_factor).round() as u32
+    }
+}
+
+impl SizeHandle for MockSizeHandle {
+    fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    fn frame(&self) -> Size {
+        Size::uniform(self.scale(4))
+    }
+
+    fn menu_frame(&self) -> Size {
+        Size::uniform(self.scale(4))
+    }
+
+    fn inner_margin(&self) -> Size {
+        Size::uniform(self.scale(2))
+    }
+
+    fn outer_margins(&self) -> Margins {
+        Margins::uniform(self.scale(4) as u16)
+    }
+
+    fn line_height(&self, _: TextClass) -> u32 {
+        self.scale(LINE_HEIGHT)
+    }
+
+    fn dpem(&self) -> f32 {
+        DPEM * self.scale_factor
+    }
+
+    fn text_bound(
+        &mut self,
+        text: &mut PreparedText,
+        class: TextClass,
+        axis: AxisInfo,
+    ) -> SizeRules {
+        let content = text.text();
+        let wrap = match class {
+            TextClass::Label | TextClass::EditMulti => true,
+            _ => false,
+        };
+        let margin = match class {
+            TextClass::Label | TextClass::LabelSingle => self.scale(4),
+            TextClass::Button | TextClass::Edit | TextClass::EditMulti => self.scale(2),
+        } as u16;
+        let margins = (margin, margin);
+        if axis.is_horizontal() {
+            let chars = content.lines().map(|line| line.chars().count());
+            let width = self.scale(CHAR_WIDTH * chars.max().unwrap_or(0) as u32);
+            let min = match wrap {
+                true => width.min(self.scale(MIN_WRAP_CHARS * CHAR_WIDTH)),
+                false => width,
+            };
+            let policy = match class {
+                TextClass::Edit | TextClass::EditMulti => StretchPolicy::HighUtility,
+                _ => StretchPolicy::LowUtility,
+            };
+            SizeRules::new(min, width, margins, policy)
+        } else {
+            let line_len = match axis.other() {
+                Some(width) if wrap => {
+                    Some((width / self.scale(CHAR_WIDTH).max(1)).max(1) as usize)
+                }
+                _ => None,
+            };
+            let lines = content.lines().map(|line| match line_len {
+                Some(len) => (line.chars().count().max(1) + len - 1) / len,
+                None => 1,
+            });
+            let lines = (lines.sum::<usize>() as u32).max(1);
+            let height = self.scale(LINE_HEIGHT * lines);
+            let policy = match class {
+                TextClass::EditMulti => StretchPolicy::HighUtility,
+                _ => StretchPolicy::Fixed,
+            };
+            SizeRules::new(height, height, margins, policy)
+        }
+    }
+
+    fn line_length(&self) -> (u32, u32) {
+        let min = MIN_WRAP_CHARS * CHAR_WIDTH;
+        (self.scale(min), self.scale(3 * min))
+    }
+
+    fn edit_marker_width(&self) -> f32 {
+        self.scale_factor
+    }
+
+    fn focus_ring_width(&self) -> f32 {
+        self.scale(2) as f32
+    }
+
+    fn button_surround(&self) -> (Size, Size) {
+        let s = Size::uniform(self.scale(4));
+        (s, s)
+    }
+
+    fn edit_surround(&self) -> (Size, Size) {
+        let s = Size::uniform(self.scale(2));
+        (s, s)
+    }
+
+    fn checkbox(&self) -> Size {
+        Size::uniform(self.scale(LINE_HEIGHT))
+    }
+
+    fn radiobox(&self) -> Size {
+        Size::uniform(self.scale(LINE_HEIGHT))
+    }
+
+    fn icon(&self) -> Size {
+        Size::uniform(self.scale(LINE_HEIGHT))
+    }
+
+    fn scrollbar(&self) -> (Size, u32) {
+        let size = Size(self.scale(16), self.scale(8));
+        (size, self.scale(32))
+    }
+
+    fn slider(&self) -> (Size, u32) {
+        let size = Size(self.scale(12), self.scale(16));
+        (size, self.scale(32))
+    }
+}
@@ -26,9 +26,10 @@
 //! and may provide their own extension traits. For this reason, themes are
 //! parameterised over an object `D: Draw + ...` (with specified trait bounds).
 //!
-//! The medium-level API will be extended in the future to support texturing
-//! (not yet supported) and potentially a more comprehensive path-based API
-//! (e.g. Lyon).
+//! Images (see [`IconImage`]) may be drawn via [`Draw::image`]. Toolkits may
+//! additionally implement [`DrawImage`], allowing RGBA data to be uploaded
+//! once and drawn (and updated) by [`ImageId`]. Arbitrary shapes may be
+//! described by a [`Path`] and drawn via [`DrawPath`].
 //!
 //! ### Low-level interface
 //!
@@ -39,14 +40,24 @@
 
 mod colour;
 mod handle;
+mod icon;
+mod image;
+mod mock;
+mod path;
+mod record;
 
 use std::any::Any;
 
-use crate::geom::{Quad, Rect, Vec2};
-use crate::text::PreparedText;
+use crate::geom::{Quad, Rect, Size, Vec2};
+use crate::text::{FallbackFont, PreparedText};
 
-pub use colour::Colour;
+pub use colour::{Colour, ColourScheme};
 pub use handle::*;
+pub use icon::{Icon, IconError, IconImage};
+pub use image::{ImageError, ImageId};
+pub use mock::MockSizeHandle;
+pub use path::{FillRule, LineCap, LineJoin, Path, PathCmd, StrokeStyle};
+pub use record::{DrawRecord, DrawRecorder};
 
 /// Pass identifier
 ///
@@ -79,6 +90,110 @@ impl Pass {
     }
 }
 
+/// Compositing parameters of a layer (see [`Draw::add_layer`])
+///
+/// ```
+/// use kas::draw::{Layer, Shadow};
+/// use kas::geom::Vec2;
+///
+/// // A semi-transparent ghost, offset from its source
+/// let ghost = Layer::new(0.5).with_offset(Vec2(8.0, 8.0));
+/// assert_eq!(ghost.scale, 1.0);
+///
+/// // A floating panel with a drop shadow
+/// let panel = Layer::default().with_shadow(Shadow::new(Vec2(2.0, 4.0), 8.0, 0.5));
+/// assert_eq!(panel.shadow.map(|s| s.radius), Some(8.0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Layer {
+    /// Opacity, from 0 (transparent) to 1 (opaque)
+    pub opacity: f32,
+    /// Translation applied when compositing
+    pub offset: Vec2,
+    /// Scale factor applied (about the centre of the layer) when compositing
+    pub scale: f32,
+    /// Radius of a gaussian blur applied to content (none if zero)
+    pub blur: f32,
+    /// A drop shadow, drawn beneath the layer
+    pub shadow: Option<Shadow>,
+}
+
+impl Layer {
+    /// Construct with the given `opacity` and no transform
+    pub fn new(opacity: f32) -> Self {
+        Layer {
+            opacity,
+            offset: Vec2::ZERO,
+            scale: 1.0,
+            blur: 0.0,
+            shadow: None,
+        }
+    }
+
+    /// Set the translation
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Set the scale factor
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Set the blur radius
+    pub fn with_blur(mut self, radius: f32) -> Self {
+        self.blur = radius;
+        self
+    }
+
+    /// Set a drop shadow
+    pub fn with_shadow(mut self, shadow: Shadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Get the target of compositing `rect`
+    pub fn transform(&self, rect: Quad) -> Quad {
+        let centre = (rect.a + rect.b) * 0.5;
+        let a = (rect.a - centre) * self.scale + centre + self.offset;
+        let b = (rect.b - centre) * self.scale + centre + self.offset;
+        Quad::with_coords(a, b)
+    }
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Layer::new(1.0)
+    }
+}
+
+/// A drop shadow (see [`Layer::with_shadow`])
+///
+/// The shadow is a black, blurred copy of the layer's content, thus follows
+/// the shape of content such as rounded frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shadow {
+    /// Offset relative to the layer
+    pub offset: Vec2,
+    /// Blur radius
+    pub radius: f32,
+    /// Opacity beneath opaque content
+    pub opacity: f32,
+}
+
+impl Shadow {
+    /// Construct
+    pub fn new(offset: Vec2, radius: f32, opacity: f32) -> Self {
+        Shadow {
+            offset,
+            radius,
+            opacity,
+        }
+    }
+}
+
 /// Bounds on type shared across [`Draw`] implementations
 pub trait DrawShared {
     type Draw: Draw;
@@ -115,6 +230,45 @@ pub trait Draw: Any {
     /// assigning depth values.
     fn add_clip_region(&mut self, rect: Rect, depth: f32) -> Pass;
 
+    /// Add a cached region
+    ///
+    /// Content of a cached region is rendered off-screen and re-used over
+    /// subsequent frames. The `key` identifies the region and must be unique
+    /// within the window (e.g. a [`kas::WidgetId`]); `valid` declares whether
+    /// content previously drawn under this `key` is still up to date.
+    ///
+    /// Returns `None` if previous content is re-used, in which case nothing
+    /// should be drawn. Otherwise, returns a new [`Pass`] (at the depth of
+    /// `parent`) to which the content of `rect` must be drawn. Cached content
+    /// not re-used or redrawn is discarded at the end of the frame.
+    ///
+    /// The default implementation does not cache: it returns a new clip region.
+    fn add_cached_region(
+        &mut self,
+        key: u64,
+        rect: Rect,
+        parent: Pass,
+        valid: bool,
+    ) -> Option<Pass> {
+        let _ = (key, valid);
+        Some(self.add_clip_region(rect, parent.depth()))
+    }
+
+    /// Add a compositing layer
+    ///
+    /// Content of a layer is rendered off-screen, then composited onto
+    /// `parent` as specified by `layer` (e.g. with reduced opacity). Returns a
+    /// new [`Pass`] (at the depth of `parent`) to which the content of `rect`
+    /// must be drawn. Like clip regions, layers must be recreated each frame.
+    /// Blur and shadows may extend beyond `rect` (by their radius).
+    ///
+    /// The default implementation does not composite: it returns a new clip
+    /// region, ignoring `layer`.
+    fn add_layer(&mut self, rect: Rect, parent: Pass, layer: Layer) -> Pass {
+        let _ = layer;
+        self.add_clip_region(rect, parent.depth())
+    }
+
     /// Draw a rectangle of uniform colour
     fn rect(&mut self, pass: Pass, rect: Quad, col: Colour);
 
@@ -122,6 +276,92 @@ pub trait Draw: Any {
     ///
     /// The frame is defined by the area inside `outer` and not inside `inner`.
     fn frame(&mut self, pass: Pass, outer: Quad, inner: Quad, col: Colour);
+
+    /// Draw an image, scaled to fill `rect`
+    ///
+    /// Implementations should cache uploaded image data by
+    /// [`IconImage::id`] (and size, where [`IconImage::is_scalable`]).
+    ///
+    /// The default implementation draws nothing.
+    fn image(&mut self, pass: Pass, rect: Quad, image: &IconImage) {
+        let _ = (pass, rect, image);
+    }
+
+    /// Draw a region of an image, scaled to fill `rect`
+    ///
+    /// The `region` is given in normalised image coordinates, where `(0, 0)`
+    /// is the top-left and `(1, 1)` the bottom-right corner of the image.
+    /// Scalable images are rasterised at the size of the whole image drawn.
+    ///
+    /// The default implementation draws nothing.
+    fn image_region(&mut self, pass: Pass, rect: Quad, image: &IconImage, region: Quad) {
+        let _ = (pass, rect, image, region);
+    }
+
+    /// Access the [`DrawImage`] API, if supported
+    ///
+    /// The default implementation returns `None`.
+    fn as_draw_image(&mut self) -> Option<&mut dyn DrawImage> {
+        None
+    }
+
+    /// Access the [`DrawPath`] API, if supported
+    ///
+    /// The default implementation returns `None`.
+    fn as_draw_path(&mut self) -> Option<&mut dyn DrawPath> {
+        None
+    }
+}
+
+/// Radii of the corners of a rounded rect (see [`DrawRounded::rounded_rect`])
+///
+/// ```
+/// use kas::draw::CornerRadii;
+///
+/// // A tab, rounded only at the top
+/// let tab = CornerRadii::new(6.0, 6.0, 0.0, 0.0);
+/// assert_eq!(tab.bottom_left, 0.0);
+/// assert_eq!(CornerRadii::uniform(4.0).top_right, 4.0);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CornerRadii {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32,
+}
+
+impl CornerRadii {
+    /// Construct, clockwise from the top-left corner
+    pub fn new(top_left: f32, top_right: f32, bottom_right: f32, bottom_left: f32) -> Self {
+        CornerRadii {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        }
+    }
+
+    /// Construct with the same radius on each corner
+    pub fn uniform(radius: f32) -> Self {
+        CornerRadii::new(radius, radius, radius, radius)
+    }
+}
+
+/// An outline stroke (see [`DrawRounded::rounded_rect`])
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Border {
+    /// Width of the outline
+    pub width: f32,
+    /// Colour of the outline
+    pub col: Colour,
+}
+
+impl Border {
+    /// Construct
+    pub fn new(width: f32, col: Colour) -> Self {
+        Border { width, col }
+    }
 }
 
 /// Drawing commands for rounded shapes
@@ -170,6 +410,21 @@ pub trait DrawRounded: Draw {
         inner_radius: f32,
         col: Colour,
     );
+
+    /// Draw a rect with independently rounded corners
+    ///
+    /// Corners of `rect` are circular with the given `radii`. Where radii are
+    /// too large to fit, they are reduced proportionally. The shape is filled
+    /// with `col`; if a `border` is given, this is drawn inside the edge of
+    /// `rect` and the fill is inset by its width.
+    fn rounded_rect(
+        &mut self,
+        pass: Pass,
+        rect: Quad,
+        radii: CornerRadii,
+        col: Colour,
+        border: Option<Border>,
+    );
 }
 
 /// Drawing commands for shaded shapes
@@ -211,6 +466,65 @@ pub trait DrawShaded: Draw {
     );
 }
 
+/// Drawing commands for uploaded images
+///
+/// This trait is an extension over [`Draw`] allowing RGBA image data to be
+/// uploaded to the graphics device once, then drawn by [`ImageId`]. Unlike
+/// [`Draw::image`], the caller is responsible for the lifetime of image data:
+/// images remain allocated until freed via [`DrawImage::image_free`].
+/// (Toolkits may use the same storage for both.)
+///
+/// Where supported, this API is available via [`Draw::as_draw_image`], e.g.
+/// on the result of [`DrawHandle::draw_device`]; uploaded images may then be
+/// drawn with [`DrawHandle::image`].
+///
+/// Image data is 8-bit sRGB RGBA with (non-premultiplied) alpha, stored
+/// row-major without padding, hence of length `4 * size.0 * size.1`.
+pub trait DrawImage: Draw {
+    /// Allocate an image of the given `size`, with initial content `rgba`
+    fn image_alloc(&mut self, size: Size, rgba: &[u8]) -> Result<ImageId, ImageError>;
+
+    /// Replace the content of image `id`
+    ///
+    /// The length of `rgba` must match the size the image was allocated with.
+    fn image_update(&mut self, id: ImageId, rgba: &[u8]) -> Result<(), ImageError>;
+
+    /// Free image `id`
+    ///
+    /// Does nothing if `id` is not allocated.
+    fn image_free(&mut self, id: ImageId);
+
+    /// Get the size of image `id`, if allocated
+    fn image_size(&self, id: ImageId) -> Option<Size>;
+
+    /// Draw the `region` of image `id`, scaled to fill `rect`
+    ///
+    /// As with [`Draw::image_region`], `region` is given in normalised image
+    /// coordinates. Nothing is drawn if `id` is not allocated.
+    fn draw_image(&mut self, pass: Pass, rect: Quad, id: ImageId, region: Quad);
+}
+
+/// Drawing commands for vector paths
+///
+/// This trait is an extension over [`Draw`] allowing arbitrary shapes,
+/// described by a [`Path`], to be filled or stroked in a uniform colour.
+///
+/// Where supported, this API is available via [`Draw::as_draw_path`], or
+/// more simply via [`DrawHandle::fill_path`] and [`DrawHandle::stroke_path`].
+///
+/// Like [`Draw::rect`], these primitives are opaque. Paths are tessellated
+/// into triangles, whose edges are not anti-aliased unless the toolkit uses
+/// multi-sampling (e.g. `kas_wgpu::Options::msaa_samples`).
+pub trait DrawPath: Draw {
+    /// Fill the area enclosed by `path`
+    ///
+    /// Open sub-paths are implicitly closed.
+    fn fill_path(&mut self, pass: Pass, path: &Path, rule: FillRule, col: Colour);
+
+    /// Stroke the outline of `path`
+    fn stroke_path(&mut self, pass: Pass, path: &Path, style: &StrokeStyle, col: Colour);
+}
+
 /// Applies effects from the given `start` position in the text
 ///
 /// This is a HACK to allow some formatting without full support in `kas_text`.
@@ -249,6 +563,17 @@ impl TextEffect {
 /// Note: the current API is designed to meet only current requirements since
 /// changes are expected to support external font shaping libraries.
 pub trait DrawText {
+    /// Set fallback fonts
+    ///
+    /// Glyphs not supported by the font of a text (i.e. mapped to the
+    /// `.notdef` glyph) are drawn using the first font of this list
+    /// supporting the glyph's character. This setting persists until changed.
+    ///
+    /// The default implementation does nothing.
+    fn set_fallback_fonts(&mut self, fonts: &[FallbackFont]) {
+        let _ = fonts;
+    }
+
     /// Draw text
     fn text(&mut self, pass: Pass, pos: Vec2, offset: Vec2, col: Colour, text: &PreparedText) {
         let effects = [TextEffect::col(0, col)];
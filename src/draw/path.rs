@@ -0,0 +1,392 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Vector paths
+
+use crate::geom::{Quad, Vec2};
+
+/// A path command
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathCmd {
+    /// Begin a new sub-path at the given point
+    MoveTo(Vec2),
+    /// A straight line to the given point
+    LineTo(Vec2),
+    /// A quadratic Bézier curve via control point `.0` to `.1`
+    QuadTo(Vec2, Vec2),
+    /// A cubic Bézier curve via control points `.0`, `.1` to `.2`
+    CubicTo(Vec2, Vec2, Vec2),
+    /// Close the current sub-path with a line to its start
+    Close,
+}
+
+/// A vector path
+///
+/// A path consists of zero or more sub-paths, each started by
+/// [`Path::move_to`]. Segments added before any `move_to` start at the
+/// origin. Paths are drawn via [`super::DrawPath`].
+///
+/// ```
+/// use kas::draw::Path;
+/// use kas::geom::Vec2;
+///
+/// let mut path = Path::new();
+/// path.move_to(Vec2(0.0, 0.0))
+///     .line_to(Vec2(10.0, 0.0))
+///     .quad_to(Vec2(10.0, 10.0), Vec2(0.0, 10.0))
+///     .close();
+/// assert_eq!(path.commands().len(), 4);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    cmds: Vec<PathCmd>,
+}
+
+impl Path {
+    /// Construct an empty path
+    #[inline]
+    pub fn new() -> Self {
+        Path { cmds: vec![] }
+    }
+
+    /// True if the path has no commands
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cmds.is_empty()
+    }
+
+    /// Access path commands
+    #[inline]
+    pub fn commands(&self) -> &[PathCmd] {
+        &self.cmds
+    }
+
+    /// Begin a new sub-path at `p`
+    pub fn move_to(&mut self, p: Vec2) -> &mut Self {
+        self.cmds.push(PathCmd::MoveTo(p));
+        self
+    }
+
+    /// Add a straight line to `p`
+    pub fn line_to(&mut self, p: Vec2) -> &mut Self {
+        self.cmds.push(PathCmd::LineTo(p));
+        self
+    }
+
+    /// Add a quadratic Bézier curve via control point `c` to `p`
+    pub fn quad_to(&mut self, c: Vec2, p: Vec2) -> &mut Self {
+        self.cmds.push(PathCmd::QuadTo(c, p));
+        self
+    }
+
+    /// Add a cubic Bézier curve via control points `c1`, `c2` to `p`
+    pub fn cubic_to(&mut self, c1: Vec2, c2: Vec2, p: Vec2) -> &mut Self {
+        self.cmds.push(PathCmd::CubicTo(c1, c2, p));
+        self
+    }
+
+    /// Close the current sub-path
+    pub fn close(&mut self) -> &mut Self {
+        self.cmds.push(PathCmd::Close);
+        self
+    }
+
+    /// Construct a copy of this path translated by `offset`
+    pub fn translated(&self, offset: Vec2) -> Path {
+        let cmds = self.cmds.iter().map(|cmd| match *cmd {
+            PathCmd::MoveTo(p) => PathCmd::MoveTo(p + offset),
+            PathCmd::LineTo(p) => PathCmd::LineTo(p + offset),
+            PathCmd::QuadTo(c, p) => PathCmd::QuadTo(c + offset, p + offset),
+            PathCmd::CubicTo(c1, c2, p) => PathCmd::CubicTo(c1 + offset, c2 + offset, p + offset),
+            PathCmd::Close => PathCmd::Close,
+        });
+        Path {
+            cmds: cmds.collect(),
+        }
+    }
+
+    /// Get a bounding box of the path
+    ///
+    /// This bounds all points, including control points, hence may be larger
+    /// than the area covered by the path. Returns `None` if the path has no
+    /// points.
+    pub fn bounds(&self) -> Option<Quad> {
+        let mut bounds: Option<(Vec2, Vec2)> = None;
+        let mut add = |p: Vec2| {
+            bounds = Some(match bounds {
+                Some((a, b)) => (a.min(p), b.max(p)),
+                None => (p, p),
+            });
+        };
+        for cmd in &self.cmds {
+            match *cmd {
+                PathCmd::MoveTo(p) | PathCmd::LineTo(p) => add(p),
+                PathCmd::QuadTo(c, p) => {
+                    add(c);
+                    add(p);
+                }
+                PathCmd::CubicTo(c1, c2, p) => {
+                    add(c1);
+                    add(c2);
+                    add(p);
+                }
+                PathCmd::Close => (),
+            }
+        }
+        bounds.map(|(a, b)| Quad::with_coords(a, b))
+    }
+
+    /// Approximate the path with polylines
+    ///
+    /// Returns one polyline per sub-path, with a flag indicating whether it is
+    /// closed. Curves are subdivided such that the approximation deviates by
+    /// at most `tolerance` from the curve.
+    pub fn flatten(&self, tolerance: f32) -> Vec<(Vec<Vec2>, bool)> {
+        let mut lines = vec![];
+        let mut line = vec![Vec2::ZERO];
+        let mut last = Vec2::ZERO;
+        let end = |line: &mut Vec<Vec2>, lines: &mut Vec<_>, closed| {
+            let line = std::mem::replace(line, vec![]);
+            if line.len() > 1 {
+                lines.push((line, closed));
+            }
+        };
+
+        for cmd in &self.cmds {
+            match *cmd {
+                PathCmd::MoveTo(p) => {
+                    end(&mut line, &mut lines, false);
+                    line.push(p);
+                    last = p;
+                }
+                PathCmd::LineTo(p) => {
+                    line.push(p);
+                    last = p;
+                }
+                PathCmd::QuadTo(c, p) => {
+                    let dd = (last - c * 2.0 + p).sum_square().sqrt();
+                    let n = segments(dd / (4.0 * tolerance));
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let u = 1.0 - t;
+                        line.push(last * (u * u) + c * (2.0 * u * t) + p * (t * t));
+                    }
+                    last = p;
+                }
+                PathCmd::CubicTo(c1, c2, p) => {
+                    let d1 = (last - c1 * 2.0 + c2).sum_square();
+                    let d2 = (c1 - c2 * 2.0 + p).sum_square();
+                    let dd = d1.max(d2).sqrt();
+                    let n = segments(0.75 * dd / tolerance);
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let u = 1.0 - t;
+                        line.push(
+                            last * (u * u * u)
+                                + c1 * (3.0 * u * u * t)
+                                + c2 * (3.0 * u * t * t)
+                                + p * (t * t * t),
+                        );
+                    }
+                    last = p;
+                }
+                PathCmd::Close => {
+                    let start = line.first().cloned().unwrap_or(last);
+                    end(&mut line, &mut lines, true);
+                    line.push(start);
+                    last = start;
+                }
+            }
+        }
+        end(&mut line, &mut lines, false);
+        lines
+    }
+
+    /// Construct a dashed version of this path
+    ///
+    /// The `pattern` lists alternating lengths of dashes and gaps, starting
+    /// with a dash; the pattern is started `offset` along each sub-path.
+    /// As in SVG, a pattern of odd length is repeated to yield an even
+    /// number of entries (thus `[3.0]` is equivalent to `[3.0, 3.0]`).
+    /// Curves are flattened (see [`Path::flatten`]). If `pattern` is empty or
+    /// has no positive length, the (flattened) path is returned undashed.
+    ///
+    /// ```
+    /// use kas::draw::{Path, PathCmd};
+    /// use kas::geom::Vec2;
+    ///
+    /// let mut path = Path::new();
+    /// path.move_to(Vec2(0.0, 0.0)).line_to(Vec2(10.0, 0.0));
+    /// let count_dashes = |path: &Path| {
+    ///     let starts = path.commands().iter().filter(|cmd| match cmd {
+    ///         PathCmd::MoveTo(_) => true,
+    ///         _ => false,
+    ///     });
+    ///     starts.count()
+    /// };
+    /// assert_eq!(count_dashes(&path.dashed(&[3.0, 2.0], 0.0, 0.1)), 2);
+    /// assert_eq!(count_dashes(&path.dashed(&[3.0], 0.0, 0.1)), 2);
+    /// ```
+    pub fn dashed(&self, pattern: &[f32], offset: f32, tolerance: f32) -> Path {
+        let doubled;
+        let pattern = match pattern.len() % 2 {
+            1 => {
+                doubled = [pattern, pattern].concat();
+                &doubled[..]
+            }
+            _ => pattern,
+        };
+        let total: f32 = pattern.iter().map(|len| len.max(0.0)).sum();
+        let mut path = Path::new();
+        for (mut line, closed) in self.flatten(tolerance) {
+            if closed {
+                line.push(line[0]);
+            }
+            if total <= 0.0 {
+                path.move_to(line[0]);
+                for p in &line[1..] {
+                    path.line_to(*p);
+                }
+                continue;
+            }
+
+            // Find the pattern position at the start of the line
+            let mut index = 0;
+            let mut remaining = pattern[0].max(0.0);
+            let mut skip = offset.rem_euclid(total);
+            while skip >= remaining {
+                skip -= remaining;
+                index = (index + 1) % pattern.len();
+                remaining = pattern[index].max(0.0);
+            }
+            remaining -= skip;
+
+            let mut drawing = false;
+            for pair in line.windows(2) {
+                let (mut a, b) = (pair[0], pair[1]);
+                let mut len = (b - a).sum_square().sqrt();
+                loop {
+                    let on = index % 2 == 0;
+                    if on && !drawing {
+                        path.move_to(a);
+                        drawing = true;
+                    }
+                    if len <= remaining {
+                        if on {
+                            path.line_to(b);
+                        }
+                        remaining -= len;
+                        break;
+                    }
+                    a = a + (b - a) * (remaining / len);
+                    len -= remaining;
+                    if on {
+                        path.line_to(a);
+                        drawing = false;
+                    }
+                    index = (index + 1) % pattern.len();
+                    remaining = pattern[index].max(0.0);
+                }
+            }
+        }
+        path
+    }
+}
+
+/// Number of line segments approximating a curve
+fn segments(x: f32) -> u32 {
+    (x.sqrt().ceil() as u32).max(1).min(1000)
+}
+
+/// Shape of the join between two stroked segments
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LineJoin {
+    /// A sharp corner (within the miter limit, otherwise beveled)
+    Miter,
+    /// A round corner
+    Round,
+    /// A cut corner
+    Bevel,
+}
+
+/// Shape of the end of a stroked line
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LineCap {
+    /// The line ends exactly at its end-point
+    Butt,
+    /// A half-circle is added beyond the end-point
+    Round,
+    /// A half-square is added beyond the end-point
+    Square,
+}
+
+/// Rule determining which areas are inside a filled path
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FillRule {
+    /// Inside where the winding number is non-zero
+    NonZero,
+    /// Inside where the winding number is odd
+    EvenOdd,
+}
+
+impl Default for FillRule {
+    fn default() -> Self {
+        FillRule::NonZero
+    }
+}
+
+/// Style of a stroked path
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrokeStyle {
+    /// Line width
+    pub width: f32,
+    /// Join between segments
+    pub join: LineJoin,
+    /// Cap at line ends
+    pub cap: LineCap,
+    /// Dash pattern (see [`Path::dashed`]); if empty, the line is solid
+    pub dashes: Vec<f32>,
+    /// Offset into the dash pattern
+    pub dash_offset: f32,
+}
+
+impl StrokeStyle {
+    /// Construct a solid style with the given `width`, miter joins and
+    /// butt caps
+    pub fn new(width: f32) -> Self {
+        StrokeStyle {
+            width,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            dashes: vec![],
+            dash_offset: 0.0,
+        }
+    }
+
+    /// Set the line join
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    /// Set the line cap
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Set a dash pattern and offset
+    pub fn with_dashes(mut self, dashes: Vec<f32>, offset: f32) -> Self {
+        self.dashes = dashes;
+        self.dash_offset = offset;
+        self
+    }
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        StrokeStyle::new(1.0)
+    }
+}
@@ -0,0 +1,424 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Recording of draw commands (debug tool)
+
+use std::fmt::{self, Write};
+use std::ops::Range;
+
+use super::{
+    ClipRegion, Colour, Draw, DrawHandle, FillRule, HighlightShape, Icon, ImageId, InputState,
+    Layer, Pass, Path, SizeHandle, StrokeStyle, TextClass, TextHighlight,
+};
+use kas::geom::{Coord, Rect, Size};
+use kas::text::PreparedText;
+use kas::{Direction, Layout, WidgetId};
+
+/// A single recorded draw command
+///
+/// See [`DrawRecorder`].
+#[derive(Clone, Debug)]
+pub struct DrawRecord {
+    /// Name of the [`DrawHandle`] method called
+    pub kind: &'static str,
+    /// Target area, in window coordinates
+    pub rect: Rect,
+    /// Nesting depth of clip regions
+    pub depth: u32,
+    /// Text content, if any
+    pub text: Option<String>,
+    /// Input state, if any
+    pub state: Option<InputState>,
+    /// Identifier of the widget found at the centre of `rect`
+    ///
+    /// This is assigned by [`DrawRecorder::annotate`].
+    pub id: Option<WidgetId>,
+}
+
+/// A [`DrawHandle`] wrapper recording all high-level draw commands
+///
+/// All calls are forwarded to the wrapped handle. Each call is also recorded
+/// as a [`DrawRecord`], allowing offline inspection of a drawn frame:
+/// ```
+/// # use kas::draw::{DrawHandle, DrawRecorder};
+/// # use kas::Layout;
+/// fn capture(widget: &dyn Layout, handle: &mut dyn DrawHandle, mgr: &kas::event::ManagerState) -> String {
+///     let mut records = vec![];
+///     widget.draw(&mut DrawRecorder::new(handle, &mut records), mgr, false);
+///     DrawRecorder::annotate(&mut records, widget);
+///     DrawRecorder::to_json(&records)
+/// }
+/// ```
+pub struct DrawRecorder<'a> {
+    handle: &'a mut dyn DrawHandle,
+    records: &'a mut Vec<DrawRecord>,
+    offset: Coord,
+    depth: u32,
+}
+
+impl<'a> DrawRecorder<'a> {
+    /// Construct, wrapping a `handle` and appending to `records`
+    pub fn new(handle: &'a mut dyn DrawHandle, records: &'a mut Vec<DrawRecord>) -> Self {
+        DrawRecorder {
+            handle,
+            records,
+            offset: Coord::ZERO,
+            depth: 0,
+        }
+    }
+
+    fn push(&mut self, kind: &'static str, rect: Rect, state: Option<InputState>) {
+        self.records.push(DrawRecord {
+            kind,
+            rect: rect - self.offset,
+            depth: self.depth,
+            text: None,
+            state,
+            id: None,
+        });
+    }
+
+    fn push_text(&mut self, kind: &'static str, pos: Coord, offset: Coord, text: &PreparedText) {
+        let bounds = text.env().bounds;
+        let size = Size(bounds.0 as u32, bounds.1 as u32);
+        self.records.push(DrawRecord {
+            kind,
+            rect: Rect::new(pos, size) - offset - self.offset,
+            depth: self.depth,
+            text: Some(text.text().to_string()),
+            state: None,
+            id: None,
+        });
+    }
+
+    /// Assign widget identifiers to records
+    ///
+    /// Each record is assigned the identifier of the widget found (via
+    /// [`Layout::find_id`]) at the centre of its `rect`.
+    pub fn annotate<W: Layout + ?Sized>(records: &mut [DrawRecord], widget: &W) {
+        for record in records {
+            let rect = record.rect;
+            let centre = rect.pos + Coord(rect.size.0 as i32 / 2, rect.size.1 as i32 / 2);
+            record.id = widget.find_id(centre);
+        }
+    }
+
+    /// Write records as a JSON array
+    pub fn to_json(records: &[DrawRecord]) -> String {
+        let mut s = String::from("[\n");
+        for (i, r) in records.iter().enumerate() {
+            let _ = write_json_record(&mut s, r);
+            s.push_str(if i + 1 < records.len() { ",\n" } else { "\n" });
+        }
+        s.push_str("]\n");
+        s
+    }
+
+    /// Write records as an SVG image of outlined rects and text
+    ///
+    /// The `size` should be the size of the window.
+    pub fn to_svg(records: &[DrawRecord], size: Size) -> String {
+        let mut s = String::new();
+        let _ = write_svg(&mut s, records, size);
+        s
+    }
+}
+
+fn escape(s: &str, xml: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' if !xml => out.push_str("\\\""),
+            '\\' if !xml => out.push_str("\\\\"),
+            '\n' if !xml => out.push_str("\\n"),
+            c if !xml && (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            '&' if xml => out.push_str("&amp;"),
+            '<' if xml => out.push_str("&lt;"),
+            '>' if xml => out.push_str("&gt;"),
+            '"' if xml => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// The (pixel-aligned) bounding rect of a path
+fn path_rect(path: &Path) -> Rect {
+    match path.bounds() {
+        Some(quad) => {
+            let pos = Coord::from(quad.a.floor());
+            let end = Coord::from(quad.b.ceil());
+            Rect::new(pos, Size::from(end - pos))
+        }
+        None => Rect::default(),
+    }
+}
+
+fn write_json_record(s: &mut String, r: &DrawRecord) -> fmt::Result {
+    let Rect { pos, size } = r.rect;
+    write!(
+        s,
+        "  {{\"kind\": \"{}\", \"rect\": [{}, {}, {}, {}], \"depth\": {}",
+        r.kind, pos.0, pos.1, size.0, size.1, r.depth
+    )?;
+    if let Some(id) = r.id {
+        write!(s, ", \"id\": {}", u32::from(id))?;
+    }
+    if let Some(ref text) = r.text {
+        write!(s, ", \"text\": \"{}\"", escape(text, false))?;
+    }
+    if let Some(state) = r.state {
+        write!(
+            s,
+            ", \"state\": {{\"disabled\": {}, \"error\": {}, \"hover\": {}, \"depress\": {}, \"nav_focus\": {}, \"char_focus\": {}}}",
+            state.disabled, state.error, state.hover, state.depress, state.nav_focus, state.char_focus
+        )?;
+    }
+    s.push('}');
+    Ok(())
+}
+
+fn write_svg(s: &mut String, records: &[DrawRecord], size: Size) -> fmt::Result {
+    writeln!(
+        s,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+        size.0, size.1
+    )?;
+    for r in records {
+        let Rect { pos, size } = r.rect;
+        let id = r.id.map(|id| id.to_string()).unwrap_or_default();
+        let col = match r.kind {
+            "clip_region" => "blue",
+            "text" | "text_with_underline" | "text_selected" | "edit_marker" => "green",
+            _ => "black",
+        };
+        writeln!(
+            s,
+            "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"{}\"><title>{} {}</title></rect>",
+            pos.0, pos.1, size.0, size.1, col, r.kind, id
+        )?;
+        if let Some(ref text) = r.text {
+            writeln!(
+                s,
+                "  <text x=\"{}\" y=\"{}\" dominant-baseline=\"hanging\">{}</text>",
+                pos.0,
+                pos.1,
+                escape(text, true)
+            )?;
+        }
+    }
+    writeln!(s, "</svg>")
+}
+
+impl<'a> DrawHandle for DrawRecorder<'a> {
+    fn size_handle_dyn(&mut self, f: &mut dyn FnMut(&mut dyn SizeHandle)) {
+        self.handle.size_handle_dyn(f)
+    }
+    fn draw_device(&mut self) -> (Pass, Coord, &mut dyn Draw) {
+        let rect = self.handle.target_rect();
+        self.push("draw_device", rect, None);
+        self.handle.draw_device()
+    }
+    fn clip_region(
+        &mut self,
+        rect: Rect,
+        offset: Coord,
+        class: ClipRegion,
+        f: &mut dyn FnMut(&mut dyn DrawHandle),
+    ) {
+        self.push("clip_region", rect, None);
+        let records = &mut *self.records;
+        let offset = self.offset + offset;
+        let depth = self.depth + 1;
+        self.handle.clip_region(rect, offset, class, &mut |handle| {
+            let mut recorder = DrawRecorder {
+                handle,
+                records: &mut *records,
+                offset,
+                depth,
+            };
+            f(&mut recorder);
+        });
+    }
+    fn cached_region(
+        &mut self,
+        key: u64,
+        rect: Rect,
+        valid: bool,
+        f: &mut dyn FnMut(&mut dyn DrawHandle),
+    ) {
+        self.push("cached_region", rect, None);
+        let records = &mut *self.records;
+        let offset = self.offset;
+        let depth = self.depth + 1;
+        self.handle.cached_region(key, rect, valid, &mut |handle| {
+            let mut recorder = DrawRecorder {
+                handle,
+                records: &mut *records,
+                offset,
+                depth,
+            };
+            f(&mut recorder);
+        });
+    }
+    fn layer(&mut self, rect: Rect, layer: Layer, f: &mut dyn FnMut(&mut dyn DrawHandle)) {
+        self.push("layer", rect, None);
+        let records = &mut *self.records;
+        let offset = self.offset;
+        let depth = self.depth + 1;
+        self.handle.layer(rect, layer, &mut |handle| {
+            let mut recorder = DrawRecorder {
+                handle,
+                records: &mut *records,
+                offset,
+                depth,
+            };
+            f(&mut recorder);
+        });
+    }
+    fn target_rect(&self) -> Rect {
+        self.handle.target_rect()
+    }
+    fn outer_frame(&mut self, rect: Rect) {
+        self.push("outer_frame", rect, None);
+        self.handle.outer_frame(rect);
+    }
+    fn menu_frame(&mut self, rect: Rect) {
+        self.push("menu_frame", rect, None);
+        self.handle.menu_frame(rect);
+    }
+    fn separator(&mut self, rect: Rect) {
+        self.push("separator", rect, None);
+        self.handle.separator(rect);
+    }
+    fn text_offset(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+    ) {
+        self.push_text("text", pos, offset, text);
+        self.handle.text_offset(pos, offset, text, class, state)
+    }
+    fn text_with_underline(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+        underline: usize,
+    ) {
+        self.push_text("text_with_underline", pos, offset, text);
+        self.handle
+            .text_with_underline(pos, offset, text, class, state, underline)
+    }
+    fn text_selected_range(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        range: Range<usize>,
+        class: TextClass,
+        state: InputState,
+    ) {
+        self.push_text("text_selected", pos, offset, text);
+        self.handle
+            .text_selected_range(pos, offset, text, range, class, state);
+    }
+    fn text_highlighted(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        state: InputState,
+        highlights: &[TextHighlight],
+    ) {
+        self.push_text("text_highlighted", pos, offset, text);
+        self.handle
+            .text_highlighted(pos, offset, text, class, state, highlights)
+    }
+    fn edit_marker(
+        &mut self,
+        pos: Coord,
+        offset: Coord,
+        text: &PreparedText,
+        class: TextClass,
+        byte: usize,
+    ) {
+        self.push_text("edit_marker", pos, offset, text);
+        self.handle.edit_marker(pos, offset, text, class, byte)
+    }
+    fn menu_entry(&mut self, rect: Rect, state: InputState) {
+        self.push("menu_entry", rect, Some(state));
+        self.handle.menu_entry(rect, state)
+    }
+    fn button(&mut self, rect: Rect, state: InputState) {
+        self.push("button", rect, Some(state));
+        self.handle.button(rect, state)
+    }
+    fn edit_box(&mut self, rect: Rect, state: InputState) {
+        self.push("edit_box", rect, Some(state));
+        self.handle.edit_box(rect, state)
+    }
+    fn checkbox(&mut self, rect: Rect, checked: bool, state: InputState) {
+        self.push("checkbox", rect, Some(state));
+        self.handle.checkbox(rect, checked, state)
+    }
+    fn radiobox(&mut self, rect: Rect, checked: bool, state: InputState) {
+        self.push("radiobox", rect, Some(state));
+        self.handle.radiobox(rect, checked, state)
+    }
+    fn scrollbar(&mut self, rect: Rect, h_rect: Rect, dir: Direction, state: InputState) {
+        self.push("scrollbar", rect, Some(state));
+        self.handle.scrollbar(rect, h_rect, dir, state)
+    }
+    fn slider(&mut self, rect: Rect, h_rect: Rect, dir: Direction, state: InputState) {
+        self.push("slider", rect, Some(state));
+        self.handle.slider(rect, h_rect, dir, state)
+    }
+    fn icon(&mut self, rect: Rect, icon: &Icon, state: InputState) {
+        self.push("icon", rect, Some(state));
+        self.handle.icon(rect, icon, state)
+    }
+    fn image(&mut self, rect: Rect, id: ImageId) {
+        self.push("image", rect, None);
+        self.handle.image(rect, id)
+    }
+    fn fill_path(&mut self, path: &Path, rule: FillRule, col: Colour) {
+        self.push("fill_path", path_rect(path), None);
+        self.handle.fill_path(path, rule, col)
+    }
+    fn stroke_path(&mut self, path: &Path, style: &StrokeStyle, col: Colour) {
+        self.push("stroke_path", path_rect(path), None);
+        self.handle.stroke_path(path, style, col)
+    }
+    fn selection(&mut self, rect: Rect, shape: HighlightShape, state: InputState) {
+        self.push("selection", rect, Some(state));
+        self.handle.selection(rect, shape, state)
+    }
+    fn focus_ring(&mut self, rect: Rect, shape: HighlightShape, state: InputState) {
+        self.push("focus_ring", rect, Some(state));
+        self.handle.focus_ring(rect, shape, state)
+    }
+    fn shadow(&mut self, rect: Rect) {
+        self.push("shadow", rect, None);
+        self.handle.shadow(rect)
+    }
+    fn well(&mut self, rect: Rect, state: InputState) {
+        self.push("well", rect, Some(state));
+        self.handle.well(rect, state)
+    }
+    fn badge(&mut self, rect: Rect, text: &PreparedText, state: InputState) {
+        self.push("badge", rect, Some(state));
+        self.push_text("text", rect.pos, Coord::ZERO, text);
+        self.handle.badge(rect, text, state)
+    }
+}
@@ -0,0 +1,114 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Event handling configuration
+
+use std::time::Duration;
+
+use super::{ScrollDelta, Shortcuts};
+use crate::geom::Coord;
+
+/// Input configuration
+///
+/// This is passed to the [`ManagerState`](super::ManagerState) by the toolkit
+/// and may be read by widgets via [`Manager::config`](super::Manager::config).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InputConfig {
+    /// Delay before an input held down begins to repeat
+    pub key_repeat_delay: Duration,
+    /// Interval between repeats of an input held down
+    pub key_repeat_interval: Duration,
+    /// Shortcut bindings
+    pub shortcuts: Shortcuts,
+    /// Number of lines scrolled per mouse-wheel notch
+    pub scroll_lines: u32,
+    /// Scroll speed of pixel-precise devices (e.g. touchpads), in percent
+    pub scroll_speed: u32,
+    /// Natural scrolling: invert the direction of wheel and touchpad scrolling
+    pub natural_scrolling: bool,
+    /// Maximum time spent on idle tasks per batch of events
+    ///
+    /// See [`Manager::on_idle`](super::Manager::on_idle).
+    pub idle_budget: Duration,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        InputConfig {
+            key_repeat_delay: Duration::from_millis(500),
+            key_repeat_interval: Duration::from_millis(40),
+            shortcuts: Shortcuts::default(),
+            scroll_lines: 3,
+            scroll_speed: 100,
+            natural_scrolling: false,
+            idle_budget: Duration::from_millis(4),
+        }
+    }
+}
+
+/// Per-widget override of scroll settings
+///
+/// Each field, where set, replaces the corresponding [`InputConfig`] setting
+/// for a single widget. Since [`ScrollDelta`] values have [`InputConfig`]
+/// already applied, widgets should pass received deltas through
+/// [`ScrollOverride::apply`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ScrollOverride {
+    /// Replaces [`InputConfig::scroll_lines`]
+    pub lines: Option<u32>,
+    /// Replaces [`InputConfig::scroll_speed`]
+    pub speed: Option<u32>,
+    /// Replaces [`InputConfig::natural_scrolling`]
+    pub natural_scrolling: Option<bool>,
+}
+
+impl ScrollOverride {
+    /// Adjust a `delta` (with `config` applied) to use these settings
+    pub fn apply(&self, config: &InputConfig, delta: ScrollDelta) -> ScrollDelta {
+        let sign = match self.natural_scrolling {
+            Some(natural) if natural != config.natural_scrolling => -1.0,
+            _ => 1.0,
+        };
+        match delta {
+            ScrollDelta::LineDelta(x, y) => {
+                let lines = self.lines.unwrap_or(config.scroll_lines);
+                let factor = sign * lines as f32 / config.scroll_lines.max(1) as f32;
+                ScrollDelta::LineDelta(x * factor, y * factor)
+            }
+            ScrollDelta::PixelDelta(d) => {
+                let speed = self.speed.unwrap_or(config.scroll_speed);
+                let factor = sign * speed as f32 / config.scroll_speed.max(1) as f32;
+                let x = (d.0 as f32 * factor).round() as i32;
+                let y = (d.1 as f32 * factor).round() as i32;
+                ScrollDelta::PixelDelta(Coord(x, y))
+            }
+        }
+    }
+}
+
+#[test]
+fn scroll_override() {
+    let config = InputConfig::default();
+    let delta = ScrollDelta::LineDelta(0.0, 3.0);
+    assert_eq!(ScrollOverride::default().apply(&config, delta), delta);
+    let lines = ScrollOverride {
+        lines: Some(1),
+        natural_scrolling: Some(true),
+        ..Default::default()
+    };
+    assert_eq!(
+        lines.apply(&config, delta),
+        ScrollDelta::LineDelta(0.0, -1.0)
+    );
+    let speed = ScrollOverride {
+        speed: Some(50),
+        ..Default::default()
+    };
+    let delta = ScrollDelta::PixelDelta(Coord(10, -20));
+    assert_eq!(
+        speed.apply(&config, delta),
+        ScrollDelta::PixelDelta(Coord(5, -10))
+    );
+}
@@ -109,6 +109,10 @@ pub enum Event {
     /// Movement of mouse or a touch press
     ///
     /// Received only given a [press grab](Manager::request_grab).
+    ///
+    /// The grab continues when the press moves outside of the window, in which
+    /// case `coord` lies outside the window bounds (and may be negative) and
+    /// `cur_id` is `None`.
     PressMove {
         source: PressSource,
         cur_id: Option<WidgetId>,
@@ -126,6 +130,32 @@ pub enum Event {
         end_id: Option<WidgetId>,
         coord: Coord,
     },
+    /// A drag is over this widget
+    ///
+    /// Sent to the widget under the cursor (in any window of the application)
+    /// each time the cursor moves during a drag started via
+    /// [`Manager::start_drag`]. Widgets accepting the `data` should respond
+    /// (e.g. by highlighting); other widgets should return the event via
+    /// [`Response::Unhandled`] so that parents may handle it instead.
+    DragOver { coord: Coord, data: DragData },
+    /// The drag has left this widget
+    ///
+    /// Sent to the previous target of [`Event::DragOver`] when the cursor
+    /// moves to another widget or window, and when the drag ends. Like
+    /// [`Event::DragOver`], this is passed to parents until handled.
+    DragLeave,
+    /// Drop the `data` on this widget
+    ///
+    /// Sent to the widget under the cursor when the drag ends. The drop is
+    /// considered accepted unless the event is returned via
+    /// [`Response::Unhandled`] (by all parents).
+    Drop { coord: Coord, data: DragData },
+    /// A drag started by this widget has ended
+    ///
+    /// `accepted` is true when the data was dropped on a widget handling
+    /// [`Event::Drop`] (possibly in another window). The source may then e.g.
+    /// remove the dragged item.
+    DragEnd { accepted: bool },
     /// Update from a timer
     ///
     /// This event is received after requesting timed wake-up(s)
@@ -153,8 +183,90 @@ pub enum Event {
     PopupRemoved(WindowId),
     /// Sent when a widget receives keyboard navigation focus
     ///
+    /// This is sent (after event handling) whenever navigation focus changes,
+    /// whether by use of the Tab key or by [`Manager::set_nav_focus`].
+    ///
     /// The widget should reply with [`Response::Focus`].
+    /// [`Manager::handle_generic`] passes this event to [`Handler::handle`],
+    /// then replies with [`Response::Focus`] unless a message is returned.
+    ///
+    /// [`Handler::handle`]: super::Handler::handle
     NavFocus,
+    /// Sent when a widget loses keyboard navigation focus
+    ///
+    /// This is the counterpart to [`Event::NavFocus`], and may be used e.g. to
+    /// validate input when focus moves on.
+    LostNavFocus,
+    /// Notification that the power status changed
+    ///
+    /// This is sent to widgets subscribed via [`Manager::update_on_power`].
+    /// Widgets may use this to e.g. reduce animation frame rates or polling
+    /// intervals while on battery power.
+    PowerChanged(PowerStatus),
+    /// Notification that the user is idle
+    ///
+    /// This is sent to widgets subscribed via [`Manager::update_on_idle`]
+    /// once the window has received no user input for the requested duration.
+    Idle,
+    /// Notification that the user is no longer idle
+    ///
+    /// This is sent on the first user input following [`Event::Idle`].
+    IdleEnd,
+}
+
+/// Source of power (see [`PowerStatus`])
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PowerSource {
+    /// Unknown (e.g. unsupported on this platform)
+    Unknown,
+    /// External power (mains / AC adapter)
+    External,
+    /// Running on battery
+    Battery,
+}
+
+impl Default for PowerSource {
+    fn default() -> Self {
+        PowerSource::Unknown
+    }
+}
+
+/// Power status (see [`Manager::power_status`])
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PowerStatus {
+    /// The current power source
+    pub source: PowerSource,
+    /// Battery charge, in the range `0.0..=1.0`, if known
+    pub battery: Option<f32>,
+}
+
+impl PowerStatus {
+    /// True if running on battery
+    #[inline]
+    pub fn on_battery(&self) -> bool {
+        self.source == PowerSource::Battery
+    }
+}
+
+/// Data carried by a drag (see [`Manager::start_drag`])
+///
+/// Drags may cross window boundaries, thus data is passed by value. The
+/// `kind` allows drop targets to decide whether they accept the data, while
+/// the `payload` is interpreted according to `kind`, for example as an index
+/// into some application-wide store. As with [`Event::HandleUpdate`], this is
+/// not type safe.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DragData {
+    pub kind: &'static str,
+    pub payload: u64,
+}
+
+impl DragData {
+    /// Construct
+    #[inline]
+    pub fn new(kind: &'static str, payload: u64) -> Self {
+        DragData { kind, payload }
+    }
 }
 
 /// Control / Navigation key ([`Event::Control`])
@@ -242,6 +354,35 @@ pub enum ControlKey {
     Backward,
     /// Navigate forwards one page/item
     Forward,
+
+    /// Media: play or pause
+    PlayPause,
+    /// Media: stop playback
+    Stop,
+    /// Media: next track
+    NextTrack,
+    /// Media: previous track
+    PrevTrack,
+    /// Mute or unmute audio
+    Mute,
+    /// Increase volume
+    VolumeUp,
+    /// Decrease volume
+    VolumeDown,
+
+    /// Zoom in
+    ///
+    /// If not handled by a widget, this increases the UI scale (see
+    /// [`Manager::set_ui_scale`]).
+    ZoomIn,
+    /// Zoom out
+    ///
+    /// If not handled by a widget, this decreases the UI scale.
+    ZoomOut,
+    /// Reset zoom
+    ///
+    /// If not handled by a widget, this resets the UI scale to 1.
+    ZoomReset,
 }
 
 impl ControlKey {
@@ -331,6 +472,11 @@ impl PressSource {
 }
 
 /// Type used by [`Event::Scroll`]
+///
+/// Mouse-wheel and touchpad deltas have [`InputConfig`] applied (lines per
+/// notch, speed and natural scrolling) before the event is sent.
+///
+/// [`InputConfig`]: super::InputConfig
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ScrollDelta {
     /// Scroll a given number of lines
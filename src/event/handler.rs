@@ -144,7 +144,12 @@ impl<'a> Manager<'a> {
             };
         }
         match event {
-            Event::NavFocus => return Response::Focus(widget.rect()),
+            Event::NavFocus => {
+                return match widget.handle(mgr, event) {
+                    Response::None | Response::Unhandled(_) => Response::Focus(widget.rect()),
+                    r => r,
+                };
+            }
             _ => (),
         }
         widget.handle(mgr, event)
@@ -8,17 +8,20 @@
 // Without winit, several things go unused
 #![cfg_attr(not(feature = "winit"), allow(unused))]
 
-use log::trace;
+use instant::Instant;
+use log::{trace, warn};
 use smallvec::SmallVec;
-use std::collections::HashMap;
-use std::time::Instant;
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::Duration;
 use std::u16;
 
 use super::*;
-use crate::geom::Coord;
-#[allow(unused)]
-use crate::WidgetConfig; // for doc-links
-use crate::{TkAction, TkWindow, Widget, WidgetId, WindowId};
+use crate::geom::{Coord, Rect};
+use crate::WidgetConfig;
+use crate::{TkAction, TkWindow, Widget, WidgetDescription, WidgetId, WindowId};
 
 mod mgr_pub;
 mod mgr_tk;
@@ -38,6 +41,23 @@ pub enum GrabMode {
     PanOnly,
 }
 
+/// Controls mouse cursor behaviour (see [`Manager::set_cursor_mode`])
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CursorMode {
+    /// Normal behaviour
+    Normal,
+    /// Confine the cursor to the given rect
+    ///
+    /// The rect is given in window coordinates.
+    Confined(Rect),
+    /// Hide the cursor and lock it in place
+    ///
+    /// In this mode mouse motion is reported only via the `delta` of
+    /// [`Event::PressMove`]; the `coord` remains fixed. This is useful for
+    /// e.g. 3D viewports and drag-to-adjust number inputs.
+    Relative,
+}
+
 #[derive(Clone, Debug)]
 struct MouseGrab {
     button: MouseButton,
@@ -73,6 +93,16 @@ struct PanGrab {
 #[derive(Clone, Debug)]
 enum Pending {
     LostCharFocus(WidgetId),
+    SetDisabled(WidgetId, bool),
+}
+
+// A low-priority task; see Manager::on_idle
+struct IdleTask(Box<dyn FnOnce(&mut Manager)>);
+
+impl fmt::Debug for IdleTask {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "IdleTask")
+    }
 }
 
 /// Event manager state
@@ -93,14 +123,23 @@ enum Pending {
 pub struct ManagerState {
     end_id: WidgetId,
     dpi_factor: f64,
+    config: InputConfig,
     modifiers: ModifiersState,
     char_focus: Option<WidgetId>,
     nav_focus: Option<WidgetId>,
+    last_nav_focus: Option<WidgetId>,
     nav_fallback: Option<WidgetId>,
+    control_targets: HashMap<ControlKey, WidgetId>,
     nav_stack: SmallVec<[u32; 16]>,
     hover: Option<WidgetId>,
     hover_icon: CursorIcon,
+    cursor_mode: Option<(WidgetId, CursorMode, Coord)>,
+    drag_source: Option<WidgetId>,
+    drag_target: Option<WidgetId>,
     key_depress: SmallVec<[(u32, WidgetId); 10]>,
+    // last key pressed without modifiers (scancode, key code), used to learn
+    // the KeyLayout
+    last_key: Option<(u32, Option<VirtualKeyCode>)>,
     last_mouse_coord: Coord,
     last_click_button: MouseButton,
     last_click_repetitions: u32,
@@ -110,15 +149,27 @@ pub struct ManagerState {
     pan_grab: SmallVec<[PanGrab; 4]>,
     accel_stack: Vec<(bool, HashMap<VirtualKeyCode, WidgetId>)>,
     accel_layers: HashMap<WidgetId, (bool, HashMap<VirtualKeyCode, WidgetId>)>,
+    names: HashMap<String, WidgetId>,
     popups: SmallVec<[(WindowId, kas::Popup); 16]>,
     new_popups: SmallVec<[WidgetId; 16]>,
     popup_removed: SmallVec<[(WidgetId, WindowId); 16]>,
 
     time_start: Instant,
     time_updates: Vec<(Instant, WidgetId)>,
+    press_repeat: SmallVec<[(Instant, WidgetId, PressSource); 2]>,
     // TODO(opt): consider other containers, e.g. C++ multimap
     // or sorted Vec with binary search yielding a range
     handle_updates: HashMap<UpdateHandle, Vec<WidgetId>>,
+    power: PowerStatus,
+    power_updates: Vec<WidgetId>,
+    last_input: Instant,
+    // (duration, widget, is idle)
+    idle_updates: Vec<(Duration, WidgetId, bool)>,
+    idle_tasks: VecDeque<IdleTask>,
+    screensaver_inhibitors: SmallVec<[WidgetId; 2]>,
+    relayout: SmallVec<[WidgetId; 4]>,
+    render_states: HashMap<WidgetId, u64>,
+    animation_frame: Cell<bool>,
     pending: SmallVec<[Pending; 8]>,
     action: TkAction,
 }
@@ -223,11 +274,34 @@ pub struct Manager<'a> {
     action: TkAction,
 }
 
+// Describe widget `id` (a descendant of `widget`) for log messages
+fn describe<W: Widget + ?Sized>(widget: &W, id: WidgetId) -> WidgetDescription {
+    WidgetDescription::find(widget.as_widget(), id)
+}
+
+/// Get `id` followed by its ancestors within `widget` (innermost first)
+fn ancestors(mut widget: &dyn WidgetConfig, id: WidgetId) -> SmallVec<[WidgetId; 16]> {
+    let mut path = SmallVec::new();
+    'l: while widget.is_ancestor_of(id) {
+        path.push(widget.id());
+        for index in 0..widget.len() {
+            let w = widget.get(index).unwrap();
+            if w.is_ancestor_of(id) {
+                widget = w;
+                continue 'l;
+            }
+        }
+        break;
+    }
+    path.reverse();
+    path
+}
+
 /// Internal methods
 impl<'a> Manager<'a> {
     fn set_hover<W: Widget + ?Sized>(&mut self, widget: &mut W, w_id: Option<WidgetId>) {
         if self.mgr.hover != w_id {
-            trace!("Manager: hover = {:?}", w_id);
+            trace!("Manager: hover = {:?}", w_id.map(|id| describe(widget, id)));
             self.mgr.hover = w_id;
             self.send_action(TkAction::Redraw);
 
@@ -246,26 +320,24 @@ impl<'a> Manager<'a> {
         }
     }
 
-    /// Match global shortcuts.
-    ///
-    /// TODO: this should be configurable and extensible with the option for
-    /// global shortcuts to target specific widgets. Possibly we should just use
-    /// an integer with a large block for user-defined codes, and require that
-    /// apps register their short-cut codes with a name and optional WidgetId.
+    /// Note user input, ending any idle state
+    fn end_idle<W: Widget + ?Sized>(&mut self, widget: &mut W) {
+        self.mgr.last_input = self.tkw.now();
+        for i in 0..self.mgr.idle_updates.len() {
+            let (_, id, idle) = self.mgr.idle_updates[i];
+            if idle {
+                self.mgr.idle_updates[i].2 = false;
+                self.send_event(widget, id, Event::IdleEnd);
+            }
+        }
+    }
+
+    /// Match shortcuts via [`InputConfig::shortcuts`], then [`ControlKey::new`]
     fn match_shortcuts(&self, vkey: VirtualKeyCode) -> Option<ControlKey> {
-        use VirtualKeyCode as VK;
-        let ctrl = self.mgr.modifiers.ctrl();
-        let shift = self.mgr.modifiers.shift();
-        Some(match (ctrl, shift, vkey) {
-            (true, false, VK::A) => ControlKey::SelectAll,
-            (true, true, VK::A) => ControlKey::Deselect,
-            (true, _, VK::C) => ControlKey::Copy,
-            (true, _, VK::V) => ControlKey::Paste,
-            (true, _, VK::X) => ControlKey::Cut,
-            (true, false, VK::Z) => ControlKey::Undo,
-            (true, true, VK::Z) => ControlKey::Redo,
-            (_, _, vkey) => return ControlKey::new(vkey),
-        })
+        let shortcuts = &self.mgr.config.shortcuts;
+        shortcuts
+            .get(self.mgr.modifiers, vkey)
+            .or_else(|| ControlKey::new(vkey))
     }
 
     fn start_key_event<W>(&mut self, widget: &mut W, vkey: VirtualKeyCode, scancode: u32)
@@ -278,11 +350,11 @@ impl<'a> Manager<'a> {
         if let Some(id) = self.mgr.char_focus {
             if let Some(key) = opt_control {
                 let event = Event::Control(key);
-                trace!("Send to {}: {:?}", id, event);
+                trace!("Send to {}: {:?}", describe(widget, id), event);
                 match widget.send(self, id, event) {
                     Response::Unhandled(Event::Control(key)) => match key {
                         ControlKey::Escape => self.set_char_focus(None),
-                        _ => (),
+                        key => self.unhandled_control_key(key),
                     },
                     _ => (),
                 }
@@ -294,9 +366,6 @@ impl<'a> Manager<'a> {
             if !self.next_nav_focus(widget.as_widget(), self.mgr.modifiers.shift()) {
                 self.clear_nav_focus();
             }
-            if let Some(id) = self.mgr.nav_focus {
-                self.send_event(widget, id, Event::NavFocus);
-            }
         } else if vkey == VK::Escape {
             if let Some(id) = self.mgr.popups.last().map(|(id, _)| *id) {
                 self.close_window(id);
@@ -324,8 +393,10 @@ impl<'a> Manager<'a> {
                             let ev = Event::Control(key);
                             id_action = Some((popup.1.parent, ev));
                         }
-                    } else if let Some(id) = self.mgr.nav_fallback {
-                        if let Some(key) = opt_control {
+                    } else if let Some(key) = opt_control {
+                        if let Some(id) = self.mgr.control_targets.get(&key) {
+                            id_action = Some((*id, Event::Control(key)));
+                        } else if let Some(id) = self.mgr.nav_fallback {
                             id_action = Some((id, Event::Control(key)));
                         }
                     }
@@ -364,7 +435,10 @@ impl<'a> Manager<'a> {
 
             if let Some((id, event)) = id_action {
                 let is_activate = event == Event::Activate;
-                self.send_event(widget, id, event);
+                trace!("Send to {}: {:?}", describe(widget, id), event);
+                if let Response::Unhandled(Event::Control(key)) = widget.send(self, id, event) {
+                    self.unhandled_control_key(key);
+                }
 
                 // Event::Activate causes buttons to be visually depressed
                 if is_activate {
@@ -377,10 +451,24 @@ impl<'a> Manager<'a> {
                     self.mgr.key_depress.push((scancode, id));
                     self.redraw(id);
                 }
+            } else if let Some(key) = opt_control {
+                self.unhandled_control_key(key);
             }
         }
     }
 
+    /// Default handling of control keys not handled by any widget
+    fn unhandled_control_key(&mut self, key: ControlKey) {
+        // UI scale is adjusted in steps of 10%
+        let steps = (self.ui_scale() * 10.0).round();
+        match key {
+            ControlKey::ZoomIn => self.set_ui_scale((steps + 1.0) * 0.1),
+            ControlKey::ZoomOut => self.set_ui_scale((steps - 1.0) * 0.1),
+            ControlKey::ZoomReset => self.set_ui_scale(1.0),
+            _ => (),
+        }
+    }
+
     fn end_key_event(&mut self, scancode: u32) {
         // We must match scancode not vkey since the latter may have changed due to modifiers
 
@@ -406,7 +494,10 @@ impl<'a> Manager<'a> {
         self.mgr.mouse_grab.clone()
     }
 
-    fn end_mouse_grab(&mut self, button: MouseButton) {
+    /// End the mouse grab of `button`, if any
+    ///
+    /// Any drag started from the grab is dropped if `drop`, otherwise cancelled.
+    fn end_mouse_grab(&mut self, button: MouseButton, drop: bool) {
         if self
             .mgr
             .mouse_grab
@@ -418,6 +509,12 @@ impl<'a> Manager<'a> {
         }
         if let Some(grab) = self.mgr.mouse_grab.take() {
             trace!("Manager: end mouse grab by {}", grab.start_id);
+            if self.mgr.cursor_mode.map(|m| m.0) == Some(grab.start_id) {
+                self.set_cursor_mode(grab.start_id, CursorMode::Normal);
+            }
+            if self.mgr.drag_source == Some(grab.start_id) {
+                self.tkw.end_drag(drop);
+            }
             self.tkw.set_cursor_icon(self.mgr.hover_icon);
             self.redraw(grab.start_id);
             self.mgr.remove_pan_grab(grab.pan_grab);
@@ -465,14 +562,68 @@ impl<'a> Manager<'a> {
         trace!("Manager: char_focus = {:?}", wid);
     }
 
+    fn apply_disabled<W: Widget + ?Sized>(&mut self, widget: &mut W, id: WidgetId, state: bool) {
+        let w = match widget.find_mut(id) {
+            Some(w) => w,
+            None => {
+                warn!("Manager::set_disabled: widget {} not found", id);
+                return;
+            }
+        };
+        trace!(
+            "Manager: set disabled = {} on {}",
+            state,
+            WidgetDescription::new(w)
+        );
+        let action = w.set_disabled(state);
+        self.send_action(action);
+
+        if state {
+            // Input focus may not remain within a disabled sub-tree
+            if let Some(char_id) = self.mgr.char_focus {
+                if w.is_ancestor_of(char_id) {
+                    self.set_char_focus(None);
+                }
+            }
+            if let Some(nav_id) = self.mgr.nav_focus {
+                if w.is_ancestor_of(nav_id) {
+                    self.clear_nav_focus();
+                }
+            }
+        }
+    }
+
     fn send_event<W: Widget + ?Sized>(&mut self, widget: &mut W, id: WidgetId, event: Event) {
-        trace!("Send to {}: {:?}", id, event);
+        trace!("Send to {}: {:?}", describe(widget, id), event);
         let _ = widget.send(self, id, event);
     }
 
+    /// Send `event` to `id`, then to each of its ancestors until handled
+    ///
+    /// Returns true if the event was handled.
+    fn send_bubbling<W: Widget + ?Sized>(
+        &mut self,
+        widget: &mut W,
+        id: WidgetId,
+        event: Event,
+    ) -> bool {
+        for id in ancestors(widget.as_widget(), id) {
+            trace!("Send to {}: {:?}", describe(widget, id), event);
+            match widget.send(self, id, event.clone()) {
+                Response::Unhandled(_) => (),
+                _ => return true,
+            }
+        }
+        false
+    }
+
     fn send_popup_first<W: Widget + ?Sized>(&mut self, widget: &mut W, id: WidgetId, event: Event) {
         while let Some((wid, parent)) = self.mgr.popups.last().map(|(wid, p)| (*wid, p.parent)) {
-            trace!("Send to popup parent: {}: {:?}", parent, event);
+            trace!(
+                "Send to popup parent: {}: {:?}",
+                describe(widget, parent),
+                event
+            );
             match widget.send(self, parent, event.clone()) {
                 Response::Unhandled(_) => (),
                 _ => return,
@@ -513,6 +664,22 @@ impl<'a: 'b, 'b> ConfigureManager<'a, 'b> {
         id
     }
 
+    /// Get the next [`WidgetId`] to be assigned, without assigning it
+    pub fn peek_id(&self) -> WidgetId {
+        *self.id
+    }
+
+    /// Skip `n` identifiers
+    ///
+    /// Widgets configuring only some children (see
+    /// [`ManagerState::configure_pending`]) may use this to reserve
+    /// identifiers for children configured later, thus keeping identifiers of
+    /// widgets outside their subtree stable.
+    pub fn skip_ids(&mut self, n: u32) {
+        let id = u32::from(*self.id) + n;
+        *self.id = WidgetId::try_from(id).unwrap();
+    }
+
     /// Get access to the wrapped [`Manager`]
     pub fn mgr(&mut self) -> &mut Manager<'a> {
         self.mgr
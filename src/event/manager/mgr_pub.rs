@@ -6,15 +6,17 @@
 //! Event manager — public API
 
 use log::{debug, trace, warn};
-use std::time::{Duration, Instant};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
 use std::u16;
 
 use super::*;
 use crate::draw::SizeHandle;
 use crate::geom::Coord;
+use crate::{Capture, NavHint, ThemeAction, ThemeApi, TkAction, WidgetId, WindowId};
 #[allow(unused)]
-use crate::WidgetConfig; // for doc-links
-use crate::{ThemeAction, ThemeApi, TkAction, WidgetId, WindowId};
+use crate::{WidgetChildren, WidgetConfig}; // for doc-links
 
 impl<'a> std::ops::AddAssign<TkAction> for Manager<'a> {
     #[inline]
@@ -25,6 +27,17 @@ impl<'a> std::ops::AddAssign<TkAction> for Manager<'a> {
 
 /// Public API (around event manager state)
 impl ManagerState {
+    /// Request that another frame be drawn
+    ///
+    /// This may be called while drawing (e.g. from [`kas::Layout::draw`]) by
+    /// widgets with a running animation (see [`kas::anim`]). The toolkit
+    /// draws the next frame as soon as possible, thus animations proceed at
+    /// the display's frame rate without the use of timers.
+    #[inline]
+    pub fn request_animation_frame(&self) {
+        self.animation_frame.set(true);
+    }
+
     /// True when accelerator key labels should be shown
     ///
     /// (True when Alt is held.)
@@ -35,6 +48,18 @@ impl ManagerState {
         self.modifiers.alt()
     }
 
+    /// Get the current modifier state
+    #[inline]
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    /// Get the input configuration
+    #[inline]
+    pub fn config(&self) -> &InputConfig {
+        &self.config
+    }
+
     /// Get whether this widget has a grab on character input
     #[inline]
     pub fn char_focus(&self, w_id: WidgetId) -> bool {
@@ -71,6 +96,21 @@ impl ManagerState {
         }
         false
     }
+
+    /// Find a widget by name
+    ///
+    /// Returns the identifier of the widget registered under this `name` via
+    /// [`Manager::register_name`], if any.
+    #[inline]
+    pub fn find_named(&self, name: &str) -> Option<WidgetId> {
+        self.names.get(name).cloned()
+    }
+
+    /// Get the current power status
+    #[inline]
+    pub fn power_status(&self) -> PowerStatus {
+        self.power
+    }
 }
 
 /// Public API (around toolkit functionality)
@@ -81,6 +121,21 @@ impl<'a> Manager<'a> {
         self.mgr.modifiers
     }
 
+    /// Get the input configuration
+    #[inline]
+    pub fn config(&self) -> &InputConfig {
+        &self.mgr.config
+    }
+
+    /// Get the current power status
+    ///
+    /// Where the toolkit does not support power status on this platform, the
+    /// source is reported as [`PowerSource::Unknown`].
+    #[inline]
+    pub fn power_status(&self) -> PowerStatus {
+        self.mgr.power
+    }
+
     /// Schedule an update
     ///
     /// Widgets requiring animation should schedule an update; as a result,
@@ -95,7 +150,7 @@ impl<'a> Manager<'a> {
     /// handler. Note that previously-scheduled updates are cleared when
     /// widgets are reconfigured.
     pub fn update_on_timer(&mut self, duration: Duration, w_id: WidgetId) {
-        let time = Instant::now() + duration;
+        let time = self.tkw.now() + duration;
         'outer: loop {
             for row in &mut self.mgr.time_updates {
                 if row.1 == w_id {
@@ -130,6 +185,66 @@ impl<'a> Manager<'a> {
             .push(w_id);
     }
 
+    /// Subscribe to power status changes
+    ///
+    /// The widget will be sent [`Event::PowerChanged`] whenever the power
+    /// source or battery level changes. Animated widgets may use this to
+    /// reduce their frame rate while [on battery](PowerStatus::on_battery).
+    ///
+    /// This should be called from [`WidgetConfig::configure`].
+    pub fn update_on_power(&mut self, w_id: WidgetId) {
+        self.mgr.power_updates.push(w_id);
+    }
+
+    /// Subscribe to idle notifications
+    ///
+    /// The widget will be sent [`Event::Idle`] once the window has received
+    /// no user input (keyboard, mouse or touch) for `duration`, then
+    /// [`Event::IdleEnd`] on the next input.
+    ///
+    /// This should be called from [`WidgetConfig::configure`].
+    pub fn update_on_idle(&mut self, duration: Duration, w_id: WidgetId) {
+        self.mgr.idle_updates.push((duration, w_id, false));
+    }
+
+    /// Schedule low-priority work
+    ///
+    /// The closure `f` is queued and run once the window is idle: after all
+    /// pending input events have been handled and when no redraw or
+    /// animation is pending. Tasks run in order; when the time
+    /// budget ([`InputConfig::idle_budget`]) is exhausted, remaining tasks are
+    /// deferred until after the next batch of events.
+    ///
+    /// Each task should be short (e.g. loading a single thumbnail); longer
+    /// jobs should be split, with each task scheduling the next. Results may
+    /// be passed to widgets via [`Manager::trigger_update`].
+    pub fn on_idle<F: FnOnce(&mut Manager) + 'static>(&mut self, f: F) {
+        self.mgr.idle_tasks.push_back(IdleTask(Box::new(f)));
+    }
+
+    /// Get the time since the last user input to this window
+    #[inline]
+    pub fn idle_time(&self) -> Duration {
+        self.tkw
+            .now()
+            .saturating_duration_since(self.mgr.last_input)
+    }
+
+    /// Notify that a widget's size requirements changed
+    ///
+    /// This is a more selective alternative to sending
+    /// [`TkAction::Resize`]: only the size requirements of widget `id` and
+    /// its ancestors are recalculated, while other widgets use cached rules
+    /// (see [`kas::layout::LayoutCache`]). For example, a parent may call this
+    /// after changing a child's size requirements. (A widget changing its own
+    /// requirements may instead use [`kas::layout::LayoutCache::set_dirty`].)
+    pub fn relayout(&mut self, id: WidgetId) {
+        if !self.mgr.relayout.contains(&id) {
+            self.mgr.relayout.push(id);
+        }
+        self.send_action(TkAction::Relayout);
+    }
+
     /// Notify that a widget must be redrawn
     ///
     /// Currently the entire window is redrawn on any redraw request and the
@@ -141,6 +256,32 @@ impl<'a> Manager<'a> {
         self.send_action(TkAction::Redraw);
     }
 
+    /// Notify that a widget must be redrawn, if its render state changed
+    ///
+    /// Widgets may use this method in place of [`Manager::redraw`] to declare
+    /// a *render state*: any hashable value determining the widget's
+    /// appearance (e.g. the displayed text and value). A redraw is scheduled
+    /// only if the hash of `state` differs from that passed by the last call
+    /// for the same widget. This avoids redundant redraws when a widget is
+    /// updated frequently (e.g. from a chatty data source) without visible
+    /// change.
+    ///
+    /// Returns true if a redraw was scheduled.
+    ///
+    /// Note that this only tracks state passed to this method; other changes
+    /// (e.g. hover or focus state) are handled separately.
+    pub fn redraw_if_changed<S: Hash + ?Sized>(&mut self, id: WidgetId, state: &S) -> bool {
+        let mut hasher = DefaultHasher::new();
+        state.hash(&mut hasher);
+        let hash = hasher.finish();
+        if self.mgr.render_states.insert(id, hash) == Some(hash) {
+            trace!("Manager: skipping redraw of {}: render state unchanged", id);
+            return false;
+        }
+        self.redraw(id);
+        true
+    }
+
     /// Notify that a [`TkAction`] action should happen
     ///
     /// This causes the given action to happen after event handling.
@@ -250,6 +391,111 @@ impl<'a> Manager<'a> {
         self.tkw.set_clipboard(content)
     }
 
+    /// Attempt to get contents of the primary selection
+    ///
+    /// The primary selection (currently supported only on X11, including
+    /// XWayland) is set on text selection and usually pasted via middle-click.
+    /// Where not supported, this returns `None`.
+    #[inline]
+    pub fn get_primary(&mut self) -> Option<String> {
+        self.tkw.get_primary()
+    }
+
+    /// Attempt to set contents of the primary selection
+    ///
+    /// Where not supported, this does nothing.
+    #[inline]
+    pub fn set_primary<'c>(&mut self, content: std::borrow::Cow<'c, str>) {
+        self.tkw.set_primary(content)
+    }
+
+    /// Set progress shown on the window's taskbar entry
+    ///
+    /// `progress` should be in the range `0.0..=1.0`; values outside this
+    /// range are clamped. `None` removes the progress indicator. On some
+    /// platforms (e.g. Linux and macOS) the indicator is shared by all windows
+    /// of the application.
+    ///
+    /// Where not supported by the platform or toolkit, this does nothing.
+    #[inline]
+    pub fn set_taskbar_progress(&mut self, progress: Option<f32>) {
+        let progress = progress.map(|p| p.max(0.0).min(1.0));
+        self.tkw.set_taskbar_progress(progress)
+    }
+
+    /// Set a count shown as a badge on the application's taskbar entry
+    ///
+    /// `None` removes the badge.
+    ///
+    /// Where not supported by the platform or toolkit, this does nothing.
+    #[inline]
+    pub fn set_taskbar_badge(&mut self, count: Option<u32>) {
+        self.tkw.set_taskbar_badge(count)
+    }
+
+    /// Request the user's attention
+    ///
+    /// This usually flashes or bounces the window's taskbar entry until the
+    /// window is focussed. A `critical` request may be more insistent.
+    ///
+    /// Where not supported by the platform or toolkit, this does nothing.
+    #[inline]
+    pub fn request_attention(&mut self, critical: bool) {
+        self.tkw.request_attention(critical)
+    }
+
+    /// Capture the window's content
+    ///
+    /// The window's next frame is captured as an RGBA image and passed to
+    /// `f`, e.g. to save a screenshot. Where not supported by the toolkit,
+    /// `f` is not called.
+    #[inline]
+    pub fn capture<F: FnOnce(Capture) + 'static>(&mut self, f: F) {
+        self.tkw.capture(Box::new(f))
+    }
+
+    /// Inhibit (or stop inhibiting) the system screensaver
+    ///
+    /// While any widget `id` inhibits the screensaver (e.g. a playing video),
+    /// the toolkit requests that the screensaver and display power-saving are
+    /// not activated. Widgets should stop inhibiting when no longer required;
+    /// inhibition also ends when the widget is removed.
+    ///
+    /// Where not supported by the platform or toolkit, this does nothing.
+    pub fn inhibit_screensaver(&mut self, id: WidgetId, inhibit: bool) {
+        let inhibitors = &mut self.mgr.screensaver_inhibitors;
+        let was_inhibited = !inhibitors.is_empty();
+        if inhibit {
+            if !inhibitors.contains(&id) {
+                inhibitors.push(id);
+            }
+        } else {
+            inhibitors.retain(|w_id| *w_id != id);
+        }
+        if was_inhibited != !inhibitors.is_empty() {
+            self.tkw.inhibit_screensaver(!was_inhibited);
+        }
+    }
+
+    /// Get the application-level UI scale factor
+    ///
+    /// This is a zoom factor (default: 1.0) applied to theme dimensions and
+    /// fonts, on top of the monitor's DPI factor.
+    #[inline]
+    pub fn ui_scale(&self) -> f32 {
+        self.tkw.ui_scale()
+    }
+
+    /// Set the application-level UI scale factor
+    ///
+    /// For example, `1.25` sets a 125% zoom. This resizes all windows.
+    /// Toolkits may clamp the value. Zoom shortcuts not handled by any widget
+    /// (see [`ControlKey::ZoomIn`]) adjust this value.
+    #[inline]
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        self.tkw.set_ui_scale(scale)
+    }
+
     /// Adjust the theme
     #[inline]
     pub fn adjust_theme<F: FnMut(&mut dyn ThemeApi) -> ThemeAction>(&mut self, mut f: F) {
@@ -285,6 +531,23 @@ impl<'a> Manager<'a> {
         }
     }
 
+    /// Register widget `id` to receive [`ControlKey`] `key`
+    ///
+    /// When a key is pressed which translates to `key` (see
+    /// [`InputConfig::shortcuts`]) but no widget has navigation focus and no
+    /// pop-up is open, widget `id` receives the key via [`Event::Control`].
+    /// This takes priority over [`Manager::register_nav_fallback`]. It allows,
+    /// for example, a media player to respond to media keys
+    /// ([`ControlKey::PlayPause`] etc.).
+    ///
+    /// The last widget to register for `key` wins. Registrations are cleared
+    /// on reconfigure, thus this should be called from
+    /// [`WidgetConfig::configure`].
+    pub fn register_control_target(&mut self, key: ControlKey, id: WidgetId) {
+        debug!("Manager: control target for {:?} = {}", key, id);
+        self.mgr.control_targets.insert(key, id);
+    }
+
     /// Add a new accelerator key layer and make it current
     ///
     /// This method affects the behaviour of [`Manager::add_accel_keys`] by
@@ -358,6 +621,33 @@ impl<'a> Manager<'a> {
         }
     }
 
+    /// Register a name for a widget
+    ///
+    /// Names allow widgets to be located via [`Manager::find_named`], e.g. from
+    /// tests, scripts or declarative bindings. Names must be unique within a
+    /// window; registering an existing name replaces the previous entry.
+    ///
+    /// Names are cleared on reconfigure, thus this should only be called from
+    /// [`WidgetConfig::configure`].
+    pub fn register_name<S: Into<String>>(&mut self, id: WidgetId, name: S) {
+        if !self.read_only {
+            let name = name.into();
+            if let Some(old_id) = self.mgr.names.insert(name.clone(), id) {
+                if old_id != id {
+                    warn!("Manager: name \"{}\" re-registered for {}", name, id);
+                }
+            }
+        }
+    }
+
+    /// Find a widget by name
+    ///
+    /// See [`Manager::register_name`].
+    #[inline]
+    pub fn find_named(&self, name: &str) -> Option<WidgetId> {
+        self.mgr.find_named(name)
+    }
+
     /// Request character-input focus
     ///
     /// If successful, [`Event::ReceivedCharacter`] events are sent to this
@@ -495,6 +785,107 @@ impl<'a> Manager<'a> {
         self.mgr.send_action(TkAction::Redraw);
     }
 
+    /// Repeat activation while a press is held
+    ///
+    /// This may be called after a successful [`Manager::request_grab`] by
+    /// press-and-hold widgets such as scroll arrows or spin buttons. After a
+    /// delay of [`InputConfig::key_repeat_delay`], [`Event::Activate`] is sent
+    /// to widget `id` every [`InputConfig::key_repeat_interval`] while the
+    /// grab of `source` by `id` remains active and depresses `id`.
+    ///
+    /// Repetition stops automatically when the grab ends.
+    pub fn request_press_repeat(&mut self, id: WidgetId, source: PressSource) {
+        if self.read_only {
+            return;
+        }
+        let time = self.tkw.now() + self.mgr.config.key_repeat_delay;
+        let repeat = &mut self.mgr.press_repeat;
+        if let Some(item) = repeat.iter_mut().find(|item| item.2 == source) {
+            *item = (time, id, source);
+        } else {
+            repeat.push((time, id, source));
+        }
+    }
+
+    /// Set the mouse cursor mode
+    ///
+    /// This allows confining the cursor to a rect or hiding the cursor and
+    /// reporting only relative motion (see [`CursorMode`]), where supported by
+    /// the platform. The mode is set on behalf of widget `id`; if this widget
+    /// has a mouse grab ([`Manager::request_grab`]), the mode is reset to
+    /// [`CursorMode::Normal`] automatically when the grab ends. Otherwise,
+    /// the widget is responsible for resetting the mode.
+    pub fn set_cursor_mode(&mut self, id: WidgetId, mode: CursorMode) {
+        if self.read_only {
+            return;
+        }
+        if mode == CursorMode::Normal {
+            if self.mgr.cursor_mode.take().is_none() {
+                return;
+            }
+        } else {
+            let lock = self.mgr.last_mouse_coord;
+            self.mgr.cursor_mode = Some((id, mode, lock));
+        }
+        trace!("Manager: set cursor mode {:?} for {}", mode, id);
+        if !self.tkw.set_cursor_grab(mode != CursorMode::Normal) {
+            warn!("Manager::set_cursor_mode: cursor grab not supported");
+        }
+        self.tkw.set_cursor_visible(mode != CursorMode::Relative);
+    }
+
+    /// Start a drag
+    ///
+    /// This may be called by a widget holding a mouse grab
+    /// ([`Manager::request_grab`] with [`GrabMode::Grab`]), usually from
+    /// [`Event::PressMove`] once the press has moved some distance. From then
+    /// on, the widget under the cursor receives [`Event::DragOver`], even if
+    /// this is in another window of the same application. When the grab ends,
+    /// the widget under the cursor receives [`Event::Drop`], following which
+    /// the widget `id` receives [`Event::DragEnd`].
+    ///
+    /// The widget `id` continues to receive press events as usual, thus may
+    /// e.g. draw a representation of the dragged item.
+    ///
+    /// Returns false (and does nothing) if `id` does not hold a mouse grab or
+    /// a drag is already active.
+    pub fn start_drag(&mut self, id: WidgetId, data: DragData) -> bool {
+        if self.read_only || self.mgr.drag_source.is_some() {
+            return false;
+        }
+        match self.mgr.mouse_grab {
+            Some(ref grab) if grab.start_id == id && grab.mode == GrabMode::Grab => (),
+            _ => return false,
+        }
+        trace!("Manager: start drag from {}: {:?}", id, data);
+        self.mgr.drag_source = Some(id);
+        self.tkw.start_drag(data);
+        true
+    }
+
+    /// True if a drag started by this window is active
+    #[inline]
+    pub fn is_dragging(&self) -> bool {
+        self.mgr.drag_source.is_some()
+    }
+
+    /// Set the disabled state of a widget
+    ///
+    /// This is an alternative to [`WidgetCore::set_disabled`] usable where only
+    /// the widget's identifier is available. The change is applied after
+    /// event handling completes.
+    ///
+    /// Disabled widgets (and their descendants) do not receive events, are
+    /// skipped by keyboard navigation and are drawn greyed out (via
+    /// [`InputState::disabled`]). When a widget is disabled, character and
+    /// navigation focus are removed from its sub-tree.
+    ///
+    /// [`WidgetCore::set_disabled`]: crate::WidgetCore::set_disabled
+    /// [`InputState::disabled`]: crate::draw::InputState::disabled
+    pub fn set_disabled(&mut self, id: WidgetId, state: bool) {
+        self.mgr.pending.push(Pending::SetDisabled(id, state));
+    }
+
     /// Get the current keyboard navigation focus, if any
     ///
     /// This is the widget selected by navigating the UI with the Tab key.
@@ -528,6 +919,9 @@ impl<'a> Manager<'a> {
     /// returns true; otherwise this will give focus to the first (or last)
     /// such widget.
     ///
+    /// Children are visited in spatial order, except as adjusted by
+    /// [`WidgetChildren::nav_hint`].
+    ///
     /// This method returns true when the navigation focus has been updated,
     /// otherwise leaves the focus unchanged. The caller may (optionally) choose
     /// to call [`Manager::clear_nav_focus`] when this method returns false.
@@ -585,14 +979,14 @@ impl<'a> Manager<'a> {
         // Breaks to given lifetime on error.
         macro_rules! do_child {
             ($lt:lifetime, $nav_stack:ident, $widget:ident, $widget_stack:ident) => {{
-                let range = $widget.spatial_range();
-                if $widget.is_disabled() || range.1 == std::usize::MAX {
+                let order = nav_order($widget);
+                if $widget.is_disabled() || order.is_empty() {
                     false
                 } else {
-                    // We have a child; the first is range.0 unless reverse
+                    // We have a child; the first in order unless reverse
                     let index = match reverse {
-                        false => range.0,
-                        true => range.1,
+                        false => order[0],
+                        true => order[order.len() - 1],
                     };
                     let new = match $widget.get(index) {
                         None => break $lt,
@@ -611,7 +1005,7 @@ impl<'a> Manager<'a> {
         // Breaks to given lifetime on error.
         macro_rules! do_sibling_or_pop {
             ($lt:lifetime, $nav_stack:ident, $widget:ident, $widget_stack:ident) => {{
-                let mut index;
+                let index;
                 match ($nav_stack.pop(), $widget_stack.pop()) {
                     (Some(i), Some(w)) => {
                         index = i as usize;
@@ -619,30 +1013,24 @@ impl<'a> Manager<'a> {
                     }
                     _ => break $lt,
                 };
-                let mut range = $widget.spatial_range();
-                if $widget.is_disabled() || range.1 == std::usize::MAX {
+                if $widget.is_disabled() {
                     break $lt;
                 }
-
-                let reverse = (range.1 < range.0) ^ reverse;
-                if range.1 < range.0 {
-                    std::mem::swap(&mut range.0, &mut range.1);
-                }
+                let order = nav_order($widget);
+                let pos = match order.iter().position(|i| *i == index) {
+                    Some(pos) => pos,
+                    None => break $lt,
+                };
 
                 // Look for next sibling
-                let have_sibling = match reverse {
-                    false if index < range.1 => {
-                        index += 1;
-                        true
-                    }
-                    true if range.0 < index => {
-                        index -= 1;
-                        true
-                    }
-                    _ => false,
+                let next = match reverse {
+                    false => order.get(pos + 1).cloned(),
+                    true if pos > 0 => Some(order[pos - 1]),
+                    true => None,
                 };
+                let have_sibling = next.is_some();
 
-                if have_sibling {
+                if let Some(index) = next {
                     let new = match $widget.get(index) {
                         None => break $lt,
                         Some(w) => w,
@@ -703,3 +1091,74 @@ impl<'a> Manager<'a> {
         false
     }
 }
+
+// Child indices of `widget` in Tab navigation order
+//
+// This is the spatial order (see `Layout::spatial_range`), adjusted by
+// `WidgetChildren::nav_hint`.
+fn nav_order(widget: &dyn WidgetConfig) -> SmallVec<[usize; 16]> {
+    let mut order = SmallVec::<[usize; 16]>::new();
+    let range = widget.spatial_range();
+    if range.1 == std::usize::MAX {
+        return order;
+    } else if range.0 <= range.1 {
+        order.extend(range.0..=range.1);
+    } else {
+        order.extend((range.1..=range.0).rev());
+    }
+
+    let hints: SmallVec<[NavHint; 16]> = order.iter().map(|i| widget.nav_hint(*i)).collect();
+    if hints.iter().all(|hint| *hint == NavHint::default()) {
+        return order;
+    }
+
+    // Sort by tab index, then by position of the group's first member
+    let mut groups = HashMap::new();
+    let mut keyed = SmallVec::<[((u32, usize, usize), usize); 16]>::new();
+    for (pos, (index, hint)) in order.iter().cloned().zip(hints).enumerate() {
+        let group_pos = match hint.group {
+            Some(group) => *groups.entry(group).or_insert(pos),
+            None => pos,
+        };
+        let tab_index = hint.tab_index.unwrap_or(std::u32::MAX);
+        keyed.push(((tab_index, group_pos, pos), index));
+    }
+    keyed.sort_unstable_by_key(|item| item.0);
+    keyed.into_iter().map(|item| item.1).collect()
+}
+
+#[test]
+fn nav_order_hints() {
+    use crate::prelude::*;
+    use crate::widget::Label;
+
+    #[layout(row)]
+    #[handler(msg = VoidMsg)]
+    #[derive(Debug, Widget)]
+    struct Hinted {
+        #[widget_core]
+        core: CoreData,
+        #[layout_data]
+        layout_data: <Self as kas::LayoutData>::Data,
+        #[widget]
+        a: Label,
+        #[widget(nav_group = g)]
+        b: Label,
+        #[widget(tab_index = 1)]
+        c: Label,
+        #[widget(nav_group = g)]
+        d: Label,
+    }
+
+    let widget = Hinted {
+        core: Default::default(),
+        layout_data: Default::default(),
+        a: Label::new("a"),
+        b: Label::new("b"),
+        c: Label::new("c"),
+        d: Label::new("d"),
+    };
+    assert_eq!(widget.nav_hint(0), NavHint::default());
+    assert_eq!(widget.nav_hint(1).group, Some("g"));
+    assert_eq!(nav_order(&widget).as_slice(), &[2, 0, 1, 3]);
+}
@@ -5,12 +5,14 @@
 
 //! Event manager — toolkit API
 
+use instant::Instant;
 use log::*;
 use smallvec::SmallVec;
-use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
 
 use super::*;
+use crate::event::KeyLayout;
 use crate::geom::{Coord, DVec2};
 #[allow(unused)]
 use crate::WidgetConfig; // for doc-links
@@ -32,14 +34,21 @@ impl ManagerState {
         ManagerState {
             end_id: Default::default(),
             dpi_factor,
+            config: Default::default(),
             modifiers: ModifiersState::empty(),
             char_focus: None,
             nav_focus: None,
+            last_nav_focus: None,
             nav_fallback: None,
+            control_targets: Default::default(),
             nav_stack: SmallVec::new(),
             hover: None,
             hover_icon: CursorIcon::Default,
+            cursor_mode: None,
+            drag_source: None,
+            drag_target: None,
             key_depress: Default::default(),
+            last_key: None,
             last_mouse_coord: Coord::ZERO,
             last_click_button: FAKE_MOUSE_BUTTON,
             last_click_repetitions: 0,
@@ -49,13 +58,24 @@ impl ManagerState {
             pan_grab: SmallVec::new(),
             accel_stack: vec![],
             accel_layers: HashMap::new(),
+            names: HashMap::new(),
             popups: Default::default(),
             new_popups: Default::default(),
             popup_removed: Default::default(),
 
             time_start: Instant::now(),
             time_updates: vec![],
+            press_repeat: Default::default(),
             handle_updates: HashMap::new(),
+            power: Default::default(),
+            power_updates: vec![],
+            last_input: Instant::now(),
+            idle_updates: vec![],
+            idle_tasks: VecDeque::new(),
+            screensaver_inhibitors: Default::default(),
+            relayout: Default::default(),
+            render_states: HashMap::new(),
+            animation_frame: Cell::new(false),
             pending: SmallVec::new(),
             action: TkAction::None,
         }
@@ -80,10 +100,21 @@ impl ManagerState {
         // We re-set these instead of remapping:
         self.accel_stack.clear();
         self.accel_layers.clear();
+        self.names.clear();
+        if self.cursor_mode.take().is_some() {
+            tkw.set_cursor_grab(false);
+            tkw.set_cursor_visible(true);
+        }
         self.time_updates.clear();
+        self.press_repeat.clear();
         self.handle_updates.clear();
+        self.power_updates.clear();
+        self.idle_updates.clear();
+        self.relayout.clear();
+        self.render_states.clear();
         self.pending.clear();
         self.nav_fallback = None;
+        self.control_targets.clear();
 
         // Enumerate and configure all widgets:
         let coord = self.last_mouse_coord;
@@ -109,15 +140,153 @@ impl ManagerState {
         }
 
         // The remaining code just updates all input states to new IDs via the map.
+        self.remap_ids(tkw, |id| map.get(&id).cloned());
+    }
+
+    /// Configure subtrees marked via [`CoreData::reconfigure`]
+    ///
+    /// Each marked widget is configured together with its descendants (via
+    /// [`WidgetConfig::configure_recurse`]) while identifiers of other widgets
+    /// are not changed, thus only the affected subtree is reconfigured. Input
+    /// state of widgets within the subtree is updated as by
+    /// [`ManagerState::configure`]. Reconfigured widgets are marked for
+    /// relayout (see [`LayoutCache::set_dirty`]).
+    ///
+    /// If a subtree no longer fits its previous range of identifiers, a
+    /// [`TkAction::Reconfigure`] is sent.
+    ///
+    /// The toolkit should call this on [`TkAction::Relayout`], before updating
+    /// layout.
+    ///
+    /// [`CoreData::reconfigure`]: crate::CoreData::reconfigure
+    /// [`LayoutCache::set_dirty`]: crate::layout::LayoutCache::set_dirty
+    pub fn configure_pending<W>(&mut self, tkw: &mut dyn TkWindow, widget: &mut W)
+    where
+        W: Widget<Msg = VoidMsg> + ?Sized,
+    {
+        // Find marked subtrees as (first id, last id, accel layer)
+        let mut subtrees = SmallVec::<[(WidgetId, WidgetId, WidgetId); 4]>::new();
+        find_pending(
+            &self.accel_layers,
+            widget.as_widget(),
+            WidgetId::FIRST,
+            widget.id(),
+            &mut subtrees,
+        );
+        if subtrees.is_empty() {
+            return;
+        }
+        trace!("Manager::configure_pending: {} subtrees", subtrees.len());
+
+        let in_subtree = |id: WidgetId| subtrees.iter().any(|s| s.0 <= id && id <= s.1);
+
+        // As in configure, we re-set registrations instead of remapping
+        for layer in self.accel_layers.values_mut() {
+            layer.1.retain(|_, id| !in_subtree(*id));
+        }
+        self.names.retain(|_, id| !in_subtree(*id));
+        if let Some((id, _, _)) = self.cursor_mode {
+            if in_subtree(id) {
+                self.cursor_mode = None;
+                tkw.set_cursor_grab(false);
+                tkw.set_cursor_visible(true);
+            }
+        }
+        self.time_updates.retain(|item| !in_subtree(item.1));
+        self.press_repeat.retain(|item| !in_subtree(item.1));
+        for ids in self.handle_updates.values_mut() {
+            ids.retain(|id| !in_subtree(*id));
+        }
+        self.power_updates.retain(|id| !in_subtree(*id));
+        self.idle_updates.retain(|item| !in_subtree(item.1));
+        self.render_states.retain(|id, _| !in_subtree(*id));
+        self.pending.retain(|item| match item {
+            Pending::LostCharFocus(id) | Pending::SetDisabled(id, _) => !in_subtree(*id),
+        });
+        if self.nav_fallback.map(in_subtree).unwrap_or(false) {
+            self.nav_fallback = None;
+        }
+        self.control_targets.retain(|_, id| !in_subtree(*id));
+
+        let mut map = HashMap::new();
+        let mut moved = false;
+        self.with(tkw, |mgr| {
+            for (first, last, layer) in subtrees.iter().cloned() {
+                let w = match widget.find_mut(last) {
+                    Some(w) => w,
+                    None => continue,
+                };
+                w.core_data_mut().reconfigure = false;
+                let mut id = first;
+                mgr.push_accel_layer(false);
+                w.configure_recurse(ConfigureManager {
+                    id: &mut id,
+                    map: &mut map,
+                    mgr: &mut *mgr,
+                });
+                let keys = mgr.mgr.accel_stack.pop().unwrap().1;
+                if let Some(layer) = mgr.mgr.accel_layers.get_mut(&layer) {
+                    layer.1.extend(keys);
+                }
+                w.core_data_mut().layout.set_dirty();
+                moved |= w.id() != last;
+            }
+        });
+        if moved {
+            warn!("Manager::configure_pending: subtree identifiers changed; reconfiguring");
+            self.send_action(TkAction::Reconfigure);
+        }
 
-        self.char_focus = self.char_focus.and_then(|id| map.get(&id).cloned());
-        self.nav_focus = self.nav_focus.and_then(|id| map.get(&id).cloned());
+        self.remap_ids(tkw, |id| match in_subtree(id) {
+            true => map.get(&id).cloned(),
+            false => Some(id),
+        });
+    }
+
+    /// Check for subtrees marked via [`CoreData::reconfigure`]
+    ///
+    /// If any widget is marked, this sends [`TkAction::Relayout`], thus
+    /// [`ManagerState::configure_pending`] will be called. The toolkit should
+    /// call this after each layout solve, since widgets may be marked from
+    /// [`kas::Layout::set_rect`].
+    ///
+    /// [`CoreData::reconfigure`]: crate::CoreData::reconfigure
+    pub fn check_pending(&mut self, widget: &dyn WidgetConfig) {
+        let mut subtrees = SmallVec::<[(WidgetId, WidgetId, WidgetId); 4]>::new();
+        find_pending(
+            &self.accel_layers,
+            widget,
+            WidgetId::FIRST,
+            widget.id(),
+            &mut subtrees,
+        );
+        if !subtrees.is_empty() {
+            self.send_action(TkAction::Relayout);
+        }
+    }
+
+    // Update input states to new IDs; states of widgets mapping to `None`
+    // are removed
+    fn remap_ids<F>(&mut self, tkw: &mut dyn TkWindow, map: F)
+    where
+        F: Fn(WidgetId) -> Option<WidgetId>,
+    {
+        self.char_focus = self.char_focus.and_then(&map);
+        self.nav_focus = self.nav_focus.and_then(&map);
+        self.last_nav_focus = self.last_nav_focus.and_then(&map);
+        self.drag_target = self.drag_target.and_then(&map);
+        if let Some(id) = self.drag_source {
+            self.drag_source = map(id);
+            if self.drag_source.is_none() {
+                tkw.end_drag(false);
+            }
+        }
         self.mouse_grab = self.mouse_grab.as_ref().and_then(|grab| {
-            map.get(&grab.start_id).map(|id| MouseGrab {
+            map(grab.start_id).map(|id| MouseGrab {
                 button: grab.button,
                 repetitions: grab.repetitions,
-                start_id: *id,
-                depress: grab.depress.and_then(|id| map.get(&id).cloned()),
+                start_id: id,
+                depress: grab.depress.and_then(&map),
                 mode: grab.mode,
                 pan_grab: grab.pan_grab,
             })
@@ -125,8 +294,8 @@ impl ManagerState {
 
         let mut i = 0;
         while i < self.pan_grab.len() {
-            if let Some(id) = map.get(&self.pan_grab[i].id) {
-                self.pan_grab[i].id = *id;
+            if let Some(id) = map(self.pan_grab[i].id) {
+                self.pan_grab[i].id = id;
                 i += 1;
             } else {
                 self.remove_pan(i);
@@ -152,19 +321,26 @@ impl ManagerState {
             };
         }
 
-        do_map!(self.touch_grab, |mut elt: TouchGrab| map
-            .get(&elt.start_id)
-            .map(|id| {
-                elt.start_id = *id;
+        do_map!(self.touch_grab, |mut elt: TouchGrab| {
+            map(elt.start_id).map(|id| {
+                elt.start_id = id;
                 if let Some(cur_id) = elt.cur_id {
-                    elt.cur_id = map.get(&cur_id).cloned();
+                    elt.cur_id = map(cur_id);
                 }
                 elt
-            }));
+            })
+        });
 
-        do_map!(self.key_depress, |elt: (u32, WidgetId)| map
-            .get(&elt.1)
-            .map(|id| (elt.0, *id)));
+        do_map!(self.key_depress, |elt: (u32, WidgetId)| {
+            map(elt.1).map(|id| (elt.0, id))
+        });
+
+        if !self.screensaver_inhibitors.is_empty() {
+            do_map!(self.screensaver_inhibitors, &map);
+            if self.screensaver_inhibitors.is_empty() {
+                tkw.inhibit_screensaver(false);
+            }
+        }
     }
 
     /// Update the widgets under the cursor and touch events
@@ -175,6 +351,9 @@ impl ManagerState {
         self.nav_focus = self
             .nav_focus
             .and_then(|id| widget.find(id).map(|w| w.id()));
+        self.last_nav_focus = self
+            .last_nav_focus
+            .and_then(|id| widget.find(id).map(|w| w.id()));
         self.char_focus = self
             .char_focus
             .and_then(|id| widget.find(id).map(|w| w.id()));
@@ -195,9 +374,40 @@ impl ManagerState {
         self.dpi_factor = dpi_factor;
     }
 
+    /// Set input configuration
+    #[inline]
+    pub fn set_config(&mut self, config: InputConfig) {
+        self.config = config;
+    }
+
+    /// Take the list of widgets requiring relayout
+    ///
+    /// The toolkit should call this on [`TkAction::Relayout`] and mark each
+    /// widget dirty via [`kas::layout::mark_dirty`] before re-solving layout.
+    #[inline]
+    pub fn take_relayout(&mut self) -> SmallVec<[WidgetId; 4]> {
+        std::mem::take(&mut self.relayout)
+    }
+
+    /// Set the initial power status
+    ///
+    /// Use [`Manager::update_power`] to notify widgets of changes.
+    #[inline]
+    pub fn set_power_status(&mut self, status: PowerStatus) {
+        self.power = status;
+    }
+
     /// Get the next resume time
     pub fn next_resume(&self) -> Option<Instant> {
-        self.time_updates.last().map(|time| time.0)
+        let time = self.time_updates.last().map(|time| time.0);
+        let repeat = self.press_repeat.iter().map(|r| r.0).min();
+        let idle = self
+            .idle_updates
+            .iter()
+            .filter(|item| !item.2)
+            .map(|item| self.last_input + item.0)
+            .min();
+        [time, repeat, idle].iter().flatten().min().cloned()
     }
 
     /// Set an action
@@ -209,6 +419,41 @@ impl ManagerState {
         self.action = self.action.max(action);
     }
 
+    /// True if any idle tasks are queued
+    ///
+    /// While this is true, the toolkit should call [`Manager::run_idle_tasks`]
+    /// after each batch of events (without waiting for further events).
+    #[inline]
+    pub fn has_idle_tasks(&self) -> bool {
+        !self.idle_tasks.is_empty()
+    }
+
+    /// True if a widget has character focus
+    ///
+    /// The toolkit may use this to enable text input methods (e.g. an IME or
+    /// on-screen keyboard) only while text input is expected.
+    #[inline]
+    pub fn has_char_focus(&self) -> bool {
+        self.char_focus.is_some()
+    }
+
+    /// True if another frame was requested for animation
+    ///
+    /// The toolkit should call this after drawing each frame, and if true,
+    /// draw another frame. See [`ManagerState::request_animation_frame`].
+    #[inline]
+    pub fn take_animation_frame(&self) -> bool {
+        self.animation_frame.replace(false)
+    }
+
+    /// Get the pending action
+    ///
+    /// This is the action accumulated since the last [`ManagerState::update`].
+    #[inline]
+    pub fn pending_action(&self) -> TkAction {
+        self.action
+    }
+
     /// Construct a [`Manager`] referring to this state
     ///
     /// Invokes the given closure on this [`Manager`].
@@ -299,17 +544,38 @@ impl ManagerState {
             }
         }
 
+        let mut pending = std::mem::take(&mut mgr.mgr.pending);
+        pending.retain(|item| match *item {
+            Pending::SetDisabled(id, state) => {
+                mgr.apply_disabled(widget, id, state);
+                false
+            }
+            _ => true,
+        });
+        mgr.mgr.pending.extend(pending);
+
+        if mgr.mgr.nav_focus != mgr.mgr.last_nav_focus {
+            if let Some(id) = mgr.mgr.last_nav_focus {
+                mgr.send_event(widget, id, Event::LostNavFocus);
+            }
+            mgr.mgr.last_nav_focus = mgr.mgr.nav_focus;
+            if let Some(id) = mgr.mgr.nav_focus {
+                mgr.send_event(widget, id, Event::NavFocus);
+            }
+        }
+
         // To avoid infinite loops, we consider mgr read-only from here on.
         // Since we don't wish to duplicate Handler::handle, we don't actually
         // make mgr const, but merely pretend it is in the public API.
         mgr.read_only = true;
 
-        for item in mgr.mgr.pending.pop() {
+        while let Some(item) = mgr.mgr.pending.pop() {
             match item {
                 Pending::LostCharFocus(id) => {
                     let event = Event::LostCharFocus;
                     mgr.send_event(widget, id, event);
                 }
+                Pending::SetDisabled(..) => unreachable!(),
             }
         }
 
@@ -325,7 +591,7 @@ impl ManagerState {
 impl<'a> Manager<'a> {
     /// Update widgets due to timer
     pub fn update_timer<W: Widget + ?Sized>(&mut self, widget: &mut W) {
-        let now = Instant::now();
+        let now = self.tkw.now();
 
         // assumption: time_updates are sorted in reverse order
         while !self.mgr.time_updates.is_empty() {
@@ -338,6 +604,83 @@ impl<'a> Manager<'a> {
         }
 
         self.mgr.time_updates.sort_by(|a, b| b.cmp(a)); // reverse sort
+
+        let mut i = 0;
+        while i < self.mgr.press_repeat.len() {
+            let (time, id, source) = self.mgr.press_repeat[i];
+            let depress = match source {
+                PressSource::Mouse(..) => self
+                    .mgr
+                    .mouse_grab
+                    .as_ref()
+                    .filter(|grab| grab.start_id == id)
+                    .map(|grab| grab.depress),
+                PressSource::Touch(touch_id) => self
+                    .mgr
+                    .touch_grab
+                    .iter()
+                    .find(|grab| grab.touch_id == touch_id && grab.start_id == id)
+                    .map(|grab| grab.depress),
+            };
+            let depress = match depress {
+                Some(depress) => depress,
+                None => {
+                    // The grab has ended
+                    self.mgr.press_repeat.remove(i);
+                    continue;
+                }
+            };
+            if time <= now {
+                self.mgr.press_repeat[i].0 = now + self.mgr.config.key_repeat_interval;
+                // Only repeat while the press remains over the widget
+                if depress == Some(id) {
+                    self.send_event(widget, id, Event::Activate);
+                }
+            }
+            i += 1;
+        }
+
+        for i in 0..self.mgr.idle_updates.len() {
+            let (duration, id, idle) = self.mgr.idle_updates[i];
+            if !idle && self.mgr.last_input + duration <= now {
+                self.mgr.idle_updates[i].2 = true;
+                self.send_event(widget, id, Event::Idle);
+            }
+        }
+    }
+
+    /// Run queued idle tasks
+    ///
+    /// This should be called after all pending events have been handled.
+    /// Nothing is done while an action (e.g. redraw) is pending. Otherwise,
+    /// tasks are run in order until the queue is empty, the time budget
+    /// ([`InputConfig::idle_budget`]) is exhausted or a timer update is due.
+    pub fn run_idle_tasks(&mut self) {
+        if self.mgr.action != TkAction::None || self.action != TkAction::None {
+            return;
+        }
+
+        let mut budget = self.mgr.config.idle_budget;
+        if let Some(time) = self.mgr.time_updates.last().map(|u| u.0) {
+            budget = budget.min(time.saturating_duration_since(self.tkw.now()));
+        }
+        let start = Instant::now();
+        let end = start + budget;
+
+        let mut n = 0;
+        while Instant::now() < end {
+            match self.mgr.idle_tasks.pop_front() {
+                Some(task) => (task.0)(self),
+                None => break,
+            }
+            n += 1;
+        }
+        trace!(
+            "Manager: ran {} idle tasks in {:?}; {} remain",
+            n,
+            start.elapsed(),
+            self.mgr.idle_tasks.len()
+        );
     }
 
     /// Update widgets due to handle
@@ -356,6 +699,69 @@ impl<'a> Manager<'a> {
         }
     }
 
+    /// Update the power status
+    ///
+    /// The toolkit should call this whenever the power status changes. If
+    /// changed, [`Event::PowerChanged`] is sent to subscribed widgets.
+    pub fn update_power<W: Widget + ?Sized>(&mut self, widget: &mut W, status: PowerStatus) {
+        if status == self.mgr.power {
+            return;
+        }
+        self.mgr.power = status;
+        // NOTE: to avoid borrow conflict, we must clone values!
+        for w_id in self.mgr.power_updates.clone() {
+            self.send_event(widget, w_id, Event::PowerChanged(status));
+        }
+    }
+
+    /// Update the target of a drag
+    ///
+    /// The toolkit should call this on the window under the cursor whenever
+    /// the cursor moves during a drag (see [`Manager::start_drag`]), passing
+    /// `None` when the cursor leaves the window.
+    pub fn drag_over<W: Widget + ?Sized>(
+        &mut self,
+        widget: &mut W,
+        coord: Option<Coord>,
+        data: &DragData,
+    ) {
+        let target = coord.and_then(|coord| widget.find_id(coord));
+        if target != self.mgr.drag_target {
+            if let Some(id) = self.mgr.drag_target {
+                self.send_bubbling(widget, id, Event::DragLeave);
+            }
+            self.mgr.drag_target = target;
+        }
+        if let (Some(id), Some(coord)) = (target, coord) {
+            let data = data.clone();
+            self.send_bubbling(widget, id, Event::DragOver { coord, data });
+        }
+    }
+
+    /// Drop the data of a drag at `coord`
+    ///
+    /// Returns true if the drop was accepted.
+    pub fn drag_drop<W: Widget + ?Sized>(
+        &mut self,
+        widget: &mut W,
+        coord: Coord,
+        data: DragData,
+    ) -> bool {
+        self.mgr.drag_target = None;
+        let id = match widget.find_id(coord) {
+            Some(id) => id,
+            None => return false,
+        };
+        self.send_bubbling(widget, id, Event::Drop { coord, data })
+    }
+
+    /// Notify the drag source that its drag has ended
+    pub fn drag_end<W: Widget + ?Sized>(&mut self, widget: &mut W, accepted: bool) {
+        if let Some(id) = self.mgr.drag_source.take() {
+            self.send_event(widget, id, Event::DragEnd { accepted });
+        }
+    }
+
     /// Handle a winit `WindowEvent`.
     ///
     /// Note that some event types are not *does not* handled, since for these
@@ -372,6 +778,19 @@ impl<'a> Manager<'a> {
         // Response are possible: None and Unhandled. We don't have any use for
         // Unhandled events here, so we can freely ignore all responses.
 
+        let is_input = match event {
+            ReceivedCharacter(_)
+            | KeyboardInput { .. }
+            | CursorMoved { .. }
+            | MouseWheel { .. }
+            | MouseInput { .. }
+            | Touch(_) => true,
+            _ => false,
+        };
+        if is_input {
+            self.end_idle(widget);
+        }
+
         match event {
             CloseRequested => self.send_action(TkAction::Close),
             /* Not yet supported: see #98
@@ -380,6 +799,12 @@ impl<'a> Manager<'a> {
             HoveredFileCancelled => ,
             */
             ReceivedCharacter(c) => {
+                if let Some((scancode, vkey)) = self.mgr.last_key.take() {
+                    if KeyLayout::learn(scancode, vkey, c) {
+                        self.trigger_update(KeyLayout::update_handle(), 0);
+                    }
+                }
+
                 if let Some(id) = self.mgr.char_focus {
                     // Filter out control codes (Unicode 5.11). These may be
                     // generated from combinations such as Ctrl+C by some other
@@ -396,6 +821,8 @@ impl<'a> Manager<'a> {
                 ..
             } => {
                 if input.state == ElementState::Pressed && !is_synthetic {
+                    self.mgr.last_key = Some((input.scancode, input.virtual_keycode))
+                        .filter(|_| self.mgr.modifiers.is_empty());
                     if let Some(vkey) = input.virtual_keycode {
                         self.start_key_event(widget, vkey, input.scancode);
                     }
@@ -412,11 +839,33 @@ impl<'a> Manager<'a> {
             }
             CursorMoved { position, .. } => {
                 self.mgr.last_click_button = FAKE_MOUSE_BUTTON;
-                let coord = position.into();
+                let mut coord: Coord = position.into();
+                let mut delta = coord - self.mgr.last_mouse_coord;
+
+                match self.mgr.cursor_mode {
+                    Some((_, CursorMode::Confined(rect), _)) => {
+                        let max = rect.pos_end() - Coord(1, 1);
+                        let clamped = coord.clamp(rect.pos, max);
+                        if clamped != coord {
+                            self.tkw.set_cursor_position(clamped);
+                            coord = clamped;
+                            delta = coord - self.mgr.last_mouse_coord;
+                        }
+                    }
+                    Some((_, CursorMode::Relative, lock)) => {
+                        if coord == lock {
+                            // This is the result of our own warp
+                            return;
+                        }
+                        self.tkw.set_cursor_position(lock);
+                        delta = coord - lock;
+                        coord = lock;
+                    }
+                    _ => (),
+                }
 
                 // Update hovered widget
                 let cur_id = widget.find_id(coord);
-                let delta = coord - self.mgr.last_mouse_coord;
                 self.set_hover(widget, cur_id);
 
                 if let Some(grab) = self.mouse_grab() {
@@ -461,10 +910,22 @@ impl<'a> Manager<'a> {
             MouseWheel { delta, .. } => {
                 self.mgr.last_click_button = FAKE_MOUSE_BUTTON;
 
+                let config = &self.mgr.config;
+                let sign = match config.natural_scrolling {
+                    false => 1.0,
+                    true => -1.0,
+                };
                 let event = Event::Scroll(match delta {
-                    MouseScrollDelta::LineDelta(x, y) => ScrollDelta::LineDelta(x, y),
+                    MouseScrollDelta::LineDelta(x, y) => {
+                        let lines = sign * config.scroll_lines as f32;
+                        ScrollDelta::LineDelta(x * lines, y * lines)
+                    }
                     MouseScrollDelta::PixelDelta(pos) => {
-                        ScrollDelta::PixelDelta(Coord::from_logical(pos, self.mgr.dpi_factor))
+                        let factor = sign * config.scroll_speed as f32 / 100.0;
+                        let d = Coord::from_logical(pos, self.mgr.dpi_factor);
+                        let x = (d.0 as f32 * factor).round() as i32;
+                        let y = (d.1 as f32 * factor).round() as i32;
+                        ScrollDelta::PixelDelta(Coord(x, y))
                     }
                 });
                 if let Some(id) = self.mgr.hover {
@@ -475,7 +936,7 @@ impl<'a> Manager<'a> {
                 let coord = self.mgr.last_mouse_coord;
 
                 if state == ElementState::Pressed {
-                    let now = Instant::now();
+                    let now = self.tkw.now();
                     if button != self.mgr.last_click_button || self.mgr.last_click_timeout < now {
                         self.mgr.last_click_button = button;
                         self.mgr.last_click_repetitions = 0;
@@ -502,7 +963,7 @@ impl<'a> Manager<'a> {
                     };
 
                     if state == ElementState::Released {
-                        self.end_mouse_grab(button);
+                        self.end_mouse_grab(button, true);
                     }
                 } else if let Some(start_id) = self.mgr.hover {
                     // No mouse grab but have a hover target
@@ -610,3 +1071,28 @@ impl<'a> Manager<'a> {
         }
     }
 }
+
+// Find widgets marked via CoreData::reconfigure, pushing (first id, last id,
+// accel layer) for each. Identifiers are assigned in order, children first,
+// thus the first id of a subtree follows the id of its previous sibling.
+fn find_pending(
+    accel_layers: &HashMap<WidgetId, (bool, HashMap<VirtualKeyCode, WidgetId>)>,
+    widget: &dyn WidgetConfig,
+    mut first: WidgetId,
+    mut layer: WidgetId,
+    subtrees: &mut SmallVec<[(WidgetId, WidgetId, WidgetId); 4]>,
+) {
+    if widget.core_data().reconfigure {
+        subtrees.push((first, widget.id(), layer));
+        return;
+    }
+    if accel_layers.contains_key(&widget.id()) {
+        layer = widget.id();
+    }
+    for i in 0..widget.len() {
+        if let Some(w) = widget.get(i) {
+            find_pending(accel_layers, w, first, layer, subtrees);
+            first = w.id().next();
+        }
+    }
+}
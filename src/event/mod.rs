@@ -68,12 +68,15 @@
 //! [`WidgetId`]: crate::WidgetId
 
 mod callback;
+mod config;
 #[cfg(not(feature = "winit"))]
 mod enums;
 mod events;
 mod handler;
 mod manager;
 mod response;
+mod shortcut;
+mod state;
 mod update;
 
 use smallvec::SmallVec;
@@ -89,12 +92,15 @@ pub use winit::event::{ModifiersState, MouseButton, VirtualKeyCode};
 pub use winit::window::CursorIcon;
 
 pub use callback::Callback;
+pub use config::{InputConfig, ScrollOverride};
 #[cfg(not(feature = "winit"))]
 pub use enums::{CursorIcon, ModifiersState, MouseButton, VirtualKeyCode};
 pub use events::*;
 pub use handler::{Handler, SendEvent};
-pub use manager::{ConfigureManager, GrabMode, Manager, ManagerState};
+pub use manager::{ConfigureManager, CursorMode, GrabMode, Manager, ManagerState};
 pub use response::Response;
+pub use shortcut::{is_numpad_key, KeyLayout, Shortcut, Shortcuts};
+pub use state::{State, StateMachine, Transition};
 pub use update::UpdateHandle;
 
 /// A type supporting a small number of key bindings
@@ -0,0 +1,392 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Keyboard shortcuts and their display
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::{ControlKey, ModifiersState, UpdateHandle, VirtualKeyCode};
+
+thread_local! {
+    static LAYOUT: RefCell<KeyLayout> = RefCell::new(KeyLayout::default());
+    static LAYOUT_HANDLE: UpdateHandle = UpdateHandle::new();
+}
+
+/// The user's keyboard layout
+///
+/// Key codes identify keys, but the character printed on a key depends on the
+/// user's keyboard layout. This records the character produced (without
+/// modifiers) by each physical key, identified by its scancode, along with
+/// the scancode of each [`VirtualKeyCode`].
+///
+/// Where the platform's layout can be queried, the toolkit does so via
+/// [`KeyLayout::update`] (currently `kas-wgpu` does this on Windows). Otherwise
+/// the layout is a heuristic learned from input: when a key press without
+/// modifiers is followed by a received character, that character is recorded
+/// for the key. In this case a key's default (US layout) name is used until
+/// the key has been pressed.
+///
+/// Whenever a character changes (e.g. the layout was switched), an update is
+/// triggered on [`KeyLayout::update_handle`]; widgets displaying shortcuts
+/// should subscribe to this and re-fetch their labels.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyLayout {
+    /// Character produced by each key, by scancode
+    chars: HashMap<u32, char>,
+    /// Scancode of each virtual key
+    scancodes: HashMap<VirtualKeyCode, u32>,
+}
+
+impl KeyLayout {
+    /// The [`UpdateHandle`] triggered on layout changes
+    pub fn update_handle() -> UpdateHandle {
+        LAYOUT_HANDLE.with(|handle| *handle)
+    }
+
+    /// Access the (thread-local) observed layout
+    pub fn with<F: FnOnce(&KeyLayout) -> T, T>(f: F) -> T {
+        LAYOUT.with(|layout| f(&layout.borrow()))
+    }
+
+    /// Modify the (thread-local) layout
+    ///
+    /// The closure should return true if it changed the layout (see
+    /// [`KeyLayout::insert`]); this value is returned. The caller is then
+    /// responsible for triggering an update on [`KeyLayout::update_handle`].
+    pub fn update<F: FnOnce(&mut KeyLayout) -> bool>(f: F) -> bool {
+        LAYOUT.with(|layout| f(&mut layout.borrow_mut()))
+    }
+
+    /// Record that the key with `scancode` (and `vkey`, if any) produces
+    /// character `c` when pressed without modifiers
+    ///
+    /// Returns true if this changes the layout.
+    pub fn insert(&mut self, scancode: u32, vkey: Option<VirtualKeyCode>, c: char) -> bool {
+        let mut changed = self.chars.insert(scancode, c) != Some(c);
+        if let Some(vkey) = vkey {
+            changed |= self.scancodes.insert(vkey, scancode) != Some(scancode);
+        }
+        changed
+    }
+
+    /// Record that the key with `scancode` (and `vkey`, if any), pressed
+    /// without modifiers, produced character `c`
+    ///
+    /// Returns true if this changes the layout.
+    pub(crate) fn learn(scancode: u32, vkey: Option<VirtualKeyCode>, c: char) -> bool {
+        if c.is_control() || c.is_whitespace() {
+            return false;
+        }
+        Self::update(|layout| layout.insert(scancode, vkey, c))
+    }
+
+    /// Get the character produced by the key with `scancode`, if known
+    pub fn char_for_scancode(&self, scancode: u32) -> Option<char> {
+        self.chars.get(&scancode).cloned()
+    }
+
+    /// Get the character produced by `key`, if known
+    pub fn char_for(&self, key: VirtualKeyCode) -> Option<char> {
+        let scancode = self.scancodes.get(&key)?;
+        self.char_for_scancode(*scancode)
+    }
+
+    /// Get a display name for `key`
+    ///
+    /// Numpad keys are always given a distinct name (e.g. "Num2").
+    pub fn key_name(&self, key: VirtualKeyCode) -> String {
+        if let Some(c) = self.char_for(key).filter(|_| !is_numpad_key(key)) {
+            return c.to_uppercase().collect();
+        }
+        default_key_name(key)
+    }
+}
+
+/// True if `key` is on the numeric keypad
+///
+/// Note that the arithmetic keys `Add`, `Subtract`, `Multiply`, `Divide` and
+/// `Decimal` are keypad keys, distinct from e.g. `Minus` and `Period`.
+pub fn is_numpad_key(key: VirtualKeyCode) -> bool {
+    use VirtualKeyCode as VK;
+    match key {
+        VK::Numlock | VK::Numpad0 | VK::Numpad1 | VK::Numpad2 | VK::Numpad3 | VK::Numpad4 => true,
+        VK::Numpad5 | VK::Numpad6 | VK::Numpad7 | VK::Numpad8 | VK::Numpad9 => true,
+        VK::Add | VK::Subtract | VK::Multiply | VK::Divide | VK::Decimal => true,
+        VK::NumpadComma | VK::NumpadEnter | VK::NumpadEquals => true,
+        _ => false,
+    }
+}
+
+fn default_key_name(key: VirtualKeyCode) -> String {
+    use VirtualKeyCode as VK;
+    if is_numpad_key(key) && key != VK::Numlock {
+        let name = match key {
+            VK::Numpad0 => "0",
+            VK::Numpad1 => "1",
+            VK::Numpad2 => "2",
+            VK::Numpad3 => "3",
+            VK::Numpad4 => "4",
+            VK::Numpad5 => "5",
+            VK::Numpad6 => "6",
+            VK::Numpad7 => "7",
+            VK::Numpad8 => "8",
+            VK::Numpad9 => "9",
+            VK::Add => "+",
+            VK::Subtract => "-",
+            VK::Multiply => "*",
+            VK::Divide => "/",
+            VK::Decimal => ".",
+            VK::NumpadComma => ",",
+            VK::NumpadEnter => "Enter",
+            VK::NumpadEquals => "=",
+            _ => unreachable!(),
+        };
+        return format!("Num{}", name);
+    }
+
+    let name = match key {
+        VK::Key1 => "1",
+        VK::Key2 => "2",
+        VK::Key3 => "3",
+        VK::Key4 => "4",
+        VK::Key5 => "5",
+        VK::Key6 => "6",
+        VK::Key7 => "7",
+        VK::Key8 => "8",
+        VK::Key9 => "9",
+        VK::Key0 => "0",
+        VK::Escape => "Esc",
+        VK::Back => "Backspace",
+        VK::Return => "Enter",
+        VK::Snapshot => "PrintScreen",
+        VK::Scroll => "ScrollLock",
+        VK::PageUp => "PgUp",
+        VK::PageDown => "PgDn",
+        VK::Numlock => "NumLock",
+        VK::Minus => "-",
+        VK::Equals => "=",
+        VK::Slash => "/",
+        VK::Backslash => "\\",
+        VK::Comma => ",",
+        VK::Period => ".",
+        VK::Semicolon => ";",
+        VK::Apostrophe => "'",
+        VK::Grave => "`",
+        VK::LBracket => "[",
+        VK::RBracket => "]",
+        VK::PlayPause => "Play/Pause",
+        VK::MediaStop => "Stop",
+        VK::NextTrack => "Next",
+        VK::PrevTrack => "Previous",
+        VK::VolumeUp => "Volume+",
+        VK::VolumeDown => "Volume-",
+        key => return format!("{:?}", key),
+    };
+    name.to_string()
+}
+
+/// A keyboard shortcut: a key with modifiers
+///
+/// This type describes a shortcut for display purposes, e.g. as a hint in a
+/// [`crate::widget::MenuEntry`]. Matching is available via
+/// [`Shortcut::matches`], but shortcuts are not handled automatically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub modifiers: ModifiersState,
+    pub key: VirtualKeyCode,
+}
+
+impl Shortcut {
+    /// Construct
+    #[inline]
+    pub fn new(modifiers: ModifiersState, key: VirtualKeyCode) -> Self {
+        Shortcut { modifiers, key }
+    }
+
+    /// Construct a shortcut with the "Ctrl" modifier (the "Command" key on
+    /// MacOS)
+    #[inline]
+    pub fn primary(key: VirtualKeyCode) -> Self {
+        #[cfg(target_os = "macos")]
+        let modifiers = ModifiersState::LOGO;
+        #[cfg(not(target_os = "macos"))]
+        let modifiers = ModifiersState::CTRL;
+        Shortcut { modifiers, key }
+    }
+
+    /// True if `key` pressed with `modifiers` matches this shortcut
+    #[inline]
+    pub fn matches(&self, modifiers: ModifiersState, key: VirtualKeyCode) -> bool {
+        self.modifiers == modifiers && self.key == key
+    }
+
+    /// Get the display label, according to the observed [`KeyLayout`]
+    pub fn label(&self) -> String {
+        KeyLayout::with(|layout| self.label_with(layout))
+    }
+
+    /// Get the display label, according to the given `layout`
+    pub fn label_with(&self, layout: &KeyLayout) -> String {
+        #[cfg(target_os = "macos")]
+        const LOGO: &str = "Cmd+";
+        #[cfg(not(target_os = "macos"))]
+        const LOGO: &str = "Super+";
+        let mods = [
+            (ModifiersState::CTRL, "Ctrl+"),
+            (ModifiersState::ALT, "Alt+"),
+            (ModifiersState::SHIFT, "Shift+"),
+            (ModifiersState::LOGO, LOGO),
+        ];
+
+        let mut s = String::new();
+        for (m, name) in &mods {
+            if self.modifiers.contains(*m) {
+                s.push_str(name);
+            }
+        }
+        s.push_str(&layout.key_name(self.key));
+        s
+    }
+}
+
+/// A registry of shortcuts
+///
+/// This maps each [`Shortcut`] to a [`ControlKey`]. When a key is pressed, the
+/// registry is checked first (using the exact modifier state); if no shortcut
+/// matches, the key is translated via [`ControlKey::new`] (ignoring
+/// modifiers). The resulting [`Event::Control`] is delivered as described in
+/// [`ControlKey`] and [`Manager::register_control_target`].
+///
+/// [`Event::Control`]: super::Event::Control
+/// [`Manager::register_control_target`]: super::Manager::register_control_target
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcuts {
+    // Few entries are expected; a Vec supports Hash (for InputConfig)
+    list: Vec<(Shortcut, ControlKey)>,
+}
+
+impl Default for Shortcuts {
+    /// Construct with standard editing, media and zoom shortcuts
+    fn default() -> Self {
+        use ControlKey as CK;
+        use VirtualKeyCode as VK;
+        let ctrl = ModifiersState::CTRL;
+        let ctrl_shift = ModifiersState::CTRL | ModifiersState::SHIFT;
+        let none = ModifiersState::empty();
+
+        let mut shortcuts = Shortcuts::empty();
+        shortcuts.bind(Shortcut::new(ctrl, VK::A), CK::SelectAll);
+        shortcuts.bind(Shortcut::new(ctrl_shift, VK::A), CK::Deselect);
+        for mods in &[ctrl, ctrl_shift] {
+            shortcuts.bind(Shortcut::new(*mods, VK::C), CK::Copy);
+            shortcuts.bind(Shortcut::new(*mods, VK::V), CK::Paste);
+            shortcuts.bind(Shortcut::new(*mods, VK::X), CK::Cut);
+        }
+        shortcuts.bind(Shortcut::new(ctrl, VK::Z), CK::Undo);
+        shortcuts.bind(Shortcut::new(ctrl_shift, VK::Z), CK::Redo);
+        shortcuts.bind(Shortcut::new(ctrl, VK::Y), CK::Redo);
+
+        shortcuts.bind(Shortcut::new(none, VK::PlayPause), CK::PlayPause);
+        shortcuts.bind(Shortcut::new(none, VK::MediaStop), CK::Stop);
+        shortcuts.bind(Shortcut::new(none, VK::NextTrack), CK::NextTrack);
+        shortcuts.bind(Shortcut::new(none, VK::PrevTrack), CK::PrevTrack);
+        shortcuts.bind(Shortcut::new(none, VK::Mute), CK::Mute);
+        shortcuts.bind(Shortcut::new(none, VK::VolumeUp), CK::VolumeUp);
+        shortcuts.bind(Shortcut::new(none, VK::VolumeDown), CK::VolumeDown);
+
+        // Ctrl+Shift+= is used for Ctrl++ on many layouts
+        for mods in &[ctrl, ctrl_shift] {
+            shortcuts.bind(Shortcut::new(*mods, VK::Equals), CK::ZoomIn);
+        }
+        shortcuts.bind(Shortcut::new(ctrl, VK::Add), CK::ZoomIn);
+        shortcuts.bind(Shortcut::new(ctrl, VK::Minus), CK::ZoomOut);
+        shortcuts.bind(Shortcut::new(ctrl, VK::Subtract), CK::ZoomOut);
+        shortcuts.bind(Shortcut::new(ctrl, VK::Key0), CK::ZoomReset);
+        shortcuts.bind(Shortcut::new(ctrl, VK::Numpad0), CK::ZoomReset);
+        shortcuts
+    }
+}
+
+impl Shortcuts {
+    /// Construct an empty registry
+    pub fn empty() -> Self {
+        Shortcuts { list: vec![] }
+    }
+
+    /// Bind `shortcut` to `key`, replacing any existing binding
+    pub fn bind(&mut self, shortcut: Shortcut, key: ControlKey) {
+        let _ = self.unbind(shortcut);
+        self.list.push((shortcut, key));
+    }
+
+    /// Remove the binding of `shortcut`
+    pub fn unbind(&mut self, shortcut: Shortcut) -> Option<ControlKey> {
+        let index = self.list.iter().position(|(s, _)| *s == shortcut)?;
+        Some(self.list.remove(index).1)
+    }
+
+    /// Find the binding for `key` pressed with `modifiers`
+    pub fn get(&self, modifiers: ModifiersState, key: VirtualKeyCode) -> Option<ControlKey> {
+        self.list
+            .iter()
+            .find(|(s, _)| s.matches(modifiers, key))
+            .map(|(_, key)| *key)
+    }
+
+    /// Find the first shortcut bound to `key` (for display)
+    pub fn find(&self, key: ControlKey) -> Option<Shortcut> {
+        self.list
+            .iter()
+            .find(|(_, k)| *k == key)
+            .map(|(shortcut, _)| *shortcut)
+    }
+}
+
+#[test]
+fn shortcut_label() {
+    let mut layout = KeyLayout::default();
+    let undo = Shortcut::new(ModifiersState::CTRL, VirtualKeyCode::Z);
+    assert_eq!(undo.label_with(&layout), "Ctrl+Z");
+    let redo = Shortcut::new(
+        ModifiersState::CTRL | ModifiersState::SHIFT,
+        VirtualKeyCode::Key2,
+    );
+    assert_eq!(redo.label_with(&layout), "Ctrl+Shift+2");
+
+    // On AZERTY, the key reported as Key2 produces 'é' without modifiers
+    assert!(layout.insert(3, Some(VirtualKeyCode::Key2), 'é'));
+    assert!(!layout.insert(3, Some(VirtualKeyCode::Key2), 'é'));
+    assert_eq!(redo.label_with(&layout), "Ctrl+Shift+É");
+
+    // Keys without a virtual key code are recorded by scancode
+    assert!(layout.insert(26, None, '^'));
+    assert_eq!(layout.char_for_scancode(26), Some('^'));
+    assert_eq!(layout.char_for_scancode(3), Some('é'));
+
+    // Switching layout changes the character of the same physical key
+    assert!(layout.insert(3, Some(VirtualKeyCode::Key2), '2'));
+    assert_eq!(redo.label_with(&layout), "Ctrl+Shift+2");
+
+    let num = Shortcut::new(ModifiersState::empty(), VirtualKeyCode::Numpad2);
+    assert_eq!(num.label_with(&layout), "Num2");
+}
+
+#[test]
+fn shortcuts() {
+    let shortcuts = Shortcuts::default();
+    let ctrl = ModifiersState::CTRL;
+    assert_eq!(
+        shortcuts.get(ctrl, VirtualKeyCode::Z),
+        Some(ControlKey::Undo)
+    );
+    assert_eq!(shortcuts.get(ctrl, VirtualKeyCode::Q), None);
+    let none = ModifiersState::empty();
+    let play = shortcuts.get(none, VirtualKeyCode::PlayPause);
+    assert_eq!(play, Some(ControlKey::PlayPause));
+    let zoom = shortcuts.get(ctrl, VirtualKeyCode::Equals);
+    assert_eq!(zoom, Some(ControlKey::ZoomIn));
+    assert_eq!(shortcuts.get(none, VirtualKeyCode::Minus), None);
+}
@@ -0,0 +1,133 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Interaction state machines
+
+use std::fmt::Debug;
+
+use super::{Event, Manager, Response};
+
+/// An interaction state of widget `W`
+///
+/// Widgets with non-trivial interaction (e.g. grab → drag → release, or
+/// hover → delayed open → close) may express this as an enum implementing
+/// this trait, with one variant per state and any per-state data as fields.
+/// Each state decides how to handle events via [`State::on_event`], possibly
+/// transitioning to another state. Entry and exit actions run on each
+/// transition and may, for example, schedule a timer via
+/// [`Manager::update_on_timer`] (the resulting [`Event::TimerUpdate`] is
+/// delivered to [`State::on_event`] like any other event) or request a redraw.
+///
+/// The widget stores its current state and implements [`StateMachine`]; its
+/// [`super::Handler::handle`] method should forward to [`StateMachine::handle_state`].
+pub trait State<W: ?Sized>: Clone + Debug + PartialEq {
+    /// Message type (usually the widget's [`super::Handler::Msg`])
+    type Msg;
+
+    /// Handle an event in this state
+    fn on_event(
+        &self,
+        widget: &mut W,
+        mgr: &mut Manager,
+        event: Event,
+    ) -> Transition<Self, Self::Msg>;
+
+    /// Entry action, called after transitioning to this state
+    fn on_enter(&self, _: &mut W, _: &mut Manager) {}
+
+    /// Exit action, called before transitioning away from this state
+    fn on_exit(&self, _: &mut W, _: &mut Manager) {}
+}
+
+/// Result of [`State::on_event`]
+#[derive(Clone, Debug)]
+pub struct Transition<S, M> {
+    /// The next state, if changed
+    pub next: Option<S>,
+    /// The response to the event
+    pub response: Response<M>,
+}
+
+impl<S, M> Transition<S, M> {
+    /// Stay in the current state; the event is used
+    #[inline]
+    pub fn stay() -> Self {
+        Transition {
+            next: None,
+            response: Response::None,
+        }
+    }
+
+    /// Stay in the current state; the event is unhandled
+    #[inline]
+    pub fn unhandled(event: Event) -> Self {
+        Transition {
+            next: None,
+            response: Response::Unhandled(event),
+        }
+    }
+
+    /// Transition to state `next`; the event is used
+    #[inline]
+    pub fn to(next: S) -> Self {
+        Transition {
+            next: Some(next),
+            response: Response::None,
+        }
+    }
+
+    /// Set the response to a message
+    #[inline]
+    pub fn with_msg(mut self, msg: M) -> Self {
+        self.response = Response::Msg(msg);
+        self
+    }
+}
+
+/// A widget whose interaction is driven by a [`State`]
+///
+/// Transitions are made only via [`StateMachine::handle_state`] and
+/// [`StateMachine::set_state`], thus entry and exit actions are never missed.
+pub trait StateMachine {
+    /// The state type
+    type State: State<Self>;
+
+    /// Access the current state
+    fn state(&self) -> &Self::State;
+
+    /// Access the current state mutably
+    ///
+    /// This is used to implement transitions; users should use
+    /// [`StateMachine::set_state`] instead.
+    fn state_mut(&mut self) -> &mut Self::State;
+
+    /// Handle an event via the current state, applying any transition
+    fn handle_state(
+        &mut self,
+        mgr: &mut Manager,
+        event: Event,
+    ) -> Response<<Self::State as State<Self>>::Msg> {
+        let state = self.state().clone();
+        let transition = state.on_event(self, mgr, event);
+        if let Some(next) = transition.next {
+            self.set_state(mgr, next);
+        }
+        transition.response
+    }
+
+    /// Transition to state `next`
+    ///
+    /// If `next` equals the current state, this does nothing. Otherwise the
+    /// current state's exit action runs, then `next`'s entry action.
+    fn set_state(&mut self, mgr: &mut Manager, next: Self::State) {
+        let prev = self.state().clone();
+        if prev == next {
+            return;
+        }
+        prev.on_exit(self, mgr);
+        *self.state_mut() = next.clone();
+        next.on_enter(self, mgr);
+    }
+}
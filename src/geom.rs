@@ -4,6 +4,16 @@
 //     https://www.apache.org/licenses/LICENSE-2.0
 
 //! Geometry data types
+//!
+//! [`Coord`], [`Size`] and [`Rect`] are measured in device pixels. Since
+//! layout is solved using these integer types, widget rects passed to
+//! [`crate::Layout::set_rect`] always lie on device pixels, even with a
+//! fractional DPI factor (e.g. 1.25). Lengths derived from a DPI factor should
+//! be converted via [`scale_px`], which never rounds a non-zero length to zero.
+//!
+//! Drawing uses the fractional types [`Vec2`] and [`Quad`]. Edges which should
+//! be sharp (e.g. 1px lines) must lie on whole pixels: use [`Quad::snap`] and
+//! [`Quad::shrink_snapped`] to avoid blurring.
 
 #[cfg(feature = "winit")]
 use winit::dpi::{LogicalPosition, PhysicalPosition, PhysicalSize, Pixel};
@@ -11,6 +21,28 @@ use winit::dpi::{LogicalPosition, PhysicalPosition, PhysicalSize, Pixel};
 mod vector;
 pub use vector::{DVec2, Quad, Vec2, Vec3};
 
+/// Scale a length by `scale_factor`, rounding to whole device pixels
+///
+/// The result is rounded to the nearest integer, except that a positive
+/// length is never rounded down to zero (thus thin lines do not disappear).
+///
+/// ```
+/// use kas::geom::scale_px;
+/// assert_eq!(scale_px(1.0, 1.25), 1);
+/// assert_eq!(scale_px(2.0, 1.25), 3);
+/// assert_eq!(scale_px(1.0, 0.4), 1);
+/// assert_eq!(scale_px(0.0, 1.5), 0);
+/// ```
+#[inline]
+pub fn scale_px(len: f32, scale_factor: f32) -> u32 {
+    let x = len * scale_factor;
+    if x > 0.0 {
+        x.round().max(1.0) as u32
+    } else {
+        0
+    }
+}
+
 /// An `(x, y)` coordinate.
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
 pub struct Coord(pub i32, pub i32);
@@ -142,7 +174,7 @@ impl std::ops::AddAssign<Size> for Coord {
 }
 
 /// A `(w, h)` size.
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
 pub struct Size(pub u32, pub u32);
 
 impl Size {
@@ -317,6 +349,27 @@ impl Rect {
         let size = Size(w, h);
         Rect { pos, size }
     }
+
+    /// Get the intersection of `self` and `other`
+    ///
+    /// If the two do not overlap, the result has zero size (along at least
+    /// one axis).
+    ///
+    /// ```
+    /// use kas::geom::{Coord, Rect, Size};
+    ///
+    /// let a = Rect::new(Coord(0, 0), Size(10, 10));
+    /// let b = Rect::new(Coord(5, -5), Size(10, 10));
+    /// assert_eq!(a.intersection(&b), Rect::new(Coord(5, 0), Size(5, 5)));
+    /// let c = Rect::new(Coord(20, 0), Size(10, 10));
+    /// assert_eq!(a.intersection(&c).size.0, 0);
+    /// ```
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let pos = self.pos.max(other.pos);
+        let end = self.pos_end().min(other.pos_end()).max(pos);
+        let size = Size((end.0 - pos.0) as u32, (end.1 - pos.1) as u32);
+        Rect { pos, size }
+    }
 }
 
 impl std::ops::Add<Coord> for Rect {
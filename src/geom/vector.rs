@@ -65,6 +65,44 @@ impl Quad {
         Quad { a, b }
     }
 
+    /// Shrink self in all directions by `value`, rounded to whole pixels
+    ///
+    /// `value` is rounded as by [`crate::geom::scale_px`]: to the nearest
+    /// integer, except that a positive value is never rounded down to zero.
+    /// Where `self` lies on whole pixels, so does the result.
+    /// In debug mode, this asserts `a.le(b)` after shrinking.
+    ///
+    /// ```
+    /// use kas::geom::{Quad, Vec2};
+    /// let q = Quad::with_coords(Vec2(0.0, 0.0), Vec2(10.0, 10.0));
+    /// assert_eq!(q.shrink_snapped(0.5), q.shrink(1.0));
+    /// assert_eq!(q.shrink_snapped(0.3), q.shrink(1.0));
+    /// assert_eq!(q.shrink_snapped(2.4), q.shrink(2.0));
+    /// assert_eq!(q.shrink_snapped(0.0), q);
+    /// ```
+    #[inline]
+    pub fn shrink_snapped(&self, value: f32) -> Quad {
+        self.shrink(crate::geom::scale_px(value, 1.0) as f32)
+    }
+
+    /// Round corners to the nearest whole pixel
+    ///
+    /// Use this on quads derived from fractional values (e.g. a proportion
+    /// of a rect's size) whose edges should be sharp.
+    ///
+    /// ```
+    /// use kas::geom::{Quad, Vec2};
+    /// let q = Quad::with_coords(Vec2(0.4, 2.5), Vec2(10.6, 7.2));
+    /// assert_eq!(q.snap(), Quad::with_coords(Vec2(0.0, 3.0), Vec2(11.0, 7.0)));
+    /// ```
+    #[inline]
+    pub fn snap(&self) -> Quad {
+        Quad {
+            a: self.a.round(),
+            b: self.b.round(),
+        }
+    }
+
     /// Shrink self in all directions by the given `value`
     ///
     /// In debug mode, this asserts `a.le(b)` after shrinking.
@@ -110,6 +148,7 @@ impl From<Rect> for Quad {
 /// vectors (consider for `lhs = (0, 1), rhs = (1, 0)`).
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec2(pub f32, pub f32);
 
 /// 2D vector (double precision)
@@ -123,6 +162,7 @@ pub struct Vec2(pub f32, pub f32);
 /// vectors (consider for `lhs = (0, 1), rhs = (1, 0)`).
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DVec2(pub f64, pub f64);
 
 macro_rules! impl_vec2 {
@@ -170,6 +210,18 @@ macro_rules! impl_vec2 {
                 $T(self.0.ceil(), self.1.ceil())
             }
 
+            /// Take the floor of each component
+            #[inline]
+            pub fn floor(self) -> Self {
+                $T(self.0.floor(), self.1.floor())
+            }
+
+            /// Round each component to the nearest integer
+            #[inline]
+            pub fn round(self) -> Self {
+                $T(self.0.round(), self.1.round())
+            }
+
             /// For each component, return `±1` with the same sign as `self`.
             #[inline]
             pub fn sign(self) -> Self {
@@ -0,0 +1,135 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Headless layout rendering (debug tool)
+
+use std::fmt::Write;
+
+use super::SolveCache;
+use crate::draw::MockSizeHandle;
+use crate::geom::{Coord, Rect, Size};
+use crate::{WidgetConfig, WidgetDescription, WidgetId};
+
+/// Horizontal pixels per character cell
+const CELL_WIDTH: u32 = 4;
+/// Vertical pixels per character cell
+const CELL_HEIGHT: u32 = 8;
+
+/// Lay out a widget tree without a toolkit and render it as text
+///
+/// The tree is sized via [`MockSizeHandle`] (thus the result does not depend
+/// on themes, fonts or the GPU) and laid out within `size`, or at its ideal
+/// size if `None`. Widget identifiers are assigned in the same order as when
+/// configured by a toolkit, but [`WidgetConfig::configure`] is not called.
+///
+/// The output is a Unicode box diagram of all widgets, each labelled with its
+/// identifier (where room allows), with one character cell per 4×8 pixels,
+/// followed by an indented list of widgets (see [`WidgetDescription`]) with
+/// their positions and sizes.
+/// Since output is deterministic, this may be used for "golden" tests:
+/// ```
+/// use kas::geom::Size;
+/// use kas::layout::layout_to_text;
+/// use kas::widget::Filler;
+///
+/// let text = layout_to_text(&mut Filler::new(), Some(Size(16, 24)));
+/// assert!(text.ends_with("#1 Filler: pos (0, 0), size 16×24\n"));
+/// ```
+///
+/// Note: widgets preparing text in [`kas::Layout::set_rect`] may require
+/// fonts to be loaded (see [`kas::text::fonts`]).
+pub fn layout_to_text(widget: &mut dyn WidgetConfig, size: Option<Size>) -> String {
+    let mut id = WidgetId::FIRST;
+    assign_ids(widget, &mut id);
+
+    let mut size_handle = MockSizeHandle::default();
+    let mut solve_cache = SolveCache::find_constraints(widget, &mut size_handle);
+    let size = size.unwrap_or_else(|| solve_cache.ideal(true));
+    let rect = Rect::new(Coord::ZERO, size);
+    solve_cache.apply_rect(widget, &mut size_handle, rect, true);
+
+    let cols = ((size.0 + CELL_WIDTH - 1) / CELL_WIDTH).max(2) as usize;
+    let rows = ((size.1 + CELL_HEIGHT - 1) / CELL_HEIGHT).max(2) as usize;
+    let mut grid = vec![vec![' '; cols]; rows];
+    draw_boxes(&mut grid, widget.as_widget());
+
+    let mut s = String::new();
+    for row in grid {
+        let line: String = row.into_iter().collect();
+        s.push_str(line.trim_end());
+        s.push('\n');
+    }
+    s.push('\n');
+    list_widgets(&mut s, widget.as_widget(), 0);
+    s
+}
+
+// Mirrors the order used by WidgetConfig::configure_recurse
+fn assign_ids(widget: &mut dyn WidgetConfig, id: &mut WidgetId) {
+    for i in 0..widget.len() {
+        if let Some(child) = widget.get_mut(i) {
+            assign_ids(child, id);
+        }
+    }
+    widget.core_data_mut().id = *id;
+    *id = id.next();
+}
+
+fn draw_boxes(grid: &mut [Vec<char>], widget: &dyn WidgetConfig) {
+    let (rows, cols) = (grid.len(), grid[0].len());
+    let rect = widget.rect();
+    let cell = |x: i32, cell: u32, len: usize| ((x.max(0) as u32 / cell) as usize).min(len - 1);
+    let c0 = cell(rect.pos.0, CELL_WIDTH, cols);
+    let r0 = cell(rect.pos.1, CELL_HEIGHT, rows);
+    let end = rect.pos_end() - Coord(1, 1);
+    let c1 = cell(end.0, CELL_WIDTH, cols).max((c0 + 1).min(cols - 1));
+    let r1 = cell(end.1, CELL_HEIGHT, rows).max((r0 + 1).min(rows - 1));
+
+    for c in c0..=c1 {
+        grid[r0][c] = '─';
+        grid[r1][c] = '─';
+    }
+    for row in &mut grid[r0..=r1] {
+        row[c0] = '│';
+        row[c1] = '│';
+    }
+    grid[r0][c0] = '┌';
+    grid[r0][c1] = '┐';
+    grid[r1][c0] = '└';
+    grid[r1][c1] = '┘';
+
+    let label = widget.id().to_string();
+    if c0 + label.len() < c1 {
+        for (i, c) in label.chars().enumerate() {
+            grid[r0][c0 + 1 + i] = c;
+        }
+    }
+
+    for i in 0..widget.len() {
+        if let Some(child) = widget.get(i) {
+            draw_boxes(grid, child);
+        }
+    }
+}
+
+fn list_widgets(s: &mut String, widget: &dyn WidgetConfig, depth: usize) {
+    let Rect { pos, size } = widget.rect();
+    let _ = writeln!(
+        s,
+        "{:indent$}{}: pos ({}, {}), size {}×{}",
+        "",
+        WidgetDescription::new(widget),
+        pos.0,
+        pos.1,
+        size.0,
+        size.1,
+        indent = 2 * depth
+    );
+    for i in 0..widget.len() {
+        if let Some(child) = widget.get(i) {
+            list_widgets(s, child, depth + 1);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Caching of size rules
+
+use super::{AxisInfo, SizeRules};
+use crate::{WidgetConfig, WidgetId};
+
+/// Invalidate cached rules of `widget` and all its descendants
+///
+/// This is called by [`super::SolveCache`] when all rules must be re-solved.
+pub(crate) fn invalidate_all(widget: &mut dyn WidgetConfig) {
+    let layout = &mut widget.core_data_mut().layout;
+    layout.invalidate();
+    layout.dirty = false;
+    for i in 0..widget.len() {
+        if let Some(w) = widget.get_mut(i) {
+            invalidate_all(w);
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Entry {
+    other: Option<u32>,
+    rules: SizeRules,
+}
+
+/// Cache of a widget's most recent [`SizeRules`]
+///
+/// This is stored in [`CoreData`] and used by widgets deriving their layout
+/// via `#[layout(...)]`: while valid, `size_rules` returns cached rules
+/// without re-solving the widget's children. Widgets implementing
+/// [`Layout::size_rules`] manually may use this in the same way:
+/// ```none
+/// if let Some(rules) = self.core.layout.get(axis) {
+///     return rules;
+/// }
+/// let rules = /* solve */;
+/// self.core.layout.set(axis, rules);
+/// rules
+/// ```
+///
+/// Entries are invalidated by [`mark_dirty`] (e.g. following
+/// [`Manager::relayout`]) and all entries are invalidated by
+/// [`TkAction::Resize`] and reconfiguration. A widget whose own size
+/// requirements change may instead call [`LayoutCache::set_dirty`] and return
+/// [`TkAction::Relayout`]; ancestors are then invalidated by
+/// [`invalidate_dirty`].
+///
+/// Cloning yields an empty cache.
+///
+/// [`CoreData`]: crate::CoreData
+/// [`Layout::size_rules`]: crate::Layout::size_rules
+/// [`Manager::relayout`]: crate::event::Manager::relayout
+/// [`TkAction::Resize`]: crate::TkAction::Resize
+/// [`TkAction::Relayout`]: crate::TkAction::Relayout
+#[derive(Debug, Default)]
+pub struct LayoutCache {
+    entries: [Option<Entry>; 2],
+    dirty: bool,
+}
+
+impl Clone for LayoutCache {
+    fn clone(&self) -> Self {
+        LayoutCache::default()
+    }
+}
+
+impl LayoutCache {
+    /// Get cached rules for `axis`, if valid
+    #[inline]
+    pub fn get(&self, axis: AxisInfo) -> Option<SizeRules> {
+        let entry = self.entries[axis.is_vertical() as usize]?;
+        if entry.other == axis.other() {
+            Some(entry.rules)
+        } else {
+            None
+        }
+    }
+
+    /// Cache rules for `axis`
+    #[inline]
+    pub fn set(&mut self, axis: AxisInfo, rules: SizeRules) {
+        self.entries[axis.is_vertical() as usize] = Some(Entry {
+            other: axis.other(),
+            rules,
+        });
+    }
+
+    /// Clear cached rules
+    #[inline]
+    pub fn invalidate(&mut self) {
+        self.entries = [None; 2];
+    }
+
+    /// Clear cached rules and mark dirty
+    ///
+    /// Cached rules of ancestors are invalidated by the next call to
+    /// [`invalidate_dirty`] (on [`TkAction::Relayout`]).
+    ///
+    /// [`TkAction::Relayout`]: crate::TkAction::Relayout
+    #[inline]
+    pub fn set_dirty(&mut self) {
+        self.invalidate();
+        self.dirty = true;
+    }
+}
+
+/// Invalidate cached rules of widget `id` and all its ancestors
+///
+/// Rules of siblings and other widgets remain cached, thus only the affected
+/// branch of the widget tree is re-solved by the next layout solve.
+pub fn mark_dirty(widget: &mut dyn WidgetConfig, id: WidgetId) {
+    if id > widget.id() {
+        return;
+    }
+    widget.core_data_mut().layout.invalidate();
+    if id == widget.id() {
+        return;
+    }
+
+    for i in 0..widget.len() {
+        if widget.get(i).map(|w| id > w.id()).unwrap_or(true) {
+            continue;
+        }
+        if let Some(w) = widget.get_mut(i) {
+            mark_dirty(w, id);
+        }
+        return;
+    }
+}
+
+/// Invalidate cached rules of all ancestors of widgets marked dirty
+///
+/// Widgets are marked via [`LayoutCache::set_dirty`]. Returns true if any
+/// widget within `widget` (inclusive) was dirty; dirty flags are cleared.
+pub fn invalidate_dirty(widget: &mut dyn WidgetConfig) -> bool {
+    let mut dirty = false;
+    for i in 0..widget.len() {
+        if let Some(w) = widget.get_mut(i) {
+            dirty |= invalidate_dirty(w);
+        }
+    }
+    let layout = &mut widget.core_data_mut().layout;
+    if dirty {
+        layout.invalidate();
+    }
+    dirty |= layout.dirty;
+    layout.dirty = false;
+    dirty
+}
+
+#[test]
+fn layout_cache() {
+    use super::StretchPolicy;
+
+    let rules = SizeRules::new(10, 20, (0, 0), StretchPolicy::Fixed);
+    let (horiz, vert) = (AxisInfo::new(false, None), AxisInfo::new(true, Some(20)));
+    let mut cache = LayoutCache::default();
+    cache.set(horiz, rules);
+    assert_eq!(cache.get(horiz), Some(rules));
+    assert_eq!(cache.get(vert), None);
+    assert_eq!(cache.clone().get(horiz), None);
+    cache.set_dirty();
+    assert_eq!(cache.get(horiz), None);
+    cache.set(vert, rules);
+    assert_eq!(cache.get(AxisInfo::new(true, Some(30))), None);
+    cache.invalidate();
+    assert_eq!(cache.get(vert), None);
+}
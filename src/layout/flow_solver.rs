@@ -0,0 +1,222 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Flow (wrapping row) solver
+
+use super::{AxisInfo, FlowStorage, RulesSetter, RulesSolver, SizeRules};
+use crate::geom::{Coord, Rect, Size};
+use crate::{Align, AlignHints};
+
+impl FlowStorage {
+    fn set_len(&mut self, len: usize) {
+        self.width_rules.resize(len, SizeRules::EMPTY);
+        self.height_rules.resize(len, SizeRules::EMPTY);
+        self.widths.resize(len, 0);
+    }
+
+    /// Assign child widths and break into rows, given the available `width`
+    ///
+    /// Children are placed left-to-right at their ideal width (but no wider
+    /// than `width` unless required by their minimum), starting a new row
+    /// whenever the next child does not fit.
+    fn wrap(&mut self, width: u32) {
+        self.rows.clear();
+        let mut x = 0u32;
+        let mut margin = 0;
+        for (i, rules) in self.width_rules.iter().enumerate() {
+            let w = rules.ideal_size().min(width).max(rules.min_size());
+            let m = margin.max(rules.margins().0) as u32;
+            if i == 0 || x.saturating_add(m + w) > width {
+                self.rows.push(i);
+                x = w;
+            } else {
+                x += m + w;
+            }
+            margin = rules.margins().1;
+            self.widths[i] = w;
+        }
+    }
+
+    /// Range of child indices in `row`
+    fn row_range(&self, row: usize) -> std::ops::Range<usize> {
+        let end = self
+            .rows
+            .get(row + 1)
+            .cloned()
+            .unwrap_or(self.width_rules.len());
+        self.rows[row]..end
+    }
+
+    /// Rules for each row, with the sum appended as the last element
+    fn row_rules(&self) -> Vec<SizeRules> {
+        let mut rules: Vec<SizeRules> = (0..self.rows.len())
+            .map(|row| {
+                self.height_rules[self.row_range(row)]
+                    .iter()
+                    .fold(SizeRules::EMPTY, |a, b| a.max(*b))
+            })
+            .collect();
+        rules.push(SizeRules::sum(&rules));
+        rules
+    }
+}
+
+/// A [`RulesSolver`] for flow layouts
+///
+/// Children are set out left-to-right, wrapping to a new row when the
+/// available width is exhausted. Since the number of rows depends on the
+/// width, the vertical [`SizeRules`] depend on the width passed via
+/// [`AxisInfo`] (height-for-width).
+///
+/// The horizontal minimum is that of the widest child while the horizontal
+/// ideal is that of all children on a single row.
+pub struct FlowSolver {
+    axis: AxisInfo,
+    rules: Option<SizeRules>,
+    min: SizeRules,
+}
+
+impl FlowSolver {
+    /// Construct.
+    ///
+    /// Argument order is consistent with other [`RulesSolver`]s.
+    ///
+    /// - `axis`: `AxisInfo` instance passed into `size_rules`
+    /// - `len`: number of items
+    /// - `storage`: reference to persistent storage
+    pub fn new(axis: AxisInfo, len: usize, storage: &mut FlowStorage) -> Self {
+        storage.set_len(len);
+
+        if axis.is_vertical() {
+            storage.wrap(axis.other().unwrap_or(u32::MAX));
+        }
+
+        FlowSolver {
+            axis,
+            rules: None,
+            min: SizeRules::EMPTY,
+        }
+    }
+}
+
+impl RulesSolver for FlowSolver {
+    type Storage = FlowStorage;
+    type ChildInfo = usize;
+
+    fn for_child<CR: FnOnce(AxisInfo) -> SizeRules>(
+        &mut self,
+        storage: &mut Self::Storage,
+        child_info: Self::ChildInfo,
+        child_rules: CR,
+    ) {
+        if self.axis.is_horizontal() {
+            let child_rules = child_rules(self.axis);
+            storage.width_rules[child_info] = child_rules;
+            self.min = self.min.max(child_rules);
+            self.rules = Some(
+                self.rules
+                    .map(|rules| rules.appended(child_rules))
+                    .unwrap_or(child_rules),
+            );
+        } else {
+            let axis = AxisInfo::new(true, Some(storage.widths[child_info]));
+            storage.height_rules[child_info] = child_rules(axis);
+        }
+    }
+
+    fn finish(self, storage: &mut Self::Storage) -> SizeRules {
+        if self.axis.is_horizontal() {
+            let mut rules = self.rules.unwrap_or(SizeRules::EMPTY);
+            rules.reduce_min_to(self.min.min_size());
+            rules.include_margins(self.min.margins());
+            rules
+        } else {
+            storage.row_rules().pop().unwrap()
+        }
+    }
+}
+
+/// A [`RulesSetter`] for flow layouts
+///
+/// See [`FlowSolver`]. Rows are aligned horizontally according to the
+/// horizontal alignment hint; each child is given the full height of its row.
+pub struct FlowSetter {
+    rects: Vec<Rect>,
+}
+
+impl FlowSetter {
+    /// Construct
+    ///
+    /// Argument order is consistent with other [`RulesSetter`]s.
+    ///
+    /// -   `rect`: the [`Rect`] within which to position children
+    /// -   `len`: number of items
+    /// -   `align`: alignment hints
+    /// -   `storage`: access to the solver's storage
+    pub fn new(rect: Rect, len: usize, align: AlignHints, storage: &mut FlowStorage) -> Self {
+        storage.set_len(len);
+        storage.wrap(rect.size.0);
+
+        let row_rules = storage.row_rules();
+        let num_rows = storage.rows.len();
+        let mut heights = vec![0; num_rows];
+        if num_rows > 0 {
+            SizeRules::solve_seq_total(&mut heights, &row_rules, rect.size.1);
+        }
+
+        let align = align.horiz.unwrap_or(Align::Default);
+        let mut rects = Vec::with_capacity(len);
+        let mut y = rect.pos.1;
+        for row in 0..num_rows {
+            if row > 0 {
+                let m0 = row_rules[row - 1].margins().1;
+                let m = m0.max(row_rules[row].margins().0);
+                y += (heights[row - 1] + m as u32) as i32;
+            }
+
+            let range = storage.row_range(row);
+            let mut width = 0;
+            for i in range.clone() {
+                if i > range.start {
+                    let m0 = storage.width_rules[i - 1].margins().1;
+                    width += m0.max(storage.width_rules[i].margins().0) as u32;
+                }
+                width += storage.widths[i];
+            }
+            let extra = rect.size.0.saturating_sub(width);
+            let mut x = rect.pos.0
+                + match align {
+                    Align::Default | Align::TL | Align::Stretch => 0,
+                    Align::Centre => extra / 2,
+                    Align::BR => extra,
+                } as i32;
+
+            for i in range.clone() {
+                if i > range.start {
+                    let m0 = storage.width_rules[i - 1].margins().1;
+                    x += m0.max(storage.width_rules[i].margins().0) as i32;
+                }
+                let size = Size(storage.widths[i], heights[row]);
+                rects.push(Rect::new(Coord(x, y), size));
+                x += size.0 as i32;
+            }
+        }
+
+        FlowSetter { rects }
+    }
+}
+
+impl RulesSetter for FlowSetter {
+    type Storage = FlowStorage;
+    type ChildInfo = usize;
+
+    fn child_rect(&mut self, _: &mut Self::Storage, index: Self::ChildInfo) -> Rect {
+        self.rects[index]
+    }
+
+    fn maximal_rect_of(&mut self, _: &mut Self::Storage, index: Self::ChildInfo) -> Rect {
+        self.rects[index]
+    }
+}
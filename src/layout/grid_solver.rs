@@ -12,6 +12,7 @@ use crate::geom::{Coord, Rect, Size};
 use kas::{Align, AlignHints};
 
 /// Per-child information
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct GridChildInfo {
     /// Column index (first column when in a span)
     pub col: u32,
@@ -43,10 +44,26 @@ impl<CSR: Default, RSR: Default, S: GridStorage> GridSolver<CSR, RSR, S> {
     /// - `axis`: `AxisInfo` instance passed into `size_rules`
     /// - `(cols, rows)`: number of columns and rows
     /// - `storage`: reference to persistent storage
-    pub fn new(axis: AxisInfo, (cols, rows): (usize, usize), storage: &mut S) -> Self {
-        let col_spans = CSR::default();
-        let row_spans = RSR::default();
+    pub fn new(axis: AxisInfo, dim: (usize, usize), storage: &mut S) -> Self {
+        let spans = (CSR::default(), RSR::default());
+        Self::new_with_spans(axis, dim, spans, storage)
+    }
+}
 
+impl<CSR, RSR, S: GridStorage> GridSolver<CSR, RSR, S> {
+    /// Construct, with explicit span storage
+    ///
+    /// This is as [`GridSolver::new`], except that storage for cell-spans is
+    /// passed by the caller (e.g. a `Vec` when the number of children is not
+    /// known at compile-time). `col_spans` must have length at least the
+    /// number of children spanning multiple columns, each with value
+    /// `Default::default()`; likewise for `row_spans`.
+    pub fn new_with_spans(
+        axis: AxisInfo,
+        (cols, rows): (usize, usize),
+        (col_spans, row_spans): (CSR, RSR),
+        storage: &mut S,
+    ) -> Self {
         storage.set_dims(cols, rows);
 
         let mut solver = GridSolver {
@@ -0,0 +1,77 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Font-relative lengths
+
+use crate::draw::{SizeHandle, TextClass};
+use crate::geom::{scale_px, Size};
+
+/// A length in user-facing units
+///
+/// Lengths are resolved to physical pixels against the current scale factor
+/// and the theme's font size, thus layouts specified in [`Length::Em`] or
+/// [`Length::Lines`] scale coherently when the user changes the font size.
+///
+/// Resolution follows the rounding rules of [`scale_px`].
+///
+/// ```
+/// use kas::draw::MockSizeHandle;
+/// use kas::layout::Length;
+///
+/// let size_handle = MockSizeHandle::new(1.5);
+/// assert_eq!(Length::Px(10.0).to_px(&size_handle), 15);
+/// assert_eq!(Length::Em(2.0).to_px(&size_handle), 48);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Length {
+    /// Logical pixels (multiplied by the scale factor)
+    Px(f32),
+    /// Multiples of the font size (see [`SizeHandle::dpem`])
+    Em(f32),
+    /// Multiples of the height of a line of text (see
+    /// [`SizeHandle::line_height`])
+    Lines(f32),
+}
+
+impl Default for Length {
+    fn default() -> Self {
+        Length::Px(0.0)
+    }
+}
+
+impl From<f32> for Length {
+    /// Interpret as logical pixels
+    #[inline]
+    fn from(px: f32) -> Self {
+        Length::Px(px)
+    }
+}
+
+impl Length {
+    /// Resolve to physical pixels given the scale factor, font size in pixels
+    /// per em, and line height
+    pub fn resolve(self, scale_factor: f32, dpem: f32, line_height: u32) -> u32 {
+        match self {
+            Length::Px(len) => scale_px(len, scale_factor),
+            Length::Em(len) => scale_px(len, dpem),
+            Length::Lines(len) => scale_px(len, line_height as f32),
+        }
+    }
+
+    /// Resolve to physical pixels using a [`SizeHandle`]
+    pub fn to_px(self, size_handle: &dyn SizeHandle) -> u32 {
+        self.resolve(
+            size_handle.scale_factor(),
+            size_handle.dpem(),
+            size_handle.line_height(TextClass::Label),
+        )
+    }
+
+    /// Resolve a pair of lengths to a [`Size`]
+    pub fn to_size(lengths: (Length, Length), size_handle: &dyn SizeHandle) -> Size {
+        Size(lengths.0.to_px(size_handle), lengths.1.to_px(size_handle))
+    }
+}
@@ -6,6 +6,9 @@
 //! Layout solver
 //!
 //! Size units are physical (real) pixels. This applies to most of KAS.
+//! User-facing specifications (e.g. margins or minimum sizes) may instead use
+//! a [`Length`]: logical pixels or units relative to the font size, resolved
+//! against the theme's current font size and scale factor.
 //!
 //! ## Data types
 //!
@@ -29,27 +32,83 @@
 //! -   [`GridSolver`] and [`GridSetter`] set out children assigned to grid
 //!     cells with optional cell-spans. This is the most powerful and flexible
 //!     layout engine.
+//! -   [`FlowSolver`] and [`FlowSetter`] set out children left-to-right,
+//!     wrapping to new rows as required by the available width.
 //!
 //! [`RowPositionSolver`] may be used with widgets set out by [`RowSetter`]
 //! to quickly locate children from a `coord` or `rect`.
+//!
+//! ## Height-for-width
+//!
+//! The width of widgets is solved first, followed by the height given the
+//! width (see [`kas::Layout::size_rules`]), thus the height of e.g. wrapped
+//! text and [`FlowSolver`] layouts may depend on the width. Layout engines
+//! pass each child its own width (e.g. the width of a column or of spanned
+//! grid cells) via [`AxisInfo::other`]; widgets surrounding content should
+//! reduce this via [`AxisInfo::sub_other`]. When the width changes,
+//! [`SolveCache::apply_rect`] solves heights again.
+//!
+//! ## Lazy layout
+//!
+//! Scroll regions inform their content of the visible region via
+//! [`kas::Layout::set_viewport`]. Containers with many children (e.g.
+//! [`kas::widget::LazyList`]) may use this to configure, size and draw only
+//! children intersecting the viewport. Since sizes of other children are not
+//! known, such containers usually estimate them, e.g. by assuming uniform
+//! child size. Where newly visible children must be configured, the container
+//! sets [`kas::CoreData::reconfigure`]; the toolkit then configures only this
+//! subtree (see [`kas::event::ManagerState::configure_pending`]).
+//!
+//! ## Size groups
+//!
+//! A [`SizeGroup`] links the widths and/or heights of widgets which need not
+//! be siblings, e.g. labels in separate rows of a form.
+//!
+//! ## Caching
+//!
+//! Widgets cache their solved [`SizeRules`] in a [`LayoutCache`]. Following a
+//! change affecting only one widget's size requirements, [`mark_dirty`]
+//! invalidates only that widget and its ancestors, thus only this branch of
+//! the widget tree need be re-solved. Alternatively a widget may mark its own
+//! cache via [`LayoutCache::set_dirty`] and return [`TkAction::Relayout`];
+//! the toolkit then calls [`invalidate_dirty`].
+//!
+//! [`TkAction::Relayout`]: crate::TkAction::Relayout
+//!
+//! ## Debugging
+//!
+//! [`layout_to_text`] lays out a widget tree without a toolkit (using
+//! [`MockSizeHandle`]) and renders the result as a text diagram.
+//!
+//! [`MockSizeHandle`]: crate::draw::MockSizeHandle
 
+mod ascii;
+mod cache;
+mod flow_solver;
 mod grid_solver;
+mod length;
 mod row_solver;
 mod single_solver;
+mod size_group;
 mod size_rules;
 mod sizer;
 mod storage;
 
 use crate::geom::Size;
 
+pub use ascii::layout_to_text;
+pub use cache::{invalidate_dirty, mark_dirty, LayoutCache};
+pub use flow_solver::{FlowSetter, FlowSolver};
 pub use grid_solver::{GridChildInfo, GridSetter, GridSolver};
+pub use length::Length;
 pub use row_solver::{RowPositionSolver, RowSetter, RowSolver};
 pub use single_solver::{SingleSetter, SingleSolver};
+pub use size_group::SizeGroup;
 pub use size_rules::{Margins, SizeRules, StretchPolicy};
 pub use sizer::{RulesSetter, RulesSolver, SolveCache};
 pub use storage::{
-    DynGridStorage, DynRowStorage, FixedGridStorage, FixedRowStorage, GridStorage, RowStorage,
-    RowTemp, Storage,
+    DynGridStorage, DynRowStorage, FixedGridStorage, FixedRowStorage, FlowStorage, GridStorage,
+    RowStorage, RowTemp, Storage,
 };
 
 /// Information on which axis is being resized
@@ -107,6 +166,21 @@ impl AxisInfo {
         }
     }
 
+    /// Reduce the size of the other axis (if fixed) by `x`
+    ///
+    /// Widgets surrounding their content (e.g. with a frame) should use this
+    /// to pass the size available to content to [`kas::Layout::size_rules`].
+    #[inline]
+    pub fn sub_other(&mut self, x: u32) {
+        self.other_axis = self.other_axis.saturating_sub(x);
+    }
+
+    /// Limit the size of the other axis (if fixed) to at most `max`
+    #[inline]
+    pub fn limit_other(&mut self, max: u32) {
+        self.other_axis = self.other_axis.min(max);
+    }
+
     /// Extract horizontal or vertical component of a [`Size`]
     #[inline]
     pub fn extract_size(&self, size: Size) -> u32 {
@@ -23,6 +23,11 @@ pub struct RowSolver<S: RowStorage> {
     axis_is_vertical: bool,
     axis_is_reversed: bool,
     rules: Option<SizeRules>,
+    // Index of the first and of the last child passed so far
+    first: Option<usize>,
+    last: usize,
+    // Whether any child passed so far is not hidden
+    visible: bool,
     _s: PhantomData<S>,
 }
 
@@ -49,11 +54,24 @@ impl<S: RowStorage> RowSolver<S> {
             axis_is_vertical,
             axis_is_reversed: dir.is_reversed(),
             rules: None,
+            first: None,
+            last: 0,
+            visible: false,
             _s: Default::default(),
         }
     }
 }
 
+// Replace the first (if `first`) or the second margin of `rules` with `value`,
+// returning the old value
+fn replace_margin(rules: &mut SizeRules, first: bool, value: u16) -> u16 {
+    let mut m = rules.margins();
+    let side = if first { &mut m.0 } else { &mut m.1 };
+    let old = std::mem::replace(side, value);
+    rules.set_margins(m);
+    old
+}
+
 impl<S: RowStorage> RulesSolver for RowSolver<S> {
     type Storage = S;
     type ChildInfo = usize;
@@ -67,8 +85,15 @@ impl<S: RowStorage> RulesSolver for RowSolver<S> {
         if self.axis.has_fixed && self.axis_is_vertical {
             self.axis.other_axis = storage.widths()[child_info];
         }
-        let child_rules = child_rules(self.axis);
+        let mut child_rules = child_rules(self.axis);
         if !self.axis_is_vertical {
+            if let (Some(first), false) = (self.first, self.visible) {
+                // All previous children are hidden: these take the outer margin
+                let side = !self.axis_is_reversed;
+                let m = replace_margin(&mut child_rules, side, 0);
+                replace_margin(&mut storage.rules()[first], side, m);
+                replace_margin(self.rules.as_mut().unwrap(), side, m);
+            }
             storage.rules()[child_info] = child_rules;
             if let Some(rules) = self.rules {
                 if self.axis_is_reversed {
@@ -86,6 +111,35 @@ impl<S: RowStorage> RulesSolver for RowSolver<S> {
                     .unwrap_or(child_rules),
             );
         }
+        self.first.get_or_insert(child_info);
+        self.last = child_info;
+        self.visible = true;
+    }
+
+    /// Hidden children are given zero size and do not separate the margins of
+    /// adjacent children: those are combined as if the child were not present.
+    fn for_hidden_child(&mut self, storage: &mut Self::Storage, child_info: Self::ChildInfo) {
+        let mut child_rules = SizeRules::EMPTY;
+        if !self.axis_is_vertical {
+            if let Some(rules) = self.rules.as_mut() {
+                // Take the trailing margin of previous children, such that it
+                // is combined with the leading margin of the next child
+                let side = self.axis_is_reversed;
+                let m = replace_margin(rules, side, 0);
+                replace_margin(&mut storage.rules()[self.last], side, 0);
+                replace_margin(&mut child_rules, side, m);
+            }
+            storage.rules()[child_info] = child_rules;
+            self.rules = Some(match self.rules {
+                Some(rules) if self.axis_is_reversed => child_rules.appended(rules),
+                Some(rules) => rules.appended(child_rules),
+                None => child_rules,
+            });
+        } else if self.rules.is_none() {
+            self.rules = Some(child_rules);
+        }
+        self.first.get_or_insert(child_info);
+        self.last = child_info;
     }
 
     fn finish(self, storage: &mut Self::Storage) -> SizeRules {
@@ -350,3 +404,26 @@ impl<D: Directional> RowPositionSolver<D> {
         }
     }
 }
+
+#[test]
+fn hidden_child_margins() {
+    use super::{DynRowStorage, StretchPolicy};
+    use crate::{geom::Size, Right};
+
+    let fixed = |size, m| SizeRules::new(size, size, m, StretchPolicy::Fixed);
+    let mut storage = DynRowStorage::default();
+    let axis = AxisInfo::new(false, None);
+    let mut solver = RowSolver::new(axis, (Right, 5), &mut storage);
+    solver.for_hidden_child(&mut storage, 0);
+    solver.for_child(&mut storage, 1, |_| fixed(10, (4, 6)));
+    solver.for_hidden_child(&mut storage, 2);
+    solver.for_child(&mut storage, 3, |_| fixed(10, (8, 2)));
+    solver.for_hidden_child(&mut storage, 4);
+    let rules = solver.finish(&mut storage);
+    // Margins around hidden children are combined as if these were absent
+    assert_eq!(rules, fixed(28, (4, 2)));
+
+    let rect = Rect::new(Coord::ZERO, Size(28, 10));
+    let setter = RowSetter::<_, Vec<u32>, _>::new(rect, (Right, 5), AlignHints::NONE, &mut storage);
+    assert_eq!(setter.offsets, [0, 0, 10, 18, 28]);
+}
@@ -0,0 +1,142 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Size groups
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::SizeRules;
+
+thread_local! {
+    static STALE: Cell<bool> = Cell::new(false);
+}
+
+/// Returns true if the combined rules of any size group changed since the
+/// last call
+///
+/// In this case, rules of group members solved earlier are out-of-date.
+pub(crate) fn take_stale() -> bool {
+    STALE.with(|stale| stale.replace(false))
+}
+
+#[derive(Debug, Default)]
+struct AxisState {
+    members: HashMap<u32, SizeRules>,
+    combined: Option<SizeRules>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    next_key: u32,
+    axes: [AxisState; 2],
+}
+
+/// A group of widgets with linked sizes
+///
+/// Members of a size group share the same [`SizeRules`] (the maximum of the
+/// rules of all members) on the linked axes. This allows alignment of widgets
+/// which are not siblings, for example labels across several rows of a form,
+/// each row being a separate widget.
+///
+/// A `SizeGroup` is a shared handle: clones refer to the same group. Usually
+/// members are widgets of type [`SizeGroupMember`]. When combined rules change
+/// during solving, [`SolveCache`] re-solves the affected axis, thus members
+/// placed before the largest member are sized correctly.
+///
+/// [`SizeGroupMember`]: crate::widget::SizeGroupMember
+/// [`SolveCache`]: super::SolveCache
+#[derive(Clone, Debug)]
+pub struct SizeGroup {
+    linked: (bool, bool),
+    state: Rc<RefCell<State>>,
+}
+
+impl SizeGroup {
+    /// Construct a group linking the given axes: `(horizontal, vertical)`
+    pub fn new(horizontal: bool, vertical: bool) -> Self {
+        SizeGroup {
+            linked: (horizontal, vertical),
+            state: Default::default(),
+        }
+    }
+
+    /// Construct a group linking widths
+    #[inline]
+    pub fn horizontal() -> Self {
+        SizeGroup::new(true, false)
+    }
+
+    /// Construct a group linking heights
+    #[inline]
+    pub fn vertical() -> Self {
+        SizeGroup::new(false, true)
+    }
+
+    /// True if the group links sizes on the given axis
+    #[inline]
+    pub fn links(&self, vertical: bool) -> bool {
+        match vertical {
+            false => self.linked.0,
+            true => self.linked.1,
+        }
+    }
+
+    /// Add a member, returning its key
+    pub fn join(&self) -> u32 {
+        let mut state = self.state.borrow_mut();
+        let key = state.next_key;
+        state.next_key += 1;
+        key
+    }
+
+    /// Remove the member with the given `key`
+    pub fn leave(&self, key: u32) {
+        let mut state = self.state.borrow_mut();
+        for axis in &mut state.axes {
+            axis.members.remove(&key);
+        }
+    }
+
+    /// Update the rules of member `key`, returning the group's rules
+    ///
+    /// This should be called from the member's [`Layout::size_rules`] for
+    /// each linked axis.
+    ///
+    /// [`Layout::size_rules`]: crate::Layout::size_rules
+    pub fn update(&self, key: u32, vertical: bool, rules: SizeRules) -> SizeRules {
+        let mut state = self.state.borrow_mut();
+        let axis = &mut state.axes[vertical as usize];
+        axis.members.insert(key, rules);
+        let combined = axis
+            .members
+            .values()
+            .fold(SizeRules::EMPTY, |a, b| a.max(*b));
+        if axis.combined.map(|c| c != combined).unwrap_or(false) {
+            STALE.with(|stale| stale.set(true));
+        }
+        axis.combined = Some(combined);
+        combined
+    }
+}
+
+#[test]
+fn size_group() {
+    use super::StretchPolicy;
+
+    let group = SizeGroup::horizontal();
+    let (a, b) = (group.join(), group.join());
+    let small = SizeRules::new(10, 20, (0, 0), StretchPolicy::Fixed);
+    let large = SizeRules::new(15, 30, (0, 0), StretchPolicy::Fixed);
+    let _ = take_stale();
+    assert_eq!(group.update(a, false, small), small);
+    assert_eq!(group.update(b, false, large), large);
+    assert!(take_stale());
+    assert_eq!(group.update(a, false, small), large);
+    assert!(!take_stale());
+    group.leave(b);
+    assert_eq!(group.update(a, false, small), small);
+}
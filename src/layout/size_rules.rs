@@ -15,6 +15,9 @@ use crate::geom::Size;
 #[allow(unused)]
 use kas::draw::SizeHandle;
 
+// Denominator of SizeRules::p
+const PROPORTION_ONE: u32 = 10_000;
+
 /// Margin sizes
 ///
 /// Used by the layout system for margins around child widgets. Margins may be
@@ -58,6 +61,34 @@ impl Margins {
         let h = size.1 + (self.vert.0 + self.vert.1) as u32;
         Size(w, h)
     }
+
+    /// Get the `(pre, post)` margins on the given axis
+    #[inline]
+    pub fn axis(self, vertical: bool) -> (u16, u16) {
+        match vertical {
+            false => self.horiz,
+            true => self.vert,
+        }
+    }
+
+    /// Scale by `factor` (e.g. [`SizeHandle::scale_factor`])
+    ///
+    /// Results are rounded to the nearest pixel.
+    pub fn scaled(self, factor: f32) -> Self {
+        let scale = |x: u16| (x as f32 * factor).round() as u16;
+        Margins {
+            horiz: (scale(self.horiz.0), scale(self.horiz.1)),
+            vert: (scale(self.vert.0), scale(self.vert.1)),
+        }
+    }
+}
+
+impl From<u16> for Margins {
+    /// Margins with equal size on each edge
+    #[inline]
+    fn from(size: u16) -> Self {
+        Margins::uniform(size)
+    }
 }
 
 /// Policy for stretching widgets beyond ideal size
@@ -99,6 +130,9 @@ impl Default for StretchPolicy {
 /// - the minimum size required for correct operation
 /// - the preferred / ideal size
 /// - a [`StretchPolicy`]
+/// - a stretch weight (see [`SizeRules::with_weight`])
+/// - optionally, a proportion of the parent's length
+///   (see [`SizeRules::with_percent`])
 ///
 /// Available space is distributed between widgets depending on whether the
 /// space is below the minimum, between the minimum and preferred, or above
@@ -141,7 +175,7 @@ impl Default for StretchPolicy {
 /// [`kas::Layout::set_rect`] and [`kas::AlignHints`].
 ///
 /// [`Rect`]: kas::geom::Rect
-#[derive(Copy, Clone, Default, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq)]
 pub struct SizeRules {
     // minimum good size
     a: u32,
@@ -150,14 +184,24 @@ pub struct SizeRules {
     // (pre, post) margins
     m: (u16, u16),
     stretch: StretchPolicy,
+    // stretch weight; w >= 1
+    w: u32,
+    // proportion of parent's length, in units of 0.01%; 0 for none
+    p: u16,
+}
+
+impl Default for SizeRules {
+    fn default() -> Self {
+        SizeRules::EMPTY
+    }
 }
 
 impl fmt::Debug for SizeRules {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "SizeRules {{ a: {}, b: {}, m: ({}, {}), stretch: {:?} }}",
-            self.a, self.b, self.m.0, self.m.1, self.stretch
+            "SizeRules {{ a: {}, b: {}, m: ({}, {}), stretch: {:?}, w: {}, p: {} }}",
+            self.a, self.b, self.m.0, self.m.1, self.stretch, self.w, self.p
         )
     }
 }
@@ -181,6 +225,8 @@ impl SizeRules {
             b: 0,
             m: (0, 0),
             stretch,
+            w: 1,
+            p: 0,
         }
     }
 
@@ -192,6 +238,8 @@ impl SizeRules {
             b: size,
             m: margins,
             stretch: StretchPolicy::Fixed,
+            w: 1,
+            p: 0,
         }
     }
 
@@ -204,6 +252,8 @@ impl SizeRules {
                 b: size.0,
                 m: margin.horiz,
                 stretch: StretchPolicy::Fixed,
+                w: 1,
+                p: 0,
             }
         } else {
             SizeRules {
@@ -211,6 +261,8 @@ impl SizeRules {
                 b: size.1,
                 m: margin.vert,
                 stretch: StretchPolicy::Fixed,
+                w: 1,
+                p: 0,
             }
         }
     }
@@ -228,6 +280,8 @@ impl SizeRules {
             b: ideal.max(min),
             m: margins,
             stretch,
+            w: 1,
+            p: 0,
         }
     }
 
@@ -243,12 +297,121 @@ impl SizeRules {
         self.b
     }
 
+    /// Set the minimum size, replacing the existing minimum
+    ///
+    /// The ideal size is increased to `min` if smaller. This may be used to
+    /// reserve space, or (with care) to allow a widget to be smaller than
+    /// its content requires.
+    #[inline]
+    pub fn set_min_size(&mut self, min: u32) {
+        self.a = min;
+        self.b = self.b.max(min);
+    }
+
+    /// Set the ideal size, replacing the existing ideal size
+    ///
+    /// This does not go below the minimum size.
+    #[inline]
+    pub fn set_ideal_size(&mut self, ideal: u32) {
+        self.b = ideal.max(self.a);
+    }
+
+    /// Cap the size at `max`
+    ///
+    /// The minimum and ideal sizes are reduced to `max` if larger. Since
+    /// `SizeRules` cannot represent an upper bound, rules which may stretch
+    /// instead have their ideal size set to `max`, and in either case the
+    /// stretch policy becomes [`StretchPolicy::Fixed`]. Note that layouts may
+    /// still assign more space than the ideal (see [`kas::Layout::set_rect`]).
+    pub fn set_max_size(&mut self, max: u32) {
+        if self.stretch != StretchPolicy::Fixed {
+            self.b = max;
+        }
+        self.a = self.a.min(max);
+        self.b = self.b.min(max);
+        self.stretch = StretchPolicy::Fixed;
+    }
+
+    /// Get the stretch weight
+    #[inline]
+    pub fn weight(self) -> u32 {
+        self.w
+    }
+
+    /// Set the stretch weight
+    ///
+    /// When a row or column has more space than the ideal size of its
+    /// children, excess space is divided between the children with the
+    /// highest [`StretchPolicy`] in proportion to their weights. The default
+    /// weight is 1; `weight` values less than 1 are treated as 1.
+    ///
+    /// Weights are only meaningful between siblings, thus the result of
+    /// combining rules (e.g. via [`SizeRules::appended`]) has weight 1.
+    #[inline]
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.w = weight.max(1);
+        self
+    }
+
+    /// Get the requested proportion of the parent's length, if any
+    ///
+    /// The result is a fraction in the range `(0, 1]`.
+    #[inline]
+    pub fn proportion(self) -> Option<f32> {
+        match self.p {
+            0 => None,
+            p => Some(p as f32 / PROPORTION_ONE as f32),
+        }
+    }
+
+    /// Request a percentage of the parent's length
+    ///
+    /// Within a row or column (more generally, wherever rules are solved via
+    /// [`SizeRules::solve_seq`]), a member with a proportional request is
+    /// given `percent`% of the available length (excluding margins), or its
+    /// ideal size if larger, provided this leaves sufficient space for all
+    /// other members to have their ideal size. Otherwise the request is
+    /// ignored and space is distributed as usual. Proportional members do not
+    /// receive additional space via stretching unless all members have
+    /// policy [`StretchPolicy::Fixed`].
+    ///
+    /// `percent` is clamped to the range `0..=100`; zero clears the request.
+    ///
+    /// Like weights, proportions are only meaningful between siblings, thus
+    /// the result of combining rules has no proportion.
+    #[inline]
+    pub fn with_percent(self, percent: u32) -> Self {
+        self.with_ratio(percent.min(100), 100)
+    }
+
+    /// Request a ratio `num / den` of the parent's length
+    ///
+    /// This is equivalent to [`SizeRules::with_percent`], but allows finer
+    /// granularity (to 0.01%). The ratio is clamped to at most 1; if `den` is
+    /// zero the request is cleared.
+    #[inline]
+    pub fn with_ratio(mut self, num: u32, den: u32) -> Self {
+        self.p = match den {
+            0 => 0,
+            den => (num.min(den) as u64 * PROPORTION_ONE as u64 / den as u64) as u16,
+        };
+        self
+    }
+
     /// Get the `(pre, post)` margin sizes
     #[inline]
     pub fn margins(self) -> (u16, u16) {
         self.m
     }
 
+    /// Set `(pre, post)` margins, replacing existing margins
+    ///
+    /// This may be used to override the margins provided by the theme.
+    #[inline]
+    pub fn set_margins(&mut self, margins: (u16, u16)) {
+        self.m = margins;
+    }
+
     /// Set margins to max of own margins and given margins
     pub fn include_margins(&mut self, margins: (u16, u16)) {
         self.m.0 = self.m.0.max(margins.0);
@@ -263,6 +426,8 @@ impl SizeRules {
             b: self.b.max(rhs.b),
             m: (self.m.0.max(rhs.m.0), self.m.1.max(rhs.m.1)),
             stretch: self.stretch.max(rhs.stretch),
+            w: 1,
+            p: 0,
         }
     }
 
@@ -285,6 +450,8 @@ impl SizeRules {
         self.b += rhs.b + c;
         self.m.1 = rhs.m.1;
         self.stretch = self.stretch.max(rhs.stretch);
+        self.w = 1;
+        self.p = 0;
     }
 
     /// Return the rules for self appended by `rhs`
@@ -303,6 +470,8 @@ impl SizeRules {
             b: self.b + rhs.b + c,
             m: (self.m.0, rhs.m.1),
             stretch: self.stretch.max(rhs.stretch),
+            w: 1,
+            p: 0,
         }
     }
 
@@ -322,6 +491,8 @@ impl SizeRules {
             b: self.b + frame.b + c,
             m,
             stretch: self.stretch.max(frame.stretch),
+            w: 1,
+            p: 0,
         }
     }
 
@@ -356,6 +527,8 @@ impl SizeRules {
         self.b = (self.b + y.b).saturating_sub(x.b);
         self.m.1 = y.m.1;
         self.stretch = self.stretch.max(y.stretch);
+        self.w = 1;
+        self.p = 0;
     }
 
     /// Reduce the minimum size
@@ -393,8 +566,8 @@ impl SizeRules {
     /// -   All widths are at least their minimum size requirement
     /// -   All widths are at least their ideal size requirement, if this can be
     ///     met without decreasing any widths
-    /// -   Excess space is divided evenly among members with the highest
-    ///     stretch policy
+    /// -   Excess space is divided among members with the highest stretch
+    ///     policy, in proportion to their [weight](SizeRules::with_weight)
     ///
     /// Input requirements: `rules.len() == out.len()`.
     ///
@@ -413,8 +586,28 @@ impl SizeRules {
         Self::solve_seq_(out, rules, total, target);
     }
 
+    // Replace proportional rules with fixed sizes, if space allows
+    fn apply_proportions(rules: &[Self], total: Self, target: u32) -> Option<Vec<Self>> {
+        let content: u32 = rules.iter().map(|r| r.b).sum();
+        let avail = target.checked_sub(total.b.saturating_sub(content))?;
+
+        let mut fixed = rules.to_vec();
+        let mut sum = 0;
+        for r in &mut fixed {
+            if r.p != 0 {
+                let size = (avail as u64 * r.p as u64 / PROPORTION_ONE as u64) as u32;
+                *r = SizeRules::fixed(size.max(r.b), r.m);
+            }
+            sum += r.b;
+        }
+
+        match sum <= avail {
+            true => Some(fixed),
+            false => None,
+        }
+    }
+
     fn solve_seq_(out: &mut [u32], rules: &[Self], total: Self, target: u32) {
-        type Targets = SmallVec<[u32; 16]>;
         #[allow(non_snake_case)]
         let N = out.len();
         assert_eq!(rules.len(), N);
@@ -422,6 +615,13 @@ impl SizeRules {
             return;
         }
 
+        if rules.iter().any(|r| r.p != 0) {
+            if let Some(rules) = Self::apply_proportions(rules, total, target) {
+                let total = SizeRules::sum(&rules);
+                return Self::solve_seq_(out, &rules, total, target);
+            }
+        }
+
         if target > total.a {
             // All minimum sizes can be met.
             out[0] = out[0].max(rules[0].a);
@@ -441,21 +641,23 @@ impl SizeRules {
             if sum == target {
                 return;
             } else if sum < target {
-                fn increase_targets<F: Fn(usize) -> u32>(
+                fn increase_targets<F: Fn(usize) -> u32, W: Fn(usize) -> u32>(
                     out: &mut [u32],
                     targets: &mut Targets,
                     base: F,
+                    weight: W,
                     mut avail: u32,
                 ) {
-                    // Calculate ceiling above which sizes will not be increased
+                    // Calculate ceiling (per unit weight) above which sizes
+                    // will not be increased
                     let mut any_removed = true;
                     while any_removed {
                         any_removed = false;
-                        let count = targets.len() as u32;
-                        let ceil = (avail + count - 1) / count; // round up
+                        let total = targets.iter().map(|t| weight(*t as usize)).sum();
                         let mut t = 0;
                         while t < targets.len() {
                             let i = targets[t] as usize;
+                            let ceil = share(avail, weight(i), total, true);
                             if out[i] >= base(i) + ceil {
                                 avail -= out[i] - base(i);
                                 targets.remove(t);
@@ -470,20 +672,9 @@ impl SizeRules {
                     }
 
                     // Since no more are removed by a ceiling, all remaining
-                    // targets will be (approx) equal. Arbitrarily distribute
-                    // rounding errors to the first ones.
-                    let count = targets.len() as u32;
-                    let per_elt = avail / count;
-                    let extra = (avail - per_elt * count) as usize;
-                    assert!(extra < targets.len());
-                    for t in 0..extra {
-                        let i = targets[t] as usize;
-                        out[i] = base(i) + per_elt + 1;
-                    }
-                    for t in extra..targets.len() {
-                        let i = targets[t] as usize;
-                        out[i] = base(i) + per_elt;
-                    }
+                    // targets will be (approx) proportional to their weight.
+                    // Arbitrarily distribute rounding errors to the first ones.
+                    distribute(out, targets, base, weight, avail);
                 }
 
                 if target - sum >= dist_under_b {
@@ -504,7 +695,8 @@ impl SizeRules {
                     }
 
                     let avail = target - sum + over;
-                    increase_targets(out, &mut targets, |i| rules[i].b, avail);
+                    let weight = |i: usize| rules[i].w;
+                    increase_targets(out, &mut targets, |i| rules[i].b, weight, avail);
                     debug_assert_eq!(target, (0..N).fold(0, |x, i| x + out[i]));
                 } else {
                     // We cannot increase sizes as far as their ideal: instead
@@ -519,25 +711,27 @@ impl SizeRules {
                     }
 
                     let avail = target - sum + over;
-                    increase_targets(out, &mut targets, |i| rules[i].a, avail);
+                    increase_targets(out, &mut targets, |i| rules[i].a, |_| 1, avail);
                     debug_assert_eq!(target, (0..N).fold(0, |x, i| x + out[i]));
                 }
             } else {
                 // sum > target: we need to decrease some sizes
-                fn reduce_targets<F: Fn(usize) -> u32>(
+                fn reduce_targets<F: Fn(usize) -> u32, W: Fn(usize) -> u32>(
                     out: &mut [u32],
                     targets: &mut Targets,
                     base: F,
+                    weight: W,
                     mut avail: u32,
                 ) {
-                    // We can ignore everything below the floor
+                    // We can ignore everything below the floor (per unit weight)
                     let mut any_removed = true;
                     while any_removed {
                         any_removed = false;
-                        let floor = avail / targets.len() as u32;
+                        let total = targets.iter().map(|t| weight(*t as usize)).sum();
                         let mut t = 0;
                         while t < targets.len() {
                             let i = targets[t] as usize;
+                            let floor = share(avail, weight(i), total, false);
                             if out[i] <= base(i) + floor {
                                 avail -= out[i] - base(i);
                                 targets.remove(t);
@@ -549,17 +743,7 @@ impl SizeRules {
                     }
 
                     // All targets remaining must be reduced to floor, bar rounding errors
-                    let floor = avail / targets.len() as u32;
-                    let extra = avail as usize - floor as usize * targets.len();
-                    assert!(extra < targets.len());
-                    for t in 0..extra {
-                        let i = targets[t] as usize;
-                        out[i] = base(i) + floor + 1;
-                    }
-                    for t in extra..targets.len() {
-                        let i = targets[t] as usize;
-                        out[i] = base(i) + floor;
-                    }
+                    distribute(out, targets, base, weight, avail);
                 }
 
                 if dist_over_b > sum - target {
@@ -597,7 +781,8 @@ impl SizeRules {
                     }
                     if sum > target {
                         avail = avail + target - sum;
-                        reduce_targets(out, &mut targets, |i| rules[i].b, avail);
+                        let weight = |i: usize| rules[i].w;
+                        reduce_targets(out, &mut targets, |i| rules[i].b, weight, avail);
                     }
                     debug_assert_eq!(target, (0..N).fold(0, |x, i| x + out[i]));
                 } else {
@@ -614,7 +799,7 @@ impl SizeRules {
                     }
                     if sum > target {
                         let avail = target + margin_sum - total.a;
-                        reduce_targets(out, &mut targets, |i| rules[i].a, avail);
+                        reduce_targets(out, &mut targets, |i| rules[i].a, |_| 1, avail);
                     }
                     debug_assert_eq!(target, (0..N).fold(0, |x, i| x + out[i]));
                 }
@@ -723,6 +908,39 @@ impl SizeRules {
     }
 }
 
+type Targets = SmallVec<[u32; 16]>;
+
+/// The share of `avail` for an item of the given `weight`, out of `total`
+fn share(avail: u32, weight: u32, total: u32, round_up: bool) -> u32 {
+    let (x, total) = (avail as u64 * weight as u64, total as u64);
+    let x = if round_up { x + total - 1 } else { x };
+    (x / total) as u32
+}
+
+/// Set each of `targets` to `base` plus its share of `avail`
+///
+/// Rounding errors are distributed to the first targets.
+fn distribute<F: Fn(usize) -> u32, W: Fn(usize) -> u32>(
+    out: &mut [u32],
+    targets: &Targets,
+    base: F,
+    weight: W,
+    avail: u32,
+) {
+    let total = targets.iter().map(|t| weight(*t as usize)).sum();
+    let mut extra = avail;
+    for t in targets.iter() {
+        let i = *t as usize;
+        let x = share(avail, weight(i), total, false);
+        out[i] = base(i) + x;
+        extra -= x;
+    }
+    assert!((extra as usize) < targets.len().max(1));
+    for t in targets.iter().take(extra as usize) {
+        out[*t as usize] += 1;
+    }
+}
+
 /// Return the sum over a sequence of rules, assuming these are ordered
 ///
 /// Uses [`SizeRules::appended`] on all rules in sequence.
@@ -748,3 +966,30 @@ impl<'a> Sum<&'a Self> for SizeRules {
         }
     }
 }
+
+#[test]
+fn solve_seq_weights() {
+    let filler = SizeRules::empty(StretchPolicy::Filler);
+    let rules = [SizeRules::fixed(10, (0, 0)), filler, filler.with_weight(3)];
+    let mut out = [0; 3];
+    SizeRules::solve_seq(&mut out, &rules, 50);
+    assert_eq!(out, [10, 10, 30]);
+    SizeRules::solve_seq(&mut out, &rules, 30);
+    assert_eq!(out, [10, 5, 15]);
+}
+
+#[test]
+fn solve_seq_proportions() {
+    let filler = SizeRules::empty(StretchPolicy::Filler);
+    let rules = [
+        SizeRules::fixed(10, (0, 0)),
+        filler,
+        filler.with_percent(50),
+    ];
+    let mut out = [0; 3];
+    SizeRules::solve_seq(&mut out, &rules, 100);
+    assert_eq!(out, [10, 40, 50]);
+    let mut out = [0; 3];
+    SizeRules::solve_seq(&mut out, &rules, 40);
+    assert_eq!(out, [10, 10, 20]);
+}
@@ -8,7 +8,7 @@
 use log::trace;
 use std::fmt;
 
-use super::{AxisInfo, Margins, SizeRules};
+use super::{cache, size_group, AxisInfo, Margins, SizeRules};
 use crate::draw::SizeHandle;
 use crate::geom::{Coord, Rect, Size};
 use crate::{AlignHints, WidgetConfig};
@@ -36,6 +36,15 @@ pub trait RulesSolver {
         child_rules: CR,
     );
 
+    /// Called instead of [`RulesSolver::for_child`] for a hidden or absent child
+    ///
+    /// The child is given zero size. The default implementation passes
+    /// [`SizeRules::EMPTY`] to `for_child`; solvers may additionally collapse
+    /// the margins of adjacent children, as if the child were not present.
+    fn for_hidden_child(&mut self, storage: &mut Self::Storage, child_info: Self::ChildInfo) {
+        self.for_child(storage, child_info, |_| SizeRules::EMPTY);
+    }
+
     /// Called at the end to output [`SizeRules`].
     ///
     /// Note that this does not include margins!
@@ -76,9 +85,32 @@ pub struct SolveCache {
     ideal: Size,
     margins: Margins,
     refresh_rules: bool,
+    invalidate_cache: bool,
     last_width: u32,
 }
 
+/// Solve size rules for `axis`
+///
+/// If any [`SizeGroup`] changed while solving then rules are solved again,
+/// since group members solved earlier may have used out-of-date rules.
+///
+/// [`SizeGroup`]: super::SizeGroup
+fn solve_axis(
+    widget: &mut dyn WidgetConfig,
+    size_handle: &mut dyn SizeHandle,
+    axis: AxisInfo,
+) -> SizeRules {
+    let _ = size_group::take_stale();
+    let rules = widget.size_rules(size_handle, axis);
+    if size_group::take_stale() {
+        trace!("layout::solve: size group changed; solving again");
+        // Cached rules of group members may be out-of-date
+        cache::invalidate_all(widget);
+        return widget.size_rules(size_handle, axis);
+    }
+    rules
+}
+
 impl SolveCache {
     /// Get the minimum size
     ///
@@ -112,8 +144,13 @@ impl SolveCache {
         widget: &mut dyn WidgetConfig,
         size_handle: &mut dyn SizeHandle,
     ) -> Self {
-        let w = widget.size_rules(size_handle, AxisInfo::new(false, None));
-        let h = widget.size_rules(size_handle, AxisInfo::new(true, Some(w.ideal_size())));
+        cache::invalidate_all(widget);
+        let w = solve_axis(widget, size_handle, AxisInfo::new(false, None));
+        let h = solve_axis(
+            widget,
+            size_handle,
+            AxisInfo::new(true, Some(w.ideal_size())),
+        );
 
         let min = Size(w.min_size(), h.min_size());
         let ideal = Size(w.ideal_size(), h.ideal_size());
@@ -131,6 +168,7 @@ impl SolveCache {
             ideal,
             margins,
             refresh_rules,
+            invalidate_cache: false,
             last_width,
         }
     }
@@ -139,9 +177,24 @@ impl SolveCache {
     ///
     /// This should be called whenever widget size rules have been changed. It
     /// forces [`SolveCache::apply_rect`] to recompute these rules when next
-    /// called.
+    /// called. All rules cached by widgets (see [`LayoutCache`]) are
+    /// invalidated at that time.
+    ///
+    /// [`LayoutCache`]: super::LayoutCache
     pub fn invalidate_rule_cache(&mut self) {
         self.refresh_rules = true;
+        self.invalidate_cache = true;
+    }
+
+    /// Update size rules of dirty widgets
+    ///
+    /// Like [`SolveCache::invalidate_rule_cache`], this forces
+    /// [`SolveCache::apply_rect`] to recompute size rules, but rules cached
+    /// by widgets remain valid except where invalidated via [`mark_dirty`].
+    ///
+    /// [`mark_dirty`]: super::mark_dirty
+    pub fn refresh_dirty_rules(&mut self) {
+        self.refresh_rules = true;
     }
 
     /// Apply layout solution to a widget
@@ -163,8 +216,12 @@ impl SolveCache {
     ) {
         // We call size_rules not because we want the result, but because our
         // spec requires that we do so before calling set_rect.
+        if self.invalidate_cache {
+            cache::invalidate_all(widget);
+            self.invalidate_cache = false;
+        }
         if self.refresh_rules {
-            let w = widget.size_rules(size_handle, AxisInfo::new(false, None));
+            let w = solve_axis(widget, size_handle, AxisInfo::new(false, None));
             self.min.0 = w.min_size();
             self.ideal.0 = w.ideal_size();
             self.margins.horiz = w.margins();
@@ -175,7 +232,7 @@ impl SolveCache {
         }
 
         if self.refresh_rules || width != self.last_width {
-            let h = widget.size_rules(size_handle, AxisInfo::new(true, Some(width)));
+            let h = solve_axis(widget, size_handle, AxisInfo::new(true, Some(width)));
             self.min.1 = h.min_size();
             self.ideal.1 = h.ideal_size();
             self.margins.vert = h.margins();
@@ -218,3 +275,17 @@ impl<'a> fmt::Display for WidgetHeirarchy<'a> {
         Ok(())
     }
 }
+
+#[test]
+fn height_for_width() {
+    use crate::draw::MockSizeHandle;
+    use crate::widget::{Frame, Label};
+    use crate::{Layout, WidgetChildren, WidgetCore};
+
+    let mut frame = Frame::new(Label::new("abcdefghijklmnop"));
+    let mut size_handle = MockSizeHandle::default();
+    let _ = frame.size_rules(&mut size_handle, AxisInfo::new(false, None));
+    let _ = frame.size_rules(&mut size_handle, AxisInfo::new(true, Some(72)));
+    // The label's width is 72 - 2 × (frame + margin) = 56: 7 chars per line
+    assert_eq!(frame.get(0).unwrap().rect().size.1, 3 * 16);
+}
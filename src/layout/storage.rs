@@ -210,6 +210,8 @@ impl GridStorage for DynGridStorage {
     fn set_dims(&mut self, cols: usize, rows: usize) {
         self.width_rules.resize(cols + 1, SizeRules::EMPTY);
         self.height_rules.resize(rows + 1, SizeRules::EMPTY);
+        self.widths.resize(cols, 0);
+        self.heights.resize(rows, 0);
     }
 
     fn rules_and_widths(&mut self) -> (&mut [SizeRules], &mut [u32]) {
@@ -220,6 +222,23 @@ impl GridStorage for DynGridStorage {
     }
 }
 
+/// Flow layout storage
+///
+/// Used by [`super::FlowSolver`] and [`super::FlowSetter`].
+#[derive(Clone, Debug, Default)]
+pub struct FlowStorage {
+    /// Horizontal rules of each child
+    pub(super) width_rules: Vec<SizeRules>,
+    /// Vertical rules of each child
+    pub(super) height_rules: Vec<SizeRules>,
+    /// Width of each child
+    pub(super) widths: Vec<u32>,
+    /// Index of the first child of each row
+    pub(super) rows: Vec<usize>,
+}
+
+impl Storage for FlowStorage {}
+
 mod sealed {
     pub trait Sealed {}
     impl<R: Clone, W: Clone> Sealed for super::FixedRowStorage<R, W> {}
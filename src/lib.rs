@@ -35,12 +35,17 @@ mod toolkit;
 mod traits;
 
 // public implementations:
+pub mod anim;
 pub mod class;
+pub mod command;
 pub mod draw;
 pub mod event;
 pub mod geom;
 pub mod layout;
+pub mod plugin;
 pub mod prelude;
+#[cfg(feature = "script")]
+pub mod script;
 pub mod string;
 pub mod text;
 pub mod widget;
@@ -52,4 +57,5 @@ pub mod macros;
 pub use crate::data::*;
 pub use crate::toolkit::*;
 pub use crate::traits::*;
+pub use instant::Instant;
 pub use kas_text::Align;
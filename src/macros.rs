@@ -43,13 +43,14 @@
 //! The behaviour of this macro is controlled by attributes on struct fields and
 //! on the widget struct itself.
 //!
-//! These attributes may be used on the struct: `widget`, `layout`, `handler`.
+//! These attributes may be used on the struct: `widget`, `layout`, `handler`,
+//! `widget_derive`.
 //! These may each appear zero or once (except `handler`; see below).
 //! They support multiple parameters, e.g. `#[widget(config=noauto, children=noauto)]`.
 //!
 //! These attributes may be used on fields: `widget`, `widget_core`,
-//! `layout_data`. The `widget` attribute supports multiple parameters,
-//! discussed below (e.g. `#[widget(row=1, handler=f)]`).
+//! `layout_data`, `widget_skip_debug`. The `widget` attribute supports
+//! multiple parameters, discussed below (e.g. `#[widget(row=1, handler=f)]`).
 //! Fields without attributes (plain data fields) are fine too.
 //!
 //! A simple example:
@@ -82,9 +83,215 @@
 //! will be enumerated by the derived implementation of this trait, in the order
 //! of definition.
 //!
-//! In case child widgets are stored within a container (e.g. `Vec`), this macro
-//! is unable to enumerate the widgets correctly. In that case one must opt out
-//! of deriving this trait with `#[widget(children = noauto)]` on the struct.
+//! A field of type `Option<W>` (where `W` is a widget type) may be marked with
+//! `#[widget]` to allow a child to be shown or hidden: when `None`, it is not
+//! enumerated, drawn or sent events, and the derived [`Layout`] gives it zero
+//! size (including in grid layouts, where its cell remains reserved).
+//!
+//! A field of type `Vec<W>` may also be marked with `#[widget]`; its
+//! elements are enumerated in order in place of the field, and events are sent
+//! to them as to other children (any `handler` or `map` applies to all
+//! elements). Since the number of children is dynamic, such fields are not
+//! supported by the derived [`Layout`].
+//!
+//! Note that after changing an `Option` or modifying a `Vec`, a
+//! [reconfigure action](crate::TkAction::Reconfigure) is required.
+//!
+//! Boxed children of type `Box<dyn Widget<Msg = M>>` (also within a `Vec` or
+//! `Option`) are supported. Where `M` is a type parameter of the struct, the
+//! bound `M: 'static` is implied for derived impls (but not for impls derived
+//! by other macros, e.g. `#[derive(Clone)]`). Where no `#[handler]` attribute
+//! is given and all boxed children have the same message type, this is used
+//! as the widget's message type (instead of [`VoidMsg`]), thus messages are
+//! simply forwarded:
+//! ```
+//! use kas::prelude::*;
+//!
+//! #[layout(single)]
+//! #[derive(Debug, Widget)]
+//! struct Wrapper<M> {
+//!     #[widget_core] core: CoreData,
+//!     #[layout_data] layout_data: <Self as LayoutData>::Data,
+//!     #[widget] inner: Box<dyn Widget<Msg = M>>,
+//! }
+//!
+//! let wrapper: Wrapper<u32> = Wrapper {
+//!     core: Default::default(),
+//!     layout_data: Default::default(),
+//!     inner: Box::new(kas::widget::TextButton::new("Ok", 1u32)),
+//! };
+//! assert_eq!(wrapper.len(), 1);
+//! ```
+//!
+//! ```
+//! use kas::prelude::*;
+//! use kas::widget::{Label, TextButton};
+//!
+//! #[handler(msg = u32)]
+//! #[derive(Clone, Debug, Widget)]
+//! struct Buttons {
+//!     #[widget_core] core: CoreData,
+//!     #[widget] buttons: Vec<TextButton<u32>>,
+//! }
+//!
+//! impl Layout for Buttons {
+//!     // (Layout of children omitted)
+//!     fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
+//!         SizeRules::EMPTY
+//!     }
+//!     fn draw(&self, _: &mut dyn DrawHandle, _: &event::ManagerState, _: bool) {}
+//! }
+//!
+//! let buttons = Buttons {
+//!     core: Default::default(),
+//!     buttons: (0..3).map(|i| TextButton::new(i.to_string(), i)).collect(),
+//! };
+//! assert_eq!(buttons.len(), 3);
+//!
+//! #[layout(row)]
+//! #[handler(msg = VoidMsg)]
+//! #[derive(Clone, Debug, Widget)]
+//! struct Panes {
+//!     #[widget_core] core: CoreData,
+//!     #[layout_data] layout_data: <Self as LayoutData>::Data,
+//!     #[widget] main: Label,
+//!     #[widget] details: Option<Label>,
+//! }
+//!
+//! let mut panes = Panes {
+//!     core: Default::default(),
+//!     layout_data: Default::default(),
+//!     main: Label::new("Main"),
+//!     details: None,
+//! };
+//! assert_eq!(panes.len(), 1);
+//! panes.details = Some(Label::new("Details"));
+//! assert_eq!(panes.len(), 2);
+//! ```
+//!
+//! In case child widgets are stored within another container (e.g.
+//! `HashMap`), this macro is unable to enumerate the widgets correctly. In that
+//! case one must opt out of deriving this trait with
+//! `#[widget(children = noauto)]` on the struct.
+//!
+//! With `#[widget(accessors)]` on the struct, typed accessors are generated
+//! for each named child field: `fn name(&self) -> &Type` and
+//! `fn name_mut(&mut self) -> &mut Type`. To access a descendant by
+//! [`WidgetId`] instead, see [`WidgetChildren::find_as`].
+//! ```
+//! use kas::prelude::*;
+//! use kas::class::SetText;
+//! use kas::widget::Label;
+//!
+//! #[widget(accessors)]
+//! #[layout(column)]
+//! #[handler(msg = VoidMsg)]
+//! #[derive(Clone, Debug, Widget)]
+//! struct Status {
+//!     #[widget_core] core: CoreData,
+//!     #[layout_data] layout_data: <Self as LayoutData>::Data,
+//!     #[widget] title: Label,
+//!     #[widget] detail: Label,
+//! }
+//!
+//! let mut status = Status {
+//!     core: Default::default(),
+//!     layout_data: Default::default(),
+//!     title: Label::new("Title"),
+//!     detail: Label::new("Detail"),
+//! };
+//! let _ = status.detail_mut().set_text("Updated");
+//! assert_eq!(status.detail().full_text(), "Updated");
+//! ```
+//!
+//! ### Clone and Debug
+//!
+//! All widgets must implement [`Debug`](std::fmt::Debug). Usually this is
+//! done with `#[derive(Debug)]`, but this fails when some field (e.g. a
+//! solver, font handle or callback) does not support `Debug`. The same applies
+//! to the (optional) [`Clone`] implementation.
+//!
+//! Instead, `#[widget_derive(Clone, Debug)]` on the struct may be used to
+//! derive either or both traits, treating fields as follows:
+//!
+//! -   by default, fields are cloned / formatted as usual
+//! -   fields marked with `#[widget_derive(skip)]` are summarised as
+//!     `<omitted>` by `Debug` and initialised with [`Default::default`] by
+//!     `Clone` (e.g. for caches or temporary state)
+//! -   fields marked with `#[widget_skip_debug]` are omitted from `Debug`
+//!     output entirely
+//!
+//! Each type parameter is required to implement the derived trait.
+//! `#[widget(debug)]` is equivalent to `#[widget_derive(Debug)]`.
+//! ```
+//! use kas::prelude::*;
+//!
+//! #[derive(Default)]
+//! struct Cache(Vec<u8>);
+//!
+//! #[widget_derive(Clone, Debug)]
+//! #[derive(Widget)]
+//! struct Custom {
+//!     #[widget_core] core: CoreData,
+//!     value: i32,
+//!     #[widget_derive(skip)] cache: Cache,
+//! }
+//! # impl Layout for Custom {
+//! #     fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
+//! #         SizeRules::EMPTY
+//! #     }
+//! #     fn draw(&self, _: &mut dyn DrawHandle, _: &event::ManagerState, _: bool) {}
+//! # }
+//!
+//! let custom = Custom {
+//!     core: Default::default(),
+//!     value: 1,
+//!     cache: Cache(vec![0; 64]),
+//! };
+//! let copy = custom.clone();
+//! assert!(copy.cache.0.is_empty());
+//! assert!(format!("{:?}", copy).contains("cache: <omitted>"));
+//! ```
+//!
+//! ### Builder
+//!
+//! With `#[widget(builder)]` on the struct, a builder type `NameBuilder` is
+//! generated, along with a constructor `Name::builder()`. The builder has a
+//! setter (chain style) for each field except the `widget_core` and
+//! `layout_data` fields (which use [`Default::default`]), and a `build`
+//! method. Setters accept `impl Into<T>`, except for boxed closures (e.g.
+//! `Box<dyn Fn(u32)>` or `Rc<dyn Fn(u32)>`), whose setters accept the closure
+//! itself.
+//!
+//! Default values may be specified with `#[widget_derive(default = expr)]` on
+//! any field or with `#[widget(default = expr)]` on child widgets. Other fields
+//! are required: `build` is only available once these are set, thus a missing
+//! field is reported at compile time.
+//! ```
+//! use kas::prelude::*;
+//! use kas::widget::Label;
+//! use std::rc::Rc;
+//!
+//! #[widget(builder)]
+//! #[layout(single)]
+//! #[handler(msg = VoidMsg)]
+//! #[derive(Clone, Debug, Widget)]
+//! struct Labelled {
+//!     #[widget_core] core: CoreData,
+//!     #[layout_data] layout_data: <Self as LayoutData>::Data,
+//!     #[widget(default = Label::new("unnamed"))] label: Label,
+//!     #[widget_derive(default = 1)] count: u32,
+//!     name: String,
+//!     #[widget_skip_debug]
+//!     #[widget_derive(default = Rc::new(|n| n))]
+//!     f: Rc<dyn Fn(u32) -> u32>,
+//! }
+//!
+//! let labelled = Labelled::builder().name("x").f(|n| 2 * n).build();
+//! assert_eq!(labelled.label.full_text(), "unnamed");
+//! assert_eq!((labelled.count, labelled.name.as_str()), (1, "x"));
+//! assert_eq!((labelled.f)(labelled.count), 2);
+//! ```
 //!
 //! ### Layout
 //!
@@ -105,6 +312,8 @@
 //! -   `left` — reversed row
 //! -   `grid` — child widgets are arranged in a grid; position is specified
 //!     via parameters to the `#[widget]` attribute on child fields
+//! -   `flow` — child widgets are arranged left-to-right, wrapping to new rows
+//!     when out of width (see [`FlowSolver`])
 //!
 //! Optionally, a second parameter of form `area=FIELD` is allowed (e.g.
 //! `#[layout(row, area=checkbox)]`). `FIELD` must identify a child widget.
@@ -125,6 +334,43 @@
 //! -   `cspan=1` — number of columns to span
 //! -   `rspan=1` — number of rows to span
 //!
+//! Alternatively, the `grid` layout may specify a number of columns, e.g.
+//! `#[layout(grid, cols = 3)]`. In this case, children specifying neither
+//! `col` nor `row` are placed automatically: each in the next free cell,
+//! filling rows left-to-right, then top-to-bottom (spans are respected and
+//! cells occupied by explicitly positioned children are skipped).
+//!
+//! The `grid` layout also supports minimum gaps between columns and rows via
+//! `gap = N` (both), `col_gap = N` and `row_gap = N` (e.g.
+//! `#[layout(grid, cols = 2, gap = 8)]`), in unscaled pixels. These act as
+//! margins on internal edges of cells (thus where theme margins are larger,
+//! these are used instead).
+//!
+//! Row and column layouts may be nested inline by following the layout type
+//! with a colon and a bracketed list of items, where each item is either a
+//! child field or another `row`, `column` (etc.) node. For example,
+//! `#[layout(column: [row: [a, b], c])]` places `a` and `b` side-by-side above
+//! `c`. Each child must appear exactly once; layout storage for intermediate
+//! rows and columns is generated automatically (at most 12 nodes are
+//! supported). `Option` child fields are not supported in nested layouts.
+//!
+//! ```
+//! use kas::macros::Widget;
+//! use kas::widget::Label;
+//! use kas::{CoreData, LayoutData};
+//!
+//! #[layout(column: [row: [a, b], c])]
+//! #[handler(msg = kas::event::VoidMsg)]
+//! #[derive(Clone, Debug, Widget)]
+//! struct Nested {
+//!     #[widget_core] core: CoreData,
+//!     #[layout_data] layout_data: <Self as LayoutData>::Data,
+//!     #[widget] a: Label,
+//!     #[widget] b: Label,
+//!     #[widget(halign = centre)] c: Label,
+//! }
+//! ```
+//!
 //! Alignment may also be specified for children. The exact behaviour depends
 //! on the child widget, and usually is only relevant when the available space
 //! is greater than the child's ideal size. These parameters are used to
@@ -133,6 +379,40 @@
 //! -   `halign = ...` — one of `default`, `left`, `centre`, `center`, `right`, `stretch`
 //! -   `valign = ...` — one of `default`, `top`, `centre`, `center`, `bottom`, `stretch`
 //!
+//! For `row`, `column` and similar layouts, a stretch weight may be specified
+//! via `stretch = N` (e.g. `#[widget(stretch = 2)]`). Where there is excess
+//! space, this is divided between the children with the highest
+//! [`StretchPolicy`] in proportion to their weights (default 1). See also
+//! [`Filler::stretch`].
+//!
+//! Margins between children are usually provided by the theme. These may be
+//! overridden via `margins = EXPR` where `EXPR` is convertible to
+//! [`Margins`] (e.g. `#[widget(margins = 0)]` for tightly packed panels or
+//! `#[widget(margins = Margins::hv((8, 8), (0, 0)))]`). Sizes are in
+//! unscaled pixels: they are multiplied by [`SizeHandle::scale_factor`].
+//!
+//! The size requirements of a child may be overridden via `min_size = EXPR`,
+//! `ideal_size = EXPR` and `max_size = EXPR`, where `EXPR` is convertible to
+//! [`Size`] (e.g. `#[widget(min_size = (200, 0))]` to reserve width for a
+//! label). Sizes are in unscaled pixels; a zero component leaves that axis
+//! unchanged. These adjust the child's
+//! [`SizeRules`]: see [`SizeRules::set_min_size`],
+//! [`SizeRules::set_ideal_size`] and [`SizeRules::set_max_size`]. The
+//! [`SizeReserve`] widget provides the same functionality as a wrapper.
+//!
+//! The Tab navigation order of children may be adjusted via
+//! `tab_index = N` (children with a tab index are visited first, in ascending
+//! order) and `nav_group = NAME` (children with the same group name, an
+//! identifier or string literal, are visited consecutively), e.g.
+//! `#[widget(tab_index = 1)] ok: TextButton<()>`. See [`NavHint`].
+//!
+//! Children may be hidden at run-time via [`WidgetCore::set_visible`]. The
+//! derived implementations treat a hidden child like an absent `Option`
+//! child: it is given zero size (the margins of its neighbours are combined
+//! as if it were not present) and is neither drawn nor sent events. The
+//! `hidden` argument, e.g. `#[widget(hidden)] details: Label`, makes a child
+//! initially hidden.
+//!
 //! **Layout data storage**
 //!
 //! When deriving [`Layout`], data storage is required (exception: layout
@@ -187,12 +467,18 @@
 //!         apply to type parameters from the struct signature (in this example, `W`)
 //!     -   `SUBS` where `SUBS` is a list of substitutions; e.g. if `M` is a
 //!         type parameter of the struct, then `M => MyMsg` will substitute the
-//!         parameter `M` for concrete type `MyMsg`.
+//!         parameter `M` for concrete type `MyMsg`. Const parameters may be
+//!         substituted by a literal or block, e.g. `N => 3` or `N => { 2 * K }`.
+//!         Substituted parameters are also replaced in remaining bounds and
+//!         `where` clauses.
 //!         (Once [rust#20041](https://github.com/rust-lang/rust/issues/20041) is
 //!         fixed, substitutions will no longer be required.)
 //!     -   `SUBS <TYPE_PARAMS> where CONDS`; e.g. if `M` is a type parameter
 //!         of the struct, one might use `M => <W as Handler>::Msg, <W: Widget>`
 //!
+//! Const generic parameters (e.g. `struct Grid<const N: usize>`) are supported
+//! on the widget struct where the compiler supports them (Rust 1.51 or later).
+//!
 //! Commonly, implementations of the [`Handler`] and [`Layout`] traits require
 //! extra type bounds on the
 //! `impl` which do not appear on the struct, for example a struct may be
@@ -238,6 +524,36 @@
 //! A handler is bound to a child via the `widget` attribute, for example
 //! `#[widget(handler = f)] child: ChildType`.
 //!
+//! Where the conversion is trivial (e.g. wrapping the child's message in an
+//! enum variant), a `map` expression may be used instead of a handler method:
+//! `#[widget(map = Out::Variant)] child: ChildType`. The expression is called
+//! with the child's message and must return the parent's message type; any
+//! function path or closure may be used. (Since a handler and a map both
+//! consume the child's message, at most one of these may be given.) Within
+//! [`make_widget`], a child of unspecified type used with `map` requires a
+//! closure with typed argument, e.g. `map = |msg: Item| Out::Variant(msg)`.
+//!
+//! ```
+//! use kas::prelude::*;
+//! use kas::widget::TextButton;
+//!
+//! #[derive(Clone, Debug, VoidMsg)]
+//! enum Item { A, B }
+//!
+//! #[derive(Clone, Debug, VoidMsg)]
+//! enum Out { Item(Item), Quit }
+//!
+//! #[layout(row)]
+//! #[handler(msg = Out)]
+//! #[derive(Debug, Widget)]
+//! struct Buttons {
+//!     #[widget_core] core: CoreData,
+//!     #[layout_data] layout_data: <Self as LayoutData>::Data,
+//!     #[widget(map = Out::Item)] a: TextButton<Item>,
+//!     #[widget(map = |_| Out::Quit)] quit: TextButton<()>,
+//! }
+//! ```
+//!
 //!
 //! ### Examples
 //!
@@ -274,6 +590,31 @@
 //! ```
 //!
 //!
+//! ### Enums
+//!
+//! `derive(Widget)` may also be used on an enum, each of whose variants wraps
+//! exactly one widget. All widget traits are implemented by dispatching to
+//! the current variant's widget; the enum has no `CoreData` of its own and
+//! does not appear as a separate node of the widget tree. The message type is
+//! that of the first variant; all variants must have the same message type.
+//! Attributes like `#[layout]` and `#[handler]` are not used.
+//!
+//! This is useful for switching between several distinct UIs without boxing.
+//! Since the new variant's widget is not configured, assignment of a new
+//! variant must be followed by a [reconfigure](crate::TkAction::Reconfigure).
+//!
+//! ```
+//! use kas::macros::Widget;
+//! use kas::widget::{Label, TextButton};
+//!
+//! #[derive(Clone, Debug, Widget)]
+//! enum Screen {
+//!     Welcome(Label),
+//!     Continue(TextButton<kas::event::VoidMsg>),
+//! }
+//! ```
+//!
+//!
 //! ## The `derive(VoidMsg)` macro
 //!
 //! This macro implements `From<VoidMsg>` for the given type (see [`VoidMsg`]).
@@ -369,7 +710,8 @@
 //! The structs are both defined with `layout` and `handler` attributes which
 //! are forwarded to the [`derive(Widget)]` macro. Attributes may be applied
 //! like usual, however `#[derive(Clone, Debug, kas::macros::Widget)]`
-//! is implied.
+//! is implied (except that `Clone` and `Debug` are not derived where an
+//! `impl Clone` or `impl Debug` block is given).
 //!
 //! Different from [`derive(Widget)`], one must specify the message type via
 //! either `#[handler(msg = ..)]` or a [`Handler`] implementation. The type does
@@ -407,13 +749,96 @@
 //! #[widget] display: for<W: Widget<Msg = VoidMsg>> Frame<W> =
 //!     Frame::new(Label::new("example")),
 //! ```
+//!
+//! ### Closure handlers
+//!
+//! Within `make_widget!`, a child's handler may be written inline as a closure
+//! following the field's value: `#[widget] _ = child => |w, mgr, msg| ...`.
+//! A handler method is generated from the closure: `w` is bound to
+//! `&mut self`, `mgr` to `&mut Manager` and `msg` to the child's message; the
+//! body must evaluate to `Response<Out>`. Since the closure becomes a method,
+//! it cannot capture local variables.
+//!
+//! The message type is deduced from the child's type where this is explicit;
+//! otherwise the `msg` argument must be typed (`|w, mgr, msg: Item|`). Closure
+//! handlers cannot be combined with `handler` or `map` arguments.
+//!
+//! ```
+//! use kas::class::SetText;
+//! use kas::prelude::*;
+//! use kas::widget::{Label, TextButton};
+//!
+//! let counter = make_widget! {
+//!     #[layout(row)]
+//!     #[handler(msg = VoidMsg)]
+//!     struct {
+//!         #[widget] display: Label = Label::new("0"),
+//!         #[widget] _: TextButton<()> = TextButton::new("+", ()) => |w, mgr, _| {
+//!             w.count += 1;
+//!             *mgr += w.display.set_text(w.count.to_string());
+//!             Response::None
+//!         },
+//!         #[widget] _ = TextButton::new("reset", 0u32) => |w, mgr, msg: u32| {
+//!             w.count = msg;
+//!             *mgr += w.display.set_text(w.count.to_string());
+//!             Response::None
+//!         },
+//!         count: u32 = 0,
+//!     }
+//! };
+//! assert_eq!(counter.count, 0);
+//! ```
+//!
+//! ### Named widgets
+//!
+//! The struct may be given a name (and optionally a visibility), in which case
+//! `make_widget!` is an *item* macro: it defines the named struct (with the
+//! given `impl` blocks) and a constructor, `new`, using the field values from
+//! the definition. The struct may then be used like any other type within its
+//! module. Since the values are evaluated within `new`, they may not refer to
+//! local variables; also all fields must have explicit (non-generic) types.
+//!
+//! ```
+//! use kas::class::SetText;
+//! use kas::prelude::*;
+//! use kas::widget::{Label, TextButton};
+//!
+//! make_widget! {
+//!     #[layout(row)]
+//!     #[handler(msg = VoidMsg)]
+//!     pub struct Counter {
+//!         #[widget] display: Label = Label::new("0"),
+//!         #[widget(handler = increment)] _: TextButton<()> = TextButton::new("+", ()),
+//!         count: u32 = 0,
+//!     }
+//!     impl {
+//!         fn increment(&mut self, mgr: &mut Manager, _: ()) -> Response<VoidMsg> {
+//!             self.count += 1;
+//!             *mgr += self.display.set_text(self.count.to_string());
+//!             Response::None
+//!         }
+//!     }
+//!     impl std::fmt::Display {
+//!         fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+//!             write!(f, "Counter({})", self.count)
+//!         }
+//!     }
+//! }
+//!
+//! let counter = Counter::new();
+//! assert_eq!(counter.to_string(), "Counter(0)");
+//! ```
 
 // Imported for doc-links
 #[allow(unused)]
 use crate::{
+    draw::SizeHandle,
     event::{Handler, SendEvent},
-    AlignHints, CoreData, Layout, LayoutData, Widget, WidgetChildren, WidgetConfig, WidgetCore,
-    WidgetId,
+    geom::Size,
+    layout::{FlowSolver, Margins, SizeRules, StretchPolicy},
+    widget::{Filler, SizeReserve},
+    AlignHints, CoreData, Layout, LayoutData, NavHint, Widget, WidgetChildren, WidgetConfig,
+    WidgetCore, WidgetId,
 };
 
 pub use kas_macros::{make_widget, VoidMsg, Widget};
@@ -0,0 +1,252 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Widget plugins
+//!
+//! A [`WidgetRegistry`] maps names to widget constructors, allowing widgets to
+//! be instantiated by name at run-time (e.g. from a configuration file).
+//!
+//! With feature `plugin`, widget libraries may be loaded from dynamic
+//! libraries via [`WidgetRegistry::load`]. A plugin library is a crate with
+//! `crate-type = ["dylib"]` which declares itself via [`export_plugin`]:
+//! ```
+//! use kas::plugin::WidgetRegistry;
+//! use kas::widget::Label;
+//!
+//! fn register(registry: &mut WidgetRegistry) {
+//!     registry.register("hello", || Box::new(Label::new("Hello from a plugin")));
+//! }
+//!
+//! kas::export_plugin!(register);
+//! ```
+//!
+//! Widgets are passed across the library boundary as Rust trait objects, which
+//! do not have a stable ABI. Plugins must therefore be built with the same
+//! compiler version and the same version of KAS as the host application.
+//! Plugins declare the KAS version and [`PLUGIN_API_VERSION`] they were built
+//! with; loading fails if either does not match.
+//!
+//! ### Sharing KAS
+//!
+//! The plugin and application must use a single copy of KAS and of `std`.
+//! KAS has global state (e.g. the counter issuing [`UpdateHandle`]s, the font
+//! database and the keyboard layout) which a second copy would duplicate,
+//! and widgets are allocated and dropped on both sides of the boundary. A
+//! `cdylib` or a statically linked `dylib` includes its own copies, thus is
+//! not usable as a plugin. Instead:
+//!
+//! -   add a crate with `crate-type = ["dylib"]` which re-exports KAS
+//!     (`pub use kas;`), and depend on this (not on `kas`) from both the
+//!     application and plugins
+//! -   build the application and plugins with `RUSTFLAGS="-C prefer-dynamic"`
+//!     (linking `std` dynamically), using the same compiler and the same
+//!     build of the above crate
+//!
+//! [`WidgetRegistry::load`] fails with [`PluginError::Unshared`] if the plugin
+//! does not use the application's copy of KAS.
+//!
+//! [`UpdateHandle`]: crate::event::UpdateHandle
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::event::VoidMsg;
+use crate::Widget;
+
+/// Type of widgets constructed by a [`WidgetRegistry`]
+///
+/// Widgets from plugins cannot send messages to their parent (since the host
+/// cannot know the message type); communication should instead use
+/// [`crate::event::UpdateHandle`] or similar.
+pub type BoxedWidget = Box<dyn Widget<Msg = VoidMsg>>;
+
+/// Version of the plugin interface
+///
+/// This is increased whenever [`PluginDeclaration`] changes.
+pub const PLUGIN_API_VERSION: u32 = 2;
+
+/// A static whose address identifies a copy of KAS
+#[doc(hidden)]
+pub static KAS_INSTANCE: u8 = 0;
+
+/// Plugin declaration
+///
+/// Plugins export a `static` instance of this type named
+/// `KAS_PLUGIN_DECLARATION`; use [`export_plugin`] to construct it.
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct PluginDeclaration {
+    /// The [`PLUGIN_API_VERSION`] used by the plugin
+    pub api_version: u32,
+    /// The version of KAS used by the plugin
+    pub kas_version: &'static str,
+    /// Identifies the copy of KAS linked by the plugin
+    ///
+    /// This must be the application's copy; see [module docs](self).
+    pub kas_instance: &'static u8,
+    /// Register the plugin's widgets
+    pub register: fn(&mut WidgetRegistry),
+}
+
+/// Declare a widget plugin
+///
+/// This macro takes a path to a function of type `fn(&mut WidgetRegistry)`,
+/// registering all widgets of the plugin, and exports a
+/// [`PluginDeclaration`] for use by [`WidgetRegistry::load`].
+///
+/// [`PluginDeclaration`]: crate::plugin::PluginDeclaration
+/// [`WidgetRegistry::load`]: crate::plugin::WidgetRegistry::load
+#[macro_export]
+macro_rules! export_plugin {
+    ($register:path) => {
+        #[doc(hidden)]
+        #[no_mangle]
+        pub static KAS_PLUGIN_DECLARATION: $crate::plugin::PluginDeclaration =
+            $crate::plugin::PluginDeclaration {
+                api_version: $crate::plugin::PLUGIN_API_VERSION,
+                kas_version: $crate::plugin::KAS_VERSION,
+                kas_instance: &$crate::plugin::KAS_INSTANCE,
+                register: $register,
+            };
+    };
+}
+
+#[doc(hidden)]
+pub const KAS_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Plugin loading errors
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum PluginError {
+    /// Failed to load the library or find its declaration
+    Load(String),
+    /// The plugin was built with an incompatible version of KAS
+    Version {
+        /// [`PLUGIN_API_VERSION`] of the plugin
+        api_version: u32,
+        /// KAS version of the plugin
+        kas_version: String,
+    },
+    /// The plugin links its own copy of KAS (see [module docs](self))
+    Unshared,
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            PluginError::Load(msg) => write!(f, "failed to load plugin: {}", msg),
+            PluginError::Version {
+                api_version,
+                kas_version,
+            } => write!(
+                f,
+                "incompatible plugin: API version {} (expected {}), KAS {} (expected {})",
+                api_version, PLUGIN_API_VERSION, kas_version, KAS_VERSION
+            ),
+            PluginError::Unshared => {
+                write!(f, "plugin does not share the application's copy of KAS")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+/// A registry of named widget constructors
+#[derive(Default)]
+pub struct WidgetRegistry {
+    factories: HashMap<String, Box<dyn Fn() -> BoxedWidget>>,
+}
+
+impl fmt::Debug for WidgetRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_set().entries(self.factories.keys()).finish()
+    }
+}
+
+impl WidgetRegistry {
+    /// Construct an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a widget constructor under `name`
+    ///
+    /// Replaces any constructor previously registered under `name`.
+    pub fn register<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn() -> BoxedWidget + 'static,
+    {
+        self.factories.insert(name.to_string(), Box::new(factory));
+    }
+
+    /// True if a constructor is registered under `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    /// Iterate over names of registered constructors (in arbitrary order)
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(|name| name.as_str())
+    }
+
+    /// Construct a widget by name
+    ///
+    /// The widget must be configured before use, e.g. by adding it to a
+    /// container and triggering [`crate::TkAction::Reconfigure`].
+    pub fn create(&self, name: &str) -> Option<BoxedWidget> {
+        self.factories.get(name).map(|factory| factory())
+    }
+
+    /// Load a plugin from the dynamic library at `path`
+    ///
+    /// All widgets declared by the plugin are registered. Loaded libraries are
+    /// never unloaded (since widgets constructed by the plugin may exist
+    /// anywhere in the application).
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initialisation code. The library must have
+    /// been built with the same compiler and KAS version as the application
+    /// and must share its copies of KAS and `std` (see [module docs](self)).
+    /// Only the KAS version and the sharing of KAS are checked.
+    #[cfg(feature = "plugin")]
+    pub unsafe fn load<P: AsRef<std::ffi::OsStr>>(&mut self, path: P) -> Result<(), PluginError> {
+        let lib = libloading::Library::new(path).map_err(|e| PluginError::Load(e.to_string()))?;
+        let decl = *lib
+            .get::<*const PluginDeclaration>(b"KAS_PLUGIN_DECLARATION\0")
+            .map_err(|e| PluginError::Load(e.to_string()))?;
+
+        // Check the API version before reading fields which may have changed
+        let api_version = (*decl).api_version;
+        if api_version != PLUGIN_API_VERSION || (*decl).kas_version != KAS_VERSION {
+            return Err(PluginError::Version {
+                api_version,
+                kas_version: (*decl).kas_version.to_string(),
+            });
+        }
+        let decl = *decl;
+        if !std::ptr::eq(decl.kas_instance, &KAS_INSTANCE) {
+            return Err(PluginError::Unshared);
+        }
+
+        // Code of the library may be referenced by any widget it constructs
+        std::mem::forget(lib);
+        (decl.register)(self);
+        Ok(())
+    }
+}
+
+#[test]
+fn registry() {
+    use crate::widget::Filler;
+
+    let mut registry = WidgetRegistry::new();
+    registry.register("filler", || Box::new(Filler::new()));
+    assert!(registry.contains("filler"));
+    assert_eq!(registry.names().collect::<Vec<_>>(), vec!["filler"]);
+    assert!(registry.create("filler").is_some());
+    assert!(registry.create("label").is_none());
+}
@@ -0,0 +1,295 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Scripted widgets (feature `script`)
+//!
+//! A [`ScriptWidget`] is defined by a [Rhai](https://schungx.github.io/rhai/)
+//! script, intended for rapid prototyping and user-extensible applications.
+//! The script follows a simple model-view-update design:
+//!
+//! -   `fn init()` (optional) returns the initial state, usually an object map
+//!     (default: an empty map)
+//! -   `fn view(state)` returns a widget tree for the given state
+//! -   `fn update(state, msg)` (optional) handles a message (a string) and
+//!     returns the new state, after which the view is rebuilt
+//!
+//! The following functions are available to scripts:
+//!
+//! -   `label(text)`: a text label
+//! -   `button(text, msg)`: a button sending `msg` when pressed
+//! -   `row([...])`, `column([...])`: a row or column of widgets; strings
+//!     within the array are converted to labels
+//! -   `timer(ms)`: request a call to `update(state, "timer")` after `ms`
+//!     milliseconds
+//!
+//! Widget properties are thus set via the state. Since the whole view is
+//! rebuilt on each update, this is not suitable for large widget trees.
+//!
+//! ```
+//! use kas::script::ScriptWidget;
+//!
+//! let counter = ScriptWidget::new(r#"
+//!     fn init() { #{ count: 0 } }
+//!     fn view(state) {
+//!         row([label("Count: " + state.count), button("+1", "incr")])
+//!     }
+//!     fn update(state, msg) {
+//!         if msg == "incr" { state.count += 1; }
+//!         state
+//!     }
+//! "#).unwrap();
+//! ```
+
+use std::cell::Cell;
+use std::fmt::{self, Debug};
+use std::rc::Rc;
+use std::time::Duration;
+
+use log::warn;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, ImmutableString, Map, ParseError, Scope, AST};
+
+use kas::prelude::*;
+use kas::widget::{BoxColumn, BoxRow, Label, TextButton};
+
+/// Script errors
+#[derive(Debug)]
+pub struct ScriptError(String);
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "script error: {}", self.0)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+impl From<ParseError> for ScriptError {
+    fn from(e: ParseError) -> Self {
+        ScriptError(e.to_string())
+    }
+}
+
+impl From<Box<EvalAltResult>> for ScriptError {
+    fn from(e: Box<EvalAltResult>) -> Self {
+        ScriptError(e.to_string())
+    }
+}
+
+/// Description of a widget tree, as constructed by a script
+#[derive(Clone, Debug)]
+enum WidgetDesc {
+    Label(String),
+    Button(String, String),
+    Row(Vec<WidgetDesc>),
+    Column(Vec<WidgetDesc>),
+}
+
+impl WidgetDesc {
+    fn list(items: Array) -> Vec<WidgetDesc> {
+        let mut list = Vec::with_capacity(items.len());
+        for item in items {
+            if item.is::<ImmutableString>() {
+                let text = item.cast::<ImmutableString>();
+                list.push(WidgetDesc::Label(text.to_string()));
+            } else if let Some(desc) = item.try_cast::<WidgetDesc>() {
+                list.push(desc);
+            } else {
+                warn!("ScriptWidget: ignoring non-widget list item");
+            }
+        }
+        list
+    }
+
+    fn build(self) -> Box<dyn Widget<Msg = String>> {
+        match self {
+            WidgetDesc::Label(text) => Box::new(ScriptLabel {
+                core: Default::default(),
+                label: Label::new(text),
+            }),
+            WidgetDesc::Button(text, msg) => Box::new(TextButton::new(text, msg)),
+            WidgetDesc::Row(list) => Box::new(BoxRow::new(
+                list.into_iter().map(|desc| desc.build()).collect(),
+            )),
+            WidgetDesc::Column(list) => Box::new(BoxColumn::new(
+                list.into_iter().map(|desc| desc.build()).collect(),
+            )),
+        }
+    }
+}
+
+/// A [`Label`] with message type `String`
+#[layout(single)]
+#[handler(msg = String)]
+#[derive(Clone, Debug, Widget)]
+struct ScriptLabel {
+    #[widget_core]
+    core: CoreData,
+    #[widget]
+    label: Label,
+}
+
+struct Script {
+    engine: Engine,
+    ast: AST,
+    state: Dynamic,
+    timer: Rc<Cell<Option<Duration>>>,
+}
+
+impl Debug for Script {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Script {{ state: {:?}, .. }}", self.state)
+    }
+}
+
+impl Script {
+    fn new(source: &str) -> Result<Self, ScriptError> {
+        let mut engine = Engine::new();
+        engine.register_type_with_name::<WidgetDesc>("Widget");
+        engine.register_fn("label", |text: ImmutableString| {
+            WidgetDesc::Label(text.to_string())
+        });
+        engine.register_fn("button", |text: ImmutableString, msg: ImmutableString| {
+            WidgetDesc::Button(text.to_string(), msg.to_string())
+        });
+        engine.register_fn("row", |items: Array| {
+            WidgetDesc::Row(WidgetDesc::list(items))
+        });
+        engine.register_fn("column", |items: Array| {
+            WidgetDesc::Column(WidgetDesc::list(items))
+        });
+        let timer = Rc::new(Cell::new(None));
+        let timer2 = timer.clone();
+        engine.register_fn("timer", move |ms: rhai::INT| {
+            timer2.set(Some(Duration::from_millis(ms.max(0) as u64)));
+        });
+
+        let ast = engine.compile(source)?;
+        let mut script = Script {
+            engine,
+            ast,
+            state: Dynamic::from(Map::new()),
+            timer,
+        };
+        if let Some(state) = script.call_optional("init", ())? {
+            script.state = state;
+        }
+        Ok(script)
+    }
+
+    /// Call a function, returning `None` if it does not exist
+    fn call_optional<A: rhai::FuncArgs>(
+        &mut self,
+        name: &str,
+        args: A,
+    ) -> Result<Option<Dynamic>, ScriptError> {
+        let mut scope = Scope::new();
+        match self.engine.call_fn(&mut scope, &self.ast, name, args) {
+            Ok(result) => Ok(Some(result)),
+            Err(e) => match *e {
+                EvalAltResult::ErrorFunctionNotFound(ref f, _) if f.starts_with(name) => Ok(None),
+                _ => Err(e.into()),
+            },
+        }
+    }
+
+    fn view(&mut self) -> Result<Box<dyn Widget<Msg = String>>, ScriptError> {
+        let mut scope = Scope::new();
+        let state = self.state.clone();
+        let desc: WidgetDesc = self
+            .engine
+            .call_fn(&mut scope, &self.ast, "view", (state,))?;
+        Ok(desc.build())
+    }
+}
+
+/// A widget defined by a script
+///
+/// See the [module documentation](self).
+#[layout(single)]
+#[handler(handle=noauto)]
+#[widget(config=noauto)]
+#[derive(Debug, Widget)]
+pub struct ScriptWidget {
+    #[widget_core]
+    core: CoreData,
+    #[widget(handler = on_msg)]
+    child: Box<dyn Widget<Msg = String>>,
+    script: Script,
+}
+
+impl ScriptWidget {
+    /// Construct from the source of a Rhai script
+    ///
+    /// Fails if the script does not compile or if `init` or `view` fail.
+    pub fn new(source: &str) -> Result<Self, ScriptError> {
+        let mut script = Script::new(source)?;
+        let child = script.view()?;
+        Ok(ScriptWidget {
+            core: Default::default(),
+            child,
+            script,
+        })
+    }
+
+    /// Access the script's state
+    pub fn state(&self) -> &Dynamic {
+        &self.script.state
+    }
+
+    fn schedule_timer(&mut self, mgr: &mut Manager) {
+        if let Some(duration) = self.script.timer.take() {
+            mgr.update_on_timer(duration, self.id());
+        }
+    }
+
+    fn update(&mut self, mgr: &mut Manager, msg: String) {
+        let state = self.script.state.clone();
+        let result = self
+            .script
+            .call_optional("update", (state, msg))
+            .and_then(|state| {
+                if let Some(state) = state {
+                    self.script.state = state;
+                }
+                self.script.view()
+            });
+        match result {
+            Ok(child) => {
+                self.child = child;
+                // The timer is scheduled when reconfigured
+                *mgr += TkAction::Reconfigure;
+            }
+            Err(e) => {
+                warn!("ScriptWidget: {}", e);
+                self.schedule_timer(mgr);
+            }
+        }
+    }
+
+    fn on_msg(&mut self, mgr: &mut Manager, msg: String) -> Response<VoidMsg> {
+        self.update(mgr, msg);
+        Response::None
+    }
+}
+
+impl WidgetConfig for ScriptWidget {
+    fn configure(&mut self, mgr: &mut Manager) {
+        self.schedule_timer(mgr);
+    }
+}
+
+impl Handler for ScriptWidget {
+    type Msg = VoidMsg;
+
+    fn handle(&mut self, mgr: &mut Manager, event: Event) -> Response<Self::Msg> {
+        match event {
+            Event::TimerUpdate => {
+                self.update(mgr, "timer".to_string());
+                Response::None
+            }
+            event => Response::Unhandled(event),
+        }
+    }
+}
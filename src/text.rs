@@ -5,6 +5,13 @@
 
 //! Abstractions over `kas-text`
 
+use std::cell::RefCell;
+use std::error::Error;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use ab_glyph::FontRef;
+use kas::geom::{Quad, Vec2};
 use kas::TkAction;
 pub use kas_text::*;
 
@@ -17,13 +24,111 @@ pub use prepared::Text as PreparedText;
 #[doc(no_inline)]
 pub use prepared::Prepare as PrepareAction;
 
+thread_local! {
+    static FALLBACK_FONTS: RefCell<Vec<(PathBuf, u32, FallbackFont)>> = RefCell::new(vec![]);
+}
+
+/// A font used to draw glyphs missing from the default font
+///
+/// `kas-text` shapes all text with its default font and is unable to load
+/// other fonts. Fallback fonts are instead loaded here and only used when
+/// drawing (see [`kas::draw::DrawText::set_fallback_fonts`]), thus text is
+/// still laid out using the metrics of the default font.
+///
+/// Fonts are loaded per thread and never freed.
+#[derive(Clone, Copy, Debug)]
+pub struct FallbackFont {
+    id: usize,
+    font: &'static FontRef<'static>,
+}
+
+impl PartialEq for FallbackFont {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl FallbackFont {
+    /// Load face `index` of the font file at `path`
+    ///
+    /// If this face was loaded previously, the same font is returned.
+    pub fn load(path: &Path, index: u32) -> Result<Self, Box<dyn Error>> {
+        FALLBACK_FONTS.with(|fonts| {
+            let mut fonts = fonts.borrow_mut();
+            if let Some(item) = fonts.iter().find(|f| f.0 == path && f.1 == index) {
+                return Ok(item.2);
+            }
+            let data = Box::leak(std::fs::read(path)?.into_boxed_slice());
+            let font = FontRef::try_from_slice_and_index(data, index)?;
+            let font = FallbackFont {
+                id: fonts.len(),
+                font: Box::leak(Box::new(font)),
+            };
+            fonts.push((path.to_path_buf(), index, font));
+            Ok(font)
+        })
+    }
+
+    /// Get the font with the given `id`, if loaded
+    pub fn get(id: usize) -> Option<Self> {
+        FALLBACK_FONTS.with(|fonts| fonts.borrow().get(id).map(|f| f.2))
+    }
+
+    /// Identifier
+    ///
+    /// Fonts are numbered sequentially from 0 in order of loading.
+    #[inline]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Access the font face
+    #[inline]
+    pub fn font(&self) -> &'static FontRef<'static> {
+        self.font
+    }
+}
+
 /// Extension trait over [`prepared::Text`]
+///
+/// Geometry methods require that the text has been prepared (see
+/// [`kas::draw::SizeHandle::text_bound`] and
+/// [`kas::draw::SizeHandle::text_measure`]). Positions are relative to the
+/// text's origin, i.e. before any draw position or scroll offset is applied.
 pub trait PreparedTextExt {
     /// Set the text
     ///
     /// This calls [`PreparedText::prepare`] internally, then returns
     /// [`TkAction::Redraw`]. (This does not force a resize.)
     fn set_and_prepare<T: Into<RichText>>(&mut self, text: T) -> TkAction;
+
+    /// Get the position of the caret at byte `index`
+    ///
+    /// This is the point on the baseline before the glyph at `index`.
+    /// Returns `None` if `index` is not a valid position.
+    fn index_pos(&self, index: usize) -> Option<Vec2>;
+
+    /// Find the byte index nearest to `pos`
+    ///
+    /// This may be used for hit-testing; the result is always on a glyph
+    /// cluster boundary.
+    fn pos_index(&self, pos: Vec2) -> usize;
+
+    /// Get the area of a caret of the given `width` at byte `index`
+    ///
+    /// The caret spans the line's ascent and descent.
+    fn caret_quad(&self, index: usize, width: f32) -> Option<Quad>;
+
+    /// Get the areas covered by the byte `range`, one per line
+    ///
+    /// These are the areas drawn by [`kas::draw::DrawHandle::text_selected`].
+    fn range_quads(&self, range: Range<usize>) -> Vec<Quad>;
+
+    /// Get the byte index at which each line starts
+    ///
+    /// The first line always starts at index 0. Lines result from both
+    /// explicit line breaks and wrapping.
+    fn line_starts(&self) -> Vec<usize>;
 }
 
 impl PreparedTextExt for PreparedText {
@@ -35,4 +140,52 @@ impl PreparedTextExt for PreparedText {
             TkAction::None
         }
     }
+
+    fn index_pos(&self, index: usize) -> Option<Vec2> {
+        self.text_glyph_pos(index)
+            .next_back()
+            .map(|marker| marker.pos.into())
+    }
+
+    fn pos_index(&self, pos: Vec2) -> usize {
+        self.text_index_nearest(pos.into())
+    }
+
+    fn caret_quad(&self, index: usize, width: f32) -> Option<Quad> {
+        self.text_glyph_pos(index).next_back().map(|marker| {
+            let (x, y) = (marker.pos.0, marker.pos.1);
+            let a = Vec2(x, y - marker.ascent);
+            let b = Vec2(x + width, y - marker.descent);
+            Quad::with_coords(a, b)
+        })
+    }
+
+    fn range_quads(&self, range: Range<usize>) -> Vec<Quad> {
+        self.highlight_lines(range)
+            .iter()
+            .map(|(p1, p2)| Quad::with_coords((*p1).into(), (*p2).into()))
+            .collect()
+    }
+
+    fn line_starts(&self) -> Vec<usize> {
+        // Nearest indices to both ends of the line, thus also handling RTL
+        let req = Vec2::from(self.required_size());
+        let bound = self.env().bounds.0;
+        let x_max = if bound.is_finite() {
+            bound.max(req.0)
+        } else {
+            req.0
+        };
+        let mut starts = vec![];
+        let mut line = 0;
+        while let Some(a) = self.line_index_nearest(line, 0.0) {
+            let b = self.line_index_nearest(line, x_max).unwrap_or(a);
+            starts.push(if line == 0 { 0 } else { a.min(b) });
+            line += 1;
+        }
+        if starts.is_empty() {
+            starts.push(0);
+        }
+        starts
+    }
 }
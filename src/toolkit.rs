@@ -16,8 +16,9 @@
 
 use std::num::NonZeroU32;
 
-use crate::draw::SizeHandle;
-use crate::{event, ThemeAction, ThemeApi};
+use crate::draw::{IconError, IconImage, SizeHandle};
+use crate::geom::{Coord, Size};
+use crate::{event, Instant, ThemeAction, ThemeApi};
 
 /// Identifier for a window or pop-up
 ///
@@ -35,6 +36,36 @@ impl WindowId {
     }
 }
 
+/// An image captured from a window
+///
+/// See [`TkWindow::capture`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capture {
+    /// Size of the image, in physical pixels
+    pub size: Size,
+    /// Image data: 8-bit sRGB RGBA, row-major without padding
+    pub rgba: Vec<u8>,
+}
+
+impl Capture {
+    /// Get the colour of the pixel at `(x, y)` as `[r, g, b, a]`
+    ///
+    /// Returns `None` if the coordinate is out of bounds.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.size.0 || y >= self.size.1 {
+            return None;
+        }
+        let i = 4 * (y as usize * self.size.0 as usize + x as usize);
+        let p = &self.rgba[i..i + 4];
+        Some([p[0], p[1], p[2], p[3]])
+    }
+
+    /// Convert to an image, e.g. for display as a thumbnail
+    pub fn into_image(self) -> Result<IconImage, IconError> {
+        IconImage::from_rgba(self.size, self.rgba)
+    }
+}
+
 /// Action required after processing
 ///
 /// This type is returned by many widgets on modification to self and is tracked
@@ -74,6 +105,15 @@ pub enum TkAction {
     Popup,
     /// Reset size of all widgets without recalculating requirements
     SetSize,
+    /// Re-solve layout of widgets marked via [`Manager::relayout`] or
+    /// [`LayoutCache::set_dirty`]
+    ///
+    /// Only requirements of these widgets and their ancestors are
+    /// recalculated; other widgets use cached rules.
+    ///
+    /// [`Manager::relayout`]: crate::event::Manager::relayout
+    /// [`LayoutCache::set_dirty`]: crate::layout::LayoutCache::set_dirty
+    Relayout,
     /// Resize all widgets
     Resize,
     /// Whole window requires reconfiguring
@@ -147,6 +187,55 @@ pub trait TkWindow {
     /// Attempt to set clipboard contents
     fn set_clipboard<'c>(&mut self, content: std::borrow::Cow<'c, str>);
 
+    /// Attempt to get contents of the primary selection
+    ///
+    /// The primary selection is a second clipboard channel, supported on some
+    /// platforms (X11), usually set on text selection and pasted via
+    /// middle-click. Where not supported this should return `None`.
+    fn get_primary(&mut self) -> Option<String>;
+
+    /// Attempt to set contents of the primary selection
+    ///
+    /// Where not supported this should do nothing.
+    fn set_primary<'c>(&mut self, content: std::borrow::Cow<'c, str>);
+
+    /// Set progress shown on the window's taskbar entry
+    ///
+    /// `progress` is in the range `0.0..=1.0` or `None` to remove the
+    /// indicator. Where not supported this should do nothing.
+    fn set_taskbar_progress(&mut self, progress: Option<f32>);
+
+    /// Set a badge count on the application's taskbar entry
+    ///
+    /// Where not supported this should do nothing.
+    fn set_taskbar_badge(&mut self, count: Option<u32>);
+
+    /// Request the user's attention (e.g. by flashing the taskbar entry)
+    ///
+    /// Where not supported this should do nothing.
+    fn request_attention(&mut self, critical: bool);
+
+    /// Inhibit (or stop inhibiting) the system screensaver
+    ///
+    /// Where not supported this should do nothing.
+    fn inhibit_screensaver(&mut self, inhibit: bool);
+
+    /// Capture the window's content
+    ///
+    /// The toolkit should render the window's next frame (also) to an
+    /// off-screen target, read it back and pass the result to `f`. Where not
+    /// supported, `f` should not be called.
+    fn capture(&mut self, f: Box<dyn FnOnce(Capture)>);
+
+    /// Get the application-level UI scale factor
+    fn ui_scale(&self) -> f32;
+
+    /// Set the application-level UI scale factor
+    ///
+    /// This multiplies the monitor's DPI factor. On change, all windows should
+    /// be resized.
+    fn set_ui_scale(&mut self, scale: f32);
+
     /// Adjust the theme
     fn adjust_theme(&mut self, f: &mut dyn FnMut(&mut dyn ThemeApi) -> ThemeAction);
 
@@ -159,6 +248,43 @@ pub trait TkWindow {
 
     /// Set the mouse cursor
     fn set_cursor_icon(&mut self, icon: event::CursorIcon);
+
+    /// Grab (confine) the mouse cursor to the window, or release the grab
+    ///
+    /// Returns false on failure (e.g. when not supported by the platform).
+    fn set_cursor_grab(&mut self, grab: bool) -> bool;
+
+    /// Set whether the mouse cursor is visible
+    fn set_cursor_visible(&mut self, visible: bool);
+
+    /// Move the mouse cursor to `coord` (in window coordinates)
+    ///
+    /// This may fail silently where not supported by the platform.
+    fn set_cursor_position(&mut self, coord: Coord);
+
+    /// Start a drag carrying `data`
+    ///
+    /// The toolkit should track the cursor across all windows of the
+    /// application, calling `Manager::drag_over` on the window under the
+    /// cursor, until [`TkWindow::end_drag`] is called.
+    fn start_drag(&mut self, data: event::DragData);
+
+    /// End the current drag, if any
+    ///
+    /// If `drop`, the toolkit should call `Manager::drag_drop` on the window
+    /// under the cursor; in any case it should then call `Manager::drag_end`
+    /// on the window which started the drag.
+    fn end_drag(&mut self, drop: bool);
+
+    /// Get the current time
+    ///
+    /// All timers (e.g. [`event::Manager::update_on_timer`]) are measured
+    /// against this. A toolkit may simulate the passing of time (e.g. for
+    /// testing) by returning a later time than [`Instant::now`].
+    #[inline]
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
 }
 
 #[cfg(test)]
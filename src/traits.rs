@@ -9,9 +9,9 @@ use std::any::Any;
 use std::fmt;
 use std::ops::DerefMut;
 
-use crate::draw::SizeHandle;
+use crate::draw::{ColourScheme, SizeHandle};
 use crate::event::{self, Manager};
-use crate::{layout, Direction, WidgetId, WindowId};
+use crate::{layout, Direction, Instant, WidgetId, WindowId};
 
 mod impls;
 mod utils;
@@ -110,6 +110,16 @@ pub trait Window: Widget<Msg = event::VoidMsg> {
     ///
     /// This allows for actions on destruction, but doesn't need to do anything.
     fn handle_closure(&mut self, _mgr: &mut Manager) {}
+
+    /// Handle presentation of a frame
+    ///
+    /// This is called once after each frame is presented, with the time of
+    /// presentation (as near as the toolkit can determine). This may be used
+    /// to synchronise external systems with what is displayed.
+    ///
+    /// Frames are only drawn on demand; to continue receiving this call, a
+    /// [redraw action](Manager::send_action) must be requested.
+    fn handle_frame(&mut self, _mgr: &mut Manager, _time: Instant) {}
 }
 
 /// Return value of [`ThemeApi`] functions
@@ -146,6 +156,15 @@ pub trait ThemeApi {
     // TODO: revise scheme identification and error handling?
     fn set_colours(&mut self, _scheme: &str) -> ThemeAction;
 
+    /// Change the colour scheme to a custom palette
+    ///
+    /// Like [`ThemeApi::set_colours`], this takes precedence over the
+    /// system's dark-mode preference. Themes without configurable colours
+    /// ignore this.
+    fn set_colour_scheme(&mut self, _scheme: &ColourScheme) -> ThemeAction {
+        ThemeAction::None
+    }
+
     /// Switch the theme
     ///
     /// Most themes do not react to this method; `kas_theme::MultiTheme` uses
@@ -153,6 +172,16 @@ pub trait ThemeApi {
     fn set_theme(&mut self, _theme: &str) -> ThemeAction {
         ThemeAction::None
     }
+
+    /// Notify of the system's dark-mode preference
+    ///
+    /// Toolkits call this on start-up (where the preference is known) and
+    /// when the preference changes. Themes may react by switching to a
+    /// matching colour scheme; a scheme chosen explicitly via
+    /// [`ThemeApi::set_colours`] should take precedence.
+    fn set_dark_mode(&mut self, _dark: bool) -> ThemeAction {
+        ThemeAction::None
+    }
 }
 
 impl<T: ThemeApi> ThemeApi for Box<T> {
@@ -162,7 +191,13 @@ impl<T: ThemeApi> ThemeApi for Box<T> {
     fn set_colours(&mut self, scheme: &str) -> ThemeAction {
         self.deref_mut().set_colours(scheme)
     }
+    fn set_colour_scheme(&mut self, scheme: &ColourScheme) -> ThemeAction {
+        self.deref_mut().set_colour_scheme(scheme)
+    }
     fn set_theme(&mut self, theme: &str) -> ThemeAction {
         self.deref_mut().set_theme(theme)
     }
+    fn set_dark_mode(&mut self, dark: bool) -> ThemeAction {
+        self.deref_mut().set_dark_mode(dark)
+    }
 }
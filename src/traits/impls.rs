@@ -7,10 +7,10 @@
 
 use super::*;
 use crate::draw::{DrawHandle, SizeHandle};
-use crate::event::{self, Event, Manager, Response};
+use crate::event::{self, ConfigureManager, Event, Manager, Response};
 use crate::geom::{Coord, Rect};
 use crate::layout::{AxisInfo, SizeRules};
-use crate::{AlignHints, CoreData, WidgetId};
+use crate::{AlignHints, CoreData, TkAction, WidgetId};
 
 impl<M: 'static> WidgetCore for Box<dyn Widget<Msg = M>> {
     fn as_any(&self) -> &dyn Any {
@@ -49,6 +49,12 @@ impl<M: 'static> WidgetChildren for Box<dyn Widget<Msg = M>> {
     fn get_mut(&mut self, index: usize) -> Option<&mut dyn WidgetConfig> {
         self.as_mut().get_mut(index)
     }
+    fn nav_hint(&self, index: usize) -> NavHint {
+        self.as_ref().nav_hint(index)
+    }
+    fn initially_hidden(&self, index: usize) -> bool {
+        self.as_ref().initially_hidden(index)
+    }
 
     fn find(&self, id: WidgetId) -> Option<&dyn WidgetConfig> {
         self.as_ref().find(id)
@@ -70,12 +76,19 @@ impl<M: 'static> WidgetConfig for Box<dyn Widget<Msg = M>> {
         self.as_mut().configure(mgr);
     }
 
+    fn configure_recurse<'a, 'b>(&mut self, cmgr: ConfigureManager<'a, 'b>) {
+        self.as_mut().configure_recurse(cmgr);
+    }
+
     fn key_nav(&self) -> bool {
         self.as_ref().key_nav()
     }
     fn cursor_icon(&self) -> event::CursorIcon {
         self.as_ref().cursor_icon()
     }
+    fn debug_label(&self) -> Option<&str> {
+        self.as_ref().debug_label()
+    }
 }
 
 impl<M: 'static> Layout for Box<dyn Widget<Msg = M>> {
@@ -87,6 +100,10 @@ impl<M: 'static> Layout for Box<dyn Widget<Msg = M>> {
         self.as_mut().set_rect(rect, align);
     }
 
+    fn set_viewport(&mut self, viewport: Rect) -> TkAction {
+        self.as_mut().set_viewport(viewport)
+    }
+
     fn find_id(&self, coord: Coord) -> Option<WidgetId> {
         self.as_ref().find_id(coord)
     }
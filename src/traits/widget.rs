@@ -35,6 +35,26 @@ impl dyn WidgetCore {
     }
 }
 
+impl dyn WidgetConfig {
+    /// Forwards to the method defined on the type `Any`.
+    #[inline]
+    pub fn is<T: Any>(&self) -> bool {
+        Any::is::<T>(self.as_any())
+    }
+
+    /// Forwards to the method defined on the type `Any`.
+    #[inline]
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        Any::downcast_ref::<T>(self.as_any())
+    }
+
+    /// Forwards to the method defined on the type `Any`.
+    #[inline]
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        Any::downcast_mut::<T>(self.as_any_mut())
+    }
+}
+
 /// Base widget functionality
 ///
 /// See the [`Widget`] trait for documentation of the widget family.
@@ -102,6 +122,61 @@ pub trait WidgetCore: Any + fmt::Debug {
         self
     }
 
+    /// Get whether the widget is hidden
+    #[inline]
+    fn is_hidden(&self) -> bool {
+        self.core_data().hidden
+    }
+
+    /// Set the visibility of a widget
+    ///
+    /// A hidden widget is excluded from layout (it is given zero size), is
+    /// not drawn and is not sent events. This is respected by layouts derived
+    /// via [`derive(Widget)`](kas::macros) and by keyboard navigation; other
+    /// parent widgets may not support it.
+    #[inline]
+    fn set_visible(&mut self, visible: bool) -> TkAction {
+        self.core_data_mut().hidden = !visible;
+        TkAction::Resize
+    }
+
+    /// Set visibility (chaining)
+    ///
+    /// This is identical to [`WidgetCore::set_visible`], but can be called in
+    /// chaining fashion.
+    #[inline]
+    fn with_visible(mut self, visible: bool) -> Self
+    where
+        Self: Sized,
+    {
+        self.core_data_mut().hidden = !visible;
+        self
+    }
+
+    /// Get the widget's style name, if any
+    #[inline]
+    fn style_name(&self) -> Option<&'static str> {
+        self.core_data().style_name
+    }
+
+    /// Set the style name (chaining)
+    ///
+    /// Unlike the widget's identifier, this name is stable: themes may use it
+    /// to style individual widgets. For example, `kas_theme::StyleSheet`
+    /// matches it with selectors of the form `#name`.
+    /// ```
+    /// use kas::{WidgetCore, widget::TextButton};
+    /// let button = TextButton::new("OK", ()).with_style_name("ok");
+    /// ```
+    #[inline]
+    fn with_style_name(mut self, name: &'static str) -> Self
+    where
+        Self: Sized,
+    {
+        self.core_data_mut().style_name = Some(name);
+        self
+    }
+
     /// Get the widget's region, relative to its parent.
     #[inline]
     fn rect(&self) -> Rect {
@@ -132,17 +207,36 @@ pub trait WidgetCore: Any + fmt::Debug {
             depress: mgr.is_depressed(id),
             nav_focus: mgr.nav_focus(id),
             char_focus: mgr.char_focus(id),
+            style_name: self.core_data().style_name,
         }
     }
 }
 
+/// Tab navigation hint of a child widget
+///
+/// See [`WidgetChildren::nav_hint`] and [`Manager::next_nav_focus`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NavHint {
+    /// Explicit position in the parent's Tab order, if any
+    ///
+    /// Children with a tab index are visited first, in ascending order; the
+    /// remainder follow in spatial order (see [`Layout::spatial_range`]).
+    pub tab_index: Option<u32>,
+    /// Navigation group, if any
+    ///
+    /// Siblings in the same group are visited consecutively, at the position
+    /// of the group's first member.
+    pub group: Option<&'static str>,
+}
+
 /// Listing of a widget's children
 ///
 /// This trait is part of the [`Widget`] family and is derived by
 /// [`derive(Widget)`] unless `#[widget(children = noauto)]` is used.
 ///
-/// Dynamic widgets must implement this trait manually, since [`derive(Widget)`]
-/// cannot currently handle fields like `Vec<SomeWidget>`.
+/// [`derive(Widget)`] supports child fields of widget type, `Vec<W>` and
+/// `Option<W>`; widgets storing children otherwise must implement this trait
+/// manually.
 ///
 /// Whenever the number of child widgets changes or child widgets are replaced,
 /// one must send [`TkAction::Reconfigure`].
@@ -170,6 +264,72 @@ pub trait WidgetChildren: WidgetCore {
     /// This method may be removed in the future.
     fn get_mut(&mut self, index: usize) -> Option<&mut dyn WidgetConfig>;
 
+    /// Get the Tab navigation hint of the child at `index`
+    ///
+    /// [`derive(Widget)`] implements this from child attributes, e.g.
+    /// `#[widget(tab_index = 1, nav_group = g)]`.
+    ///
+    /// Defaults to [`NavHint::default`].
+    ///
+    /// [`derive(Widget)`]: macros/index.html#the-derivewidget-macro
+    #[inline]
+    fn nav_hint(&self, _index: usize) -> NavHint {
+        NavHint::default()
+    }
+
+    /// Whether the child at `index` is initially hidden
+    ///
+    /// Children for which this returns true are hidden (see
+    /// [`WidgetCore::set_visible`]) when this widget is first configured.
+    /// [`derive(Widget)`] implements this from the `#[widget(hidden)]` child
+    /// attribute.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`derive(Widget)`]: macros/index.html#the-derivewidget-macro
+    #[inline]
+    fn initially_hidden(&self, _index: usize) -> bool {
+        false
+    }
+
+    /// Get a child widget by index, downcast to type `T`
+    ///
+    /// Returns `None` if the index is out of bounds or the child is not of
+    /// type `T`. On `dyn WidgetConfig`, use `get(index)` followed by
+    /// `downcast_ref` instead.
+    ///
+    /// ```
+    /// use kas::event::VoidMsg;
+    /// use kas::widget::{Label, Row, Separator};
+    /// use kas::{Widget, WidgetChildren};
+    ///
+    /// let row: Row<Box<dyn Widget<Msg = VoidMsg>>> = Row::new(vec![
+    ///     Box::new(Label::new("A label")),
+    ///     Box::new(Separator::new()),
+    /// ]);
+    /// assert!(row.get_as::<Label>(0).is_some());
+    /// assert!(row.get_as::<Label>(1).is_none());
+    /// assert!(row.get_as::<Separator<VoidMsg>>(1).is_some());
+    /// ```
+    #[inline]
+    fn get_as<T: Any>(&self, index: usize) -> Option<&T>
+    where
+        Self: Sized,
+    {
+        self.get(index).and_then(|w| w.downcast_ref::<T>())
+    }
+
+    /// Mutable variant of [`WidgetChildren::get_as`]
+    ///
+    /// The warning on [`WidgetChildren::get_mut`] applies.
+    #[inline]
+    fn get_as_mut<T: Any>(&mut self, index: usize) -> Option<&mut T>
+    where
+        Self: Sized,
+    {
+        self.get_mut(index).and_then(|w| w.downcast_mut::<T>())
+    }
+
     /// Check whether `id` is a descendant
     ///
     /// This function assumes that `id` is a valid widget.
@@ -226,6 +386,30 @@ pub trait WidgetChildren: WidgetCore {
         None
     }
 
+    /// Find a descendant widget by identifier, downcast to type `T`
+    ///
+    /// Returns `None` if no widget with this `id` is found or if the widget
+    /// is not of type `T`. As with [`WidgetChildren::find`], the widget tree
+    /// must already have been configured.
+    #[inline]
+    fn find_as<T: Any>(&self, id: WidgetId) -> Option<&T>
+    where
+        Self: Sized,
+    {
+        self.find(id).and_then(|w| w.downcast_ref::<T>())
+    }
+
+    /// Mutable variant of [`WidgetChildren::find_as`]
+    ///
+    /// The warning on [`WidgetChildren::get_mut`] applies.
+    #[inline]
+    fn find_as_mut<T: Any>(&mut self, id: WidgetId) -> Option<&mut T>
+    where
+        Self: Sized,
+    {
+        self.find_mut(id).and_then(|w| w.downcast_mut::<T>())
+    }
+
     /// Walk through all widgets, calling `f` once on each.
     ///
     /// This walk is iterative (nonconcurrent), depth-first, and always calls
@@ -309,8 +493,14 @@ pub trait WidgetConfig: Layout {
     /// method but instead use [`WidgetConfig::configure`]; the exception is
     /// widgets with pop-ups.
     fn configure_recurse<'a, 'b>(&mut self, mut cmgr: ConfigureManager<'a, 'b>) {
+        // Widgets are constructed with the default id, replaced when configured
+        let first = self.id() == WidgetId::default();
         for i in 0..self.len() {
+            let hidden = first && self.initially_hidden(i);
             if let Some(w) = self.get_mut(i) {
+                if hidden {
+                    let _ = w.set_visible(false);
+                }
                 w.configure_recurse(cmgr.child());
             }
         }
@@ -331,6 +521,17 @@ pub trait WidgetConfig: Layout {
     fn cursor_icon(&self) -> event::CursorIcon {
         event::CursorIcon::Default
     }
+
+    /// A label describing this widget, for debugging purposes
+    ///
+    /// Widgets with a text label or title should return it. This is used,
+    /// along with the widget's id and type name, to describe widgets in log
+    /// messages (see [`WidgetDescription`]).
+    ///
+    /// Defaults to `None`.
+    fn debug_label(&self) -> Option<&str> {
+        None
+    }
 }
 
 /// Positioning and drawing routines for widgets
@@ -358,7 +559,9 @@ pub trait Layout: WidgetChildren {
     ///
     /// To allow automatic flow of content over new lines, the width is sized
     /// first, followed by the height; when sizing for height, [`AxisInfo`]
-    /// contains the size of the *other* axis (i.e. the width).
+    /// contains the size of the *other* axis (i.e. the width). Widgets
+    /// surrounding their children with a frame or padding should reduce this
+    /// before passing `axis` to children (see [`AxisInfo::sub_other`]).
     ///
     /// For widgets with children, a [`kas::layout::RulesSolver`] engine may be
     /// useful to calculate requirements of complex layouts.
@@ -386,6 +589,23 @@ pub trait Layout: WidgetChildren {
         self.core_data_mut().rect = rect;
     }
 
+    /// Set the visible region (viewport)
+    ///
+    /// This is called by scroll regions on their content after
+    /// [`Layout::set_rect`] and whenever the scroll offset changes, passing the
+    /// part of the content's rect which is visible (in the content's
+    /// coordinate space). Lazy containers (see [`kas::layout`]) may use this to
+    /// configure and position only children within the viewport, setting
+    /// [`CoreData::reconfigure`] and returning [`TkAction::Relayout`] where
+    /// newly visible children must be configured.
+    ///
+    /// The default implementation does nothing. Widgets are not required to
+    /// forward this to children.
+    #[inline]
+    fn set_viewport(&mut self, _viewport: Rect) -> TkAction {
+        TkAction::None
+    }
+
     /// Get translation of a child
     ///
     /// Children may live in a translated coordinate space relative to their
@@ -493,3 +713,58 @@ impl<W: Widget + Sized> Boxed<dyn Widget<Msg = W::Msg>> for W {
         Box::new(self)
     }
 }
+
+/// Describes a widget by id, type name and label (if any)
+///
+/// This implements [`fmt::Display`], producing e.g. `#12 TextButton "Save"`.
+/// Labels are truncated to at most 32 characters. The widget is only looked up
+/// when formatted, thus construction is cheap (e.g. for disabled log output).
+#[derive(Clone, Copy)]
+pub struct WidgetDescription<'a> {
+    root: &'a dyn WidgetConfig,
+    id: WidgetId,
+}
+
+impl<'a> WidgetDescription<'a> {
+    /// Describe `widget`
+    #[inline]
+    pub fn new(widget: &'a dyn WidgetConfig) -> Self {
+        WidgetDescription {
+            root: widget,
+            id: widget.id(),
+        }
+    }
+
+    /// Describe the widget `id`, a descendant of `root`
+    ///
+    /// If no such widget is found, only the id is printed.
+    #[inline]
+    pub fn find(root: &'a dyn WidgetConfig, id: WidgetId) -> Self {
+        WidgetDescription { root, id }
+    }
+}
+
+impl<'a> fmt::Display for WidgetDescription<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const MAX_LEN: usize = 32;
+
+        let widget = match self.root.find(self.id) {
+            Some(widget) => widget,
+            None => return write!(f, "{}", self.id),
+        };
+        write!(f, "{} {}", self.id, widget.widget_name())?;
+        if let Some(label) = widget.debug_label() {
+            match label.char_indices().nth(MAX_LEN) {
+                Some((index, _)) => write!(f, " {:?}…", &label[..index])?,
+                None => write!(f, " {:?}", label)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Debug for WidgetDescription<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
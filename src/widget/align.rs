@@ -0,0 +1,131 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Alignment wrapper
+
+use kas::layout::StretchPolicy;
+use kas::prelude::*;
+
+/// Forcibly align a child within surplus space
+///
+/// On each axis with an alignment hint (other than [`kas::Align::Stretch`]),
+/// this widget accepts surplus space, sizes its child to its ideal size and
+/// aligns it within the surplus. Hints provided here take precedence over
+/// those of the parent; on other axes the parent's hints are forwarded.
+#[handler(msg = <W as Handler>::Msg)]
+#[derive(Clone, Debug, Default, Widget)]
+pub struct Align<W: Widget> {
+    #[widget_core]
+    core: CoreData,
+    #[widget]
+    child: W,
+    hints: AlignHints,
+    ideal: Size,
+}
+
+impl<W: Widget> Align<W> {
+    /// Construct, with alignment on each axis
+    #[inline]
+    pub fn new(child: W, horiz: Option<kas::Align>, vert: Option<kas::Align>) -> Self {
+        Align {
+            core: Default::default(),
+            child,
+            hints: AlignHints::new(horiz, vert),
+            ideal: Size::ZERO,
+        }
+    }
+
+    /// Construct, centred on both axes
+    #[inline]
+    pub fn centre(child: W) -> Self {
+        let centre = Some(kas::Align::Centre);
+        Align::new(child, centre, centre)
+    }
+
+    /// Get the alignment hints
+    #[inline]
+    pub fn hints(&self) -> AlignHints {
+        self.hints
+    }
+
+    /// Set the alignment hints
+    ///
+    /// Triggers a [resize action](Manager::send_action).
+    pub fn set_hints(&mut self, hints: AlignHints) -> TkAction {
+        self.hints = hints;
+        TkAction::Resize
+    }
+
+    /// Access the child widget
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.child
+    }
+
+    /// Access the child widget mutably
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut W {
+        &mut self.child
+    }
+}
+
+impl<W: Widget> Layout for Align<W> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let rules = self.child.size_rules(size_handle, axis);
+        let hint = match axis.is_horizontal() {
+            true => {
+                self.ideal.0 = rules.ideal_size();
+                self.hints.horiz
+            }
+            false => {
+                self.ideal.1 = rules.ideal_size();
+                self.hints.vert
+            }
+        };
+
+        match hint {
+            Some(align) if align != kas::Align::Stretch => SizeRules::new(
+                rules.min_size(),
+                rules.ideal_size(),
+                rules.margins(),
+                StretchPolicy::Filler,
+            ),
+            _ => rules,
+        }
+    }
+
+    fn set_rect(&mut self, rect: Rect, align: AlignHints) {
+        self.core.rect = rect;
+        let align = self.hints.combine(align);
+        let rect = align
+            .complete(kas::Align::Stretch, kas::Align::Stretch, self.ideal)
+            .apply(rect);
+        self.child.set_rect(rect, align);
+    }
+
+    #[inline]
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        if !self.rect().contains(coord) {
+            return None;
+        }
+        self.child.find_id(coord).or(Some(self.id()))
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
+        let disabled = disabled || self.is_disabled();
+        self.child.draw(draw_handle, mgr, disabled);
+    }
+}
+
+#[test]
+fn align_centre() {
+    use super::{Filler, Frame};
+
+    let mut align = Align::centre(Frame::new(Filler::new()));
+    let _ = kas::layout::layout_to_text(&mut align, Some(Size(200, 100)));
+    let rect = align.inner().rect();
+    assert_eq!(rect.size, align.ideal);
+    assert_eq!(rect.pos, Coord::from((Size(200, 100) - align.ideal) / 2));
+}
@@ -8,11 +8,13 @@
 use std::fmt::Debug;
 
 use kas::class::{CloneText, SetAccel};
-use kas::draw::TextClass;
+use kas::draw::{Icon, TextClass};
 use kas::event::{VirtualKeyCode, VirtualKeyCodes};
 use kas::prelude::*;
 
 /// A push-button with a text label
+///
+/// An [`Icon`] may be shown before the label (see [`TextButton::with_icon`]).
 #[handler(handle=noauto)]
 #[widget(config=noauto)]
 #[derive(Clone, Debug, Default, Widget)]
@@ -23,7 +25,12 @@ pub struct TextButton<M: Clone + Debug + 'static> {
     keys2: VirtualKeyCodes,
     // label_rect: Rect,
     label: PreparedText,
+    label_pos: Coord,
     underline: usize,
+    icon: Option<Icon>,
+    icon_rect: Rect,
+    icon_x: u32,
+    icon_space: u32,
     msg: M,
 }
 
@@ -36,6 +43,10 @@ impl<M: Clone + Debug + 'static> WidgetConfig for TextButton<M> {
     fn key_nav(&self) -> bool {
         true
     }
+
+    fn debug_label(&self) -> Option<&str> {
+        Some(self.label.text())
+    }
 }
 
 impl<M: Clone + Debug + 'static> Layout for TextButton<M> {
@@ -44,35 +55,73 @@ impl<M: Clone + Debug + 'static> Layout for TextButton<M> {
         let margins = size_handle.outer_margins();
         let frame_rules = SizeRules::extract_fixed(axis.is_vertical(), sides.0 + sides.1, margins);
 
-        let content_rules = size_handle.text_bound(&mut self.label, TextClass::Button, axis);
+        // Space taken by the icon (if any) left of the label
+        let icon = size_handle.icon();
+        self.icon_rect.size = icon;
+        self.icon_x = sides.0 .0;
+        self.icon_space = match self.icon {
+            Some(_) => sides.0 .0 + icon.0 + size_handle.inner_margin().0,
+            None => 0,
+        };
+
+        let mut content_axis = axis;
+        if axis.is_vertical() {
+            content_axis.sub_other((sides.0 + sides.1).0 + self.icon_space);
+        }
+        let mut content_rules =
+            size_handle.text_bound(&mut self.label, TextClass::Button, content_axis);
+        if self.icon.is_some() {
+            if axis.is_horizontal() {
+                let space = self.icon_space - sides.0 .0;
+                content_rules.append(SizeRules::fixed(space, (0, 0)));
+            } else {
+                content_rules.max_with(SizeRules::fixed(icon.1, (0, 0)));
+            }
+        }
         content_rules.surrounded_by(frame_rules, true)
     }
 
     fn set_rect(&mut self, rect: Rect, align: AlignHints) {
         self.core.rect = rect;
 
+        let mut text_rect = rect;
+        if self.icon.is_some() {
+            let size = self.icon_rect.size.min(rect.size);
+            let x = rect.pos.0 + self.icon_x as i32;
+            let y = rect.pos.1 + (rect.size.1 - size.1) as i32 / 2;
+            self.icon_rect = Rect::new(Coord(x, y), size);
+            text_rect.pos.0 += self.icon_space as i32;
+            text_rect.size.0 = text_rect.size.0.saturating_sub(self.icon_space);
+        }
+        self.label_pos = text_rect.pos;
+
         // In theory, text rendering should be restricted as in EditBox.
         // In practice, it sometimes overflows a tiny bit, and looks better if
         // we let it overflow. Since the text is centred this is okay.
         // self.label_rect = ...
         self.label.update_env(|env| {
-            env.set_bounds(rect.size.into());
+            env.set_bounds(text_rect.size.into());
             env.set_align(align.unwrap_or(Align::Centre, Align::Centre));
         });
     }
 
     fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
-        draw_handle.button(self.core.rect, self.input_state(mgr, disabled));
+        let state = self.input_state(mgr, disabled);
+        draw_handle.button(self.core.rect, state);
+        if let Some(icon) = self.icon.as_ref() {
+            draw_handle.icon(self.icon_rect, icon, state);
+        }
         if mgr.show_accel_labels() {
             draw_handle.text_with_underline(
-                self.core.rect.pos,
+                self.label_pos,
                 Coord::ZERO,
                 &self.label,
                 TextClass::Button,
+                state,
                 self.underline,
             );
         } else {
-            draw_handle.text(self.core.rect.pos, &self.label, TextClass::Button);
+            draw_handle.text(self.label_pos, &self.label, TextClass::Button, state);
         }
     }
 }
@@ -95,11 +144,24 @@ impl<M: Clone + Debug + 'static> TextButton<M> {
             keys2,
             // label_rect: Default::default(),
             label: text,
+            label_pos: Coord::ZERO,
             underline,
+            icon: None,
+            icon_rect: Rect::default(),
+            icon_x: 0,
+            icon_space: 0,
             msg,
         }
     }
 
+    /// Show an icon before the label (chain style)
+    ///
+    /// Use an empty label for an icon-only button.
+    pub fn with_icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
     /// Add accelerator keys (chain style)
     ///
     /// These keys are added to those inferred from the label via `&` marks.
@@ -60,7 +60,7 @@ impl<M: Clone + Debug + 'static> kas::Layout for ComboBox<M> {
             state.depress = true;
         }
         draw_handle.button(self.core.rect, state);
-        draw_handle.text(self.core.rect.pos, &self.label, TextClass::Button);
+        draw_handle.text(self.core.rect.pos, &self.label, TextClass::Button, state);
     }
 }
 
@@ -8,9 +8,10 @@
 //! KAS dialog boxes are pre-configured windows, usually allowing some
 //! customisation.
 
+use kas::draw::Icon;
 use kas::event::VirtualKeyCode;
 use kas::prelude::*;
-use kas::widget::{Label, TextButton};
+use kas::widget::{IconView, Label, TextButton};
 use kas::WindowId;
 
 #[derive(Clone, Debug, VoidMsg)]
@@ -18,7 +19,24 @@ enum DialogButton {
     Close,
 }
 
+/// A message with an icon
+#[layout(row)]
+#[derive(Clone, Debug, Widget)]
+struct IconMessage {
+    #[widget_core]
+    core: CoreData,
+    #[layout_data]
+    layout_data: <Self as kas::LayoutData>::Data,
+    #[widget]
+    icon: IconView,
+    #[widget]
+    label: Label,
+}
+
 /// A simple message box.
+///
+/// The message is shown with an icon: [`Icon::Info`] by default (see
+/// [`MessageBox::with_icon`]).
 #[layout(column)]
 #[widget(config=noauto)]
 #[derive(Clone, Debug, Widget)]
@@ -29,7 +47,7 @@ pub struct MessageBox {
     layout_data: <Self as kas::LayoutData>::Data,
     title: String,
     #[widget]
-    label: Label,
+    message: IconMessage,
     #[widget(handler = handle_button)]
     button: TextButton<DialogButton>,
 }
@@ -40,7 +58,12 @@ impl MessageBox {
             core: Default::default(),
             layout_data: Default::default(),
             title: title.to_string(),
-            label: Label::new(message),
+            message: IconMessage {
+                core: Default::default(),
+                layout_data: Default::default(),
+                icon: IconView::new(Icon::Info).with_scale(2.0),
+                label: Label::new(message),
+            },
             button: TextButton::new("Ok", DialogButton::Close).with_keys(&[
                 VirtualKeyCode::Return,
                 VirtualKeyCode::Space,
@@ -49,6 +72,14 @@ impl MessageBox {
         }
     }
 
+    /// Set the icon (chain style)
+    ///
+    /// E.g. use [`Icon::Warning`] or [`Icon::Error`] to report problems.
+    pub fn with_icon(mut self, icon: Icon) -> Self {
+        let _ = self.message.icon.set_icon(icon);
+        self
+    }
+
     fn handle_button(&mut self, mgr: &mut Manager, msg: DialogButton) -> Response<VoidMsg> {
         match msg {
             DialogButton::Close => mgr.send_action(TkAction::Close),
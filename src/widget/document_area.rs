@@ -0,0 +1,546 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! An area of movable, resizable sub-windows
+
+use std::ops::{Deref, Index, IndexMut};
+
+use super::{MenuEntry, SubMenu};
+use kas::draw::{ClipRegion, InputState, TextClass};
+use kas::event::{CursorIcon, GrabMode, PressSource};
+use kas::prelude::*;
+
+/// A document area of boxed widgets
+///
+/// This is a parametrisation of [`DocumentArea`].
+pub type BoxDocumentArea<M> = DocumentArea<Box<dyn Widget<Msg = M>>>;
+
+/// A command affecting a [`DocumentArea`]
+///
+/// These are the messages of entries created by
+/// [`DocumentArea::window_menu`]; apply them via [`DocumentArea::command`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentCommand {
+    /// Arrange documents in an overlapping diagonal stack
+    Cascade,
+    /// Arrange documents side-by-side, dividing the area between them
+    Tile,
+    /// Raise the document with the given index
+    Activate(usize),
+}
+
+/// A sub-window of a [`DocumentArea`]
+#[derive(Clone, Debug, Default)]
+struct Document {
+    title: PreparedText,
+    /// Outer rect, relative to the area's position
+    rect: Rect,
+    /// False until `rect` has been assigned
+    placed: bool,
+    min: Size,
+    ideal: Size,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DragMode {
+    Move,
+    Resize,
+}
+
+/// An in-progress move or resize
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+    source: PressSource,
+    index: usize,
+    mode: DragMode,
+    start: Coord,
+    rect: Rect,
+}
+
+/// An area of movable, resizable sub-windows (MDI)
+///
+/// Each child (a *document*) is placed within an internal sub-window with a
+/// frame and title bar. Sub-windows may be moved by dragging their title bar
+/// or frame and resized by dragging their bottom-right corner; pressing on a
+/// sub-window raises it. Sub-windows remain within the area and are drawn in
+/// z-order; events go to the top-most sub-window under the cursor.
+///
+/// New documents are placed as by [`DocumentArea::cascade`], at their ideal
+/// size. Documents may be re-arranged via [`DocumentArea::cascade`] and
+/// [`DocumentArea::tile`], or by passing a [`DocumentCommand`] from the
+/// entries of a [`DocumentArea::window_menu`] to [`DocumentArea::command`].
+///
+/// The area's size requirements are those of its largest sub-window; it
+/// accepts as much space as is available.
+#[handler(noauto)]
+#[widget(children=noauto)]
+#[derive(Clone, Default, Debug, Widget)]
+pub struct DocumentArea<W: Widget> {
+    #[widget_core]
+    core: CoreData,
+    widgets: Vec<W>,
+    docs: Vec<Document>,
+    /// Document indices, from bottom to top
+    order: Vec<usize>,
+    frame: Size,
+    title_height: u32,
+    drag: Option<Drag>,
+}
+
+impl<W: Widget> WidgetChildren for DocumentArea<W> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.widgets.len()
+    }
+    #[inline]
+    fn get(&self, index: usize) -> Option<&dyn WidgetConfig> {
+        self.widgets.get(index).map(|w| w.as_widget())
+    }
+    #[inline]
+    fn get_mut(&mut self, index: usize) -> Option<&mut dyn WidgetConfig> {
+        self.widgets.get_mut(index).map(|w| w.as_widget_mut())
+    }
+}
+
+impl<W: Widget> Layout for DocumentArea<W> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        self.frame = size_handle.frame();
+        let frame = 2 * axis.extract_size(self.frame);
+        let (mut min, mut ideal) = (0, 0);
+        for (child, doc) in self.widgets.iter_mut().zip(self.docs.iter_mut()) {
+            let rules = child.size_rules(size_handle, axis);
+            let title = size_handle.text_bound(&mut doc.title, TextClass::LabelSingle, axis);
+            let (doc_min, doc_ideal) = match axis.is_vertical() {
+                false => {
+                    let doc_min = rules.min_size().max(title.min_size()) + frame;
+                    let doc_ideal = rules.ideal_size().max(title.ideal_size()) + frame;
+                    doc.min.0 = doc_min;
+                    doc.ideal.0 = doc_ideal;
+                    (doc_min, doc_ideal)
+                }
+                true => {
+                    self.title_height = title.ideal_size();
+                    let doc_min = rules.min_size() + self.title_height + frame;
+                    let doc_ideal = rules.ideal_size() + self.title_height + frame;
+                    doc.min.1 = doc_min;
+                    doc.ideal.1 = doc_ideal;
+                    (doc_min, doc_ideal)
+                }
+            };
+            min = min.max(doc_min);
+            ideal = ideal.max(doc_ideal);
+        }
+
+        let margins = size_handle.outer_margins().axis(axis.is_vertical());
+        SizeRules::new(min, ideal, margins, StretchPolicy::Maximise)
+    }
+
+    fn set_rect(&mut self, rect: Rect, _: AlignHints) {
+        self.core.rect = rect;
+        for index in 0..self.docs.len() {
+            let doc = &self.docs[index];
+            let rect = if doc.placed {
+                doc.rect
+            } else {
+                let n = self.order.iter().position(|i| *i == index).unwrap_or(0);
+                self.cascade_rect(n, doc.ideal)
+            };
+            self.place(index, rect);
+        }
+    }
+
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        if !self.rect().contains(coord) {
+            return None;
+        }
+
+        if let Some(index) = self.doc_at(coord) {
+            let child = &self.widgets[index];
+            if child.rect().contains(coord) {
+                return child.find_id(coord).or(Some(self.id()));
+            }
+        }
+        Some(self.id())
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
+        let disabled = disabled || self.is_disabled();
+        let active = self.order.last().cloned();
+        for (depth, index) in self.order.iter().cloned().enumerate() {
+            let outer = self.docs[index].rect + self.core.rect.pos;
+            // Each document uses its own layer, drawn over those below but
+            // clipped by this area (and below any pop-ups)
+            let layer = ClipRegion::Layer(depth.min(u8::MAX as usize) as u8);
+            draw_handle.clip_region(outer, Coord::ZERO, layer, &mut |draw_handle| {
+                draw_handle.menu_frame(outer);
+                let inner = self.inner_rect(outer);
+                let title_rect = Rect::new(inner.pos, Size(inner.size.0, self.title_height));
+                let state = InputState {
+                    disabled,
+                    depress: self.drag.map(|drag| drag.index == index).unwrap_or(false),
+                    nav_focus: active == Some(index),
+                    ..Default::default()
+                };
+                draw_handle.menu_entry(title_rect, state);
+                let title = &self.docs[index].title;
+                draw_handle.text(title_rect.pos, title, TextClass::LabelSingle, state);
+                self.widgets[index].draw(draw_handle, mgr, disabled);
+            });
+        }
+    }
+}
+
+impl<W: Widget> event::Handler for DocumentArea<W> {
+    type Msg = <W as event::Handler>::Msg;
+
+    fn handle(&mut self, mgr: &mut Manager, event: Event) -> Response<Self::Msg> {
+        match event {
+            Event::PressStart { source, coord, .. } => {
+                let index = match self.doc_at(coord) {
+                    Some(index) => index,
+                    None => return Response::Unhandled(event),
+                };
+                *mgr += self.activate(index);
+
+                let rect = self.docs[index].rect;
+                let end = (rect + self.core.rect.pos).pos_end();
+                let grip = Coord::from(self.frame) + Coord::uniform(self.title_height as i32);
+                let (mode, icon) = if coord.0 >= end.0 - grip.0 && coord.1 >= end.1 - grip.1 {
+                    (DragMode::Resize, CursorIcon::SeResize)
+                } else {
+                    (DragMode::Move, CursorIcon::Grabbing)
+                };
+                if mgr.request_grab(self.id(), source, coord, GrabMode::Grab, Some(icon)) {
+                    let start = coord;
+                    self.drag = Some(Drag {
+                        source,
+                        index,
+                        mode,
+                        start,
+                        rect,
+                    });
+                }
+                Response::None
+            }
+            Event::PressMove { source, coord, .. } => match self.drag {
+                Some(drag) if drag.source == source => {
+                    let delta = coord - drag.start;
+                    let mut rect = drag.rect;
+                    match drag.mode {
+                        DragMode::Move => rect.pos = rect.pos + delta,
+                        DragMode::Resize => {
+                            let size = Coord::from(rect.size) + delta;
+                            rect.size = Size::from(size.max(Coord::ZERO));
+                        }
+                    }
+                    if self.place(drag.index, rect) {
+                        *mgr += TkAction::RegionMoved;
+                    }
+                    Response::None
+                }
+                _ => Response::Unhandled(event),
+            },
+            Event::PressEnd { source, .. } => match self.drag {
+                Some(drag) if drag.source == source => {
+                    self.drag = None;
+                    *mgr += TkAction::Redraw;
+                    Response::None
+                }
+                _ => Response::Unhandled(event),
+            },
+            event => Response::Unhandled(event),
+        }
+    }
+}
+
+impl<W: Widget> event::SendEvent for DocumentArea<W> {
+    fn send(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
+        if self.is_disabled() {
+            return Response::Unhandled(event);
+        }
+
+        for index in 0..self.widgets.len() {
+            if id <= self.widgets[index].id() {
+                if let Event::PressStart { .. } = event {
+                    // Pressing within a document raises it
+                    *mgr += self.activate(index);
+                }
+                return self.widgets[index].send(mgr, id, event);
+            }
+        }
+
+        Manager::handle_generic(self, mgr, event)
+    }
+}
+
+impl<W: Widget> DocumentArea<W> {
+    /// Construct a new, empty instance
+    pub fn new() -> Self {
+        DocumentArea {
+            core: Default::default(),
+            widgets: vec![],
+            docs: vec![],
+            order: vec![],
+            frame: Size::ZERO,
+            title_height: 0,
+            drag: None,
+        }
+    }
+
+    /// Add a document (chain style)
+    ///
+    /// See [`DocumentArea::push`].
+    pub fn with<T: Into<LabelString>>(mut self, title: T, widget: W) -> Self {
+        let _ = self.push(title, widget);
+        self
+    }
+
+    /// True if there are no documents
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
+
+    /// Returns the number of documents
+    pub fn len(&self) -> usize {
+        self.widgets.len()
+    }
+
+    /// Add a document, with the given title, on top of existing documents
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action).
+    pub fn push<T: Into<LabelString>>(&mut self, title: T, widget: W) -> TkAction {
+        self.widgets.push(widget);
+        let title = PreparedText::new_single(title.into().deref().into());
+        self.docs.push(Document {
+            title,
+            ..Default::default()
+        });
+        self.order.push(self.widgets.len() - 1);
+        TkAction::Reconfigure
+    }
+
+    /// Removes the document at position `index`
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action).
+    pub fn remove(&mut self, index: usize) -> (W, TkAction) {
+        let r = self.widgets.remove(index);
+        self.docs.remove(index);
+        self.order.retain(|i| *i != index);
+        for i in &mut self.order {
+            if *i > index {
+                *i -= 1;
+            }
+        }
+        self.drag = None;
+        (r, TkAction::Reconfigure)
+    }
+
+    /// Get the title of the document at `index`
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn title(&self, index: usize) -> &str {
+        self.docs[index].title.text()
+    }
+
+    /// Set the title of the document at `index`
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_title<T: Into<LabelString>>(&mut self, index: usize, title: T) -> TkAction {
+        self.docs[index].title.set_and_prepare(title.into().deref())
+    }
+
+    /// Get the index of the active (top-most) document, if any
+    pub fn active(&self) -> Option<usize> {
+        self.order.last().cloned()
+    }
+
+    /// Raise the document at `index` above all others
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// Returns [`TkAction::RegionMoved`] if the z-order changed.
+    pub fn activate(&mut self, index: usize) -> TkAction {
+        assert!(index < self.docs.len());
+        if self.active() == Some(index) {
+            return TkAction::None;
+        }
+        self.order.retain(|i| *i != index);
+        self.order.push(index);
+        TkAction::RegionMoved
+    }
+
+    /// Arrange documents in an overlapping diagonal stack
+    ///
+    /// Documents are given their ideal size (limited to the area's size) and
+    /// stacked in z-order, each offset by the height of a title bar.
+    pub fn cascade(&mut self) -> TkAction {
+        let order = self.order.clone();
+        for (n, index) in order.into_iter().enumerate() {
+            let rect = self.cascade_rect(n, self.docs[index].ideal);
+            self.place(index, rect);
+        }
+        TkAction::RegionMoved
+    }
+
+    /// Arrange documents side-by-side, dividing the area between them
+    ///
+    /// Documents are placed in a grid of cells, filling rows first. Where a
+    /// cell is smaller than a document's minimum size, the document overlaps
+    /// its neighbours.
+    pub fn tile(&mut self) -> TkAction {
+        let n = self.docs.len() as u32;
+        if n == 0 {
+            return TkAction::None;
+        }
+        let mut cols = 1;
+        while cols * cols < n {
+            cols += 1;
+        }
+        let rows = (n + cols - 1) / cols;
+        let size = Size(self.core.rect.size.0 / cols, self.core.rect.size.1 / rows);
+        for index in 0..self.docs.len() {
+            let (col, row) = (index as u32 % cols, index as u32 / cols);
+            let pos = Coord((col * size.0) as i32, (row * size.1) as i32);
+            self.place(index, Rect::new(pos, size));
+        }
+        TkAction::RegionMoved
+    }
+
+    /// Apply a [`DocumentCommand`]
+    pub fn command(&mut self, command: DocumentCommand) -> TkAction {
+        match command {
+            DocumentCommand::Cascade => self.cascade(),
+            DocumentCommand::Tile => self.tile(),
+            DocumentCommand::Activate(index) if index < self.docs.len() => self.activate(index),
+            DocumentCommand::Activate(_) => TkAction::None,
+        }
+    }
+
+    /// Construct a "Window" menu for this area
+    ///
+    /// The menu has entries to cascade and tile documents, followed by one
+    /// entry per document (in order of index) to activate that document. The
+    /// menu is not updated automatically: reconstruct it when documents are
+    /// added, removed or renamed.
+    pub fn window_menu<D: Directional + Default, S: Into<AccelString>>(
+        &self,
+        label: S,
+    ) -> SubMenu<D, MenuEntry<DocumentCommand>> {
+        let mut entries = vec![
+            MenuEntry::new("&Cascade", DocumentCommand::Cascade),
+            MenuEntry::new("&Tile", DocumentCommand::Tile),
+        ];
+        for (index, doc) in self.docs.iter().enumerate() {
+            let title = doc.title.text().replace('&', "&&");
+            let label = match index {
+                0..=8 => format!("&{} {}", index + 1, title),
+                _ => format!("{} {}", index + 1, title),
+            };
+            entries.push(MenuEntry::new(label, DocumentCommand::Activate(index)));
+        }
+        SubMenu::new(label, entries)
+    }
+
+    /// Iterate over documents, in order of index
+    pub fn iter(&self) -> std::slice::Iter<W> {
+        self.widgets.iter()
+    }
+
+    // Find the top-most document containing coord
+    fn doc_at(&self, coord: Coord) -> Option<usize> {
+        let pos = self.core.rect.pos;
+        let docs = &self.docs;
+        let mut order = self.order.iter().rev();
+        order
+            .find(|i| (docs[**i].rect + pos).contains(coord))
+            .cloned()
+    }
+
+    fn inner_rect(&self, outer: Rect) -> Rect {
+        let frame = self.frame;
+        let w = outer.size.0.saturating_sub(2 * frame.0);
+        let h = outer.size.1.saturating_sub(2 * frame.1);
+        Rect::new(outer.pos + Coord::from(frame), Size(w, h))
+    }
+
+    // Rect of the n-th document in a cascade, wrapping within the area
+    fn cascade_rect(&self, n: usize, ideal: Size) -> Rect {
+        let area = self.core.rect.size;
+        let size = ideal.min(area);
+        let step = (self.title_height + self.frame.1) as i32 * n as i32;
+        let free = Coord::from(area - size) + Coord::uniform(1);
+        Rect::new(Coord(step % free.0, step % free.1), size)
+    }
+
+    // Assign the outer rect of a document (relative to the area), limited by
+    // the document's minimum size and the area, and set child rects.
+    //
+    // Returns true if the document's rect changed.
+    fn place(&mut self, index: usize, rect: Rect) -> bool {
+        let area = self.core.rect.size;
+        let doc = &mut self.docs[index];
+        let size = rect.size.max(doc.min).min(area);
+        let pos = rect.pos.clamp(Coord::ZERO, Coord::from(area - size));
+        let rect = Rect::new(pos, size);
+        let changed = !doc.placed || doc.rect != rect;
+        doc.rect = rect;
+        doc.placed = true;
+
+        let inner = self.inner_rect(rect + self.core.rect.pos);
+        let title_height = self.title_height.min(inner.size.1);
+        let doc = &mut self.docs[index];
+        doc.title.update_env(|env| {
+            env.set_bounds(Size(inner.size.0, title_height).into());
+            env.set_align((Align::Default, Align::Centre));
+        });
+        let pos = inner.pos + Coord(0, title_height as i32);
+        let size = Size(inner.size.0, inner.size.1 - title_height);
+        self.widgets[index].set_rect(Rect::new(pos, size), AlignHints::NONE);
+        changed
+    }
+}
+
+impl<W: Widget> Index<usize> for DocumentArea<W> {
+    type Output = W;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.widgets[index]
+    }
+}
+
+impl<W: Widget> IndexMut<usize> for DocumentArea<W> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.widgets[index]
+    }
+}
+
+#[test]
+fn document_area_tile() {
+    use super::Filler;
+
+    // Text is shaped when set, which requires a font
+    kas::text::fonts().load_default().unwrap();
+    let mut area = DocumentArea::new()
+        .with("A", Filler::new())
+        .with("B", Filler::new())
+        .with("C", Filler::new());
+    let _ = kas::layout::layout_to_text(&mut area, Some(Size(200, 100)));
+    assert_eq!(area.active(), Some(2));
+
+    assert_eq!(area.tile(), TkAction::RegionMoved);
+    let size = Size(area.core.rect.size.0 / 2, area.core.rect.size.1 / 2);
+    let (w, h) = (size.0 as i32, size.1 as i32);
+    assert_eq!(area.docs[1].rect, Rect::new(Coord(w, 0), size));
+    assert_eq!(area.docs[2].rect, Rect::new(Coord(0, h), size));
+
+    assert_eq!(
+        area.command(DocumentCommand::Activate(0)),
+        TkAction::RegionMoved
+    );
+    assert_eq!(area.active(), Some(0));
+    assert_eq!(area.doc_at(Coord(10, 10)), Some(0));
+}
@@ -7,9 +7,78 @@
 
 use std::fmt::Debug;
 
-use kas::event::PressSource;
+use kas::event::{PressSource, State, StateMachine, Transition};
 use kas::prelude::*;
 
+/// Interaction state of a [`DragHandle`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DragState {
+    /// Not being dragged
+    Idle,
+    /// Being dragged by `source`
+    Dragging {
+        /// The press source
+        source: PressSource,
+        /// Offset of the press relative to the handle's offset
+        press_offset: Coord,
+    },
+}
+
+impl Default for DragState {
+    fn default() -> Self {
+        DragState::Idle
+    }
+}
+
+impl State<DragHandle> for DragState {
+    type Msg = Coord;
+
+    fn on_event(
+        &self,
+        handle: &mut DragHandle,
+        mgr: &mut Manager,
+        event: Event,
+    ) -> Transition<Self, Coord> {
+        match (*self, event) {
+            (_, Event::PressStart { source, coord, .. }) => {
+                if !handle.grab_press(mgr, source, coord) {
+                    return Transition::stay();
+                }
+
+                // Event delivery implies coord is over the handle.
+                let press_offset = coord - handle.offset();
+                Transition::to(DragState::Dragging {
+                    source,
+                    press_offset,
+                })
+            }
+            (
+                DragState::Dragging {
+                    source,
+                    press_offset,
+                },
+                Event::PressMove {
+                    source: s, coord, ..
+                },
+            ) if s == source => {
+                let (offset, action) = handle.set_offset(coord - press_offset);
+                if action == TkAction::None {
+                    Transition::stay()
+                } else {
+                    mgr.send_action(action);
+                    Transition::stay().with_msg(offset)
+                }
+            }
+            (DragState::Dragging { source, .. }, Event::PressEnd { source: s, .. })
+                if s == source =>
+            {
+                Transition::to(DragState::Idle)
+            }
+            (_, event) => Transition::unhandled(event),
+        }
+    }
+}
+
 /// Draggable Handle
 ///
 /// A `DragHandle` is a draggable object with a given size which is restricted
@@ -32,8 +101,7 @@ pub struct DragHandle {
     core: CoreData,
     // The track is the area within which this DragHandle may move
     track: Rect,
-    press_source: Option<event::PressSource>,
-    press_offset: Coord,
+    state: DragState,
 }
 
 impl DragHandle {
@@ -42,11 +110,16 @@ impl DragHandle {
         DragHandle {
             core: Default::default(),
             track: Default::default(),
-            press_source: None,
-            press_offset: Coord::ZERO,
+            state: DragState::Idle,
         }
     }
 
+    /// True while the handle is being dragged
+    #[inline]
+    pub fn is_dragging(&self) -> bool {
+        self.state != DragState::Idle
+    }
+
     /// Set a new handle size and offset
     ///
     /// Returns [`TkAction::Redraw`] if a redraw is required.
@@ -108,25 +181,26 @@ impl DragHandle {
             return self.offset();
         }
 
-        self.press_offset = Coord::from(self.core.rect.size / 2) + self.track.pos;
+        let press_offset = Coord::from(self.core.rect.size / 2) + self.track.pos;
+        let state = DragState::Dragging {
+            source,
+            press_offset,
+        };
+        self.set_state(mgr, state);
 
         // Since the press is not on the handle, we move the bar immediately.
-        let (offset, action) = self.set_offset(coord - self.press_offset);
+        let (offset, action) = self.set_offset(coord - press_offset);
         debug_assert!(action == TkAction::Redraw);
         mgr.send_action(action);
         offset
     }
 
+    // Interacting with a scrollbar with multiple presses does not make sense.
+    // On success, the caller transitions to a new DragState::Dragging, thus
+    // any other press gets aborted.
     fn grab_press(&mut self, mgr: &mut Manager, source: PressSource, coord: Coord) -> bool {
         let cur = Some(event::CursorIcon::Grabbing);
-        if mgr.request_grab(self.id(), source, coord, event::GrabMode::Grab, cur) {
-            // Interacting with a scrollbar with multiple presses
-            // does not make sense. Any other gets aborted.
-            self.press_source = Some(source);
-            true
-        } else {
-            false
-        }
+        mgr.request_grab(self.id(), source, coord, event::GrabMode::Grab, cur)
     }
 }
 
@@ -149,35 +223,22 @@ impl Layout for DragHandle {
     fn draw(&self, _: &mut dyn DrawHandle, _: &event::ManagerState, _: bool) {}
 }
 
+impl StateMachine for DragHandle {
+    type State = DragState;
+
+    fn state(&self) -> &DragState {
+        &self.state
+    }
+
+    fn state_mut(&mut self) -> &mut DragState {
+        &mut self.state
+    }
+}
+
 impl event::Handler for DragHandle {
     type Msg = Coord;
 
     fn handle(&mut self, mgr: &mut Manager, event: Event) -> Response<Self::Msg> {
-        match event {
-            Event::PressStart { source, coord, .. } => {
-                if !self.grab_press(mgr, source, coord) {
-                    return Response::None;
-                }
-
-                // Event delivery implies coord is over the handle.
-                self.press_offset = coord - self.offset();
-                Response::None
-            }
-            Event::PressMove { source, coord, .. } if Some(source) == self.press_source => {
-                let offset = coord - self.press_offset;
-                let (offset, action) = self.set_offset(offset);
-                if action == TkAction::None {
-                    Response::None
-                } else {
-                    mgr.send_action(action);
-                    Response::Msg(offset)
-                }
-            }
-            Event::PressEnd { source, .. } if Some(source) == self.press_source => {
-                self.press_source = None;
-                Response::None
-            }
-            event => Response::Unhandled(event),
-        }
+        self.handle_state(mgr, event)
     }
 }
@@ -11,26 +11,21 @@ use std::time::Duration;
 use unicode_segmentation::{GraphemeCursor, UnicodeSegmentation};
 
 use kas::class::HasString;
+use kas::command::UndoStack;
 use kas::draw::TextClass;
-use kas::event::{ControlKey, GrabMode, PressSource, ScrollDelta};
+use kas::event::{ControlKey, GrabMode, MouseButton, PressSource, ScrollDelta, ScrollOverride};
 use kas::geom::Vec2;
 use kas::prelude::*;
 use kas::text::PrepareAction;
 
-#[derive(Clone, Debug, PartialEq)]
+/// Merge group of an edit (see [`UndoStack::push_merge`])
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum LastEdit {
-    None,
     Insert,
     Delete,
     Paste,
 }
 
-impl Default for LastEdit {
-    fn default() -> Self {
-        LastEdit::None
-    }
-}
-
 enum EditAction {
     None,
     Unhandled,
@@ -166,10 +161,10 @@ pub struct EditBox<G: 'static> {
     sel_pos: usize,
     anchor_pos: usize,
     edit_x_coord: Option<f32>,
-    old_state: Option<(String, usize, usize)>,
-    last_edit: LastEdit,
+    undo: UndoStack<(String, usize, usize)>,
     error_state: bool,
     touch_phase: TouchPhase,
+    scroll_override: ScrollOverride,
     /// The associated [`EditGuard`] implementation
     pub guard: G,
 }
@@ -201,7 +196,12 @@ impl<G: 'static> Layout for EditBox<G> {
         } else {
             TextClass::Edit
         };
-        let content_rules = size_handle.text_bound(&mut self.text, class, axis);
+        let mut content_axis = axis;
+        if axis.is_vertical() {
+            // self.frame_size.0 was set when sizing the horizontal axis
+            content_axis.sub_other(self.frame_size.0);
+        }
+        let content_rules = size_handle.text_bound(&mut self.text, class, content_axis);
         let m = content_rules.margins();
 
         // Note: we do not allocate space for the edit marker (size_handle.edit_marker_width());
@@ -251,7 +251,13 @@ impl<G: 'static> Layout for EditBox<G> {
         input_state.error = self.error_state;
         draw_handle.edit_box(self.core.rect, input_state);
         if self.sel_pos == self.edit_pos {
-            draw_handle.text_offset(self.text_pos, self.view_offset, &self.text, class);
+            draw_handle.text_offset(
+                self.text_pos,
+                self.view_offset,
+                &self.text,
+                class,
+                input_state,
+            );
         } else {
             // TODO(opt): we could cache the selection rectangles here to make
             // drawing more efficient (self.text.highlight_lines(range) output).
@@ -262,6 +268,7 @@ impl<G: 'static> Layout for EditBox<G> {
                 &self.text,
                 self.selection(),
                 class,
+                input_state,
             );
         }
         if input_state.char_focus {
@@ -294,10 +301,10 @@ impl EditBox<EditVoid> {
             sel_pos: edit_pos,
             anchor_pos: edit_pos,
             edit_x_coord: None,
-            old_state: None,
-            last_edit: LastEdit::None,
+            undo: UndoStack::new(),
             error_state: false,
             touch_phase: TouchPhase::None,
+            scroll_override: Default::default(),
             guard: EditVoid,
         }
     }
@@ -323,10 +330,10 @@ impl EditBox<EditVoid> {
             sel_pos: self.sel_pos,
             anchor_pos: self.anchor_pos,
             edit_x_coord: self.edit_x_coord,
-            old_state: self.old_state,
-            last_edit: self.last_edit,
+            undo: self.undo,
             error_state: self.error_state,
             touch_phase: self.touch_phase,
+            scroll_override: self.scroll_override,
             guard,
         };
         let _ = G::edit(&mut edit);
@@ -374,6 +381,16 @@ impl EditBox<EditVoid> {
 }
 
 impl<G> EditBox<G> {
+    /// Override scroll settings (inline)
+    ///
+    /// By default, [`InputConfig`] settings are used.
+    ///
+    /// [`InputConfig`]: kas::event::InputConfig
+    pub fn with_scroll_override(mut self, scroll: ScrollOverride) -> Self {
+        self.scroll_override = scroll;
+        self
+    }
+
     /// Set whether this `EditBox` is editable (inline)
     pub fn editable(mut self, editable: bool) -> Self {
         self.editable = editable;
@@ -417,6 +434,52 @@ impl<G> EditBox<G> {
         range
     }
 
+    /// Length of `content` to paste
+    fn paste_len(&self, content: &str) -> usize {
+        if !self.multi_line {
+            // We cut the content short on control characters and
+            // ignore them (preventing line-breaks and ignoring any
+            // actions such as recursive-paste).
+            for (i, c) in content.char_indices() {
+                if c < '\u{20}' || (c >= '\u{7f}' && c <= '\u{9f}') {
+                    return i;
+                }
+            }
+        }
+        content.len()
+    }
+
+    /// Paste the primary selection at `coord` (middle-click paste)
+    fn paste_primary(&mut self, mgr: &mut Manager, coord: Coord) -> EditAction {
+        if !self.editable {
+            return EditAction::Unhandled;
+        }
+        let content = match mgr.get_primary() {
+            Some(content) => content,
+            None => return EditAction::None,
+        };
+        let s = &content[0..self.paste_len(&content)];
+
+        self.undo
+            .push((self.text.clone_string(), self.edit_pos, self.sel_pos));
+        self.set_edit_pos_from_coord(mgr, coord);
+        let pos = self.edit_pos;
+        let _ = self.text.replace_range(pos..pos, s);
+        self.edit_pos = pos + s.len();
+        self.sel_pos = self.edit_pos;
+        self.text.prepare();
+        self.set_view_offset_from_edit_pos();
+        EditAction::Edit
+    }
+
+    /// Set the primary selection from the current selection, if any
+    fn set_primary(&self, mgr: &mut Manager) {
+        let selection = self.selection();
+        if selection.start < selection.end {
+            mgr.set_primary((self.text.text()[selection]).into());
+        }
+    }
+
     fn received_char(&mut self, mgr: &mut Manager, c: char) -> EditAction {
         if !self.editable {
             return EditAction::Unhandled;
@@ -425,9 +488,13 @@ impl<G> EditBox<G> {
         let pos = self.edit_pos;
         let selection = self.selection();
         let have_sel = selection.start < selection.end;
-        if self.last_edit != LastEdit::Insert || have_sel {
-            self.old_state = Some((self.text.clone_string(), pos, self.sel_pos));
-            self.last_edit = LastEdit::Insert;
+        let text = &self.text;
+        let sel_pos = self.sel_pos;
+        let state = || (text.clone_string(), pos, sel_pos);
+        if have_sel {
+            self.undo.push(state());
+        } else {
+            self.undo.push_merge(LastEdit::Insert as u32, state);
         }
         if have_sel {
             let mut buf = [0u8; 4];
@@ -657,19 +724,7 @@ impl<G> EditBox<G> {
             }
             ControlKey::Paste => {
                 if let Some(content) = mgr.get_clipboard() {
-                    let mut end = content.len();
-                    if !self.multi_line {
-                        // We cut the content short on control characters and
-                        // ignore them (preventing line-breaks and ignoring any
-                        // actions such as recursive-paste).
-                        for (i, c) in content.char_indices() {
-                            if c < '\u{20}' || (c >= '\u{7f}' && c <= '\u{9f}') {
-                                end = i;
-                                break;
-                            }
-                        }
-                    }
-
+                    let end = self.paste_len(&content);
                     string = content;
                     Action::Insert(&string[0..end], LastEdit::Paste)
                 } else {
@@ -677,17 +732,16 @@ impl<G> EditBox<G> {
                 }
             }
             ControlKey::Undo | ControlKey::Redo => {
-                // TODO: maintain full edit history (externally?)
-                // NOTE: undo *and* redo shortcuts map to this control char
-                if let Some((state, pos2, sel_pos)) = self.old_state.as_mut() {
-                    prep_action |= self.text.swap_string(state);
-                    self.edit_pos = *pos2;
-                    *pos2 = pos;
-                    std::mem::swap(sel_pos, &mut self.sel_pos);
+                let current = (self.text.clone_string(), pos, self.sel_pos);
+                if let Some((mut state, pos2, sel_pos)) = self.undo.handle_key(key, current) {
+                    prep_action |= self.text.swap_string(&mut state);
+                    self.edit_pos = pos2;
+                    self.sel_pos = sel_pos;
                     self.edit_x_coord = None;
-                    self.last_edit = LastEdit::None;
+                    Action::Edit
+                } else {
+                    Action::None
                 }
-                Action::Edit
             }
             _ => Action::Unhandled,
         };
@@ -699,18 +753,18 @@ impl<G> EditBox<G> {
             Action::Edit => EditAction::Edit,
             Action::Insert(s, edit) => {
                 let mut pos = pos;
+                let text = &self.text;
+                let sel_pos = self.sel_pos;
+                let state = || (text.clone_string(), pos, sel_pos);
+                if have_sel || edit == LastEdit::Paste {
+                    self.undo.push(state());
+                } else {
+                    self.undo.push_merge(edit as u32, state);
+                }
                 if have_sel {
-                    self.old_state = Some((self.text.clone_string(), pos, self.sel_pos));
-                    self.last_edit = edit;
-
                     prep_action |= self.text.replace_range(selection.clone(), s);
                     pos = selection.start;
                 } else {
-                    if self.last_edit != edit {
-                        self.old_state = Some((self.text.clone_string(), pos, self.sel_pos));
-                        self.last_edit = edit;
-                    }
-
                     prep_action |= self.text.replace_range(pos..pos, s);
                 }
                 self.edit_pos = pos + s.len();
@@ -719,10 +773,10 @@ impl<G> EditBox<G> {
                 EditAction::Edit
             }
             Action::Delete(sel) => {
-                if self.last_edit != LastEdit::Delete {
-                    self.old_state = Some((self.text.clone_string(), pos, self.sel_pos));
-                    self.last_edit = LastEdit::Delete;
-                }
+                let text = &self.text;
+                let sel_pos = self.sel_pos;
+                let state = || (text.clone_string(), pos, sel_pos);
+                self.undo.push_merge(LastEdit::Delete as u32, state);
 
                 prep_action |= self.text.replace_range(sel.clone(), "");
                 self.edit_pos = sel.start;
@@ -731,9 +785,12 @@ impl<G> EditBox<G> {
                 EditAction::Edit
             }
             Action::Move(pos, x_coord) => {
+                self.undo.end_merge();
                 self.edit_pos = pos;
                 if !shift {
                     self.sel_pos = self.edit_pos;
+                } else {
+                    self.set_primary(mgr);
                 }
                 self.edit_x_coord = x_coord;
                 mgr.redraw(self.id());
@@ -854,7 +911,12 @@ impl<G: EditGuard> HasString for EditBox<G> {
     fn set_string(&mut self, text: String) -> TkAction {
         let action = self.text.set_and_prepare(text);
         let _ = G::edit(self);
-        action
+        if action == TkAction::None {
+            return action;
+        }
+        // Multi-line boxes may change height
+        self.core.layout.set_dirty();
+        TkAction::Relayout
     }
 }
 
@@ -884,6 +946,18 @@ impl<G: EditGuard + 'static> event::Handler for EditBox<G> {
                 EditAction::Activate => G::activate(self).into(),
                 EditAction::Edit => G::edit(self).into(),
             },
+            Event::PressStart {
+                source: PressSource::Mouse(MouseButton::Middle, _),
+                coord,
+                ..
+            } => {
+                mgr.request_char_focus(self.id());
+                match self.paste_primary(mgr, coord) {
+                    EditAction::None | EditAction::Unhandled => Response::None,
+                    EditAction::Activate => G::activate(self).into(),
+                    EditAction::Edit => G::edit(self).into(),
+                }
+            }
             Event::PressStart { source, coord, .. } if source.is_primary() => {
                 if let PressSource::Touch(touch_id) = source {
                     if self.touch_phase == TouchPhase::None {
@@ -960,13 +1034,14 @@ impl<G: EditGuard + 'static> event::Handler for EditBox<G> {
                     }
                     _ => (),
                 }
+                // Set only once the selection is complete, not on each move
+                self.set_primary(mgr);
                 Response::None
             }
             Event::Scroll(delta) => {
-                let delta2 = match delta {
+                let delta2 = match self.scroll_override.apply(mgr.config(), delta) {
                     ScrollDelta::LineDelta(x, y) => {
-                        // We arbitrarily scroll 3 lines:
-                        let dist = 3.0 * self.text.env().line_height(Default::default());
+                        let dist = self.text.env().line_height(Default::default());
                         let x = (x * dist).round() as i32;
                         let y = (y * dist).round() as i32;
                         Coord(x, y)
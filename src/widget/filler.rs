@@ -10,17 +10,21 @@ use kas::prelude::*;
 /// A space filler
 ///
 /// This widget has zero minimum size but can expand according to the given
-/// stretch policy.
+/// stretch policy. Where several fillers (or other widgets with the same
+/// policy) share a row or column, excess space is divided according to their
+/// weights; for example a button may be pushed to the right edge of a row by
+/// preceding it with a filler, or centred by placing a filler on each side.
 #[derive(Clone, Debug, Default, Widget)]
 pub struct Filler {
     #[widget_core]
     core: CoreData,
     policy: StretchPolicy,
+    weight: u32,
 }
 
 impl Layout for Filler {
     fn size_rules(&mut self, _: &mut dyn SizeHandle, _: AxisInfo) -> SizeRules {
-        SizeRules::empty(self.policy)
+        SizeRules::empty(self.policy).with_weight(self.weight)
     }
 
     fn draw(&self, _: &mut dyn DrawHandle, _: &event::ManagerState, _: bool) {}
@@ -37,11 +41,28 @@ impl Filler {
         Filler::with_policy(StretchPolicy::Maximise)
     }
 
+    /// Construct a filler with policy [`StretchPolicy::Filler`] and the given
+    /// stretch `weight`
+    ///
+    /// See [`SizeRules::with_weight`].
+    pub fn stretch(weight: u32) -> Self {
+        Filler::new().with_weight(weight)
+    }
+
     /// Construct with a custom stretch policy
     pub fn with_policy(policy: StretchPolicy) -> Self {
         Filler {
             core: Default::default(),
             policy,
+            weight: 1,
         }
     }
+
+    /// Set the stretch weight (chain style)
+    ///
+    /// See [`SizeRules::with_weight`].
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
 }
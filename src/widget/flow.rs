@@ -0,0 +1,206 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! A wrapping row with run-time adjustable contents
+
+use std::ops::{Index, IndexMut};
+
+use kas::layout::{RulesSetter, RulesSolver};
+use kas::prelude::*;
+
+/// A flow of boxed widgets
+///
+/// This is parameterised over handler message type.
+///
+/// See documentation of [`FlowLayout`] type.
+pub type BoxFlowLayout<M> = FlowLayout<Box<dyn Widget<Msg = M>>>;
+
+/// A generic flow (wrapping row) widget
+///
+/// Child widgets are placed left-to-right at their ideal size, wrapping to a
+/// new row when out of width. The height required thus depends on the width
+/// available. This is useful for e.g. tag clouds, toolbars and button trays.
+///
+/// Rows are aligned according to the horizontal alignment hint passed by the
+/// parent (see [`AlignHints`]).
+///
+/// For fixed configurations of child widgets, [`make_widget`] may be used with
+/// `#[layout(flow)]` instead.
+///
+/// [`make_widget`]: ../macros/index.html#the-make_widget-macro
+#[handler(send=noauto, msg=<W as event::Handler>::Msg)]
+#[widget(children=noauto)]
+#[derive(Clone, Default, Debug, Widget)]
+pub struct FlowLayout<W: Widget> {
+    #[widget_core]
+    core: CoreData,
+    widgets: Vec<W>,
+    data: layout::FlowStorage,
+}
+
+impl<W: Widget> WidgetChildren for FlowLayout<W> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.widgets.len()
+    }
+    #[inline]
+    fn get(&self, index: usize) -> Option<&dyn WidgetConfig> {
+        self.widgets.get(index).map(|w| w.as_widget())
+    }
+    #[inline]
+    fn get_mut(&mut self, index: usize) -> Option<&mut dyn WidgetConfig> {
+        self.widgets.get_mut(index).map(|w| w.as_widget_mut())
+    }
+}
+
+impl<W: Widget> Layout for FlowLayout<W> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let len = self.widgets.len();
+        let mut solver = layout::FlowSolver::new(axis, len, &mut self.data);
+        for (n, child) in self.widgets.iter_mut().enumerate() {
+            solver.for_child(&mut self.data, n, |axis| {
+                child.size_rules(size_handle, axis)
+            });
+        }
+        solver.finish(&mut self.data)
+    }
+
+    fn set_rect(&mut self, rect: Rect, align: AlignHints) {
+        self.core.rect = rect;
+        let len = self.widgets.len();
+        let mut setter = layout::FlowSetter::new(rect, len, align, &mut self.data);
+
+        for (n, child) in self.widgets.iter_mut().enumerate() {
+            let align = AlignHints::default();
+            child.set_rect(setter.child_rect(&mut self.data, n), align);
+        }
+    }
+
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        if !self.rect().contains(coord) {
+            return None;
+        }
+
+        for child in &self.widgets {
+            if let Some(id) = child.find_id(coord) {
+                return Some(id);
+            }
+        }
+
+        Some(self.id())
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
+        let disabled = disabled || self.is_disabled();
+        let rect = draw_handle.target_rect();
+        let (pos0, pos1) = (rect.pos, rect.pos_end());
+        for child in &self.widgets {
+            let (c0, c1) = (child.rect().pos, child.rect().pos_end());
+            if c0.0 <= pos1.0 && c1.0 >= pos0.0 && c0.1 <= pos1.1 && c1.1 >= pos0.1 {
+                child.draw(draw_handle, mgr, disabled);
+            }
+        }
+    }
+}
+
+impl<W: Widget> event::SendEvent for FlowLayout<W> {
+    fn send(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
+        if !self.is_disabled() {
+            for child in &mut self.widgets {
+                if id <= child.id() {
+                    return child.send(mgr, id, event);
+                }
+            }
+        }
+
+        Response::Unhandled(event)
+    }
+}
+
+impl<W: Widget> FlowLayout<W> {
+    /// Construct a new instance
+    pub fn new(widgets: Vec<W>) -> Self {
+        FlowLayout {
+            core: Default::default(),
+            widgets,
+            data: Default::default(),
+        }
+    }
+
+    /// True if there are no child widgets
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
+
+    /// Returns the number of child widgets
+    pub fn len(&self) -> usize {
+        self.widgets.len()
+    }
+
+    /// Remove all child widgets
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action) if any widget is
+    /// removed.
+    pub fn clear(&mut self) -> TkAction {
+        let action = match self.widgets.is_empty() {
+            true => TkAction::None,
+            false => TkAction::Reconfigure,
+        };
+        self.widgets.clear();
+        action
+    }
+
+    /// Append a child widget
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action).
+    pub fn push(&mut self, widget: W) -> TkAction {
+        self.widgets.push(widget);
+        TkAction::Reconfigure
+    }
+
+    /// Remove the last child widget
+    ///
+    /// Returns `None` if there are no children. Otherwise, this
+    /// triggers a reconfigure before the next draw operation.
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action) if any widget is
+    /// removed.
+    pub fn pop(&mut self) -> (Option<W>, TkAction) {
+        let action = match self.widgets.is_empty() {
+            true => TkAction::None,
+            false => TkAction::Reconfigure,
+        };
+        (self.widgets.pop(), action)
+    }
+
+    /// Removes the child widget at position `index`
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action).
+    pub fn remove(&mut self, index: usize) -> (W, TkAction) {
+        let r = self.widgets.remove(index);
+        (r, TkAction::Reconfigure)
+    }
+
+    /// Iterate over children
+    pub fn iter(&self) -> std::slice::Iter<W> {
+        self.widgets.iter()
+    }
+}
+
+impl<W: Widget> Index<usize> for FlowLayout<W> {
+    type Output = W;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.widgets[index]
+    }
+}
+
+impl<W: Widget> IndexMut<usize> for FlowLayout<W> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.widgets[index]
+    }
+}
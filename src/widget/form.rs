@@ -0,0 +1,438 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Forms generated from a schema
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{BoxGrid, CheckBoxBare, EditBox, EditGuard, Label};
+use kas::class::{HasBool, HasString};
+use kas::prelude::*;
+
+/// The kind of a [`FormField`], determining its input widget and validation
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldKind {
+    /// Free text
+    Text,
+    /// An integer, with optional inclusive bounds
+    Integer { min: Option<i64>, max: Option<i64> },
+    /// A floating-point number, with optional inclusive bounds
+    Number { min: Option<f64>, max: Option<f64> },
+    /// A boolean, shown as a check box
+    Boolean,
+}
+
+impl FieldKind {
+    /// Parse and validate the text of an input field
+    ///
+    /// Empty input is not handled here (see [`FormField::required`]).
+    pub fn parse(&self, text: &str) -> Result<FormValue, String> {
+        let text = text.trim();
+        match *self {
+            FieldKind::Text => Ok(FormValue::Text(text.to_string())),
+            FieldKind::Integer { min, max } => {
+                let x: i64 = text.parse().map_err(|_| "not an integer".to_string())?;
+                check_bounds(x, min, max).map(|_| FormValue::Integer(x))
+            }
+            FieldKind::Number { min, max } => {
+                let x: f64 = text.parse().map_err(|_| "not a number".to_string())?;
+                if !x.is_finite() {
+                    return Err("not a number".to_string());
+                }
+                check_bounds(x, min, max).map(|_| FormValue::Number(x))
+            }
+            FieldKind::Boolean => match text {
+                "true" => Ok(FormValue::Boolean(true)),
+                "false" => Ok(FormValue::Boolean(false)),
+                _ => Err("not a boolean".to_string()),
+            },
+        }
+    }
+}
+
+fn check_bounds<T: PartialOrd + fmt::Display>(
+    x: T,
+    min: Option<T>,
+    max: Option<T>,
+) -> Result<(), String> {
+    match (min, max) {
+        (Some(min), _) if x < min => Err(format!("must be at least {}", min)),
+        (_, Some(max)) if x > max => Err(format!("must be at most {}", max)),
+        _ => Ok(()),
+    }
+}
+
+/// A value collected from a [`Form`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum FormValue {
+    Text(String),
+    Integer(i64),
+    Number(f64),
+    Boolean(bool),
+}
+
+impl fmt::Display for FormValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            FormValue::Text(s) => write!(f, "{}", s),
+            FormValue::Integer(x) => write!(f, "{}", x),
+            FormValue::Number(x) => write!(f, "{}", x),
+            FormValue::Boolean(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// Description of a single field of a [`FormSchema`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormField {
+    /// Key under which the field's value is reported
+    pub name: String,
+    /// Label text (defaults to `name`)
+    pub label: String,
+    /// Kind of input
+    pub kind: FieldKind,
+    /// If true, the field may not be left empty
+    pub required: bool,
+    /// Initial value
+    pub default: Option<FormValue>,
+}
+
+impl FormField {
+    /// Construct a field of the given `kind`
+    pub fn new<S: Into<String>>(name: S, kind: FieldKind) -> Self {
+        let name = name.into();
+        FormField {
+            label: name.clone(),
+            name,
+            kind,
+            required: false,
+            default: None,
+        }
+    }
+
+    /// Set the label
+    pub fn with_label<S: Into<String>>(mut self, label: S) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    /// Mark the field as required
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Set the initial value
+    pub fn with_default(mut self, value: FormValue) -> Self {
+        self.default = Some(value);
+        self
+    }
+}
+
+/// Error from [`FormSchema::from_json_schema`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaError(String);
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "unsupported schema: {}", self.0)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// A data description from which a [`Form`] is generated
+///
+/// This is a flat list of [`FormField`]s, constructed either directly or (with
+/// feature `json`) from a JSON Schema via [`FormSchema::from_json_schema`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FormSchema {
+    pub fields: Vec<FormField>,
+}
+
+impl FormSchema {
+    /// Construct an empty schema
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a field
+    pub fn with(mut self, field: FormField) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Construct from a JSON Schema (feature `json`)
+    ///
+    /// The schema must describe an `object`; each of its `properties` becomes
+    /// a field. Supported property types are `string`, `integer`, `number`
+    /// and `boolean`, with keywords `title`, `minimum`, `maximum` and
+    /// `default`; other keywords are ignored. Fields listed under `required`
+    /// are marked as such.
+    ///
+    /// Fields appear in the order of `properties` as given by `serde_json`,
+    /// which is alphabetical unless its `preserve_order` feature is enabled.
+    #[cfg(feature = "json")]
+    pub fn from_json_schema(schema: &serde_json::Value) -> Result<Self, SchemaError> {
+        use serde_json::Value;
+
+        if schema.get("type").and_then(Value::as_str) != Some("object") {
+            return Err(SchemaError("expected type \"object\"".to_string()));
+        }
+        let required: Vec<&str> = match schema.get("required") {
+            Some(Value::Array(list)) => list.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        let properties = match schema.get("properties") {
+            Some(Value::Object(map)) => map,
+            _ => return Err(SchemaError("expected \"properties\" object".to_string())),
+        };
+
+        let mut fields = Vec::with_capacity(properties.len());
+        for (name, prop) in properties {
+            let ty = prop.get("type").and_then(Value::as_str);
+            let (kind, default) = match ty {
+                Some("string") => (
+                    FieldKind::Text,
+                    prop.get("default")
+                        .and_then(Value::as_str)
+                        .map(|s| FormValue::Text(s.to_string())),
+                ),
+                Some("integer") => (
+                    FieldKind::Integer {
+                        min: prop.get("minimum").and_then(Value::as_i64),
+                        max: prop.get("maximum").and_then(Value::as_i64),
+                    },
+                    prop.get("default")
+                        .and_then(Value::as_i64)
+                        .map(FormValue::Integer),
+                ),
+                Some("number") => (
+                    FieldKind::Number {
+                        min: prop.get("minimum").and_then(Value::as_f64),
+                        max: prop.get("maximum").and_then(Value::as_f64),
+                    },
+                    prop.get("default")
+                        .and_then(Value::as_f64)
+                        .map(FormValue::Number),
+                ),
+                Some("boolean") => (
+                    FieldKind::Boolean,
+                    prop.get("default")
+                        .and_then(Value::as_bool)
+                        .map(FormValue::Boolean),
+                ),
+                _ => {
+                    let msg = format!("property \"{}\" has unsupported type", name);
+                    return Err(SchemaError(msg));
+                }
+            };
+            let label = prop.get("title").and_then(Value::as_str).unwrap_or(name);
+            fields.push(FormField {
+                name: name.clone(),
+                label: label.to_string(),
+                kind,
+                required: required.contains(&name.as_str()),
+                default,
+            });
+        }
+        Ok(FormSchema { fields })
+    }
+}
+
+/// A validation error of a [`Form`] field
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldError {
+    /// The field's [`FormField::name`]
+    pub field: String,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// [`EditGuard`] validating input against a [`FieldKind`]
+///
+/// Invalid (non-empty) input is indicated via the error state.
+#[derive(Clone, Debug)]
+struct FieldGuard(FieldKind);
+
+impl EditGuard for FieldGuard {
+    type Msg = VoidMsg;
+
+    fn edit(edit: &mut EditBox<Self>) -> Option<VoidMsg> {
+        let text = edit.get_str();
+        let error = !text.trim().is_empty() && edit.guard.0.parse(text).is_err();
+        edit.set_error_state(error);
+        None
+    }
+}
+
+/// A form generated from a [`FormSchema`]
+///
+/// Each field is shown as a row with a label and an input widget: a check box
+/// for [`FieldKind::Boolean`] and an [`EditBox`] otherwise. Edit boxes are
+/// validated as the user types. Values are collected via [`Form::values`].
+///
+/// ```
+/// use kas::widget::{FieldKind, Form, FormField, FormSchema, FormValue};
+///
+/// let schema = FormSchema::new()
+///     .with(FormField::new("name", FieldKind::Text).required())
+///     .with(FormField::new("age", FieldKind::Integer { min: Some(0), max: None })
+///         .with_default(FormValue::Integer(30)));
+/// let form = Form::new(schema);
+/// assert!(form.values().is_err()); // "name" is required
+/// ```
+#[layout(single)]
+#[handler(msg = VoidMsg)]
+#[derive(Debug, Widget)]
+pub struct Form {
+    #[widget_core]
+    core: CoreData,
+    #[widget]
+    grid: BoxGrid<VoidMsg>,
+    schema: FormSchema,
+}
+
+impl Form {
+    /// Construct from a schema
+    pub fn new(schema: FormSchema) -> Self {
+        let mut grid = BoxGrid::new().with_gap(8, 4);
+        for (row, field) in schema.fields.iter().enumerate() {
+            let row = row as u32;
+            let mut label = field.label.clone();
+            if field.required {
+                label.push_str(" *");
+            }
+            grid = grid.with(Box::new(Label::new(label)), 0, row, (1, 1));
+            let input: Box<dyn Widget<Msg = VoidMsg>> = match field.kind {
+                FieldKind::Boolean => {
+                    let state = field.default == Some(FormValue::Boolean(true));
+                    Box::new(CheckBoxBare::new().state(state))
+                }
+                ref kind => {
+                    let text = field.default.as_ref().map(|v| v.to_string());
+                    let edit = EditBox::new(text.unwrap_or_default());
+                    Box::new(edit.with_guard(FieldGuard(kind.clone())))
+                }
+            };
+            grid = grid.with(input, 1, row, (1, 1));
+        }
+        Form {
+            core: Default::default(),
+            grid,
+            schema,
+        }
+    }
+
+    /// Access the schema
+    pub fn schema(&self) -> &FormSchema {
+        &self.schema
+    }
+
+    /// Collect and validate values
+    ///
+    /// On success, returns a map from each field's name to its value; empty
+    /// optional fields are omitted. Otherwise, returns an error for each
+    /// invalid field, in order.
+    pub fn values(&self) -> Result<HashMap<String, FormValue>, Vec<FieldError>> {
+        let mut values = HashMap::new();
+        let mut errors = vec![];
+        for (i, field) in self.schema.fields.iter().enumerate() {
+            let input = self.grid[2 * i + 1].as_any();
+            let result = if let Some(check) = input.downcast_ref::<CheckBoxBare<VoidMsg>>() {
+                Ok(Some(FormValue::Boolean(check.get_bool())))
+            } else if let Some(edit) = input.downcast_ref::<EditBox<FieldGuard>>() {
+                let text = edit.get_str();
+                if text.trim().is_empty() {
+                    match field.required {
+                        true => Err("required".to_string()),
+                        false => Ok(None),
+                    }
+                } else {
+                    field.kind.parse(text).map(Some)
+                }
+            } else {
+                unreachable!()
+            };
+            match result {
+                Ok(Some(value)) => {
+                    values.insert(field.name.clone(), value);
+                }
+                Ok(None) => (),
+                Err(message) => errors.push(FieldError {
+                    field: field.name.clone(),
+                    message,
+                }),
+            }
+        }
+        if errors.is_empty() {
+            Ok(values)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Set the value of the field named `name`
+    ///
+    /// Does nothing if there is no such field or the value's type does not
+    /// match the field. The value is not validated.
+    pub fn set_value(&mut self, name: &str, value: &FormValue) -> TkAction {
+        let index = match self.schema.fields.iter().position(|f| f.name == name) {
+            Some(index) => index,
+            None => return TkAction::None,
+        };
+        let input = self.grid[2 * index + 1].as_any_mut();
+        match value {
+            FormValue::Boolean(b) => match input.downcast_mut::<CheckBoxBare<VoidMsg>>() {
+                Some(check) => check.set_bool(*b),
+                None => TkAction::None,
+            },
+            value => match input.downcast_mut::<EditBox<FieldGuard>>() {
+                Some(edit) => edit.set_string(value.to_string()),
+                None => TkAction::None,
+            },
+        }
+    }
+}
+
+#[test]
+fn form_values() {
+    // Text is shaped when set, which requires a font
+    kas::text::fonts().load_default().unwrap();
+
+    let schema = FormSchema::new()
+        .with(FormField::new("name", FieldKind::Text).required())
+        .with(FormField::new("note", FieldKind::Text))
+        .with(FormField::new(
+            "level",
+            FieldKind::Integer {
+                min: Some(1),
+                max: Some(10),
+            },
+        ))
+        .with(FormField::new("ok", FieldKind::Boolean).with_default(FormValue::Boolean(true)));
+    let mut form = Form::new(schema);
+
+    let errors = form.values().unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].field, "name");
+
+    let _ = form.set_value("name", &FormValue::Text("x".to_string()));
+    let _ = form.set_value("level", &FormValue::Integer(11));
+    assert_eq!(form.values().unwrap_err()[0].message, "must be at most 10");
+
+    let _ = form.set_value("level", &FormValue::Integer(3));
+    let values = form.values().unwrap();
+    assert_eq!(values.len(), 3);
+    assert_eq!(values["level"], FormValue::Integer(3));
+    assert_eq!(values["ok"], FormValue::Boolean(true));
+}
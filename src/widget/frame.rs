@@ -12,6 +12,9 @@ use kas::prelude::*;
 ///
 /// This widget provides a simple abstraction: drawing a frame around its
 /// contents.
+///
+/// By default the frame fills the space it is given and alignment hints are
+/// passed to its content. See [`Frame::fit_content`].
 #[handler(msg = <W as Handler>::Msg)]
 #[derive(Clone, Debug, Default, Widget)]
 pub struct Frame<W: Widget> {
@@ -21,6 +24,8 @@ pub struct Frame<W: Widget> {
     child: W,
     m0: Size,
     m1: Size,
+    ideal: Size,
+    fit: bool,
 }
 
 impl<W: Widget> Frame<W> {
@@ -32,8 +37,21 @@ impl<W: Widget> Frame<W> {
             child,
             m0: Size::ZERO,
             m1: Size::ZERO,
+            ideal: Size::ZERO,
+            fit: false,
         }
     }
+
+    /// Fit the frame to its content
+    ///
+    /// If enabled, alignment hints apply to the frame itself: on each axis
+    /// with a hint (other than stretch), the frame is sized to its ideal size
+    /// within surplus space and aligned accordingly.
+    #[inline]
+    pub fn fit_content(mut self, fit: bool) -> Self {
+        self.fit = fit;
+        self
+    }
 }
 
 impl<W: Widget> Layout for Frame<W> {
@@ -42,7 +60,12 @@ impl<W: Widget> Layout for Frame<W> {
         let margins = Margins::ZERO;
         let frame_rules = SizeRules::extract_fixed(axis.is_vertical(), size + size, margins);
 
-        let child_rules = self.child.size_rules(size_handle, axis);
+        let mut child_axis = axis;
+        if axis.is_vertical() {
+            // self.m0.0, self.m1.0 were set when sizing the horizontal axis
+            child_axis.sub_other(self.m0.0 + self.m1.0);
+        }
+        let child_rules = self.child.size_rules(size_handle, child_axis);
         let m = child_rules.margins();
 
         if axis.is_horizontal() {
@@ -53,10 +76,21 @@ impl<W: Widget> Layout for Frame<W> {
             self.m1.1 = size.1 + m.1 as u32;
         }
 
-        child_rules.surrounded_by(frame_rules, true)
+        let rules = child_rules.surrounded_by(frame_rules, true);
+        if axis.is_horizontal() {
+            self.ideal.0 = rules.ideal_size();
+        } else {
+            self.ideal.1 = rules.ideal_size();
+        }
+        rules
     }
 
     fn set_rect(&mut self, mut rect: Rect, align: AlignHints) {
+        if self.fit {
+            rect = align
+                .complete(Align::Stretch, Align::Stretch, self.ideal)
+                .apply(rect);
+        }
         self.core.rect = rect;
         rect.pos += self.m0;
         rect.size -= self.m0 + self.m1;
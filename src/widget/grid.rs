@@ -0,0 +1,387 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! A grid with run-time adjustable contents
+
+use std::ops::{Index, IndexMut};
+
+use kas::layout::{GridChildInfo, RulesSetter, RulesSolver};
+use kas::prelude::*;
+
+/// A grid of boxed widgets
+///
+/// This is parameterised over handler message type.
+///
+/// See documentation of [`Grid`] type.
+pub type BoxGrid<M> = Grid<Box<dyn Widget<Msg = M>>>;
+
+/// A generic grid widget
+///
+/// Each child is placed at a given column and row, optionally spanning
+/// multiple cells. The number of columns and rows is determined by the
+/// placement of children. Cells may be left empty, but children should not
+/// overlap.
+///
+/// Alternatively, children may be placed automatically via
+/// [`Grid::add_auto`] in the next free cell, filling rows of
+/// [`Grid::with_columns`] cells left-to-right. Minimum gaps between columns
+/// and rows may be set via [`Grid::with_gap`].
+///
+/// This provides run-time construction of grid layouts, without use of
+/// macros:
+/// ```
+/// use kas::event::VoidMsg;
+/// use kas::widget::{BoxGrid, Filler, Label};
+///
+/// let grid: BoxGrid<VoidMsg> = BoxGrid::new()
+///     .with(Box::new(Label::new("Name")), 0, 0, (1, 1))
+///     .with(Box::new(Label::new("Value")), 1, 0, (1, 1))
+///     .with(Box::new(Filler::new()), 0, 1, (2, 1));
+/// assert_eq!(grid.dim(), (2, 2));
+/// ```
+///
+/// For fixed configurations of child widgets, [`make_widget`] may be used with
+/// `#[layout(grid)]` instead.
+///
+/// [`make_widget`]: ../macros/index.html#the-make_widget-macro
+#[handler(send=noauto, msg=<W as event::Handler>::Msg)]
+#[widget(children=noauto)]
+#[derive(Clone, Default, Debug, Widget)]
+pub struct Grid<W: Widget> {
+    #[widget_core]
+    core: CoreData,
+    widgets: Vec<W>,
+    info: Vec<GridChildInfo>,
+    /// Number of columns and rows
+    dim: (usize, usize),
+    /// Number of columns used by automatic placement
+    auto_cols: Option<u32>,
+    /// Next cell considered by automatic placement
+    cursor: (u32, u32),
+    gap: (u16, u16),
+    data: layout::DynGridStorage,
+}
+
+/// Margins implementing `gap` on internal edges of the cell(s) of `info`
+fn gap_margins(
+    gap: (u16, u16),
+    dim: (usize, usize),
+    info: &GridChildInfo,
+    axis: AxisInfo,
+    scale_factor: f32,
+) -> (u16, u16) {
+    let (gap, begin, end, len) = match axis.is_vertical() {
+        false => (gap.0, info.col, info.col_end, dim.0),
+        true => (gap.1, info.row, info.row_end, dim.1),
+    };
+    let gap = (gap as f32 * scale_factor).round() as u16;
+    let m0 = if begin > 0 { gap } else { 0 };
+    let m1 = if (end as usize) < len { gap } else { 0 };
+    (m0, m1)
+}
+
+impl<W: Widget> WidgetChildren for Grid<W> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.widgets.len()
+    }
+    #[inline]
+    fn get(&self, index: usize) -> Option<&dyn WidgetConfig> {
+        self.widgets.get(index).map(|w| w.as_widget())
+    }
+    #[inline]
+    fn get_mut(&mut self, index: usize) -> Option<&mut dyn WidgetConfig> {
+        self.widgets.get_mut(index).map(|w| w.as_widget_mut())
+    }
+}
+
+impl<W: Widget> Layout for Grid<W> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        if let Some(rules) = self.core.layout.get(axis) {
+            return rules;
+        }
+
+        let span = (SizeRules::EMPTY, 0, 0);
+        let col_spans = self.info.iter().filter(|i| i.col_end > i.col + 1);
+        let row_spans = self.info.iter().filter(|i| i.row_end > i.row + 1);
+        let spans = (vec![span; col_spans.count()], vec![span; row_spans.count()]);
+        let mut solver = layout::GridSolver::<Vec<_>, Vec<_>, _>::new_with_spans(
+            axis,
+            self.dim,
+            spans,
+            &mut self.data,
+        );
+        let (gap, dim) = (self.gap, self.dim);
+        let scale_factor = size_handle.scale_factor();
+        for (child, info) in self.widgets.iter_mut().zip(self.info.iter()) {
+            solver.for_child(&mut self.data, *info, |axis| {
+                let mut rules = child.size_rules(size_handle, axis);
+                rules.include_margins(gap_margins(gap, dim, info, axis, scale_factor));
+                rules
+            });
+        }
+        let rules = solver.finish(&mut self.data);
+        self.core.layout.set(axis, rules);
+        rules
+    }
+
+    fn set_rect(&mut self, rect: Rect, align: AlignHints) {
+        self.core.rect = rect;
+        let mut setter =
+            layout::GridSetter::<Vec<u32>, Vec<u32>, _>::new(rect, self.dim, align, &mut self.data);
+
+        for (child, info) in self.widgets.iter_mut().zip(self.info.iter()) {
+            let align = AlignHints::default();
+            child.set_rect(setter.child_rect(&mut self.data, *info), align);
+        }
+    }
+
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        if !self.rect().contains(coord) {
+            return None;
+        }
+
+        for child in &self.widgets {
+            if let Some(id) = child.find_id(coord) {
+                return Some(id);
+            }
+        }
+
+        Some(self.id())
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
+        let disabled = disabled || self.is_disabled();
+        for child in &self.widgets {
+            child.draw(draw_handle, mgr, disabled);
+        }
+    }
+}
+
+impl<W: Widget> event::SendEvent for Grid<W> {
+    fn send(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
+        if !self.is_disabled() {
+            for child in &mut self.widgets {
+                if id <= child.id() {
+                    return child.send(mgr, id, event);
+                }
+            }
+        }
+
+        Response::Unhandled(event)
+    }
+}
+
+impl<W: Widget> Grid<W> {
+    /// Construct a new, empty instance
+    pub fn new() -> Self {
+        Grid {
+            core: Default::default(),
+            widgets: vec![],
+            info: vec![],
+            dim: (0, 0),
+            auto_cols: None,
+            cursor: (0, 0),
+            gap: (0, 0),
+            data: Default::default(),
+        }
+    }
+
+    /// Set the number of columns used by automatic placement (chain style)
+    ///
+    /// See [`Grid::add_auto`]. If not set, automatically placed children
+    /// are appended to the first row.
+    pub fn with_columns(mut self, cols: u32) -> Self {
+        assert!(cols > 0, "Grid::with_columns: cols must be non-zero");
+        self.auto_cols = Some(cols);
+        self
+    }
+
+    /// Set the minimum gaps between columns and rows (chain style)
+    ///
+    /// Gaps are in unscaled pixels and act as margins on internal edges of
+    /// cells (thus where theme margins are larger, these are used instead).
+    pub fn with_gap(mut self, col_gap: u16, row_gap: u16) -> Self {
+        self.gap = (col_gap, row_gap);
+        self
+    }
+
+    /// Add a child widget in the next free cell (chain style)
+    ///
+    /// See [`Grid::add_auto`].
+    pub fn with_auto(mut self, widget: W, span: (u32, u32)) -> Self {
+        let _ = self.add_auto(widget, span);
+        self
+    }
+
+    /// Add a child widget (chain style)
+    ///
+    /// See [`Grid::add_at`].
+    pub fn with(mut self, widget: W, col: u32, row: u32, span: (u32, u32)) -> Self {
+        let _ = self.add_at(widget, col, row, span);
+        self
+    }
+
+    fn update_dim(&mut self) {
+        self.dim = self.info.iter().fold((0, 0), |(cols, rows), info| {
+            let cols = cols.max(info.col_end as usize);
+            (cols, rows.max(info.row_end as usize))
+        });
+    }
+
+    /// Get the number of columns and rows
+    pub fn dim(&self) -> (usize, usize) {
+        self.dim
+    }
+
+    /// True if there are no child widgets
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
+
+    /// Returns the number of child widgets
+    pub fn len(&self) -> usize {
+        self.widgets.len()
+    }
+
+    /// Remove all child widgets
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action) if any widget is
+    /// removed.
+    pub fn clear(&mut self) -> TkAction {
+        let action = match self.widgets.is_empty() {
+            true => TkAction::None,
+            false => TkAction::Reconfigure,
+        };
+        self.widgets.clear();
+        self.info.clear();
+        self.dim = (0, 0);
+        self.cursor = (0, 0);
+        action
+    }
+
+    /// Add a child widget at column `col` and row `row`
+    ///
+    /// The child spans `span.0` columns and `span.1` rows (use `(1, 1)` for a
+    /// single cell). Panics if either span is zero.
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action).
+    pub fn add_at(&mut self, widget: W, col: u32, row: u32, span: (u32, u32)) -> TkAction {
+        assert!(
+            span.0 > 0 && span.1 > 0,
+            "Grid::add_at: span must be non-zero"
+        );
+        self.widgets.push(widget);
+        self.info.push(GridChildInfo {
+            col,
+            col_end: col + span.0,
+            row,
+            row_end: row + span.1,
+        });
+        self.update_dim();
+        TkAction::Reconfigure
+    }
+
+    /// Add a child widget in the next free cell
+    ///
+    /// The child spans `span.0` columns and `span.1` rows. Cells are filled
+    /// left-to-right, moving to the next row after the number of columns set
+    /// by [`Grid::with_columns`]. Cells occupied by other children are
+    /// skipped, but placement never moves back to an earlier cell. Panics if
+    /// either span is zero or if `span.0` exceeds the number of columns.
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action).
+    pub fn add_auto(&mut self, widget: W, span: (u32, u32)) -> TkAction {
+        let cols = self.auto_cols.unwrap_or(u32::MAX);
+        assert!(span.0 <= cols, "Grid::add_auto: span exceeds columns");
+        let (mut col, mut row) = self.cursor;
+        loop {
+            if col.saturating_add(span.0) > cols {
+                col = 0;
+                row += 1;
+            }
+            let overlaps = self.info.iter().any(|info| {
+                col < info.col_end
+                    && info.col < col + span.0
+                    && row < info.row_end
+                    && info.row < row + span.1
+            });
+            if !overlaps {
+                break;
+            }
+            col += 1;
+        }
+        self.cursor = (col + span.0, row);
+        self.add_at(widget, col, row, span)
+    }
+
+    /// Set the minimum gaps between columns and rows
+    ///
+    /// See [`Grid::with_gap`].
+    ///
+    /// Triggers a [resize action](Manager::send_action).
+    pub fn set_gap(&mut self, col_gap: u16, row_gap: u16) -> TkAction {
+        self.gap = (col_gap, row_gap);
+        TkAction::Resize
+    }
+
+    /// Removes the child widget at position `index`
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action).
+    pub fn remove(&mut self, index: usize) -> (W, TkAction) {
+        let r = self.widgets.remove(index);
+        self.info.remove(index);
+        self.update_dim();
+        (r, TkAction::Reconfigure)
+    }
+
+    /// Get the placement of the child at `index`
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn child_info(&self, index: usize) -> GridChildInfo {
+        self.info[index]
+    }
+
+    /// Iterate over children
+    pub fn iter(&self) -> std::slice::Iter<W> {
+        self.widgets.iter()
+    }
+}
+
+impl<W: Widget> Index<usize> for Grid<W> {
+    type Output = W;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.widgets[index]
+    }
+}
+
+impl<W: Widget> IndexMut<usize> for Grid<W> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.widgets[index]
+    }
+}
+
+#[test]
+fn auto_placement() {
+    use kas::widget::Filler;
+
+    let grid = Grid::new()
+        .with_columns(3)
+        .with(Filler::new(), 1, 0, (1, 2))
+        .with_auto(Filler::new(), (1, 1))
+        .with_auto(Filler::new(), (2, 1))
+        .with_auto(Filler::new(), (1, 1));
+    let pos = |index| {
+        let info = grid.child_info(index);
+        (info.col, info.row)
+    };
+    assert_eq!(pos(1), (0, 0));
+    assert_eq!(pos(2), (0, 2));
+    assert_eq!(pos(3), (2, 2));
+    assert_eq!(grid.dim(), (3, 3));
+}
@@ -0,0 +1,73 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! An icon widget
+
+use kas::draw::Icon;
+use kas::prelude::*;
+
+/// A static icon
+///
+/// The icon is drawn by the theme at its standard icon size (optionally
+/// scaled), centred within the available space.
+#[derive(Clone, Debug, Widget)]
+pub struct IconView {
+    #[widget_core]
+    core: CoreData,
+    icon: Icon,
+    scale: f32,
+    icon_rect: Rect,
+}
+
+impl IconView {
+    /// Construct, at standard size
+    #[inline]
+    pub fn new(icon: Icon) -> Self {
+        IconView {
+            core: Default::default(),
+            icon,
+            scale: 1.0,
+            icon_rect: Rect::default(),
+        }
+    }
+
+    /// Scale relative to the standard icon size (chain style)
+    #[inline]
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Get the icon
+    #[inline]
+    pub fn icon(&self) -> &Icon {
+        &self.icon
+    }
+
+    /// Set the icon
+    pub fn set_icon(&mut self, icon: Icon) -> TkAction {
+        self.icon = icon;
+        TkAction::Redraw
+    }
+}
+
+impl Layout for IconView {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let size = size_handle.icon() * self.scale;
+        self.icon_rect.size = size;
+        SizeRules::extract_fixed(axis.is_vertical(), size, size_handle.outer_margins())
+    }
+
+    fn set_rect(&mut self, rect: Rect, _: AlignHints) {
+        self.core.rect = rect;
+        let size = self.icon_rect.size.min(rect.size);
+        let pos = rect.pos + Coord::from((rect.size - size) / 2);
+        self.icon_rect = Rect::new(pos, size);
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
+        draw_handle.icon(self.icon_rect, &self.icon, self.input_state(mgr, disabled));
+    }
+}
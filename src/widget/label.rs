@@ -8,20 +8,47 @@
 use kas::class::{CloneText, SetAccel, SetText};
 use kas::draw::TextClass;
 use kas::event::VirtualKeyCodes;
+use kas::geom::Vec2;
 use kas::prelude::*;
 use std::ops::Deref;
 
 /// A simple text label
+///
+/// In path mode (see [`Label::elide_path`]) the text is a single line, with
+/// the middle of the path elided as required to fit the available width.
+#[widget(config=noauto)]
 #[derive(Clone, Default, Debug, Widget)]
 pub struct Label {
     #[widget_core]
     core: CoreData,
     reserve: Option<&'static str>,
+    path: Option<String>,
     label: PreparedText,
 }
 
+impl WidgetConfig for Label {
+    fn debug_label(&self) -> Option<&str> {
+        Some(self.full_text())
+    }
+}
+
 impl Layout for Label {
     fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        if let Some(path) = self.path.as_ref() {
+            // Size to the full path, but allow elision down to the filename
+            let _ = self.label.set_and_prepare(path.as_str());
+            let mut rules = size_handle.text_bound(&mut self.label, TextClass::LabelSingle, axis);
+            if axis.is_horizontal() {
+                let mut prepared = PreparedText::new_single(elided_min(path).into());
+                let min_rules = size_handle.text_bound(&mut prepared, TextClass::LabelSingle, axis);
+                rules.reduce_min_to(min_rules.min_size());
+                self.core.rect.size.0 = rules.ideal_size();
+            } else {
+                self.core.rect.size.1 = rules.ideal_size();
+            }
+            return rules;
+        }
+
         let mut prepared;
         let text = if let Some(s) = self.reserve {
             prepared = PreparedText::new_multi(s.into());
@@ -44,10 +71,27 @@ impl Layout for Label {
             env.set_bounds(rect.size.into());
             env.set_align(align.unwrap_or(Align::Default, Align::Centre));
         });
+
+        if let Some(path) = self.path.as_ref() {
+            let width = rect.size.0 as f32;
+            let mut remove = 0;
+            while let Some(text) = elide_path(path, remove) {
+                let _ = self.label.set_and_prepare(text.as_str());
+                if Vec2::from(self.label.required_size()).0 <= width {
+                    break;
+                }
+                remove += 1;
+            }
+        }
     }
 
-    fn draw(&self, draw_handle: &mut dyn DrawHandle, _: &ManagerState, _: bool) {
-        draw_handle.text(self.core.rect.pos, &self.label, TextClass::Label);
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState, disabled: bool) {
+        let class = match self.path.is_some() {
+            false => TextClass::Label,
+            true => TextClass::LabelSingle,
+        };
+        let state = self.input_state(mgr, disabled);
+        draw_handle.text(self.core.rect.pos, &self.label, class, state);
     }
 }
 
@@ -57,10 +101,32 @@ impl Label {
         Label {
             core: Default::default(),
             reserve: None,
+            path: None,
             label: PreparedText::new_multi(label.into().deref().into()),
         }
     }
 
+    /// Display the text as a file path, eliding the middle where necessary
+    ///
+    /// When there is insufficient width to display the whole path, middle
+    /// components are replaced by `…`, keeping the root (or drive) and the
+    /// filename visible. Elision is recomputed whenever the label is resized.
+    /// This mode implies single-line text and is incompatible with
+    /// [`Label::reserve`].
+    pub fn elide_path(mut self) -> Self {
+        self.path = Some(self.label.text().to_string());
+        self.reserve = None;
+        self
+    }
+
+    /// Get the full text (not elided)
+    pub fn full_text(&self) -> &str {
+        match self.path.as_ref() {
+            Some(path) => path,
+            None => self.label.text(),
+        }
+    }
+
     /// Reserve sufficient room for the given text
     ///
     /// If this option is used, the label will be sized to fit this text, not
@@ -74,16 +140,66 @@ impl Label {
 
 impl CloneText for Label {
     fn clone_text(&self) -> kas::text::RichText {
-        self.label.clone_text()
+        match self.path.as_ref() {
+            Some(path) => path.as_str().into(),
+            None => self.label.clone_text(),
+        }
     }
 }
 
 impl SetText for Label {
     fn set_rich_text(&mut self, text: kas::text::RichText) -> TkAction {
-        self.label.set_and_prepare(text)
+        let action = self.label.set_and_prepare(text);
+        if self.path.is_some() {
+            // Elision happens in set_rect
+            self.path = Some(self.label.text().to_string());
+        } else if action == TkAction::None {
+            return action;
+        }
+        // Size requirements may have changed
+        self.core.layout.set_dirty();
+        TkAction::Relayout
     }
 }
 
+// Elide `remove` components from the middle of `path`, keeping the first
+// (root or drive) and last (filename) components
+//
+// Returns `None` if there are fewer than `remove` middle components.
+fn elide_path(path: &str, remove: usize) -> Option<String> {
+    if remove == 0 {
+        return Some(path.to_string());
+    }
+    let sep = match path.contains('/') || !path.contains('\\') {
+        true => '/',
+        false => '\\',
+    };
+    let parts: Vec<&str> = path.split(sep).collect();
+    let middle = parts.len().saturating_sub(2);
+    if remove > middle {
+        return None;
+    }
+
+    let keep = middle - remove;
+    let left = keep / 2;
+    let right = keep - left;
+    let mut out = Vec::with_capacity(keep + 3);
+    out.push(parts[0]);
+    out.extend_from_slice(&parts[1..1 + left]);
+    out.push("…");
+    out.extend_from_slice(&parts[parts.len() - 1 - right..]);
+    Some(out.join(&sep.to_string()))
+}
+
+// The most elided form of `path`
+fn elided_min(path: &str) -> String {
+    let parts = path.matches(|c| c == '/' || c == '\\').count();
+    let middle = parts.saturating_sub(1);
+    elide_path(path, middle)
+        .or_else(|| elide_path(path, 0))
+        .unwrap()
+}
+
 /// A label supporting an accelerator key
 ///
 /// Accelerator keys are not useful on plain labels, but this widget may be
@@ -116,17 +232,19 @@ impl Layout for AccelLabel {
         });
     }
 
-    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState, _: bool) {
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &ManagerState, disabled: bool) {
+        let state = self.input_state(mgr, disabled);
         if mgr.show_accel_labels() {
             draw_handle.text_with_underline(
                 self.core.rect.pos,
                 Coord::ZERO,
                 &self.label,
                 TextClass::Label,
+                state,
                 self.underline,
             );
         } else {
-            draw_handle.text(self.core.rect.pos, &self.label, TextClass::Label);
+            draw_handle.text(self.core.rect.pos, &self.label, TextClass::Label, state);
         }
     }
 }
@@ -162,6 +280,25 @@ impl SetAccel for AccelLabel {
     fn set_accel_string(&mut self, label: AccelString) -> TkAction {
         let text = label.text().to_string();
         self.keys = label.take_keys();
-        self.label.set_and_prepare(text)
+        if self.label.set_and_prepare(text) == TkAction::None {
+            return TkAction::None;
+        }
+        self.core.layout.set_dirty();
+        TkAction::Relayout
     }
 }
+
+#[test]
+fn elide_paths() {
+    let path = "/home/user/docs/file.txt";
+    assert_eq!(elide_path(path, 0).unwrap(), path);
+    assert_eq!(elide_path(path, 1).unwrap(), "/home/…/docs/file.txt");
+    assert_eq!(elide_path(path, 2).unwrap(), "/…/docs/file.txt");
+    assert_eq!(elide_path(path, 3).unwrap(), "/…/file.txt");
+    assert_eq!(elide_path(path, 4), None);
+    assert_eq!(elided_min(path), "/…/file.txt");
+
+    let path = r"C:\Users\me\file.txt";
+    assert_eq!(elide_path(path, 2).unwrap(), r"C:\…\file.txt");
+    assert_eq!(elided_min("file.txt"), "file.txt");
+}
@@ -0,0 +1,303 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! A row or column which configures only visible children
+
+use std::ops::Range;
+
+use kas::event::ConfigureManager;
+use kas::layout::StretchPolicy;
+use kas::prelude::*;
+
+/// A lazily configured row
+///
+/// See documentation of [`LazyList`] type.
+pub type LazyRow<W> = LazyList<kas::Right, W>;
+
+/// A lazily configured column
+///
+/// See documentation of [`LazyList`] type.
+pub type LazyColumn<W> = LazyList<kas::Down, W>;
+
+/// A row/column which configures only visible children
+///
+/// This is similar to [`super::List`], but intended for large numbers of
+/// children within a [`super::ScrollRegion`]. Only children within or near
+/// the viewport (see [`Layout::set_viewport`]) are *active*: configured,
+/// positioned, drawn and sent events. Other children are not configured and
+/// are not enumerated via [`WidgetChildren`].
+///
+/// All children are assumed to have the same size as the first, thus sizing is
+/// O(1) in the number of children. Activating children requires reconfiguring
+/// this list (but not other widgets; see [`CoreData::reconfigure`]); to make
+/// this less frequent, children up to one viewport-length either side of the
+/// viewport are also activated.
+#[handler(send=noauto, msg=<W as event::Handler>::Msg)]
+#[widget(children=noauto, config=noauto)]
+#[derive(Clone, Default, Debug, Widget)]
+pub struct LazyList<D: Directional, W: Widget> {
+    #[widget_core]
+    core: CoreData,
+    widgets: Vec<W>,
+    direction: D,
+    child_size: u32,
+    spacing: u32,
+    viewport: Option<Rect>,
+    active: Range<usize>,
+    wanted: Range<usize>,
+    id_block: u32,
+}
+
+impl<D: Directional, W: Widget> WidgetChildren for LazyList<D, W> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.active.len()
+    }
+    #[inline]
+    fn get(&self, index: usize) -> Option<&dyn WidgetConfig> {
+        if index < self.active.len() {
+            Some(self.widgets[self.active.start + index].as_widget())
+        } else {
+            None
+        }
+    }
+    #[inline]
+    fn get_mut(&mut self, index: usize) -> Option<&mut dyn WidgetConfig> {
+        if index < self.active.len() {
+            Some(self.widgets[self.active.start + index].as_widget_mut())
+        } else {
+            None
+        }
+    }
+}
+
+impl<D: Directional, W: Widget> WidgetConfig for LazyList<D, W> {
+    fn configure_recurse<'a, 'b>(&mut self, mut cmgr: ConfigureManager<'a, 'b>) {
+        self.core.reconfigure = false;
+        let n = self.widgets.len();
+        self.active = self.wanted.start.min(n)..self.wanted.end.min(n);
+        let start = u32::from(cmgr.peek_id());
+        for child in &mut self.widgets[self.active.clone()] {
+            child.configure_recurse(cmgr.child());
+        }
+
+        // Reserve identifiers for children activated later, so that these may
+        // be configured without changing identifiers of other widgets
+        let used = u32::from(cmgr.peek_id()) - start;
+        if used > self.id_block {
+            let len = self.active.len() as u32;
+            self.id_block = (2 * len * (used / len.max(1))).max(used);
+        }
+        cmgr.skip_ids(self.id_block - used);
+
+        self.core_data_mut().id = cmgr.next_id(self.id());
+        self.configure(cmgr.mgr());
+    }
+}
+
+impl<D: Directional, W: Widget> Layout for LazyList<D, W> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        if self.widgets.is_empty() {
+            return SizeRules::EMPTY;
+        }
+
+        // Note: this child may not be configured, but sizing does not require it
+        let rules = self.widgets[0].size_rules(size_handle, axis);
+        if axis.is_vertical() != self.direction.is_vertical() {
+            return rules;
+        }
+
+        let margins = rules.margins();
+        self.child_size = rules.ideal_size();
+        self.spacing = margins.0.max(margins.1) as u32;
+        let n = self.widgets.len() as u32;
+        let size = n * self.child_size + (n - 1) * self.spacing;
+        SizeRules::new(size, size, margins, StretchPolicy::Fixed)
+    }
+
+    fn set_rect(&mut self, rect: Rect, _: AlignHints) {
+        self.core.rect = rect;
+        let _ = self.update_wanted(self.viewport.unwrap_or(rect));
+
+        for index in self.active.clone() {
+            let rect = self.child_rect(index);
+            self.widgets[index].set_rect(rect, AlignHints::NONE);
+        }
+    }
+
+    fn set_viewport(&mut self, viewport: Rect) -> TkAction {
+        self.viewport = Some(viewport);
+        self.update_wanted(viewport)
+    }
+
+    fn spatial_range(&self) -> (usize, usize) {
+        let last = WidgetChildren::len(self).wrapping_sub(1);
+        match self.direction.is_reversed() {
+            false => (0, last),
+            true => (last, 0),
+        }
+    }
+
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        if !self.rect().contains(coord) {
+            return None;
+        }
+
+        for child in &self.widgets[self.active.clone()] {
+            if let Some(id) = child.find_id(coord) {
+                return Some(id);
+            }
+        }
+        Some(self.id())
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
+        let disabled = disabled || self.is_disabled();
+        for child in &self.widgets[self.active.clone()] {
+            child.draw(draw_handle, mgr, disabled);
+        }
+    }
+}
+
+impl<D: Directional, W: Widget> event::SendEvent for LazyList<D, W> {
+    fn send(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
+        if !self.is_disabled() {
+            for child in &mut self.widgets[self.active.clone()] {
+                if id <= child.id() {
+                    return child.send(mgr, id, event);
+                }
+            }
+        }
+
+        Response::Unhandled(event)
+    }
+}
+
+impl<D: Directional + Default, W: Widget> LazyList<D, W> {
+    /// Construct a new instance
+    ///
+    /// This constructor is available where the direction is determined by the
+    /// type: for `D: Directional + Default`. In other cases, use
+    /// [`LazyList::new_with_direction`].
+    pub fn new(widgets: Vec<W>) -> Self {
+        LazyList::new_with_direction(D::default(), widgets)
+    }
+}
+
+impl<D: Directional, W: Widget> LazyList<D, W> {
+    /// Construct a new instance with explicit direction
+    pub fn new_with_direction(direction: D, widgets: Vec<W>) -> Self {
+        LazyList {
+            core: Default::default(),
+            widgets,
+            direction,
+            child_size: 0,
+            spacing: 0,
+            viewport: None,
+            active: 0..0,
+            wanted: 0..0,
+            id_block: 0,
+        }
+    }
+
+    /// Get the range of active children (by index)
+    pub fn active_range(&self) -> Range<usize> {
+        self.active.clone()
+    }
+
+    /// Append a child widget
+    ///
+    /// Triggers reconfiguration of this list (only) and a
+    /// [relayout action](TkAction::Relayout).
+    pub fn push(&mut self, widget: W) -> TkAction {
+        self.widgets.push(widget);
+        self.core.reconfigure = true;
+        TkAction::Relayout
+    }
+
+    /// Remove the last child widget
+    ///
+    /// Returns `None` if there are no children. Otherwise, this triggers
+    /// reconfiguration of this list (only) and a
+    /// [relayout action](TkAction::Relayout).
+    pub fn pop(&mut self) -> (Option<W>, TkAction) {
+        if self.widgets.is_empty() {
+            return (None, TkAction::None);
+        }
+        self.core.reconfigure = true;
+        (self.widgets.pop(), TkAction::Relayout)
+    }
+
+    fn child_rect(&self, index: usize) -> Rect {
+        let n = self.widgets.len();
+        let pos = match self.direction.is_reversed() {
+            false => index,
+            true => n - 1 - index,
+        };
+        let offset = (pos as u32 * (self.child_size + self.spacing)) as i32;
+        let rect = self.core.rect;
+        if self.direction.is_vertical() {
+            let pos = Coord(rect.pos.0, rect.pos.1 + offset);
+            Rect::new(pos, Size(rect.size.0, self.child_size))
+        } else {
+            let pos = Coord(rect.pos.0 + offset, rect.pos.1);
+            Rect::new(pos, Size(self.child_size, rect.size.1))
+        }
+    }
+
+    // Update the wanted range; if any visible child is not active, marks this
+    // list for reconfiguration and returns Relayout.
+    fn update_wanted(&mut self, viewport: Rect) -> TkAction {
+        let n = self.widgets.len();
+        let stride = self.child_size + self.spacing;
+        let (start, len) = if self.direction.is_vertical() {
+            (viewport.pos.1 - self.core.rect.pos.1, viewport.size.1)
+        } else {
+            (viewport.pos.0 - self.core.rect.pos.0, viewport.size.0)
+        };
+        let visible = if stride == 0 {
+            0..n
+        } else {
+            let start = start.max(0) as u32;
+            let first = ((start / stride) as usize).min(n);
+            let last = (((start + len + stride - 1) / stride) as usize).min(n);
+            match self.direction.is_reversed() {
+                false => first..last,
+                true => (n - last)..(n - first),
+            }
+        };
+
+        if visible.start >= self.active.start && visible.end <= self.active.end {
+            return TkAction::None;
+        }
+        let overscan = visible.len();
+        self.wanted = visible.start.saturating_sub(overscan)..(visible.end + overscan).min(n);
+        self.core.reconfigure = true;
+        TkAction::Relayout
+    }
+}
+
+#[test]
+fn lazy_list() {
+    use super::Label;
+
+    let widgets = (0..100).map(|i| Label::new(i.to_string())).collect();
+    let mut list = LazyColumn::new(widgets);
+    let _ = kas::layout::layout_to_text(&mut list, Some(Size(16, 32)));
+    assert!(list.core.reconfigure);
+    assert!(list.wanted.start == 0 && list.wanted.end < 100);
+
+    list.active = list.wanted.clone();
+    list.core.reconfigure = false;
+    let stride = (list.child_size + list.spacing) as i32;
+    let pos = list.core.rect.pos;
+    let viewport = Rect::new(pos + Coord(0, 50 * stride), Size(16, 2 * stride as u32));
+    assert_eq!(list.set_viewport(viewport), TkAction::Relayout);
+    assert!(list.core.reconfigure);
+    assert_eq!(list.wanted, 48..54);
+    list.active = list.wanted.clone();
+    let viewport = Rect::new(pos + Coord(0, 49 * stride), Size(16, 2 * stride as u32));
+    assert_eq!(list.set_viewport(viewport), TkAction::None);
+}
@@ -113,6 +113,10 @@ impl<D: Directional, W: Widget> WidgetChildren for List<D, W> {
 
 impl<D: Directional, W: Widget> Layout for List<D, W> {
     fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        if let Some(rules) = self.core.layout.get(axis) {
+            return rules;
+        }
+
         let dim = (self.direction, self.widgets.len());
         let mut solver = layout::RowSolver::new(axis, dim, &mut self.data);
         for (n, child) in self.widgets.iter_mut().enumerate() {
@@ -120,7 +124,9 @@ impl<D: Directional, W: Widget> Layout for List<D, W> {
                 child.size_rules(size_handle, axis)
             });
         }
-        solver.finish(&mut self.data)
+        let rules = solver.finish(&mut self.data);
+        self.core.layout.set(axis, rules);
+        rules
     }
 
     fn set_rect(&mut self, rect: Rect, align: AlignHints) {
@@ -205,6 +211,15 @@ impl<D: Directional, W: Widget> List<D, W> {
         }
     }
 
+    /// Append a child widget (chain style)
+    ///
+    /// This allows construction without macros, e.g.
+    /// `Row::new(vec![]).with(a).with(b)`. See [`List::push`].
+    pub fn with(mut self, widget: W) -> Self {
+        self.widgets.push(widget);
+        self
+    }
+
     /// Get the direction of contents
     pub fn direction(&self) -> Direction {
         self.direction.as_direction()
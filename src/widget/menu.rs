@@ -18,6 +18,7 @@ pub use menubar::MenuBar;
 pub use submenu::SubMenu;
 
 use kas::prelude::*;
+use kas::NavHint;
 
 /// Trait governing menus, sub-menus and menu-entries
 pub trait Menu: Widget {
@@ -75,6 +76,12 @@ impl<M: 'static> WidgetChildren for Box<dyn Menu<Msg = M>> {
     fn get_mut(&mut self, index: usize) -> Option<&mut dyn WidgetConfig> {
         self.as_mut().get_mut(index)
     }
+    fn nav_hint(&self, index: usize) -> NavHint {
+        self.as_ref().nav_hint(index)
+    }
+    fn initially_hidden(&self, index: usize) -> bool {
+        self.as_ref().initially_hidden(index)
+    }
 
     fn find(&self, id: WidgetId) -> Option<&dyn WidgetConfig> {
         self.as_ref().find(id)
@@ -96,12 +103,19 @@ impl<M: 'static> WidgetConfig for Box<dyn Menu<Msg = M>> {
         self.as_mut().configure(mgr);
     }
 
+    fn configure_recurse<'a, 'b>(&mut self, cmgr: kas::event::ConfigureManager<'a, 'b>) {
+        self.as_mut().configure_recurse(cmgr);
+    }
+
     fn key_nav(&self) -> bool {
         self.as_ref().key_nav()
     }
     fn cursor_icon(&self) -> event::CursorIcon {
         self.as_ref().cursor_icon()
     }
+    fn debug_label(&self) -> Option<&str> {
+        self.as_ref().debug_label()
+    }
 }
 
 impl<M: 'static> Layout for Box<dyn Menu<Msg = M>> {
@@ -113,6 +127,10 @@ impl<M: 'static> Layout for Box<dyn Menu<Msg = M>> {
         self.as_mut().set_rect(rect, align);
     }
 
+    fn set_viewport(&mut self, viewport: Rect) -> TkAction {
+        self.as_mut().set_viewport(viewport)
+    }
+
     fn find_id(&self, coord: Coord) -> Option<WidgetId> {
         self.as_ref().find_id(coord)
     }
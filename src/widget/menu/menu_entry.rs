@@ -9,13 +9,15 @@ use std::fmt::{self, Debug};
 
 use super::Menu;
 use kas::class::{CloneText, HasBool, SetAccel};
-use kas::draw::TextClass;
-use kas::event::VirtualKeyCodes;
+use kas::draw::{Icon, TextClass};
+use kas::event::{KeyLayout, Shortcut, VirtualKeyCodes};
 use kas::layout::{RulesSetter, RulesSolver};
 use kas::prelude::*;
 use kas::widget::{AccelLabel, CheckBoxBare};
 
 /// A standard menu entry
+///
+/// An [`Icon`] may be shown before the label (see [`MenuEntry::with_icon`]).
 #[widget(config=noauto)]
 #[handler(handle=noauto)]
 #[derive(Clone, Debug, Default, Widget)]
@@ -26,17 +28,29 @@ pub struct MenuEntry<M: Clone + Debug + 'static> {
     label: PreparedText,
     underline: usize,
     label_off: Coord,
+    icon: Option<Icon>,
+    icon_rect: Rect,
+    icon_space: u32,
+    shortcut: Option<Shortcut>,
+    hint: PreparedText,
     msg: M,
 }
 
 impl<M: Clone + Debug + 'static> WidgetConfig for MenuEntry<M> {
     fn configure(&mut self, mgr: &mut Manager) {
         mgr.add_accel_keys(self.id(), &self.keys);
+        if self.shortcut.is_some() {
+            mgr.update_on_handle(KeyLayout::update_handle(), self.id());
+        }
     }
 
     fn key_nav(&self) -> bool {
         true
     }
+
+    fn debug_label(&self) -> Option<&str> {
+        Some(self.label.text())
+    }
 }
 
 impl<M: Clone + Debug + 'static> Layout for MenuEntry<M> {
@@ -44,31 +58,73 @@ impl<M: Clone + Debug + 'static> Layout for MenuEntry<M> {
         let size = size_handle.menu_frame();
         self.label_off = size.into();
         let frame_rules = SizeRules::extract_fixed(axis.is_vertical(), size + size, Margins::ZERO);
-        let text_rules = size_handle.text_bound(&mut self.label, TextClass::LabelSingle, axis);
+        let mut text_rules = size_handle.text_bound(&mut self.label, TextClass::LabelSingle, axis);
+        if self.icon.is_some() {
+            let icon = size_handle.icon();
+            self.icon_rect.size = icon;
+            self.icon_space = icon.0 + size.0;
+            if axis.is_horizontal() {
+                text_rules.append(SizeRules::fixed(self.icon_space, (0, 0)));
+            } else {
+                text_rules.max_with(SizeRules::fixed(icon.1, (0, 0)));
+            }
+        }
+        if self.shortcut.is_some() {
+            let hint_rules = size_handle.text_bound(&mut self.hint, TextClass::LabelSingle, axis);
+            if axis.is_horizontal() {
+                text_rules.append(SizeRules::fixed(2 * size.0, (0, 0)));
+                text_rules.append(hint_rules);
+            } else {
+                text_rules.max_with(hint_rules);
+            }
+        }
         text_rules.surrounded_by(frame_rules, true)
     }
 
     fn set_rect(&mut self, rect: Rect, align: AlignHints) {
         self.core.rect = rect;
+        if self.icon.is_some() {
+            let size = self.icon_rect.size.min(rect.size);
+            let y = (rect.size.1 - size.1) as i32 / 2;
+            let pos = rect.pos + Coord(self.label_off.0, y);
+            self.icon_rect = Rect::new(pos, size);
+        }
+        let label_size = Size(rect.size.0.saturating_sub(self.icon_space), rect.size.1);
         self.label.update_env(|env| {
-            env.set_bounds(rect.size.into());
+            env.set_bounds(label_size.into());
             env.set_align(align.unwrap_or(Align::Default, Align::Centre));
         });
+        if self.shortcut.is_some() {
+            let width = rect.size.0.saturating_sub(2 * self.label_off.0 as u32);
+            self.hint.update_env(|env| {
+                env.set_bounds(Size(width, rect.size.1).into());
+                env.set_align((Align::BR, align.vert.unwrap_or(Align::Centre)));
+            });
+        }
     }
 
     fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
-        draw_handle.menu_entry(self.core.rect, self.input_state(mgr, disabled));
-        let pos = self.core.rect.pos + self.label_off;
+        let state = self.input_state(mgr, disabled);
+        draw_handle.menu_entry(self.core.rect, state);
+        if let Some(icon) = self.icon.as_ref() {
+            draw_handle.icon(self.icon_rect, icon, state);
+        }
+        if self.shortcut.is_some() {
+            let pos = self.core.rect.pos + self.label_off;
+            draw_handle.text(pos, &self.hint, TextClass::LabelSingle, state);
+        }
+        let pos = self.core.rect.pos + self.label_off + Coord(self.icon_space as i32, 0);
         if mgr.show_accel_labels() {
             draw_handle.text_with_underline(
                 pos,
                 Coord::ZERO,
                 &self.label,
                 TextClass::LabelSingle,
+                state,
                 self.underline,
             );
         } else {
-            draw_handle.text(pos, &self.label, TextClass::LabelSingle);
+            draw_handle.text(pos, &self.label, TextClass::LabelSingle, state);
         }
     }
 }
@@ -90,10 +146,32 @@ impl<M: Clone + Debug + 'static> MenuEntry<M> {
             label: text,
             underline,
             label_off: Coord::ZERO,
+            icon: None,
+            icon_rect: Rect::default(),
+            icon_space: 0,
+            shortcut: None,
+            hint: PreparedText::new_single("".into()),
             msg,
         }
     }
 
+    /// Show an icon before the label (chain style)
+    pub fn with_icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Show a keyboard shortcut hint
+    ///
+    /// The shortcut is displayed according to the user's keyboard layout (see
+    /// [`KeyLayout`]), updating when the layout changes. This is a hint only:
+    /// the application is responsible for handling the shortcut.
+    pub fn with_shortcut(mut self, shortcut: Shortcut) -> Self {
+        self.hint = PreparedText::new_single(shortcut.label().into());
+        self.shortcut = Some(shortcut);
+        self
+    }
+
     /// Replace the message value
     pub fn set_msg(&mut self, msg: M) {
         self.msg = msg;
@@ -117,9 +195,19 @@ impl<M: Clone + Debug + 'static> SetAccel for MenuEntry<M> {
 impl<M: Clone + Debug + 'static> event::Handler for MenuEntry<M> {
     type Msg = M;
 
-    fn handle(&mut self, _: &mut Manager, event: Event) -> Response<M> {
+    fn handle(&mut self, mgr: &mut Manager, event: Event) -> Response<M> {
         match event {
             Event::Activate => self.msg.clone().into(),
+            Event::HandleUpdate { .. } => {
+                if let Some(shortcut) = self.shortcut {
+                    let label = shortcut.label();
+                    if label != self.hint.text() {
+                        let _ = self.hint.set_and_prepare(label);
+                        mgr.send_action(TkAction::Resize);
+                    }
+                }
+                Response::None
+            }
             event => Response::Unhandled(event),
         }
     }
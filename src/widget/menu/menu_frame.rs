@@ -39,7 +39,12 @@ impl<W: Widget> Layout for MenuFrame<W> {
         let margins = Margins::ZERO;
         let frame_rules = SizeRules::extract_fixed(axis.is_vertical(), size + size, margins);
 
-        let child_rules = self.inner.size_rules(size_handle, axis);
+        let mut child_axis = axis;
+        if axis.is_vertical() {
+            // self.m0.0, self.m1.0 were set when sizing the horizontal axis
+            child_axis.sub_other(self.m0.0 + self.m1.0);
+        }
+        let child_rules = self.inner.size_rules(size_handle, child_axis);
         let m = child_rules.margins();
 
         if axis.is_horizontal() {
@@ -69,6 +74,7 @@ impl<W: Widget> Layout for MenuFrame<W> {
     }
 
     fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
+        draw_handle.shadow(self.core_data().rect);
         draw_handle.menu_frame(self.core_data().rect);
         let disabled = disabled || self.is_disabled();
         self.inner.draw(draw_handle, mgr, disabled);
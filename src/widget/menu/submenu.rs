@@ -143,10 +143,11 @@ impl<D: Directional, W: Menu> kas::Layout for SubMenu<D, W> {
                 Coord::ZERO,
                 &self.label,
                 TextClass::Label,
+                state,
                 self.underline,
             );
         } else {
-            draw_handle.text(pos, &self.label, TextClass::Label);
+            draw_handle.text(pos, &self.label, TextClass::Label, state);
         }
     }
 }
@@ -16,12 +16,22 @@
 //! ## Container widgets
 //!
 //! -   [`Frame`]: a simple frame around a single child
+//! -   [`Align`]: aligns a single child within surplus space
 //! -   [`ScrollRegion`]: may be larger on the inside than the outside
 //! -   [`Stack`]: a stack of widgets in the same rect (TODO: `TabbedStack`)
+//! -   [`OverlayStack`]: widgets layered in the same rect with z-order
+//! -   [`DocumentArea`]: movable, resizable sub-windows (MDI)
 //! -   [`List`]: a dynamic row / column of children
+//! -   [`LazyList`]: a row / column configuring only visible children
+//! -   [`Grid`]: a dynamic grid of children, supporting cell-spans
+//! -   [`FlowLayout`]: a dynamic row of children, wrapping to new rows
 //! -   [`Splitter`]: similar to [`List`] but with resizing handles
+//! -   [`SizeGroupMember`]: links its size to other members of a group
+//! -   [`SizeReserve`]: overrides the min/ideal/max size or proportion of its child
+//! -   [`RenderCache`]: caches the draw output of its child
 //! -   [`Window`] is usually the root widget and has special handling for
-//!     pop-ups and callbacks
+//!     pop-ups and callbacks; [`WindowTemplate`] allows instantiating
+//!     multiple windows from one description
 //!
 //! ## Menus
 //!
@@ -38,12 +48,15 @@
 //! -   [`EditBox`]: a text-editing box
 //! -   [`ScrollBar`]: a scrollbar
 //! -   [`Slider`]: a slider
+//! -   [`Scrubber`]: a numeric field adjusted by dragging
+//! -   [`Form`]: a form generated from a [`FormSchema`]
 //!
 //! ## Static widgets
 //!
 //! -   [`Filler`]: an empty widget, sometimes used to fill space
 //! -   [`Separator`]: a visible bar to separate things
 //! -   [`Label`]: a simple text label
+//! -   [`IconView`]: a standard or custom icon
 //!
 //! ## Components
 //!
@@ -52,42 +65,66 @@
 //! -   [`RadioBoxBare`]: `RadioBox` without its label
 //! -   [`DragHandle`]: a handle (e.g. for a slider, splitter or scrollbar)
 
+mod align;
 mod button;
 mod checkbox;
 mod combobox;
 mod dialog;
+mod document_area;
 mod drag;
 mod editbox;
 mod filler;
+mod flow;
+mod form;
 mod frame;
+mod grid;
+mod icon;
 mod label;
+mod lazy_list;
 mod list;
 mod menu;
+mod overlay_stack;
 mod radiobox;
+mod render_cache;
 mod scroll;
 mod scrollbar;
+mod scrubber;
 mod separator;
+mod size_group;
+mod size_reserve;
 mod slider;
 mod splitter;
 mod stack;
 mod window;
 
+pub use align::Align;
 pub use button::TextButton;
 pub use checkbox::{CheckBox, CheckBoxBare};
 pub use combobox::ComboBox;
 pub use dialog::MessageBox;
-pub use drag::DragHandle;
+pub use document_area::{BoxDocumentArea, DocumentArea, DocumentCommand};
+pub use drag::{DragHandle, DragState};
 pub use editbox::{EditBox, EditBoxVoid, EditGuard};
 pub use filler::Filler;
+pub use flow::{BoxFlowLayout, FlowLayout};
+pub use form::{FieldError, FieldKind, Form, FormField, FormSchema, FormValue, SchemaError};
 pub use frame::Frame;
+pub use grid::{BoxGrid, Grid};
+pub use icon::IconView;
 pub use label::{AccelLabel, Label};
+pub use lazy_list::{LazyColumn, LazyList, LazyRow};
 pub use list::*;
 pub use menu::*;
+pub use overlay_stack::{BoxOverlayStack, OverlayStack};
 pub use radiobox::{RadioBox, RadioBoxBare};
-pub use scroll::ScrollRegion;
+pub use render_cache::RenderCache;
+pub use scroll::{ScrollBarPolicy, ScrollRegion};
 pub use scrollbar::ScrollBar;
+pub use scrubber::Scrubber;
 pub use separator::Separator;
+pub use size_group::SizeGroupMember;
+pub use size_reserve::SizeReserve;
 pub use slider::{Slider, SliderType};
 pub use splitter::*;
 pub use stack::{BoxStack, RefStack, Stack};
-pub use window::Window;
+pub use window::{Window, WindowLayer, WindowTemplate};
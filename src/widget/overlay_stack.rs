@@ -0,0 +1,251 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! A stack of overlaid widgets
+
+use std::ops::{Index, IndexMut};
+
+use kas::prelude::*;
+
+/// A stack of overlaid boxed widgets
+///
+/// This is a parametrisation of [`OverlayStack`].
+pub type BoxOverlayStack<M> = OverlayStack<Box<dyn Widget<Msg = M>>>;
+
+/// Placement of a child of an [`OverlayStack`]
+#[derive(Clone, Copy, Debug, Default)]
+struct Layer {
+    z: i32,
+    align: AlignHints,
+    ideal: Size,
+}
+
+/// A stack of overlaid widgets
+///
+/// An overlay stack places all child widgets within the same rect, drawn in
+/// order of increasing z-order (thus children with higher `z` appear on top).
+/// Where children overlap, events go to the top-most child. Children with
+/// equal `z` are drawn in the order added.
+///
+/// Each child has its own alignment: a child aligned other than
+/// [`Align::Stretch`] (the default) on an axis is given its ideal size on that
+/// axis and positioned within the stack's rect accordingly. This allows
+/// e.g. badges, loading indicators and watermark labels to be layered over
+/// other content.
+///
+/// The stack's size requirements are the maximum of those of its children.
+#[handler(send=noauto, msg=<W as event::Handler>::Msg)]
+#[widget(children=noauto)]
+#[derive(Clone, Default, Debug, Widget)]
+pub struct OverlayStack<W: Widget> {
+    #[widget_core]
+    core: CoreData,
+    widgets: Vec<W>,
+    layers: Vec<Layer>,
+    /// Child indices, sorted by z-order
+    order: Vec<usize>,
+}
+
+impl<W: Widget> WidgetChildren for OverlayStack<W> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.widgets.len()
+    }
+    #[inline]
+    fn get(&self, index: usize) -> Option<&dyn WidgetConfig> {
+        self.widgets.get(index).map(|w| w.as_widget())
+    }
+    #[inline]
+    fn get_mut(&mut self, index: usize) -> Option<&mut dyn WidgetConfig> {
+        self.widgets.get_mut(index).map(|w| w.as_widget_mut())
+    }
+}
+
+impl<W: Widget> Layout for OverlayStack<W> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let mut rules = SizeRules::EMPTY;
+        for (child, layer) in self.widgets.iter_mut().zip(self.layers.iter_mut()) {
+            let child_rules = child.size_rules(size_handle, axis);
+            match axis.is_vertical() {
+                false => layer.ideal.0 = child_rules.ideal_size(),
+                true => layer.ideal.1 = child_rules.ideal_size(),
+            }
+            rules = rules.max(child_rules);
+        }
+        rules
+    }
+
+    fn set_rect(&mut self, rect: Rect, _: AlignHints) {
+        self.core.rect = rect;
+        for (child, layer) in self.widgets.iter_mut().zip(self.layers.iter()) {
+            let align = layer.align;
+            let child_rect = align
+                .complete(Align::Stretch, Align::Stretch, layer.ideal)
+                .apply(rect);
+            child.set_rect(child_rect, align);
+        }
+    }
+
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        if !self.rect().contains(coord) {
+            return None;
+        }
+
+        for index in self.order.iter().rev() {
+            if let Some(id) = self.widgets[*index].find_id(coord) {
+                return Some(id);
+            }
+        }
+
+        Some(self.id())
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
+        let disabled = disabled || self.is_disabled();
+        for index in &self.order {
+            self.widgets[*index].draw(draw_handle, mgr, disabled);
+        }
+    }
+}
+
+impl<W: Widget> event::SendEvent for OverlayStack<W> {
+    fn send(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
+        if !self.is_disabled() {
+            for child in &mut self.widgets {
+                if id <= child.id() {
+                    return child.send(mgr, id, event);
+                }
+            }
+        }
+
+        Response::Unhandled(event)
+    }
+}
+
+impl<W: Widget> OverlayStack<W> {
+    /// Construct a new, empty instance
+    pub fn new() -> Self {
+        OverlayStack {
+            core: Default::default(),
+            widgets: vec![],
+            layers: vec![],
+            order: vec![],
+        }
+    }
+
+    /// Add a child widget (chain style)
+    ///
+    /// See [`OverlayStack::push`].
+    pub fn with(mut self, widget: W, z: i32, align: AlignHints) -> Self {
+        let _ = self.push(widget, z, align);
+        self
+    }
+
+    fn update_order(&mut self) {
+        let layers = &self.layers;
+        self.order = (0..layers.len()).collect();
+        // Note: sort is stable, preserving insertion order for equal z
+        self.order.sort_by_key(|index| layers[*index].z);
+    }
+
+    /// True if there are no child widgets
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
+
+    /// Returns the number of child widgets
+    pub fn len(&self) -> usize {
+        self.widgets.len()
+    }
+
+    /// Remove all child widgets
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action) if any widget is
+    /// removed.
+    pub fn clear(&mut self) -> TkAction {
+        let action = match self.widgets.is_empty() {
+            true => TkAction::None,
+            false => TkAction::Reconfigure,
+        };
+        self.widgets.clear();
+        self.layers.clear();
+        self.order.clear();
+        action
+    }
+
+    /// Append a child widget with z-order `z` and alignment `align`
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action).
+    pub fn push(&mut self, widget: W, z: i32, align: AlignHints) -> TkAction {
+        self.widgets.push(widget);
+        let ideal = Size::ZERO;
+        self.layers.push(Layer { z, align, ideal });
+        self.update_order();
+        TkAction::Reconfigure
+    }
+
+    /// Removes the child widget at position `index`
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action).
+    pub fn remove(&mut self, index: usize) -> (W, TkAction) {
+        let r = self.widgets.remove(index);
+        self.layers.remove(index);
+        self.update_order();
+        (r, TkAction::Reconfigure)
+    }
+
+    /// Get the z-order of the child at `index`
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn z_order(&self, index: usize) -> i32 {
+        self.layers[index].z
+    }
+
+    /// Set the z-order of the child at `index`
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// Returns [`TkAction::RegionMoved`] if the z-order changed (since this
+    /// affects both drawing and which widget is under the mouse).
+    pub fn set_z_order(&mut self, index: usize, z: i32) -> TkAction {
+        if self.layers[index].z == z {
+            return TkAction::None;
+        }
+        self.layers[index].z = z;
+        self.update_order();
+        TkAction::RegionMoved
+    }
+
+    /// Set the alignment of the child at `index`
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// Triggers a [set-size action](Manager::send_action).
+    pub fn set_align(&mut self, index: usize, align: AlignHints) -> TkAction {
+        self.layers[index].align = align;
+        TkAction::SetSize
+    }
+
+    /// Iterate over children
+    pub fn iter(&self) -> std::slice::Iter<W> {
+        self.widgets.iter()
+    }
+}
+
+impl<W: Widget> Index<usize> for OverlayStack<W> {
+    type Output = W;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.widgets[index]
+    }
+}
+
+impl<W: Widget> IndexMut<usize> for OverlayStack<W> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.widgets[index]
+    }
+}
@@ -0,0 +1,152 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Render cache wrapper
+
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use kas::prelude::*;
+
+/// A wrapper caching the draw output of its child
+///
+/// The child is drawn via [`DrawHandle::cached_region`]: where supported by
+/// the toolkit, output is rendered to a texture and re-used on subsequent
+/// frames until invalidated. This may significantly reduce the cost of
+/// redrawing complex but rarely-changing content, e.g. a panel beside an
+/// animation.
+///
+/// Cached content is invalidated automatically when the child is resized,
+/// receives an event, or when the input state (hover, depress, focus) of any
+/// widget within changes. Toolkits additionally invalidate cached content when
+/// the theme changes, and themes redraw content while animated (e.g. a hover
+/// highlight fading in). Where the child is changed by other means (e.g. via
+/// [`RenderCache::inner_mut`] or a handle), call [`RenderCache::invalidate`].
+///
+/// Limitation: content drawn within nested clip regions (e.g. a
+/// [`ScrollRegion`](super::ScrollRegion)) is not supported.
+#[handler(send=noauto, msg = <W as Handler>::Msg)]
+#[derive(Clone, Debug, Widget)]
+pub struct RenderCache<W: Widget> {
+    #[widget_core]
+    core: CoreData,
+    #[widget]
+    child: W,
+    valid: Cell<bool>,
+    input_hash: Cell<u64>,
+}
+
+impl<W: Widget> RenderCache<W> {
+    /// Construct
+    pub fn new(child: W) -> Self {
+        RenderCache {
+            core: Default::default(),
+            child,
+            valid: Cell::new(false),
+            input_hash: Cell::new(0),
+        }
+    }
+
+    /// Invalidate cached content
+    ///
+    /// The child is redrawn on the next frame. Note that this does not itself
+    /// request a redraw.
+    #[inline]
+    pub fn invalidate(&self) {
+        self.valid.set(false);
+    }
+
+    /// Access inner widget directly
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.child
+    }
+
+    /// Access inner widget directly
+    ///
+    /// This invalidates cached content.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut W {
+        self.invalidate();
+        &mut self.child
+    }
+}
+
+/// Hash the input state of `widget` and its descendants
+fn hash_input_state<H: Hasher>(widget: &dyn WidgetConfig, mgr: &ManagerState, state: &mut H) {
+    let id = widget.id();
+    if mgr.is_hovered(id)
+        || mgr.is_depressed(id)
+        || mgr.nav_focus(id)
+        || mgr.char_focus(id)
+        || widget.is_disabled()
+    {
+        id.hash(state);
+        mgr.is_hovered(id).hash(state);
+        mgr.is_depressed(id).hash(state);
+        mgr.nav_focus(id).hash(state);
+        mgr.char_focus(id).hash(state);
+        widget.is_disabled().hash(state);
+    }
+    for index in 0..widget.len() {
+        if let Some(child) = widget.get(index) {
+            hash_input_state(child, mgr, state);
+        }
+    }
+}
+
+impl<W: Widget> Layout for RenderCache<W> {
+    #[inline]
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        self.child.size_rules(size_handle, axis)
+    }
+
+    fn set_rect(&mut self, rect: Rect, align: AlignHints) {
+        self.invalidate();
+        self.core.rect = rect;
+        self.child.set_rect(rect, align);
+    }
+
+    #[inline]
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        if !self.rect().contains(coord) {
+            return None;
+        }
+        self.child.find_id(coord).or(Some(self.id()))
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
+        let disabled = disabled || self.is_disabled();
+
+        let mut hasher = DefaultHasher::new();
+        disabled.hash(&mut hasher);
+        mgr.show_accel_labels().hash(&mut hasher);
+        hash_input_state(&self.child, mgr, &mut hasher);
+        let hash = hasher.finish();
+        let same_input = self.input_hash.replace(hash) == hash;
+        let valid = self.valid.replace(true) && same_input;
+
+        let child = &self.child;
+        draw_handle.cached_region(u64::from(self.id()), self.core.rect, valid, &mut |dh| {
+            child.draw(dh, mgr, disabled)
+        });
+    }
+}
+
+impl<W: Widget> event::SendEvent for RenderCache<W> {
+    fn send(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
+        if self.is_disabled() {
+            return Response::Unhandled(event);
+        }
+
+        if id <= self.child.id() {
+            self.invalidate();
+            self.child.send(mgr, id, event)
+        } else {
+            Manager::handle_generic(self, mgr, event)
+        }
+    }
+}
@@ -9,15 +9,59 @@ use std::fmt::Debug;
 
 use super::ScrollBar;
 use kas::draw::{ClipRegion, TextClass};
-use kas::event::ControlKey;
 use kas::event::ScrollDelta::{LineDelta, PixelDelta};
+use kas::event::{ControlKey, ScrollOverride};
 use kas::prelude::*;
 
+/// Visibility policy of a scroll bar
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScrollBarPolicy {
+    /// The bar is always shown
+    Always,
+    /// The bar is never shown (content may still be scrolled by other means)
+    Never,
+    /// The bar is shown only when content does not fit
+    Auto,
+}
+
+impl Default for ScrollBarPolicy {
+    fn default() -> Self {
+        ScrollBarPolicy::Never
+    }
+}
+
+/// Decide which bars to show, given each axis's policy
+///
+/// Each bar takes space from the other axis, thus showing one bar may require
+/// showing the other. Since showing a bar never frees space, iterating until
+/// stable converges (in at most three steps).
+fn solve_bars(
+    policy: (ScrollBarPolicy, ScrollBarPolicy),
+    size: Size,
+    min_child_size: Size,
+    bar_width: u32,
+) -> (bool, bool) {
+    use ScrollBarPolicy::*;
+    let mut show = (policy.0 == Always, policy.1 == Always);
+    loop {
+        let width = size.0.saturating_sub(if show.1 { bar_width } else { 0 });
+        let height = size.1.saturating_sub(if show.0 { bar_width } else { 0 });
+        let next = (
+            show.0 || (policy.0 == Auto && min_child_size.0 > width),
+            show.1 || (policy.1 == Auto && min_child_size.1 > height),
+        );
+        if next == show {
+            return show;
+        }
+        show = next;
+    }
+}
+
 /// A scrollable region
 ///
 /// This region supports scrolling via mouse wheel and drag.
-/// Optionally, it can have scroll bars (see [`ScrollRegion::show_bars`] and
-/// [`ScrollRegion::with_bars`]).
+/// Optionally, it can have scroll bars, each controlled by a
+/// [`ScrollBarPolicy`] (see [`ScrollRegion::with_bar_policy`]).
 ///
 /// Scroll regions translate their contents by an `offset`, which has a
 /// minimum value of [`Coord::ZERO`] and a maximum value of
@@ -33,8 +77,9 @@ pub struct ScrollRegion<W: Widget> {
     max_offset: Coord,
     offset: Coord,
     scroll_rate: f32,
+    scroll_override: ScrollOverride,
     bar_width: u32,
-    auto_bars: bool,
+    bar_policy: (ScrollBarPolicy, ScrollBarPolicy),
     show_bars: (bool, bool),
     #[widget]
     horiz_bar: ScrollBar<kas::Right>,
@@ -54,9 +99,10 @@ impl<W: Widget> ScrollRegion<W> {
             inner_size: Size::ZERO,
             max_offset: Coord::ZERO,
             offset: Coord::ZERO,
-            scroll_rate: 30.0,
+            scroll_rate: 10.0,
+            scroll_override: Default::default(),
             bar_width: 0,
-            auto_bars: false,
+            bar_policy: (ScrollBarPolicy::Never, ScrollBarPolicy::Never),
             show_bars: (false, false),
             horiz_bar: ScrollBar::new(),
             vert_bar: ScrollBar::new(),
@@ -64,30 +110,88 @@ impl<W: Widget> ScrollRegion<W> {
         }
     }
 
-    /// Auto-enable bars
+    /// Override the number of lines scrolled per mouse-wheel notch
+    ///
+    /// By default, [`InputConfig::scroll_lines`] is used.
+    ///
+    /// [`InputConfig::scroll_lines`]: kas::event::InputConfig::scroll_lines
+    #[inline]
+    pub fn with_scroll_lines(mut self, lines: u32) -> Self {
+        self.scroll_override.lines = Some(lines);
+        self
+    }
+
+    /// Override scroll settings
+    ///
+    /// By default, [`InputConfig`] settings are used.
     ///
-    /// If enabled, this automatically enables/disables scroll bars when
-    /// resized.
+    /// [`InputConfig`]: kas::event::InputConfig
+    #[inline]
+    pub fn with_scroll_override(mut self, scroll: ScrollOverride) -> Self {
+        self.scroll_override = scroll;
+        self
+    }
+
+    /// Set the policy of each scroll bar
     ///
-    /// This has the side-effect of reserving enough space for scroll bars even
-    /// when not required.
+    /// Bars with policy [`ScrollBarPolicy::Auto`] are enabled/disabled
+    /// automatically when resized. This has the side-effect of reserving
+    /// enough space for scroll bars even when not required.
     #[inline]
-    pub fn with_auto_bars(mut self, enable: bool) -> Self {
-        self.auto_bars = enable;
+    pub fn with_bar_policy(mut self, horiz: ScrollBarPolicy, vert: ScrollBarPolicy) -> Self {
+        self.bar_policy = (horiz, vert);
         self
     }
 
+    /// Set the policy of each scroll bar
+    ///
+    /// Returns [`TkAction::Resize`] since the change affects layout.
+    #[inline]
+    pub fn set_bar_policy(&mut self, horiz: ScrollBarPolicy, vert: ScrollBarPolicy) -> TkAction {
+        self.bar_policy = (horiz, vert);
+        TkAction::Resize
+    }
+
+    /// Get the policy of each scroll bar
+    #[inline]
+    pub fn bar_policy(&self) -> (ScrollBarPolicy, ScrollBarPolicy) {
+        self.bar_policy
+    }
+
+    /// Auto-enable bars
+    ///
+    /// If enabled, both bars use [`ScrollBarPolicy::Auto`], otherwise
+    /// [`ScrollBarPolicy::Never`].
+    #[inline]
+    pub fn with_auto_bars(self, enable: bool) -> Self {
+        let policy = match enable {
+            true => ScrollBarPolicy::Auto,
+            false => ScrollBarPolicy::Never,
+        };
+        self.with_bar_policy(policy, policy)
+    }
+
     /// Set which scroll bars are visible
+    ///
+    /// Sets each bar's policy to [`ScrollBarPolicy::Always`] or
+    /// [`ScrollBarPolicy::Never`].
     #[inline]
     pub fn with_bars(mut self, horiz: bool, vert: bool) -> Self {
-        self.show_bars = (horiz, vert);
+        self.show_bars(horiz, vert);
         self
     }
 
     /// Set which scroll bars are visible
+    ///
+    /// Sets each bar's policy to [`ScrollBarPolicy::Always`] or
+    /// [`ScrollBarPolicy::Never`]. A resize is required to take effect.
     #[inline]
     pub fn show_bars(&mut self, horiz: bool, vert: bool) {
-        self.show_bars = (horiz, vert);
+        let policy = |show| match show {
+            true => ScrollBarPolicy::Always,
+            false => ScrollBarPolicy::Never,
+        };
+        self.bar_policy = (policy(horiz), policy(vert));
     }
 
     /// Access inner widget directly
@@ -125,7 +229,8 @@ impl<W: Widget> ScrollRegion<W> {
             TkAction::None
         } else {
             self.offset = offset;
-            TkAction::RegionMoved
+            let viewport = Rect::new(self.core.rect.pos + offset, self.inner_size);
+            TkAction::RegionMoved + self.inner.set_viewport(viewport)
         }
     }
 }
@@ -138,37 +243,46 @@ impl<W: Widget> WidgetConfig for ScrollRegion<W> {
 
 impl<W: Widget> Layout for ScrollRegion<W> {
     fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
-        let mut rules = self.inner.size_rules(size_handle, axis);
+        let mut child_axis = axis;
+        if let Some(mut width) = axis.size_other_if_fixed(true) {
+            // The child is never narrower than its minimum width, but a
+            // vertical scrollbar may take some of the available width.
+            if self.bar_policy.1 != ScrollBarPolicy::Never {
+                width = width.saturating_sub(self.bar_width);
+            }
+            child_axis = AxisInfo::new(true, Some(width.max(self.min_child_size.0)));
+        }
+        let mut rules = self.inner.size_rules(size_handle, child_axis);
         if axis.is_horizontal() {
             self.min_child_size.0 = rules.min_size();
         } else {
             self.min_child_size.1 = rules.min_size();
         }
         let line_height = size_handle.line_height(TextClass::Label);
-        self.scroll_rate = 3.0 * line_height as f32;
+        self.scroll_rate = line_height as f32;
         rules.reduce_min_to(line_height);
         self.bar_width = (size_handle.scrollbar().0).1;
 
-        if axis.is_horizontal() && (self.auto_bars || self.show_bars.1) {
+        if axis.is_horizontal() && self.bar_policy.1 != ScrollBarPolicy::Never {
             rules.append(self.vert_bar.size_rules(size_handle, axis));
-        } else if axis.is_vertical() && (self.auto_bars || self.show_bars.0) {
+        } else if axis.is_vertical() && self.bar_policy.0 != ScrollBarPolicy::Never {
             rules.append(self.horiz_bar.size_rules(size_handle, axis));
         }
         rules
     }
 
-    fn set_rect(&mut self, rect: Rect, _: AlignHints) {
+    fn set_rect(&mut self, rect: Rect, align: AlignHints) {
         self.core.rect = rect;
         // We use simplified layout code here
         let pos = rect.pos;
         self.inner_size = rect.size;
 
-        if self.auto_bars {
-            self.show_bars = (
-                self.min_child_size.0 + self.bar_width > rect.size.0,
-                self.min_child_size.1 + self.bar_width > rect.size.1,
-            );
-        }
+        self.show_bars = solve_bars(
+            self.bar_policy,
+            rect.size,
+            self.min_child_size,
+            self.bar_width,
+        );
         if self.show_bars.0 {
             self.inner_size.1 -= self.bar_width;
         }
@@ -178,9 +292,13 @@ impl<W: Widget> Layout for ScrollRegion<W> {
 
         let child_size = self.inner_size.max(self.min_child_size);
         let child_rect = Rect::new(pos, child_size);
-        self.inner.set_rect(child_rect, AlignHints::NONE);
+        self.inner.set_rect(child_rect, align);
         self.max_offset = Coord::from(child_size) - Coord::from(self.inner_size);
         self.offset = self.offset.clamp(Coord::ZERO, self.max_offset);
+        let viewport = Rect::new(pos + self.offset, self.inner_size);
+        // Lazy content marks itself via CoreData::reconfigure, which the
+        // toolkit checks after solving layout
+        let _ = self.inner.set_viewport(viewport);
 
         if self.show_bars.0 {
             let pos = Coord(pos.0, pos.1 + self.inner_size.1 as i32);
@@ -314,7 +432,10 @@ impl<W: Widget> event::SendEvent for ScrollRegion<W> {
 
         let scroll = |w: &mut Self, mgr: &mut Manager, delta| {
             let d = match delta {
-                LineDelta(x, y) => Coord((-w.scroll_rate * x) as i32, (w.scroll_rate * y) as i32),
+                LineDelta(x, y) => {
+                    let rate = w.scroll_rate;
+                    Coord((-rate * x) as i32, (rate * y) as i32)
+                }
                 PixelDelta(d) => d,
             };
             let action = w.set_offset(w.offset - d);
@@ -330,11 +451,12 @@ impl<W: Widget> event::SendEvent for ScrollRegion<W> {
 
         match event {
             Event::Control(key) => {
+                let lines = mgr.config().scroll_lines as f32;
                 let delta = match key {
-                    ControlKey::Left => LineDelta(-1.0, 0.0),
-                    ControlKey::Right => LineDelta(1.0, 0.0),
-                    ControlKey::Up => LineDelta(0.0, 1.0),
-                    ControlKey::Down => LineDelta(0.0, -1.0),
+                    ControlKey::Left => LineDelta(-lines, 0.0),
+                    ControlKey::Right => LineDelta(lines, 0.0),
+                    ControlKey::Up => LineDelta(0.0, lines),
+                    ControlKey::Down => LineDelta(0.0, -lines),
                     ControlKey::Home | ControlKey::End => {
                         let action = self.set_offset(match key {
                             ControlKey::Home => Coord::ZERO,
@@ -355,7 +477,13 @@ impl<W: Widget> event::SendEvent for ScrollRegion<W> {
                 };
                 scroll(self, mgr, delta)
             }
-            Event::Scroll(delta) => scroll(self, mgr, delta),
+            Event::Scroll(delta) => {
+                let adjusted = self.scroll_override.apply(mgr.config(), delta);
+                match scroll(self, mgr, adjusted) {
+                    Response::Unhandled(_) => Response::Unhandled(Event::Scroll(delta)),
+                    r => r,
+                }
+            }
             Event::PressStart { source, coord, .. } if source.is_primary() => {
                 mgr.request_grab(
                     self.id(),
@@ -383,3 +511,17 @@ impl<W: Widget> event::SendEvent for ScrollRegion<W> {
         }
     }
 }
+
+#[test]
+fn scroll_bar_policy() {
+    use ScrollBarPolicy::*;
+    let size = Size(100, 100);
+    let bars = |policy, min| solve_bars(policy, size, min, 10);
+    assert_eq!(bars((Auto, Auto), Size(100, 100)), (false, false));
+    assert_eq!(bars((Auto, Auto), Size(101, 50)), (true, false));
+    // The horizontal bar leaves only 90 pixels of height
+    assert_eq!(bars((Auto, Auto), Size(101, 95)), (true, true));
+    assert_eq!(bars((Never, Auto), Size(101, 95)), (false, false));
+    assert_eq!(bars((Always, Auto), Size(50, 95)), (true, true));
+    assert_eq!(bars((Always, Never), Size(50, 50)), (true, false));
+}
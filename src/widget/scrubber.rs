@@ -0,0 +1,209 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! `Scrubber` control
+
+use super::SliderType;
+use kas::draw::TextClass;
+use kas::event::{ControlKey, CursorIcon, CursorMode, GrabMode, PressSource};
+use kas::prelude::*;
+
+/// Drag distance (in pixels, before DPI scaling) per `step`
+const PIXELS_PER_STEP: f64 = 4.0;
+
+/// A numeric field adjusted by dragging ("scrubbing")
+///
+/// The value is displayed as text. Dragging horizontally adjusts the value by
+/// one `step` per few pixels of motion: holding <kbd>Shift</kbd> reduces the
+/// rate (for fine adjustment) while holding <kbd>Ctrl</kbd> increases it.
+/// With a mouse, the cursor is hidden and locked in place during the drag
+/// (see [`CursorMode::Relative`]), thus dragging is not limited by the
+/// window's edges.
+///
+/// When keyboard navigation is used, arrow keys adjust the value by `step`
+/// and <kbd>Home</kbd>/<kbd>End</kbd> select the range's bounds.
+#[handler(handle=noauto)]
+#[widget(config(key_nav = true, cursor_icon = CursorIcon::EwResize))]
+#[derive(Clone, Debug, Widget)]
+pub struct Scrubber<T: SliderType> {
+    #[widget_core]
+    core: CoreData,
+    range: (T, T),
+    step: T,
+    value: T,
+    label: PreparedText,
+    format: fn(T) -> String,
+    scale_factor: f64,
+    /// Accumulated drag in units of `step`
+    drag_acc: f64,
+}
+
+impl<T: SliderType> Scrubber<T> {
+    /// Construct a scrubber
+    ///
+    /// Values vary between the given `min` and `max` in increments of `step`.
+    /// The initial value defaults to the range's lower bound but may be
+    /// specified via [`Scrubber::with_value`].
+    ///
+    /// The value is displayed using its `Debug` representation by default;
+    /// see [`Scrubber::with_format`].
+    pub fn new(min: T, max: T, step: T) -> Self {
+        assert!(min <= max);
+        let format = |value| format!("{:?}", value);
+        Scrubber {
+            core: Default::default(),
+            range: (min, max),
+            step,
+            value: min,
+            label: PreparedText::new_single(format(min).into()),
+            format,
+            scale_factor: 1.0,
+            drag_acc: 0.0,
+        }
+    }
+
+    /// Set the initial value
+    #[inline]
+    pub fn with_value(mut self, value: T) -> Self {
+        let _ = self.set_value(value);
+        self
+    }
+
+    /// Set the formatter used to display the value
+    pub fn with_format(mut self, format: fn(T) -> String) -> Self {
+        self.format = format;
+        let _ = self.label.set_and_prepare(format(self.value));
+        self
+    }
+
+    /// Get the current value
+    #[inline]
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// Set the value
+    ///
+    /// The value is clamped to the range. Returns [`TkAction::Redraw`] if a
+    /// redraw is required.
+    pub fn set_value(&mut self, mut value: T) -> TkAction {
+        if value < self.range.0 {
+            value = self.range.0;
+        } else if value > self.range.1 {
+            value = self.range.1;
+        }
+        if value == self.value {
+            TkAction::None
+        } else {
+            self.value = value;
+            self.label.set_and_prepare((self.format)(value)) + TkAction::Redraw
+        }
+    }
+
+    /// Adjust the value by `n` steps, stopping at the range's bounds
+    fn step_by(&mut self, n: i64) -> TkAction {
+        let mut value = self.value;
+        for _ in 0..n.abs() {
+            if n > 0 {
+                if self.range.1 - value <= self.step {
+                    value = self.range.1;
+                    break;
+                }
+                value = value + self.step;
+            } else {
+                if value - self.range.0 <= self.step {
+                    value = self.range.0;
+                    break;
+                }
+                value = value - self.step;
+            }
+        }
+        self.set_value(value)
+    }
+
+    fn respond(&self, mgr: &mut Manager, action: TkAction) -> Response<T> {
+        if action == TkAction::None {
+            Response::None
+        } else {
+            mgr.send_action(action);
+            Response::Msg(self.value)
+        }
+    }
+}
+
+impl<T: SliderType> Layout for Scrubber<T> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        self.scale_factor = size_handle.scale_factor() as f64;
+        let frame_sides = size_handle.edit_surround();
+        let inner = size_handle.inner_margin();
+        let frame_size = frame_sides.0 + frame_sides.1 + inner + inner;
+        let margins = size_handle.outer_margins();
+        let frame_rules = SizeRules::extract_fixed(axis.is_vertical(), frame_size, margins);
+
+        let content_rules = size_handle.text_bound(&mut self.label, TextClass::Edit, axis);
+        content_rules.surrounded_by(frame_rules, true)
+    }
+
+    fn set_rect(&mut self, rect: Rect, align: AlignHints) {
+        self.core.rect = rect;
+        self.label.update_env(|env| {
+            env.set_bounds(rect.size.into());
+            env.set_align(align.unwrap_or(Align::Centre, Align::Centre));
+        });
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
+        let state = self.input_state(mgr, disabled);
+        draw_handle.edit_box(self.core.rect, state);
+        draw_handle.text(self.core.rect.pos, &self.label, TextClass::Edit, state);
+    }
+}
+
+impl<T: SliderType> event::Handler for Scrubber<T> {
+    type Msg = T;
+
+    fn handle(&mut self, mgr: &mut Manager, event: Event) -> Response<T> {
+        match event {
+            Event::Control(key) => {
+                let action = match key {
+                    ControlKey::Left | ControlKey::Down => self.step_by(-1),
+                    ControlKey::Right | ControlKey::Up => self.step_by(1),
+                    ControlKey::Home => self.set_value(self.range.0),
+                    ControlKey::End => self.set_value(self.range.1),
+                    key => return Response::Unhandled(Event::Control(key)),
+                };
+                self.respond(mgr, action)
+            }
+            Event::PressStart { source, coord, .. } if source.is_primary() => {
+                let icon = Some(CursorIcon::EwResize);
+                if mgr.request_grab(self.id(), source, coord, GrabMode::Grab, icon) {
+                    self.drag_acc = 0.0;
+                    if let PressSource::Mouse(..) = source {
+                        mgr.set_cursor_mode(self.id(), CursorMode::Relative);
+                    }
+                }
+                Response::None
+            }
+            Event::PressMove { delta, .. } => {
+                let modifiers = mgr.modifiers();
+                let rate = if modifiers.shift() {
+                    0.1
+                } else if modifiers.ctrl() {
+                    10.0
+                } else {
+                    1.0
+                };
+                let pixels = PIXELS_PER_STEP * self.scale_factor;
+                self.drag_acc += delta.0 as f64 * rate / pixels;
+                let n = self.drag_acc.trunc();
+                self.drag_acc -= n;
+                let action = self.step_by(n as i64);
+                self.respond(mgr, action)
+            }
+            Event::PressEnd { .. } => Response::None,
+            event => Response::Unhandled(event),
+        }
+    }
+}
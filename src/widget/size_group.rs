@@ -0,0 +1,111 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Size group member
+
+use kas::layout::SizeGroup;
+use kas::prelude::*;
+
+/// A widget whose size is linked to other members of a [`SizeGroup`]
+///
+/// This wrapper reports the combined [`SizeRules`] of all members of the
+/// group on linked axes. For example, to align the labels of a form where each
+/// row is a separate widget, wrap each label with the same horizontal group:
+/// ```
+/// use kas::layout::SizeGroup;
+/// use kas::widget::{Label, SizeGroupMember};
+///
+/// let group = SizeGroup::horizontal();
+/// let a = SizeGroupMember::new(Label::new("Name"), &group);
+/// let b = SizeGroupMember::new(Label::new("Date of birth"), &group);
+/// ```
+///
+/// Cloning a member adds the clone to the same group.
+#[handler(msg = <W as Handler>::Msg)]
+#[derive(Debug, Widget)]
+pub struct SizeGroupMember<W: Widget> {
+    #[widget_core]
+    core: CoreData,
+    #[widget]
+    child: W,
+    group: SizeGroup,
+    key: u32,
+}
+
+impl<W: Widget> SizeGroupMember<W> {
+    /// Construct, adding `child` to `group`
+    pub fn new(child: W, group: &SizeGroup) -> Self {
+        SizeGroupMember {
+            core: Default::default(),
+            child,
+            group: group.clone(),
+            key: group.join(),
+        }
+    }
+
+    /// Access inner widget directly
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.child
+    }
+
+    /// Access inner widget directly
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut W {
+        &mut self.child
+    }
+
+    /// Access the group
+    #[inline]
+    pub fn group(&self) -> &SizeGroup {
+        &self.group
+    }
+}
+
+impl<W: Widget + Clone> Clone for SizeGroupMember<W> {
+    fn clone(&self) -> Self {
+        SizeGroupMember {
+            core: self.core.clone(),
+            child: self.child.clone(),
+            group: self.group.clone(),
+            key: self.group.join(),
+        }
+    }
+}
+
+impl<W: Widget> Drop for SizeGroupMember<W> {
+    fn drop(&mut self) {
+        self.group.leave(self.key);
+    }
+}
+
+impl<W: Widget> Layout for SizeGroupMember<W> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let rules = self.child.size_rules(size_handle, axis);
+        if self.group.links(axis.is_vertical()) {
+            self.group.update(self.key, axis.is_vertical(), rules)
+        } else {
+            rules
+        }
+    }
+
+    fn set_rect(&mut self, rect: Rect, align: AlignHints) {
+        self.core.rect = rect;
+        self.child.set_rect(rect, align);
+    }
+
+    #[inline]
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        if !self.rect().contains(coord) {
+            return None;
+        }
+        self.child.find_id(coord).or(Some(self.id()))
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
+        let disabled = disabled || self.is_disabled();
+        self.child.draw(draw_handle, mgr, disabled);
+    }
+}
@@ -0,0 +1,206 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Size reservation wrapper
+
+use kas::layout::Length;
+use kas::prelude::*;
+
+/// A wrapper overriding the size requirements of its child
+///
+/// Sizes are specified in logical pixels (before DPI scaling) or, via
+/// [`SizeReserve::with_min`] etc., as a [`Length`] (which may be relative to
+/// the font size):
+///
+/// -   the minimum size may be used to reserve space, e.g. for a label whose
+///     text changes (see [`SizeRules::set_min_size`])
+/// -   the ideal size replaces the child's preference
+/// -   the maximum size caps widget growth: the child is never given a larger
+///     rect, and is aligned within any excess space according to the
+///     [`AlignHints`] provided by the parent (default: centred)
+/// -   a percentage of the parent's length may be requested (see
+///     [`SizeRules::with_percent`])
+///
+/// A zero length leaves that axis unchanged; e.g. a maximum size of
+/// `Size(200, 0)` caps only the width.
+///
+/// The same may be achieved for children of widgets using `make_widget` or
+/// `#[derive(Widget)]` with `#[widget(min_size = ...)]` etc.; see the
+/// [`macros`](kas::macros) documentation.
+#[handler(msg = <W as Handler>::Msg)]
+#[derive(Clone, Debug, Widget)]
+pub struct SizeReserve<W: Widget> {
+    #[widget_core]
+    core: CoreData,
+    #[widget]
+    child: W,
+    min: Option<(Length, Length)>,
+    ideal: Option<(Length, Length)>,
+    max: Option<(Length, Length)>,
+    percent: Option<u32>,
+    max_px: Size,
+}
+
+impl<W: Widget> SizeReserve<W> {
+    /// Construct, without any size overrides
+    pub fn new(child: W) -> Self {
+        SizeReserve {
+            core: Default::default(),
+            child,
+            min: None,
+            ideal: None,
+            max: None,
+            percent: None,
+            max_px: Size::ZERO,
+        }
+    }
+
+    /// Set the minimum size in logical pixels (chain style)
+    #[inline]
+    pub fn with_min_size(self, size: Size) -> Self {
+        self.with_min(logical(size.0), logical(size.1))
+    }
+
+    /// Set the ideal size in logical pixels (chain style)
+    #[inline]
+    pub fn with_ideal_size(self, size: Size) -> Self {
+        self.with_ideal(logical(size.0), logical(size.1))
+    }
+
+    /// Set the maximum size in logical pixels (chain style)
+    #[inline]
+    pub fn with_max_size(self, size: Size) -> Self {
+        self.with_max(logical(size.0), logical(size.1))
+    }
+
+    /// Set the minimum width and height (chain style)
+    #[inline]
+    pub fn with_min(mut self, width: Length, height: Length) -> Self {
+        self.min = Some((width, height));
+        self
+    }
+
+    /// Set the ideal width and height (chain style)
+    #[inline]
+    pub fn with_ideal(mut self, width: Length, height: Length) -> Self {
+        self.ideal = Some((width, height));
+        self
+    }
+
+    /// Set the maximum width and height (chain style)
+    #[inline]
+    pub fn with_max(mut self, width: Length, height: Length) -> Self {
+        self.max = Some((width, height));
+        self
+    }
+
+    /// Request a percentage of the parent's length (chain style)
+    ///
+    /// This applies on both axes, but within a row or column is only effective
+    /// along the main axis (e.g. a sidebar taking 30% of a row's width).
+    #[inline]
+    pub fn with_percent(mut self, percent: u32) -> Self {
+        self.percent = Some(percent);
+        self
+    }
+
+    /// Access inner widget directly
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.child
+    }
+
+    /// Access inner widget directly
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut W {
+        &mut self.child
+    }
+}
+
+fn logical(px: u32) -> Length {
+    Length::Px(px as f32)
+}
+
+impl<W: Widget> Layout for SizeReserve<W> {
+    fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
+        let mut child_axis = axis;
+        if let (true, Some(lengths)) = (axis.is_vertical(), self.max) {
+            // The child is never given more than the maximum width
+            let width = lengths.0.to_px(size_handle);
+            if width != 0 {
+                child_axis.limit_other(width);
+            }
+        }
+        let mut rules = self.child.size_rules(size_handle, child_axis);
+
+        let size_handle = &*size_handle;
+        // Zero is treated as unset
+        let resolve = |lengths: (Length, Length)| {
+            Some(axis.extract_size(Length::to_size(lengths, size_handle))).filter(|px| *px != 0)
+        };
+        if let Some(min) = self.min.and_then(|l| resolve(l)) {
+            rules.set_min_size(min);
+        }
+        if let Some(ideal) = self.ideal.and_then(|l| resolve(l)) {
+            rules.set_ideal_size(ideal);
+        }
+        if let Some(lengths) = self.max {
+            self.max_px = Length::to_size(lengths, size_handle);
+            if let Some(max) = resolve(lengths) {
+                rules.set_max_size(max);
+            }
+        }
+        if let Some(percent) = self.percent {
+            rules = rules.with_percent(percent);
+        }
+        rules
+    }
+
+    fn set_rect(&mut self, mut rect: Rect, align: AlignHints) {
+        if self.max.is_some() {
+            let mut max = self.max_px;
+            if max.0 == 0 {
+                max.0 = rect.size.0;
+            }
+            if max.1 == 0 {
+                max.1 = rect.size.1;
+            }
+            let (horiz, vert) = align.unwrap_or(Align::Centre, Align::Centre);
+            let stretch_to_centre = |align| match align {
+                Align::Stretch => Align::Centre,
+                align => align,
+            };
+            rect = AlignHints::NONE
+                .complete(stretch_to_centre(horiz), stretch_to_centre(vert), max)
+                .apply(rect);
+        }
+        self.core.rect = rect;
+        self.child.set_rect(rect, align);
+    }
+
+    #[inline]
+    fn find_id(&self, coord: Coord) -> Option<WidgetId> {
+        if !self.rect().contains(coord) {
+            return None;
+        }
+        self.child.find_id(coord).or(Some(self.id()))
+    }
+
+    fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
+        let disabled = disabled || self.is_disabled();
+        self.child.draw(draw_handle, mgr, disabled);
+    }
+}
+
+#[test]
+fn max_size_zero_axis() {
+    use super::{Filler, Frame};
+
+    let mut reserve = SizeReserve::new(Frame::new(Filler::new())).with_max_size(Size(20, 0));
+    let _ = kas::layout::layout_to_text(&mut reserve, Some(Size(200, 100)));
+    let rect = reserve.inner().rect();
+    assert_eq!(rect.size, Size(20, 100));
+    assert_eq!(rect.pos, Coord(90, 0));
+}
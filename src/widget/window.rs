@@ -7,33 +7,44 @@
 
 use smallvec::SmallVec;
 use std::fmt::{self, Debug};
+use std::rc::Rc;
 
 use kas::draw::ClipRegion;
 use kas::event::Callback;
 use kas::prelude::*;
-use kas::WindowId;
+use kas::{Instant, WindowId};
+
+/// A root layer of a [`Window`]
+pub type WindowLayer = Box<dyn Widget<Msg = VoidMsg>>;
 
 /// The main instantiation of the [`Window`] trait.
+///
+/// Besides its main content, a window may host additional *layers* (see
+/// [`Window::add_layer`]): independent widget trees drawn above the content
+/// (but below pop-ups), in order. These are useful for e.g. notifications,
+/// drag ghosts or debug overlays, without entangling these with the content.
 #[handler(send=noauto, generics = <> where W: Widget<Msg = VoidMsg>)]
-#[widget(config=noauto)]
+#[widget(config=noauto, children=noauto)]
 #[derive(Widget)]
 pub struct Window<W: Widget + 'static> {
     #[widget_core]
     core: CoreData,
     restrict_dimensions: (bool, bool),
+    align: AlignHints,
     title: String,
-    #[widget]
     w: W,
+    layers: Vec<WindowLayer>,
     popups: SmallVec<[(WindowId, kas::Popup); 16]>,
     fns: Vec<(Callback, &'static dyn Fn(&mut W, &mut Manager))>,
+    frame_fns: Vec<&'static dyn Fn(&mut W, &mut Manager, Instant)>,
 }
 
 impl<W: Widget> Debug for Window<W> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "Window {{ core: {:?}, solver: <omitted>, w: {:?}, fns: [",
-            self.core, self.w
+            "Window {{ core: {:?}, solver: <omitted>, w: {:?}, layers: {:?}, fns: [",
+            self.core, self.w, self.layers
         )?;
         let mut iter = self.fns.iter();
         if let Some(first) = iter.next() {
@@ -42,7 +53,7 @@ impl<W: Widget> Debug for Window<W> {
                 write!(f, ", ({:?}, <Fn>)", next.0)?;
             }
         }
-        write!(f, "] }}")
+        write!(f, "], frame_fns: <{} omitted> }}", self.frame_fns.len())
     }
 }
 
@@ -51,10 +62,13 @@ impl<W: Widget + Clone> Clone for Window<W> {
         Window {
             core: self.core.clone(),
             restrict_dimensions: self.restrict_dimensions.clone(),
+            align: self.align,
             title: self.title.clone(),
             w: self.w.clone(),
+            layers: self.layers.clone(),
             popups: Default::default(), // these are temporary; don't clone
             fns: self.fns.clone(),
+            frame_fns: self.frame_fns.clone(),
         }
     }
 }
@@ -65,10 +79,13 @@ impl<W: Widget> Window<W> {
         Window {
             core: Default::default(),
             restrict_dimensions: (true, false),
+            align: AlignHints::NONE,
             title: title.to_string(),
             w,
+            layers: vec![],
             popups: Default::default(),
             fns: Vec::new(),
+            frame_fns: Vec::new(),
         }
     }
 
@@ -79,6 +96,55 @@ impl<W: Widget> Window<W> {
         self.restrict_dimensions = (min, max);
     }
 
+    /// Set alignment hints for the content
+    ///
+    /// These apply where the window is larger than its content's ideal size,
+    /// and are combined with any hints given by the toolkit (which take
+    /// precedence). By default, no hints are set.
+    pub fn set_align(&mut self, horiz: Option<Align>, vert: Option<Align>) {
+        self.align = AlignHints::new(horiz, vert);
+    }
+
+    /// Add a root layer
+    ///
+    /// Layers are independent widget trees covering the whole window, drawn
+    /// above the main content (but below pop-ups) in the order added. The
+    /// root widget of each layer is transparent to input: coordinates not over
+    /// one of its descendants fall through to lower layers and the content.
+    ///
+    /// Layers do not affect the window's size requirements.
+    ///
+    /// Returns the index of the new layer. If the window is already running,
+    /// a [reconfigure action](Manager::send_action) is required.
+    pub fn add_layer(&mut self, layer: WindowLayer) -> usize {
+        self.layers.push(layer);
+        self.layers.len() - 1
+    }
+
+    /// Remove the layer at `index`
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// Triggers a [reconfigure action](Manager::send_action).
+    pub fn remove_layer(&mut self, index: usize) -> (WindowLayer, TkAction) {
+        (self.layers.remove(index), TkAction::Reconfigure)
+    }
+
+    /// Get the number of layers (excluding the main content)
+    pub fn num_layers(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Access the layer at `index`, if any
+    pub fn layer(&self, index: usize) -> Option<&WindowLayer> {
+        self.layers.get(index)
+    }
+
+    /// Mutably access the layer at `index`, if any
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut WindowLayer> {
+        self.layers.get_mut(index)
+    }
+
     /// Add a closure to be called, with a reference to self, on the given
     /// condition. The closure must be passed by reference.
     // TODO: consider whether to keep this. The only functionality added is for
@@ -86,6 +152,40 @@ impl<W: Widget> Window<W> {
     pub fn add_callback(&mut self, condition: Callback, f: &'static dyn Fn(&mut W, &mut Manager)) {
         self.fns.push((condition, f));
     }
+
+    /// Add a closure to be called, with a reference to self, after each frame
+    /// is presented. The closure must be passed by reference.
+    ///
+    /// The closure is passed the time of presentation (see
+    /// [`kas::Window::handle_frame`]), allowing external systems (e.g. an
+    /// audio position display or simulation interpolation) to be synchronised
+    /// with what is actually displayed. Frames are only drawn on demand; the
+    /// closure may request a [redraw action](Manager::send_action) to continue
+    /// receiving calls.
+    pub fn add_frame_callback(&mut self, f: &'static dyn Fn(&mut W, &mut Manager, Instant)) {
+        self.frame_fns.push(f);
+    }
+}
+
+impl<W: Widget> WidgetChildren for Window<W> {
+    #[inline]
+    fn len(&self) -> usize {
+        1 + self.layers.len()
+    }
+    #[inline]
+    fn get(&self, index: usize) -> Option<&dyn WidgetConfig> {
+        match index {
+            0 => Some(self.w.as_widget()),
+            i => self.layers.get(i - 1).map(|w| w.as_widget()),
+        }
+    }
+    #[inline]
+    fn get_mut(&mut self, index: usize) -> Option<&mut dyn WidgetConfig> {
+        match index {
+            0 => Some(self.w.as_widget_mut()),
+            i => self.layers.get_mut(i - 1).map(|w| w.as_widget_mut()),
+        }
+    }
 }
 
 impl<W: Widget> WidgetConfig for Window<W> {
@@ -97,19 +197,30 @@ impl<W: Widget> WidgetConfig for Window<W> {
             }
         }
     }
+
+    fn debug_label(&self) -> Option<&str> {
+        Some(&self.title)
+    }
 }
 
 impl<W: Widget> Layout for Window<W> {
     #[inline]
     fn size_rules(&mut self, size_handle: &mut dyn SizeHandle, axis: AxisInfo) -> SizeRules {
         // Note: we do not consider popups, since they are usually temporary
+        for layer in &mut self.layers {
+            let _ = layer.size_rules(size_handle, axis);
+        }
         self.w.size_rules(size_handle, axis)
     }
 
     #[inline]
     fn set_rect(&mut self, rect: Rect, align: AlignHints) {
         self.core.rect = rect;
+        let align = align.combine(self.align);
         self.w.set_rect(rect, align);
+        for layer in &mut self.layers {
+            layer.set_rect(rect, align);
+        }
     }
 
     #[inline]
@@ -122,6 +233,12 @@ impl<W: Widget> Layout for Window<W> {
                 return Some(id);
             }
         }
+        for layer in self.layers.iter().rev() {
+            match layer.find_id(coord) {
+                Some(id) if id != layer.id() => return Some(id),
+                _ => (),
+            }
+        }
         self.w.find_id(coord).or(Some(self.id()))
     }
 
@@ -129,6 +246,12 @@ impl<W: Widget> Layout for Window<W> {
     fn draw(&self, draw_handle: &mut dyn DrawHandle, mgr: &event::ManagerState, disabled: bool) {
         let disabled = disabled || self.is_disabled();
         self.w.draw(draw_handle, mgr, disabled);
+        for (i, layer) in self.layers.iter().enumerate() {
+            let class = ClipRegion::Layer(i.min(u8::MAX as usize) as u8);
+            draw_handle.clip_region(self.core.rect, Coord::ZERO, class, &mut |draw_handle| {
+                layer.draw(draw_handle, mgr, disabled);
+            });
+        }
         for popup in &self.popups {
             let class = ClipRegion::Popup;
             draw_handle.clip_region(self.core.rect, Coord::ZERO, class, &mut |draw_handle| {
@@ -141,8 +264,15 @@ impl<W: Widget> Layout for Window<W> {
 
 impl<W: Widget<Msg = VoidMsg> + 'static> event::SendEvent for Window<W> {
     fn send(&mut self, mgr: &mut Manager, id: WidgetId, event: Event) -> Response<Self::Msg> {
-        if !self.is_disabled() && id <= self.w.id() {
-            return self.w.send(mgr, id, event);
+        if !self.is_disabled() {
+            if id <= self.w.id() {
+                return self.w.send(mgr, id, event);
+            }
+            for layer in &mut self.layers {
+                if id <= layer.id() {
+                    return layer.send(mgr, id, event);
+                }
+            }
         }
         Response::Unhandled(event)
     }
@@ -188,6 +318,60 @@ impl<W: Widget<Msg = VoidMsg> + 'static> kas::Window for Window<W> {
             }
         }
     }
+
+    fn handle_frame(&mut self, mgr: &mut Manager, time: Instant) {
+        for f in &self.frame_fns {
+            f(&mut self.w, mgr, time);
+        }
+    }
+}
+
+/// A description from which windows may be instantiated
+///
+/// This wraps a closure constructing a window. Each instance is constructed
+/// afresh (and, once added, configured with fresh widget identifiers), thus
+/// widgets need not support `Clone`. Templates themselves are cheap to clone
+/// and may be stored within widgets, e.g. to support a "New Window" action in
+/// a multi-document application:
+///
+/// ```
+/// use kas::widget::{Label, Window, WindowTemplate};
+///
+/// let template = WindowTemplate::new(|| Window::new("Document", Label::new("Empty")));
+/// let window = template.instantiate();
+/// assert_eq!(kas::Window::title(&*window), "Document");
+/// // Within an event handler: mgr.add_window(template.instantiate());
+/// ```
+#[derive(Clone)]
+pub struct WindowTemplate {
+    f: Rc<dyn Fn() -> Box<dyn kas::Window>>,
+}
+
+impl Debug for WindowTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WindowTemplate {{ .. }}")
+    }
+}
+
+impl WindowTemplate {
+    /// Construct from a closure
+    pub fn new<W: kas::Window + 'static, F: Fn() -> W + 'static>(f: F) -> Self {
+        WindowTemplate {
+            f: Rc::new(move || Box::new(f())),
+        }
+    }
+
+    /// Construct from a closure returning a boxed window
+    pub fn new_boxed<F: Fn() -> Box<dyn kas::Window> + 'static>(f: F) -> Self {
+        WindowTemplate { f: Rc::new(f) }
+    }
+
+    /// Construct a new window instance
+    ///
+    /// The result may be passed to [`Manager::add_window`].
+    pub fn instantiate(&self) -> Box<dyn kas::Window> {
+        (self.f)()
+    }
 }
 
 // This is like WidgetChildren::find, but returns a translated Rect.